@@ -0,0 +1,131 @@
+//! Indicator benchmarks over realistic daily series.
+//!
+//! `cargo bench --bench indicators` times RSI, SMA, EMA, MACD, Bollinger
+//! bands and stochastics over 250 (a year) to 2500 (ten years) bars, plus the
+//! CPU side of analysing one symbol: the indicator set, trailing returns,
+//! strategy signals and 52-week events, i.e. everything the engine's
+//! per-symbol loop does between the Yahoo fetch and the database write.
+
+use auto_analyser_2::analytics;
+use auto_analyser_2::highs_lows;
+use auto_analyser_2::indicators::TechnicalIndicators;
+use auto_analyser_2::models::{HistoricalPrice, StockAnalysis};
+use auto_analyser_2::signals;
+use auto_analyser_2::units;
+use chrono::{Duration, TimeZone, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const BAR_COUNTS: [usize; 3] = [250, 1000, 2500];
+
+/// Seeded random walk of `len` daily bars ending today, so every run times
+/// the same data.
+fn series(len: usize) -> Vec<HistoricalPrice> {
+    let mut rng = StdRng::seed_from_u64(42);
+    let start = Utc::now().date_naive() - Duration::days(len as i64 - 1);
+    let mut close = 100.0_f64;
+    (0..len)
+        .map(|i| {
+            let open = close;
+            close = (close * (1.0 + rng.gen_range(-0.03..0.03))).max(1.0);
+            let high = open.max(close) * (1.0 + rng.gen_range(0.0..0.01));
+            let low = open.min(close) * (1.0 - rng.gen_range(0.0..0.01));
+            HistoricalPrice {
+                date: Utc.from_utc_datetime(
+                    &(start + Duration::days(i as i64))
+                        .and_hms_opt(20, 0, 0)
+                        .unwrap(),
+                ),
+                open,
+                high,
+                low,
+                close,
+                volume: rng.gen_range(500_000.0..5_000_000.0),
+            }
+        })
+        .collect()
+}
+
+fn bench_indicators(c: &mut Criterion) {
+    let mut group = c.benchmark_group("indicators");
+    for len in BAR_COUNTS {
+        let prices = series(len);
+        group.bench_with_input(BenchmarkId::new("rsi_14", len), &prices, |b, p| {
+            b.iter(|| TechnicalIndicators::calculate_rsi(black_box(p), 14))
+        });
+        group.bench_with_input(BenchmarkId::new("sma_50", len), &prices, |b, p| {
+            b.iter(|| TechnicalIndicators::calculate_sma(black_box(p), 50))
+        });
+        group.bench_with_input(BenchmarkId::new("ema_26", len), &prices, |b, p| {
+            b.iter(|| TechnicalIndicators::calculate_ema(black_box(p), 26))
+        });
+        group.bench_with_input(BenchmarkId::new("macd", len), &prices, |b, p| {
+            b.iter(|| TechnicalIndicators::calculate_macd(black_box(p)))
+        });
+        group.bench_with_input(BenchmarkId::new("bollinger_20", len), &prices, |b, p| {
+            b.iter(|| TechnicalIndicators::calculate_bollinger_bands(black_box(p), 20, 2.0))
+        });
+        group.bench_with_input(BenchmarkId::new("stochastic_14", len), &prices, |b, p| {
+            b.iter(|| TechnicalIndicators::calculate_stochastic(black_box(p), 14, 3))
+        });
+    }
+    group.finish();
+}
+
+/// What `AnalysisEngine` computes for one symbol once its bars are in hand.
+fn analyse(prices: &[HistoricalPrice], template: &StockAnalysis) -> usize {
+    let latest = prices.last().unwrap();
+    let rsi = TechnicalIndicators::calculate_rsi(prices, 14);
+    let stochastic = TechnicalIndicators::calculate_stochastic(prices, 14, 3);
+    let analysis = StockAnalysis {
+        price: latest.close,
+        rsi,
+        sma_20: TechnicalIndicators::calculate_sma(prices, 20),
+        sma_50: TechnicalIndicators::calculate_sma(prices, 50),
+        macd: TechnicalIndicators::calculate_macd(prices),
+        bollinger: TechnicalIndicators::calculate_bollinger_bands(prices, 20, 2.0),
+        volume: units::to_u64(latest.volume),
+        is_oversold: TechnicalIndicators::is_oversold(rsi),
+        is_overbought: TechnicalIndicators::is_overbought(rsi),
+        stoch_oversold: TechnicalIndicators::is_stoch_oversold(stochastic.as_ref()),
+        stoch_overbought: TechnicalIndicators::is_stoch_overbought(stochastic.as_ref()),
+        stochastic,
+        williams_r: TechnicalIndicators::calculate_williams_r(prices, 14),
+        cci: TechnicalIndicators::calculate_cci(prices, 20),
+        mfi: TechnicalIndicators::calculate_mfi(prices, 14),
+        vwap: TechnicalIndicators::calculate_vwap(prices),
+        obv: TechnicalIndicators::calculate_obv(prices, 20),
+        cross_signal: TechnicalIndicators::calculate_ma_cross(prices, 50, 200)
+            .map(|(_, signal)| signal)
+            .unwrap_or_default(),
+        supertrend: TechnicalIndicators::calculate_supertrend(prices, 10, 3.0)
+            .map(|(supertrend, _)| supertrend),
+        performance: analytics::performance_returns(prices),
+        ..template.clone()
+    };
+    signals::detect(&analysis, latest).len()
+        + highs_lows::detect(&analysis.symbol, prices, analysis.analyzed_at).len()
+}
+
+fn bench_symbol_analysis(c: &mut Criterion) {
+    let template: StockAnalysis = serde_json::from_value(serde_json::json!({
+        "symbol": "BENCH",
+        "price": 100.0,
+        "is_oversold": false,
+        "is_overbought": false,
+        "analyzed_at": Utc::now()
+    }))
+    .unwrap();
+    let mut group = c.benchmark_group("symbol_analysis");
+    for len in BAR_COUNTS {
+        let prices = series(len);
+        group.bench_with_input(BenchmarkId::from_parameter(len), &prices, |b, p| {
+            b.iter(|| analyse(black_box(p), &template))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_indicators, bench_symbol_analysis);
+criterion_main!(benches);
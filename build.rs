@@ -0,0 +1,9 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Use the vendored protoc so the build doesn't depend on a system install.
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_build::configure()
+        .build_client(true)
+        .compile_protos(&["proto/analyser.proto"], &["proto"])?;
+    println!("cargo:rerun-if-changed=proto/analyser.proto");
+    Ok(())
+}
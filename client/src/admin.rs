@@ -0,0 +1,214 @@
+//! Service status, cycle progress, cache and `/api/admin` endpoints.
+
+use auto_analyser_2::cache::CacheStats;
+use auto_analyser_2::maintenance::MaintenanceStatus;
+use auto_analyser_2::models::{CachePin, DeadLetter, SymbolAlias, SymbolCycleStatus};
+use auto_analyser_2::repair::{CorruptDocument, RepairReport};
+use auto_analyser_2::transfer::ImportSummary;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::responses::{
+    AiModels, AiStatus, AnalysisRuns, BackupRun, Backups, CollectionSizes, CycleSymbols, DbStats,
+    Health, PinnedStock, Progress, ServiceInfo, WeekendStatus,
+};
+use crate::{enum_param, field, segment, Client, Result};
+
+impl Client {
+    /// `GET /`
+    pub async fn service_info(&self) -> Result<ServiceInfo> {
+        self.get("/", &[]).await
+    }
+
+    /// `GET /health`
+    pub async fn health(&self) -> Result<Health> {
+        self.get("/health", &[]).await
+    }
+
+    /// `GET /api/progress`
+    pub async fn progress(&self) -> Result<Progress> {
+        self.get("/api/progress", &[]).await
+    }
+
+    /// `GET /api/progress/symbols`, optionally only symbols in one state.
+    pub async fn progress_symbols(
+        &self,
+        status: Option<SymbolCycleStatus>,
+    ) -> Result<CycleSymbols> {
+        let status = status.map(|s| enum_param(&s));
+        self.get("/api/progress/symbols", &[("status", status)])
+            .await
+    }
+
+    /// `GET /api/runs`: recorded analysis cycles, most recent first,
+    /// optionally only those started at or after `since`.
+    pub async fn analysis_runs(
+        &self,
+        since: Option<DateTime<Utc>>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<AnalysisRuns> {
+        let query = [
+            ("since", since.map(|t| t.to_rfc3339())),
+            ("page", page.map(|v| v.to_string())),
+            ("page_size", page_size.map(|v| v.to_string())),
+        ];
+        self.get("/api/runs", &query).await
+    }
+
+    /// `GET /api/symbols/aliases`
+    pub async fn symbol_aliases(&self) -> Result<Vec<SymbolAlias>> {
+        self.get_field("/api/symbols/aliases", &[], "aliases").await
+    }
+
+    /// `GET /api/admin/dead-letters`
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        self.get_field("/api/admin/dead-letters", &[], "dead_letters")
+            .await
+    }
+
+    /// `POST /api/admin/dead-letters/:symbol/requeue`. `false` when the
+    /// symbol wasn't dead-lettered.
+    pub async fn requeue_dead_letter(&self, symbol: &str) -> Result<bool> {
+        let path = format!("/api/admin/dead-letters/{}/requeue", segment(symbol));
+        field(self.post(&path, &json!({})).await?, "requeued")
+    }
+
+    /// `POST /api/admin/dead-letters/requeue`. Returns how many were requeued.
+    pub async fn requeue_all_dead_letters(&self) -> Result<u64> {
+        field(
+            self.post("/api/admin/dead-letters/requeue", &json!({}))
+                .await?,
+            "requeued",
+        )
+    }
+
+    /// `GET /api/admin/db/stats`
+    pub async fn db_stats(&self) -> Result<DbStats> {
+        self.get("/api/admin/db/stats", &[]).await
+    }
+
+    /// `GET /api/admin/db/collections`
+    pub async fn collection_sizes(&self) -> Result<CollectionSizes> {
+        self.get("/api/admin/db/collections", &[]).await
+    }
+
+    /// `GET /api/admin/corrupt-documents`: analyses the last full read
+    /// couldn't deserialize as stored.
+    pub async fn corrupt_documents(&self) -> Result<Vec<CorruptDocument>> {
+        self.get_field("/api/admin/corrupt-documents", &[], "documents")
+            .await
+    }
+
+    /// `POST /api/admin/repair`: run the startup repair pass now.
+    pub async fn run_repair(&self) -> Result<RepairReport> {
+        field(self.post("/api/admin/repair", &json!({})).await?, "report")
+    }
+
+    /// `DELETE /api/admin/db/stats`
+    pub async fn reset_db_stats(&self) -> Result<()> {
+        self.delete::<Value>("/api/admin/db/stats").await.map(drop)
+    }
+
+    /// `POST /api/admin/cross-section`. Returns how many symbols were updated.
+    pub async fn run_cross_section(&self) -> Result<usize> {
+        field(
+            self.post("/api/admin/cross-section", &json!({})).await?,
+            "updated",
+        )
+    }
+
+    /// `GET /api/admin/export`: `collections` (`all` or comma-separated,
+    /// server default `stock_analysis`) in the seed format, gzipped unless
+    /// `gzip` is false.
+    pub async fn export(&self, collections: Option<&str>, gzip: bool) -> Result<Vec<u8>> {
+        let format = if gzip { "gzip" } else { "ndjson" };
+        let query = [
+            ("format", Some(format.to_string())),
+            ("collections", collections.map(str::to_string)),
+        ];
+        self.get_bytes("/api/admin/export", &query).await
+    }
+
+    /// `POST /api/admin/import`: load an [`Self::export`] (either format).
+    pub async fn import(&self, export: Vec<u8>) -> Result<ImportSummary> {
+        self.post_text("/api/admin/import", &[], export).await
+    }
+
+    /// `GET /api/admin/weekend`
+    pub async fn weekend_status(&self) -> Result<WeekendStatus> {
+        self.get("/api/admin/weekend", &[]).await
+    }
+
+    /// `POST /api/admin/weekend`. The run continues in the background; poll
+    /// [`Client::weekend_status`] for its report.
+    pub async fn run_weekend(&self) -> Result<bool> {
+        field(
+            self.post("/api/admin/weekend", &json!({})).await?,
+            "started",
+        )
+    }
+
+    /// `GET /api/admin/backups`
+    pub async fn backups(&self) -> Result<Backups> {
+        self.get("/api/admin/backups", &[]).await
+    }
+
+    /// `POST /api/admin/backups`
+    pub async fn run_backup(&self) -> Result<BackupRun> {
+        self.post("/api/admin/backups", &json!({})).await
+    }
+
+    /// `GET /api/admin/maintenance`
+    pub async fn maintenance(&self) -> Result<MaintenanceStatus> {
+        self.get_field("/api/admin/maintenance", &[], "maintenance")
+            .await
+    }
+
+    /// `PUT /api/admin/maintenance`: switch read-only mode on or off.
+    /// While on, every other mutating call fails with a 503.
+    pub async fn set_maintenance(
+        &self,
+        read_only: bool,
+        reason: Option<&str>,
+    ) -> Result<MaintenanceStatus> {
+        let body = json!({ "read_only": read_only, "reason": reason });
+        field(
+            self.put("/api/admin/maintenance", &body).await?,
+            "maintenance",
+        )
+    }
+
+    /// `GET /api/cache/stats`
+    pub async fn cache_stats(&self) -> Result<CacheStats> {
+        self.get_field("/api/cache/stats", &[], "stats").await
+    }
+
+    /// `GET /api/cache/pins`
+    pub async fn cache_pins(&self) -> Result<Vec<CachePin>> {
+        self.get_field("/api/cache/pins", &[], "pins").await
+    }
+
+    /// `PUT /api/cache/pins/:symbol`. `ttl_secs: None` keeps the symbol
+    /// cached until it is unpinned.
+    pub async fn pin_stock(&self, symbol: &str, ttl_secs: Option<u64>) -> Result<PinnedStock> {
+        let path = format!("/api/cache/pins/{}", segment(symbol));
+        self.put(&path, &json!({ "ttl_secs": ttl_secs })).await
+    }
+
+    /// `DELETE /api/cache/pins/:symbol`. `false` when it wasn't pinned.
+    pub async fn unpin_stock(&self, symbol: &str) -> Result<bool> {
+        let path = format!("/api/cache/pins/{}", segment(symbol));
+        field(self.delete(&path).await?, "deleted")
+    }
+
+    /// `GET /api/ai/status`
+    pub async fn ai_status(&self) -> Result<AiStatus> {
+        self.get("/api/ai/status", &[]).await
+    }
+
+    /// `GET /api/ai/models`
+    pub async fn ai_models(&self) -> Result<AiModels> {
+        self.get("/api/ai/models", &[]).await
+    }
+}
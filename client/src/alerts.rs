@@ -0,0 +1,249 @@
+//! Watchlists, alert rules, notification channels and the notification
+//! inbox.
+
+use auto_analyser_2::notifications::models::{
+    AddSymbolInput, AlertAuditEvent, AlertRule, CreateAlertRuleInput, CreateChannelInput,
+    CreateWatchlistInput, NotificationChannel, SnoozeRuleInput, UpdateAlertRuleInput,
+    UpdateChannelInput, UpdateWatchlistInput, Watchlist,
+};
+use auto_analyser_2::notifications::recap::WatchlistRecap;
+use bson::oid::ObjectId;
+use serde_json::{json, Value};
+
+use crate::responses::{HistoryPage, RecapDelivery, RuleTest};
+use crate::{field, segment, Client, Result};
+
+impl Client {
+    /// `GET /api/watchlists`
+    pub async fn watchlists(&self) -> Result<Vec<Watchlist>> {
+        self.get_field("/api/watchlists", &[], "watchlists").await
+    }
+
+    /// `POST /api/watchlists`
+    pub async fn create_watchlist(&self, input: &CreateWatchlistInput) -> Result<Watchlist> {
+        field(self.post("/api/watchlists", input).await?, "watchlist")
+    }
+
+    /// `GET /api/watchlists/:id`
+    pub async fn watchlist(&self, id: ObjectId) -> Result<Watchlist> {
+        let path = format!("/api/watchlists/{}", id.to_hex());
+        self.get_field(&path, &[], "watchlist").await
+    }
+
+    /// `PATCH /api/watchlists/:id`
+    pub async fn update_watchlist(
+        &self,
+        id: ObjectId,
+        input: &UpdateWatchlistInput,
+    ) -> Result<Watchlist> {
+        let path = format!("/api/watchlists/{}", id.to_hex());
+        field(self.patch(&path, input).await?, "watchlist")
+    }
+
+    /// `DELETE /api/watchlists/:id`
+    pub async fn delete_watchlist(&self, id: ObjectId) -> Result<()> {
+        let path = format!("/api/watchlists/{}", id.to_hex());
+        self.delete::<Value>(&path).await.map(drop)
+    }
+
+    /// `POST /api/watchlists/:id/symbols`
+    pub async fn add_watchlist_symbol(&self, id: ObjectId, symbol: &str) -> Result<Watchlist> {
+        let path = format!("/api/watchlists/{}/symbols", id.to_hex());
+        let input = AddSymbolInput {
+            symbol: symbol.to_string(),
+        };
+        field(self.post(&path, &input).await?, "watchlist")
+    }
+
+    /// `DELETE /api/watchlists/:id/symbols/:symbol`
+    pub async fn remove_watchlist_symbol(&self, id: ObjectId, symbol: &str) -> Result<Watchlist> {
+        let path = format!(
+            "/api/watchlists/{}/symbols/{}",
+            id.to_hex(),
+            segment(symbol)
+        );
+        field(self.delete(&path).await?, "watchlist")
+    }
+
+    /// `GET /api/watchlists/:id/recap`: today's end-of-day recap, unsent.
+    pub async fn watchlist_recap(&self, id: ObjectId) -> Result<WatchlistRecap> {
+        let path = format!("/api/watchlists/{}/recap", id.to_hex());
+        self.get_field(&path, &[], "recap").await
+    }
+
+    /// `POST /api/watchlists/:id/recap`: send today's recap to every
+    /// `daily_recap` channel now.
+    pub async fn send_watchlist_recap(&self, id: ObjectId) -> Result<RecapDelivery> {
+        let path = format!("/api/watchlists/{}/recap", id.to_hex());
+        self.post(&path, &json!({})).await
+    }
+
+    /// `GET /api/alerts/rules`
+    pub async fn alert_rules(&self) -> Result<Vec<AlertRule>> {
+        self.get_field("/api/alerts/rules", &[], "rules").await
+    }
+
+    /// `POST /api/alerts/rules`
+    pub async fn create_alert_rule(&self, input: &CreateAlertRuleInput) -> Result<AlertRule> {
+        field(self.post("/api/alerts/rules", input).await?, "rule")
+    }
+
+    /// `GET /api/alerts/rules/:id`
+    pub async fn alert_rule(&self, id: ObjectId) -> Result<AlertRule> {
+        let path = format!("/api/alerts/rules/{}", id.to_hex());
+        self.get_field(&path, &[], "rule").await
+    }
+
+    /// `PUT /api/alerts/rules/:id`. Only the fields set in `input` change.
+    pub async fn update_alert_rule(
+        &self,
+        id: ObjectId,
+        input: &UpdateAlertRuleInput,
+    ) -> Result<AlertRule> {
+        let path = format!("/api/alerts/rules/{}", id.to_hex());
+        field(self.put(&path, input).await?, "rule")
+    }
+
+    /// `DELETE /api/alerts/rules/:id`
+    pub async fn delete_alert_rule(&self, id: ObjectId) -> Result<()> {
+        let path = format!("/api/alerts/rules/{}", id.to_hex());
+        self.delete::<Value>(&path).await.map(drop)
+    }
+
+    /// `POST /api/alerts/rules/:id/toggle`: flip `enabled`.
+    pub async fn toggle_alert_rule(&self, id: ObjectId) -> Result<AlertRule> {
+        self.rule_action(id, "toggle").await
+    }
+
+    /// `POST /api/alerts/rules/:id/test`: send a one-off notification for
+    /// `symbol`, or the first symbol in the rule's scope.
+    pub async fn test_alert_rule(&self, id: ObjectId, symbol: Option<&str>) -> Result<RuleTest> {
+        let path = format!("/api/alerts/rules/{}/test", id.to_hex());
+        self.post(&path, &json!({ "symbol": symbol })).await
+    }
+
+    /// `POST /api/alerts/rules/:id/snooze`
+    pub async fn snooze_alert_rule(
+        &self,
+        id: ObjectId,
+        input: &SnoozeRuleInput,
+    ) -> Result<AlertRule> {
+        let path = format!("/api/alerts/rules/{}/snooze", id.to_hex());
+        field(self.post(&path, input).await?, "rule")
+    }
+
+    /// `DELETE /api/alerts/rules/:id/snooze`
+    pub async fn unsnooze_alert_rule(&self, id: ObjectId) -> Result<AlertRule> {
+        let path = format!("/api/alerts/rules/{}/snooze", id.to_hex());
+        field(self.delete(&path).await?, "rule")
+    }
+
+    /// `PUT /api/alerts/rules/:id/mute/:symbol`
+    pub async fn mute_alert_symbol(&self, id: ObjectId, symbol: &str) -> Result<AlertRule> {
+        let path = format!("/api/alerts/rules/{}/mute/{}", id.to_hex(), segment(symbol));
+        field(self.put(&path, &json!({})).await?, "rule")
+    }
+
+    /// `DELETE /api/alerts/rules/:id/mute/:symbol`
+    pub async fn unmute_alert_symbol(&self, id: ObjectId, symbol: &str) -> Result<AlertRule> {
+        let path = format!("/api/alerts/rules/{}/mute/{}", id.to_hex(), segment(symbol));
+        field(self.delete(&path).await?, "rule")
+    }
+
+    /// `POST /api/alerts/rules/:id/reset`: zero `trigger_count` and
+    /// re-enable an exhausted rule.
+    pub async fn reset_alert_rule(&self, id: ObjectId) -> Result<AlertRule> {
+        self.rule_action(id, "reset").await
+    }
+
+    /// `GET /api/alerts/rules/:id/audit`, newest first (default 100).
+    pub async fn alert_rule_audit(
+        &self,
+        id: ObjectId,
+        limit: Option<u32>,
+    ) -> Result<Vec<AlertAuditEvent>> {
+        let path = format!("/api/alerts/rules/{}/audit", id.to_hex());
+        self.get_field(&path, &[("limit", limit.map(|v| v.to_string()))], "events")
+            .await
+    }
+
+    async fn rule_action(&self, id: ObjectId, action: &str) -> Result<AlertRule> {
+        let path = format!("/api/alerts/rules/{}/{}", id.to_hex(), action);
+        field(self.post(&path, &json!({})).await?, "rule")
+    }
+
+    /// `GET /api/alerts/channels`. Webhook URLs come back masked.
+    pub async fn channels(&self) -> Result<Vec<NotificationChannel>> {
+        self.get_field("/api/alerts/channels", &[], "channels")
+            .await
+    }
+
+    /// `POST /api/alerts/channels`
+    pub async fn create_channel(&self, input: &CreateChannelInput) -> Result<NotificationChannel> {
+        field(self.post("/api/alerts/channels", input).await?, "channel")
+    }
+
+    /// `GET /api/alerts/channels/:id`
+    pub async fn channel(&self, id: ObjectId) -> Result<NotificationChannel> {
+        let path = format!("/api/alerts/channels/{}", id.to_hex());
+        self.get_field(&path, &[], "channel").await
+    }
+
+    /// `PUT /api/alerts/channels/:id`
+    pub async fn update_channel(
+        &self,
+        id: ObjectId,
+        input: &UpdateChannelInput,
+    ) -> Result<NotificationChannel> {
+        let path = format!("/api/alerts/channels/{}", id.to_hex());
+        field(self.put(&path, input).await?, "channel")
+    }
+
+    /// `DELETE /api/alerts/channels/:id`
+    pub async fn delete_channel(&self, id: ObjectId) -> Result<()> {
+        let path = format!("/api/alerts/channels/{}", id.to_hex());
+        self.delete::<Value>(&path).await.map(drop)
+    }
+
+    /// `POST /api/alerts/channels/:id/test`: send a test message.
+    pub async fn test_channel(&self, id: ObjectId) -> Result<()> {
+        let path = format!("/api/alerts/channels/{}/test", id.to_hex());
+        self.post::<Value>(&path, &json!({})).await.map(drop)
+    }
+
+    /// `GET /api/alerts/history`, newest first.
+    pub async fn notification_history(
+        &self,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        rule_id: Option<ObjectId>,
+        symbol: Option<&str>,
+    ) -> Result<HistoryPage> {
+        let query = [
+            ("page", page.map(|v| v.to_string())),
+            ("page_size", page_size.map(|v| v.to_string())),
+            ("rule_id", rule_id.map(|id| id.to_hex())),
+            ("symbol", symbol.map(str::to_string)),
+        ];
+        self.get("/api/alerts/history", &query).await
+    }
+
+    /// `GET /api/alerts/history/unread-count`
+    pub async fn unread_count(&self) -> Result<u64> {
+        self.get_field("/api/alerts/history/unread-count", &[], "unread")
+            .await
+    }
+
+    /// `PATCH /api/alerts/history/:id/read`
+    pub async fn mark_history_read(&self, id: ObjectId, read: bool) -> Result<()> {
+        let path = format!("/api/alerts/history/{}/read", id.to_hex());
+        self.patch::<Value>(&path, &json!({ "read": read }))
+            .await
+            .map(drop)
+    }
+
+    /// `GET /api/alerts/status`: whether the alert engine is running.
+    pub async fn alerts_enabled(&self) -> Result<bool> {
+        self.get_field("/api/alerts/status", &[], "enabled").await
+    }
+}
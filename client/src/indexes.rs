@@ -0,0 +1,121 @@
+//! Index, theme and screen endpoints.
+
+use auto_analyser_2::indexes::{IndexContributors, IndexInfo};
+use auto_analyser_2::screens::ScreenResult;
+use auto_analyser_2::themes::{Theme, ThemeInput, ThemeMembershipChange};
+use chrono::NaiveDate;
+
+use crate::responses::{Heatmap, IndexDetail, PerformanceHistory, ScreenSummary, ThemeUpdate};
+use crate::{field, segment, Client, Result};
+
+impl Client {
+    /// `GET /api/indexes`
+    pub async fn indexes(&self) -> Result<Vec<IndexInfo>> {
+        self.get_field("/api/indexes", &[], "indexes").await
+    }
+
+    /// `GET /api/indexes/:index_id`
+    pub async fn index(&self, index_id: &str) -> Result<IndexDetail> {
+        let path = format!("/api/indexes/{}", segment(index_id));
+        self.get_field(&path, &[], "index").await
+    }
+
+    /// `GET /api/indexes/:index_id/heatmap`. `period` is one of `1d` (the
+    /// default), `1w`, `1m`, `6m`, `1y`.
+    pub async fn index_heatmap(&self, index_id: &str, period: Option<&str>) -> Result<Heatmap> {
+        let path = format!("/api/indexes/{}/heatmap", segment(index_id));
+        self.get(&path, &[("period", period.map(str::to_string))])
+            .await
+    }
+
+    /// `GET /api/indexes/:index_id/performance` over the last `days`
+    /// (default 30).
+    pub async fn index_performance(
+        &self,
+        index_id: &str,
+        days: Option<i64>,
+    ) -> Result<PerformanceHistory> {
+        let path = format!("/api/indexes/{}/performance", segment(index_id));
+        self.get(&path, &[("days", days.map(|v| v.to_string()))])
+            .await
+    }
+
+    /// `GET /api/indexes/:index_id/contributors` for `date`, or the latest
+    /// recorded day.
+    pub async fn index_contributors(
+        &self,
+        index_id: &str,
+        date: Option<NaiveDate>,
+        limit: Option<usize>,
+    ) -> Result<IndexContributors> {
+        let path = format!("/api/indexes/{}/contributors", segment(index_id));
+        let query = [
+            ("date", date.map(|d| d.format("%Y-%m-%d").to_string())),
+            ("limit", limit.map(|v| v.to_string())),
+        ];
+        self.get_field(&path, &query, "contributors").await
+    }
+
+    /// `GET /api/screens`
+    pub async fn screens(&self) -> Result<Vec<ScreenSummary>> {
+        self.get_field("/api/screens", &[], "screens").await
+    }
+
+    /// `GET /api/screens/:name`
+    pub async fn screen(&self, name: &str, limit: Option<usize>) -> Result<ScreenResult> {
+        let path = format!("/api/screens/{}", segment(name));
+        self.get_field(&path, &[("limit", limit.map(|v| v.to_string()))], "screen")
+            .await
+    }
+
+    /// `GET /api/themes`
+    pub async fn themes(&self) -> Result<Vec<Theme>> {
+        self.get_field("/api/themes", &[], "themes").await
+    }
+
+    /// `GET /api/themes/:theme_id`
+    pub async fn theme(&self, theme_id: &str) -> Result<Theme> {
+        let path = format!("/api/themes/{}", segment(theme_id));
+        self.get_field(&path, &[], "theme").await
+    }
+
+    /// `GET /api/themes/:theme_id/heatmap`
+    pub async fn theme_heatmap(&self, theme_id: &str, period: Option<&str>) -> Result<Heatmap> {
+        let path = format!("/api/themes/{}/heatmap", segment(theme_id));
+        self.get(&path, &[("period", period.map(str::to_string))])
+            .await
+    }
+
+    /// `GET /api/themes/:theme_id/performance`
+    pub async fn theme_performance(
+        &self,
+        theme_id: &str,
+        days: Option<i64>,
+    ) -> Result<PerformanceHistory> {
+        let path = format!("/api/themes/{}/performance", segment(theme_id));
+        self.get(&path, &[("days", days.map(|v| v.to_string()))])
+            .await
+    }
+
+    /// `PUT /api/admin/themes/:theme_id`: create or replace a theme.
+    pub async fn save_theme(&self, theme_id: &str, input: &ThemeInput) -> Result<Theme> {
+        let path = format!("/api/admin/themes/{}", segment(theme_id));
+        field(self.put(&path, input).await?, "theme")
+    }
+
+    /// `POST /api/admin/themes/:theme_id/symbols`
+    pub async fn update_theme_symbols(
+        &self,
+        theme_id: &str,
+        change: &ThemeMembershipChange,
+    ) -> Result<ThemeUpdate> {
+        let path = format!("/api/admin/themes/{}/symbols", segment(theme_id));
+        self.post(&path, change).await
+    }
+
+    /// `DELETE /api/admin/themes/:theme_id`. `false` when it didn't exist.
+    pub async fn delete_theme(&self, theme_id: &str) -> Result<bool> {
+        let path = format!("/api/admin/themes/{}", segment(theme_id));
+        field(self.delete(&path).await?, "deleted")
+    }
+}
@@ -0,0 +1,391 @@
+//! Typed client for the Auto Stock Analyser HTTP API.
+//!
+//! Every REST endpoint the server mounts has a method on [`Client`]. Bodies
+//! and payloads use the server's own types (re-exported as [`types`]), so a
+//! field added on the server is picked up here on the next build. Responses
+//! whose payload is a single key (`{ "success": true, "watchlist": {...} }`)
+//! are unwrapped to that value; the others deserialize into the structs in
+//! [`responses`].
+//!
+//! Both error shapes the server uses, `{ "success": false, "error": ... }`
+//! with a 200 and a 4xx/5xx status, surface as [`Error::Api`].
+//!
+//! ```no_run
+//! # async fn demo() -> auto_analyser_client::Result<()> {
+//! let client = auto_analyser_client::Client::new("http://localhost:3333");
+//! let summary = client.market_summary(Some(1e9), None).await?;
+//! println!("{} stocks analyzed", summary.total_stocks);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The WebSocket (`/ws`) and the AI analysis SSE stream are not wrapped;
+//! [`Client::url`] builds their addresses.
+
+mod admin;
+mod alerts;
+mod indexes;
+mod market;
+mod positions;
+pub mod responses;
+
+pub use auto_analyser_2 as types;
+
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The server answered with `success: false` or a non-2xx status.
+    #[error("API error ({status}): {message}")]
+    Api { status: u16, message: String },
+    #[error("unexpected response body: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    /// `base_url` is the server root, e.g. `http://localhost:3333`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Use a preconfigured `reqwest::Client` (timeouts, proxies, headers).
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { http, base_url }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Absolute URL of `path` on this server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http.request(method, self.url(path))
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, Option<String>)],
+    ) -> Result<T> {
+        self.send(self.request(Method::GET, path).query(&present(query)))
+            .await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: &impl Serialize) -> Result<T> {
+        self.send(self.request(Method::POST, path).json(body)).await
+    }
+
+    async fn put<T: DeserializeOwned>(&self, path: &str, body: &impl Serialize) -> Result<T> {
+        self.send(self.request(Method::PUT, path).json(body)).await
+    }
+
+    async fn patch<T: DeserializeOwned>(&self, path: &str, body: &impl Serialize) -> Result<T> {
+        self.send(self.request(Method::PATCH, path).json(body))
+            .await
+    }
+
+    /// `POST` with a raw body, for file uploads.
+    async fn post_text<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, Option<String>)],
+        body: impl Into<reqwest::Body>,
+    ) -> Result<T> {
+        let request = self.request(Method::POST, path).query(&present(query));
+        self.send(request.body(body)).await
+    }
+
+    async fn delete<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.send(self.request(Method::DELETE, path)).await
+    }
+
+    /// `GET` returning one key of the response object.
+    async fn get_field<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, Option<String>)],
+        key: &str,
+    ) -> Result<T> {
+        field(self.get(path, query).await?, key)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = request.send().await?;
+        let status = response.status();
+        let bytes = response.bytes().await?;
+        let body = match serde_json::from_slice::<Value>(&bytes) {
+            Ok(body) => body,
+            Err(_) if !status.is_success() => {
+                return Err(api_error(
+                    status,
+                    String::from_utf8_lossy(&bytes).into_owned(),
+                ))
+            }
+            Err(e) => return Err(Error::Decode(e)),
+        };
+        check(status, body).and_then(|body| Ok(serde_json::from_value(body)?))
+    }
+
+    /// Raw body of a non-JSON endpoint such as a CSV export.
+    async fn get_text(&self, path: &str, query: &[(&str, Option<String>)]) -> Result<String> {
+        let bytes = self.get_bytes(path, query).await?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Raw body of a binary endpoint such as a gzipped export.
+    async fn get_bytes(&self, path: &str, query: &[(&str, Option<String>)]) -> Result<Vec<u8>> {
+        let response = self
+            .request(Method::GET, path)
+            .query(&present(query))
+            .send()
+            .await?;
+        let status = response.status();
+        let bytes = response.bytes().await?.to_vec();
+        if status.is_success() {
+            return Ok(bytes);
+        }
+        let message = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|body| body.get("error")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(&bytes).into_owned());
+        Err(api_error(status, message))
+    }
+}
+
+/// Turn an error envelope into [`Error::Api`], pass anything else through.
+fn check(status: StatusCode, body: Value) -> Result<Value> {
+    if status.is_success() && body.get("success") != Some(&Value::Bool(false)) {
+        return Ok(body);
+    }
+    let message = match body.get("error") {
+        Some(Value::String(message)) => message.clone(),
+        _ => body.to_string(),
+    };
+    Err(api_error(status, message))
+}
+
+fn api_error(status: StatusCode, message: String) -> Error {
+    Error::Api {
+        status: status.as_u16(),
+        message,
+    }
+}
+
+fn field<T: DeserializeOwned>(mut body: Value, key: &str) -> Result<T> {
+    let value = body.get_mut(key).map(Value::take).unwrap_or(Value::Null);
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Query pairs with the unset ones dropped.
+fn present<'a>(query: &'a [(&'a str, Option<String>)]) -> Vec<(&'a str, &'a str)> {
+    query
+        .iter()
+        .filter_map(|(key, value)| Some((*key, value.as_deref()?)))
+        .collect()
+}
+
+/// A unit enum's serde name (e.g. `dead_lettered`) as a query value.
+fn enum_param(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Percent-encode one path segment (symbols like `BRK/B`, theme ids).
+fn segment(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one canned response and hand back the request line it got.
+    async fn serve_once(status: &str, body: &str) -> (Client, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = Client::new(format!("http://{}/", listener.local_addr().unwrap()));
+        let response = format!(
+            "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 8192];
+            let n = socket.read(&mut buf).await.unwrap();
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+            request.lines().next().unwrap_or_default().to_string()
+        });
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn test_unwraps_payload_and_encodes_request() {
+        let (client, server) = serve_once(
+            "200 OK",
+            r#"{"success":true,"symbol":"BRK/B","requeued":true}"#,
+        )
+        .await;
+        assert!(client.requeue_dead_letter("BRK/B").await.unwrap());
+        assert_eq!(
+            server.await.unwrap(),
+            "POST /api/admin/dead-letters/BRK%2FB/requeue HTTP/1.1"
+        );
+
+        let (client, server) = serve_once(
+            "200 OK",
+            r#"{"success":true,"since":"2025-06-10","total_events":0,"breadth":[],"events":[],
+                "pagination":{"page":1,"page_size":200,"total":0,"total_pages":0,"next_cursor":null}}"#,
+        )
+        .await;
+        let events = client
+            .week52_events(Some(types::highs_lows::Week52Kind::High), Some(7), None)
+            .await
+            .unwrap();
+        assert_eq!(events.since, "2025-06-10");
+        assert_eq!(
+            server.await.unwrap(),
+            "GET /api/events/52w?kind=high&days=7 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_envelopes_become_api_errors() {
+        // Market-data routes report failures with a 200.
+        let (client, _server) = serve_once(
+            "200 OK",
+            r#"{"success":false,"error":"Theme 'x' not found"}"#,
+        )
+        .await;
+        match client.theme("x").await {
+            Err(Error::Api { status, message }) => {
+                assert_eq!(status, 200);
+                assert_eq!(message, "Theme 'x' not found");
+            }
+            other => panic!("expected an API error, got {:?}", other),
+        }
+
+        // Notification routes use the status code.
+        let (client, _server) =
+            serve_once("404 Not Found", r#"{"success":false,"error":"not found"}"#).await;
+        match client
+            .delete_watchlist(bson::oid::ObjectId::parse_str("0123456789abcdef01234567").unwrap())
+            .await
+        {
+            Err(Error::Api { status, message }) => {
+                assert_eq!(status, 404);
+                assert_eq!(message, "not found");
+            }
+            other => panic!("expected an API error, got {:?}", other),
+        }
+    }
+
+    /// Every route the server mounts is described in `openapi.json`, so the
+    /// TypeScript client generated from it covers the whole API too.
+    #[test]
+    fn test_openapi_spec_covers_every_route() {
+        let spec: Value = serde_json::from_str(include_str!("../../openapi.json")).unwrap();
+        let paths = spec["paths"].as_object().unwrap();
+        let mut missing = BTreeSet::new();
+        for source in [
+            include_str!("../../src/api/mod.rs"),
+            include_str!("../../src/api/admin.rs"),
+            include_str!("../../src/api/ai.rs"),
+            include_str!("../../src/api/market.rs"),
+            include_str!("../../src/api/stocks.rs"),
+            include_str!("../../src/api/ws.rs"),
+            include_str!("../../src/notifications/api.rs"),
+        ] {
+            for (path, method) in routes(source) {
+                let openapi_path = path
+                    .split('/')
+                    .map(|part| match part.strip_prefix(':') {
+                        Some(param) => format!("{{{}}}", param),
+                        None => part.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if path == "/ws" {
+                    continue;
+                }
+                if paths
+                    .get(&openapi_path)
+                    .and_then(|ops| ops.get(method.as_str()))
+                    .is_none()
+                {
+                    missing.insert(format!("{} {}", method.to_uppercase(), path));
+                }
+            }
+        }
+        assert!(
+            missing.is_empty(),
+            "missing from openapi.json: {:?}",
+            missing
+        );
+    }
+
+    /// `(path, method)` of each `.route("path", get(..).post(..))` call.
+    fn routes(source: &str) -> Vec<(String, String)> {
+        let mut found = Vec::new();
+        for call in source.split(".route(").skip(1) {
+            let Some(path) = call.split('"').nth(1) else {
+                continue;
+            };
+            // The last route of a chain runs on to the end of the file.
+            let call = call.split(';').next().unwrap_or(call);
+            let call = call.split("\n}").next().unwrap_or(call);
+            for method in ["get", "post", "put", "patch", "delete"] {
+                let listed = call
+                    .match_indices(&format!("{}(", method))
+                    .any(|(i, _)| !call[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_'));
+                if listed {
+                    found.push((path.to_string(), method.to_string()));
+                }
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_check_and_segment() {
+        assert!(check(StatusCode::OK, serde_json::json!({"success": true})).is_ok());
+        assert!(check(StatusCode::OK, serde_json::json!({"status": "running"})).is_ok());
+        assert!(check(StatusCode::OK, serde_json::json!({"success": false})).is_err());
+        assert_eq!(segment("BRK.B"), "BRK.B");
+        assert_eq!(segment("a b/c"), "a%20b%2Fc");
+    }
+}
@@ -0,0 +1,312 @@
+//! Stocks, symbol notes and tags, market summary, quotes, news, sectors,
+//! signals (built-in and ingested), earnings and the analytics endpoints.
+
+use auto_analyser_2::analytics::WhatIfResult;
+use auto_analyser_2::highs_lows::Week52Kind;
+use auto_analyser_2::ingest::SignalInput;
+use auto_analyser_2::models::{
+    AIAnalysisResponse, AnalysisHistoryPoint, CompanyProfile, EarningsData, HistoricalPrice,
+    InsiderTrade, MarketSummary, Seasonality, SectorPerformance, StockAnalysis, StockFilter,
+    SymbolMatch,
+};
+use auto_analyser_2::notes::{NoteInput, SymbolNote, TagCount};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::responses::{
+    AiAnalysis, CorrelationMatrix, EarningsCalendar, ExternalSignals, IngestedSignal, NewsPage,
+    Quotes, SectorEtfs, SignalPerformance, StockDetail, StockPage, TotalReturnHistory,
+    Week52Events,
+};
+use crate::{enum_param, field, segment, Client, Result};
+
+impl Client {
+    /// `GET /api/stocks`: the 50 largest analyzed stocks by market cap.
+    pub async fn stocks(&self) -> Result<Vec<StockAnalysis>> {
+        self.get_field("/api/stocks", &[], "stocks").await
+    }
+
+    /// `GET /api/stocks?as_of=`: the same view as [`Self::stocks`] from the
+    /// analysis history at `as_of`.
+    pub async fn stocks_as_of(&self, as_of: DateTime<Utc>) -> Result<Vec<StockAnalysis>> {
+        let query = [("as_of", Some(as_of.to_rfc3339()))];
+        self.get_field("/api/stocks", &query, "stocks").await
+    }
+
+    /// `POST /api/stocks/filter`
+    pub async fn filter_stocks(&self, filter: &StockFilter) -> Result<StockPage> {
+        self.post("/api/stocks/filter", filter).await
+    }
+
+    /// `GET /api/stocks/:symbol`
+    pub async fn stock(&self, symbol: &str) -> Result<StockDetail> {
+        self.get(&format!("/api/stocks/{}", segment(symbol)), &[])
+            .await
+    }
+
+    /// `GET /api/stocks/:symbol/history`: 90 days of daily bars.
+    pub async fn stock_history(&self, symbol: &str) -> Result<Vec<HistoricalPrice>> {
+        let path = format!("/api/stocks/{}/history", segment(symbol));
+        self.get_field(&path, &[], "history").await
+    }
+
+    /// `GET /api/stocks/:symbol/history?total_return=true`
+    pub async fn stock_history_total_return(&self, symbol: &str) -> Result<TotalReturnHistory> {
+        let path = format!("/api/stocks/{}/history", segment(symbol));
+        self.get(&path, &[("total_return", Some("true".to_string()))])
+            .await
+    }
+
+    /// `GET /api/stocks/:symbol/profile`
+    pub async fn stock_profile(&self, symbol: &str) -> Result<CompanyProfile> {
+        let path = format!("/api/stocks/{}/profile", segment(symbol));
+        self.get_field(&path, &[], "profile").await
+    }
+
+    /// `GET /api/stocks/:symbol/ai-analysis`. The streaming variant lives at
+    /// `/api/stocks/:symbol/ai-analysis/stream` (server-sent events).
+    pub async fn ai_analysis(&self, symbol: &str) -> Result<AiAnalysis> {
+        let path = format!("/api/stocks/{}/ai-analysis", segment(symbol));
+        self.get(&path, &[]).await
+    }
+
+    /// `GET /api/stocks/:symbol/ai-summary`: `None` until a weekend run
+    /// has summarized the symbol.
+    pub async fn ai_summary(&self, symbol: &str) -> Result<Option<AIAnalysisResponse>> {
+        let path = format!("/api/stocks/{}/ai-summary", segment(symbol));
+        self.get_field(&path, &[], "summary").await
+    }
+
+    /// `GET /api/stocks/:symbol/insiders`
+    pub async fn insider_trades(&self, symbol: &str) -> Result<Vec<InsiderTrade>> {
+        let path = format!("/api/stocks/{}/insiders", segment(symbol));
+        self.get_field(&path, &[], "trades").await
+    }
+
+    /// `GET /api/stocks/:symbol/notes`: `None` when the symbol has no notes
+    /// or tags.
+    pub async fn stock_notes(&self, symbol: &str) -> Result<Option<SymbolNote>> {
+        let path = format!("/api/stocks/{}/notes", segment(symbol));
+        self.get_field(&path, &[], "note").await
+    }
+
+    /// `PUT /api/stocks/:symbol/notes`: replace the notes and tags; empty
+    /// input deletes them.
+    pub async fn save_stock_notes(
+        &self,
+        symbol: &str,
+        input: &NoteInput,
+    ) -> Result<Option<SymbolNote>> {
+        let path = format!("/api/stocks/{}/notes", segment(symbol));
+        field(self.put(&path, input).await?, "note")
+    }
+
+    /// `GET /api/tags`
+    pub async fn tags(&self) -> Result<Vec<TagCount>> {
+        self.get_field("/api/tags", &[], "tags").await
+    }
+
+    /// `GET /api/stocks/:symbol/earnings`
+    pub async fn stock_earnings(&self, symbol: &str) -> Result<EarningsData> {
+        let path = format!("/api/stocks/{}/earnings", segment(symbol));
+        self.get_field(&path, &[], "earnings").await
+    }
+
+    /// `GET /api/stocks/:symbol/seasonality`: `None` until a weekend run
+    /// has backfilled the symbol.
+    pub async fn stock_seasonality(&self, symbol: &str) -> Result<Option<Seasonality>> {
+        let path = format!("/api/stocks/{}/seasonality", segment(symbol));
+        self.get_field(&path, &[], "seasonality").await
+    }
+
+    /// `GET /api/stocks/:symbol/analysis-history`: saved analyses over the
+    /// last `days` (server default 30), oldest first.
+    pub async fn analysis_history(
+        &self,
+        symbol: &str,
+        days: Option<i64>,
+    ) -> Result<Vec<AnalysisHistoryPoint>> {
+        let path = format!("/api/stocks/{}/analysis-history", segment(symbol));
+        self.get_field(&path, &[("days", days.map(|v| v.to_string()))], "history")
+            .await
+    }
+
+    /// `GET /api/search`: listed symbols whose ticker or company name
+    /// matches `q`, best first; `limit` is 1 to 50 (server default 10).
+    pub async fn search(&self, q: &str, limit: Option<usize>) -> Result<Vec<SymbolMatch>> {
+        let query = [
+            ("q", Some(q.to_string())),
+            ("limit", limit.map(|v| v.to_string())),
+        ];
+        self.get_field("/api/search", &query, "results").await
+    }
+
+    /// `GET /api/market-summary`
+    pub async fn market_summary(
+        &self,
+        min_market_cap: Option<f64>,
+        max_price_change_percent: Option<f64>,
+    ) -> Result<MarketSummary> {
+        let query = [
+            ("min_market_cap", min_market_cap.map(|v| v.to_string())),
+            (
+                "max_price_change_percent",
+                max_price_change_percent.map(|v| v.to_string()),
+            ),
+        ];
+        self.get_field("/api/market-summary", &query, "summary")
+            .await
+    }
+
+    /// `GET /api/market-summary?as_of=`: the summary of the analysis
+    /// history at `as_of`.
+    pub async fn market_summary_as_of(
+        &self,
+        min_market_cap: Option<f64>,
+        max_price_change_percent: Option<f64>,
+        as_of: DateTime<Utc>,
+    ) -> Result<MarketSummary> {
+        let query = [
+            ("min_market_cap", min_market_cap.map(|v| v.to_string())),
+            (
+                "max_price_change_percent",
+                max_price_change_percent.map(|v| v.to_string()),
+            ),
+            ("as_of", Some(as_of.to_rfc3339())),
+        ];
+        self.get_field("/api/market-summary", &query, "summary")
+            .await
+    }
+
+    /// `GET /api/quotes`: near-current prices for up to 100 symbols.
+    pub async fn quotes(&self, symbols: &[&str]) -> Result<Quotes> {
+        self.get("/api/quotes", &[("symbols", Some(symbols.join(",")))])
+            .await
+    }
+
+    /// `GET /api/news`
+    pub async fn news(
+        &self,
+        sector: Option<&str>,
+        search: Option<&str>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<NewsPage> {
+        let query = [
+            ("sector", sector.map(str::to_string)),
+            ("search", search.map(str::to_string)),
+            ("page", page.map(|v| v.to_string())),
+            ("page_size", page_size.map(|v| v.to_string())),
+        ];
+        self.get("/api/news", &query).await
+    }
+
+    /// `GET /api/sectors`
+    pub async fn sectors(&self) -> Result<Vec<SectorPerformance>> {
+        self.get_field("/api/sectors", &[], "sectors").await
+    }
+
+    /// `GET /api/sectors/etfs`
+    pub async fn sector_etfs(&self) -> Result<SectorEtfs> {
+        self.get("/api/sectors/etfs", &[]).await
+    }
+
+    /// `GET /api/signals/performance`, optionally over the last `days`.
+    pub async fn signal_performance(&self, days: Option<i64>) -> Result<SignalPerformance> {
+        self.get(
+            "/api/signals/performance",
+            &[("days", days.map(|v| v.to_string()))],
+        )
+        .await
+    }
+
+    /// `GET /api/events/52w`
+    pub async fn week52_events(
+        &self,
+        kind: Option<Week52Kind>,
+        days: Option<i64>,
+        limit: Option<usize>,
+    ) -> Result<Week52Events> {
+        let query = [
+            ("kind", kind.map(|k| enum_param(&k))),
+            ("days", days.map(|v| v.to_string())),
+            ("limit", limit.map(|v| v.to_string())),
+        ];
+        self.get("/api/events/52w", &query).await
+    }
+
+    /// `POST /api/ingest/signal`. Set `input.token` when the server has
+    /// `INGEST_TOKEN`.
+    pub async fn ingest_signal(&self, input: &SignalInput) -> Result<IngestedSignal> {
+        self.post("/api/ingest/signal", input).await
+    }
+
+    /// `POST /api/ingest/tradingview`: forward a TradingView alert body
+    /// unchanged. `token` is the server's `INGEST_TOKEN`, if any.
+    pub async fn ingest_tradingview(
+        &self,
+        alert: String,
+        token: Option<&str>,
+    ) -> Result<IngestedSignal> {
+        self.post_text(
+            "/api/ingest/tradingview",
+            &[("token", token.map(str::to_string))],
+            alert,
+        )
+        .await
+    }
+
+    /// `GET /api/ingest/signals`, newest first. `hours` defaults to the
+    /// server's signal TTL.
+    pub async fn external_signals(
+        &self,
+        symbol: Option<&str>,
+        hours: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<ExternalSignals> {
+        let query = [
+            ("symbol", symbol.map(str::to_string)),
+            ("hours", hours.map(|v| v.to_string())),
+            ("limit", limit.map(|v| v.to_string())),
+        ];
+        self.get("/api/ingest/signals", &query).await
+    }
+
+    /// `GET /api/earnings`
+    pub async fn earnings_calendar(&self, days_ahead: Option<u32>) -> Result<EarningsCalendar> {
+        self.get(
+            "/api/earnings",
+            &[("days_ahead", days_ahead.map(|v| v.to_string()))],
+        )
+        .await
+    }
+
+    /// `GET /api/analytics/correlation` over 2-20 symbols.
+    pub async fn correlation(
+        &self,
+        symbols: &[&str],
+        days: Option<i64>,
+    ) -> Result<CorrelationMatrix> {
+        let query = [
+            ("symbols", Some(symbols.join(","))),
+            ("days", days.map(|v| v.to_string())),
+        ];
+        self.get("/api/analytics/correlation", &query).await
+    }
+
+    /// `GET /api/analytics/what-if`: `amount` (default 1000) invested on
+    /// `date` and held until today.
+    pub async fn what_if(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+        amount: Option<f64>,
+    ) -> Result<WhatIfResult> {
+        let query = [
+            ("symbol", Some(symbol.to_string())),
+            ("date", Some(date.format("%Y-%m-%d").to_string())),
+            ("amount", amount.map(|v| v.to_string())),
+        ];
+        self.get_field("/api/analytics/what-if", &query, "result")
+            .await
+    }
+}
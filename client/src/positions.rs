@@ -0,0 +1,136 @@
+//! Tracked positions (tax lots), rebalancing, broker imports, valuation
+//! history and realized gains.
+
+use auto_analyser_2::notifications::models::{
+    CreatePositionInput, PositionView, SellPositionInput, UpdatePositionInput, ValuationSnapshot,
+};
+use auto_analyser_2::notifications::rebalance::RebalanceTarget;
+use bson::oid::ObjectId;
+use serde_json::{json, Value};
+
+use crate::responses::{ImportPreview, ImportResult, RealizedGains, Rebalance, Sale};
+use crate::{enum_param, field, Client, Result};
+
+impl Client {
+    /// `GET /api/positions`, each lot joined with its latest price.
+    pub async fn positions(&self) -> Result<Vec<PositionView>> {
+        self.get_field("/api/positions", &[], "positions").await
+    }
+
+    /// `POST /api/positions`
+    pub async fn create_position(&self, input: &CreatePositionInput) -> Result<PositionView> {
+        field(self.post("/api/positions", input).await?, "position")
+    }
+
+    /// `GET /api/positions/:id`
+    pub async fn position(&self, id: ObjectId) -> Result<PositionView> {
+        let path = format!("/api/positions/{}", id.to_hex());
+        self.get_field(&path, &[], "position").await
+    }
+
+    /// `PATCH /api/positions/:id`
+    pub async fn update_position(
+        &self,
+        id: ObjectId,
+        input: &UpdatePositionInput,
+    ) -> Result<PositionView> {
+        let path = format!("/api/positions/{}", id.to_hex());
+        field(self.patch(&path, input).await?, "position")
+    }
+
+    /// `DELETE /api/positions/:id`
+    pub async fn delete_position(&self, id: ObjectId) -> Result<()> {
+        let path = format!("/api/positions/{}", id.to_hex());
+        self.delete::<Value>(&path).await.map(drop)
+    }
+
+    /// `GET /api/positions/rebalance`. Costs default to no commission and
+    /// 5 bps of slippage.
+    pub async fn rebalance(
+        &self,
+        target: RebalanceTarget,
+        commission_per_trade: Option<f64>,
+        slippage_bps: Option<f64>,
+    ) -> Result<Rebalance> {
+        let query = [
+            ("target", Some(enum_param(&target))),
+            (
+                "commission_per_trade",
+                commission_per_trade.map(|v| v.to_string()),
+            ),
+            ("slippage_bps", slippage_bps.map(|v| v.to_string())),
+        ];
+        self.get("/api/positions/rebalance", &query).await
+    }
+
+    /// `POST /api/positions/sell`: close lots and record the realized gains.
+    pub async fn sell_position(&self, input: &SellPositionInput) -> Result<Sale> {
+        self.post("/api/positions/sell", input).await
+    }
+
+    /// `POST /api/positions/import?dry_run=true`: parse a broker export
+    /// without storing anything. `broker` is sniffed from `file` if omitted.
+    pub async fn preview_import(
+        &self,
+        broker: Option<&str>,
+        file: String,
+    ) -> Result<ImportPreview> {
+        let query = [
+            ("broker", broker.map(str::to_string)),
+            ("dry_run", Some("true".to_string())),
+        ];
+        self.post_text("/api/positions/import", &query, file).await
+    }
+
+    /// `POST /api/positions/import`. `trades` replays the trade log even
+    /// when the file also lists open positions.
+    pub async fn import_positions(
+        &self,
+        broker: Option<&str>,
+        trades: bool,
+        file: String,
+    ) -> Result<ImportResult> {
+        let query = [
+            ("broker", broker.map(str::to_string)),
+            ("trades", trades.then(|| "true".to_string())),
+        ];
+        self.post_text("/api/positions/import", &query, file).await
+    }
+
+    /// `GET /api/positions/history`: daily snapshots over the last `days`
+    /// (default 365), oldest first.
+    pub async fn valuation_history(&self, days: Option<i64>) -> Result<Vec<ValuationSnapshot>> {
+        self.get_field(
+            "/api/positions/history",
+            &[("days", days.map(|v| v.to_string()))],
+            "snapshots",
+        )
+        .await
+    }
+
+    /// `POST /api/positions/history`: take (or refresh) today's snapshot.
+    pub async fn snapshot_positions(&self) -> Result<ValuationSnapshot> {
+        field(
+            self.post("/api/positions/history", &json!({})).await?,
+            "snapshot",
+        )
+    }
+
+    /// `GET /api/positions/realized`, optionally for one tax year.
+    pub async fn realized_gains(&self, year: Option<i32>) -> Result<RealizedGains> {
+        self.get(
+            "/api/positions/realized",
+            &[("year", year.map(|v| v.to_string()))],
+        )
+        .await
+    }
+
+    /// `GET /api/positions/realized/export`: the same gains as CSV.
+    pub async fn export_realized_gains(&self, year: Option<i32>) -> Result<String> {
+        self.get_text(
+            "/api/positions/realized/export",
+            &[("year", year.map(|v| v.to_string()))],
+        )
+        .await
+    }
+}
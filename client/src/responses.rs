@@ -0,0 +1,488 @@
+//! Response bodies that carry more than one payload key.
+//!
+//! The `success` flag is checked by the client before decoding, so it is not
+//! repeated here. Nested values are the server's own types wherever the
+//! server has one; the structs below only cover the shapes handlers build
+//! inline with `json!`.
+
+use auto_analyser_2::backup::BackupManifest;
+use auto_analyser_2::db::CollectionStats;
+use auto_analyser_2::highs_lows::{Week52Breadth, Week52Event};
+use auto_analyser_2::indexes::{IndexHeatmapData, IndexPerformance};
+use auto_analyser_2::ingest::ExternalSignal;
+use auto_analyser_2::maintenance::MaintenanceStatus;
+use auto_analyser_2::models::{
+    AggregatedNewsItem, CachePin, DividendEvent, EarningsData, EngineMode, HistoricalPrice,
+    LiveQuote, StockAnalysis, SymbolProgress,
+};
+use auto_analyser_2::notifications::brokers::BrokerImport;
+use auto_analyser_2::notifications::lots::RealizedSummary;
+use auto_analyser_2::notifications::models::{DeliveryResult, NotificationHistory, RealizedGain};
+use auto_analyser_2::notifications::rebalance::RebalancePlan;
+use auto_analyser_2::notifications::recap::WatchlistRecap;
+use auto_analyser_2::query_profiler::QueryStatView;
+use auto_analyser_2::rate_budget::BudgetStats;
+use auto_analyser_2::runs::{AnalysisRun, RunSummary};
+use auto_analyser_2::sectors::SectorEtfSnapshot;
+use auto_analyser_2::signals::StrategyPerformance;
+use auto_analyser_2::themes::Theme;
+use auto_analyser_2::weekend::WeekendReport;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// `GET /`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub version: String,
+    /// `running` or `read_only`.
+    pub status: String,
+    pub maintenance: MaintenanceStatus,
+}
+
+/// `GET /health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Health {
+    /// `healthy` or `degraded`.
+    pub status: String,
+    pub database: String,
+    pub total_analyses: u64,
+    pub last_cycle_started: Option<DateTime<Utc>>,
+    pub last_cycle_completed: Option<DateTime<Utc>>,
+    pub last_successful_cycle: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub mode: EngineMode,
+    pub degraded_reason: Option<String>,
+    pub maintenance: MaintenanceStatus,
+    pub yahoo_budget: Option<BudgetStats>,
+}
+
+/// `GET /api/progress`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Progress {
+    pub total_stocks: usize,
+    pub analyzed: usize,
+    pub current_symbol: Option<String>,
+    pub cycle_start: DateTime<Utc>,
+    pub errors: usize,
+    pub last_cycle_started: Option<DateTime<Utc>>,
+    pub last_cycle_completed: Option<DateTime<Utc>>,
+    pub last_successful_cycle: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub mode: EngineMode,
+    pub degraded_reason: Option<String>,
+    pub completion_percentage: f64,
+}
+
+/// `GET /api/progress/symbols`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleSymbols {
+    pub cycle_start: DateTime<Utc>,
+    pub count: usize,
+    pub symbols: Vec<SymbolProgress>,
+}
+
+/// `GET /api/runs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisRuns {
+    pub count: usize,
+    /// Over the runs on this page.
+    pub summary: RunSummary,
+    /// Most recent first.
+    pub runs: Vec<AnalysisRun>,
+    pub pagination: Pagination,
+}
+
+/// `GET /api/admin/db/stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbStats {
+    pub slow_query_ms: u64,
+    pub total_queries: u64,
+    pub slow_queries: u64,
+    pub queries: Vec<QueryStatView>,
+}
+
+/// `GET /api/admin/db/collections`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionSizes {
+    /// `HISTORY_RETENTION_DAYS`; 0 keeps all.
+    pub history_retention_days: u32,
+    pub total_storage_bytes: u64,
+    /// Largest on disk first.
+    pub collections: Vec<CollectionStats>,
+}
+
+/// `GET /api/admin/weekend`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekendStatus {
+    pub running: bool,
+    /// Saturday New York hour of the scheduled run; `None` runs on demand only.
+    pub hour_ny: Option<u32>,
+    /// In memory only, so `None` after a restart.
+    pub last_report: Option<WeekendReport>,
+}
+
+/// `GET /api/admin/backups`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backups {
+    pub count: usize,
+    /// Backups kept after each run; `0` keeps all of them.
+    pub retention: usize,
+    pub backups: Vec<BackupManifest>,
+}
+
+/// `POST /api/admin/backups`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRun {
+    pub backup: BackupManifest,
+    pub pruned: usize,
+}
+
+/// `PUT /api/cache/pins/:symbol`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedStock {
+    pub pin: CachePin,
+    /// Whether the symbol had an analysis to load into the cache.
+    pub warmed: bool,
+}
+
+/// `GET /api/ai/status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiStatus {
+    pub enabled: bool,
+    pub current_model: Option<String>,
+    pub available_models_count: usize,
+}
+
+/// `GET /api/ai/models`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiModels {
+    pub models: Vec<String>,
+    pub count: usize,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Pagination {
+    pub page: u32,
+    pub page_size: u32,
+    pub total: u64,
+    pub total_pages: u32,
+    /// Pass back as `cursor` for the next page; `None` on the last page.
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+/// `POST /api/stocks/filter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockPage {
+    pub count: usize,
+    pub stocks: Vec<StockAnalysis>,
+    pub cached: bool,
+    pub pagination: Pagination,
+}
+
+/// `GET /api/stocks/:symbol`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockDetail {
+    /// Other share classes of the same company, e.g. `GOOG` for `GOOGL`.
+    pub share_classes: Vec<String>,
+    pub stock: StockAnalysis,
+    pub cached: bool,
+}
+
+/// `GET /api/stocks/:symbol/history?total_return=true`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotalReturnHistory {
+    pub symbol: String,
+    /// Close series with every dividend reinvested, aligned with `history`.
+    pub total_return_index: Vec<f64>,
+    pub history: Vec<HistoricalPrice>,
+    pub dividends: Vec<DividendEvent>,
+}
+
+/// The indicator values an AI analysis was based on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiStockData {
+    pub price: f64,
+    pub rsi: Option<f64>,
+    pub sma_20: Option<f64>,
+    pub sma_50: Option<f64>,
+    pub is_oversold: bool,
+    pub is_overbought: bool,
+}
+
+/// `GET /api/stocks/:symbol/ai-analysis`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiAnalysis {
+    pub symbol: String,
+    pub analysis: String,
+    pub model_used: String,
+    pub generated_at: DateTime<Utc>,
+    pub stock_data: AiStockData,
+}
+
+/// `GET /api/quotes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quotes {
+    pub count: usize,
+    /// How many quotes came from the cache rather than Yahoo.
+    pub cached: usize,
+    /// In request order.
+    pub quotes: Vec<LiveQuote>,
+    pub missing: Vec<String>,
+}
+
+/// `GET /api/news`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsPage {
+    pub news: Vec<AggregatedNewsItem>,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SectorEtf {
+    pub sector: String,
+    pub etf: String,
+}
+
+/// `GET /api/sectors/etfs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorEtfs {
+    pub mapping: Vec<SectorEtf>,
+    pub etfs: Vec<SectorEtfSnapshot>,
+}
+
+/// `GET /api/signals/performance`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalPerformance {
+    /// `YYYY-MM-DD` start of the window, when one was requested.
+    pub since: Option<String>,
+    pub total_signals: usize,
+    pub strategies: Vec<StrategyPerformance>,
+}
+
+/// `GET /api/events/52w`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Week52Events {
+    pub since: String,
+    pub total_events: usize,
+    /// Newest day first, over every event in the window.
+    pub breadth: Vec<Week52Breadth>,
+    /// Newest first, one page of them.
+    pub events: Vec<Week52Event>,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestedSignal {
+    /// The stored signal, with its `_id`.
+    pub signal: ExternalSignal,
+    /// Signals now active on the symbol, including this one.
+    pub active_signals: usize,
+    /// False when the symbol has no stored analysis yet.
+    pub analysis_updated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalSignals {
+    pub since: DateTime<Utc>,
+    pub count: usize,
+    /// Newest first.
+    pub signals: Vec<ExternalSignal>,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarningsCalendarRow {
+    pub symbol: String,
+    pub sector: Option<String>,
+    pub market_cap: Option<f64>,
+    pub price: f64,
+    pub earnings: EarningsData,
+}
+
+/// `GET /api/earnings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarningsCalendar {
+    /// Soonest first.
+    pub earnings: Vec<EarningsCalendarRow>,
+    pub count: usize,
+    pub days_ahead: u32,
+    pub failed_symbols: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedSymbol {
+    pub symbol: String,
+    pub error: String,
+}
+
+/// `GET /api/analytics/correlation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationMatrix {
+    pub requested_symbols: Vec<String>,
+    /// Symbols with history, in the order of `matrix`'s rows and columns.
+    pub symbols: Vec<String>,
+    pub matrix: Vec<Vec<f64>>,
+    pub days: i64,
+    pub failed_symbols: Vec<FailedSymbol>,
+}
+
+/// `GET /api/indexes/:index_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexDetail {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub symbol_count: usize,
+    pub symbols: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapStats {
+    pub total_constituents: usize,
+    pub stocks_with_data: usize,
+    pub total_market_cap: f64,
+    pub period: String,
+    /// Symbols whose period return fell back to the daily change.
+    pub fallback_symbols: Vec<String>,
+}
+
+/// `GET /api/indexes/:index_id/heatmap` and `GET /api/themes/:theme_id/heatmap`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heatmap {
+    pub heatmap: IndexHeatmapData,
+    pub stats: HeatmapStats,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CumulativeReturns {
+    pub equal_weight_return_pct: f64,
+    pub cap_weight_return_pct: f64,
+    pub divergence_pct: f64,
+}
+
+/// `GET /api/indexes/:index_id/performance` and
+/// `GET /api/themes/:theme_id/performance`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceHistory {
+    /// The index or theme id.
+    #[serde(alias = "index_id", alias = "theme_id")]
+    pub id: String,
+    /// Recorded days in the window.
+    pub days: usize,
+    pub latest: Option<IndexPerformance>,
+    pub cumulative: CumulativeReturns,
+    pub history: Vec<IndexPerformance>,
+}
+
+/// One entry of `GET /api/screens`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenSummary {
+    pub name: String,
+    pub description: String,
+    pub metric: String,
+    /// `None` until the screen has been computed.
+    pub total: Option<usize>,
+    pub computed_at: Option<DateTime<Utc>>,
+}
+
+/// `POST /api/admin/themes/:theme_id/symbols`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeUpdate {
+    pub changed: bool,
+    pub theme: Theme,
+}
+
+/// `POST /api/alerts/rules/:id/test`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTest {
+    pub delivered: Vec<DeliveryResult>,
+    pub symbol: String,
+}
+
+/// `POST /api/watchlists/:id/recap`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecapDelivery {
+    pub recap: WatchlistRecap,
+    pub delivered: Vec<DeliveryResult>,
+}
+
+/// `GET /api/alerts/history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub history: Vec<NotificationHistory>,
+    pub pagination: Pagination,
+}
+
+/// `GET /api/positions/rebalance`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rebalance {
+    pub plan: RebalancePlan,
+    /// Held symbols without a cached price, left out of the plan.
+    pub unpriced: Vec<String>,
+}
+
+/// `POST /api/positions/sell`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sale {
+    pub gains: Vec<RealizedGain>,
+    pub summary: RealizedSummary,
+}
+
+/// `POST /api/positions/import?dry_run=true`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub broker: String,
+    pub import: BrokerImport,
+}
+
+/// `POST /api/positions/import`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub broker: String,
+    /// `positions` or `trades`.
+    pub mode: String,
+    pub lots_created: usize,
+    pub realized: RealizedSummary,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// `GET /api/positions/realized`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealizedGains {
+    pub year: Option<i32>,
+    pub realized: RealizedSummary,
+    pub unrealized_pnl: f64,
+    /// Open lots without a cached price, left out of `unrealized_pnl`.
+    pub unpriced: Vec<String>,
+    pub gains: Vec<RealizedGain>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_performance_history_accepts_index_and_theme_ids() {
+        let body = |key: &str| {
+            serde_json::json!({
+                key: "ai",
+                "days": 0,
+                "latest": null,
+                "cumulative": {
+                    "equal_weight_return_pct": 0.0,
+                    "cap_weight_return_pct": 0.0,
+                    "divergence_pct": 0.0
+                },
+                "history": []
+            })
+        };
+        for key in ["index_id", "theme_id"] {
+            let history: PerformanceHistory = serde_json::from_value(body(key)).unwrap();
+            assert_eq!(history.id, "ai");
+        }
+    }
+}
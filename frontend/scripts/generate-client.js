@@ -0,0 +1,205 @@
+#!/usr/bin/env node
+// Generates src/generated/client.ts from the repository's openapi.json:
+// one TypeScript type per component schema and one typed method per
+// operation on `ApiClient`. Run with `npm run generate:client` after
+// changing the spec; CI type-checks the output with the rest of the app.
+//
+// Only the OpenAPI subset the spec uses is supported: $ref, allOf with a
+// single member, oneOf, enum, nullable, arrays and objects.
+
+const fs = require('fs');
+const path = require('path');
+
+const SPEC = path.resolve(__dirname, '../../openapi.json');
+const OUT = path.resolve(__dirname, '../src/generated/client.ts');
+
+const spec = JSON.parse(fs.readFileSync(SPEC, 'utf8'));
+
+const pascal = (s) => s.charAt(0).toUpperCase() + s.slice(1);
+const literal = (v) => (typeof v === 'string' ? `'${v.replace(/'/g, "\\'")}'` : String(v));
+const key = (k) => (/^[A-Za-z_][A-Za-z0-9_]*$/.test(k) ? k : literal(k));
+const refName = (ref) => ref.split('/').pop();
+
+function doc(text, indent) {
+  return text ? `${indent}/** ${text.replace(/\*\//g, '*\\/')} */\n` : '';
+}
+
+function tsType(schema, indent = '') {
+  let t;
+  if (schema.$ref) {
+    t = refName(schema.$ref);
+  } else if (schema.allOf && schema.allOf.length === 1) {
+    t = tsType(schema.allOf[0], indent);
+  } else if (schema.oneOf) {
+    const members = schema.oneOf.map((s) => tsType(s, indent + '  '));
+    t = members.some((m) => m.includes('\n'))
+      ? members.map((m) => `\n${indent}  | ${m}`).join('')
+      : members.join(' | ');
+  } else if (schema.enum) {
+    t = schema.enum.map(literal).join(' | ');
+  } else if (schema.type === 'array') {
+    const item = tsType(schema.items, indent);
+    t = /[ |&]/.test(item) ? `Array<${item}>` : `${item}[]`;
+  } else if (schema.type === 'object') {
+    t = objectType(schema, indent);
+  } else if (schema.type === 'integer' || schema.type === 'number') {
+    t = 'number';
+  } else if (schema.type === 'string' || schema.type === 'boolean') {
+    t = schema.type;
+  } else {
+    t = 'unknown';
+  }
+  return schema.nullable ? `${t} | null` : t;
+}
+
+function objectType(schema, indent) {
+  const props = Object.entries(schema.properties || {});
+  if (props.length === 0) {
+    return 'Record<string, unknown>';
+  }
+  const required = new Set(schema.required || []);
+  const inner = indent + '  ';
+  const lines = props.map(
+    ([name, prop]) =>
+      doc(prop.description, inner) +
+      `${inner}${key(name)}${required.has(name) ? '' : '?'}: ${tsType(prop, inner)};\n`
+  );
+  return `{\n${lines.join('')}${indent}}`;
+}
+
+function schemaDecl(name, schema) {
+  const body = tsType(schema);
+  const head = doc(schema.description, '');
+  if (schema.type === 'object' && !schema.nullable && body.startsWith('{')) {
+    return `${head}export interface ${name} ${body}\n`;
+  }
+  return `${head}export type ${name} =${body.startsWith('\n') ? '' : ' '}${body};\n`;
+}
+
+const types = [];
+const methods = [];
+
+for (const [name, schema] of Object.entries(spec.components.schemas)) {
+  types.push(schemaDecl(name, schema));
+}
+
+for (const [route, ops] of Object.entries(spec.paths)) {
+  for (const [method, op] of Object.entries(ops)) {
+    const ok = op.responses['200'].content;
+    if (ok['text/event-stream']) {
+      // Server-sent events need EventSource, not axios; see url().
+      continue;
+    }
+    const id = op.operationId;
+    const params = op.parameters || [];
+    const pathParams = params.filter((p) => p.in === 'path');
+    const queryParams = params.filter((p) => p.in === 'query');
+
+    let responseType = 'string';
+    const json = ok['application/json'];
+    if (json) {
+      if (json.schema.$ref) {
+        responseType = refName(json.schema.$ref);
+      } else {
+        responseType = `${pascal(id)}Response`;
+        types.push(schemaDecl(responseType, json.schema));
+      }
+    }
+
+    const args = pathParams.map((p) => `${p.name}: string`);
+    const options = [];
+    const body = op.requestBody && op.requestBody.content;
+    if (body && body['application/json']) {
+      let bodyType = tsType(body['application/json'].schema);
+      if (!body['application/json'].schema.$ref) {
+        types.push(schemaDecl(`${pascal(id)}Body`, body['application/json'].schema));
+        bodyType = `${pascal(id)}Body`;
+      }
+      args.push(`body: ${bodyType}`);
+      options.push('data: body');
+    } else if (body && body['text/plain']) {
+      args.push('body: string');
+      options.push('data: body', "headers: { 'Content-Type': 'text/plain' }");
+    } else if (['post', 'put', 'patch'].includes(method)) {
+      options.push('data: {}');
+    }
+    if (queryParams.length > 0) {
+      const queryType = `${pascal(id)}Query`;
+      types.push(
+        schemaDecl(queryType, {
+          type: 'object',
+          properties: Object.fromEntries(
+            queryParams.map((p) => [p.name, { ...p.schema, description: p.description }])
+          ),
+          required: queryParams.filter((p) => p.required).map((p) => p.name),
+        })
+      );
+      const optional = queryParams.every((p) => !p.required);
+      args.push(optional ? `query: ${queryType} = {}` : `query: ${queryType}`);
+      options.unshift('params: query');
+    }
+    if (!json) options.push("responseType: 'text'");
+
+    const url = route.replace(/\{(\w+)\}/g, (_, p) => `\${encodeURIComponent(${p})}`);
+
+    methods.push(
+      doc(`\`${method.toUpperCase()} ${route}\`: ${op.summary}.`, '  ') +
+        `  ${id}(${args.join(', ')}): Promise<${responseType}> {\n` +
+        `    return this.request('${method}', \`${url}\`, ${options.length ? `{ ${options.join(', ')} }` : '{}'});\n` +
+        '  }\n'
+    );
+  }
+}
+
+const out = `// Generated by scripts/generate-client.js from openapi.json. Do not edit;
+// run \`npm run generate:client\` instead.
+/* eslint-disable */
+
+import axios, { AxiosInstance, AxiosRequestConfig, Method } from 'axios';
+
+${types.join('\n')}
+/** \`success: false\` bodies and non-2xx statuses. */
+export class ApiError extends Error {
+  constructor(message: string, readonly status: number) {
+    super(message);
+    this.name = 'ApiError';
+  }
+}
+
+export class ApiClient {
+  private readonly http: AxiosInstance;
+
+  /** \`baseUrl\` is the server root; '' targets the page's own origin. */
+  constructor(readonly baseUrl = '', http: AxiosInstance = axios.create()) {
+    this.baseUrl = baseUrl.replace(/\\/$/, '');
+    this.http = http;
+  }
+
+  /** Absolute URL of \`path\`, for the WebSocket and the SSE stream. */
+  url(path: string): string {
+    return \`\${this.baseUrl}\${path}\`;
+  }
+
+  private async request<T>(method: Method, path: string, config: AxiosRequestConfig): Promise<T> {
+    let response;
+    try {
+      response = await this.http.request({ ...config, method, url: this.url(path) });
+    } catch (e) {
+      if (axios.isAxiosError(e) && e.response) {
+        const data: any = e.response.data;
+        throw new ApiError((data && data.error) || e.message, e.response.status);
+      }
+      throw e;
+    }
+    if (response.data && response.data.success === false) {
+      throw new ApiError(response.data.error || 'request failed', response.status);
+    }
+    return response.data;
+  }
+
+${methods.join('\n')}}
+`;
+
+fs.mkdirSync(path.dirname(OUT), { recursive: true });
+fs.writeFileSync(OUT, out);
+console.log(`Wrote ${path.relative(process.cwd(), OUT)}`);
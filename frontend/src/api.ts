@@ -1,5 +1,5 @@
 import axios from 'axios';
-import { StockAnalysis, StockFilter, AnalysisProgress, HistoricalDataPoint, MarketSummary, PaginationInfo, AIAnalysisResponse, GlobalSettings, CompanyProfile, IndexInfo, IndexHeatmapResponse, AggregatedNewsItem, SectorPerformance, InsiderTrade, EarningsData, EarningsCalendarRow, CorrelationData, Watchlist, NotificationChannel, AlertRule, NotificationHistoryItem, DeliveryResult, AlertScope, ConditionGroup, QuietHours, DiscordChannelConfig, HealthStatus, PositionView, CreatePositionInput, UpdatePositionInput } from './types';
+import { StockAnalysis, StockFilter, AnalysisProgress, HistoricalDataPoint, MarketSummary, PaginationInfo, AIAnalysisResponse, GlobalSettings, CompanyProfile, IndexInfo, IndexHeatmapResponse, AggregatedNewsItem, SectorPerformance, InsiderTrade, EarningsData, EarningsCalendarRow, CorrelationData, WhatIfResult, SectorEtfSnapshot, Watchlist, WatchlistRecap, NotificationChannel, AlertRule, AlertAuditEvent, NotificationHistoryItem, DeliveryResult, AlertScope, ConditionGroup, QuietHours, DiscordChannelConfig, HealthStatus, MaintenanceStatus, PositionView, CreatePositionInput, UpdatePositionInput, RebalancePlan, RebalanceTarget, LotMethod, RealizedGain, RealizedSummary, BrokerFormat, BrokerImportPreview, BrokerImportResult, ValuationSnapshot, SymbolCycleStatus, SymbolProgress, DeadLetter, QuotesResponse, IndexPerformanceResponse, IndexContributorsResponse, Theme, ThemeInput, ThemePerformanceResponse, ScreenResult, ScreenSummary, DbStatsResponse, CollectionSizesResponse, BackupManifest, BackupsResponse, Week52EventsResponse, ExternalSignalsResponse, SymbolNote, TagCount } from './types';
 
 const API_BASE_URL = (process.env.REACT_APP_API_URL || '').replace(/\/$/, '');
 
@@ -11,14 +11,14 @@ export interface FilterResponse {
 
 export const api = {
   // Get all stocks (paginated)
-  getStocks: async (): Promise<StockAnalysis[]> => {
-    const response = await axios.get(`${API_BASE_URL}/api/stocks`);
+  getStocks: async (humanize = false): Promise<StockAnalysis[]> => {
+    const response = await axios.get(`${API_BASE_URL}/api/stocks`, { params: humanize ? { humanize } : undefined });
     return response.data.stocks || response.data;
   },
 
   // Get a single stock by symbol
-  getStock: async (symbol: string): Promise<{ stock: StockAnalysis | null; cached: boolean }> => {
-    const response = await axios.get(`${API_BASE_URL}/api/stocks/${symbol}`);
+  getStock: async (symbol: string, humanize = false): Promise<{ stock: StockAnalysis | null; cached: boolean }> => {
+    const response = await axios.get(`${API_BASE_URL}/api/stocks/${symbol}`, { params: humanize ? { humanize } : undefined });
     if (response.data.success) {
       return { stock: response.data.stock, cached: response.data.cached };
     }
@@ -26,8 +26,8 @@ export const api = {
   },
 
   // Filter stocks with pagination
-  filterStocks: async (filter: StockFilter): Promise<FilterResponse> => {
-    const response = await axios.post(`${API_BASE_URL}/api/stocks/filter`, filter);
+  filterStocks: async (filter: StockFilter, humanize = false): Promise<FilterResponse> => {
+    const response = await axios.post(`${API_BASE_URL}/api/stocks/filter`, filter, { params: humanize ? { humanize } : undefined });
     return {
       stocks: response.data.stocks || [],
       pagination: response.data.pagination || { page: 1, page_size: 50, total: 0, total_pages: 0 },
@@ -58,6 +58,15 @@ export const api = {
     throw new Error(response.data.error || 'Failed to fetch market summary');
   },
 
+  // Near-current prices from the short-TTL quote cache (max 100 symbols)
+  getQuotes: async (symbols: string[]): Promise<QuotesResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/quotes`, { params: { symbols: symbols.join(',') } });
+    if (response.data.success) {
+      return response.data;
+    }
+    throw new Error(response.data.error || 'Failed to fetch quotes');
+  },
+
   // Get AI analysis for a stock
   getAIAnalysis: async (symbol: string): Promise<AIAnalysisResponse> => {
     const response = await axios.get(`${API_BASE_URL}/api/stocks/${symbol}/ai-analysis`);
@@ -121,11 +130,26 @@ export const api = {
   },
 
   // Get stock historical data
-  getStockHistory: async (symbol: string): Promise<HistoricalDataPoint[]> => {
-    const response = await axios.get(`${API_BASE_URL}/api/stocks/${symbol}/history`);
+  getStockHistory: async (
+    symbol: string,
+    candleType: 'regular' | 'heikin_ashi' = 'regular',
+  ): Promise<HistoricalDataPoint[]> => {
+    const response = await axios.get(`${API_BASE_URL}/api/stocks/${symbol}/history`, { params: { candle_type: candleType } });
     return response.data.history || [];
   },
 
+  // History plus dividends and a dividends-reinvested close series
+  getStockTotalReturnHistory: async (
+    symbol: string,
+  ): Promise<{ history: HistoricalDataPoint[]; dividends: { date: string; amount: number }[]; total_return_index: number[] }> => {
+    const response = await axios.get(`${API_BASE_URL}/api/stocks/${symbol}/history`, { params: { total_return: true } });
+    return {
+      history: response.data.history || [],
+      dividends: response.data.dividends || [],
+      total_return_index: response.data.total_return_index || [],
+    };
+  },
+
   // Get company profile (description, industry, website, etc.)
   getCompanyProfile: async (symbol: string): Promise<CompanyProfile | null> => {
     try {
@@ -145,6 +169,86 @@ export const api = {
     return response.data;
   },
 
+  // Per-symbol state of the current analysis cycle
+  getProgressSymbols: async (status?: SymbolCycleStatus): Promise<SymbolProgress[]> => {
+    const response = await axios.get(`${API_BASE_URL}/api/progress/symbols`, { params: status ? { status } : undefined });
+    return response.data.symbols || [];
+  },
+
+  // Dead-letter queue (symbols excluded after repeated failures)
+  getDeadLetters: async (): Promise<DeadLetter[]> => {
+    const response = await axios.get(`${API_BASE_URL}/api/admin/dead-letters`);
+    return response.data.dead_letters || [];
+  },
+
+  // Requeue one symbol, or every dead-lettered symbol when omitted
+  requeueDeadLetters: async (symbol?: string): Promise<void> => {
+    const path = symbol
+      ? `/api/admin/dead-letters/${encodeURIComponent(symbol)}/requeue`
+      : '/api/admin/dead-letters/requeue';
+    await axios.post(`${API_BASE_URL}${path}`);
+  },
+
+  // MongoDB query timings by command/collection/filter shape
+  getDbStats: async (): Promise<DbStatsResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/admin/db/stats`);
+    return response.data;
+  },
+
+  resetDbStats: async (): Promise<void> => {
+    await axios.delete(`${API_BASE_URL}/api/admin/db/stats`);
+  },
+
+  // Per-collection sizes and history retention
+  getCollectionSizes: async (): Promise<CollectionSizesResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/admin/db/collections`);
+    return response.data;
+  },
+
+  getBackups: async (): Promise<BackupsResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/admin/backups`);
+    return response.data;
+  },
+
+  runBackup: async (): Promise<{ success: boolean; backup: BackupManifest; pruned: number }> => {
+    const response = await axios.post(`${API_BASE_URL}/api/admin/backups`);
+    return response.data;
+  },
+
+  // Read-only maintenance mode: mutations answer 503 and the engine pauses
+  getMaintenance: async (): Promise<MaintenanceStatus> => {
+    const response = await axios.get(`${API_BASE_URL}/api/admin/maintenance`);
+    return response.data.maintenance;
+  },
+
+  setMaintenance: async (readOnly: boolean, reason?: string): Promise<MaintenanceStatus> => {
+    const response = await axios.put(`${API_BASE_URL}/api/admin/maintenance`, {
+      read_only: readOnly,
+      reason,
+    });
+    return response.data.maintenance;
+  },
+
+  // New 52-week highs/lows and daily breadth counts
+  getWeek52Events: async (params?: {
+    kind?: 'high' | 'low';
+    days?: number;
+    limit?: number;
+  }): Promise<Week52EventsResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/events/52w`, { params });
+    return response.data;
+  },
+
+  // Signals pushed by external systems to /api/ingest/signal
+  getExternalSignals: async (params?: {
+    symbol?: string;
+    hours?: number;
+    limit?: number;
+  }): Promise<ExternalSignalsResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/ingest/signals`, { params });
+    return response.data;
+  },
+
   // Health check
   healthCheck: async (): Promise<HealthStatus> => {
     const response = await axios.get(`${API_BASE_URL}/health`);
@@ -165,6 +269,13 @@ export const api = {
     return `${wsProtocol}//${window.location.host}/ws`;
   },
 
+  // Intraday candle subscription commands for the /ws socket
+  intradaySubscribeMessage: (symbols: string[]): string =>
+    JSON.stringify({ action: 'subscribe', symbols }),
+
+  intradayUnsubscribeMessage: (symbols: string[]): string =>
+    JSON.stringify({ action: 'unsubscribe', symbols }),
+
   // Get list of available market indexes
   getIndexes: async (): Promise<IndexInfo[]> => {
     const response = await axios.get(`${API_BASE_URL}/api/indexes`);
@@ -180,6 +291,78 @@ export const api = {
     return response.data;
   },
 
+  // Daily equal- vs cap-weighted returns for an index
+  getIndexPerformance: async (indexId: string, days: number = 30): Promise<IndexPerformanceResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/indexes/${indexId}/performance`, { params: { days } });
+    return response.data;
+  },
+
+  // Top positive/negative contributors to an index's move (latest day by default)
+  getIndexContributors: async (indexId: string, params?: { date?: string; limit?: number }): Promise<IndexContributorsResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/indexes/${indexId}/contributors`, { params });
+    return response.data;
+  },
+
+  // Pre-computed screens, refreshed at the end of every cycle
+  getScreens: async (): Promise<ScreenSummary[]> => {
+    const response = await axios.get(`${API_BASE_URL}/api/screens`);
+    return response.data.screens || [];
+  },
+
+  getScreen: async (name: string, limit?: number): Promise<ScreenResult | null> => {
+    const response = await axios.get(`${API_BASE_URL}/api/screens/${name}`, { params: { limit } });
+    return response.data.success ? response.data.screen : null;
+  },
+
+  // User notes and tags on symbols
+  getStockNotes: async (symbol: string): Promise<SymbolNote | null> => {
+    const response = await axios.get(`${API_BASE_URL}/api/stocks/${symbol}/notes`);
+    return response.data.success ? response.data.note : null;
+  },
+
+  saveStockNotes: async (symbol: string, notes: string, tags: string[]): Promise<SymbolNote | null> => {
+    const response = await axios.put(`${API_BASE_URL}/api/stocks/${symbol}/notes`, { notes, tags });
+    if (!response.data.success) throw new Error(response.data.error);
+    return response.data.note;
+  },
+
+  getTags: async (): Promise<TagCount[]> => {
+    const response = await axios.get(`${API_BASE_URL}/api/tags`);
+    return response.data.tags || [];
+  },
+
+  // Thematic symbol sets (AI, EV, semis, ...)
+  getThemes: async (): Promise<Theme[]> => {
+    const response = await axios.get(`${API_BASE_URL}/api/themes`);
+    return response.data.themes || [];
+  },
+
+  getThemeHeatmap: async (themeId: string, period: string = '1d'): Promise<IndexHeatmapResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/themes/${themeId}/heatmap`, { params: { period } });
+    return response.data;
+  },
+
+  getThemePerformance: async (themeId: string, days: number = 30): Promise<ThemePerformanceResponse> => {
+    const response = await axios.get(`${API_BASE_URL}/api/themes/${themeId}/performance`, { params: { days } });
+    return response.data;
+  },
+
+  saveTheme: async (themeId: string, input: ThemeInput): Promise<Theme> => {
+    const response = await axios.put(`${API_BASE_URL}/api/admin/themes/${themeId}`, input);
+    if (!response.data.success) throw new Error(response.data.error);
+    return response.data.theme;
+  },
+
+  updateThemeSymbols: async (themeId: string, change: { add?: string[]; remove?: string[] }): Promise<Theme> => {
+    const response = await axios.post(`${API_BASE_URL}/api/admin/themes/${themeId}/symbols`, change);
+    if (!response.data.success) throw new Error(response.data.error);
+    return response.data.theme;
+  },
+
+  deleteTheme: async (themeId: string): Promise<void> => {
+    await axios.delete(`${API_BASE_URL}/api/admin/themes/${themeId}`);
+  },
+
   // Get aggregated news feed
   getNews: async (params?: { sector?: string; search?: string; page?: number; page_size?: number }): Promise<{ news: AggregatedNewsItem[]; pagination: PaginationInfo }> => {
     const queryParams = new URLSearchParams();
@@ -217,6 +400,12 @@ export const api = {
     return response.data.sectors || [];
   },
 
+  // Sector → proxy ETF mapping and the ETFs' trailing returns
+  getSectorEtfs: async (): Promise<{ mapping: { sector: string; etf: string }[]; etfs: SectorEtfSnapshot[] }> => {
+    const response = await axios.get(`${API_BASE_URL}/api/sectors/etfs`);
+    return { mapping: response.data.mapping || [], etfs: response.data.etfs || [] };
+  },
+
   // Get correlation matrix
   getCorrelationMatrix: async (symbols: string[], days?: number): Promise<CorrelationData> => {
     const params = new URLSearchParams();
@@ -226,6 +415,13 @@ export const api = {
     return response.data;
   },
 
+  // Simulate buying `amount` of `symbol` on a past date
+  getWhatIf: async (symbol: string, date: string, amount = 1000): Promise<WhatIfResult> => {
+    const response = await axios.get(`${API_BASE_URL}/api/analytics/what-if`, { params: { symbol, date, amount } });
+    if (!response.data.success) throw new Error(response.data.error);
+    return response.data.result;
+  },
+
   // Get earnings data for a single stock
   getStockEarnings: async (symbol: string): Promise<EarningsData | null> => {
     try {
@@ -277,6 +473,16 @@ export const api = {
       const r = await axios.delete(`${API_BASE_URL}/api/watchlists/${id}/symbols/${symbol}`);
       return r.data.watchlist;
     },
+    getWatchlistRecap: async (id: string): Promise<WatchlistRecap> => {
+      const r = await axios.get(`${API_BASE_URL}/api/watchlists/${id}/recap`);
+      return r.data.recap;
+    },
+    sendWatchlistRecap: async (
+      id: string,
+    ): Promise<{ recap: WatchlistRecap; delivered: DeliveryResult[] }> => {
+      const r = await axios.post(`${API_BASE_URL}/api/watchlists/${id}/recap`);
+      return { recap: r.data.recap, delivered: r.data.delivered || [] };
+    },
 
     // ---- positions ----
     listPositions: async (): Promise<PositionView[]> => {
@@ -298,6 +504,62 @@ export const api = {
     deletePosition: async (id: string): Promise<void> => {
       await axios.delete(`${API_BASE_URL}/api/positions/${id}`);
     },
+    sellPosition: async (input: {
+      symbol: string;
+      quantity: number;
+      price: number;
+      method?: LotMethod;
+      lot_ids?: string[];
+      sold_at?: string;
+    }): Promise<{ gains: RealizedGain[]; summary: RealizedSummary }> => {
+      const r = await axios.post(`${API_BASE_URL}/api/positions/sell`, input);
+      return { gains: r.data.gains || [], summary: r.data.summary };
+    },
+    getRealizedGains: async (
+      year?: number,
+    ): Promise<{ realized: RealizedSummary; unrealized_pnl: number; unpriced: string[]; gains: RealizedGain[] }> => {
+      const r = await axios.get(`${API_BASE_URL}/api/positions/realized`, { params: { year } });
+      return {
+        realized: r.data.realized,
+        unrealized_pnl: r.data.unrealized_pnl,
+        unpriced: r.data.unpriced || [],
+        gains: r.data.gains || [],
+      };
+    },
+    getPositionHistory: async (days = 365): Promise<ValuationSnapshot[]> => {
+      const r = await axios.get(`${API_BASE_URL}/api/positions/history`, { params: { days } });
+      return r.data.snapshots || [];
+    },
+    snapshotPositions: async (): Promise<ValuationSnapshot | null> => {
+      const r = await axios.post(`${API_BASE_URL}/api/positions/history`);
+      return r.data.snapshot;
+    },
+    previewBrokerImport: async (content: string, broker?: BrokerFormat): Promise<BrokerImportPreview> => {
+      const r = await axios.post(`${API_BASE_URL}/api/positions/import`, content, {
+        params: { broker, dry_run: true },
+        headers: { 'Content-Type': 'text/plain' },
+      });
+      return { broker: r.data.broker, ...r.data.import };
+    },
+    importBrokerExport: async (
+      content: string,
+      opts: { broker?: BrokerFormat; trades?: boolean } = {},
+    ): Promise<BrokerImportResult> => {
+      const r = await axios.post(`${API_BASE_URL}/api/positions/import`, content, {
+        params: opts,
+        headers: { 'Content-Type': 'text/plain' },
+      });
+      return r.data;
+    },
+    realizedGainsExportUrl: (year?: number): string =>
+      `${API_BASE_URL}/api/positions/realized/export${year ? `?year=${year}` : ''}`,
+    rebalancePositions: async (
+      target: RebalanceTarget,
+      costs: { commission_per_trade?: number; slippage_bps?: number } = {},
+    ): Promise<{ plan: RebalancePlan; unpriced: string[] }> => {
+      const r = await axios.get(`${API_BASE_URL}/api/positions/rebalance`, { params: { target, ...costs } });
+      return { plan: r.data.plan, unpriced: r.data.unpriced || [] };
+    },
 
     // ---- channels ----
     listChannels: async (): Promise<NotificationChannel[]> => {
@@ -319,7 +581,7 @@ export const api = {
     },
     updateChannel: async (
       id: string,
-      patch: { name?: string; enabled?: boolean; config?: { kind: 'discord' } & DiscordChannelConfig },
+      patch: { name?: string; enabled?: boolean; daily_recap?: boolean; config?: { kind: 'discord' } & DiscordChannelConfig },
     ): Promise<NotificationChannel> => {
       const r = await axios.put(`${API_BASE_URL}/api/alerts/channels/${id}`, patch);
       return r.data.channel;
@@ -355,11 +617,13 @@ export const api = {
       channel_ids: string[];
       message_template?: string | null;
       require_consecutive?: number;
+      max_triggers?: number | null;
+      muted_symbols?: string[];
     }): Promise<AlertRule> => {
       const r = await axios.post(`${API_BASE_URL}/api/alerts/rules`, input);
       return r.data.rule;
     },
-    updateRule: async (id: string, patch: Partial<Omit<AlertRule, '_id' | 'created_at' | 'updated_at'>>): Promise<AlertRule> => {
+    updateRule: async (id: string, patch: Partial<Omit<AlertRule, '_id' | 'created_at' | 'updated_at' | 'trigger_count' | 'snoozed_until'>>): Promise<AlertRule> => {
       const r = await axios.put(`${API_BASE_URL}/api/alerts/rules/${id}`, patch);
       return r.data.rule;
     },
@@ -370,6 +634,30 @@ export const api = {
     deleteRule: async (id: string): Promise<void> => {
       await axios.delete(`${API_BASE_URL}/api/alerts/rules/${id}`);
     },
+    snoozeRule: async (id: string, snooze: { until?: string; minutes?: number }): Promise<AlertRule> => {
+      const r = await axios.post(`${API_BASE_URL}/api/alerts/rules/${id}/snooze`, snooze);
+      return r.data.rule;
+    },
+    unsnoozeRule: async (id: string): Promise<AlertRule> => {
+      const r = await axios.delete(`${API_BASE_URL}/api/alerts/rules/${id}/snooze`);
+      return r.data.rule;
+    },
+    muteSymbol: async (id: string, symbol: string): Promise<AlertRule> => {
+      const r = await axios.put(`${API_BASE_URL}/api/alerts/rules/${id}/mute/${encodeURIComponent(symbol)}`);
+      return r.data.rule;
+    },
+    unmuteSymbol: async (id: string, symbol: string): Promise<AlertRule> => {
+      const r = await axios.delete(`${API_BASE_URL}/api/alerts/rules/${id}/mute/${encodeURIComponent(symbol)}`);
+      return r.data.rule;
+    },
+    resetRule: async (id: string): Promise<AlertRule> => {
+      const r = await axios.post(`${API_BASE_URL}/api/alerts/rules/${id}/reset`);
+      return r.data.rule;
+    },
+    getRuleAudit: async (id: string, limit = 100): Promise<AlertAuditEvent[]> => {
+      const r = await axios.get(`${API_BASE_URL}/api/alerts/rules/${id}/audit`, { params: { limit } });
+      return r.data.events || [];
+    },
     testRule: async (
       id: string,
       symbol?: string,
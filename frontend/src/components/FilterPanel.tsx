@@ -293,6 +293,18 @@ const FilterPanel: React.FC<FilterPanelProps> = ({ onApplyFilter, activeFilterCo
                 </HStack>
               </Box>
 
+              {/* Tags (see the Notes & Tags box on a stock's page) */}
+              <Box>
+                <Text fontSize="lg" fontWeight="semibold" mb={2}>
+                  Tags
+                </Text>
+                <Input
+                  value={(filter.tags || []).join(',')}
+                  onChange={(e) => updateFilter('tags', e.target.value ? e.target.value.split(',') : undefined)}
+                  placeholder="earnings-play, avoid"
+                />
+              </Box>
+
               {/* Market Cap Range */}
               <Box>
                 <Text fontSize="lg" fontWeight="semibold" mb={2}>
@@ -355,6 +367,18 @@ const FilterPanel: React.FC<FilterPanelProps> = ({ onApplyFilter, activeFilterCo
                   >
                     Only Overbought (RSI &gt; 70)
                   </Checkbox>
+                  <Checkbox
+                    checked={filter.only_stoch_oversold || false}
+                    onCheckedChange={(e: any) => updateFilter('only_stoch_oversold', e.checked)}
+                  >
+                    Only Stochastic Oversold (%K &lt; 20)
+                  </Checkbox>
+                  <Checkbox
+                    checked={filter.only_stoch_overbought || false}
+                    onCheckedChange={(e: any) => updateFilter('only_stoch_overbought', e.checked)}
+                  >
+                    Only Stochastic Overbought (%K &gt; 80)
+                  </Checkbox>
                 </VStack>
               </Box>
             </VStack>
@@ -0,0 +1,83 @@
+import React, { useEffect, useState } from 'react';
+import { Button, Flex, HStack, Input, Text, Textarea } from '@chakra-ui/react';
+import { Save } from 'lucide-react';
+import { api } from '../api';
+import { Surface, SignalBadge } from './ui/primitives';
+import { toaster } from './ui/toaster';
+
+/**
+ * Free-form notes and comma-separated tags for one symbol, saved with
+ * PUT /api/stocks/:symbol/notes. Tags come back normalized ("Earnings Play"
+ * → "earnings-play") and can be used as a screener filter.
+ */
+export const NotesEditor: React.FC<{
+  symbol: string;
+  notes?: string;
+  tags?: string[];
+}> = ({ symbol, notes = '', tags = [] }) => {
+  const [text, setText] = useState(notes);
+  const [tagText, setTagText] = useState(tags.join(', '));
+  const [saved, setSaved] = useState<string[]>(tags);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    setText(notes);
+    setTagText(tags.join(', '));
+    setSaved(tags);
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [symbol]);
+
+  const save = async () => {
+    setSaving(true);
+    try {
+      const list = tagText.split(',').map(t => t.trim()).filter(Boolean);
+      const note = await api.saveStockNotes(symbol, text, list);
+      setSaved(note?.tags || []);
+      setTagText((note?.tags || []).join(', '));
+      toaster.create({ title: `Notes saved for ${symbol}`, type: 'success' });
+    } catch (e: any) {
+      toaster.create({
+        title: 'Failed to save notes',
+        description: e?.response?.data?.error || e?.message,
+        type: 'error',
+      });
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <Surface mb={4} p={4}>
+      <Flex justify="space-between" align="center" mb={2} gap={2} wrap="wrap">
+        <Text color="fg.muted" fontSize="xs" textTransform="uppercase" letterSpacing="wider">Notes &amp; Tags</Text>
+        <HStack wrap="wrap">
+          {saved.map(tag => (
+            <SignalBadge key={tag} tone="neutral">{tag}</SignalBadge>
+          ))}
+        </HStack>
+      </Flex>
+      <Textarea
+        bg="bg.inset"
+        color="fg.default"
+        rows={3}
+        maxLength={2000}
+        placeholder="Why you're watching, earnings plan, reasons to avoid..."
+        value={text}
+        onChange={e => setText(e.target.value)}
+      />
+      <Flex mt={2} gap={2}>
+        <Input
+          bg="bg.inset"
+          color="fg.default"
+          size="sm"
+          placeholder="Tags, comma-separated (e.g. earnings-play, avoid)"
+          value={tagText}
+          onChange={e => setTagText(e.target.value)}
+        />
+        <Button size="sm" colorPalette="blue" onClick={save} loading={saving}>
+          <Save size={14} /> Save
+        </Button>
+      </Flex>
+    </Surface>
+  );
+};
@@ -190,6 +190,8 @@ const LeafRow: React.FC<{
       case 'bollinger_bandwidth_below':
       case 'volume_above':
       case 'drop_from_high_pct':
+      case 'trailing_stop_pct':
+      case 'volume_ratio_above':
         return (
           <Input
             size="sm"
@@ -221,6 +223,90 @@ const LeafRow: React.FC<{
             }
           />
         );
+      case 'price_near_sma':
+        return (
+          <HStack gap={1}>
+            <NativeSelect.Root size="sm" w="100px">
+              <NativeSelect.Field
+                value={String((condition as any).period)}
+                bg="bg.surface"
+                color="fg.default"
+                borderColor="border.subtle"
+                onChange={e =>
+                  onChange({ ...(condition as any), period: parseInt(e.target.value, 10) })
+                }
+              >
+                <option value="20">SMA 20</option>
+                <option value="50">SMA 50</option>
+              </NativeSelect.Field>
+            </NativeSelect.Root>
+            <Input
+              size="sm"
+              type="number"
+              w="90px"
+              bg="bg.surface"
+              borderColor="border.subtle"
+              color="fg.default"
+              placeholder="%"
+              value={(condition as any).within_pct}
+              onChange={e =>
+                onChange({ ...(condition as any), within_pct: parseFloat(e.target.value) || 0 })
+              }
+            />
+          </HStack>
+        );
+      case 'earnings_within_days':
+      case 'ex_dividend_within_days':
+        return (
+          <Input
+            size="sm"
+            type="number"
+            w="120px"
+            bg="bg.surface"
+            borderColor="border.subtle"
+            color="fg.default"
+            placeholder="days"
+            min={0}
+            value={(condition as any).days}
+            onChange={e =>
+              onChange({ ...(condition as any), days: Math.max(0, parseInt(e.target.value, 10) || 0) })
+            }
+          />
+        );
+      case 'external_signal':
+        return (
+          <HStack gap={1}>
+            <Input
+              size="sm"
+              w="110px"
+              bg="bg.surface"
+              borderColor="border.subtle"
+              color="fg.default"
+              placeholder="any signal"
+              value={(condition as any).signal ?? ''}
+              onChange={e =>
+                onChange({ ...(condition as any), signal: e.target.value.trim() || undefined })
+              }
+            />
+            <Input
+              size="sm"
+              type="number"
+              w="90px"
+              bg="bg.surface"
+              borderColor="border.subtle"
+              color="fg.default"
+              placeholder="min"
+              min={1}
+              value={(condition as any).within_minutes}
+              onChange={e =>
+                onChange({
+                  ...(condition as any),
+                  within_minutes: Math.max(1, parseInt(e.target.value, 10) || 1),
+                })
+              }
+            />
+          </HStack>
+        );
       case 'sector_equals':
         return (
           <Input
@@ -295,5 +381,11 @@ function describeCondition(c: Condition): string {
     case 'volume_above': return `Vol>${c.value}`;
     case 'sector_equals': return `Sector=${c.sector}`;
     case 'drop_from_high_pct': return `Down≥${c.value}% from 52w-high`;
+    case 'earnings_within_days': return `Earnings≤${c.days}d`;
+    case 'ex_dividend_within_days': return `Ex-div≤${c.days}d`;
+    case 'trailing_stop_pct': return `Down≥${c.value}% from peak`;
+    case 'price_near_sma': return `±${c.within_pct}% of SMA${c.period}`;
+    case 'volume_ratio_above': return `Vol≥${c.value}×avg`;
+    case 'external_signal': return `${c.signal ?? 'Any'} signal ≤${c.within_minutes}m`;
   }
 }
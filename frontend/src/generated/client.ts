@@ -0,0 +1,2943 @@
+// Generated by scripts/generate-client.js from openapi.json. Do not edit;
+// run `npm run generate:client` instead.
+/* eslint-disable */
+
+import axios, { AxiosInstance, AxiosRequestConfig, Method } from 'axios';
+
+/** MongoDB id as extended JSON. Path and query parameters take the bare hex string. */
+export interface ObjectId {
+  '$oid': string;
+}
+
+export interface FieldError {
+  field: string;
+  code: string;
+  message: string;
+}
+
+export interface MaintenanceStatus {
+  read_only: boolean;
+  reason?: string;
+  since?: string;
+}
+
+export interface MaintenanceInput {
+  read_only: boolean;
+  reason?: string;
+}
+
+/** `errors` lists each invalid field of a request body rejected with 422. `maintenance` is set on the 503 returned for mutations in read-only mode. */
+export interface ErrorResponse {
+  success: false;
+  error: string;
+  errors?: FieldError[];
+  maintenance?: MaintenanceStatus;
+}
+
+export type EngineMode = 'normal' | 'degraded';
+
+export type SymbolCycleStatus = 'pending' | 'done' | 'failed' | 'skipped' | 'dead_lettered';
+
+export interface MACDIndicator {
+  macd_line: number;
+  signal_line: number;
+  histogram: number;
+}
+
+export interface BollingerBands {
+  upper_band: number;
+  lower_band: number;
+  middle_band: number;
+  bandwidth: number;
+}
+
+export interface StochasticOscillator {
+  k_line: number;
+  d_line: number;
+}
+
+export interface OnBalanceVolume {
+  /** Running OBV at the latest bar, counted from the first fetched bar. */
+  obv: number;
+  /** Least-squares slope of OBV over the last 20 bars, in shares per bar. */
+  slope: number;
+}
+
+export interface SuperTrend {
+  /** The active band: trailing stop in an uptrend, ceiling in a downtrend. */
+  value: number;
+  uptrend: boolean;
+}
+
+/** Latest SMA 50 / SMA 200 crossing: golden (50 above 200) or death (50 below 200). */
+export type CrossSignal = 'golden_cross' | 'death_cross' | 'none';
+
+/** Nearest swing-pivot levels around the price; either side is null when no recent pivot lies on it. */
+export interface SupportResistance {
+  /** Highest pivot level below the price. */
+  support?: number | null;
+  /** Lowest pivot level above the price. */
+  resistance?: number | null;
+}
+
+/** RSI, SMA and MACD periods an analysis was computed with (`INDICATOR_*` settings). */
+export interface IndicatorConfig {
+  rsi_period: number;
+  /** Period of `sma_20`. */
+  sma_short: number;
+  /** Period of `sma_50`. */
+  sma_long: number;
+  macd_fast: number;
+  macd_slow: number;
+  macd_signal: number;
+}
+
+export interface EarningsData {
+  earnings_date?: string;
+  eps_estimate?: number;
+  revenue_estimate?: number;
+}
+
+export interface NasdaqTechnicals {
+  exchange?: string;
+  sector?: string;
+  industry?: string;
+  one_year_target?: number;
+  todays_high?: number;
+  todays_low?: number;
+  share_volume?: number;
+  average_volume?: number;
+  previous_close?: number;
+  fifty_two_week_high?: number;
+  fifty_two_week_low?: number;
+  pe_ratio?: number;
+  forward_pe?: number;
+  eps?: number;
+  annualized_dividend?: number;
+  ex_dividend_date?: string;
+  dividend_pay_date?: string;
+  current_yield?: number;
+  last_sale_price?: number;
+  net_change?: number;
+  percentage_change?: number;
+  company_name?: string;
+  /** NASDAQ asset class, e.g. `STOCKS` or `ETF`. */
+  asset_class?: string;
+}
+
+export interface NasdaqNewsItem {
+  title: string;
+  url: string;
+  publisher?: string;
+  created?: string;
+  ago?: string;
+}
+
+export interface SectorRelative {
+  etf: string;
+  relative_1d_pct: number | null;
+  relative_5d_pct: number | null;
+  relative_20d_pct: number | null;
+}
+
+export interface PerformanceReturns {
+  return_1w_pct: number | null;
+  return_1m_pct: number | null;
+  return_3m_pct: number | null;
+  return_ytd_pct: number | null;
+}
+
+export interface CrossSectionStats {
+  symbol: string;
+  beta: number | null;
+  correlation: number | null;
+  rs_rank: number | null;
+  benchmark: string;
+  as_of: string;
+  computed_at: string;
+}
+
+export interface MonthlySeasonality {
+  /** 1 = January */
+  month: number;
+  avg_return_pct: number;
+  /** Share of years the month closed higher, 0-100 */
+  positive_pct: number;
+  years: number;
+}
+
+export interface Seasonality {
+  symbol: string;
+  months: MonthlySeasonality[];
+  from: string;
+  to: string;
+  computed_at: string;
+}
+
+export interface AIAnalysisResponse {
+  symbol: string;
+  analysis: string;
+  model_used: string;
+  generated_at: string;
+}
+
+export interface PercentileRanks {
+  rsi?: number;
+  price_change_percent?: number;
+  volume_ratio?: number;
+  pe_ratio?: number;
+  return_1m_pct?: number;
+  return_3m_pct?: number;
+  /** RS rating: rank of the 1-month plus the 3-month return. */
+  rs_rank?: number;
+}
+
+/** Signal or note pushed to `/api/ingest/signal`. */
+export interface ExternalSignal {
+  _id?: ObjectId;
+  symbol: string;
+  source: string;
+  signal: string;
+  strategy?: string;
+  message?: string;
+  price?: number;
+  received_at: string;
+}
+
+export interface StockAnalysis {
+  symbol: string;
+  price: number;
+  price_change?: number;
+  price_change_percent?: number;
+  rsi?: number;
+  sma_20?: number;
+  sma_50?: number;
+  /** 200-day SMA, whatever the configured periods. */
+  sma_200?: number;
+  macd?: MACDIndicator;
+  volume?: number;
+  market_cap?: number;
+  /** Present only with `?humanize=true`. */
+  volume_display?: string;
+  /** Present only with `?humanize=true`. */
+  market_cap_display?: string;
+  sector?: string;
+  is_oversold: boolean;
+  is_overbought: boolean;
+  /** Stochastic %K below 20. */
+  stoch_oversold?: boolean;
+  /** Stochastic %K above 80. */
+  stoch_overbought?: boolean;
+  analyzed_at: string;
+  bollinger?: BollingerBands;
+  stochastic?: StochasticOscillator;
+  /** Williams %R(14), 0 to -100; below -80 is oversold, above -20 overbought. */
+  williams_r?: number;
+  /** Commodity Channel Index(20); beyond ±100 is stretched. */
+  cci?: number;
+  /** Money Flow Index(14), 0-100; below 20 is oversold, above 80 overbought. */
+  mfi?: number;
+  /** Volume-weighted average price over the fetched window. */
+  vwap?: number;
+  obv?: OnBalanceVolume;
+  cross_signal?: CrossSignal;
+  /** Session of the cross in `cross_signal`. */
+  cross_date?: string;
+  supertrend?: SuperTrend;
+  /** Session the current SuperTrend direction began on, when within the fetched window. */
+  supertrend_flip_date?: string;
+  support_resistance?: SupportResistance;
+  indicator_periods?: IndicatorConfig;
+  earnings?: EarningsData;
+  technicals?: NasdaqTechnicals;
+  news?: NasdaqNewsItem[];
+  sector_relative?: SectorRelative;
+  performance?: PerformanceReturns;
+  /** Annualised Sharpe ratio of daily returns over the fetched window, net of `RISK_FREE_RATE_PCT`. */
+  sharpe_ratio?: number;
+  /** Annualised Sortino ratio: like `sharpe_ratio`, over the downside deviation. */
+  sortino_ratio?: number;
+  cross_section?: CrossSectionStats;
+  percentiles?: PercentileRanks;
+  external_signals?: ExternalSignal[];
+  warnings?: string[];
+  indexes?: string[];
+  notes?: string;
+  tags?: string[];
+  asset_type?: AssetType;
+}
+
+/** What kind of security a symbol is. Only equities feed screens and market summary leaders. */
+export type AssetType = 'equity' | 'etf' | 'warrant' | 'right' | 'unit' | 'spac';
+
+export interface StockFilter {
+  min_price?: number;
+  max_price?: number;
+  min_volume?: number;
+  min_market_cap?: number;
+  max_market_cap?: number;
+  min_rsi?: number;
+  max_rsi?: number;
+  sectors?: string[];
+  only_oversold?: boolean;
+  only_overbought?: boolean;
+  /** Only rows with stochastic %K below 20. */
+  only_stoch_oversold?: boolean;
+  /** Only rows with stochastic %K above 80. */
+  only_stoch_overbought?: boolean;
+  cross_signal?: CrossSignal;
+  /** `true` for rows in a SuperTrend uptrend, `false` for downtrends. */
+  supertrend_uptrend?: boolean;
+  symbol_search?: string;
+  min_stochastic_k?: number;
+  max_stochastic_k?: number;
+  min_williams_r?: number;
+  max_williams_r?: number;
+  min_cci?: number;
+  max_cci?: number;
+  min_mfi?: number;
+  max_mfi?: number;
+  min_bandwidth?: number;
+  max_bandwidth?: number;
+  max_abs_price_change_percent?: number;
+  primary_class_only?: boolean;
+  index?: string;
+  theme?: string;
+  /** Symbols carrying at least one of these tags. */
+  tags?: string[];
+  /** Comma-separated asset types (equity, etf, warrant, right, unit, spac), or `all`. Equities only when unset. */
+  asset_type?: string;
+  return_period?: '1w' | '1m' | '3m' | 'ytd';
+  min_return_pct?: number;
+  max_return_pct?: number;
+  /** Required with `min_percentile` / `max_percentile`. */
+  percentile_metric?: 'rsi' | 'price_change_percent' | 'volume_ratio' | 'pe_ratio' | 'return_1m_pct' | 'return_3m_pct' | 'rs_rank';
+  min_percentile?: number;
+  max_percentile?: number;
+  /** Minimum RS rating (`percentiles.rs_rank`). */
+  min_rs_rank?: number;
+  /** Screen each symbol's latest `analysis_history` snapshot at or before this time instead of the live analyses. */
+  as_of?: string;
+  /** `market_cap` (default), `price_change_percent`, `rsi`, `price`, `analyzed_at`, `volume`, `return_1w_pct`, `return_1m_pct`, `return_3m_pct`, `return_ytd_pct`, `sharpe_ratio` or `sortino_ratio`. */
+  sort_by?: string;
+  sort_order?: 'asc' | 'desc';
+  page?: number;
+  page_size?: number;
+}
+
+export interface Pagination {
+  page: number;
+  page_size: number;
+  total: number;
+  total_pages: number;
+  /** Pass back as `cursor` for the next page; null on the last page. */
+  next_cursor?: string | null;
+}
+
+export interface HistoricalPrice {
+  date: string;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume: number;
+}
+
+export interface DividendEvent {
+  date: string;
+  amount: number;
+}
+
+export interface CompanyProfile {
+  short_name?: string;
+  long_name?: string;
+  exchange?: string;
+  exchange_name?: string;
+  quote_type?: string;
+  currency?: string;
+  long_business_summary?: string;
+  industry?: string;
+  sector?: string;
+  website?: string;
+  full_time_employees?: number;
+  city?: string;
+  state?: string;
+  country?: string;
+  phone?: string;
+  current_price?: number;
+  target_high_price?: number;
+  target_low_price?: number;
+  target_mean_price?: number;
+  recommendation_key?: string;
+  number_of_analyst_opinions?: number;
+  total_revenue?: number;
+  revenue_per_share?: number;
+  profit_margins?: number;
+  gross_margins?: number;
+  operating_margins?: number;
+  return_on_equity?: number;
+  free_cash_flow?: number;
+  revenue_growth?: number;
+  earnings_growth?: number;
+  market_cap?: number;
+  enterprise_value?: number;
+  beta?: number;
+  trailing_pe?: number;
+  forward_pe?: number;
+  peg_ratio?: number;
+  price_to_book?: number;
+  book_value?: number;
+  trailing_eps?: number;
+  forward_eps?: number;
+  dividend_rate?: number;
+  dividend_yield?: number;
+  payout_ratio?: number;
+  average_volume?: number;
+  average_volume_10_day?: number;
+  fifty_two_week_high?: number;
+  fifty_two_week_low?: number;
+  fifty_day_average?: number;
+  two_hundred_day_average?: number;
+  shares_outstanding?: number;
+  float_shares?: number;
+  held_percent_insiders?: number;
+  held_percent_institutions?: number;
+  net_income_to_common?: number;
+}
+
+export interface InsiderTrade {
+  insider_name: string;
+  relation?: string;
+  transaction_type: string;
+  date?: string;
+  shares_traded?: number;
+  price?: number;
+  shares_held?: number;
+}
+
+export interface MarketSummary {
+  total_stocks: number;
+  top_gainers: StockAnalysis[];
+  top_losers: StockAnalysis[];
+  most_oversold: StockAnalysis[];
+  most_overbought: StockAnalysis[];
+  mega_cap_highlights: StockAnalysis[];
+  top_weekly_gainers: StockAnalysis[];
+  top_monthly_gainers: StockAnalysis[];
+  top_ytd_gainers: StockAnalysis[];
+  generated_at: string;
+}
+
+export interface LiveQuote {
+  symbol: string;
+  price: number;
+  day_volume?: number | null;
+  quoted_at: string;
+}
+
+export interface AggregatedNewsItem {
+  symbol: string;
+  sector?: string;
+  title: string;
+  url: string;
+  publisher?: string;
+  created?: string;
+  ago?: string;
+}
+
+export interface SectorPerformance {
+  sector: string;
+  stock_count: number;
+  avg_change_percent: number;
+  avg_rsi: number;
+  /** Sum of the sector's market caps, in dollars */
+  total_market_cap: number;
+  top_gainer?: SectorMover;
+  top_loser?: SectorMover;
+  top_performers: StockAnalysis[];
+  bottom_performers: StockAnalysis[];
+}
+
+export interface SectorMover {
+  symbol: string;
+  price_change_percent?: number | null;
+}
+
+export interface AnalysisHistoryPoint {
+  analyzed_at: string;
+  price: number;
+  price_change_percent?: number | null;
+  rsi?: number | null;
+  sma_20?: number | null;
+  sma_50?: number | null;
+  macd?: MACDIndicator | null;
+  volume?: number | null;
+}
+
+/** A listed symbol matching a /api/search query. */
+export interface SymbolMatch {
+  symbol: string;
+  /** Company name as the NASDAQ screener lists it. */
+  name: string;
+  asset_type: AssetType;
+  /** Last sale at the most recent screener fetch. */
+  price: number;
+  /** Dollars. */
+  market_cap?: number | null;
+}
+
+export interface SectorEtfSnapshot {
+  etf: string;
+  price: number;
+  return_1d_pct: number | null;
+  return_5d_pct: number | null;
+  return_20d_pct: number | null;
+  updated_at: string;
+}
+
+export interface HorizonPerformance {
+  evaluated: number;
+  avg_return_pct: number | null;
+  win_rate_pct: number | null;
+}
+
+export interface StrategyPerformance {
+  strategy: 'rsi_oversold' | 'rsi_overbought' | 'stochastic_oversold' | 'stochastic_overbought' | 'bollinger_lower_touch' | 'bollinger_upper_touch' | 'macd_trend_up';
+  signals: number;
+  return_5d: HorizonPerformance;
+  return_20d: HorizonPerformance;
+}
+
+export interface Week52Event {
+  symbol: string;
+  kind: 'high' | 'low';
+  date: string;
+  price: number;
+  close: number;
+  previous_extreme: number;
+  recorded_at: string;
+}
+
+export interface Week52Breadth {
+  date: string;
+  new_highs: number;
+  new_lows: number;
+}
+
+export interface EarningsCalendarRow {
+  symbol: string;
+  sector: string | null;
+  market_cap: number | null;
+  price: number;
+  earnings: EarningsData;
+}
+
+export interface Drawdown {
+  peak_date: string;
+  trough_date: string;
+  recovery_date: string | null;
+  depth_pct: number;
+}
+
+export interface TotalReturn {
+  dividends_reinvested: number;
+  shares: number;
+  current_value: number;
+  total_return_pct: number;
+  cagr_pct: number | null;
+}
+
+export interface WhatIfResult {
+  symbol: string;
+  amount: number;
+  entry_date: string;
+  entry_price: number;
+  shares: number;
+  as_of: string;
+  current_price: number;
+  current_value: number;
+  total_return_pct: number;
+  cagr_pct: number | null;
+  max_drawdown_pct: number;
+  drawdowns: Drawdown[];
+  with_dividends: TotalReturn;
+}
+
+export interface SymbolProgress {
+  symbol: string;
+  status: SymbolCycleStatus;
+  error?: string;
+  cycle_start: string;
+  updated_at: string;
+}
+
+export interface AnalysisRun {
+  started_at: string;
+  completed_at: string;
+  duration_secs: number;
+  mode: EngineMode;
+  universe: number;
+  attempted: number;
+  skipped: number;
+  processed: number;
+  saved: number;
+  /** Symbol-level failures (yahoo, analysis and database) */
+  errors: number;
+  /** Errors keyed by source: yahoo, nasdaq, analysis, database or an optional stage's name */
+  errors_by_source: Record<string, unknown>;
+  outcome: RunOutcome;
+  /** Why the cycle failed or ended early */
+  error?: string | null;
+}
+
+export type RunOutcome = 'completed' | 'ended_early' | 'failed';
+
+export interface RunSummary {
+  runs: number;
+  completed: number;
+  ended_early: number;
+  failed: number;
+  /** Share of runs that completed, 0 to 1 */
+  success_rate?: number | null;
+  avg_duration_secs?: number | null;
+  /** Symbol-level errors per symbol processed */
+  error_rate?: number | null;
+  errors_by_source: Record<string, unknown>;
+}
+
+export interface SymbolAlias {
+  old_symbol: string;
+  new_symbol: string;
+  company_name: string;
+  detected_at: string;
+}
+
+export interface FailureRecord {
+  error: string;
+  failed_at: string;
+}
+
+export interface DeadLetter {
+  symbol: string;
+  consecutive_failures: number;
+  errors: FailureRecord[];
+  dead_lettered_at?: string | null;
+}
+
+export interface BudgetStats {
+  per_minute: number;
+  available: number;
+  interactive_granted: number;
+  background_granted: number;
+  background_wait_ms: number;
+}
+
+export interface QueryStat {
+  command: string;
+  collection: string;
+  shape: string;
+  count: number;
+  failures: number;
+  slow: number;
+  avg_ms: number;
+  max_ms: number;
+  total_ms: number;
+}
+
+export interface CollectionStats {
+  name: string;
+  count: number;
+  /** Uncompressed size of the documents */
+  size_bytes: number;
+  /** Space the documents take on disk */
+  storage_bytes: number;
+  index_bytes: number;
+  /** Days kept, for collections pruned by HISTORY_RETENTION_DAYS */
+  retention_days?: number | null;
+}
+
+export interface CorruptDocument {
+  /** Collection the document was read from */
+  source: string;
+  document_id: string;
+  symbol?: string | null;
+  /** Why the strict read failed */
+  error: string;
+  /** Whether repairing known legacy shapes in memory recovered it */
+  recovered: boolean;
+  seen_at: string;
+}
+
+export interface RepairReport {
+  scanned: number;
+  /** Documents fixed in place */
+  repaired: number;
+  fields_fixed: number;
+  news_items_removed: number;
+  units_migrated: number;
+  /** Unreadable documents moved to quarantine */
+  quarantined: number;
+  /** Older duplicates of a symbol moved to quarantine */
+  duplicates: number;
+}
+
+export interface ImportSummary {
+  /** Documents inserted or replaced per collection */
+  imported: Record<string, unknown>;
+  /** Lines for collections outside the seed set, ignored */
+  skipped: number;
+}
+
+export interface BackupCollection {
+  collection: string;
+  documents: number;
+  size_bytes: number;
+}
+
+export interface BackupManifest {
+  name: string;
+  database: string;
+  created_at: string;
+  collections: BackupCollection[];
+}
+
+export interface ArchiveDay {
+  date: string;
+  files: number;
+  size_bytes: number;
+}
+
+export interface ArchivedResponse {
+  source: 'yahoo' | 'nasdaq';
+  /** chart, quote, profile, earnings, technicals, news, insider or screener */
+  kind: string;
+  fetched_at: string;
+  url: string;
+  /** Response body as JSON, or a string if it was not JSON */
+  body: unknown;
+}
+
+export interface ReplayRequest {
+  from: string;
+  to: string;
+  /** Empty or omitted replays every archived symbol */
+  symbols?: string[];
+}
+
+export interface ReplayReport {
+  started_at: string;
+  finished_at: string;
+  from: string;
+  to: string;
+  days: number;
+  charts: number;
+  merged: number;
+  inserted: number;
+  skipped: number;
+  failures: number;
+}
+
+export interface WeekendReport {
+  started_at: string;
+  finished_at: string;
+  backfilled: number;
+  backfill_failures: number;
+  seasonality: number;
+  ai_summaries: number;
+  ai_failures: number;
+  orphans_removed: number;
+  compacted: string[];
+}
+
+export interface CacheStats {
+  stock_hits: number;
+  stock_misses: number;
+  db_reads: number;
+  db_errors: number;
+  hit_rate: number;
+  stock_entries: number;
+  stock_weighted_bytes: number;
+  pinned_entries: number;
+  read_through: boolean;
+  response_hits: number;
+  response_misses: number;
+  response_bypasses: number;
+  response_entries: number;
+  response_weighted_bytes: number;
+}
+
+export interface CachePin {
+  symbol: string;
+  ttl_secs?: number | null;
+  pinned_at: string;
+}
+
+export interface IndexInfo {
+  id: string;
+  name: string;
+  description: string;
+  symbol_count: number;
+}
+
+export interface StockHeatmapItem {
+  symbol: string;
+  name?: string;
+  price: number;
+  change_percent: number;
+  contribution: number;
+  market_cap?: number;
+  sector?: string;
+}
+
+export interface IndexHeatmapData {
+  index_id: string;
+  index_name: string;
+  period: string;
+  index_performance: number;
+  generated_at: string;
+  stocks: StockHeatmapItem[];
+}
+
+export interface HeatmapStats {
+  total_constituents: number;
+  stocks_with_data: number;
+  total_market_cap: number;
+  period: string;
+  fallback_symbols: string[];
+}
+
+export interface IndexPerformance {
+  index_id: string;
+  date: string;
+  equal_weight_return_pct: number;
+  cap_weight_return_pct: number;
+  divergence_pct: number;
+  constituents: number;
+  advancers: number;
+  decliners: number;
+  updated_at: string;
+}
+
+export interface CumulativeReturns {
+  equal_weight_return_pct: number;
+  cap_weight_return_pct: number;
+  divergence_pct: number;
+}
+
+export interface IndexContribution {
+  symbol: string;
+  change_percent: number;
+  weight_pct: number;
+  contribution_pct: number;
+  contribution_points: number | null;
+}
+
+export interface IndexContributors {
+  index_id: string;
+  date: string;
+  index_return_pct: number;
+  previous_close: number | null;
+  index_points: number | null;
+  top: IndexContribution[];
+  bottom: IndexContribution[];
+  updated_at: string;
+}
+
+export interface ScreenRow {
+  symbol: string;
+  price: number;
+  price_change_percent: number | null;
+  market_cap: number | null;
+  sector: string | null;
+  rsi: number | null;
+  value: number;
+}
+
+export interface ScreenResult {
+  name: string;
+  description: string;
+  metric: string;
+  total: number;
+  rows: ScreenRow[];
+  computed_at: string;
+}
+
+export interface ScreenSummary {
+  name: string;
+  description: string;
+  metric: string;
+  total: number | null;
+  computed_at: string | null;
+}
+
+export interface Theme {
+  id: string;
+  name: string;
+  description: string;
+  symbols: string[];
+  updated_at: string;
+}
+
+export interface ThemeInput {
+  name: string;
+  description?: string;
+  symbols: string[];
+}
+
+export interface SymbolNote {
+  symbol: string;
+  notes: string;
+  tags: string[];
+  updated_at: string;
+}
+
+export interface NoteInput {
+  notes?: string;
+  /** Lowercased; spaces become dashes. */
+  tags?: string[];
+}
+
+export interface TagCount {
+  tag: string;
+  symbols: number;
+}
+
+export interface ThemeMembershipChange {
+  add?: string[];
+  remove?: string[];
+}
+
+export interface NotificationChannel {
+  _id?: ObjectId;
+  name: string;
+  kind: 'discord';
+  /** Masked in responses. */
+  webhook_url: string;
+  username?: string;
+  avatar_url?: string;
+  enabled: boolean;
+  /** Also receives the end-of-day watchlist recap. */
+  daily_recap?: boolean;
+  created_at: string;
+}
+
+export interface CreateChannelInput {
+  name: string;
+  kind: 'discord';
+  webhook_url: string;
+  username?: string;
+  avatar_url?: string;
+  enabled?: boolean;
+  daily_recap?: boolean;
+}
+
+export interface ChannelConfig {
+  kind: 'discord';
+  webhook_url: string;
+  username?: string;
+  avatar_url?: string;
+}
+
+export interface UpdateChannelInput {
+  name?: string;
+  config?: ChannelConfig;
+  enabled?: boolean;
+  daily_recap?: boolean;
+}
+
+export interface Watchlist {
+  _id?: ObjectId;
+  name: string;
+  symbols: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CreateWatchlistInput {
+  name: string;
+  symbols?: string[];
+}
+
+export interface UpdateWatchlistInput {
+  name?: string;
+  symbols?: string[];
+}
+
+export interface AddSymbolInput {
+  symbol: string;
+}
+
+export interface RecapEntry {
+  symbol: string;
+  close: number;
+  change: number | null;
+  change_percent: number | null;
+  signals: Array<'rsi_oversold' | 'rsi_overbought' | 'stochastic_oversold' | 'stochastic_overbought' | 'bollinger_lower_touch' | 'bollinger_upper_touch' | 'macd_trend_up'>;
+  /** Alert rules that notified for this symbol during the session. */
+  alerts: string[];
+  top_news: NasdaqNewsItem | null;
+}
+
+export interface WatchlistRecap {
+  watchlist_id: ObjectId | null;
+  watchlist: string;
+  date: string;
+  /** Biggest movers first. */
+  entries: RecapEntry[];
+  /** Watchlist symbols with no analysis yet. */
+  missing: string[];
+  generated_at: string;
+}
+
+/** Leaf predicate, tagged by `type`. */
+export type Condition =
+  | {
+    type: 'rsi_below';
+    value: number;
+  }
+  | {
+    type: 'rsi_above';
+    value: number;
+  }
+  | {
+    type: 'price_below';
+    value: number;
+  }
+  | {
+    type: 'price_above';
+    value: number;
+  }
+  | {
+    type: 'price_change_pct_below';
+    value: number;
+  }
+  | {
+    type: 'price_change_pct_above';
+    value: number;
+  }
+  | {
+    type: 'near_52_week_low';
+    within_pct: number;
+  }
+  | {
+    type: 'near_52_week_high';
+    within_pct: number;
+  }
+  | {
+    type: 'macd_bullish_cross';
+  }
+  | {
+    type: 'macd_bearish_cross';
+  }
+  | {
+    type: 'stochastic_k_below';
+    value: number;
+  }
+  | {
+    type: 'stochastic_k_above';
+    value: number;
+  }
+  | {
+    type: 'bollinger_bandwidth_below';
+    value: number;
+  }
+  | {
+    type: 'is_oversold';
+  }
+  | {
+    type: 'is_overbought';
+  }
+  | {
+    type: 'volume_above';
+    value: number;
+  }
+  | {
+    type: 'sector_equals';
+    sector: string;
+  }
+  | {
+    type: 'drop_from_high_pct';
+    value: number;
+  }
+  | {
+    type: 'earnings_within_days';
+    days: number;
+  }
+  | {
+    type: 'ex_dividend_within_days';
+    days: number;
+  }
+  | {
+    type: 'trailing_stop_pct';
+    value: number;
+  }
+  | {
+    type: 'price_near_sma';
+    period: number;
+    within_pct: number;
+  }
+  | {
+    type: 'volume_ratio_above';
+    value: number;
+  }
+  | {
+    type: 'external_signal';
+    signal?: string;
+    within_minutes: number;
+  };
+
+/** Boolean tree of conditions, tagged by `op`. */
+export type ConditionGroup =
+  | {
+    op: 'and';
+    children: ConditionGroup[];
+  }
+  | {
+    op: 'or';
+    children: ConditionGroup[];
+  }
+  | {
+    op: 'not';
+    child: ConditionGroup;
+  }
+  | {
+    op: 'leaf';
+    condition: Condition;
+  };
+
+export type AlertScope =
+  | {
+    type: 'all_watched';
+  }
+  | {
+    type: 'watchlist';
+    watchlist_id: ObjectId;
+  }
+  | {
+    type: 'symbols';
+    symbols: string[];
+  }
+  | {
+    type: 'all_analyzed';
+  };
+
+export interface QuietHours {
+  start_hour: number;
+  end_hour: number;
+  tz?: string;
+}
+
+export interface AlertRule {
+  _id?: ObjectId;
+  name: string;
+  enabled: boolean;
+  scope: AlertScope;
+  conditions: ConditionGroup;
+  cooldown_minutes: number;
+  quiet_hours?: QuietHours | null;
+  channel_ids: ObjectId[];
+  message_template?: string | null;
+  require_consecutive: number;
+  snoozed_until?: string | null;
+  max_triggers?: number | null;
+  trigger_count: number;
+  muted_symbols?: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CreateAlertRuleInput {
+  name: string;
+  enabled?: boolean;
+  scope: AlertScope;
+  conditions: ConditionGroup;
+  cooldown_minutes?: number;
+  quiet_hours?: QuietHours | null;
+  channel_ids?: ObjectId[];
+  message_template?: string | null;
+  require_consecutive?: number;
+  max_triggers?: number | null;
+  muted_symbols?: string[];
+}
+
+/** Only the fields present change; `null` clears a nullable field. */
+export interface UpdateAlertRuleInput {
+  name?: string;
+  enabled?: boolean;
+  scope?: AlertScope;
+  conditions?: ConditionGroup;
+  cooldown_minutes?: number;
+  quiet_hours?: QuietHours | null;
+  channel_ids?: ObjectId[];
+  message_template?: string | null;
+  require_consecutive?: number;
+  max_triggers?: number | null;
+  muted_symbols?: string[];
+}
+
+/** Exactly one of `until` or `minutes`; `until` wins if both are set. */
+export interface SnoozeRuleInput {
+  until?: string | null;
+  minutes?: number | null;
+}
+
+export interface AlertAuditEvent {
+  _id?: ObjectId;
+  rule_id: ObjectId;
+  action: 'created' | 'updated' | 'enabled' | 'disabled' | 'snoozed' | 'unsnoozed' | 'muted' | 'unmuted' | 'triggered' | 'exhausted' | 'reset' | 'deleted';
+  symbol?: string;
+  detail?: string;
+  at: string;
+}
+
+export interface DeliveryResult {
+  channel_id: ObjectId;
+  channel_name: string;
+  ok: boolean;
+  error?: string;
+  sent_at: string;
+}
+
+export interface NotificationHistory {
+  _id?: ObjectId;
+  rule_id: ObjectId;
+  rule_name: string;
+  symbol: string;
+  matched_conditions: string[];
+  message: string;
+  channel_ids: ObjectId[];
+  delivered: DeliveryResult[];
+  snapshot: StockAnalysis;
+  created_at: string;
+  read: boolean;
+}
+
+export type LotMethod = 'fifo' | 'lifo' | 'specific';
+
+/** An open lot joined with its latest cached price. */
+export interface PositionView {
+  _id?: ObjectId;
+  symbol: string;
+  quantity: number;
+  cost_basis_per_share: number;
+  opened_at: string;
+  notes?: string | null;
+  created_at: string;
+  updated_at: string;
+  current_price: number | null;
+  market_value: number | null;
+  cost_basis_total: number;
+  unrealized_pnl: number | null;
+  unrealized_pnl_pct: number | null;
+}
+
+export interface CreatePositionInput {
+  symbol: string;
+  quantity: number;
+  cost_basis_per_share: number;
+  opened_at?: string | null;
+  notes?: string | null;
+}
+
+export interface UpdatePositionInput {
+  quantity?: number;
+  cost_basis_per_share?: number;
+  opened_at?: string;
+  notes?: string;
+}
+
+export interface SellPositionInput {
+  symbol: string;
+  quantity: number;
+  price: number;
+  method?: LotMethod;
+  lot_ids?: ObjectId[];
+  sold_at?: string | null;
+}
+
+export interface RealizedGain {
+  _id?: ObjectId;
+  symbol: string;
+  position_id: ObjectId;
+  quantity: number;
+  cost_basis_per_share: number;
+  proceeds_per_share: number;
+  realized_pnl: number;
+  opened_at: string;
+  sold_at: string;
+  holding_days: number;
+  term: 'short_term' | 'long_term';
+  method: LotMethod;
+}
+
+export interface RealizedSummary {
+  sales: number;
+  proceeds: number;
+  cost_basis: number;
+  realized_pnl: number;
+  short_term_pnl: number;
+  long_term_pnl: number;
+}
+
+export type RebalanceTarget = 'equal_weight' | 'sector_neutral';
+
+export interface RebalanceTrade {
+  symbol: string;
+  side: 'buy' | 'sell';
+  shares: number;
+  price: number;
+  trade_value: number;
+  current_weight_pct: number;
+  target_weight_pct: number;
+  estimated_cost: number;
+}
+
+export interface RebalancePlan {
+  target: RebalanceTarget;
+  total_value: number;
+  trades: RebalanceTrade[];
+  estimated_total_cost: number;
+  turnover_pct: number;
+}
+
+export interface SnapshotHolding {
+  symbol: string;
+  quantity: number;
+  price: number;
+  market_value: number;
+  cost_basis: number;
+}
+
+export interface ValuationSnapshot {
+  _id?: ObjectId;
+  date: string;
+  total_value: number;
+  total_cost_basis: number;
+  holdings: SnapshotHolding[];
+  unpriced: string[];
+  taken_at: string;
+}
+
+export type BrokerFormat = 'ibkr_flex' | 'schwab_csv';
+
+export interface ImportedPosition {
+  symbol: string;
+  quantity: number;
+  cost_basis_per_share: number;
+  opened_at: string | null;
+}
+
+export interface ImportedTrade {
+  symbol: string;
+  side: 'buy' | 'sell';
+  quantity: number;
+  price: number;
+  traded_at: string;
+  /** The broker's execution id (IBKR `tradeID`), when the export has one. */
+  trade_id?: string;
+}
+
+export interface BrokerImport {
+  broker: BrokerFormat;
+  positions: ImportedPosition[];
+  trades: ImportedTrade[];
+  skipped: string[];
+}
+
+export interface SignalInput {
+  /** Alias `ticker`. */
+  symbol: string;
+  /** Alias `action`; `note` when omitted with a message. */
+  signal?: string;
+  source?: string;
+  strategy?: string;
+  /** Aliases `note`, `text`. */
+  message?: string;
+  /** Alias `close`; numeric strings are accepted. */
+  price?: number | string;
+  /** `INGEST_TOKEN`, when not sent as a header. */
+  token?: string;
+}
+
+export interface ServiceInfoResponse {
+  name: string;
+  version: string;
+  status: 'running' | 'read_only';
+  maintenance: MaintenanceStatus;
+}
+
+export interface HealthResponse {
+  status: 'healthy' | 'degraded';
+  database: string;
+  total_analyses: number;
+  last_cycle_started: string | null;
+  last_cycle_completed: string | null;
+  last_successful_cycle: string | null;
+  last_error: string | null;
+  mode: EngineMode;
+  degraded_reason: string | null;
+  maintenance: MaintenanceStatus;
+  yahoo_budget: BudgetStats | null;
+}
+
+export interface ProgressResponse {
+  total_stocks: number;
+  analyzed: number;
+  current_symbol: string | null;
+  cycle_start: string;
+  errors: number;
+  last_cycle_started: string | null;
+  last_cycle_completed: string | null;
+  last_successful_cycle: string | null;
+  last_error: string | null;
+  mode: EngineMode;
+  degraded_reason: string | null;
+  completion_percentage: number;
+}
+
+export interface ProgressSymbolsResponse {
+  success: boolean;
+  cycle_start: string;
+  count: number;
+  symbols: SymbolProgress[];
+}
+
+export interface ProgressSymbolsQuery {
+  status?: SymbolCycleStatus;
+}
+
+export interface AnalysisRunsResponse {
+  success: boolean;
+  count: number;
+  summary: RunSummary;
+  runs: AnalysisRun[];
+  pagination: Pagination;
+}
+
+export interface AnalysisRunsQuery {
+  /** Only runs started at or after this time. */
+  since?: string;
+  page?: number;
+  page_size?: number;
+  /** Opaque `next_cursor` from a previous page; wins over `page`. */
+  cursor?: string;
+}
+
+export interface SymbolAliasesResponse {
+  success: boolean;
+  aliases: SymbolAlias[];
+}
+
+export interface DeadLettersResponse {
+  success: boolean;
+  dead_letters: DeadLetter[];
+}
+
+export interface RequeueAllDeadLettersResponse {
+  success: boolean;
+  requeued: number;
+}
+
+export interface RequeueDeadLetterResponse {
+  success: boolean;
+  requeued: boolean;
+}
+
+export interface DbStatsResponse {
+  success: boolean;
+  slow_query_ms: number;
+  total_queries: number;
+  slow_queries: number;
+  queries: QueryStat[];
+}
+
+export interface ResetDbStatsResponse {
+  success: boolean;
+}
+
+export interface DbCollectionsResponse {
+  success: boolean;
+  history_retention_days: number;
+  total_storage_bytes: number;
+  collections: CollectionStats[];
+}
+
+export interface CorruptDocumentsResponse {
+  success: boolean;
+  count: number;
+  recovered: number;
+  documents: CorruptDocument[];
+}
+
+export interface RunRepairResponse {
+  success: boolean;
+  report: RepairReport;
+}
+
+export interface RunCrossSectionResponse {
+  success: boolean;
+  updated: number;
+}
+
+export interface WeekendStatusResponse {
+  success: boolean;
+  running: boolean;
+  hour_ny: number | null;
+  last_report: WeekendReport | null;
+}
+
+export interface RunWeekendResponse {
+  success: boolean;
+  started: boolean;
+}
+
+export interface BackupsResponse {
+  success: boolean;
+  count: number;
+  retention: number;
+  backups: BackupManifest[];
+}
+
+export interface RunBackupResponse {
+  success: boolean;
+  backup: BackupManifest;
+  pruned: number;
+}
+
+export interface ExportDataQuery {
+  format?: 'gzip' | 'ndjson';
+  /** all, or a comma-separated list of seed collections */
+  collections?: string;
+}
+
+export interface ImportDataResponse {
+  success: boolean;
+  /** Documents inserted or replaced per collection */
+  imported: Record<string, unknown>;
+  skipped: number;
+  /** Analyses older than the stored one, left unchanged */
+  stale: number;
+}
+
+export interface RawArchiveResponse {
+  success: boolean;
+  dir: string;
+  sample_rate: number;
+  retention_days: number;
+  total_files: number;
+  total_bytes: number;
+  days: ArchiveDay[];
+}
+
+export interface ReplayStatusResponse {
+  success: boolean;
+  running: boolean;
+  last_report: ReplayReport | null;
+}
+
+export interface RunReplayResponse {
+  success: boolean;
+  started: boolean;
+}
+
+export interface ArchivedResponsesResponse {
+  success: boolean;
+  symbol: string;
+  date: string;
+  count: number;
+  responses: ArchivedResponse[];
+}
+
+export interface MaintenanceResponse {
+  success: boolean;
+  maintenance: MaintenanceStatus;
+}
+
+export interface SetMaintenanceResponse {
+  success: boolean;
+  maintenance: MaintenanceStatus;
+}
+
+export interface CacheStatsResponse {
+  success: boolean;
+  stats: CacheStats;
+}
+
+export interface CachePinsResponse {
+  success: boolean;
+  pins: CachePin[];
+}
+
+export interface PinStockResponse {
+  success: boolean;
+  pin: CachePin;
+  warmed: boolean;
+}
+
+export interface PinStockBody {
+  ttl_secs?: number | null;
+}
+
+export interface UnpinStockResponse {
+  success: boolean;
+  deleted: boolean;
+}
+
+export interface AiStatusResponse {
+  success: boolean;
+  enabled: boolean;
+  current_model: string | null;
+  available_models_count: number;
+}
+
+export interface AiModelsResponse {
+  success: boolean;
+  models: string[];
+  count: number;
+  description: string;
+}
+
+export interface StocksResponse {
+  success: boolean;
+  count?: number;
+  stocks: StockAnalysis[];
+  pagination: Pagination;
+}
+
+export interface StocksQuery {
+  /** Add `volume_display` and `market_cap_display`. */
+  humanize?: boolean;
+  /** Answer from the analysis history as of this time. */
+  as_of?: string;
+  /** Comma-separated asset types, or `all`. Equities only when unset. */
+  asset_type?: string;
+  page?: number;
+  page_size?: number;
+  /** Opaque `next_cursor` from a previous page; wins over `page`. */
+  cursor?: string;
+}
+
+export interface FilterStocksResponse {
+  success: boolean;
+  count: number;
+  stocks: StockAnalysis[];
+  cached: boolean;
+  pagination: Pagination;
+}
+
+export interface FilterStocksQuery {
+  /** Add `volume_display` and `market_cap_display`. */
+  humanize?: boolean;
+  /** Overrides the body's `page`. */
+  page?: number;
+  /** Overrides the body's `page_size`. */
+  page_size?: number;
+  /** Opaque `next_cursor` from a previous page; wins over `page`. */
+  cursor?: string;
+}
+
+export interface StockResponse {
+  success: boolean;
+  share_classes: string[];
+  stock: StockAnalysis;
+  cached: boolean;
+}
+
+export interface StockQuery {
+  /** Add `volume_display` and `market_cap_display`. */
+  humanize?: boolean;
+}
+
+export interface StockHistoryResponse {
+  success: boolean;
+  history: HistoricalPrice[];
+  symbol?: string;
+  total_return_index?: number[];
+  dividends?: DividendEvent[];
+}
+
+export interface StockHistoryQuery {
+  /** Also return a dividend-reinvested close series and the dividends. */
+  total_return?: boolean;
+  /** Back-adjust the bars for splits and dividends. */
+  adjusted?: boolean;
+  /** `heikin_ashi` returns Heikin-Ashi candles instead of the bars. */
+  candle_type?: 'regular' | 'heikin_ashi';
+}
+
+export interface AiAnalysisResponse {
+  success: boolean;
+  symbol: string;
+  analysis: string;
+  model_used: string;
+  generated_at: string;
+  stock_data: {
+    price: number;
+    rsi: number | null;
+    sma_20: number | null;
+    sma_50: number | null;
+    is_oversold: boolean;
+    is_overbought: boolean;
+  };
+}
+
+export interface StockAiSummaryResponse {
+  success: boolean;
+  symbol: string;
+  summary: AIAnalysisResponse | null;
+}
+
+export interface StockProfileResponse {
+  success: boolean;
+  profile: CompanyProfile;
+}
+
+export interface StockNotesResponse {
+  success: boolean;
+  symbol: string;
+  note: SymbolNote | null;
+}
+
+export interface SaveStockNotesResponse {
+  success: boolean;
+  symbol: string;
+  note: SymbolNote | null;
+}
+
+export interface TagsResponse {
+  success: boolean;
+  count: number;
+  tags: TagCount[];
+}
+
+export interface SearchSymbolsResponse {
+  success: boolean;
+  query: string;
+  count: number;
+  results: SymbolMatch[];
+}
+
+export interface SearchSymbolsQuery {
+  q: string;
+  limit?: number;
+}
+
+export interface InsiderTradesResponse {
+  success: boolean;
+  trades: InsiderTrade[];
+}
+
+export interface StockEarningsResponse {
+  success: boolean;
+  earnings: EarningsData;
+}
+
+export interface StockSeasonalityResponse {
+  success: boolean;
+  symbol: string;
+  seasonality: Seasonality | null;
+}
+
+export interface StockAnalysisHistoryResponse {
+  success: boolean;
+  symbol: string;
+  days: number;
+  count: number;
+  history: AnalysisHistoryPoint[];
+}
+
+export interface StockAnalysisHistoryQuery {
+  days?: number;
+}
+
+export interface MarketSummaryResponse {
+  success: boolean;
+  summary: MarketSummary;
+}
+
+export interface MarketSummaryQuery {
+  min_market_cap?: number;
+  max_price_change_percent?: number;
+  /** Answer from the analysis history as of this time. */
+  as_of?: string;
+}
+
+export interface QuotesResponse {
+  success: boolean;
+  count: number;
+  cached: number;
+  quotes: LiveQuote[];
+  missing: string[];
+}
+
+export interface QuotesQuery {
+  /** Comma-separated. */
+  symbols: string;
+}
+
+export interface NewsResponse {
+  success: boolean;
+  news: AggregatedNewsItem[];
+  pagination: Pagination;
+}
+
+export interface NewsQuery {
+  sector?: string;
+  search?: string;
+  page?: number;
+  page_size?: number;
+  /** Opaque `next_cursor` from a previous page; wins over `page`. */
+  cursor?: string;
+}
+
+export interface SectorsResponse {
+  success: boolean;
+  sectors: SectorPerformance[];
+}
+
+export interface SectorEtfsResponse {
+  success: boolean;
+  mapping: Array<{
+    sector: string;
+    etf: string;
+  }>;
+  etfs: SectorEtfSnapshot[];
+}
+
+export interface SignalPerformanceResponse {
+  success: boolean;
+  since: string | null;
+  total_signals: number;
+  strategies: StrategyPerformance[];
+}
+
+export interface SignalPerformanceQuery {
+  days?: number;
+}
+
+export interface Week52EventsResponse {
+  success: boolean;
+  since: string;
+  total_events: number;
+  breadth: Week52Breadth[];
+  events: Week52Event[];
+  pagination: Pagination;
+}
+
+export interface Week52EventsQuery {
+  kind?: 'high' | 'low';
+  days?: number;
+  /** Older name for `page_size`. */
+  limit?: number;
+  page?: number;
+  page_size?: number;
+  /** Opaque `next_cursor` from a previous page; wins over `page`. */
+  cursor?: string;
+}
+
+export interface IngestSignalResponse {
+  success: boolean;
+  signal: ExternalSignal;
+  active_signals: number;
+  analysis_updated: boolean;
+}
+
+export interface IngestSignalQuery {
+  /** `INGEST_TOKEN`, for senders that can't set headers. */
+  token?: string;
+}
+
+export interface IngestTradingViewResponse {
+  success: boolean;
+  signal: ExternalSignal;
+  active_signals: number;
+  analysis_updated: boolean;
+}
+
+export interface IngestTradingViewQuery {
+  /** `INGEST_TOKEN`; TradingView can't set headers. */
+  token?: string;
+}
+
+export interface ExternalSignalsResponse {
+  success: boolean;
+  since: string;
+  count: number;
+  signals: ExternalSignal[];
+  pagination: Pagination;
+}
+
+export interface ExternalSignalsQuery {
+  symbol?: string;
+  /** Defaults to `INGEST_SIGNAL_TTL_HOURS`. */
+  hours?: number;
+  /** Older name for `page_size`. */
+  limit?: number;
+  page?: number;
+  page_size?: number;
+  /** Opaque `next_cursor` from a previous page; wins over `page`. */
+  cursor?: string;
+}
+
+export interface EarningsCalendarResponse {
+  success: boolean;
+  earnings: EarningsCalendarRow[];
+  count: number;
+  days_ahead: number;
+  failed_symbols: string[];
+}
+
+export interface EarningsCalendarQuery {
+  days_ahead?: number;
+}
+
+export interface CorrelationResponse {
+  success: boolean;
+  requested_symbols: string[];
+  symbols: string[];
+  matrix: number[][];
+  days: number;
+  failed_symbols: Array<{
+    symbol: string;
+    error: string;
+  }>;
+}
+
+export interface CorrelationQuery {
+  /** Comma-separated. */
+  symbols: string;
+  days?: number;
+}
+
+export interface WhatIfResponse {
+  success: boolean;
+  result: WhatIfResult;
+}
+
+export interface WhatIfQuery {
+  symbol: string;
+  date: string;
+  amount?: number;
+}
+
+export interface IndexesResponse {
+  success: boolean;
+  indexes: IndexInfo[];
+}
+
+export interface IndexResponse {
+  success: boolean;
+  index: {
+    id: string;
+    name: string;
+    description: string;
+    symbol_count: number;
+    symbols: string[];
+  };
+}
+
+export interface IndexHeatmapResponse {
+  success: boolean;
+  heatmap: IndexHeatmapData;
+  stats: HeatmapStats;
+}
+
+export interface IndexHeatmapQuery {
+  period?: '1d' | '1w' | '1m' | '6m' | '1y';
+}
+
+export interface IndexPerformanceResponse {
+  success: boolean;
+  index_id: string;
+  days: number;
+  latest: IndexPerformance | null;
+  cumulative: CumulativeReturns;
+  history: IndexPerformance[];
+}
+
+export interface IndexPerformanceQuery {
+  days?: number;
+}
+
+export interface IndexContributorsResponse {
+  success: boolean;
+  contributors: IndexContributors;
+}
+
+export interface IndexContributorsQuery {
+  date?: string;
+  limit?: number;
+}
+
+export interface ScreensResponse {
+  success: boolean;
+  screens: ScreenSummary[];
+}
+
+export interface ScreenResponse {
+  success: boolean;
+  screen: ScreenResult;
+}
+
+export interface ScreenQuery {
+  limit?: number;
+}
+
+export interface ThemesResponse {
+  success: boolean;
+  themes: Theme[];
+}
+
+export interface ThemeResponse {
+  success: boolean;
+  theme: Theme;
+}
+
+export interface ThemeHeatmapResponse {
+  success: boolean;
+  heatmap: IndexHeatmapData;
+  stats: HeatmapStats;
+}
+
+export interface ThemeHeatmapQuery {
+  period?: '1d' | '1w' | '1m' | '6m' | '1y';
+}
+
+export interface ThemePerformanceResponse {
+  success: boolean;
+  theme_id: string;
+  days: number;
+  latest: IndexPerformance | null;
+  cumulative: CumulativeReturns;
+  history: IndexPerformance[];
+}
+
+export interface ThemePerformanceQuery {
+  days?: number;
+}
+
+export interface SaveThemeResponse {
+  success: boolean;
+  theme: Theme;
+}
+
+export interface DeleteThemeResponse {
+  success: boolean;
+  deleted: boolean;
+}
+
+export interface UpdateThemeSymbolsResponse {
+  success: boolean;
+  changed: boolean;
+  theme: Theme;
+}
+
+export interface WatchlistsResponse {
+  success: boolean;
+  watchlists: Watchlist[];
+}
+
+export interface CreateWatchlistResponse {
+  success: boolean;
+  watchlist: Watchlist;
+}
+
+export interface WatchlistResponse {
+  success: boolean;
+  watchlist: Watchlist;
+}
+
+export interface UpdateWatchlistResponse {
+  success: boolean;
+  watchlist: Watchlist;
+}
+
+export interface DeleteWatchlistResponse {
+  success: boolean;
+}
+
+export interface AddWatchlistSymbolResponse {
+  success: boolean;
+  watchlist: Watchlist;
+}
+
+export interface RemoveWatchlistSymbolResponse {
+  success: boolean;
+  watchlist: Watchlist;
+}
+
+export interface WatchlistRecapResponse {
+  success: boolean;
+  recap: WatchlistRecap;
+}
+
+export interface SendWatchlistRecapResponse {
+  success: boolean;
+  recap: WatchlistRecap;
+  delivered: DeliveryResult[];
+}
+
+export interface AlertRulesResponse {
+  success: boolean;
+  rules: AlertRule[];
+}
+
+export interface CreateAlertRuleResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface AlertRuleResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface UpdateAlertRuleResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface DeleteAlertRuleResponse {
+  success: boolean;
+}
+
+export interface ToggleAlertRuleResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface TestAlertRuleResponse {
+  success: boolean;
+  delivered: DeliveryResult[];
+  symbol: string;
+}
+
+export interface TestAlertRuleBody {
+  symbol?: string | null;
+}
+
+export interface SnoozeAlertRuleResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface UnsnoozeAlertRuleResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface MuteAlertSymbolResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface UnmuteAlertSymbolResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface ResetAlertRuleResponse {
+  success: boolean;
+  rule: AlertRule;
+}
+
+export interface AlertRuleAuditResponse {
+  success: boolean;
+  events: AlertAuditEvent[];
+}
+
+export interface AlertRuleAuditQuery {
+  limit?: number;
+}
+
+export interface ChannelsResponse {
+  success: boolean;
+  channels: NotificationChannel[];
+}
+
+export interface CreateChannelResponse {
+  success: boolean;
+  channel: NotificationChannel;
+}
+
+export interface ChannelResponse {
+  success: boolean;
+  channel: NotificationChannel;
+}
+
+export interface UpdateChannelResponse {
+  success: boolean;
+  channel: NotificationChannel;
+}
+
+export interface DeleteChannelResponse {
+  success: boolean;
+}
+
+export interface TestChannelResponse {
+  success: boolean;
+}
+
+export interface NotificationHistoryResponse {
+  success: boolean;
+  history: NotificationHistory[];
+  pagination: Pagination;
+}
+
+export interface NotificationHistoryQuery {
+  page?: number;
+  page_size?: number;
+  rule_id?: string;
+  symbol?: string;
+  /** Opaque `next_cursor` from a previous page; wins over `page`. */
+  cursor?: string;
+}
+
+export interface UnreadCountResponse {
+  success: boolean;
+  unread: number;
+}
+
+export interface MarkHistoryReadResponse {
+  success: boolean;
+}
+
+export interface MarkHistoryReadBody {
+  read: boolean;
+}
+
+export interface AlertsStatusResponse {
+  success: boolean;
+  enabled: boolean;
+}
+
+export interface PositionsResponse {
+  success: boolean;
+  positions: PositionView[];
+}
+
+export interface CreatePositionResponse {
+  success: boolean;
+  position: PositionView;
+}
+
+export interface RebalanceResponse {
+  success: boolean;
+  plan: RebalancePlan;
+  unpriced: string[];
+}
+
+export interface RebalanceQuery {
+  target?: RebalanceTarget;
+  commission_per_trade?: number;
+  slippage_bps?: number;
+}
+
+export interface SellPositionResponse {
+  success: boolean;
+  gains: RealizedGain[];
+  summary: RealizedSummary;
+}
+
+/** `import` with `dry_run=true`; the other fields otherwise. */
+export interface ImportPositionsResponse {
+  success: boolean;
+  broker: BrokerFormat;
+  import?: BrokerImport;
+  mode?: 'positions' | 'trades';
+  lots_created?: number;
+  realized?: RealizedSummary;
+  skipped?: string[];
+  /** Rows imported by an earlier request, left alone. */
+  already_imported?: string[];
+  errors?: string[];
+}
+
+export interface ImportPositionsQuery {
+  /** Sniffed from the body if omitted. */
+  broker?: BrokerFormat;
+  /** Parse only; return the rows as `import`. */
+  dry_run?: boolean;
+  /** Replay the trade log even when the file also lists open positions. */
+  trades?: boolean;
+}
+
+export interface ValuationHistoryResponse {
+  success: boolean;
+  snapshots: ValuationSnapshot[];
+}
+
+export interface ValuationHistoryQuery {
+  days?: number;
+}
+
+export interface SnapshotPositionsResponse {
+  success: boolean;
+  snapshot: ValuationSnapshot;
+}
+
+export interface RealizedGainsResponse {
+  success: boolean;
+  year: number | null;
+  realized: RealizedSummary;
+  unrealized_pnl: number;
+  unpriced: string[];
+  gains: RealizedGain[];
+}
+
+export interface RealizedGainsQuery {
+  year?: number;
+}
+
+export interface ExportRealizedGainsQuery {
+  year?: number;
+}
+
+export interface PositionResponse {
+  success: boolean;
+  position: PositionView;
+}
+
+export interface UpdatePositionResponse {
+  success: boolean;
+  position: PositionView;
+}
+
+export interface DeletePositionResponse {
+  success: boolean;
+}
+
+/** `success: false` bodies and non-2xx statuses. */
+export class ApiError extends Error {
+  constructor(message: string, readonly status: number) {
+    super(message);
+    this.name = 'ApiError';
+  }
+}
+
+export class ApiClient {
+  private readonly http: AxiosInstance;
+
+  /** `baseUrl` is the server root; '' targets the page's own origin. */
+  constructor(readonly baseUrl = '', http: AxiosInstance = axios.create()) {
+    this.baseUrl = baseUrl.replace(/\/$/, '');
+    this.http = http;
+  }
+
+  /** Absolute URL of `path`, for the WebSocket and the SSE stream. */
+  url(path: string): string {
+    return `${this.baseUrl}${path}`;
+  }
+
+  private async request<T>(method: Method, path: string, config: AxiosRequestConfig): Promise<T> {
+    let response;
+    try {
+      response = await this.http.request({ ...config, method, url: this.url(path) });
+    } catch (e) {
+      if (axios.isAxiosError(e) && e.response) {
+        const data: any = e.response.data;
+        throw new ApiError((data && data.error) || e.message, e.response.status);
+      }
+      throw e;
+    }
+    if (response.data && response.data.success === false) {
+      throw new ApiError(response.data.error || 'request failed', response.status);
+    }
+    return response.data;
+  }
+
+  /** `GET /`: Service name and version. */
+  serviceInfo(): Promise<ServiceInfoResponse> {
+    return this.request('get', `/`, {});
+  }
+
+  /** `GET /health`: Database, cycle and degraded-mode status. */
+  health(): Promise<HealthResponse> {
+    return this.request('get', `/health`, {});
+  }
+
+  /** `GET /api/progress`: Current analysis cycle progress. */
+  progress(): Promise<ProgressResponse> {
+    return this.request('get', `/api/progress`, {});
+  }
+
+  /** `GET /api/progress/symbols`: Per-symbol state of the current cycle. */
+  progressSymbols(query: ProgressSymbolsQuery = {}): Promise<ProgressSymbolsResponse> {
+    return this.request('get', `/api/progress/symbols`, { params: query });
+  }
+
+  /** `GET /api/runs`: Recorded analysis cycles, most recent first, with a summary of the page. */
+  analysisRuns(query: AnalysisRunsQuery = {}): Promise<AnalysisRunsResponse> {
+    return this.request('get', `/api/runs`, { params: query });
+  }
+
+  /** `GET /api/symbols/aliases`: Detected ticker renames. */
+  symbolAliases(): Promise<SymbolAliasesResponse> {
+    return this.request('get', `/api/symbols/aliases`, {});
+  }
+
+  /** `GET /api/admin/dead-letters`: Symbols skipped after repeated failures. */
+  deadLetters(): Promise<DeadLettersResponse> {
+    return this.request('get', `/api/admin/dead-letters`, {});
+  }
+
+  /** `POST /api/admin/dead-letters/requeue`: Requeue every dead-lettered symbol. */
+  requeueAllDeadLetters(): Promise<RequeueAllDeadLettersResponse> {
+    return this.request('post', `/api/admin/dead-letters/requeue`, { data: {} });
+  }
+
+  /** `POST /api/admin/dead-letters/{symbol}/requeue`: Requeue one dead-lettered symbol. */
+  requeueDeadLetter(symbol: string): Promise<RequeueDeadLetterResponse> {
+    return this.request('post', `/api/admin/dead-letters/${encodeURIComponent(symbol)}/requeue`, { data: {} });
+  }
+
+  /** `GET /api/admin/db/stats`: Per-query-shape MongoDB statistics. */
+  dbStats(): Promise<DbStatsResponse> {
+    return this.request('get', `/api/admin/db/stats`, {});
+  }
+
+  /** `DELETE /api/admin/db/stats`: Reset the query statistics. */
+  resetDbStats(): Promise<ResetDbStatsResponse> {
+    return this.request('delete', `/api/admin/db/stats`, {});
+  }
+
+  /** `GET /api/admin/db/collections`: Document count and size of every collection, with history retention. */
+  dbCollections(): Promise<DbCollectionsResponse> {
+    return this.request('get', `/api/admin/db/collections`, {});
+  }
+
+  /** `GET /api/admin/corrupt-documents`: Analyses the last full read couldn't deserialize as stored. */
+  corruptDocuments(): Promise<CorruptDocumentsResponse> {
+    return this.request('get', `/api/admin/corrupt-documents`, {});
+  }
+
+  /** `POST /api/admin/repair`: Repair legacy analyses in place and quarantine unreadable ones now. */
+  runRepair(): Promise<RunRepairResponse> {
+    return this.request('post', `/api/admin/repair`, { data: {} });
+  }
+
+  /** `POST /api/admin/cross-section`: Recompute beta, correlation and RS rank now. */
+  runCrossSection(): Promise<RunCrossSectionResponse> {
+    return this.request('post', `/api/admin/cross-section`, { data: {} });
+  }
+
+  /** `GET /api/admin/weekend`: Weekend deep-analysis schedule and last report. */
+  weekendStatus(): Promise<WeekendStatusResponse> {
+    return this.request('get', `/api/admin/weekend`, {});
+  }
+
+  /** `POST /api/admin/weekend`: Start the weekend deep-analysis jobs now. */
+  runWeekend(): Promise<RunWeekendResponse> {
+    return this.request('post', `/api/admin/weekend`, { data: {} });
+  }
+
+  /** `GET /api/admin/backups`: List database backups. */
+  backups(): Promise<BackupsResponse> {
+    return this.request('get', `/api/admin/backups`, {});
+  }
+
+  /** `POST /api/admin/backups`: Take a backup now. */
+  runBackup(): Promise<RunBackupResponse> {
+    return this.request('post', `/api/admin/backups`, { data: {} });
+  }
+
+  /** `GET /api/admin/export`: Stream collections as seed-format NDJSON, gzipped by default. */
+  exportData(query: ExportDataQuery = {}): Promise<string> {
+    return this.request('get', `/api/admin/export`, { params: query, responseType: 'text' });
+  }
+
+  /** `POST /api/admin/import`: Load an export, replacing documents with the same key. */
+  importData(body: string): Promise<ImportDataResponse> {
+    return this.request('post', `/api/admin/import`, { data: body, headers: { 'Content-Type': 'text/plain' } });
+  }
+
+  /** `GET /api/admin/archive`: Raw upstream response archive usage. */
+  rawArchive(): Promise<RawArchiveResponse> {
+    return this.request('get', `/api/admin/archive`, {});
+  }
+
+  /** `GET /api/admin/archive/replay`: Whether an archive replay is running and its last report. */
+  replayStatus(): Promise<ReplayStatusResponse> {
+    return this.request('get', `/api/admin/archive/replay`, {});
+  }
+
+  /** `POST /api/admin/archive/replay`: Recompute analysis history from archived responses. */
+  runReplay(body: ReplayRequest): Promise<RunReplayResponse> {
+    return this.request('post', `/api/admin/archive/replay`, { data: body });
+  }
+
+  /** `GET /api/admin/archive/{date}/{symbol}`: Archived raw responses for a symbol on one day. */
+  archivedResponses(date: string, symbol: string): Promise<ArchivedResponsesResponse> {
+    return this.request('get', `/api/admin/archive/${encodeURIComponent(date)}/${encodeURIComponent(symbol)}`, {});
+  }
+
+  /** `GET /api/admin/maintenance`: Read-only maintenance mode. */
+  maintenance(): Promise<MaintenanceResponse> {
+    return this.request('get', `/api/admin/maintenance`, {});
+  }
+
+  /** `PUT /api/admin/maintenance`: Switch read-only maintenance mode on or off. */
+  setMaintenance(body: MaintenanceInput): Promise<SetMaintenanceResponse> {
+    return this.request('put', `/api/admin/maintenance`, { data: body });
+  }
+
+  /** `GET /api/cache/stats`: Stock and response cache hit rates and size. */
+  cacheStats(): Promise<CacheStatsResponse> {
+    return this.request('get', `/api/cache/stats`, {});
+  }
+
+  /** `GET /api/cache/pins`: Symbols pinned in the cache. */
+  cachePins(): Promise<CachePinsResponse> {
+    return this.request('get', `/api/cache/pins`, {});
+  }
+
+  /** `PUT /api/cache/pins/{symbol}`: Pin a symbol in the cache. */
+  pinStock(symbol: string, body: PinStockBody): Promise<PinStockResponse> {
+    return this.request('put', `/api/cache/pins/${encodeURIComponent(symbol)}`, { data: body });
+  }
+
+  /** `DELETE /api/cache/pins/{symbol}`: Unpin a symbol. */
+  unpinStock(symbol: string): Promise<UnpinStockResponse> {
+    return this.request('delete', `/api/cache/pins/${encodeURIComponent(symbol)}`, {});
+  }
+
+  /** `GET /api/ai/status`: Whether AI analysis is configured. */
+  aiStatus(): Promise<AiStatusResponse> {
+    return this.request('get', `/api/ai/status`, {});
+  }
+
+  /** `GET /api/ai/models`: Models tried in order. */
+  aiModels(): Promise<AiModelsResponse> {
+    return this.request('get', `/api/ai/models`, {});
+  }
+
+  /** `GET /api/stocks`: The 50 largest analyzed stocks by market cap. */
+  stocks(query: StocksQuery = {}): Promise<StocksResponse> {
+    return this.request('get', `/api/stocks`, { params: query });
+  }
+
+  /** `POST /api/stocks/filter`: Filter, sort and paginate analyzed stocks. */
+  filterStocks(body: StockFilter, query: FilterStocksQuery = {}): Promise<FilterStocksResponse> {
+    return this.request('post', `/api/stocks/filter`, { params: query, data: body });
+  }
+
+  /** `GET /api/stocks/{symbol}`: Latest analysis of one symbol. */
+  stock(symbol: string, query: StockQuery = {}): Promise<StockResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}`, { params: query });
+  }
+
+  /** `GET /api/stocks/{symbol}/history`: Daily bars for the last 90 days. */
+  stockHistory(symbol: string, query: StockHistoryQuery = {}): Promise<StockHistoryResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/history`, { params: query });
+  }
+
+  /** `GET /api/stocks/{symbol}/ai-analysis`: AI-written analysis of one symbol. */
+  aiAnalysis(symbol: string): Promise<AiAnalysisResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/ai-analysis`, {});
+  }
+
+  /** `GET /api/stocks/{symbol}/ai-summary`: AI summary stored by the weekend run. */
+  stockAiSummary(symbol: string): Promise<StockAiSummaryResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/ai-summary`, {});
+  }
+
+  /** `GET /api/stocks/{symbol}/profile`: Company profile and key statistics. */
+  stockProfile(symbol: string): Promise<StockProfileResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/profile`, {});
+  }
+
+  /** `GET /api/stocks/{symbol}/notes`: User notes and tags of one symbol. */
+  stockNotes(symbol: string): Promise<StockNotesResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/notes`, {});
+  }
+
+  /** `PUT /api/stocks/{symbol}/notes`: Replace a symbol's notes and tags. */
+  saveStockNotes(symbol: string, body: NoteInput): Promise<SaveStockNotesResponse> {
+    return this.request('put', `/api/stocks/${encodeURIComponent(symbol)}/notes`, { data: body });
+  }
+
+  /** `GET /api/tags`: Tags in use with their symbol counts. */
+  tags(): Promise<TagsResponse> {
+    return this.request('get', `/api/tags`, {});
+  }
+
+  /** `GET /api/search`: Ticker and company name search over the screener listings, best match first. */
+  searchSymbols(query: SearchSymbolsQuery): Promise<SearchSymbolsResponse> {
+    return this.request('get', `/api/search`, { params: query });
+  }
+
+  /** `GET /api/stocks/{symbol}/insiders`: Recent insider transactions. */
+  insiderTrades(symbol: string): Promise<InsiderTradesResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/insiders`, {});
+  }
+
+  /** `GET /api/stocks/{symbol}/earnings`: Next earnings date and estimates. */
+  stockEarnings(symbol: string): Promise<StockEarningsResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/earnings`, {});
+  }
+
+  /** `GET /api/stocks/{symbol}/seasonality`: Month-of-year returns from the multi-year backfill. */
+  stockSeasonality(symbol: string): Promise<StockSeasonalityResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/seasonality`, {});
+  }
+
+  /** `GET /api/stocks/{symbol}/analysis-history`: A symbol's saved analyses over the last days, oldest first. */
+  stockAnalysisHistory(symbol: string, query: StockAnalysisHistoryQuery = {}): Promise<StockAnalysisHistoryResponse> {
+    return this.request('get', `/api/stocks/${encodeURIComponent(symbol)}/analysis-history`, { params: query });
+  }
+
+  /** `GET /api/market-summary`: Top movers and highlights. */
+  marketSummary(query: MarketSummaryQuery = {}): Promise<MarketSummaryResponse> {
+    return this.request('get', `/api/market-summary`, { params: query });
+  }
+
+  /** `GET /api/quotes`: Near-current prices for up to 100 symbols. */
+  quotes(query: QuotesQuery): Promise<QuotesResponse> {
+    return this.request('get', `/api/quotes`, { params: query });
+  }
+
+  /** `GET /api/news`: News across analyzed stocks. */
+  news(query: NewsQuery = {}): Promise<NewsResponse> {
+    return this.request('get', `/api/news`, { params: query });
+  }
+
+  /** `GET /api/sectors`: Per-sector count, averages, total market cap and leaders. */
+  sectors(): Promise<SectorsResponse> {
+    return this.request('get', `/api/sectors`, {});
+  }
+
+  /** `GET /api/sectors/etfs`: Sector proxy ETFs and their returns. */
+  sectorEtfs(): Promise<SectorEtfsResponse> {
+    return this.request('get', `/api/sectors/etfs`, {});
+  }
+
+  /** `GET /api/signals/performance`: Forward returns of past indicator signals. */
+  signalPerformance(query: SignalPerformanceQuery = {}): Promise<SignalPerformanceResponse> {
+    return this.request('get', `/api/signals/performance`, { params: query });
+  }
+
+  /** `GET /api/events/52w`: New 52-week highs and lows with daily breadth. */
+  week52Events(query: Week52EventsQuery = {}): Promise<Week52EventsResponse> {
+    return this.request('get', `/api/events/52w`, { params: query });
+  }
+
+  /** `POST /api/ingest/signal`: Push an external signal or note for a symbol. */
+  ingestSignal(body: SignalInput, query: IngestSignalQuery = {}): Promise<IngestSignalResponse> {
+    return this.request('post', `/api/ingest/signal`, { params: query, data: body });
+  }
+
+  /** `POST /api/ingest/tradingview`: Push a TradingView webhook alert as is. */
+  ingestTradingView(body: string, query: IngestTradingViewQuery = {}): Promise<IngestTradingViewResponse> {
+    return this.request('post', `/api/ingest/tradingview`, { params: query, data: body, headers: { 'Content-Type': 'text/plain' } });
+  }
+
+  /** `GET /api/ingest/signals`: Stored external signals, newest first. */
+  externalSignals(query: ExternalSignalsQuery = {}): Promise<ExternalSignalsResponse> {
+    return this.request('get', `/api/ingest/signals`, { params: query });
+  }
+
+  /** `GET /api/earnings`: Upcoming earnings of the largest stocks. */
+  earningsCalendar(query: EarningsCalendarQuery = {}): Promise<EarningsCalendarResponse> {
+    return this.request('get', `/api/earnings`, { params: query });
+  }
+
+  /** `GET /api/analytics/correlation`: Return correlation matrix of 2-20 symbols. */
+  correlation(query: CorrelationQuery): Promise<CorrelationResponse> {
+    return this.request('get', `/api/analytics/correlation`, { params: query });
+  }
+
+  /** `GET /api/analytics/what-if`: Value today of a past investment. */
+  whatIf(query: WhatIfQuery): Promise<WhatIfResponse> {
+    return this.request('get', `/api/analytics/what-if`, { params: query });
+  }
+
+  /** `GET /api/indexes`: Tracked indexes. */
+  indexes(): Promise<IndexesResponse> {
+    return this.request('get', `/api/indexes`, {});
+  }
+
+  /** `GET /api/indexes/{index_id}`: One index and its constituents. */
+  index(index_id: string): Promise<IndexResponse> {
+    return this.request('get', `/api/indexes/${encodeURIComponent(index_id)}`, {});
+  }
+
+  /** `GET /api/indexes/{index_id}/heatmap`: Constituent returns over a period. */
+  indexHeatmap(index_id: string, query: IndexHeatmapQuery = {}): Promise<IndexHeatmapResponse> {
+    return this.request('get', `/api/indexes/${encodeURIComponent(index_id)}/heatmap`, { params: query });
+  }
+
+  /** `GET /api/indexes/{index_id}/performance`: Equal- vs cap-weight daily returns. */
+  indexPerformance(index_id: string, query: IndexPerformanceQuery = {}): Promise<IndexPerformanceResponse> {
+    return this.request('get', `/api/indexes/${encodeURIComponent(index_id)}/performance`, { params: query });
+  }
+
+  /** `GET /api/indexes/{index_id}/contributors`: Largest contributors to a day's move. */
+  indexContributors(index_id: string, query: IndexContributorsQuery = {}): Promise<IndexContributorsResponse> {
+    return this.request('get', `/api/indexes/${encodeURIComponent(index_id)}/contributors`, { params: query });
+  }
+
+  /** `GET /api/screens`: Precomputed screens. */
+  screens(): Promise<ScreensResponse> {
+    return this.request('get', `/api/screens`, {});
+  }
+
+  /** `GET /api/screens/{name}`: One screen's ranked rows. */
+  screen(name: string, query: ScreenQuery = {}): Promise<ScreenResponse> {
+    return this.request('get', `/api/screens/${encodeURIComponent(name)}`, { params: query });
+  }
+
+  /** `GET /api/themes`: Thematic baskets. */
+  themes(): Promise<ThemesResponse> {
+    return this.request('get', `/api/themes`, {});
+  }
+
+  /** `GET /api/themes/{theme_id}`: One theme. */
+  theme(theme_id: string): Promise<ThemeResponse> {
+    return this.request('get', `/api/themes/${encodeURIComponent(theme_id)}`, {});
+  }
+
+  /** `GET /api/themes/{theme_id}/heatmap`: Member returns over a period. */
+  themeHeatmap(theme_id: string, query: ThemeHeatmapQuery = {}): Promise<ThemeHeatmapResponse> {
+    return this.request('get', `/api/themes/${encodeURIComponent(theme_id)}/heatmap`, { params: query });
+  }
+
+  /** `GET /api/themes/{theme_id}/performance`: Equal- vs cap-weight daily returns. */
+  themePerformance(theme_id: string, query: ThemePerformanceQuery = {}): Promise<ThemePerformanceResponse> {
+    return this.request('get', `/api/themes/${encodeURIComponent(theme_id)}/performance`, { params: query });
+  }
+
+  /** `PUT /api/admin/themes/{theme_id}`: Create or replace a theme. */
+  saveTheme(theme_id: string, body: ThemeInput): Promise<SaveThemeResponse> {
+    return this.request('put', `/api/admin/themes/${encodeURIComponent(theme_id)}`, { data: body });
+  }
+
+  /** `DELETE /api/admin/themes/{theme_id}`: Delete a theme. */
+  deleteTheme(theme_id: string): Promise<DeleteThemeResponse> {
+    return this.request('delete', `/api/admin/themes/${encodeURIComponent(theme_id)}`, {});
+  }
+
+  /** `POST /api/admin/themes/{theme_id}/symbols`: Add and remove theme members. */
+  updateThemeSymbols(theme_id: string, body: ThemeMembershipChange): Promise<UpdateThemeSymbolsResponse> {
+    return this.request('post', `/api/admin/themes/${encodeURIComponent(theme_id)}/symbols`, { data: body });
+  }
+
+  /** `GET /api/watchlists`: All watchlists. */
+  watchlists(): Promise<WatchlistsResponse> {
+    return this.request('get', `/api/watchlists`, {});
+  }
+
+  /** `POST /api/watchlists`: Create a watchlist. */
+  createWatchlist(body: CreateWatchlistInput): Promise<CreateWatchlistResponse> {
+    return this.request('post', `/api/watchlists`, { data: body });
+  }
+
+  /** `GET /api/watchlists/{id}`: One watchlist. */
+  watchlist(id: string): Promise<WatchlistResponse> {
+    return this.request('get', `/api/watchlists/${encodeURIComponent(id)}`, {});
+  }
+
+  /** `PATCH /api/watchlists/{id}`: Rename or replace symbols. */
+  updateWatchlist(id: string, body: UpdateWatchlistInput): Promise<UpdateWatchlistResponse> {
+    return this.request('patch', `/api/watchlists/${encodeURIComponent(id)}`, { data: body });
+  }
+
+  /** `DELETE /api/watchlists/{id}`: Delete a watchlist. */
+  deleteWatchlist(id: string): Promise<DeleteWatchlistResponse> {
+    return this.request('delete', `/api/watchlists/${encodeURIComponent(id)}`, {});
+  }
+
+  /** `POST /api/watchlists/{id}/symbols`: Add a symbol. */
+  addWatchlistSymbol(id: string, body: AddSymbolInput): Promise<AddWatchlistSymbolResponse> {
+    return this.request('post', `/api/watchlists/${encodeURIComponent(id)}/symbols`, { data: body });
+  }
+
+  /** `DELETE /api/watchlists/{id}/symbols/{symbol}`: Remove a symbol. */
+  removeWatchlistSymbol(id: string, symbol: string): Promise<RemoveWatchlistSymbolResponse> {
+    return this.request('delete', `/api/watchlists/${encodeURIComponent(id)}/symbols/${encodeURIComponent(symbol)}`, {});
+  }
+
+  /** `GET /api/watchlists/{id}/recap`: Preview today's end-of-day recap. */
+  watchlistRecap(id: string): Promise<WatchlistRecapResponse> {
+    return this.request('get', `/api/watchlists/${encodeURIComponent(id)}/recap`, {});
+  }
+
+  /** `POST /api/watchlists/{id}/recap`: Send today's recap to daily_recap channels now. */
+  sendWatchlistRecap(id: string): Promise<SendWatchlistRecapResponse> {
+    return this.request('post', `/api/watchlists/${encodeURIComponent(id)}/recap`, { data: {} });
+  }
+
+  /** `GET /api/alerts/rules`: All alert rules. */
+  alertRules(): Promise<AlertRulesResponse> {
+    return this.request('get', `/api/alerts/rules`, {});
+  }
+
+  /** `POST /api/alerts/rules`: Create an alert rule. */
+  createAlertRule(body: CreateAlertRuleInput): Promise<CreateAlertRuleResponse> {
+    return this.request('post', `/api/alerts/rules`, { data: body });
+  }
+
+  /** `GET /api/alerts/rules/{id}`: One alert rule. */
+  alertRule(id: string): Promise<AlertRuleResponse> {
+    return this.request('get', `/api/alerts/rules/${encodeURIComponent(id)}`, {});
+  }
+
+  /** `PUT /api/alerts/rules/{id}`: Update the fields present in the body. */
+  updateAlertRule(id: string, body: UpdateAlertRuleInput): Promise<UpdateAlertRuleResponse> {
+    return this.request('put', `/api/alerts/rules/${encodeURIComponent(id)}`, { data: body });
+  }
+
+  /** `DELETE /api/alerts/rules/{id}`: Delete an alert rule. */
+  deleteAlertRule(id: string): Promise<DeleteAlertRuleResponse> {
+    return this.request('delete', `/api/alerts/rules/${encodeURIComponent(id)}`, {});
+  }
+
+  /** `POST /api/alerts/rules/{id}/toggle`: Flip `enabled`. */
+  toggleAlertRule(id: string): Promise<ToggleAlertRuleResponse> {
+    return this.request('post', `/api/alerts/rules/${encodeURIComponent(id)}/toggle`, { data: {} });
+  }
+
+  /** `POST /api/alerts/rules/{id}/test`: Send a one-off test notification. */
+  testAlertRule(id: string, body: TestAlertRuleBody): Promise<TestAlertRuleResponse> {
+    return this.request('post', `/api/alerts/rules/${encodeURIComponent(id)}/test`, { data: body });
+  }
+
+  /** `POST /api/alerts/rules/{id}/snooze`: Pause a rule until a time. */
+  snoozeAlertRule(id: string, body: SnoozeRuleInput): Promise<SnoozeAlertRuleResponse> {
+    return this.request('post', `/api/alerts/rules/${encodeURIComponent(id)}/snooze`, { data: body });
+  }
+
+  /** `DELETE /api/alerts/rules/{id}/snooze`: Clear a snooze. */
+  unsnoozeAlertRule(id: string): Promise<UnsnoozeAlertRuleResponse> {
+    return this.request('delete', `/api/alerts/rules/${encodeURIComponent(id)}/snooze`, {});
+  }
+
+  /** `PUT /api/alerts/rules/{id}/mute/{symbol}`: Stop a rule firing for one symbol. */
+  muteAlertSymbol(id: string, symbol: string): Promise<MuteAlertSymbolResponse> {
+    return this.request('put', `/api/alerts/rules/${encodeURIComponent(id)}/mute/${encodeURIComponent(symbol)}`, { data: {} });
+  }
+
+  /** `DELETE /api/alerts/rules/{id}/mute/{symbol}`: Unmute a symbol. */
+  unmuteAlertSymbol(id: string, symbol: string): Promise<UnmuteAlertSymbolResponse> {
+    return this.request('delete', `/api/alerts/rules/${encodeURIComponent(id)}/mute/${encodeURIComponent(symbol)}`, {});
+  }
+
+  /** `POST /api/alerts/rules/{id}/reset`: Zero the trigger count and re-enable. */
+  resetAlertRule(id: string): Promise<ResetAlertRuleResponse> {
+    return this.request('post', `/api/alerts/rules/${encodeURIComponent(id)}/reset`, { data: {} });
+  }
+
+  /** `GET /api/alerts/rules/{id}/audit`: A rule's audit log, newest first. */
+  alertRuleAudit(id: string, query: AlertRuleAuditQuery = {}): Promise<AlertRuleAuditResponse> {
+    return this.request('get', `/api/alerts/rules/${encodeURIComponent(id)}/audit`, { params: query });
+  }
+
+  /** `GET /api/alerts/channels`: All notification channels. */
+  channels(): Promise<ChannelsResponse> {
+    return this.request('get', `/api/alerts/channels`, {});
+  }
+
+  /** `POST /api/alerts/channels`: Create a channel. */
+  createChannel(body: CreateChannelInput): Promise<CreateChannelResponse> {
+    return this.request('post', `/api/alerts/channels`, { data: body });
+  }
+
+  /** `GET /api/alerts/channels/{id}`: One channel. */
+  channel(id: string): Promise<ChannelResponse> {
+    return this.request('get', `/api/alerts/channels/${encodeURIComponent(id)}`, {});
+  }
+
+  /** `PUT /api/alerts/channels/{id}`: Update a channel. */
+  updateChannel(id: string, body: UpdateChannelInput): Promise<UpdateChannelResponse> {
+    return this.request('put', `/api/alerts/channels/${encodeURIComponent(id)}`, { data: body });
+  }
+
+  /** `DELETE /api/alerts/channels/{id}`: Delete a channel. */
+  deleteChannel(id: string): Promise<DeleteChannelResponse> {
+    return this.request('delete', `/api/alerts/channels/${encodeURIComponent(id)}`, {});
+  }
+
+  /** `POST /api/alerts/channels/{id}/test`: Send a test message. */
+  testChannel(id: string): Promise<TestChannelResponse> {
+    return this.request('post', `/api/alerts/channels/${encodeURIComponent(id)}/test`, { data: {} });
+  }
+
+  /** `GET /api/alerts/history`: Sent notifications, newest first. */
+  notificationHistory(query: NotificationHistoryQuery = {}): Promise<NotificationHistoryResponse> {
+    return this.request('get', `/api/alerts/history`, { params: query });
+  }
+
+  /** `GET /api/alerts/history/unread-count`: Unread notifications. */
+  unreadCount(): Promise<UnreadCountResponse> {
+    return this.request('get', `/api/alerts/history/unread-count`, {});
+  }
+
+  /** `PATCH /api/alerts/history/{id}/read`: Mark a notification read or unread. */
+  markHistoryRead(id: string, body: MarkHistoryReadBody): Promise<MarkHistoryReadResponse> {
+    return this.request('patch', `/api/alerts/history/${encodeURIComponent(id)}/read`, { data: body });
+  }
+
+  /** `GET /api/alerts/status`: Whether the alert engine is running. */
+  alertsStatus(): Promise<AlertsStatusResponse> {
+    return this.request('get', `/api/alerts/status`, {});
+  }
+
+  /** `GET /api/positions`: Open lots with their P&L. */
+  positions(): Promise<PositionsResponse> {
+    return this.request('get', `/api/positions`, {});
+  }
+
+  /** `POST /api/positions`: Open a lot. */
+  createPosition(body: CreatePositionInput): Promise<CreatePositionResponse> {
+    return this.request('post', `/api/positions`, { data: body });
+  }
+
+  /** `GET /api/positions/rebalance`: Trades that reach a target allocation. */
+  rebalance(query: RebalanceQuery = {}): Promise<RebalanceResponse> {
+    return this.request('get', `/api/positions/rebalance`, { params: query });
+  }
+
+  /** `POST /api/positions/sell`: Close lots and record realized gains. */
+  sellPosition(body: SellPositionInput): Promise<SellPositionResponse> {
+    return this.request('post', `/api/positions/sell`, { data: body });
+  }
+
+  /** `POST /api/positions/import`: Import a broker export. */
+  importPositions(body: string, query: ImportPositionsQuery = {}): Promise<ImportPositionsResponse> {
+    return this.request('post', `/api/positions/import`, { params: query, data: body, headers: { 'Content-Type': 'text/plain' } });
+  }
+
+  /** `GET /api/positions/history`: Daily valuation snapshots, oldest first. */
+  valuationHistory(query: ValuationHistoryQuery = {}): Promise<ValuationHistoryResponse> {
+    return this.request('get', `/api/positions/history`, { params: query });
+  }
+
+  /** `POST /api/positions/history`: Take today's snapshot now. */
+  snapshotPositions(): Promise<SnapshotPositionsResponse> {
+    return this.request('post', `/api/positions/history`, { data: {} });
+  }
+
+  /** `GET /api/positions/realized`: Realized gains and open P&L. */
+  realizedGains(query: RealizedGainsQuery = {}): Promise<RealizedGainsResponse> {
+    return this.request('get', `/api/positions/realized`, { params: query });
+  }
+
+  /** `GET /api/positions/realized/export`: Realized gains as CSV. */
+  exportRealizedGains(query: ExportRealizedGainsQuery = {}): Promise<string> {
+    return this.request('get', `/api/positions/realized/export`, { params: query, responseType: 'text' });
+  }
+
+  /** `GET /api/positions/{id}`: One lot. */
+  position(id: string): Promise<PositionResponse> {
+    return this.request('get', `/api/positions/${encodeURIComponent(id)}`, {});
+  }
+
+  /** `PATCH /api/positions/{id}`: Edit a lot. */
+  updatePosition(id: string, body: UpdatePositionInput): Promise<UpdatePositionResponse> {
+    return this.request('patch', `/api/positions/${encodeURIComponent(id)}`, { data: body });
+  }
+
+  /** `DELETE /api/positions/{id}`: Delete a lot. */
+  deletePosition(id: string): Promise<DeletePositionResponse> {
+    return this.request('delete', `/api/positions/${encodeURIComponent(id)}`, {});
+  }
+}
@@ -82,7 +82,8 @@ export const useWebSocket = () => {
   const connect = useCallback(() => {
     try {
       closedRef.current = false;
-      const ws = new WebSocket(api.getWebSocketUrl());
+      // Differential mode: a full keyframe now and then, deltas in between.
+      const ws = new WebSocket(`${api.getWebSocketUrl()}?diff=true`);
 
       ws.onopen = () => {
         console.log('WebSocket connected');
@@ -93,13 +94,14 @@ export const useWebSocket = () => {
       ws.onmessage = (event) => {
         try {
           const data = JSON.parse(event.data);
-          if (
-            data &&
-            typeof data === 'object' &&
-            typeof data.total_stocks === 'number' &&
-            typeof data.analyzed === 'number'
-          ) {
-            setProgress(data as AnalysisProgress);
+          if (data?.type === 'keyframe' && data.progress) {
+            setProgress(data.progress as AnalysisProgress);
+          } else if (data?.type === 'delta' && data.changed) {
+            // Deltas before the first keyframe can't be applied; the
+            // keyframe is always the first message, so this is defensive.
+            setProgress((prev) =>
+              prev ? ({ ...prev, ...data.changed } as AnalysisProgress) : prev
+            );
           }
         } catch (err) {
           console.error('Failed to parse WebSocket message:', err);
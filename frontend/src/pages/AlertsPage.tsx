@@ -253,7 +253,7 @@ const WatchlistsTab: React.FC = () => {
 // Rules
 // ---------------------------------------------------------------------------
 
-const blankRule = (): Omit<AlertRule, '_id' | 'created_at' | 'updated_at'> => ({
+const blankRule = (): Omit<AlertRule, '_id' | 'created_at' | 'updated_at' | 'trigger_count'> => ({
   name: 'New rule',
   enabled: true,
   scope: { type: 'all_watched' },
@@ -939,6 +939,12 @@ const ChannelsTab: React.FC = () => {
     reload();
   };
 
+  const handleToggleRecap = async (c: NotificationChannel) => {
+    if (!c._id) return;
+    await api.alerts.updateChannel(c._id, { daily_recap: !c.daily_recap });
+    reload();
+  };
+
   if (loading) return <Spinner color="accent.solid" />;
 
   return (
@@ -963,6 +969,7 @@ const ChannelsTab: React.FC = () => {
               <SignalBadge tone={c.enabled ? 'up' : 'neutral'} size="sm">{c.enabled ? 'enabled' : 'disabled'}</SignalBadge>
               <Text color="fg.default" fontWeight="bold">{c.name}</Text>
               <Badge colorPalette="blue">{c.kind}</Badge>
+              {c.daily_recap && <Badge colorPalette="purple">daily recap</Badge>}
             </HStack>
             <HStack>
               <Button size="xs" variant="outline" onClick={() => handleTest(c._id!)}>
@@ -971,6 +978,9 @@ const ChannelsTab: React.FC = () => {
               <Button size="xs" variant="outline" onClick={() => handleToggleEnabled(c)}>
                 {c.enabled ? 'Disable' : 'Enable'}
               </Button>
+              <Button size="xs" variant="outline" onClick={() => handleToggleRecap(c)}>
+                {c.daily_recap ? 'Stop recap' : 'Daily recap'}
+              </Button>
               <Button size="xs" variant="ghost" colorPalette="red" onClick={() => handleDelete(c._id!)}>
                 <Trash2 size={12} />
               </Button>
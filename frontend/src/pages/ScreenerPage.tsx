@@ -51,11 +51,16 @@ export const ScreenerPage: React.FC = () => {
   const [maxRsi, setMaxRsi] = useState('');
   const [minStochK, setMinStochK] = useState('');
   const [maxStochK, setMaxStochK] = useState('');
+  const [maxWilliamsR, setMaxWilliamsR] = useState('');
+  const [maxCci, setMaxCci] = useState('');
+  const [maxMfi, setMaxMfi] = useState('');
+  const [minRsRank, setMinRsRank] = useState('');
   const [minBandwidth, setMinBandwidth] = useState('');
   const [maxBandwidth, setMaxBandwidth] = useState('');
   const [minMarketCap, setMinMarketCap] = useState<number | null>(null);
   const [onlyOversold, setOnlyOversold] = useState(false);
   const [onlyOverbought, setOnlyOverbought] = useState(false);
+  const [superTrendUp, setSuperTrendUp] = useState(false);
   const [sortBy, setSortBy] = useState('market_cap');
   const [sortOrder, setSortOrder] = useState('desc');
 
@@ -64,16 +69,21 @@ export const ScreenerPage: React.FC = () => {
     max_rsi: maxRsi ? parseFloat(maxRsi) : undefined,
     min_stochastic_k: minStochK ? parseFloat(minStochK) : undefined,
     max_stochastic_k: maxStochK ? parseFloat(maxStochK) : undefined,
+    max_williams_r: maxWilliamsR ? parseFloat(maxWilliamsR) : undefined,
+    max_cci: maxCci ? parseFloat(maxCci) : undefined,
+    max_mfi: maxMfi ? parseFloat(maxMfi) : undefined,
+    min_rs_rank: minRsRank ? parseFloat(minRsRank) : undefined,
     min_bandwidth: minBandwidth ? parseFloat(minBandwidth) : undefined,
     max_bandwidth: maxBandwidth ? parseFloat(maxBandwidth) : undefined,
     min_market_cap: minMarketCap || undefined,
     only_oversold: onlyOversold || undefined,
     only_overbought: onlyOverbought || undefined,
+    supertrend_uptrend: superTrendUp || undefined,
     sort_by: sortBy,
     sort_order: sortOrder,
     page: overridePage ?? page,
     page_size: 50,
-  }), [minRsi, maxRsi, minStochK, maxStochK, minBandwidth, maxBandwidth, minMarketCap, onlyOversold, onlyOverbought, sortBy, sortOrder, page]);
+  }), [minRsi, maxRsi, minStochK, maxStochK, maxWilliamsR, maxCci, maxMfi, minRsRank, minBandwidth, maxBandwidth, minMarketCap, onlyOversold, onlyOverbought, superTrendUp, sortBy, sortOrder, page]);
 
   const runScreener = useCallback(async (overridePage?: number) => {
     try {
@@ -120,11 +130,16 @@ export const ScreenerPage: React.FC = () => {
     setMaxRsi(f.max_rsi?.toString() || '');
     setMinStochK(f.min_stochastic_k?.toString() || '');
     setMaxStochK(f.max_stochastic_k?.toString() || '');
+    setMaxWilliamsR(f.max_williams_r?.toString() || '');
+    setMaxCci(f.max_cci?.toString() || '');
+    setMaxMfi(f.max_mfi?.toString() || '');
+    setMinRsRank(f.min_rs_rank?.toString() || '');
     setMinBandwidth(f.min_bandwidth?.toString() || '');
     setMaxBandwidth(f.max_bandwidth?.toString() || '');
     setMinMarketCap(f.min_market_cap || null);
     setOnlyOversold(f.only_oversold || false);
     setOnlyOverbought(f.only_overbought || false);
+    setSuperTrendUp(f.supertrend_uptrend || false);
     setSortBy(f.sort_by || 'market_cap');
     setSortOrder(f.sort_order || 'desc');
     setPage(1);
@@ -163,6 +178,10 @@ export const ScreenerPage: React.FC = () => {
             <FilterInput label="Max RSI" value={maxRsi} onChange={setMaxRsi} placeholder="100" />
             <FilterInput label="Min Stoch %K" value={minStochK} onChange={setMinStochK} placeholder="0" />
             <FilterInput label="Max Stoch %K" value={maxStochK} onChange={setMaxStochK} placeholder="100" />
+            <FilterInput label="Max Williams %R" value={maxWilliamsR} onChange={setMaxWilliamsR} placeholder="0" />
+            <FilterInput label="Max CCI" value={maxCci} onChange={setMaxCci} placeholder="100" />
+            <FilterInput label="Max MFI" value={maxMfi} onChange={setMaxMfi} placeholder="100" />
+            <FilterInput label="Min RS Rank" value={minRsRank} onChange={setMinRsRank} placeholder="0" />
             <FilterInput label="Min Bandwidth" value={minBandwidth} onChange={setMinBandwidth} placeholder="0" />
             <FilterInput label="Max Bandwidth" value={maxBandwidth} onChange={setMaxBandwidth} placeholder="1" />
           </SimpleGrid>
@@ -202,11 +221,19 @@ export const ScreenerPage: React.FC = () => {
               >
                 Overbought Only
               </Button>
+              <Button
+                size="xs"
+                variant={superTrendUp ? 'solid' : 'outline'}
+                colorPalette={superTrendUp ? 'green' : 'gray'}
+                onClick={() => setSuperTrendUp(!superTrendUp)}
+              >
+                SuperTrend Up
+              </Button>
             </HStack>
 
             <HStack gap={2} wrap="wrap">
               <Text color="fg.muted" fontSize="xs">Sort:</Text>
-              {['market_cap', 'price_change_percent', 'rsi', 'price'].map(field => (
+              {['market_cap', 'price_change_percent', 'rsi', 'price', 'sharpe_ratio'].map(field => (
                 <Button
                   key={field}
                   size="xs"
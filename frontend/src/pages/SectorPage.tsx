@@ -32,7 +32,7 @@ const SectorCard: React.FC<{ sector: SectorPerformance }> = ({ sector }) => {
           <Badge colorPalette="gray" size="sm" variant="subtle">{sector.stock_count} stocks</Badge>
         </Flex>
 
-        <SimpleGrid columns={2} gap={4} w="100%">
+        <SimpleGrid columns={3} gap={4} w="100%">
           <Box>
             <Text color="fg.muted" fontSize="xs" textTransform="uppercase" letterSpacing="wider" mb={1}>Avg Change</Text>
             <Num
@@ -54,6 +54,16 @@ const SectorCard: React.FC<{ sector: SectorPerformance }> = ({ sector }) => {
               fontWeight="semibold"
             />
           </Box>
+          <Box>
+            <Text color="fg.muted" fontSize="xs" textTransform="uppercase" letterSpacing="wider" mb={1}>Market Cap</Text>
+            <Num
+              value={sector.total_market_cap}
+              prefix="$"
+              compact
+              fontSize="lg"
+              fontWeight="semibold"
+            />
+          </Box>
         </SimpleGrid>
 
         {sector.top_performers.length > 0 && (
@@ -29,6 +29,7 @@ import {
   getMarketCapTierLabel
 } from '../types';
 import { WatchButton } from '../components/alerts/WatchButton';
+import { NotesEditor } from '../components/NotesEditor';
 import { Surface, Num, SignalBadge } from '../components/ui/primitives';
 
 const toTradingViewSymbol = (symbol: string): string => {
@@ -436,6 +437,10 @@ export const StockDetailPage: React.FC = () => {
       </Surface>
 
       {/* Earnings Card (shown on overview) */}
+      {activeTab === 'overview' && (
+        <NotesEditor symbol={stock.symbol} notes={stock.notes} tags={stock.tags} />
+      )}
+
       {activeTab === 'overview' && stockEarnings && stockEarnings.earnings_date && (
         <Surface mb={4} p={4}>
           <Flex align="center" gap={6} wrap="wrap">
@@ -10,17 +10,144 @@ export interface StockAnalysis {
   macd?: MACDIndicator;
   volume?: number;
   market_cap?: number;
+  /** Present only with `?humanize=true`. */
+  volume_display?: string;
+  market_cap_display?: string;
   sector?: string;
   is_oversold: boolean;
   is_overbought: boolean;
+  /** Stochastic %K < 20 / > 80. */
+  stoch_oversold?: boolean;
+  stoch_overbought?: boolean;
   analyzed_at: string;
   bollinger?: BollingerBands;
   stochastic?: StochasticOscillator;
+  /** Williams %R(14), 0 to -100. */
+  williams_r?: number;
+  /** Commodity Channel Index(20). */
+  cci?: number;
+  /** Money Flow Index(14), 0-100. */
+  mfi?: number;
+  /** Volume-weighted average price over the fetched window. */
+  vwap?: number;
+  obv?: OnBalanceVolume;
+  /** Latest SMA 50 / SMA 200 crossing in the fetched window, and its session. */
+  cross_signal?: CrossSignal;
+  cross_date?: string;
+  supertrend?: SuperTrend;
+  /** Session the current SuperTrend direction began on, if in the fetched window. */
+  supertrend_flip_date?: string;
+  support_resistance?: SupportResistance;
+  /** Periods rsi, sma_20, sma_50 and macd were computed with. */
+  indicator_periods?: IndicatorConfig;
   earnings?: EarningsData;
   technicals?: NasdaqTechnicals;
   news?: NasdaqNewsItem[];
+  /** Stock minus sector-ETF return, in percentage points. */
+  sector_relative?: SectorRelative;
+  performance?: PerformanceReturns;
+  /** Annualised Sharpe / Sortino ratio over the fetched window. */
+  sharpe_ratio?: number;
+  sortino_ratio?: number;
+  /** Beta / SPY correlation / RS rank from the nightly batch. */
+  cross_section?: CrossSectionStats;
+  /** 1-99 ranks across the universe as of the last completed cycle. */
+  percentiles?: PercentileRanks;
+  /** Signals pushed to /api/ingest/signal within the inbox TTL, newest first. */
+  external_signals?: ExternalSignal[];
+  /** Pipeline stages that failed or timed out, e.g. "news: timed out after 20s". */
+  warnings?: string[];
+  /** Major indexes the symbol belongs to, e.g. ["sp500", "nasdaq100"]. */
+  indexes?: string[];
+  /** User notes and tags (PUT /api/stocks/:symbol/notes). */
+  notes?: string;
+  tags?: string[];
+  /** Missing on analyses saved before classification, which are equities. */
+  asset_type?: AssetType;
 }
 
+export type AssetType = 'equity' | 'etf' | 'warrant' | 'right' | 'unit' | 'spac';
+
+export interface PerformanceReturns {
+  return_1w_pct: number | null;
+  return_1m_pct: number | null;
+  return_3m_pct: number | null;
+  return_ytd_pct: number | null;
+}
+
+export interface PercentileRanks {
+  rsi?: number;
+  price_change_percent?: number;
+  volume_ratio?: number;
+  pe_ratio?: number;
+  return_1m_pct?: number;
+  return_3m_pct?: number;
+  /** RS rating: rank of the 1-month plus the 3-month return. */
+  rs_rank?: number;
+}
+
+export type PercentileMetric = keyof PercentileRanks;
+
+export interface CrossSectionStats {
+  symbol: string;
+  beta: number | null;
+  correlation: number | null;
+  /** 1-99 percentile of the weighted 3/6/9/12-month return; 99 is strongest. */
+  rs_rank: number | null;
+  benchmark: string;
+  as_of: string;
+  computed_at: string;
+}
+
+export interface SectorRelative {
+  etf: string;
+  relative_1d_pct: number | null;
+  relative_5d_pct: number | null;
+  relative_20d_pct: number | null;
+}
+
+export interface SectorEtfSnapshot {
+  etf: string;
+  price: number;
+  return_1d_pct: number | null;
+  return_5d_pct: number | null;
+  return_20d_pct: number | null;
+  updated_at: string;
+}
+
+export interface LiveQuote {
+  symbol: string;
+  price: number;
+  day_volume?: number | null;
+  quoted_at: string;
+}
+
+export interface QuotesResponse {
+  success: boolean;
+  count: number;
+  cached: number;
+  quotes: LiveQuote[];
+  missing: string[];
+  error?: string;
+}
+
+export interface IntradayCandle {
+  symbol: string;
+  start: string;
+  interval_secs: number;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume: number;
+}
+
+export type IntradayWsMessage =
+  | { type: 'subscribed'; symbols: string[]; max_symbols: number }
+  | { type: 'candle'; candle: IntradayCandle }
+  | { type: 'analysis'; analysis: StockAnalysis }
+  | { type: 'error'; error: string };
+
 export interface MACDIndicator {
   macd_line: number;
   signal_line: number;
@@ -39,6 +166,39 @@ export interface StochasticOscillator {
   d_line: number;
 }
 
+/** INDICATOR_* settings an analysis was computed with. */
+export interface IndicatorConfig {
+  rsi_period: number;
+  /** Period of sma_20. */
+  sma_short: number;
+  /** Period of sma_50. */
+  sma_long: number;
+  macd_fast: number;
+  macd_slow: number;
+  macd_signal: number;
+}
+
+/** Nearest swing-pivot levels around the price; null when none on that side. */
+export interface SupportResistance {
+  support: number | null;
+  resistance: number | null;
+}
+
+export type CrossSignal = 'golden_cross' | 'death_cross' | 'none';
+
+export interface OnBalanceVolume {
+  /** Running OBV at the latest bar, counted from the first fetched bar. */
+  obv: number;
+  /** Least-squares slope of OBV over the last 20 bars, in shares per bar. */
+  slope: number;
+}
+
+export interface SuperTrend {
+  /** Active band: trailing stop in an uptrend, ceiling in a downtrend. */
+  value: number;
+  uptrend: boolean;
+}
+
 export interface EarningsData {
   earnings_date?: string;
   eps_estimate?: number;
@@ -60,10 +220,37 @@ export interface SectorPerformance {
   stock_count: number;
   avg_change_percent: number;
   avg_rsi: number;
+  total_market_cap: number;
+  top_gainer?: SectorMover | null;
+  top_loser?: SectorMover | null;
   top_performers: StockAnalysis[];
   bottom_performers: StockAnalysis[];
 }
 
+export interface AnalysisHistoryPoint {
+  analyzed_at: string;
+  price: number;
+  price_change_percent?: number | null;
+  rsi?: number | null;
+  sma_20?: number | null;
+  sma_50?: number | null;
+  macd?: MACDIndicator | null;
+  volume?: number | null;
+}
+
+export interface SymbolMatch {
+  symbol: string;
+  name: string;
+  asset_type: AssetType;
+  price: number;
+  market_cap?: number | null;
+}
+
+export interface SectorMover {
+  symbol: string;
+  price_change_percent?: number | null;
+}
+
 export interface AggregatedNewsItem {
   symbol: string;
   sector?: string;
@@ -79,6 +266,30 @@ export interface CorrelationData {
   matrix: number[][];
 }
 
+/** Mirrors Rust `analytics::WhatIfResult`. */
+export interface WhatIfResult {
+  symbol: string;
+  amount: number;
+  entry_date: string;
+  entry_price: number;
+  shares: number;
+  as_of: string;
+  current_price: number;
+  current_value: number;
+  total_return_pct: number;
+  cagr_pct: number | null;
+  max_drawdown_pct: number;
+  drawdowns: { peak_date: string; trough_date: string; recovery_date: string | null; depth_pct: number }[];
+  /** Same holding with dividends reinvested. */
+  with_dividends: {
+    dividends_reinvested: number;
+    shares: number;
+    current_value: number;
+    total_return_pct: number;
+    cagr_pct: number | null;
+  };
+}
+
 export interface NasdaqTechnicals {
   exchange?: string;
   sector?: string;
@@ -183,13 +394,48 @@ export interface StockFilter {
   sectors?: string[];
   only_oversold?: boolean;
   only_overbought?: boolean;
+  /** Stochastic %K < 20 / > 80. */
+  only_stoch_oversold?: boolean;
+  only_stoch_overbought?: boolean;
+  /** Only rows whose latest SMA 50 / SMA 200 cross is this one. */
+  cross_signal?: CrossSignal;
+  /** true for SuperTrend uptrends, false for downtrends. */
+  supertrend_uptrend?: boolean;
   symbol_search?: string;
   min_stochastic_k?: number;
   max_stochastic_k?: number;
+  min_williams_r?: number;
+  max_williams_r?: number;
+  min_cci?: number;
+  max_cci?: number;
+  min_mfi?: number;
+  max_mfi?: number;
   min_bandwidth?: number;
   max_bandwidth?: number;
   /** Drop rows whose |price_change_percent| exceeds this. Server-side. */
   max_abs_price_change_percent?: number;
+  /** Hide secondary share classes (GOOG, FOX, BRK-A, ...). */
+  primary_class_only?: boolean;
+  /** Only members of this index: "sp500", "nasdaq100", "dow30", "russell2000". */
+  index?: string;
+  /** Only members of this theme (see /api/themes), e.g. "ai". */
+  theme?: string;
+  /** Only symbols carrying at least one of these tags. */
+  tags?: string[];
+  /** Comma-separated asset types, or "all". Equities only when unset. */
+  asset_type?: string;
+  /** Which return min/max_return_pct apply to (default "1m"). */
+  return_period?: '1w' | '1m' | '3m' | 'ytd';
+  min_return_pct?: number;
+  max_return_pct?: number;
+  /** Which rank min/max_percentile apply to; required with either bound. */
+  percentile_metric?: PercentileMetric;
+  min_percentile?: number;
+  max_percentile?: number;
+  /** Minimum RS rating (percentiles.rs_rank). */
+  min_rs_rank?: number;
+  /** RFC 3339; screen the analysis history as of this time. */
+  as_of?: string;
   sort_by?: string;      // "market_cap", "price_change_percent", "rsi", "price"
   sort_order?: string;   // "asc" or "desc"
   page?: number;
@@ -201,6 +447,7 @@ export interface PaginationInfo {
   page_size: number;
   total: number;
   total_pages: number;
+  next_cursor?: string | null;
 }
 
 export interface MarketSummary {
@@ -210,6 +457,9 @@ export interface MarketSummary {
   most_oversold: StockAnalysis[];
   most_overbought: StockAnalysis[];
   mega_cap_highlights: StockAnalysis[];
+  top_weekly_gainers: StockAnalysis[];
+  top_monthly_gainers: StockAnalysis[];
+  top_ytd_gainers: StockAnalysis[];
   generated_at: string;
 }
 
@@ -230,6 +480,8 @@ export interface AIAnalysisResponse {
   error?: string;
 }
 
+export type EngineMode = 'normal' | 'degraded';
+
 export interface AnalysisProgress {
   total_stocks: number;
   analyzed: number;
@@ -240,9 +492,73 @@ export interface AnalysisProgress {
   last_cycle_completed?: string | null;
   last_successful_cycle?: string | null;
   last_error?: string | null;
+  mode?: EngineMode;
+  degraded_reason?: string | null;
   completion_percentage?: number;
 }
 
+export type SymbolCycleStatus = 'pending' | 'done' | 'failed' | 'skipped' | 'dead_lettered';
+
+export interface SymbolProgress {
+  symbol: string;
+  status: SymbolCycleStatus;
+  error?: string;
+  cycle_start: string;
+  updated_at: string;
+}
+
+export type RunOutcome = 'completed' | 'ended_early' | 'failed';
+
+/** One analysis cycle from `GET /api/runs`. Mirrors Rust `AnalysisRun`. */
+export interface AnalysisRun {
+  started_at: string;
+  completed_at: string;
+  duration_secs: number;
+  mode: EngineMode;
+  universe: number;
+  attempted: number;
+  skipped: number;
+  processed: number;
+  saved: number;
+  /** Symbol-level failures (yahoo, analysis and database). */
+  errors: number;
+  /** yahoo, nasdaq, analysis, database, or an optional stage's name. */
+  errors_by_source: Record<string, number>;
+  outcome: RunOutcome;
+  error?: string | null;
+}
+
+export interface RunSummary {
+  runs: number;
+  completed: number;
+  ended_early: number;
+  failed: number;
+  success_rate?: number | null;
+  avg_duration_secs?: number | null;
+  error_rate?: number | null;
+  errors_by_source: Record<string, number>;
+}
+
+export interface AnalysisRunsResponse {
+  success: boolean;
+  count: number;
+  summary: RunSummary;
+  runs: AnalysisRun[];
+  pagination: PaginationInfo;
+}
+
+export interface FailureRecord {
+  error: string;
+  failed_at: string;
+}
+
+export interface DeadLetter {
+  symbol: string;
+  consecutive_failures: number;
+  errors: FailureRecord[];
+  dead_lettered_at?: string | null;
+}
+
 export interface HealthStatus {
   status: string;
   database: string;
@@ -251,6 +567,25 @@ export interface HealthStatus {
   last_cycle_completed?: string | null;
   last_successful_cycle?: string | null;
   last_error?: string | null;
+  mode?: EngineMode;
+  degraded_reason?: string | null;
+  maintenance?: MaintenanceStatus;
+  yahoo_budget?: YahooBudgetStats | null;
+}
+
+/** Read-only maintenance mode. Mirrors Rust `MaintenanceStatus`. */
+export interface MaintenanceStatus {
+  read_only: boolean;
+  reason?: string;
+  since?: string;
+}
+
+export interface YahooBudgetStats {
+  per_minute: number;
+  available: number;
+  interactive_granted: number;
+  background_granted: number;
+  background_wait_ms: number;
 }
 
 export interface EarningsCalendarRow {
@@ -424,6 +759,232 @@ export interface IndexHeatmapResponse {
   error?: string;
 }
 
+export interface IndexPerformance {
+  index_id: string;
+  date: string;
+  equal_weight_return_pct: number;
+  cap_weight_return_pct: number;
+  /** Equal minus cap weight; negative when a few mega caps carry the index. */
+  divergence_pct: number;
+  constituents: number;
+  advancers: number;
+  decliners: number;
+  updated_at: string;
+}
+
+export interface IndexPerformanceResponse {
+  success: boolean;
+  index_id?: string;
+  days?: number;
+  latest?: IndexPerformance | null;
+  cumulative?: {
+    equal_weight_return_pct: number;
+    cap_weight_return_pct: number;
+    divergence_pct: number;
+  };
+  history?: IndexPerformance[];
+  error?: string;
+}
+
+export interface IndexContribution {
+  symbol: string;
+  change_percent: number;
+  weight_pct: number;
+  /** Percentage points of the index return. */
+  contribution_pct: number;
+  contribution_points: number | null;
+}
+
+export interface IndexContributors {
+  index_id: string;
+  date: string;
+  index_return_pct: number;
+  previous_close: number | null;
+  index_points: number | null;
+  top: IndexContribution[];
+  bottom: IndexContribution[];
+  updated_at: string;
+}
+
+export interface IndexContributorsResponse {
+  success: boolean;
+  contributors?: IndexContributors;
+  error?: string;
+}
+
+export interface SymbolNote {
+  symbol: string;
+  notes: string;
+  tags: string[];
+  updated_at: string;
+}
+
+export interface TagCount {
+  tag: string;
+  symbols: number;
+}
+
+export interface Theme {
+  id: string;
+  name: string;
+  description: string;
+  symbols: string[];
+  updated_at: string;
+}
+
+export interface ThemeInput {
+  name: string;
+  description?: string;
+  symbols: string[];
+}
+
+export type ThemePerformanceResponse = Omit<IndexPerformanceResponse, 'index_id'> & {
+  theme_id?: string;
+};
+
+export interface ScreenRow {
+  symbol: string;
+  price: number;
+  price_change_percent: number | null;
+  market_cap: number | null;
+  sector: string | null;
+  rsi: number | null;
+  /** The screen's ranking value; see ScreenResult.metric. */
+  value: number;
+}
+
+export interface ScreenResult {
+  name: string;
+  description: string;
+  metric: string;
+  total: number;
+  rows: ScreenRow[];
+  computed_at: string;
+}
+
+export interface ScreenSummary {
+  name: string;
+  description: string;
+  metric: string;
+  total: number | null;
+  computed_at: string | null;
+}
+
+export interface QueryStat {
+  command: string;
+  collection: string;
+  /** Sorted top-level filter keys, e.g. "{market_cap, sector}". */
+  shape: string;
+  count: number;
+  failures: number;
+  slow: number;
+  avg_ms: number;
+  max_ms: number;
+  total_ms: number;
+}
+
+export interface DbStatsResponse {
+  success: boolean;
+  slow_query_ms: number;
+  total_queries: number;
+  slow_queries: number;
+  queries: QueryStat[];
+}
+
+export interface CollectionStats {
+  name: string;
+  count: number;
+  size_bytes: number;
+  storage_bytes: number;
+  index_bytes: number;
+  /** Days kept, for collections pruned by HISTORY_RETENTION_DAYS. */
+  retention_days?: number | null;
+}
+
+export interface CollectionSizesResponse {
+  success: boolean;
+  history_retention_days: number;
+  total_storage_bytes: number;
+  collections: CollectionStats[];
+}
+
+export interface CorruptDocument {
+  /** Collection the document was read from. */
+  source: string;
+  document_id: string;
+  symbol?: string | null;
+  /** Why the strict read failed. */
+  error: string;
+  /** Whether repairing known legacy shapes in memory recovered it. */
+  recovered: boolean;
+  seen_at: string;
+}
+
+export interface BackupCollection {
+  collection: string;
+  documents: number;
+  size_bytes: number;
+}
+
+export interface BackupManifest {
+  name: string;
+  database: string;
+  created_at: string;
+  collections: BackupCollection[];
+}
+
+export interface BackupsResponse {
+  success: boolean;
+  count: number;
+  retention: number;
+  backups: BackupManifest[];
+}
+
+export interface Week52Event {
+  symbol: string;
+  kind: 'high' | 'low';
+  date: string;
+  price: number;
+  close: number;
+  previous_extreme: number;
+  recorded_at: string;
+}
+
+export interface Week52Breadth {
+  date: string;
+  new_highs: number;
+  new_lows: number;
+}
+
+export interface Week52EventsResponse {
+  success: boolean;
+  since: string;
+  total_events: number;
+  breadth: Week52Breadth[];
+  events: Week52Event[];
+}
+
+export interface ExternalSignal {
+  _id?: { $oid: string };
+  symbol: string;
+  /** Sender, e.g. "tradingview". */
+  source: string;
+  /** Lowercased label such as "buy"; "note" for message-only signals. */
+  signal: string;
+  /** Strategy or indicator name, e.g. from a TradingView strategy alert. */
+  strategy?: string;
+  message?: string;
+  price?: number;
+  received_at: string;
+}
+
+export interface ExternalSignalsResponse {
+  success: boolean;
+  since: string;
+  count: number;
+  signals: ExternalSignal[];
+}
+
 // Time period options for heatmap
 export type HeatmapPeriod = '1d' | '1w' | '1m' | '6m' | '1y';
 
@@ -456,6 +1017,8 @@ export interface NotificationChannel {
   username?: string;
   avatar_url?: string;
   enabled: boolean;
+  /** Also receives the end-of-day watchlist recap. */
+  daily_recap?: boolean;
   created_at: string;
 }
 
@@ -467,6 +1030,30 @@ export interface Watchlist {
   updated_at: string;
 }
 
+/** One symbol of a watchlist recap. Mirrors Rust `RecapEntry`. */
+export interface RecapEntry {
+  symbol: string;
+  close: number;
+  change?: number | null;
+  change_percent?: number | null;
+  /** Built-in strategies firing, e.g. `rsi_oversold`. */
+  signals: string[];
+  /** Alert rules that notified for this symbol during the session. */
+  alerts: string[];
+  top_news?: NasdaqNewsItem | null;
+}
+
+/** End-of-day watchlist recap. Mirrors Rust `WatchlistRecap`. */
+export interface WatchlistRecap {
+  watchlist_id?: string | null;
+  watchlist: string;
+  date: string;
+  /** Biggest movers first. */
+  entries: RecapEntry[];
+  missing: string[];
+  generated_at: string;
+}
+
 /** Open position. Mirrors Rust `Position`. */
 export interface Position {
   _id?: string;
@@ -488,6 +1075,84 @@ export interface PositionView extends Position {
   unrealized_pnl_pct: number | null;
 }
 
+export type LotMethod = 'fifo' | 'lifo' | 'specific';
+
+/** Mirrors Rust `RealizedGain` — one lot's share of a sell. */
+export interface RealizedGain {
+  _id?: string;
+  symbol: string;
+  position_id: string;
+  quantity: number;
+  cost_basis_per_share: number;
+  proceeds_per_share: number;
+  realized_pnl: number;
+  opened_at: string;
+  sold_at: string;
+  holding_days: number;
+  term: 'short_term' | 'long_term';
+  method: LotMethod;
+}
+
+export interface RealizedSummary {
+  sales: number;
+  proceeds: number;
+  cost_basis: number;
+  realized_pnl: number;
+  short_term_pnl: number;
+  long_term_pnl: number;
+}
+
+export type RebalanceTarget = 'equal_weight' | 'sector_neutral';
+
+/** Mirrors Rust `RebalanceTrade`. */
+export interface RebalanceTrade {
+  symbol: string;
+  side: 'buy' | 'sell';
+  shares: number;
+  price: number;
+  trade_value: number;
+  current_weight_pct: number;
+  target_weight_pct: number;
+  estimated_cost: number;
+}
+
+export interface RebalancePlan {
+  target: RebalanceTarget;
+  total_value: number;
+  trades: RebalanceTrade[];
+  estimated_total_cost: number;
+  turnover_pct: number;
+}
+
+/** Mirrors Rust `ValuationSnapshot` — one per market date. */
+export interface ValuationSnapshot {
+  date: string;
+  total_value: number;
+  total_cost_basis: number;
+  holdings: { symbol: string; quantity: number; price: number; market_value: number; cost_basis: number }[];
+  unpriced: string[];
+  taken_at: string;
+}
+
+export type BrokerFormat = 'ibkr_flex' | 'schwab_csv';
+
+/** Mirrors Rust `BrokerImport` (dry-run output). */
+export interface BrokerImportPreview {
+  broker: BrokerFormat;
+  positions: { symbol: string; quantity: number; cost_basis_per_share: number; opened_at: string | null }[];
+  trades: { symbol: string; side: 'buy' | 'sell'; quantity: number; price: number; traded_at: string }[];
+  skipped: string[];
+}
+
+export interface BrokerImportResult {
+  broker: BrokerFormat;
+  mode: 'positions' | 'trades';
+  lots_created: number;
+  realized: RealizedSummary;
+  skipped: string[];
+  errors: string[];
+}
+
 export interface CreatePositionInput {
   symbol: string;
   quantity: number;
@@ -522,7 +1187,13 @@ export type Condition =
   | { type: 'is_overbought' }
   | { type: 'volume_above'; value: number }
   | { type: 'sector_equals'; sector: string }
-  | { type: 'drop_from_high_pct'; value: number };
+  | { type: 'drop_from_high_pct'; value: number }
+  | { type: 'earnings_within_days'; days: number }
+  | { type: 'ex_dividend_within_days'; days: number }
+  | { type: 'trailing_stop_pct'; value: number }
+  | { type: 'price_near_sma'; period: number; within_pct: number }
+  | { type: 'volume_ratio_above'; value: number }
+  | { type: 'external_signal'; signal?: string; within_minutes: number };
 
 export type ConditionType = Condition['type'];
 
@@ -555,10 +1226,37 @@ export interface AlertRule {
   channel_ids: string[];
   message_template?: string | null;
   require_consecutive: number;
+  snoozed_until?: string | null;
+  max_triggers?: number | null;
+  trigger_count: number;
+  muted_symbols?: string[];
   created_at: string;
   updated_at: string;
 }
 
+export type AlertAuditAction =
+  | 'created'
+  | 'updated'
+  | 'enabled'
+  | 'disabled'
+  | 'snoozed'
+  | 'unsnoozed'
+  | 'muted'
+  | 'unmuted'
+  | 'triggered'
+  | 'exhausted'
+  | 'reset'
+  | 'deleted';
+
+export interface AlertAuditEvent {
+  _id?: string;
+  rule_id: string;
+  action: AlertAuditAction;
+  symbol?: string;
+  detail?: string;
+  at: string;
+}
+
 export interface DeliveryResult {
   channel_id: string;
   channel_name: string;
@@ -601,6 +1299,12 @@ export const CONDITION_LABELS: Record<ConditionType, string> = {
   volume_above: 'Volume above',
   sector_equals: 'Sector equals',
   drop_from_high_pct: 'Down % from 52w high',
+  earnings_within_days: 'Earnings within N days',
+  ex_dividend_within_days: 'Ex-dividend within N days',
+  trailing_stop_pct: 'Trailing stop: down % from peak',
+  price_near_sma: 'Price within % of SMA',
+  volume_ratio_above: 'Volume × average above',
+  external_signal: 'External signal within N min',
 };
 
 /** Construct a default value for a freshly-picked condition type. */
@@ -624,6 +1328,12 @@ export function defaultCondition(type: ConditionType): Condition {
     case 'volume_above': return { type, value: 1_000_000 };
     case 'sector_equals': return { type, sector: 'Technology' };
     case 'drop_from_high_pct': return { type, value: 20 };
+    case 'earnings_within_days': return { type, days: 2 };
+    case 'ex_dividend_within_days': return { type, days: 1 };
+    case 'trailing_stop_pct': return { type, value: 10 };
+    case 'price_near_sma': return { type, period: 50, within_pct: 2 };
+    case 'volume_ratio_above': return { type, value: 1.5 };
+    case 'external_signal': return { type, signal: 'buy', within_minutes: 60 };
   }
 }
 
@@ -0,0 +1,979 @@
+//! Technical indicator math shared by the server, its WASM build and any
+//! embedded frontend, so every consumer computes the exact same RSI, MACD,
+//! Bollinger bands and stochastics.
+//!
+//! Functions take any slice of [`Bar`]s: the server implements it for its
+//! `HistoricalPrice`, and a plain `f64` counts as a bar whose high, low and
+//! close are all that value, with no volume. [`RsiState`], [`EmaState`] and
+//! [`MacdState`] carry RSI and MACD forward one close at a time for callers
+//! that keep them between runs. Without the default `std` feature the crate is
+//! `no_std` + `alloc`; enable `serde` to (de)serialize the result types.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+/// One price bar, as far as the indicators are concerned.
+pub trait Bar {
+    fn close(&self) -> f64;
+    fn high(&self) -> f64;
+    fn low(&self) -> f64;
+
+    /// Shares traded. Bars without volume report zero, which leaves the
+    /// volume indicators (VWAP, OBV) undefined.
+    fn volume(&self) -> f64 {
+        0.0
+    }
+}
+
+/// A bare close; high and low are the close itself.
+impl Bar for f64 {
+    fn close(&self) -> f64 {
+        *self
+    }
+
+    fn high(&self) -> f64 {
+        *self
+    }
+
+    fn low(&self) -> f64 {
+        *self
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MACDIndicator {
+    pub macd_line: f64,
+    pub signal_line: f64,
+    pub histogram: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BollingerBands {
+    pub upper_band: f64,
+    pub lower_band: f64,
+    pub middle_band: f64,
+    pub bandwidth: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StochasticOscillator {
+    pub k_line: f64,
+    pub d_line: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnBalanceVolume {
+    /// Running OBV at the latest bar, counted from the first bar.
+    pub obv: f64,
+    /// Least-squares slope of OBV over the slope window, in shares per bar.
+    pub slope: f64,
+}
+
+/// Nearest swing levels around the latest close. Either side is `None`
+/// when no recent pivot lies on it (e.g. at an all-time high).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SupportResistance {
+    /// Highest pivot level below the close.
+    pub support: Option<f64>,
+    /// Lowest pivot level above the close.
+    pub resistance: Option<f64>,
+}
+
+/// SuperTrend at the latest bar: an ATR band below the price in an uptrend
+/// and above it in a downtrend, flipping when a close crosses it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuperTrend {
+    /// The active band: the trailing stop in an uptrend, the ceiling in a
+    /// downtrend.
+    pub value: f64,
+    pub uptrend: bool,
+}
+
+/// Bars on each side a pivot high (low) must exceed (undercut).
+pub const PIVOT_STRENGTH: usize = 5;
+/// Most recent bars searched for pivots.
+pub const PIVOT_LOOKBACK: usize = 120;
+
+/// Most recent crossing of a fast moving average over a slow one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CrossSignal {
+    /// Fast average crossed above the slow one.
+    GoldenCross,
+    /// Fast average crossed below the slow one.
+    DeathCross,
+    #[default]
+    None,
+}
+
+pub struct TechnicalIndicators;
+
+impl TechnicalIndicators {
+    /// Calculate RSI (Relative Strength Index) using Wilder's Smoothing
+    /// This matches TradingView's RSI calculation
+    pub fn calculate_rsi<B: Bar>(prices: &[B], period: usize) -> Option<f64> {
+        RsiState::seed(prices, period).map(|state| state.value())
+    }
+
+    /// Calculate Simple Moving Average
+    pub fn calculate_sma<B: Bar>(prices: &[B], period: usize) -> Option<f64> {
+        if prices.len() < period {
+            return None;
+        }
+
+        let sum: f64 = prices.iter().rev().take(period).map(Bar::close).sum();
+        Some(sum / period as f64)
+    }
+
+    /// Calculate MACD (Moving Average Convergence Divergence) with a real
+    /// signal line computed as EMA(9) of the MACD series.
+    ///
+    /// Requires at least 34 bars (`26 + 9 - 1`) so the signal EMA has enough
+    /// MACD samples to seed itself.
+    pub fn calculate_macd<B: Bar>(prices: &[B]) -> Option<MACDIndicator> {
+        Self::calculate_macd_with(prices, 12, 26, 9)
+    }
+
+    /// Calculate MACD with custom fast/slow EMA and signal periods. Needs
+    /// `slow + signal - 1` bars and `fast < slow`.
+    pub fn calculate_macd_with<B: Bar>(
+        prices: &[B],
+        fast: usize,
+        slow: usize,
+        signal: usize,
+    ) -> Option<MACDIndicator> {
+        MacdState::seed(prices, fast, slow, signal).map(|state| state.value())
+    }
+
+    /// Calculate Exponential Moving Average — chronological, seeded with the
+    /// SMA of the first `period` samples. Returns `None` if `prices.len() < period`.
+    pub fn calculate_ema<B: Bar>(prices: &[B], period: usize) -> Option<f64> {
+        if prices.len() < period {
+            return None;
+        }
+        EmaState::seed(prices, period).map(|state| state.value)
+    }
+
+    /// Calculate Bollinger Bands
+    pub fn calculate_bollinger_bands<B: Bar>(
+        prices: &[B],
+        period: usize,
+        std_dev_multiplier: f64,
+    ) -> Option<BollingerBands> {
+        if prices.len() < period {
+            return None;
+        }
+
+        let recent: Vec<f64> = prices.iter().rev().take(period).map(Bar::close).collect();
+        let middle_band = recent.iter().sum::<f64>() / period as f64;
+
+        let variance = recent
+            .iter()
+            .map(|x| (x - middle_band) * (x - middle_band))
+            .sum::<f64>()
+            / period as f64;
+        let std_dev = sqrt(variance);
+
+        let upper_band = middle_band + std_dev_multiplier * std_dev;
+        let lower_band = middle_band - std_dev_multiplier * std_dev;
+        let bandwidth = if middle_band > 0.0 {
+            (upper_band - lower_band) / middle_band * 100.0
+        } else {
+            0.0
+        };
+
+        Some(BollingerBands {
+            upper_band,
+            lower_band,
+            middle_band,
+            bandwidth,
+        })
+    }
+
+    /// Calculate Stochastic Oscillator (%K and %D)
+    pub fn calculate_stochastic<B: Bar>(
+        prices: &[B],
+        k_period: usize,
+        d_period: usize,
+    ) -> Option<StochasticOscillator> {
+        let needed = k_period + d_period - 1;
+        if prices.len() < needed {
+            return None;
+        }
+
+        // Calculate multiple %K values for the D period
+        let mut k_values = Vec::with_capacity(d_period);
+
+        for i in 0..d_period {
+            let end = prices.len() - i;
+            let start = end.saturating_sub(k_period);
+            let window = &prices[start..end];
+
+            let highest_high = window
+                .iter()
+                .map(Bar::high)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let lowest_low = window.iter().map(Bar::low).fold(f64::INFINITY, f64::min);
+            let close = window.last()?.close();
+
+            let range = highest_high - lowest_low;
+            let k = if range > 0.0 {
+                ((close - lowest_low) / range) * 100.0
+            } else {
+                50.0
+            };
+            k_values.push(k);
+        }
+
+        let k_line = k_values[0]; // Most recent %K
+        let d_line = k_values.iter().sum::<f64>() / k_values.len() as f64;
+
+        Some(StochasticOscillator { k_line, d_line })
+    }
+
+    /// Calculate Williams %R over the last `period` bars: where the close
+    /// sits in the period's high-low range, from 0 (at the high) to -100
+    /// (at the low). A flat range reads -50.
+    pub fn calculate_williams_r<B: Bar>(prices: &[B], period: usize) -> Option<f64> {
+        if period == 0 || prices.len() < period {
+            return None;
+        }
+        let window = &prices[prices.len() - period..];
+        let highest_high = window
+            .iter()
+            .map(Bar::high)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let lowest_low = window.iter().map(Bar::low).fold(f64::INFINITY, f64::min);
+        let range = highest_high - lowest_low;
+        if range <= 0.0 {
+            return Some(-50.0);
+        }
+        Some((highest_high - window.last()?.close()) / range * -100.0)
+    }
+
+    /// Calculate CCI (Commodity Channel Index) over the last `period` bars:
+    /// the typical price's distance from its SMA in units of 0.015 mean
+    /// deviations, so roughly 70-80% of readings fall within ±100.
+    pub fn calculate_cci<B: Bar>(prices: &[B], period: usize) -> Option<f64> {
+        if period == 0 || prices.len() < period {
+            return None;
+        }
+        let typical: Vec<f64> = prices[prices.len() - period..]
+            .iter()
+            .map(typical_price)
+            .collect();
+        let mean = typical.iter().sum::<f64>() / period as f64;
+        let mean_deviation =
+            typical.iter().map(|tp| (tp - mean).abs()).sum::<f64>() / period as f64;
+        if mean_deviation == 0.0 {
+            return Some(0.0);
+        }
+        Some((typical.last()? - mean) / (0.015 * mean_deviation))
+    }
+
+    /// Calculate MFI (Money Flow Index), a volume-weighted RSI over the
+    /// typical price. Needs `period + 1` bars and some volume.
+    pub fn calculate_mfi<B: Bar>(prices: &[B], period: usize) -> Option<f64> {
+        if period == 0 || prices.len() < period + 1 {
+            return None;
+        }
+        let window = &prices[prices.len() - period - 1..];
+        let (mut positive, mut negative) = (0.0, 0.0);
+        for pair in window.windows(2) {
+            let (previous, current) = (typical_price(&pair[0]), typical_price(&pair[1]));
+            let flow = current * pair[1].volume();
+            if current > previous {
+                positive += flow;
+            } else if current < previous {
+                negative += flow;
+            }
+        }
+        if window[1..].iter().all(|bar| bar.volume() <= 0.0) {
+            return None;
+        }
+        if negative == 0.0 {
+            return Some(if positive == 0.0 { 50.0 } else { 100.0 });
+        }
+        Some(100.0 - 100.0 / (1.0 + positive / negative))
+    }
+
+    /// Find the most recent crossing of the `fast` SMA over the `slow` SMA,
+    /// with the index of the bar it happened on. Bars where the averages
+    /// are equal don't count as a cross on their own.
+    pub fn calculate_ma_cross<B: Bar>(
+        prices: &[B],
+        fast: usize,
+        slow: usize,
+    ) -> Option<(usize, CrossSignal)> {
+        if fast == 0 || fast >= slow || prices.len() <= slow {
+            return None;
+        }
+        let closes: Vec<f64> = prices.iter().map(Bar::close).collect();
+        let fast_sma = sma_series(&closes, fast);
+        let slow_sma = sma_series(&closes, slow);
+        // Both series end at the last bar; align them on the slow one.
+        let offset = slow - fast;
+
+        let mut last_side = 0.0;
+        let mut cross = None;
+        for (i, slow_value) in slow_sma.iter().enumerate() {
+            let diff = fast_sma[i + offset] - slow_value;
+            if diff == 0.0 {
+                continue;
+            }
+            let side = diff.signum();
+            if last_side != 0.0 && side != last_side {
+                let signal = if side > 0.0 {
+                    CrossSignal::GoldenCross
+                } else {
+                    CrossSignal::DeathCross
+                };
+                cross = Some((i + slow - 1, signal));
+            }
+            last_side = side;
+        }
+        cross
+    }
+
+    /// SuperTrend with Wilder's ATR over `period` bars, bands `multiplier`
+    /// ATRs from the bar's high-low midpoint, and the index of the bar the
+    /// current trend began on (`None` if it holds since the first bar with
+    /// an ATR). A band only ratchets towards the price until a close crosses
+    /// it, which flips the trend.
+    pub fn calculate_supertrend<B: Bar>(
+        prices: &[B],
+        period: usize,
+        multiplier: f64,
+    ) -> Option<(SuperTrend, Option<usize>)> {
+        if period == 0 || prices.len() <= period {
+            return None;
+        }
+        let true_range = |i: usize| {
+            let bar = &prices[i];
+            let range = bar.high() - bar.low();
+            match i.checked_sub(1).map(|p| prices[p].close()) {
+                Some(previous) => range
+                    .max((bar.high() - previous).abs())
+                    .max((bar.low() - previous).abs()),
+                None => range,
+            }
+        };
+
+        let mut atr = (0..period).map(true_range).sum::<f64>() / period as f64;
+        let mut upper = f64::INFINITY;
+        let mut lower = f64::NEG_INFINITY;
+        let mut uptrend = true;
+        let mut flip = None;
+        for i in period - 1..prices.len() {
+            if i >= period {
+                atr = (atr * (period - 1) as f64 + true_range(i)) / period as f64;
+            }
+            let bar = &prices[i];
+            let mid = (bar.high() + bar.low()) / 2.0;
+            let previous_close = prices[i.saturating_sub(1)].close();
+            let basic_upper = mid + multiplier * atr;
+            let basic_lower = mid - multiplier * atr;
+            if basic_upper < upper || previous_close > upper {
+                upper = basic_upper;
+            }
+            if basic_lower > lower || previous_close < lower {
+                lower = basic_lower;
+            }
+            if i == period - 1 {
+                uptrend = bar.close() >= mid;
+            } else if uptrend && bar.close() < lower {
+                uptrend = false;
+                flip = Some(i);
+            } else if !uptrend && bar.close() > upper {
+                uptrend = true;
+                flip = Some(i);
+            }
+        }
+        let value = if uptrend { lower } else { upper };
+        Some((SuperTrend { value, uptrend }, flip))
+    }
+
+    /// Find the nearest support and resistance from swing pivots in the
+    /// last [`PIVOT_LOOKBACK`] bars. A pivot high is a high no lower than
+    /// the [`PIVOT_STRENGTH`] highs on either side, a pivot low the mirror
+    /// image. Broken levels swap roles, so support is the highest pivot of
+    /// either kind below the latest close and resistance the lowest above.
+    pub fn detect_support_resistance<B: Bar>(prices: &[B]) -> Option<SupportResistance> {
+        let k = PIVOT_STRENGTH;
+        if prices.len() < 2 * k + 2 {
+            return None;
+        }
+        let close = prices.last()?.close();
+        let recent = &prices[prices.len().saturating_sub(PIVOT_LOOKBACK)..];
+
+        let mut levels = Vec::new();
+        for i in k..recent.len().saturating_sub(k) {
+            let window = &recent[i - k..=i + k];
+            let (high, low) = (recent[i].high(), recent[i].low());
+            if window.iter().all(|b| b.high() <= high) {
+                levels.push(high);
+            }
+            if window.iter().all(|b| b.low() >= low) {
+                levels.push(low);
+            }
+        }
+        let support = levels
+            .iter()
+            .copied()
+            .filter(|level| *level < close)
+            .reduce(f64::max);
+        let resistance = levels
+            .iter()
+            .copied()
+            .filter(|level| *level > close)
+            .reduce(f64::min);
+        Some(SupportResistance {
+            support,
+            resistance,
+        })
+    }
+
+    /// Calculate VWAP (Volume Weighted Average Price) over all `prices`,
+    /// using the typical price (high + low + close) / 3
+    pub fn calculate_vwap<B: Bar>(prices: &[B]) -> Option<f64> {
+        let (mut value, mut volume) = (0.0, 0.0);
+        for bar in prices {
+            value += typical_price(bar) * bar.volume();
+            volume += bar.volume();
+        }
+        (volume > 0.0).then(|| value / volume)
+    }
+
+    /// Calculate the OBV (On-Balance Volume) series: starts at zero and adds
+    /// each bar's volume on an up close, subtracts it on a down close
+    pub fn obv_series<B: Bar>(prices: &[B]) -> Vec<f64> {
+        let mut series = Vec::with_capacity(prices.len());
+        let mut obv = 0.0;
+        for (i, bar) in prices.iter().enumerate() {
+            if i > 0 {
+                let previous = prices[i - 1].close();
+                if bar.close() > previous {
+                    obv += bar.volume();
+                } else if bar.close() < previous {
+                    obv -= bar.volume();
+                }
+            }
+            series.push(obv);
+        }
+        series
+    }
+
+    /// Calculate the latest OBV and its slope over the last `slope_period`
+    /// bars. `None` without enough bars or without volume.
+    pub fn calculate_obv<B: Bar>(prices: &[B], slope_period: usize) -> Option<OnBalanceVolume> {
+        if slope_period < 2
+            || prices.len() < slope_period
+            || prices.iter().all(|b| b.volume() <= 0.0)
+        {
+            return None;
+        }
+        let series = Self::obv_series(prices);
+        let window = &series[series.len() - slope_period..];
+        let n = slope_period as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = window.iter().sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (i, y) in window.iter().enumerate() {
+            let dx = i as f64 - mean_x;
+            cov += dx * (y - mean_y);
+            var += dx * dx;
+        }
+        Some(OnBalanceVolume {
+            obv: *series.last()?,
+            slope: cov / var,
+        })
+    }
+
+    /// Calculate Pearson correlation coefficient between two price series
+    pub fn calculate_correlation(prices_a: &[f64], prices_b: &[f64]) -> Option<f64> {
+        let n = prices_a.len().min(prices_b.len());
+        if n < 2 {
+            return None;
+        }
+
+        let a = &prices_a[..n];
+        let b = &prices_b[..n];
+
+        let mean_a = a.iter().sum::<f64>() / n as f64;
+        let mean_b = b.iter().sum::<f64>() / n as f64;
+
+        let mut cov = 0.0;
+        let mut var_a = 0.0;
+        let mut var_b = 0.0;
+
+        for i in 0..n {
+            let da = a[i] - mean_a;
+            let db = b[i] - mean_b;
+            cov += da * db;
+            var_a += da * da;
+            var_b += db * db;
+        }
+
+        let denom = sqrt(var_a * var_b);
+        if denom == 0.0 {
+            return None;
+        }
+
+        Some(cov / denom)
+    }
+
+    /// Determine if stock is oversold (RSI < 30)
+    pub fn is_oversold(rsi: Option<f64>) -> bool {
+        rsi.is_some_and(|r| r < 30.0)
+    }
+
+    /// Determine if stock is overbought (RSI > 70)
+    pub fn is_overbought(rsi: Option<f64>) -> bool {
+        rsi.is_some_and(|r| r > 70.0)
+    }
+
+    /// Determine if the stochastic is oversold (%K < 20)
+    pub fn is_stoch_oversold(stochastic: Option<&StochasticOscillator>) -> bool {
+        stochastic.is_some_and(|s| s.k_line < 20.0)
+    }
+
+    /// Determine if the stochastic is overbought (%K > 80)
+    pub fn is_stoch_overbought(stochastic: Option<&StochasticOscillator>) -> bool {
+        stochastic.is_some_and(|s| s.k_line > 80.0)
+    }
+}
+
+/// EMA that can be carried forward one close at a time, so a caller that
+/// keeps it can skip re-reading the history it was seeded from.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmaState {
+    pub period: usize,
+    /// EMA at the last close fed in.
+    pub value: f64,
+}
+
+impl EmaState {
+    /// Seed from `prices` exactly as [`TechnicalIndicators::calculate_ema`]
+    /// does. `None` with fewer than `period` bars.
+    pub fn seed<B: Bar>(prices: &[B], period: usize) -> Option<Self> {
+        let closes: Vec<f64> = prices.iter().map(Bar::close).collect();
+        Self::seed_closes(&closes, period)
+    }
+
+    fn seed_closes(closes: &[f64], period: usize) -> Option<Self> {
+        Some(Self {
+            period,
+            value: *ema_series(closes, period).last()?,
+        })
+    }
+
+    /// Fold in the next close and return the new EMA.
+    pub fn update(&mut self, close: f64) -> f64 {
+        let k = 2.0 / (self.period as f64 + 1.0);
+        self.value = (close - self.value) * k + self.value;
+        self.value
+    }
+}
+
+/// Wilder RSI that can be carried forward one close at a time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RsiState {
+    pub period: usize,
+    pub avg_gain: f64,
+    pub avg_loss: f64,
+    pub last_close: f64,
+}
+
+impl RsiState {
+    /// Seed from `prices` exactly as [`TechnicalIndicators::calculate_rsi`]
+    /// does. `None` with fewer than `period + 1` bars.
+    pub fn seed<B: Bar>(prices: &[B], period: usize) -> Option<Self> {
+        if period == 0 || prices.len() < period + 1 {
+            return None;
+        }
+
+        // Initial averages are the SMA of the first `period` changes.
+        let (mut gains, mut losses) = (0.0, 0.0);
+        for i in 1..=period {
+            let change = prices[i].close() - prices[i - 1].close();
+            if change > 0.0 {
+                gains += change;
+            } else {
+                losses += change.abs();
+            }
+        }
+        let mut state = Self {
+            period,
+            avg_gain: gains / period as f64,
+            avg_loss: losses / period as f64,
+            last_close: prices[period].close(),
+        };
+        for bar in &prices[period + 1..] {
+            state.update(bar.close());
+        }
+        Some(state)
+    }
+
+    /// Fold in the next close with Wilder's smoothing and return the new RSI.
+    pub fn update(&mut self, close: f64) -> f64 {
+        let change = close - self.last_close;
+        let gain = if change > 0.0 { change } else { 0.0 };
+        let loss = if change < 0.0 { change.abs() } else { 0.0 };
+
+        // Wilder's smoothing: (previous_avg * (period - 1) + current_value) / period
+        let period = self.period as f64;
+        self.avg_gain = (self.avg_gain * (period - 1.0) + gain) / period;
+        self.avg_loss = (self.avg_loss * (period - 1.0) + loss) / period;
+        self.last_close = close;
+        self.value()
+    }
+
+    /// RSI at the last close fed in.
+    pub fn value(&self) -> f64 {
+        if self.avg_loss == 0.0 {
+            if self.avg_gain == 0.0 {
+                return 50.0; // No movement
+            }
+            return 100.0; // All gains, no losses
+        }
+        if self.avg_gain == 0.0 {
+            return 0.0; // All losses, no gains
+        }
+        let rs = self.avg_gain / self.avg_loss;
+        100.0 - (100.0 / (1.0 + rs))
+    }
+}
+
+/// MACD's three EMAs, carried forward one close at a time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MacdState {
+    pub fast: EmaState,
+    pub slow: EmaState,
+    /// EMA of the MACD line.
+    pub signal: EmaState,
+}
+
+impl MacdState {
+    /// Seed from `prices` exactly as [`TechnicalIndicators::calculate_macd_with`]
+    /// does. Needs `slow + signal - 1` bars and `fast < slow`.
+    pub fn seed<B: Bar>(prices: &[B], fast: usize, slow: usize, signal: usize) -> Option<Self> {
+        if fast == 0 || signal == 0 || fast >= slow || prices.len() < slow + signal - 1 {
+            return None;
+        }
+
+        let closes: Vec<f64> = prices.iter().map(Bar::close).collect();
+        let ema_fast = ema_series(&closes, fast);
+        let ema_slow = ema_series(&closes, slow);
+
+        // `ema_fast` starts at index fast-1 in `closes`; `ema_slow` at slow-1.
+        // Align `ema_fast` forward by `slow - fast` so the two series start on
+        // the same bar.
+        let offset = slow - fast;
+        let macd_series: Vec<f64> = ema_slow
+            .iter()
+            .enumerate()
+            .map(|(i, &slow)| ema_fast[i + offset] - slow)
+            .collect();
+
+        Some(Self {
+            fast: EmaState {
+                period: fast,
+                value: *ema_fast.last()?,
+            },
+            slow: EmaState {
+                period: slow,
+                value: *ema_slow.last()?,
+            },
+            signal: EmaState::seed_closes(&macd_series, signal)?,
+        })
+    }
+
+    /// Fold in the next close and return the new MACD.
+    pub fn update(&mut self, close: f64) -> MACDIndicator {
+        let macd_line = self.fast.update(close) - self.slow.update(close);
+        self.signal.update(macd_line);
+        self.value()
+    }
+
+    /// MACD at the last close fed in.
+    pub fn value(&self) -> MACDIndicator {
+        let macd_line = self.fast.value - self.slow.value;
+        let signal_line = self.signal.value;
+        MACDIndicator {
+            macd_line,
+            signal_line,
+            histogram: macd_line - signal_line,
+        }
+    }
+}
+
+/// (high + low + close) / 3
+fn typical_price<B: Bar>(bar: &B) -> f64 {
+    (bar.high() + bar.low() + bar.close()) / 3.0
+}
+
+/// Compute the SMA series for `closes`; element `i` averages
+/// `closes[i..i + period]`, so the vector has `closes.len() - period + 1`
+/// entries (empty if there aren't enough samples).
+fn sma_series(closes: &[f64], period: usize) -> Vec<f64> {
+    if closes.len() < period || period == 0 {
+        return Vec::new();
+    }
+    let mut out = Vec::with_capacity(closes.len() - period + 1);
+    let mut sum: f64 = closes[..period].iter().sum();
+    out.push(sum / period as f64);
+    for i in period..closes.len() {
+        sum += closes[i] - closes[i - period];
+        out.push(sum / period as f64);
+    }
+    out
+}
+
+/// Compute the EMA series for `closes`, seeded with the SMA of the first
+/// `period` values. The returned vector has length `closes.len() - period + 1`
+/// (empty if there aren't enough samples). Iterates chronologically.
+fn ema_series(closes: &[f64], period: usize) -> Vec<f64> {
+    if closes.len() < period || period == 0 {
+        return Vec::new();
+    }
+    let k = 2.0 / (period as f64 + 1.0);
+    let mut out = Vec::with_capacity(closes.len() - period + 1);
+    let mut ema: f64 = closes[..period].iter().sum::<f64>() / period as f64;
+    out.push(ema);
+    for &c in &closes[period..] {
+        ema = (c - ema) * k + ema;
+        out.push(ema);
+    }
+    out
+}
+
+#[cfg(feature = "std")]
+fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+fn sqrt(x: f64) -> f64 {
+    libm::sqrt(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closes_are_bars() {
+        let closes: Vec<f64> = (1..=40).map(f64::from).collect();
+        assert_eq!(TechnicalIndicators::calculate_sma(&closes, 4), Some(38.5));
+        assert_eq!(TechnicalIndicators::calculate_rsi(&closes, 14), Some(100.0));
+        let macd = TechnicalIndicators::calculate_macd(&closes).unwrap();
+        assert!(macd.macd_line > 0.0);
+        // High == low == close, so %K sits at the top of the range.
+        let stochastic = TechnicalIndicators::calculate_stochastic(&closes, 14, 3).unwrap();
+        assert_eq!(stochastic.k_line, 100.0);
+        assert!(TechnicalIndicators::is_stoch_overbought(Some(&stochastic)));
+        assert!(!TechnicalIndicators::is_stoch_oversold(Some(&stochastic)));
+        assert!(!TechnicalIndicators::is_stoch_oversold(None));
+    }
+
+    struct VolumeBar {
+        close: f64,
+        volume: f64,
+    }
+
+    impl Bar for VolumeBar {
+        fn close(&self) -> f64 {
+            self.close
+        }
+
+        fn high(&self) -> f64 {
+            self.close + 1.0
+        }
+
+        fn low(&self) -> f64 {
+            self.close - 1.0
+        }
+
+        fn volume(&self) -> f64 {
+            self.volume
+        }
+    }
+
+    #[test]
+    fn vwap_and_obv_weight_by_volume() {
+        let bars: Vec<VolumeBar> = [(10.0, 100.0), (11.0, 300.0), (10.5, 200.0), (12.0, 400.0)]
+            .into_iter()
+            .map(|(close, volume)| VolumeBar { close, volume })
+            .collect();
+        // Typical price is the close, since high and low straddle it evenly.
+        let vwap = TechnicalIndicators::calculate_vwap(&bars).unwrap();
+        assert!((vwap - 11.2).abs() < 1e-9);
+
+        assert_eq!(
+            TechnicalIndicators::obv_series(&bars),
+            vec![0.0, 300.0, 100.0, 500.0]
+        );
+        let obv = TechnicalIndicators::calculate_obv(&bars, 4).unwrap();
+        assert_eq!(obv.obv, 500.0);
+        assert!((obv.slope - 130.0).abs() < 1e-9);
+        assert!(TechnicalIndicators::calculate_obv(&bars, 5).is_none());
+
+        // Bare closes carry no volume.
+        let closes = [1.0, 2.0, 3.0];
+        assert_eq!(TechnicalIndicators::calculate_vwap(&closes), None);
+        assert!(TechnicalIndicators::calculate_obv(&closes, 3).is_none());
+    }
+
+    #[test]
+    fn ma_cross_finds_the_latest_crossing() {
+        // Falls for 30 bars, then rallies: the 5-bar average crosses above
+        // the 10-bar one a few bars into the rally.
+        let mut closes: Vec<f64> = (0..30).map(|i| 100.0 - i as f64).collect();
+        closes.extend((1..=10).map(|i| 71.0 + 3.0 * i as f64));
+        let (index, signal) = TechnicalIndicators::calculate_ma_cross(&closes, 5, 10).unwrap();
+        assert_eq!(signal, CrossSignal::GoldenCross);
+        assert!(index > 29 && index < closes.len());
+
+        // Then a slide back down turns it into a death cross.
+        closes.extend((1..=15).map(|i| 101.0 - 4.0 * i as f64));
+        let (later, signal) = TechnicalIndicators::calculate_ma_cross(&closes, 5, 10).unwrap();
+        assert_eq!(signal, CrossSignal::DeathCross);
+        assert!(later > index);
+
+        // A steady trend never crosses.
+        let rising: Vec<f64> = (0..40).map(f64::from).collect();
+        assert_eq!(
+            TechnicalIndicators::calculate_ma_cross(&rising, 5, 10),
+            None
+        );
+        assert_eq!(
+            TechnicalIndicators::calculate_ma_cross(&rising[..10], 5, 10),
+            None
+        );
+    }
+
+    #[test]
+    fn support_resistance_brackets_the_close() {
+        // Rally to a 120 peak, pull back to a 100 trough, then drift to 110.
+        let mut closes: Vec<f64> = (0..=20).map(|i| 80.0 + 2.0 * i as f64).collect();
+        closes.extend((1..=10).map(|i| 120.0 - 2.0 * i as f64));
+        closes.extend((1..=10).map(|i| 100.0 + i as f64));
+        let levels = TechnicalIndicators::detect_support_resistance(&closes).unwrap();
+        assert_eq!(levels.support, Some(100.0));
+        assert_eq!(levels.resistance, Some(120.0));
+
+        // Breaking out leaves nothing overhead; the old peak becomes support.
+        closes.push(125.0);
+        let levels = TechnicalIndicators::detect_support_resistance(&closes).unwrap();
+        assert_eq!(levels.support, Some(120.0));
+        assert_eq!(levels.resistance, None);
+
+        assert_eq!(
+            TechnicalIndicators::detect_support_resistance(&closes[..5]),
+            None
+        );
+    }
+
+    #[test]
+    fn states_carried_forward_match_the_batch_functions() {
+        let closes: Vec<f64> = (0..80)
+            .map(|i| 100.0 + (i as f64 * 0.7).sin() * 5.0 + i as f64 * 0.1)
+            .collect();
+        let mut rsi = RsiState::seed(&closes[..50], 14).unwrap();
+        let mut ema = EmaState::seed(&closes[..50], 20).unwrap();
+        let mut macd = MacdState::seed(&closes[..50], 12, 26, 9).unwrap();
+        for &close in &closes[50..] {
+            rsi.update(close);
+            ema.update(close);
+            macd.update(close);
+        }
+
+        let batch_rsi = TechnicalIndicators::calculate_rsi(&closes, 14).unwrap();
+        let batch_ema = TechnicalIndicators::calculate_ema(&closes, 20).unwrap();
+        let batch_macd = TechnicalIndicators::calculate_macd(&closes).unwrap();
+        assert!((rsi.value() - batch_rsi).abs() < 1e-9);
+        assert!((ema.value - batch_ema).abs() < 1e-9);
+        assert!((macd.value().macd_line - batch_macd.macd_line).abs() < 1e-9);
+        assert!((macd.value().signal_line - batch_macd.signal_line).abs() < 1e-9);
+        assert!(RsiState::seed(&closes[..14], 14).is_none());
+    }
+
+    #[test]
+    fn williams_cci_and_mfi() {
+        let bars: Vec<VolumeBar> = (0..20)
+            .map(|i| VolumeBar {
+                close: 100.0 + i as f64,
+                volume: 1_000.0,
+            })
+            .collect();
+        // Closing 1 below the window's high of 120 in a 120-105 range.
+        let williams = TechnicalIndicators::calculate_williams_r(&bars, 14).unwrap();
+        assert!((williams - (-100.0 / 15.0)).abs() < 1e-9);
+        // A steady climb: the latest typical price is well above its mean.
+        let cci = TechnicalIndicators::calculate_cci(&bars, 5).unwrap();
+        assert!((cci - 2.0 / (0.015 * 1.2)).abs() < 1e-9);
+        // Every flow is positive.
+        assert_eq!(TechnicalIndicators::calculate_mfi(&bars, 14), Some(100.0));
+
+        let closes: Vec<f64> = (0..20).map(f64::from).collect();
+        assert_eq!(TechnicalIndicators::calculate_mfi(&closes, 14), None);
+        assert_eq!(
+            TechnicalIndicators::calculate_williams_r(&closes[..5], 14),
+            None
+        );
+        assert_eq!(
+            TechnicalIndicators::calculate_cci(&[5.0; 20], 20),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn supertrend_follows_and_flips() {
+        // Rise for 30 bars, then fall for 15.
+        let closes: Vec<f64> = (0..30)
+            .map(|i| 100.0 + i as f64)
+            .chain((1..=15).map(|i| 129.0 - 3.0 * i as f64))
+            .collect();
+
+        let (rising, flip) =
+            TechnicalIndicators::calculate_supertrend(&closes[..30], 10, 3.0).unwrap();
+        assert!(rising.uptrend);
+        assert_eq!(flip, None);
+        // Closes only, so the ATR approaches the 1-point daily move and
+        // the stop trails about 3 below the last close.
+        assert!(rising.value > 126.0 && rising.value < 126.1);
+
+        let (falling, flip) = TechnicalIndicators::calculate_supertrend(&closes, 10, 3.0).unwrap();
+        assert!(!falling.uptrend);
+        assert!(falling.value > *closes.last().unwrap());
+        // The first lower close, 126, is already under the stop.
+        assert_eq!(flip, Some(30));
+
+        assert_eq!(
+            TechnicalIndicators::calculate_supertrend(&closes[..10], 10, 3.0),
+            None
+        );
+    }
+
+    #[test]
+    fn bands_use_population_deviation() {
+        let closes = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let bands = TechnicalIndicators::calculate_bollinger_bands(&closes, 8, 2.0).unwrap();
+        assert_eq!(bands.middle_band, 5.0);
+        assert_eq!(bands.upper_band, 9.0);
+        assert_eq!(bands.lower_band, 1.0);
+    }
+}
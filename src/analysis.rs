@@ -1,21 +1,52 @@
 use crate::{
+    analytics,
+    archive::{RawArchive, Source},
+    asset_types::{self, AssetType},
     async_fetcher::{AsyncStockFetcher, FetcherConfig},
     cache::CacheLayer,
+    cross_section::PriceHistory,
     db::MongoDB,
-    indicators::TechnicalIndicators,
-    models::{AnalysisProgress, HistoricalPrice, NasdaqResponse, NasdaqTechnicals, StockAnalysis},
-    nasdaq::NasdaqClient,
+    degradation::{DegradationMonitor, DegradationPolicy, Upstream},
+    highs_lows,
+    incremental::IndicatorState,
+    indexes::{self, IndexContributors, IndexDataProvider, IndexPerformance},
+    indicators::{IndicatorConfig, TechnicalIndicators},
+    ingest::SignalInbox,
+    lanes::{self, LaneMode},
+    maintenance::MaintenanceMode,
+    models::{
+        AnalysisProgress, BollingerBands, CrossSectionStats, CrossSignal, EarningsData, EngineMode,
+        HistoricalPrice, MACDIndicator, NasdaqNewsItem, NasdaqTechnicals, OnBalanceVolume,
+        StochasticOscillator, Stock, StockAnalysis, SuperTrend, SupportResistance, SymbolAlias,
+        SymbolCycleStatus, SymbolProgress,
+    },
+    nasdaq::{self, NasdaqClient},
+    notes::SymbolNotes,
     notifications::AlertEngine,
-    yahoo::YahooFinanceClient,
+    percentiles::{self, PercentileRanks},
+    pipeline::{PipelineStages, Stage},
+    renames,
+    runs::{self, AnalysisRun},
+    screens::{self, Screen},
+    sectors::{self, SectorEtfSnapshot},
+    signals,
+    storage::Storage,
+    units,
+    yahoo::{PriceAdjustment, YahooFinanceClient},
 };
-use chrono::Utc;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::{NaiveDate, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration};
 use tracing::{debug, error, info, warn};
 
+/// Calendar days of daily bars fetched per symbol: enough for the
+/// indicators (about 260 sessions, so the SMA 200 has two months of history
+/// to cross the SMA 50 in) and for a year-to-date return on December 31st.
+pub(crate) const HISTORY_DAYS: i64 = 380;
+
 /// Per-symbol fetch health for the Yahoo circuit breaker.
 ///
 /// Tracks consecutive non-rate-limit failures. When the count reaches the
@@ -110,6 +141,9 @@ impl CircuitBreaker {
 
 pub struct AnalysisEngine {
     db: MongoDB,
+    /// Where the latest analyses are saved and read back; `db` holds the
+    /// rest (history, runs, progress, index and theme performance).
+    storage: Storage,
     nasdaq_client: NasdaqClient,
     http_client: reqwest::Client,
     cache: CacheLayer,
@@ -127,12 +161,88 @@ pub struct AnalysisEngine {
     alert_engine: Option<AlertEngine>,
     /// Per-symbol Yahoo fetch circuit breaker (in-memory, process-local).
     breaker: Arc<CircuitBreaker>,
+    /// Symbols that get earnings calendar data fetched so earnings alert
+    /// conditions can fire (see `AlertRepo::earnings_symbols`), refreshed
+    /// every cycle. `None` means every analyzed symbol.
+    earnings_symbols: Arc<RwLock<Option<HashSet<String>>>>,
+    /// Sector proxy ETFs keyed by ticker, refreshed at the start of every
+    /// cycle and used for `StockAnalysis::sector_relative`.
+    sector_etfs: Arc<RwLock<HashMap<String, SectorEtfSnapshot>>>,
+    /// Last nightly cross-section stats keyed by symbol, reloaded at the
+    /// start of every cycle and copied onto fresh analyses.
+    cross_section: Arc<RwLock<HashMap<String, CrossSectionStats>>>,
+    /// Universe percentile ranks from the last completed cycle, copied onto
+    /// fresh analyses.
+    percentiles: Arc<RwLock<HashMap<String, PercentileRanks>>>,
+    /// Signals pushed by external systems, copied onto fresh analyses.
+    signals: SignalInbox,
+    /// User notes and tags, copied onto fresh analyses.
+    notes: SymbolNotes,
+    /// Optional pipeline stages enabled for this deployment.
+    stages: PipelineStages,
+    /// Budget for each network-bound stage of a single symbol.
+    stage_timeout: Duration,
+    /// Upstream error tracking and the degraded-mode switch.
+    degradation: DegradationMonitor,
+    /// Consecutive failures before a symbol is dead-lettered; 0 disables.
+    dead_letter_threshold: u32,
+    /// Russell 2000 members outside the main universe queued per cycle;
+    /// 0 disables.
+    russell_chunk_size: usize,
+    /// Where the next small-cap chunk starts. In memory only: a restart
+    /// begins again from the top of the list.
+    russell_cursor: AtomicUsize,
+    /// Screener market caps of Russell 2000 members below the main
+    /// universe's cap floor, refreshed with the screener.
+    small_cap_caps: Arc<RwLock<HashMap<String, f64>>>,
+    /// Cycles pause while the API is read-only.
+    maintenance: MaintenanceMode,
+    /// How the queue is split into per-sector lanes.
+    sector_lanes: LaneMode,
+    /// Where raw screener and NASDAQ responses are kept, when enabled.
+    raw_archive: Option<RawArchive>,
+    /// RSI, SMA and MACD periods.
+    indicator_config: IndicatorConfig,
+    /// Fetch only the days since each symbol's stored bars.
+    incremental_fetch: bool,
+    /// Annual risk-free rate (%) the Sharpe and Sortino ratios are net of.
+    risk_free_rate_pct: f64,
+    /// Optional-stage failures this cycle by stage name, for its
+    /// `analysis_runs` entry.
+    stage_failures: Arc<RwLock<BTreeMap<&'static str, usize>>>,
+}
+
+/// Fewest symbols processed between the list-cache refreshes lanes add.
+const MIN_LANE_REFRESH: usize = 50;
+
+/// Output of the indicators stage.
+#[derive(Default)]
+struct IndicatorSet {
+    rsi: Option<f64>,
+    sma_20: Option<f64>,
+    sma_50: Option<f64>,
+    sma_200: Option<f64>,
+    macd: Option<MACDIndicator>,
+    bollinger: Option<BollingerBands>,
+    stochastic: Option<StochasticOscillator>,
+    williams_r: Option<f64>,
+    cci: Option<f64>,
+    mfi: Option<f64>,
+    vwap: Option<f64>,
+    obv: Option<OnBalanceVolume>,
+    cross_signal: CrossSignal,
+    cross_date: Option<chrono::DateTime<Utc>>,
+    supertrend: Option<SuperTrend>,
+    supertrend_flip_date: Option<chrono::DateTime<Utc>>,
+    support_resistance: Option<SupportResistance>,
+    periods: Option<IndicatorConfig>,
 }
 
 impl AnalysisEngine {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         db: MongoDB,
+        storage: Storage,
         cache: CacheLayer,
         interval_secs: u64,
         yahoo_delay_ms: u64,
@@ -145,6 +255,19 @@ impl AnalysisEngine {
         alert_engine: Option<AlertEngine>,
         circuit_failure_threshold: u32,
         circuit_skip_cycles: u32,
+        stages: PipelineStages,
+        stage_timeout: Duration,
+        degradation: DegradationPolicy,
+        dead_letter_threshold: u32,
+        russell_chunk_size: usize,
+        signals: SignalInbox,
+        notes: SymbolNotes,
+        maintenance: MaintenanceMode,
+        sector_lanes: LaneMode,
+        raw_archive: Option<RawArchive>,
+        indicator_config: IndicatorConfig,
+        incremental_fetch: bool,
+        risk_free_rate_pct: f64,
     ) -> Self {
         let progress = Arc::new(RwLock::new(AnalysisProgress {
             total_stocks: 0,
@@ -156,6 +279,9 @@ impl AnalysisEngine {
             last_cycle_completed: None,
             last_successful_cycle: None,
             last_error: None,
+            mode: EngineMode::Normal,
+            degraded_reason: None,
+            saved_symbols: Vec::new(),
         }));
 
         let http_client = reqwest::Client::builder()
@@ -164,10 +290,14 @@ impl AnalysisEngine {
             .build()
             .expect("Failed to create HTTP client");
 
-        let nasdaq_client = NasdaqClient::new(nasdaq_delay_ms);
+        let mut nasdaq_client = NasdaqClient::new(nasdaq_delay_ms);
+        if let Some(archive) = &raw_archive {
+            nasdaq_client = nasdaq_client.with_archive(archive.clone());
+        }
 
         AnalysisEngine {
             db,
+            storage,
             nasdaq_client,
             http_client,
             cache,
@@ -185,37 +315,112 @@ impl AnalysisEngine {
                 circuit_failure_threshold,
                 circuit_skip_cycles,
             )),
+            earnings_symbols: Arc::new(RwLock::new(Some(HashSet::new()))),
+            sector_etfs: Arc::new(RwLock::new(HashMap::new())),
+            cross_section: Arc::new(RwLock::new(HashMap::new())),
+            percentiles: Arc::new(RwLock::new(HashMap::new())),
+            signals,
+            notes,
+            stages,
+            stage_timeout,
+            degradation: DegradationMonitor::new(degradation),
+            dead_letter_threshold,
+            russell_chunk_size,
+            russell_cursor: AtomicUsize::new(0),
+            small_cap_caps: Arc::new(RwLock::new(HashMap::new())),
+            maintenance,
+            sector_lanes,
+            raw_archive,
+            indicator_config,
+            incremental_fetch,
+            risk_free_rate_pct,
+            stage_failures: Arc::new(RwLock::new(BTreeMap::new())),
         }
     }
 
-    /// Load existing data from MongoDB and populate cache
-    pub async fn load_existing_data(&self) -> anyhow::Result<usize> {
+    /// Warm the stock cache from MongoDB. Watchlisted and pinned symbols are
+    /// always loaded, followed by the `warm_limit` largest market caps; the
+    /// rest of the universe is cached lazily when first read. A `warm_limit`
+    /// of 0 loads every analysis.
+    pub async fn load_existing_data(&self, warm_limit: usize) -> anyhow::Result<usize> {
         info!("Loading existing data from MongoDB...");
 
-        match self.db.get_all_analyses().await {
+        if warm_limit == 0 {
+            let analyses = match self.db.get_all_analyses().await {
+                Ok(analyses) => analyses,
+                Err(e) => {
+                    warn!("Failed to load existing data: {}. Starting fresh.", e);
+                    return Ok(0);
+                }
+            };
+            let count = analyses.len();
+            for analysis in analyses {
+                self.cache
+                    .set_stock(analysis.symbol.clone(), Arc::new(analysis))
+                    .await;
+            }
+            info!("✅ Loaded {} analyses into cache", count);
+            return Ok(count);
+        }
+
+        let mut priority: Vec<String> = Vec::new();
+        if let Some(engine) = &self.alert_engine {
+            match engine.repo().all_watched_symbols().await {
+                Ok(symbols) => priority.extend(symbols),
+                Err(e) => warn!("Failed to load watchlisted symbols for warm-up: {}", e),
+            }
+        }
+        match self.db.get_cache_pins().await {
+            Ok(pins) => priority.extend(pins.into_iter().map(|p| p.symbol)),
+            Err(e) => warn!("Failed to load cache pins for warm-up: {}", e),
+        }
+        priority.sort();
+        priority.dedup();
+
+        let mut loaded = std::collections::HashSet::new();
+        match self.db.get_analyses_by_symbols(&priority).await {
             Ok(analyses) => {
-                let count = analyses.len();
-                if count > 0 {
-                    info!("Found {} existing analyses in database", count);
+                for analysis in analyses {
+                    loaded.insert(analysis.symbol.clone());
+                    self.cache
+                        .set_stock(analysis.symbol.clone(), Arc::new(analysis))
+                        .await;
+                }
+            }
+            Err(e) => warn!("Failed to warm watchlisted/pinned symbols: {}", e),
+        }
+        let priority_count = loaded.len();
 
-                    // Populate cache
-                    for analysis in analyses {
+        match self
+            .db
+            .get_top_analyses_by_market_cap(warm_limit as i64)
+            .await
+        {
+            Ok(analyses) => {
+                for analysis in analyses {
+                    if loaded.insert(analysis.symbol.clone()) {
                         self.cache
-                            .set_stock(analysis.symbol.clone(), analysis)
+                            .set_stock(analysis.symbol.clone(), Arc::new(analysis))
                             .await;
                     }
-
-                    info!("✅ Loaded {} analyses into cache", count);
-                } else {
-                    info!("No existing analyses found in database");
                 }
-                Ok(count)
             }
             Err(e) => {
                 warn!("Failed to load existing data: {}. Starting fresh.", e);
-                Ok(0)
             }
         }
+
+        if loaded.is_empty() {
+            info!("No existing analyses found in database");
+        } else {
+            info!(
+                "✅ Warmed cache with {} analyses ({} watchlisted/pinned, top {} by market cap)",
+                loaded.len(),
+                priority_count,
+                warm_limit
+            );
+        }
+        Ok(loaded.len())
     }
 
     pub fn get_progress(&self) -> Arc<RwLock<AnalysisProgress>> {
@@ -234,13 +439,21 @@ impl AnalysisEngine {
         );
 
         loop {
+            if self.maintenance.is_read_only() {
+                info!("⏸️  Read-only mode: analysis paused");
+                self.maintenance.wait_until_writable().await;
+                info!("▶️  Read-only mode lifted, resuming analysis");
+            }
             info!("Beginning new analysis cycle");
 
-            if let Err(e) = self.run_analysis_cycle().await {
+            let mut run = AnalysisRun::start(Utc::now(), self.degradation.mode().0);
+            let result = self.run_analysis_cycle(&mut run).await;
+            if let Err(e) = &result {
                 error!("Analysis cycle error: {}", e);
                 let mut progress = self.progress.write().await;
                 progress.last_error = Some(e.to_string());
             }
+            self.record_run(run, &result).await;
 
             info!(
                 "Analysis cycle complete. Waiting {} seconds before next cycle",
@@ -250,36 +463,107 @@ impl AnalysisEngine {
         }
     }
 
-    async fn run_analysis_cycle(&self) -> anyhow::Result<()> {
+    /// Store `run` in `analysis_runs` once its cycle has ended.
+    async fn record_run(&self, mut run: AnalysisRun, result: &anyhow::Result<()>) {
+        for (stage, count) in std::mem::take(&mut *self.stage_failures.write().await) {
+            run.add_errors(stage, count);
+        }
+        run.finish(Utc::now(), result);
+        if self.maintenance.is_read_only() {
+            debug!("Read-only mode: not recording the analysis run");
+            return;
+        }
+        if let Err(e) = self.db.save_analysis_run(&run).await {
+            warn!("Failed to record analysis run: {}", e);
+        }
+    }
+
+    async fn run_analysis_cycle(&self, run: &mut AnalysisRun) -> anyhow::Result<()> {
         use crate::async_fetcher::FetchResult;
 
         // Advance the cycle counter so the circuit breaker can compare
         // `open_until_cycle` deterministically without timestamps.
         self.breaker.advance_cycle();
+        self.stage_failures.write().await.clear();
 
-        let cycle_started = Utc::now();
+        let cycle_started = run.started_at;
+        let degraded = self.degradation.is_degraded();
         {
             let mut progress = self.progress.write().await;
+            self.apply_mode(&mut progress);
             progress.last_cycle_started = Some(cycle_started);
             progress.cycle_start = cycle_started;
             progress.current_symbol = None;
             progress.errors = 0;
             progress.last_error = None;
+            progress.saved_symbols.clear();
+        }
+
+        if let Some(engine) = &self.alert_engine {
+            match engine.repo().earnings_symbols().await {
+                Ok(symbols) => *self.earnings_symbols.write().await = symbols,
+                Err(e) => warn!("Failed to refresh earnings alert symbols: {}", e),
+            }
         }
 
-        // Get list of stocks from NASDAQ API
-        let symbols = self.get_stock_symbols().await;
+        match self.db.get_cross_section_stats().await {
+            Ok(stats) => {
+                *self.cross_section.write().await =
+                    stats.into_iter().map(|s| (s.symbol.clone(), s)).collect()
+            }
+            Err(e) => warn!("Failed to load cross-section stats: {}", e),
+        }
+
+        // After a restart, carry the stored ranks until this cycle recomputes them.
+        if self.percentiles.read().await.is_empty() {
+            match self.db.get_percentile_ranks().await {
+                Ok(ranks) => *self.percentiles.write().await = ranks,
+                Err(e) => warn!("Failed to load percentile ranks: {}", e),
+            }
+        }
+
+        // Sector-relative returns need the NASDAQ sector.
+        if self.stages.enabled(Stage::Technicals) && !degraded {
+            self.refresh_sector_etfs().await;
+        }
+
+        // Get list of stocks from NASDAQ API. Small caps outside it go last
+        // so they never hold up the large caps.
+        let mut symbols = self.get_stock_symbols(run).await;
+        let small_caps = self.next_small_cap_chunk(&symbols).await;
+        let small_cap_symbols: HashSet<String> =
+            small_caps.iter().map(|(s, _)| s.clone()).collect();
+        symbols.extend(small_caps);
 
         // Build map of symbol -> market_cap for later use
         let market_cap_map: HashMap<String, Option<f64>> =
             symbols.iter().map(|(s, mc)| (s.clone(), *mc)).collect();
 
-        // Filter to symbols that need analysis
+        let dead_lettered = if self.dead_letter_threshold > 0 {
+            self.db
+                .get_dead_lettered_symbols()
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to load dead-lettered symbols: {}", e);
+                    HashSet::new()
+                })
+        } else {
+            HashSet::new()
+        };
+
+        // Filter to symbols that need analysis, noting each one's stored
+        // sector for the lanes
         let mut symbols_to_analyze: Vec<String> = Vec::new();
+        let mut sectors: HashMap<String, String> = HashMap::new();
         let mut skipped = 0;
 
         for (symbol, _) in &symbols {
-            match self.db.get_analysis_by_symbol(symbol).await {
+            if dead_lettered.contains(symbol) {
+                debug!("☠️  Skipping {} - dead-lettered", symbol);
+                skipped += 1;
+                continue;
+            }
+            match self.storage.get_analysis_by_symbol(symbol).await {
                 Ok(Some(existing)) => {
                     let now = Utc::now();
                     let elapsed = now
@@ -293,6 +577,9 @@ impl AnalysisEngine {
                         debug!("⏹️  Skipping {} - circuit open", symbol);
                         skipped += 1;
                     } else {
+                        if let Some(sector) = existing.sector {
+                            sectors.insert(symbol.clone(), sector);
+                        }
                         symbols_to_analyze.push(symbol.clone());
                     }
                 }
@@ -314,7 +601,32 @@ impl AnalysisEngine {
             }
         }
 
+        // Persist per-symbol state so operators can see what's stuck.
+        let queued: HashSet<&String> = symbols_to_analyze.iter().collect();
+        let entries: Vec<SymbolProgress> = symbols
+            .iter()
+            .map(|(symbol, _)| SymbolProgress {
+                symbol: symbol.clone(),
+                status: if queued.contains(symbol) {
+                    SymbolCycleStatus::Pending
+                } else if dead_lettered.contains(symbol) {
+                    SymbolCycleStatus::DeadLettered
+                } else {
+                    SymbolCycleStatus::Skipped
+                },
+                error: None,
+                cycle_start: cycle_started,
+                updated_at: cycle_started,
+            })
+            .collect();
+        if let Err(e) = self.db.reset_cycle_symbols(&entries).await {
+            warn!("Failed to persist cycle symbol state: {}", e);
+        }
+
         let total_to_analyze = symbols_to_analyze.len();
+        run.universe = symbols.len();
+        run.attempted = total_to_analyze;
+        run.skipped = skipped;
         info!(
             "📊 Analyzing {} stocks ({} skipped, already up-to-date)",
             total_to_analyze, skipped
@@ -347,52 +659,209 @@ impl AnalysisEngine {
             self.yahoo_concurrency
         );
 
+        let yahoo_delay_ms = if degraded {
+            let delay = self.yahoo_delay_ms * self.degradation.policy().delay_multiplier;
+            warn!(
+                "⚠️  Degraded mode: price-only refresh, Yahoo delay {}ms",
+                delay
+            );
+            delay
+        } else {
+            self.yahoo_delay_ms
+        };
+        let mut indicator_states = if self.incremental_fetch {
+            self.db
+                .get_indicator_states(&symbols_to_analyze)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to load indicator state: {}", e);
+                    HashMap::new()
+                })
+        } else {
+            HashMap::new()
+        };
+        // Symbols whose stored state can't be patched are fetched in full.
+        let days_by_symbol: HashMap<String, i64> = indicator_states
+            .values()
+            .filter_map(|state| {
+                let days = state.delta_days(&self.indicator_config, cycle_started)?;
+                Some((state.symbol.clone(), days))
+            })
+            .collect();
+        indicator_states.retain(|symbol, _| days_by_symbol.contains_key(symbol));
+        if self.incremental_fetch {
+            info!(
+                "📉 {} of {} symbols fetch only their latest bars",
+                days_by_symbol.len(),
+                total_to_analyze
+            );
+        }
         let fetcher = AsyncStockFetcher::with_client(
             FetcherConfig {
                 concurrency: self.yahoo_concurrency,
-                delay_between_requests_ms: self.yahoo_delay_ms,
-                days: 90, // 90 days for technical indicators
+                delay_between_requests_ms: yahoo_delay_ms,
+                days: HISTORY_DAYS,
+                days_by_symbol: Arc::new(days_by_symbol),
+                adjustment: PriceAdjustment::Adjusted,
             },
             self.yahoo_client.clone(),
         );
 
-        let (mut rx, fetch_handle) = fetcher.fetch_batch_streaming(symbols_to_analyze.clone());
+        // Small caps stay behind the main universe in every lane mode.
+        let (main, small): (Vec<String>, Vec<String>) = symbols_to_analyze
+            .into_iter()
+            .partition(|s| !small_cap_symbols.contains(s));
+        let lanes = lanes::partition(
+            main.iter()
+                .map(|s| (s.clone(), sectors.remove(s)))
+                .collect(),
+        );
+        let lane_count = lanes.len();
+        let (mut rx, fetch_handle) = match self.sector_lanes {
+            LaneMode::Off => fetcher.fetch_batch_streaming(main.into_iter().chain(small).collect()),
+            LaneMode::RoundRobin => {
+                info!("🛣️  {} sector lanes, round-robin", lane_count);
+                let mut queue = lanes::round_robin(&lanes);
+                queue.extend(small);
+                fetcher.fetch_batch_streaming(queue)
+            }
+            LaneMode::Parallel => {
+                let mut queues: Vec<Vec<String>> = lanes.into_iter().map(|l| l.symbols).collect();
+                if !small.is_empty() {
+                    queues.push(small);
+                }
+                let lane_concurrency = (self.yahoo_concurrency / queues.len().max(1)).max(1);
+                info!(
+                    "🛣️  {} sector lanes in parallel, concurrency {} each",
+                    queues.len(),
+                    lane_concurrency
+                );
+                fetcher.fetch_lanes_streaming(queues, lane_concurrency)
+            }
+        };
+        // With lanes, every sector has had a share of the queue processed
+        // each time another 1/lanes of it is done; refresh the list caches
+        // then so sector aggregates don't wait for the end of the cycle.
+        let refresh_every = match self.sector_lanes {
+            LaneMode::Off => None,
+            _ => Some((total_to_analyze / lane_count.max(1)).max(MIN_LANE_REFRESH)),
+        };
 
         let mut analyzed_count = 0;
         let mut error_count = 0;
         let mut success_count = 0;
+        // Set when Yahoo trips degraded mode or read-only mode switches on
+        // mid-cycle.
+        let mut ended_early: Option<String> = None;
 
         // Process results as they arrive
         while let Some(result) = rx.recv().await {
+            if self.maintenance.is_read_only() {
+                // Stop writing; unsaved symbols stay `pending` for later.
+                warn!("⏸️  Read-only mode switched on; ending cycle early");
+                ended_early = Some("read-only mode".to_string());
+                fetch_handle.abort();
+                break;
+            }
             match result {
                 FetchResult::Success { symbol, prices } => {
                     let current_symbol = symbol.clone();
 
                     self.breaker.record_success(&symbol).await;
+                    self.degradation.record(Upstream::Yahoo, true);
 
                     let market_cap = market_cap_map.get(&symbol).copied().flatten();
 
-                    match self
-                        .process_stock_with_prices(&symbol, market_cap, prices)
-                        .await
-                    {
-                        Ok(analysis) => {
-                            if let Err(e) = self.db.save_analysis(&analysis).await {
+                    let carried = indicator_states.remove(&symbol);
+                    let (prices, state, processed) =
+                        match self.prepare_prices(&symbol, prices, carried).await {
+                            Ok((prices, state)) => {
+                                let processed = self
+                                    .process_stock_with_prices(
+                                        &symbol,
+                                        market_cap,
+                                        &prices,
+                                        state.as_ref(),
+                                    )
+                                    .await;
+                                (prices, state, processed)
+                            }
+                            Err(e) => (Vec::new(), None, Err(e)),
+                        };
+
+                    match processed {
+                        Ok(analysis) => match self.storage.save_analysis(&analysis).await {
+                            Err(e) => {
                                 error!("Failed to save analysis for {}: {}", symbol, e);
+                                self.mark_symbol(
+                                    &symbol,
+                                    SymbolCycleStatus::Failed,
+                                    Some(e.to_string()),
+                                )
+                                .await;
+                                run.record_error(runs::DATABASE_SOURCE);
                                 error_count += 1;
-                            } else {
-                                self.cache.set_stock(symbol.clone(), analysis.clone()).await;
+                            }
+                            Ok(false) => {
+                                // A fresher analysis was saved concurrently;
+                                // it already reached the cache and alerts.
+                                debug!("Kept newer stored analysis for {}", symbol);
+                                self.mark_symbol(&symbol, SymbolCycleStatus::Done, None)
+                                    .await;
+                                self.clear_failures(&symbol).await;
+                                success_count += 1;
+                            }
+                            Ok(true) => {
+                                self.mark_symbol(&symbol, SymbolCycleStatus::Done, None)
+                                    .await;
+                                self.clear_failures(&symbol).await;
+                                self.track_signals(&analysis, &prices).await;
+                                self.track_week52(&analysis, &prices).await;
+                                let history = PriceHistory::from_prices(&symbol, &prices);
+                                if let Err(e) = self.db.save_price_history(&history).await {
+                                    warn!("Failed to save price history for {}: {}", symbol, e);
+                                }
+                                if let Some(state) = &state {
+                                    if let Err(e) = self.db.save_indicator_state(state).await {
+                                        warn!(
+                                            "Failed to save indicator state for {}: {}",
+                                            symbol, e
+                                        );
+                                    }
+                                }
+                                if let Err(e) = self.db.record_analysis_snapshot(&analysis).await {
+                                    warn!(
+                                        "Failed to record analysis snapshot for {}: {}",
+                                        symbol, e
+                                    );
+                                }
+                                self.cache
+                                    .set_stock(symbol.clone(), Arc::new(analysis.clone()))
+                                    .await;
                                 // Hand the analysis off to the alert engine
                                 // immediately so rule evaluation tracks
                                 // per-symbol latency, not full-cycle latency.
                                 if let Some(engine) = &self.alert_engine {
                                     engine.submit(analysis);
                                 }
+                                self.progress
+                                    .write()
+                                    .await
+                                    .saved_symbols
+                                    .push(symbol.clone());
                                 success_count += 1;
                             }
-                        }
+                        },
                         Err(e) => {
                             warn!("Failed to process {}: {}", symbol, e);
+                            self.mark_symbol(
+                                &symbol,
+                                SymbolCycleStatus::Failed,
+                                Some(e.to_string()),
+                            )
+                            .await;
+                            self.record_failure(&symbol, &e.to_string()).await;
+                            run.record_error(runs::ANALYSIS_SOURCE);
                             error_count += 1;
                         }
                     }
@@ -406,6 +875,7 @@ impl AnalysisEngine {
                     }
                     {
                         let mut progress = self.progress.write().await;
+                        self.apply_mode(&mut progress);
                         progress.current_symbol = Some(current_symbol);
                         progress.analyzed = skipped + analyzed_count;
                         progress.errors = error_count;
@@ -423,31 +893,68 @@ impl AnalysisEngine {
                         // Only non-rate-limit failures consume the breaker
                         // budget; 429s are global and shouldn't bench symbols.
                         self.breaker.record_failure(&symbol, &error).await;
+                        self.record_failure(&symbol, &error).await;
                     }
+                    self.mark_symbol(&symbol, SymbolCycleStatus::Failed, Some(error.clone()))
+                        .await;
+                    run.record_error(Upstream::Yahoo.name());
                     error_count += 1;
                     analyzed_count += 1;
+                    if let Some(reason) = self.degradation.record(Upstream::Yahoo, false) {
+                        warn!(
+                            "⚠️  Entering degraded mode ({}); ending cycle early",
+                            reason
+                        );
+                        ended_early = Some(reason);
+                    }
                     {
                         let mut progress = self.progress.write().await;
+                        self.apply_mode(&mut progress);
                         progress.current_symbol = Some(symbol);
                         progress.analyzed = skipped + analyzed_count;
                         progress.errors = error_count;
                     }
+                    if ended_early.is_some() {
+                        // Remaining symbols stay `pending` and are retried
+                        // next cycle at the degraded delay.
+                        fetch_handle.abort();
+                        break;
+                    }
                 }
             }
+            if refresh_every.is_some_and(|every| analyzed_count % every == 0) {
+                self.cache.invalidate_all_lists().await;
+            }
         }
 
+        run.processed = analyzed_count;
+        run.saved = success_count;
+        run.errors = error_count;
+
+        // Invalidate list caches after cycle, before the early returns below:
+        // a cycle that ends early or loses its fetch task still saved rows.
+        self.cache.invalidate_all_lists().await;
+
         // Wait for the fetch task to complete
         if let Err(e) = fetch_handle.await {
+            if e.is_cancelled() {
+                if let Some(reason) = ended_early {
+                    run.end_early(reason.clone());
+                    let mut progress = self.progress.write().await;
+                    progress.current_symbol = None;
+                    progress.last_cycle_completed = Some(Utc::now());
+                    progress.last_error = Some(format!("cycle ended early: {}", reason));
+                    return Ok(());
+                }
+            }
             error_count += 1;
+            run.errors = error_count;
             let mut progress = self.progress.write().await;
             progress.errors = error_count;
             progress.last_error = Some(format!("fetch task failed: {}", e));
             return Err(anyhow::anyhow!("fetch task failed: {}", e));
         }
 
-        // Invalidate list caches after cycle
-        self.cache.invalidate_all_lists().await;
-
         // Note: alert evaluation runs asynchronously per-analysis via
         // `AlertEngine::submit` (see the success branch above). No cycle-end
         // batch dispatch is needed.
@@ -455,6 +962,7 @@ impl AnalysisEngine {
         let completed = Utc::now();
         {
             let mut progress = self.progress.write().await;
+            self.apply_mode(&mut progress);
             progress.analyzed = skipped + analyzed_count;
             progress.current_symbol = None;
             progress.errors = error_count;
@@ -464,6 +972,15 @@ impl AnalysisEngine {
             }
         }
 
+        self.record_index_performance().await;
+        match self.db.get_all_analyses().await {
+            Ok(analyses) => {
+                self.refresh_screens(&analyses).await;
+                self.refresh_percentiles(&analyses).await;
+            }
+            Err(e) => warn!("Failed to load analyses for screens: {}", e),
+        }
+
         let progress = self.progress.read().await;
         info!(
             "✅ Cycle complete. {} total, {} processed, {} saved, {} skipped, {} errors",
@@ -477,59 +994,248 @@ impl AnalysisEngine {
         Ok(())
     }
 
-    /// Process a stock with pre-fetched historical prices
-    async fn process_stock_with_prices(
-        &self,
-        symbol: &str,
-        market_cap: Option<f64>,
-        historical_prices: Vec<HistoricalPrice>,
-    ) -> anyhow::Result<StockAnalysis> {
-        // Data-quality gate: reject thinly-traded / brand-new / delisted stocks
-        // before running any indicator math. Without this, the feed fills up
-        // with tickers whose RSI is based on 5 bars of zero-volume noise.
-        const MIN_BARS: usize = 30;
-        if historical_prices.len() < MIN_BARS {
-            return Err(anyhow::anyhow!(
-                "{}: only {} bars (need {}+)",
-                symbol,
-                historical_prices.len(),
-                MIN_BARS
-            ));
-        }
+    /// Recompute today's equal- and cap-weighted return and top contributors
+    /// for every index, and the returns of every theme, from the stored
+    /// constituent analyses. Best-effort, and skipped on weekends.
+    async fn record_index_performance(&self) {
+        let Some(date) = indexes::trading_date(Utc::now()) else {
+            return;
+        };
+        for index in IndexDataProvider::get_indexes() {
+            let symbols: Vec<String> = IndexDataProvider::get_index_symbols(&index.id)
+                .unwrap_or_default()
+                .iter()
+                .map(|s| crate::symbols::normalize_symbol_key(s))
+                .collect();
+            let constituents = match self.db.get_analyses_by_symbols(&symbols).await {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Failed to load {} constituents: {}", index.id, e);
+                    continue;
+                }
+            };
+            let Some(perf) = IndexPerformance::from_constituents(&index.id, date, &constituents)
+            else {
+                continue;
+            };
+            if let Err(e) = self.db.save_index_performance(&perf).await {
+                warn!("Failed to save {} performance: {}", index.id, e);
+            }
 
-        let latest_price = historical_prices
-            .last()
-            .ok_or_else(|| anyhow::anyhow!("No price data for {}", symbol))?;
+            let previous_close = self.index_previous_close(&index.id, date).await;
+            if let Some(contributors) = IndexContributors::from_constituents(
+                &index.id,
+                date,
+                &constituents,
+                previous_close,
+                indexes::STORED_CONTRIBUTORS,
+            ) {
+                if let Err(e) = self.db.save_index_contributors(&contributors).await {
+                    warn!("Failed to save {} contributors: {}", index.id, e);
+                }
+            }
+        }
 
-        if latest_price.volume <= 0.0 {
-            return Err(anyhow::anyhow!(
-                "{}: latest bar has zero volume, refusing to save analysis",
-                symbol
-            ));
+        let themes = match self.db.get_themes().await {
+            Ok(themes) => themes,
+            Err(e) => {
+                warn!("Failed to load themes: {}", e);
+                return;
+            }
+        };
+        for theme in themes {
+            let constituents = match self.db.get_analyses_by_symbols(&theme.symbols).await {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Failed to load theme {} members: {}", theme.id, e);
+                    continue;
+                }
+            };
+            let Some(perf) = IndexPerformance::from_constituents(&theme.id, date, &constituents)
+            else {
+                continue;
+            };
+            if let Err(e) = self.db.save_theme_performance(&perf).await {
+                warn!("Failed to save theme {} performance: {}", theme.id, e);
+            }
         }
+    }
 
-        // Calculate technical indicators
-        let rsi = TechnicalIndicators::calculate_rsi(&historical_prices, 14);
-        let sma_20 = TechnicalIndicators::calculate_sma(&historical_prices, 20);
-        let sma_50 = TechnicalIndicators::calculate_sma(&historical_prices, 50);
-        let macd = TechnicalIndicators::calculate_macd(&historical_prices);
-        let bollinger = TechnicalIndicators::calculate_bollinger_bands(&historical_prices, 20, 2.0);
-        let stochastic = TechnicalIndicators::calculate_stochastic(&historical_prices, 14, 3);
+    /// Re-run every pre-computed screen over the stored analyses. A screen
+    /// that fails to save keeps last cycle's result.
+    async fn refresh_screens(&self, analyses: &[StockAnalysis]) {
+        for screen in Screen::ALL {
+            let result = screen.run(analyses, screens::SCREEN_RESULT_LIMIT);
+            if let Err(e) = self.db.save_screen_result(&result).await {
+                warn!("Failed to save screen {}: {}", screen.name(), e);
+            }
+        }
+        debug!("Refreshed {} screens", Screen::ALL.len());
+    }
 
-        // Fetch NASDAQ technicals
-        self.nasdaq_client.apply_delay().await;
-        let technicals = match self.nasdaq_client.get_technicals(symbol).await {
-            Ok(t) => {
-                debug!("Fetched NASDAQ technicals for {}", symbol);
-                Some(t)
+    /// Re-rank the universe and write the ranks that changed onto the stored
+    /// analyses.
+    async fn refresh_percentiles(&self, analyses: &[StockAnalysis]) {
+        let ranks = percentiles::compute(analyses);
+        let mut changed = 0;
+        for analysis in analyses {
+            let fresh = ranks.get(&analysis.symbol);
+            if fresh == analysis.percentiles.as_ref() {
+                continue;
             }
+            let value = match fresh.map(mongodb::bson::to_bson).transpose() {
+                Ok(value) => value.unwrap_or(mongodb::bson::Bson::Null),
+                Err(e) => {
+                    warn!(
+                        "Failed to encode percentiles for {}: {}",
+                        analysis.symbol, e
+                    );
+                    continue;
+                }
+            };
+            match self
+                .db
+                .update_analysis_fields(
+                    &analysis.symbol,
+                    mongodb::bson::doc! { "percentiles": value },
+                )
+                .await
+            {
+                Ok(_) => {
+                    self.cache.invalidate_stock(&analysis.symbol).await;
+                    changed += 1;
+                }
+                Err(e) => warn!("Failed to save percentiles for {}: {}", analysis.symbol, e),
+            }
+        }
+        *self.percentiles.write().await = ranks;
+        if changed > 0 {
+            self.cache.invalidate_all_lists().await;
+        }
+        debug!("Updated percentile ranks of {} analyses", changed);
+    }
+
+    /// The index's close on the last session before `date`, for point
+    /// contributions. Not fetched while degraded.
+    async fn index_previous_close(&self, index_id: &str, date: NaiveDate) -> Option<f64> {
+        let ticker = IndexDataProvider::level_ticker(index_id)?;
+        if self.degradation.is_degraded() {
+            return None;
+        }
+        match self
+            .yahoo_client
+            .get_historical_prices(ticker, 10, PriceAdjustment::Raw)
+            .await
+        {
+            Ok(prices) => prices
+                .iter()
+                .rev()
+                .find(|p| {
+                    p.date
+                        .with_timezone(&chrono_tz::America::New_York)
+                        .date_naive()
+                        < date
+                })
+                .map(|p| p.close),
             Err(e) => {
-                debug!("Could not fetch NASDAQ technicals for {}: {}", symbol, e);
+                debug!("Failed to fetch {} level: {}", ticker, e);
                 None
             }
+        }
+    }
+
+    /// Record a symbol's outcome in `cycle_symbols`. Best-effort: a write
+    /// failure here must not fail the analysis.
+    async fn mark_symbol(&self, symbol: &str, status: SymbolCycleStatus, error: Option<String>) {
+        if let Err(e) = self
+            .db
+            .set_cycle_symbol_status(symbol, status, error.as_deref())
+            .await
+        {
+            debug!("Failed to record cycle status for {}: {}", symbol, e);
+        }
+    }
+
+    /// Add a failure to the symbol's dead-letter streak. Best-effort.
+    async fn record_failure(&self, symbol: &str, error: &str) {
+        if self.dead_letter_threshold == 0 {
+            return;
+        }
+        match self
+            .db
+            .record_analysis_failure(symbol, error, self.dead_letter_threshold)
+            .await
+        {
+            Ok(true) => warn!(
+                "☠️  {} dead-lettered after {} consecutive failures: {}",
+                symbol, self.dead_letter_threshold, error
+            ),
+            Ok(false) => {}
+            Err(e) => debug!("Failed to record failure for {}: {}", symbol, e),
+        }
+    }
+
+    async fn clear_failures(&self, symbol: &str) {
+        if self.dead_letter_threshold == 0 {
+            return;
+        }
+        if let Err(e) = self.db.clear_analysis_failures(symbol).await {
+            debug!("Failed to clear failure streak for {}: {}", symbol, e);
+        }
+    }
+
+    /// Process a stock with pre-fetched historical prices
+    async fn process_stock_with_prices(
+        &self,
+        symbol: &str,
+        market_cap: Option<f64>,
+        historical_prices: &[HistoricalPrice],
+        state: Option<&IndicatorState>,
+    ) -> anyhow::Result<StockAnalysis> {
+        let latest_price = usable_latest_bar(symbol, historical_prices)?;
+
+        let indicators = if self.stages.enabled(Stage::Indicators) {
+            Self::indicator_stage(&self.indicator_config, historical_prices, state)
+        } else {
+            IndicatorSet::default()
+        };
+        let rsi = indicators.rsi;
+        let risk = analytics::risk_ratios(historical_prices, self.risk_free_rate_pct);
+
+        // Degraded mode skips the NASDAQ stages; keep the last stored values
+        // rather than blanking sector, news and earnings.
+        let carried = if self.degradation.is_degraded() {
+            self.storage
+                .get_analysis_by_symbol(symbol)
+                .await
+                .ok()
+                .flatten()
+        } else {
+            None
         };
 
-        let sector = technicals.as_ref().and_then(|t| t.sector.clone());
+        let mut warnings = Vec::new();
+        let technicals = self
+            .run_stage(
+                Stage::Technicals,
+                &mut warnings,
+                self.technicals_stage(symbol),
+            )
+            .await;
+
+        let sector = technicals
+            .as_ref()
+            .and_then(|t| t.sector.clone())
+            .or_else(|| carried.as_ref().and_then(|p| p.sector.clone()));
+        let sector_relative = match sector.as_deref().and_then(sectors::etf_for_sector) {
+            Some(etf) => self
+                .sector_etfs
+                .read()
+                .await
+                .get(etf)
+                .map(|snapshot| sectors::relative_to(historical_prices, snapshot)),
+            None => None,
+        };
 
         let previous_price = historical_prices.get(historical_prices.len().saturating_sub(2));
         let quote = resolve_quote(latest_price, previous_price, technicals.as_ref());
@@ -546,50 +1252,337 @@ impl AnalysisEngine {
             }
         }
 
-        // Fetch news only for symbols likely to matter in the feed. Detail/API
-        // routes can still fetch on demand; the 24/7 cycle should not burst
-        // thousands of NASDAQ headline calls.
-        let should_fetch_news = is_notable_for_news(market_cap, rsi, quote.price_change_percent);
-        let news = if let Some(cached_news) = self.cache.get_news(symbol).await {
-            Some(cached_news)
-        } else if !should_fetch_news {
-            None
-        } else {
-            self.nasdaq_client.apply_delay().await;
-            match self.nasdaq_client.get_news(symbol, 10).await {
-                Ok(n) if !n.is_empty() => {
-                    self.cache.set_news(symbol.to_string(), n.clone()).await;
-                    Some(n)
-                }
-                _ => None,
-            }
+        let news = self
+            .run_stage(
+                Stage::News,
+                &mut warnings,
+                self.news_stage(symbol, market_cap, rsi, quote.price_change_percent),
+            )
+            .await;
+
+        let earnings = self
+            .run_stage(
+                Stage::Fundamentals,
+                &mut warnings,
+                self.earnings_for(symbol),
+            )
+            .await;
+
+        // Stale quote data must not override the fresh price above, so the
+        // carried-over technicals are only applied here.
+        let (technicals, news, earnings) = match carried {
+            Some(previous) => (
+                technicals.or(previous.technicals),
+                news.or(previous.news),
+                earnings.or(previous.earnings),
+            ),
+            None => (technicals, news, earnings),
         };
+        let asset_type = classify_asset(symbol, technicals.as_ref());
 
-        Ok(StockAnalysis {
+        let mut analysis = StockAnalysis {
             id: None,
             symbol: symbol.to_string(),
             price: quote.price,
             price_change: quote.price_change,
             price_change_percent: quote.price_change_percent,
             rsi,
-            sma_20,
-            sma_50,
-            macd,
-            volume: Some(latest_price.volume),
-            market_cap,
+            sma_20: indicators.sma_20,
+            sma_50: indicators.sma_50,
+            sma_200: indicators.sma_200,
+            macd: indicators.macd,
+            volume: units::to_u64(latest_price.volume),
+            market_cap: market_cap.and_then(units::to_u64),
             sector,
             is_oversold: TechnicalIndicators::is_oversold(rsi),
             is_overbought: TechnicalIndicators::is_overbought(rsi),
+            stoch_oversold: TechnicalIndicators::is_stoch_oversold(indicators.stochastic.as_ref()),
+            stoch_overbought: TechnicalIndicators::is_stoch_overbought(
+                indicators.stochastic.as_ref(),
+            ),
             analyzed_at: Utc::now(),
-            bollinger,
-            stochastic,
-            earnings: None,
+            bollinger: indicators.bollinger,
+            stochastic: indicators.stochastic,
+            williams_r: indicators.williams_r,
+            cci: indicators.cci,
+            mfi: indicators.mfi,
+            vwap: indicators.vwap,
+            obv: indicators.obv,
+            cross_signal: indicators.cross_signal,
+            cross_date: indicators.cross_date,
+            supertrend: indicators.supertrend,
+            supertrend_flip_date: indicators.supertrend_flip_date,
+            support_resistance: indicators.support_resistance,
+            indicator_periods: indicators.periods,
+            earnings,
             technicals,
             news,
-        })
+            sector_relative,
+            performance: analytics::performance_returns(historical_prices),
+            sharpe_ratio: risk.sharpe_ratio,
+            sortino_ratio: risk.sortino_ratio,
+            cross_section: self.cross_section.read().await.get(symbol).cloned(),
+            percentiles: self.percentiles.read().await.get(symbol).cloned(),
+            external_signals: self.signals.active(symbol).await,
+            warnings,
+            indexes: IndexDataProvider::indexes_for(symbol),
+            notes: None,
+            tags: Vec::new(),
+            asset_type,
+        };
+        self.notes.stamp(&mut analysis).await;
+        Ok(analysis)
+    }
+
+    /// Run one optional stage under `stage_timeout`. Disabled stages yield
+    /// `None`; failures and timeouts also yield `None` and are recorded in
+    /// `warnings` so one hung upstream can't stall or fail the symbol.
+    async fn run_stage<T>(
+        &self,
+        stage: Stage,
+        warnings: &mut Vec<String>,
+        fut: impl std::future::Future<Output = anyhow::Result<Option<T>>>,
+    ) -> Option<T> {
+        if !self.stages.enabled(stage) || self.degradation.skips(stage) {
+            return None;
+        }
+        let outcome = tokio::time::timeout(self.stage_timeout, fut).await;
+        // `Ok(None)` means the stage chose not to call out; don't count it.
+        let called_ok = match &outcome {
+            Ok(Ok(None)) => None,
+            Ok(Ok(Some(_))) => Some(true),
+            _ => Some(false),
+        };
+        if called_ok == Some(false) {
+            *self
+                .stage_failures
+                .write()
+                .await
+                .entry(stage.name())
+                .or_default() += 1;
+        }
+        if let (Some(upstream), Some(ok)) = (Upstream::of_stage(stage), called_ok) {
+            if let Some(reason) = self.degradation.record(upstream, ok) {
+                warn!("⚠️  Entering degraded mode ({})", reason);
+            }
+        }
+        match outcome {
+            Ok(Ok(value)) => value,
+            Ok(Err(e)) => {
+                debug!("{} stage failed: {}", stage.name(), e);
+                warnings.push(format!("{}: {}", stage.name(), e));
+                None
+            }
+            Err(_) => {
+                warnings.push(format!(
+                    "{}: timed out after {}s",
+                    stage.name(),
+                    self.stage_timeout.as_secs()
+                ));
+                None
+            }
+        }
+    }
+
+    /// Copy the current engine mode into `progress`.
+    fn apply_mode(&self, progress: &mut AnalysisProgress) {
+        (progress.mode, progress.degraded_reason) = self.degradation.mode();
+    }
+
+    /// The bars to analyze and, with `INCREMENTAL_FETCH`, the state to
+    /// store for next cycle: `prices` spliced onto the carried `state`, or a
+    /// new state seeded from a full fetch. A delta that can't be spliced is
+    /// replaced by a full fetch.
+    async fn prepare_prices(
+        &self,
+        symbol: &str,
+        prices: Vec<HistoricalPrice>,
+        state: Option<IndicatorState>,
+    ) -> anyhow::Result<(Vec<HistoricalPrice>, Option<IndicatorState>)> {
+        if !self.incremental_fetch {
+            return Ok((prices, None));
+        }
+        let state = match state {
+            Some(mut state) => match state.advance(&prices) {
+                Ok(()) => state,
+                Err(e) => {
+                    debug!("Full refetch: {}", e);
+                    let full = self
+                        .yahoo_client
+                        .get_historical_prices(symbol, HISTORY_DAYS, PriceAdjustment::Adjusted)
+                        .await?;
+                    IndicatorState::seed(symbol, self.indicator_config, &full)
+                }
+            },
+            None => IndicatorState::seed(symbol, self.indicator_config, &prices),
+        };
+        Ok((state.bars.clone(), Some(state)))
+    }
+
+    /// Every indicator over `prices`; RSI and MACD come from `state`
+    /// instead when the cycle carried one forward.
+    fn indicator_stage(
+        config: &IndicatorConfig,
+        prices: &[HistoricalPrice],
+        state: Option<&IndicatorState>,
+    ) -> IndicatorSet {
+        let cross = TechnicalIndicators::calculate_ma_cross(prices, 50, 200);
+        let supertrend = TechnicalIndicators::calculate_supertrend(prices, 10, 3.0);
+        let (rsi, macd) = match state {
+            Some(state) => state.latest(),
+            None => (config.rsi(prices), config.macd(prices)),
+        };
+        IndicatorSet {
+            rsi,
+            sma_20: config.sma_short(prices),
+            sma_50: config.sma_long(prices),
+            sma_200: TechnicalIndicators::calculate_sma(prices, 200),
+            macd,
+            bollinger: TechnicalIndicators::calculate_bollinger_bands(prices, 20, 2.0),
+            stochastic: TechnicalIndicators::calculate_stochastic(prices, 14, 3),
+            williams_r: TechnicalIndicators::calculate_williams_r(prices, 14),
+            cci: TechnicalIndicators::calculate_cci(prices, 20),
+            mfi: TechnicalIndicators::calculate_mfi(prices, 14),
+            vwap: TechnicalIndicators::calculate_vwap(prices),
+            obv: TechnicalIndicators::calculate_obv(prices, 20),
+            cross_signal: cross.map(|(_, signal)| signal).unwrap_or_default(),
+            cross_date: cross.map(|(index, _)| prices[index].date),
+            supertrend: supertrend.map(|(supertrend, _)| supertrend),
+            supertrend_flip_date: supertrend
+                .and_then(|(_, flip)| flip)
+                .map(|index| prices[index].date),
+            support_resistance: TechnicalIndicators::detect_support_resistance(prices),
+            periods: Some(*config),
+        }
     }
 
-    async fn get_stock_symbols(&self) -> Vec<(String, Option<f64>)> {
+    async fn technicals_stage(&self, symbol: &str) -> anyhow::Result<Option<NasdaqTechnicals>> {
+        self.nasdaq_client.apply_delay().await;
+        let technicals = self.nasdaq_client.get_technicals(symbol).await?;
+        debug!("Fetched NASDAQ technicals for {}", symbol);
+        Ok(Some(technicals))
+    }
+
+    /// Fetch news only for symbols likely to matter in the feed. Detail/API
+    /// routes can still fetch on demand; the 24/7 cycle should not burst
+    /// thousands of NASDAQ headline calls.
+    async fn news_stage(
+        &self,
+        symbol: &str,
+        market_cap: Option<f64>,
+        rsi: Option<f64>,
+        price_change_percent: Option<f64>,
+    ) -> anyhow::Result<Option<Vec<NasdaqNewsItem>>> {
+        if let Some(cached_news) = self.cache.get_news(symbol).await {
+            return Ok(Some(cached_news));
+        }
+        if !is_notable_for_news(market_cap, rsi, price_change_percent) {
+            return Ok(None);
+        }
+        self.nasdaq_client.apply_delay().await;
+        let news = self.nasdaq_client.get_news(symbol, 10).await?;
+        if news.is_empty() {
+            return Ok(None);
+        }
+        self.cache.set_news(symbol.to_string(), news.clone()).await;
+        Ok(Some(news))
+    }
+
+    /// Fetch every sector proxy ETF's bars and store its trailing returns.
+    /// An ETF that fails keeps last cycle's snapshot (if any).
+    async fn refresh_sector_etfs(&self) {
+        let mut refreshed = 0;
+        for etf in sectors::proxy_etfs() {
+            match self
+                .yahoo_client
+                .get_historical_prices(etf, 90, PriceAdjustment::Adjusted)
+                .await
+            {
+                Ok(prices) => {
+                    let Some(snapshot) = SectorEtfSnapshot::from_prices(etf, &prices) else {
+                        continue;
+                    };
+                    if let Err(e) = self.db.save_sector_etf(&snapshot).await {
+                        warn!("Failed to save sector ETF {}: {}", etf, e);
+                    }
+                    self.sector_etfs
+                        .write()
+                        .await
+                        .insert(etf.to_string(), snapshot);
+                    refreshed += 1;
+                }
+                Err(e) => warn!("Failed to fetch sector ETF {}: {}", etf, e),
+            }
+            sleep(Duration::from_millis(self.yahoo_delay_ms)).await;
+        }
+        debug!("Refreshed {} sector ETFs", refreshed);
+    }
+
+    /// Record any built-in strategy signals this analysis fires, then fill
+    /// forward returns for the symbol's earlier signals from the same bars.
+    /// Failures only cost leaderboard data, so they're logged and swallowed.
+    async fn track_signals(&self, analysis: &StockAnalysis, prices: &[HistoricalPrice]) {
+        let Some(latest_bar) = prices.last() else {
+            return;
+        };
+        let fresh = signals::detect(analysis, latest_bar);
+        if !fresh.is_empty() {
+            if let Err(e) = self.db.record_signals(&fresh).await {
+                warn!("Failed to record signals for {}: {}", analysis.symbol, e);
+            }
+        }
+
+        match self.db.get_open_signals(&analysis.symbol).await {
+            Ok(open) => {
+                for mut signal in open {
+                    if signal.fill_forward_returns(prices) {
+                        if let Err(e) = self.db.update_signal_returns(&signal).await {
+                            warn!(
+                                "Failed to update signal returns for {}: {}",
+                                signal.symbol, e
+                            );
+                        }
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to load open signals for {}: {}", analysis.symbol, e),
+        }
+    }
+
+    async fn track_week52(&self, analysis: &StockAnalysis, prices: &[HistoricalPrice]) {
+        let events = highs_lows::detect(&analysis.symbol, prices, analysis.analyzed_at);
+        if events.is_empty() {
+            return;
+        }
+        if let Err(e) = self.db.record_week52_events(&events).await {
+            warn!(
+                "Failed to record 52-week events for {}: {}",
+                analysis.symbol, e
+            );
+        }
+    }
+
+    /// Earnings calendar for `symbol`. Served from cache when available;
+    /// otherwise only fetched for symbols an earnings alert can fire on,
+    /// since that's an extra Yahoo request per stock and only alert rules
+    /// consume it.
+    async fn earnings_for(&self, symbol: &str) -> anyhow::Result<Option<EarningsData>> {
+        if let Some(earnings) = self.cache.get_earnings(symbol).await {
+            return Ok(Some(earnings));
+        }
+        if let Some(symbols) = self.earnings_symbols.read().await.as_ref() {
+            if !symbols.contains(symbol) {
+                return Ok(None);
+            }
+        }
+        let earnings = self.yahoo_client.get_earnings_data(symbol).await?;
+        self.cache
+            .set_earnings(symbol.to_string(), earnings.clone())
+            .await;
+        Ok(Some(earnings))
+    }
+
+    async fn get_stock_symbols(&self, run: &mut AnalysisRun) -> Vec<(String, Option<f64>)> {
         // Try to fetch from NASDAQ API
         match self.fetch_nasdaq_stocks().await {
             Ok(stocks) => {
@@ -604,6 +1597,7 @@ impl AnalysisEngine {
                     "Failed to fetch NASDAQ stocks: {}. Using cached/fallback list.",
                     e
                 );
+                run.record_error(Upstream::Nasdaq.name());
                 // Use cached symbols if available
                 let cached = self.cached_symbols.read().await;
                 if !cached.is_empty() {
@@ -629,35 +1623,68 @@ impl AnalysisEngine {
         }
     }
 
+    /// The next `russell_chunk_size` Russell 2000 members missing from
+    /// `universe`, rotating through them across cycles so the whole index is
+    /// covered every few cycles.
+    async fn next_small_cap_chunk(
+        &self,
+        universe: &[(String, Option<f64>)],
+    ) -> Vec<(String, Option<f64>)> {
+        if self.russell_chunk_size == 0 {
+            return Vec::new();
+        }
+        let included: HashSet<&str> = universe.iter().map(|(s, _)| s.as_str()).collect();
+        let pool: Vec<String> = IndexDataProvider::get_index_symbols("russell2000")
+            .unwrap_or_default()
+            .iter()
+            .map(|s| crate::symbols::normalize_symbol_key(s))
+            .filter(|s| !included.contains(s.as_str()) && !is_junk_symbol(s))
+            .collect();
+        let cursor = self.russell_cursor.load(Ordering::Relaxed);
+        let (chunk, next) = rotating_chunk(&pool, cursor, self.russell_chunk_size);
+        self.russell_cursor.store(next, Ordering::Relaxed);
+        if !chunk.is_empty() {
+            info!(
+                "Queued {} of {} Russell 2000 small caps outside the main universe",
+                chunk.len(),
+                pool.len()
+            );
+        }
+
+        let caps = self.small_cap_caps.read().await;
+        chunk
+            .into_iter()
+            .map(|symbol| {
+                let cap = caps.get(&symbol).copied();
+                (symbol, cap)
+            })
+            .collect()
+    }
+
     async fn fetch_nasdaq_stocks(&self) -> anyhow::Result<Vec<(String, Option<f64>)>> {
         let url = "https://api.nasdaq.com/api/screener/stocks?tableonly=true&limit=0";
 
         let response = self.http_client.get(url).send().await?.error_for_status()?;
-
-        let nasdaq_response: NasdaqResponse = response.json().await?;
+        let body = response.bytes().await?;
+        if let Some(archive) = &self.raw_archive {
+            archive.record(Source::Nasdaq, "screener", "_universe", url, &body);
+        }
 
         let min_cap = self.min_market_cap_usd;
-        let total_before = nasdaq_response.data.table.rows.len();
-
-        let mut stocks: Vec<(String, Option<f64>)> = nasdaq_response
-            .data
-            .table
-            .rows
-            .into_iter()
-            .filter_map(|stock| {
-                if stock.symbol.is_empty() || is_junk_symbol(&stock.symbol) {
-                    return None;
-                }
-                let mc = parse_market_cap(&stock.market_cap)?;
-                if mc < min_cap {
-                    return None;
-                }
-                Some((
-                    crate::symbols::normalize_symbol_key(&stock.symbol),
-                    Some(mc),
-                ))
-            })
-            .collect();
+        let ScreenerUniverse {
+            names,
+            listings,
+            mut stocks,
+            small_caps,
+            total_rows: total_before,
+        } = screen_universe(&body, min_cap)?;
+        drop(body);
+        self.track_renames(names).await;
+        if let Err(e) = self.db.replace_stock_listings(&listings).await {
+            warn!("Failed to save stock listings: {}", e);
+        }
+        drop(listings);
+        *self.small_cap_caps.write().await = small_caps;
 
         let mut seen: std::collections::HashSet<String> =
             stocks.iter().map(|(symbol, _)| symbol.clone()).collect();
@@ -686,11 +1713,170 @@ impl AnalysisEngine {
         Ok(stocks)
     }
 
+    /// Compare this universe's company names with the previous fetch's and
+    /// move renamed tickers over. Best-effort: failures are logged.
+    async fn track_renames(&self, current: HashMap<String, String>) {
+        let previous = match self.db.get_universe_names().await {
+            Ok(previous) => previous,
+            Err(e) => {
+                warn!("Failed to load previous universe names: {}", e);
+                return;
+            }
+        };
+        // A truncated screener response would look like mass delistings.
+        if current.len() < previous.len() / 2 {
+            warn!(
+                "Universe shrank from {} to {} symbols; skipping rename detection",
+                previous.len(),
+                current.len()
+            );
+            return;
+        }
+        for (old, new) in renames::detect_renames(&previous, &current) {
+            let company_name = current.get(&new).cloned().unwrap_or_default();
+            if let Err(e) = self.apply_rename(&old, &new, company_name).await {
+                warn!("Failed to migrate renamed ticker {} → {}: {}", old, new, e);
+            }
+        }
+        if let Err(e) = self.db.replace_universe_names(&current).await {
+            warn!("Failed to save universe names: {}", e);
+        }
+    }
+
+    async fn apply_rename(&self, old: &str, new: &str, company_name: String) -> anyhow::Result<()> {
+        info!("🔁 Ticker rename detected: {} → {}", old, new);
+        self.db.migrate_symbol(old, new).await?;
+        if let Some(engine) = &self.alert_engine {
+            engine.repo().rename_symbol(old, new).await?;
+        }
+        self.db
+            .save_symbol_alias(&SymbolAlias {
+                old_symbol: old.to_string(),
+                new_symbol: new.to_string(),
+                company_name,
+                detected_at: Utc::now(),
+            })
+            .await?;
+        self.cache.add_symbol_alias(old, new);
+        self.cache.invalidate_stock(old).await;
+        Ok(())
+    }
+
     fn parse_market_cap(market_cap_str: &str) -> Option<f64> {
         parse_market_cap(market_cap_str)
     }
 }
 
+/// Data-quality gate: reject thinly-traded / brand-new / delisted stocks
+/// before running any indicator math. Without this, the feed fills up with
+/// tickers whose RSI is based on 5 bars of zero-volume noise. Returns the
+/// latest bar.
+fn usable_latest_bar<'a>(
+    symbol: &str,
+    historical_prices: &'a [HistoricalPrice],
+) -> anyhow::Result<&'a HistoricalPrice> {
+    const MIN_BARS: usize = 30;
+    if historical_prices.len() < MIN_BARS {
+        return Err(anyhow::anyhow!(
+            "{}: only {} bars (need {}+)",
+            symbol,
+            historical_prices.len(),
+            MIN_BARS
+        ));
+    }
+
+    let latest_price = historical_prices
+        .last()
+        .ok_or_else(|| anyhow::anyhow!("No price data for {}", symbol))?;
+
+    if latest_price.volume <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "{}: latest bar has zero volume, refusing to save analysis",
+            symbol
+        ));
+    }
+    Ok(latest_price)
+}
+
+/// The analysis a cycle at `analyzed_at` would have built from these bars
+/// and NASDAQ quote, without the stages that need live state (news,
+/// earnings, sector ETFs, cross-section, signals, notes). Used by
+/// `replay.rs` to recompute history from archived responses.
+pub(crate) fn replayed_analysis(
+    symbol: &str,
+    historical_prices: &[HistoricalPrice],
+    technicals: Option<NasdaqTechnicals>,
+    market_cap: Option<f64>,
+    analyzed_at: chrono::DateTime<Utc>,
+    indicator_config: &IndicatorConfig,
+) -> anyhow::Result<StockAnalysis> {
+    let latest_price = usable_latest_bar(symbol, historical_prices)?;
+    let indicators = AnalysisEngine::indicator_stage(indicator_config, historical_prices, None);
+    let previous_price = historical_prices.get(historical_prices.len().saturating_sub(2));
+    let quote = resolve_quote(latest_price, previous_price, technicals.as_ref());
+    let rsi = indicators.rsi;
+    let asset_type = classify_asset(symbol, technicals.as_ref());
+    Ok(StockAnalysis {
+        id: None,
+        symbol: symbol.to_string(),
+        price: quote.price,
+        price_change: quote.price_change,
+        price_change_percent: quote.price_change_percent,
+        rsi,
+        sma_20: indicators.sma_20,
+        sma_50: indicators.sma_50,
+        sma_200: indicators.sma_200,
+        macd: indicators.macd,
+        volume: units::to_u64(latest_price.volume),
+        market_cap: market_cap.and_then(units::to_u64),
+        sector: technicals.as_ref().and_then(|t| t.sector.clone()),
+        is_oversold: TechnicalIndicators::is_oversold(rsi),
+        is_overbought: TechnicalIndicators::is_overbought(rsi),
+        stoch_oversold: TechnicalIndicators::is_stoch_oversold(indicators.stochastic.as_ref()),
+        stoch_overbought: TechnicalIndicators::is_stoch_overbought(indicators.stochastic.as_ref()),
+        analyzed_at,
+        bollinger: indicators.bollinger,
+        stochastic: indicators.stochastic,
+        williams_r: indicators.williams_r,
+        cci: indicators.cci,
+        mfi: indicators.mfi,
+        vwap: indicators.vwap,
+        obv: indicators.obv,
+        cross_signal: indicators.cross_signal,
+        cross_date: indicators.cross_date,
+        supertrend: indicators.supertrend,
+        supertrend_flip_date: indicators.supertrend_flip_date,
+        support_resistance: indicators.support_resistance,
+        indicator_periods: indicators.periods,
+        earnings: None,
+        technicals,
+        news: None,
+        sector_relative: None,
+        performance: analytics::performance_returns(historical_prices),
+        // Depend on RISK_FREE_RATE_PCT, so not replayed.
+        sharpe_ratio: None,
+        sortino_ratio: None,
+        cross_section: None,
+        percentiles: None,
+        external_signals: Vec::new(),
+        warnings: Vec::new(),
+        indexes: IndexDataProvider::indexes_for(symbol),
+        notes: None,
+        tags: Vec::new(),
+        asset_type,
+    })
+}
+
+/// Asset type from the symbol and, when NASDAQ answered, its company name
+/// and asset class.
+fn classify_asset(symbol: &str, technicals: Option<&NasdaqTechnicals>) -> AssetType {
+    asset_types::classify(
+        symbol,
+        technicals.and_then(|t| t.company_name.as_deref()),
+        technicals.and_then(|t| t.asset_class.as_deref()),
+    )
+}
+
 #[derive(Debug, PartialEq)]
 struct ResolvedQuote {
     price: f64,
@@ -762,6 +1948,24 @@ fn resolve_quote(
     }
 }
 
+/// Up to `size` entries of `pool` starting at `cursor` (wrapping), and the
+/// cursor for the next call.
+fn rotating_chunk<T: Clone>(pool: &[T], cursor: usize, size: usize) -> (Vec<T>, usize) {
+    if pool.is_empty() || size == 0 {
+        return (Vec::new(), 0);
+    }
+    let start = cursor % pool.len();
+    let take = size.min(pool.len());
+    let chunk = pool
+        .iter()
+        .cycle()
+        .skip(start)
+        .take(take)
+        .cloned()
+        .collect();
+    (chunk, (start + take) % pool.len())
+}
+
 /// Reject warrants, units, rights, preferred shares, and other non-common-stock
 /// tickers that clutter the NASDAQ screener. Match is case-insensitive on the
 /// *suffix* following a dot/dash/slash so we don't accidentally drop legit
@@ -772,19 +1976,67 @@ fn resolve_quote(
 ///   * `FOO.U`, `FOO-U` — units (typical SPAC structure)
 ///   * `FOO.R`, `FOO-R` — rights
 pub(crate) fn is_junk_symbol(symbol: &str) -> bool {
-    // Find the suffix after the last '.', '-', or '/'. Case-insensitive.
-    let upper = symbol.to_ascii_uppercase();
-    let last_sep = upper.rfind(|c: char| c == '.' || c == '-' || c == '/');
-    let Some(idx) = last_sep else {
-        return false;
-    };
-    let suffix = &upper[idx + 1..];
-
-    // Preserved as common-stock sub-classes we must NOT reject, despite their
-    // separator-prefixed letters. BRK-B, BF-B, HEI-A, GOOG-L style tickers are
-    // single-letter share classes (A/B/C/K/L), not warrants/units/rights.
-    // Warrants/units/rights use a distinct set of suffixes:
-    matches!(suffix, "W" | "WS" | "WSA" | "WSB" | "U" | "UN" | "R" | "RT")
+    // Single-letter share classes (BRK-B, BF-B, HEI-A) are common stock and
+    // have no suffix type, so they're kept.
+    asset_types::suffix_type(symbol).is_some()
+}
+
+/// The screener reduced to what a cycle needs.
+#[derive(Debug, Default)]
+struct ScreenerUniverse {
+    /// Normalized company name per symbol, for rename tracking.
+    names: HashMap<String, String>,
+    /// Every listed symbol with its display name, for `/api/search`.
+    listings: Vec<Stock>,
+    /// Common stocks at or over the market-cap floor.
+    stocks: Vec<(String, Option<f64>)>,
+    /// Russell 2000 members under the floor, with their caps.
+    small_caps: HashMap<String, f64>,
+    total_rows: usize,
+}
+
+/// Filter and convert screener rows while the body is parsed, so the
+/// response is never held as owned rows.
+fn screen_universe(body: &[u8], min_cap: f64) -> anyhow::Result<ScreenerUniverse> {
+    let mut universe = ScreenerUniverse::default();
+    let total_rows = nasdaq::parse_screener_rows(body, |row| {
+        if row.symbol.is_empty() {
+            return;
+        }
+        let symbol = crate::symbols::normalize_symbol_key(&row.symbol);
+        universe
+            .names
+            .insert(symbol.clone(), renames::normalize_company_name(&row.name));
+        universe.listings.push(Stock {
+            id: None,
+            symbol: symbol.clone(),
+            name: row.name.trim().to_string(),
+            price: parse_market_cap(&row.last_sale).unwrap_or(0.0),
+            market_cap: parse_market_cap(&row.market_cap).map(|mc| mc as u64),
+            volume: None,
+            sector: None,
+            asset_type: asset_types::classify(&symbol, Some(&row.name), None),
+            last_updated: Utc::now(),
+        });
+        if is_junk_symbol(&row.symbol) {
+            return;
+        }
+        let Some(mc) = parse_market_cap(&row.market_cap) else {
+            return;
+        };
+        if mc < min_cap {
+            if IndexDataProvider::indexes_for(&symbol)
+                .iter()
+                .any(|index| index == "russell2000")
+            {
+                universe.small_caps.insert(symbol, mc);
+            }
+            return;
+        }
+        universe.stocks.push((symbol, Some(mc)));
+    })?;
+    universe.total_rows = total_rows;
+    Ok(universe)
 }
 
 /// Parse a NASDAQ screener `marketCap` string.
@@ -808,7 +2060,6 @@ pub(crate) fn parse_market_cap(market_cap_str: &str) -> Option<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{NasdaqResponse, NasdaqStock};
     use chrono::{TimeZone, Utc};
 
     fn historical_price(close: f64, volume: f64) -> HistoricalPrice {
@@ -830,6 +2081,8 @@ mod tests {
     ) -> NasdaqTechnicals {
         NasdaqTechnicals {
             exchange: None,
+            company_name: None,
+            asset_class: None,
             sector: None,
             industry: None,
             one_year_target: None,
@@ -903,6 +2156,33 @@ mod tests {
         assert_opt_close(quote.price_change_percent, 2.0);
     }
 
+    #[test]
+    fn test_replayed_analysis_uses_archived_quote_and_time() {
+        let at = Utc.with_ymd_and_hms(2025, 6, 2, 14, 30, 5).unwrap();
+        let prices: Vec<HistoricalPrice> = (0..60)
+            .map(|i| historical_price(100.0 + i as f64, 1_000_000.0))
+            .collect();
+        let mut tech = technicals(Some(160.0), Some(1.0), Some(0.6), None);
+        tech.sector = Some("Technology".to_string());
+        let config = IndicatorConfig {
+            sma_long: 30,
+            ..Default::default()
+        };
+
+        let analysis =
+            replayed_analysis("AAPL", &prices, Some(tech), Some(3.0e12), at, &config).unwrap();
+        assert_eq!(analysis.analyzed_at, at);
+        assert_close(analysis.price, 160.0);
+        assert_eq!(analysis.sector.as_deref(), Some("Technology"));
+        assert_eq!(analysis.market_cap, Some(3_000_000_000_000));
+        assert!(analysis.rsi.is_some());
+        // sma_50 holds the configured long SMA, and the periods are kept.
+        assert_opt_close(analysis.sma_50, 144.5);
+        assert_eq!(analysis.indicator_periods, Some(config));
+
+        assert!(replayed_analysis("AAPL", &prices[..5], None, None, at, &config).is_err());
+    }
+
     #[test]
     fn test_parse_market_cap_valid() {
         assert_eq!(parse_market_cap("$1,234,567,890"), Some(1_234_567_890.0));
@@ -926,6 +2206,17 @@ mod tests {
         );
     }
 
+    /// A screener body with one row per `(symbol, name, market cap)`.
+    fn screener_body(rows: &[(&str, &str, &str)]) -> Vec<u8> {
+        let rows: Vec<_> = rows
+            .iter()
+            .map(|(symbol, name, cap)| {
+                serde_json::json!({ "symbol": symbol, "name": name, "marketCap": cap })
+            })
+            .collect();
+        serde_json::to_vec(&serde_json::json!({ "data": { "table": { "rows": rows } } })).unwrap()
+    }
+
     #[test]
     fn test_nasdaq_screener_deserialization() {
         let json = r#"{
@@ -939,45 +2230,34 @@ mod tests {
                 }
             }
         }"#;
-        let resp: NasdaqResponse = serde_json::from_str(json).unwrap();
-        assert_eq!(resp.data.table.rows.len(), 3);
-        assert_eq!(resp.data.table.rows[0].symbol, "AAPL");
-        assert_eq!(resp.data.table.rows[1].market_cap, "0");
+        let mut rows = Vec::new();
+        let count = nasdaq::parse_screener_rows(json.as_bytes(), |row| {
+            rows.push((row.symbol.into_owned(), row.market_cap.into_owned()))
+        })
+        .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(rows[0].0, "AAPL");
+        assert_eq!(rows[1].1, "0");
     }
 
     #[test]
     fn test_nasdaq_screener_filter_pipeline() {
-        // Replicate the filter in fetch_nasdaq_stocks so the filtering behavior
-        // stays covered even when it evolves.
-        let rows = vec![
-            NasdaqStock {
-                symbol: "AAPL".to_string(),
-                name: "Apple".to_string(),
-                market_cap: "3,400,000,000,000".to_string(),
-            },
-            NasdaqStock {
-                symbol: "".to_string(),
-                name: "Empty".to_string(),
-                market_cap: "1,000,000".to_string(),
-            },
-            NasdaqStock {
-                symbol: "ZERO".to_string(),
-                name: "Zero".to_string(),
-                market_cap: "0".to_string(),
-            },
-        ];
-        let filtered: Vec<_> = rows
-            .into_iter()
-            .filter_map(|s| {
-                let mc = parse_market_cap(&s.market_cap)?;
-                if s.symbol.is_empty() {
-                    return None;
-                }
-                Some((s.symbol, mc))
-            })
-            .collect();
-        assert_eq!(filtered.len(), 1);
-        assert_eq!(filtered[0].0, "AAPL");
+        let body = screener_body(&[
+            ("AAPL", "Apple", "3,400,000,000,000"),
+            ("", "Empty", "1,000,000"),
+            ("ZERO", "Zero", "0"),
+        ]);
+        let universe = screen_universe(&body, 0.0).unwrap();
+        assert_eq!(universe.total_rows, 3);
+        assert_eq!(universe.stocks.len(), 1);
+        assert_eq!(universe.stocks[0].0, "AAPL");
+        // Every named symbol is tracked for renames, kept or not.
+        assert_eq!(universe.names.len(), 2);
+        assert_eq!(universe.names["ZERO"], "zero");
+        // ...and listed for search.
+        assert_eq!(universe.listings.len(), 2);
+        assert_eq!(universe.listings[1].name, "Zero");
+        assert_eq!(universe.listings[1].market_cap, None);
     }
 
     // ---- is_junk_symbol ------------------------------------------------------
@@ -1006,62 +2286,40 @@ mod tests {
     #[test]
     fn test_screener_filter_with_min_market_cap_and_junk() {
         // Mix of legit large caps, warrants/SPACs, and sub-threshold micro caps.
-        let rows = vec![
-            NasdaqStock {
-                symbol: "AAPL".into(),
-                name: "Apple".into(),
-                market_cap: "3,400,000,000,000".into(),
-            },
-            NasdaqStock {
-                symbol: "MSFT".into(),
-                name: "Microsoft".into(),
-                market_cap: "3,000,000,000,000".into(),
-            },
-            NasdaqStock {
-                symbol: "SPAC.U".into(),
-                name: "SPAC Unit".into(),
-                market_cap: "500,000,000".into(),
-            },
-            NasdaqStock {
-                symbol: "XYZ.WS".into(),
-                name: "Warrant".into(),
-                market_cap: "100,000,000".into(),
-            },
-            NasdaqStock {
-                symbol: "MICRO".into(),
-                name: "Micro-cap".into(),
-                market_cap: "50,000,000".into(),
-            },
-            NasdaqStock {
-                symbol: "ZERO".into(),
-                name: "Zero".into(),
-                market_cap: "0".into(),
-            },
-            NasdaqStock {
-                symbol: "BRK-B".into(),
-                name: "Berkshire".into(),
-                market_cap: "1,000,000,000,000".into(),
-            },
-        ];
+        let body = screener_body(&[
+            ("AAPL", "Apple", "3,400,000,000,000"),
+            ("MSFT", "Microsoft", "3,000,000,000,000"),
+            ("SPAC.U", "SPAC Unit", "500,000,000"),
+            ("XYZ.WS", "Warrant", "100,000,000"),
+            ("MICRO", "Micro-cap", "50,000,000"),
+            ("ZERO", "Zero", "0"),
+            ("BRK-B", "Berkshire", "1,000,000,000,000"),
+        ]);
         let min_cap = 300_000_000.0;
 
-        let kept: Vec<_> = rows
+        let kept: Vec<_> = screen_universe(&body, min_cap)
+            .unwrap()
+            .stocks
             .into_iter()
-            .filter_map(|s| {
-                if s.symbol.is_empty() || is_junk_symbol(&s.symbol) {
-                    return None;
-                }
-                let mc = parse_market_cap(&s.market_cap)?;
-                if mc < min_cap {
-                    return None;
-                }
-                Some(s.symbol)
-            })
+            .map(|(symbol, _)| symbol)
             .collect();
 
         assert_eq!(kept, vec!["AAPL", "MSFT", "BRK-B"]);
     }
 
+    #[test]
+    fn test_rotating_chunk_wraps() {
+        let pool = ["A", "B", "C", "D", "E"];
+        let (first, cursor) = rotating_chunk(&pool, 0, 2);
+        assert_eq!((first, cursor), (vec!["A", "B"], 2));
+        let (wrapped, cursor) = rotating_chunk(&pool, 4, 2);
+        assert_eq!((wrapped, cursor), (vec!["E", "A"], 1));
+        // A pool that shrank since the cursor was taken still wraps.
+        let (all, cursor) = rotating_chunk(&pool, 12, 10);
+        assert_eq!((all, cursor), (vec!["C", "D", "E", "A", "B"], 2));
+        assert!(rotating_chunk::<&str>(&[], 3, 2).0.is_empty());
+    }
+
     // --- Circuit breaker -------------------------------------------------
 
     #[tokio::test]
@@ -1131,4 +2389,27 @@ mod tests {
         assert!(cb.is_open("AAPL").await);
         assert!(!cb.is_open("MSFT").await, "MSFT should be untouched");
     }
+
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn prop_market_cap_is_positive_and_finite(raw in any::<String>()) {
+            prop_assert!(parse_market_cap(&raw).is_none_or(|v| v.is_finite() && v > 0.0));
+        }
+
+        #[test]
+        fn prop_market_cap_round_trips_screener_format(cap in 1u64..10_000_000_000_000) {
+            let digits = cap.to_string();
+            let mut grouped = String::new();
+            for (i, c) in digits.chars().enumerate() {
+                if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                    grouped.push(',');
+                }
+                grouped.push(c);
+            }
+            prop_assert_eq!(parse_market_cap(&format!("${}", grouped)), Some(cap as f64));
+            prop_assert_eq!(parse_market_cap(&digits), Some(cap as f64));
+        }
+    }
 }
@@ -0,0 +1,517 @@
+//! Historical "what if I had bought" simulation and total-return series.
+//!
+//! Pure functions over daily bars: buy a dollar amount at the first close on
+//! or after a start date, hold to the last bar, and report the ending value,
+//! CAGR and the drawdowns sat through along the way. Price-only figures are
+//! reported next to total-return ones (dividends reinvested at the
+//! ex-dividend close) so yield-heavy names compare fairly with growth names.
+//! Trailing 1-week to year-to-date returns for every analysis come from
+//! [`performance_returns`], its Sharpe and Sortino ratios from
+//! [`risk_ratios`]. [`adjust_for_events`] back-adjusts bars for splits and
+//! dividends, and [`heikin_ashi`] smooths them for charting.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DividendEvent, HistoricalPrice, PerformanceReturns, RiskRatios, SplitEvent};
+use crate::sectors::trailing_return;
+
+/// How many of the deepest drawdown episodes to report.
+const MAX_EPISODES: usize = 5;
+
+/// One peak-to-trough decline, and when (if ever) the peak was regained.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Drawdown {
+    pub peak_date: DateTime<Utc>,
+    pub trough_date: DateTime<Utc>,
+    pub recovery_date: Option<DateTime<Utc>>,
+    /// Negative percent, e.g. `-33.9`.
+    pub depth_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatIfResult {
+    pub symbol: String,
+    pub amount: f64,
+    /// Date of the bar actually bought (first session on/after the request).
+    pub entry_date: DateTime<Utc>,
+    pub entry_price: f64,
+    pub shares: f64,
+    pub as_of: DateTime<Utc>,
+    pub current_price: f64,
+    pub current_value: f64,
+    pub total_return_pct: f64,
+    /// `None` for holding periods shorter than a month, where annualising
+    /// is meaningless.
+    pub cagr_pct: Option<f64>,
+    pub max_drawdown_pct: f64,
+    /// Deepest episodes first.
+    pub drawdowns: Vec<Drawdown>,
+    pub with_dividends: TotalReturn,
+}
+
+/// The same holding with every dividend reinvested.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TotalReturn {
+    pub dividends_reinvested: usize,
+    pub shares: f64,
+    pub current_value: f64,
+    pub total_return_pct: f64,
+    pub cagr_pct: Option<f64>,
+}
+
+/// Total-return index aligned with `prices`: each close times the share
+/// count one original share has grown to by reinvesting every dividend at
+/// its ex-date close. Starts equal to the first close, so it can be charted
+/// against the raw series. Dividends dated before the first bar are ignored.
+pub fn total_return_index(prices: &[HistoricalPrice], dividends: &[DividendEvent]) -> Vec<f64> {
+    reinvest(prices, dividends).0
+}
+
+/// `(index, dividends applied)`.
+fn reinvest(prices: &[HistoricalPrice], dividends: &[DividendEvent]) -> (Vec<f64>, usize) {
+    let Some(first) = prices.first() else {
+        return (Vec::new(), 0);
+    };
+    let mut pending = dividends
+        .iter()
+        .skip_while(|d| d.date.date_naive() < first.date.date_naive())
+        .peekable();
+    let mut shares = 1.0;
+    let mut applied = 0;
+    let index = prices
+        .iter()
+        .map(|bar| {
+            while let Some(d) = pending.next_if(|d| d.date.date_naive() <= bar.date.date_naive()) {
+                if bar.close > 0.0 {
+                    shares *= 1.0 + d.amount / bar.close;
+                    applied += 1;
+                }
+            }
+            bar.close * shares
+        })
+        .collect();
+    (index, applied)
+}
+
+/// Back-adjust `prices` (oldest first) in place the way Yahoo computes its
+/// adjusted close, so the series has no gap at a split or ex-dividend date:
+/// every bar before a split is scaled by `1 / ratio` (volume by `ratio`),
+/// and every bar before an ex-date by `1 - amount / previous close`. The
+/// latest bar is left as traded; events at or before the first bar are
+/// ignored.
+pub fn adjust_for_events(
+    prices: &mut [HistoricalPrice],
+    splits: &[SplitEvent],
+    dividends: &[DividendEvent],
+) {
+    let mut price_factor = 1.0;
+    let mut volume_factor = 1.0;
+    for i in (1..prices.len()).rev() {
+        let (earlier, later) = prices.split_at_mut(i);
+        let previous = &mut earlier[i - 1];
+        let since_previous = |date: &DateTime<Utc>| {
+            date.date_naive() > previous.date.date_naive()
+                && date.date_naive() <= later[0].date.date_naive()
+        };
+        for ratio in splits
+            .iter()
+            .filter(|s| since_previous(&s.date))
+            .filter_map(SplitEvent::ratio)
+        {
+            price_factor /= ratio;
+            volume_factor *= ratio;
+        }
+        for dividend in dividends.iter().filter(|d| since_previous(&d.date)) {
+            if previous.close > dividend.amount {
+                price_factor *= 1.0 - dividend.amount / previous.close;
+            }
+        }
+        previous.open *= price_factor;
+        previous.high *= price_factor;
+        previous.low *= price_factor;
+        previous.close *= price_factor;
+        previous.volume *= volume_factor;
+    }
+}
+
+/// Heikin-Ashi candles for `prices` (oldest first): each close is the bar's
+/// OHLC average, each open the midpoint of the previous candle's open and
+/// close (the first bar's open and close for the first candle), and high and
+/// low stretch to cover both. Dates and volume are kept.
+pub fn heikin_ashi(prices: &[HistoricalPrice]) -> Vec<HistoricalPrice> {
+    let mut candles: Vec<HistoricalPrice> = Vec::with_capacity(prices.len());
+    for bar in prices {
+        let close = (bar.open + bar.high + bar.low + bar.close) / 4.0;
+        let open = match candles.last() {
+            Some(previous) => (previous.open + previous.close) / 2.0,
+            None => (bar.open + bar.close) / 2.0,
+        };
+        candles.push(HistoricalPrice {
+            date: bar.date,
+            open,
+            high: bar.high.max(open).max(close),
+            low: bar.low.min(open).min(close),
+            close,
+            volume: bar.volume,
+        });
+    }
+    candles
+}
+
+fn cagr_pct(growth: f64, years: f64) -> Option<f64> {
+    (years >= 1.0 / 12.0).then(|| (growth.powf(1.0 / years) - 1.0) * 100.0)
+}
+
+/// Simulate buying `amount` of `symbol` on `start`. `prices` must be daily
+/// bars in date order. Returns `None` when no bar falls on/after `start`.
+pub fn what_if(
+    symbol: &str,
+    prices: &[HistoricalPrice],
+    dividends: &[DividendEvent],
+    start: NaiveDate,
+    amount: f64,
+) -> Option<WhatIfResult> {
+    let from = prices.iter().position(|p| p.date.date_naive() >= start)?;
+    let held = &prices[from..];
+    let entry = held.first()?;
+    let last = held.last()?;
+    if entry.close <= 0.0 {
+        return None;
+    }
+
+    let shares = amount / entry.close;
+    let growth = last.close / entry.close;
+    let years = (last.date - entry.date).num_days() as f64 / 365.25;
+    let drawdowns = drawdowns(held);
+
+    // The buy fills at the entry close, so a dividend going ex that same
+    // session isn't owned yet.
+    let after_entry: Vec<DividendEvent> = dividends
+        .iter()
+        .filter(|d| d.date.date_naive() > entry.date.date_naive())
+        .cloned()
+        .collect();
+    let (index, applied) = reinvest(held, &after_entry);
+    let tr_growth = index.last().copied().unwrap_or(entry.close) / entry.close;
+    let with_dividends = TotalReturn {
+        dividends_reinvested: applied,
+        shares: shares * tr_growth / growth,
+        current_value: amount * tr_growth,
+        total_return_pct: (tr_growth - 1.0) * 100.0,
+        cagr_pct: cagr_pct(tr_growth, years),
+    };
+
+    Some(WhatIfResult {
+        symbol: symbol.to_string(),
+        amount,
+        entry_date: entry.date,
+        entry_price: entry.close,
+        shares,
+        as_of: last.date,
+        current_price: last.close,
+        current_value: shares * last.close,
+        total_return_pct: (growth - 1.0) * 100.0,
+        cagr_pct: cagr_pct(growth, years),
+        max_drawdown_pct: drawdowns.first().map(|d| d.depth_pct).unwrap_or(0.0),
+        drawdowns,
+        with_dividends,
+    })
+}
+
+/// Peak-to-trough episodes on closing prices, deepest first.
+pub fn drawdowns(prices: &[HistoricalPrice]) -> Vec<Drawdown> {
+    let mut episodes = Vec::new();
+    let Some(first) = prices.first() else {
+        return episodes;
+    };
+    let mut peak = first;
+    let mut trough: Option<&HistoricalPrice> = None;
+
+    for bar in &prices[1..] {
+        if bar.close >= peak.close {
+            if let Some(t) = trough.take() {
+                episodes.push(episode(peak, t, Some(bar.date)));
+            }
+            peak = bar;
+        } else if trough.is_none_or(|t| bar.close < t.close) {
+            trough = Some(bar);
+        }
+    }
+    if let Some(t) = trough {
+        episodes.push(episode(peak, t, None));
+    }
+
+    episodes.sort_by(|a, b| {
+        a.depth_pct
+            .partial_cmp(&b.depth_pct)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    episodes.truncate(MAX_EPISODES);
+    episodes
+}
+
+/// Sessions per trailing period for `performance_returns`.
+const SESSIONS_1W: usize = 5;
+const SESSIONS_1M: usize = 21;
+const SESSIONS_3M: usize = 63;
+
+/// 1-week, 1-month, 3-month and year-to-date returns as of the last bar.
+/// YTD is measured from the last close of the previous calendar year, so it
+/// needs history reaching back past January 1st.
+pub fn performance_returns(prices: &[HistoricalPrice]) -> Option<PerformanceReturns> {
+    let last = prices.last()?;
+    let year = last.date.year();
+    let ytd_base = prices.iter().rev().find(|bar| bar.date.year() < year);
+    Some(PerformanceReturns {
+        return_1w_pct: trailing_return(prices, SESSIONS_1W),
+        return_1m_pct: trailing_return(prices, SESSIONS_1M),
+        return_3m_pct: trailing_return(prices, SESSIONS_3M),
+        return_ytd_pct: ytd_base
+            .filter(|base| base.close > 0.0)
+            .map(|base| (last.close / base.close - 1.0) * 100.0),
+    })
+}
+
+/// Trading sessions in a year, for annualising daily figures.
+const SESSIONS_PER_YEAR: f64 = 252.0;
+/// Fewer daily returns than this gives no Sharpe or Sortino ratio.
+const MIN_RISK_RETURNS: usize = 60;
+
+/// Annualised Sharpe and Sortino ratios of the daily returns in `prices`,
+/// net of `risk_free_rate_pct` (an annual rate, e.g. `4.5`). Each is `None`
+/// with fewer than 60 returns or no dispersion to divide by.
+pub fn risk_ratios(prices: &[HistoricalPrice], risk_free_rate_pct: f64) -> RiskRatios {
+    let daily_risk_free = risk_free_rate_pct / 100.0 / SESSIONS_PER_YEAR;
+    let excess: Vec<f64> = prices
+        .windows(2)
+        .filter(|pair| pair[0].close > 0.0)
+        .map(|pair| pair[1].close / pair[0].close - 1.0 - daily_risk_free)
+        .collect();
+    if excess.len() < MIN_RISK_RETURNS {
+        return RiskRatios::default();
+    }
+    let n = excess.len() as f64;
+    let mean = excess.iter().sum::<f64>() / n;
+    let std_dev = (excess.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    // Downside deviation: only shortfalls below the risk-free rate count,
+    // averaged over every session.
+    let downside = (excess.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+    // Below this the returns are constant up to rounding.
+    let annualise =
+        |deviation: f64| (deviation > 1e-12).then(|| mean / deviation * SESSIONS_PER_YEAR.sqrt());
+    RiskRatios {
+        sharpe_ratio: annualise(std_dev),
+        sortino_ratio: annualise(downside),
+    }
+}
+
+fn episode(
+    peak: &HistoricalPrice,
+    trough: &HistoricalPrice,
+    recovery_date: Option<DateTime<Utc>>,
+) -> Drawdown {
+    Drawdown {
+        peak_date: peak.date,
+        trough_date: trough.date,
+        recovery_date,
+        depth_pct: (trough.close / peak.close - 1.0) * 100.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn bars(start: DateTime<Utc>, step_days: i64, closes: &[f64]) -> Vec<HistoricalPrice> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| HistoricalPrice {
+                date: start + Duration::days(i as i64 * step_days),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn buys_first_bar_on_or_after_start_and_annualises() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 2, 21, 0, 0).unwrap();
+        // Roughly yearly bars: 100 → 121 over two years = 10% CAGR.
+        let prices = bars(start, 365, &[80.0, 100.0, 110.0, 121.0]);
+        let r = what_if(
+            "AAPL",
+            &prices,
+            &[],
+            NaiveDate::from_ymd_opt(2020, 6, 1).unwrap(),
+            1000.0,
+        )
+        .unwrap();
+        assert_eq!(r.entry_price, 100.0);
+        assert_eq!(r.shares, 10.0);
+        assert!((r.current_value - 1210.0).abs() < 1e-9);
+        assert!((r.total_return_pct - 21.0).abs() < 1e-9);
+        assert!((r.cagr_pct.unwrap() - 10.0).abs() < 0.05);
+        assert_eq!(r.max_drawdown_pct, 0.0);
+        assert_eq!(r.with_dividends.total_return_pct, r.total_return_pct);
+
+        assert!(what_if(
+            "AAPL",
+            &prices,
+            &[],
+            NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+            1.0
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn drawdown_episodes_track_recovery() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+        let prices = bars(start, 1, &[100.0, 90.0, 80.0, 100.0, 110.0, 99.0]);
+        let dd = drawdowns(&prices);
+        assert_eq!(dd.len(), 2);
+
+        assert!((dd[0].depth_pct + 20.0).abs() < 1e-9);
+        assert_eq!(dd[0].trough_date, prices[2].date);
+        assert_eq!(dd[0].recovery_date, Some(prices[3].date));
+
+        // Still under water at the last bar.
+        assert!((dd[1].depth_pct + 10.0).abs() < 1e-9);
+        assert_eq!(dd[1].recovery_date, None);
+    }
+
+    #[test]
+    fn reinvested_dividends_lift_total_return() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+        let prices = bars(start, 1, &[100.0, 100.0, 50.0, 50.0]);
+        let div = |day: i64, amount: f64| DividendEvent {
+            date: start + Duration::days(day),
+            amount,
+        };
+        // $2 at 100 → 1.02 shares, then $5 at 50 → ×1.1.
+        let index = total_return_index(&prices, &[div(-3, 9.0), div(1, 2.0), div(2, 5.0)]);
+        assert_eq!(index[0], 100.0);
+        assert!((index[1] - 102.0).abs() < 1e-9);
+        assert!((index[3] - 50.0 * 1.02 * 1.1).abs() < 1e-9);
+
+        let r = what_if(
+            "T",
+            &prices,
+            &[div(0, 1.0), div(2, 5.0)],
+            start.date_naive(),
+            1000.0,
+        )
+        .unwrap();
+        assert_eq!(r.total_return_pct, -50.0);
+        // Same-day dividend is skipped: bought at that close.
+        assert_eq!(r.with_dividends.dividends_reinvested, 1);
+        assert!((r.with_dividends.current_value - 550.0).abs() < 1e-9);
+        assert!((r.with_dividends.shares - 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn performance_returns_cover_trailing_periods_and_ytd() {
+        // Daily bars from Dec 1st: 30 in December, then up 1 per day.
+        let start = Utc.with_ymd_and_hms(2024, 12, 1, 21, 0, 0).unwrap();
+        let closes: Vec<f64> = (0..100).map(|i| 100.0 + i as f64).collect();
+        let prices = bars(start, 1, &closes);
+        let r = performance_returns(&prices).unwrap();
+
+        // Last close 199; 5 sessions back is 194.
+        assert!((r.return_1w_pct.unwrap() - (199.0 / 194.0 - 1.0) * 100.0).abs() < 1e-9);
+        assert!((r.return_1m_pct.unwrap() - (199.0 / 178.0 - 1.0) * 100.0).abs() < 1e-9);
+        assert!(r.return_3m_pct.is_some());
+        // Dec 31st closed at 130.
+        assert!((r.return_ytd_pct.unwrap() - (199.0 / 130.0 - 1.0) * 100.0).abs() < 1e-9);
+
+        let short = performance_returns(&prices[95..]).unwrap();
+        assert_eq!(short.return_1m_pct, None);
+        assert_eq!(short.return_ytd_pct, None);
+        assert!(performance_returns(&[]).is_none());
+    }
+
+    #[test]
+    fn risk_ratios_annualise_excess_returns() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+        // Alternating +2% / -1% sessions.
+        let mut closes = vec![100.0];
+        for i in 0..100 {
+            let last = *closes.last().unwrap();
+            closes.push(last * if i % 2 == 0 { 1.02 } else { 0.99 });
+        }
+        let prices = bars(start, 1, &closes);
+        let ratios = risk_ratios(&prices, 0.0);
+        let sharpe = ratios.sharpe_ratio.unwrap();
+        let sortino = ratios.sortino_ratio.unwrap();
+        assert!(sharpe > 0.0);
+        // Only the down sessions count against Sortino.
+        assert!(sortino > sharpe);
+        assert!(risk_ratios(&prices, 5.0).sharpe_ratio.unwrap() < sharpe);
+
+        // A steady 0.1% a day has no volatility, only a shortfall against
+        // a 50% risk-free rate.
+        let steady: Vec<f64> = (0..100).map(|i| 100.0 * 1.001f64.powi(i)).collect();
+        let ratios = risk_ratios(&bars(start, 1, &steady), 50.0);
+        assert_eq!(ratios.sharpe_ratio, None);
+        assert!((ratios.sortino_ratio.unwrap() + SESSIONS_PER_YEAR.sqrt()).abs() < 1e-9);
+
+        assert_eq!(risk_ratios(&prices[..50], 0.0), RiskRatios::default());
+    }
+
+    #[test]
+    fn adjusts_bars_before_splits_and_ex_dates() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+        let mut prices = bars(start, 1, &[400.0, 404.0, 101.0, 100.0, 102.0]);
+        for bar in &mut prices {
+            bar.volume = 1_000.0;
+        }
+        let splits = [SplitEvent {
+            date: prices[2].date,
+            numerator: 4.0,
+            denominator: 1.0,
+        }];
+        // $1 ex-dividend on the last bar: 1 - 1/100 before it.
+        let dividends = [DividendEvent {
+            date: prices[4].date,
+            amount: 1.0,
+        }];
+        adjust_for_events(&mut prices, &splits, &dividends);
+
+        let closes: Vec<f64> = prices.iter().map(|p| p.close).collect();
+        let expected = [99.0, 99.99, 99.99, 99.0, 102.0];
+        for (close, expected) in closes.iter().zip(expected) {
+            assert!((close - expected).abs() < 1e-9, "{:?}", closes);
+        }
+        assert_eq!(prices[0].volume, 4_000.0);
+        assert_eq!(prices[2].volume, 1_000.0);
+    }
+
+    #[test]
+    fn heikin_ashi_averages_and_chains_opens() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+        let mut prices = bars(start, 1, &[10.0, 12.0]);
+        prices[0].open = 8.0;
+        prices[0].high = 11.0;
+        prices[0].low = 7.0;
+        prices[1].open = 10.0;
+        prices[1].high = 13.0;
+        prices[1].low = 10.0;
+        prices[1].volume = 500.0;
+
+        let ha = heikin_ashi(&prices);
+        assert_eq!(ha.len(), 2);
+        assert_eq!((ha[0].open, ha[0].close), (9.0, 9.0));
+        assert_eq!((ha[0].high, ha[0].low), (11.0, 7.0));
+        // Open 9 from the first candle, close (10 + 13 + 10 + 12) / 4.
+        assert_eq!((ha[1].open, ha[1].close), (9.0, 11.25));
+        assert_eq!((ha[1].high, ha[1].low), (13.0, 9.0));
+        assert_eq!((ha[1].date, ha[1].volume), (prices[1].date, 500.0));
+        assert!(heikin_ashi(&[]).is_empty());
+    }
+}
@@ -0,0 +1,904 @@
+//! Operator endpoints (read-only switch, dead letters, query stats,
+//! backups, raw archive, cache pins) and the signal ingest webhooks.
+
+use super::{pagination::PageQuery, AppState};
+use crate::{
+    archive::{self, RawArchive},
+    backup::{self, BackupSettings},
+    cache::CacheLayer,
+    db::MongoDB,
+    indicators::IndicatorConfig,
+    ingest::{self, SignalInbox, SignalInput},
+    maintenance::{MaintenanceInput, MaintenanceMode},
+    models::CachePin,
+    notifications::AlertEngine,
+    openrouter::OpenRouterClient,
+    repair,
+    replay::{self, ReplayRequest},
+    retention::HISTORY_COLLECTIONS,
+    storage::Storage,
+    transfer,
+    weekend::{self, WeekendSettings},
+    yahoo::YahooFinanceClient,
+};
+use axum::{
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, FromRef, Path, Query, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, put},
+    Router,
+};
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+pub(super) fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            crate::maintenance::TOGGLE_PATH,
+            get(get_maintenance).put(set_maintenance),
+        )
+        .route("/api/admin/dead-letters", get(list_dead_letters))
+        .route(
+            "/api/admin/dead-letters/requeue",
+            post(requeue_all_dead_letters),
+        )
+        .route(
+            "/api/admin/dead-letters/:symbol/requeue",
+            post(requeue_dead_letter),
+        )
+        .route(
+            "/api/admin/db/stats",
+            get(get_db_stats).delete(reset_db_stats),
+        )
+        .route("/api/admin/db/collections", get(get_collection_stats))
+        .route("/api/admin/corrupt-documents", get(list_corrupt_documents))
+        .route("/api/admin/repair", post(run_repair))
+        .route("/api/admin/cross-section", post(run_cross_section))
+        .route(
+            "/api/admin/weekend",
+            get(get_weekend_status).post(start_weekend_run),
+        )
+        .route("/api/admin/backups", get(list_backups).post(run_backup))
+        .route("/api/admin/export", get(export_data))
+        .route(
+            "/api/admin/import",
+            post(import_data).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
+        .route("/api/admin/archive", get(get_raw_archive))
+        .route(
+            "/api/admin/archive/replay",
+            get(get_replay_status).post(start_replay),
+        )
+        .route(
+            "/api/admin/archive/:date/:symbol",
+            get(get_archived_responses),
+        )
+        .route("/api/cache/stats", get(get_cache_stats))
+        .route("/api/cache/pins", get(list_cache_pins))
+        .route(
+            "/api/cache/pins/:symbol",
+            put(pin_cached_stock).delete(unpin_cached_stock),
+        )
+        .route("/api/ingest/signal", post(ingest_signal))
+        .route("/api/ingest/tradingview", post(ingest_tradingview))
+        .route("/api/ingest/signals", get(list_external_signals))
+}
+
+/// Slice of `AppState` the admin and ingest handlers use.
+#[derive(Clone)]
+pub(super) struct AdminState {
+    db: MongoDB,
+    storage: Storage,
+    cache: CacheLayer,
+    yahoo_client: YahooFinanceClient,
+    openrouter_client: OpenRouterClient,
+    alert_engine: AlertEngine,
+    backups: Option<BackupSettings>,
+    raw_archive: Option<RawArchive>,
+    signals: SignalInbox,
+    ingest_token: Option<String>,
+    maintenance: MaintenanceMode,
+    weekend: WeekendSettings,
+    indicators: IndicatorConfig,
+    history_retention_days: u32,
+}
+
+impl FromRef<AppState> for AdminState {
+    fn from_ref(state: &AppState) -> Self {
+        Self {
+            db: state.db.clone(),
+            storage: state.storage.clone(),
+            cache: state.cache.clone(),
+            yahoo_client: state.yahoo_client.clone(),
+            openrouter_client: state.openrouter_client.clone(),
+            alert_engine: state.alert_engine.clone(),
+            backups: state.backups.clone(),
+            raw_archive: state.raw_archive.clone(),
+            signals: state.signals.clone(),
+            ingest_token: state.ingest_token.clone(),
+            maintenance: state.maintenance.clone(),
+            weekend: state.weekend.clone(),
+            indicators: state.indicators,
+            history_retention_days: state.history_retention_days,
+        }
+    }
+}
+
+/// Current read-only mode.
+async fn get_maintenance(State(state): State<AdminState>) -> impl IntoResponse {
+    Json(json!({ "success": true, "maintenance": state.maintenance.status() }))
+}
+
+/// Switch read-only mode on or off. Stays reachable while read-only.
+async fn set_maintenance(
+    State(state): State<AdminState>,
+    Json(input): Json<MaintenanceInput>,
+) -> impl IntoResponse {
+    let status = state.maintenance.set(input.read_only, input.reason);
+    if status.read_only {
+        tracing::warn!(
+            "🚧 Read-only mode on{}",
+            status
+                .reason
+                .as_deref()
+                .map(|r| format!(": {}", r))
+                .unwrap_or_default()
+        );
+    } else {
+        tracing::info!("✅ Read-only mode off");
+    }
+    Json(json!({ "success": true, "maintenance": status }))
+}
+
+/// Symbols parked in the dead-letter queue, with their recent errors.
+async fn list_dead_letters(State(state): State<AdminState>) -> impl IntoResponse {
+    match state.db.get_dead_letters().await {
+        Ok(entries) => Json(json!({
+            "success": true,
+            "count": entries.len(),
+            "dead_letters": entries
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Return one dead-lettered symbol to the analysis cycle.
+async fn requeue_dead_letter(
+    State(state): State<AdminState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = crate::symbols::normalize_symbol_key(&symbol);
+    match state.db.requeue_dead_letters(Some(&symbol)).await {
+        Ok(requeued) => Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "requeued": requeued > 0
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Return every dead-lettered symbol to the analysis cycle, e.g. after a
+/// provider outage.
+async fn requeue_all_dead_letters(State(state): State<AdminState>) -> impl IntoResponse {
+    match state.db.requeue_dead_letters(None).await {
+        Ok(requeued) => Json(json!({
+            "success": true,
+            "requeued": requeued
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Query timings aggregated by command, collection and filter shape, most
+/// total time first. Shapes with a high `avg_ms` are index candidates.
+async fn get_db_stats(State(state): State<AdminState>) -> impl IntoResponse {
+    let profiler = state.db.profiler();
+    let queries = profiler.snapshot();
+    Json(json!({
+        "success": true,
+        "slow_query_ms": profiler.slow_threshold().as_millis() as u64,
+        "total_queries": queries.iter().map(|q| q.count).sum::<u64>(),
+        "slow_queries": queries.iter().map(|q| q.slow).sum::<u64>(),
+        "queries": queries
+    }))
+}
+
+/// Analyses the last full read couldn't deserialize as stored, with the
+/// error and whether the lenient read recovered them.
+async fn list_corrupt_documents(State(state): State<AdminState>) -> impl IntoResponse {
+    match state.db.get_corrupt_documents().await {
+        Ok(documents) => Json(json!({
+            "success": true,
+            "count": documents.len(),
+            "recovered": documents.iter().filter(|d| d.recovered).count(),
+            "documents": documents
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Database error: {}", e)
+        })),
+    }
+}
+
+/// Run the startup repair pass now: fix known legacy shapes in place and
+/// quarantine what is still unreadable. Clears the `stock_analysis`
+/// corrupt-document report, which the next full read rebuilds.
+async fn run_repair(State(state): State<AdminState>) -> impl IntoResponse {
+    let report = match repair::run(&state.db).await {
+        Ok(report) => report,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Repair failed: {}", e)
+            }))
+        }
+    };
+    info!("🩺 Repair: {}", report);
+    if let Err(e) = state
+        .db
+        .replace_corrupt_documents("stock_analysis", &[])
+        .await
+    {
+        warn!("Failed to clear corrupt documents: {}", e);
+    }
+    Json(json!({ "success": true, "report": report }))
+}
+
+/// Size of every collection, with the retention applied to the history
+/// collections (see `retention.rs`).
+async fn get_collection_stats(State(state): State<AdminState>) -> impl IntoResponse {
+    let stats = match state.db.collection_stats().await {
+        Ok(stats) => stats,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Database error: {}", e)
+            }))
+        }
+    };
+    let retention_days = state.history_retention_days;
+    let mut collections = stats;
+    for collection in &mut collections {
+        let pruned = HISTORY_COLLECTIONS
+            .iter()
+            .any(|(name, _)| *name == collection.name);
+        collection.retention_days = (pruned && retention_days > 0).then_some(retention_days);
+    }
+    Json(json!({
+        "success": true,
+        "history_retention_days": retention_days,
+        "total_storage_bytes": collections.iter().map(|c| c.storage_bytes).sum::<u64>(),
+        "collections": collections
+    }))
+}
+
+/// Start the aggregates over, e.g. after adding an index.
+async fn reset_db_stats(State(state): State<AdminState>) -> impl IntoResponse {
+    state.db.profiler().reset();
+    Json(json!({ "success": true }))
+}
+
+/// Completed backups, newest first.
+async fn list_backups(State(state): State<AdminState>) -> impl IntoResponse {
+    let Some(settings) = &state.backups else {
+        return Json(json!({ "success": false, "error": "Backups are disabled (set BACKUP_DIR)" }));
+    };
+    match backup::list_backups(&settings.dir) {
+        Ok(backups) => Json(json!({
+            "success": true,
+            "count": backups.len(),
+            "retention": settings.retention,
+            "backups": backups
+        })),
+        Err(e) => Json(json!({ "success": false, "error": e.to_string() })),
+    }
+}
+
+/// Largest import body accepted (gzipped, when it is).
+const MAX_IMPORT_BYTES: usize = 1024 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// `gzip` (default) or `ndjson`.
+    pub format: Option<String>,
+    /// `all` or a comma-separated list of collections (default
+    /// `stock_analysis`).
+    pub collections: Option<String>,
+}
+
+/// Stream collections in the seed format (see `transfer.rs`).
+async fn export_data(
+    State(state): State<AdminState>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let gzip = match query.format.as_deref().unwrap_or("gzip") {
+        "gzip" => true,
+        "ndjson" => false,
+        other => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "success": false,
+                    "error": format!("unknown format '{}'; expected gzip or ndjson", other)
+                })),
+            )
+                .into_response()
+        }
+    };
+    let collections = match transfer::parse_collections(query.collections.as_deref()) {
+        Ok(collections) => collections,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "success": false, "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+    let (content_type, extension) = if gzip {
+        ("application/gzip", "ndjson.gz")
+    } else {
+        ("application/x-ndjson", "ndjson")
+    };
+    let filename = format!(
+        "auto-analyser-export-{}.{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+        extension
+    );
+    let body = Body::from_stream(transfer::export_stream(state.db.clone(), collections, gzip));
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Load an export (gzipped or plain NDJSON) from the request body,
+/// replacing documents with the same key unless a stored analysis is newer.
+async fn import_data(State(state): State<AdminState>, body: Bytes) -> impl IntoResponse {
+    if body.is_empty() {
+        return Json(json!({ "success": false, "error": "empty body" }));
+    }
+    match transfer::import(&state.db, state.storage.as_ref(), &state.cache, &body).await {
+        Ok(summary) => {
+            info!(
+                "📥 Imported {} documents",
+                summary.imported.values().sum::<u64>()
+            );
+            Json(json!({
+                "success": true,
+                "imported": summary.imported,
+                "skipped": summary.skipped,
+                "stale": summary.stale,
+            }))
+        }
+        Err(e) => Json(json!({ "success": false, "error": format!("Import failed: {}", e) })),
+    }
+}
+
+/// Back up now, then prune to the retention count.
+async fn run_backup(State(state): State<AdminState>) -> impl IntoResponse {
+    let Some(settings) = &state.backups else {
+        return Json(json!({ "success": false, "error": "Backups are disabled (set BACKUP_DIR)" }));
+    };
+    let manifest = match backup::run_backup(&state.db, settings).await {
+        Ok(manifest) => manifest,
+        Err(e) => return Json(json!({ "success": false, "error": e.to_string() })),
+    };
+    let pruned = backup::prune(settings).unwrap_or_else(|e| {
+        warn!("Backup pruning failed: {}", e);
+        0
+    });
+    Json(json!({ "success": true, "backup": manifest, "pruned": pruned }))
+}
+
+/// Raw response archive settings and per-day usage, newest first.
+async fn get_raw_archive(State(state): State<AdminState>) -> impl IntoResponse {
+    let Some(archive) = state.raw_archive else {
+        return Json(
+            json!({ "success": false, "error": "Raw archive is disabled (set RAW_ARCHIVE_DIR)" }),
+        );
+    };
+    let settings = archive.settings().clone();
+    match tokio::task::spawn_blocking(move || archive.days()).await {
+        Ok(Ok(days)) => Json(json!({
+            "success": true,
+            "dir": settings.dir.display().to_string(),
+            "sample_rate": settings.sample_rate,
+            "retention_days": settings.retention_days,
+            "total_files": days.iter().map(|d| d.files).sum::<u64>(),
+            "total_bytes": days.iter().map(|d| d.size_bytes).sum::<u64>(),
+            "days": days
+        })),
+        Ok(Err(e)) => Json(json!({ "success": false, "error": e.to_string() })),
+        Err(e) => Json(json!({ "success": false, "error": e.to_string() })),
+    }
+}
+
+/// Every archived response for a symbol on a `YYYY-MM-DD` (UTC) day,
+/// oldest first, with the body as parsed JSON (or text if it isn't JSON).
+async fn get_archived_responses(
+    State(state): State<AdminState>,
+    Path((date, symbol)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let Some(archive) = state.raw_archive else {
+        return Json(
+            json!({ "success": false, "error": "Raw archive is disabled (set RAW_ARCHIVE_DIR)" }),
+        );
+    };
+    let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") else {
+        return Json(json!({ "success": false, "error": "date must be YYYY-MM-DD" }));
+    };
+    let symbol = symbol.to_uppercase();
+    let read = {
+        let symbol = symbol.clone();
+        tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<serde_json::Value>> {
+            archive
+                .symbol_entries(date, &symbol)?
+                .into_iter()
+                .map(|entry| {
+                    let response = archive::read_entry(&entry.path)?;
+                    let body = serde_json::from_str(&response.body)
+                        .unwrap_or(serde_json::Value::String(response.body));
+                    Ok(json!({
+                        "source": entry.source,
+                        "kind": entry.kind,
+                        "fetched_at": entry.fetched_at,
+                        "url": response.url,
+                        "body": body
+                    }))
+                })
+                .collect()
+        })
+    };
+    match read.await {
+        Ok(Ok(responses)) => Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "date": date.to_string(),
+            "count": responses.len(),
+            "responses": responses
+        })),
+        Ok(Err(e)) => Json(json!({ "success": false, "error": e.to_string() })),
+        Err(e) => Json(json!({ "success": false, "error": e.to_string() })),
+    }
+}
+
+/// Whether a replay is in progress and the last one's report.
+async fn get_replay_status() -> impl IntoResponse {
+    Json(json!({
+        "success": true,
+        "running": replay::is_running(),
+        "last_report": replay::last_report()
+    }))
+}
+
+/// Replay archived responses into `analysis_history` in the background.
+async fn start_replay(
+    State(state): State<AdminState>,
+    Json(request): Json<ReplayRequest>,
+) -> impl IntoResponse {
+    let Some(archive) = state.raw_archive else {
+        return Json(
+            json!({ "success": false, "error": "Raw archive is disabled (set RAW_ARCHIVE_DIR)" }),
+        );
+    };
+    if request.from > request.to {
+        return Json(json!({ "success": false, "error": "from must not be after to" }));
+    }
+    if replay::is_running() {
+        return Json(json!({ "success": false, "error": "a replay is already in progress" }));
+    }
+    tokio::spawn(async move {
+        match replay::run(&state.db, &archive, &request, &state.indicators).await {
+            Ok(report) => info!(
+                "⏪ Replay {}..{}: {} snapshots updated, {} added",
+                report.from, report.to, report.merged, report.inserted
+            ),
+            Err(e) => warn!("Replay failed: {}", e),
+        }
+    });
+    Json(json!({ "success": true, "started": true }))
+}
+
+/// Recompute beta, benchmark correlation and RS rank now instead of waiting
+/// for the nightly run.
+async fn run_cross_section(State(state): State<AdminState>) -> impl IntoResponse {
+    match crate::cross_section::run(&state.db, &state.yahoo_client).await {
+        Ok(updated) => Json(json!({ "success": true, "updated": updated })),
+        Err(e) => Json(json!({ "success": false, "error": e.to_string() })),
+    }
+}
+
+/// Whether a weekend run is in progress, its schedule and the last report.
+async fn get_weekend_status(State(state): State<AdminState>) -> impl IntoResponse {
+    Json(json!({
+        "success": true,
+        "running": weekend::is_running(),
+        "hour_ny": state.weekend.hour_ny,
+        "last_report": weekend::last_report()
+    }))
+}
+
+/// Start the weekend deep-analysis jobs now. They take hours over the full
+/// universe, so this returns at once; poll `GET /api/admin/weekend`.
+async fn start_weekend_run(State(state): State<AdminState>) -> impl IntoResponse {
+    if weekend::is_running() {
+        return Json(json!({
+            "success": false,
+            "error": "the weekend run is already in progress"
+        }));
+    }
+    tokio::spawn(async move {
+        let yahoo = state.yahoo_client.background();
+        match weekend::run(
+            &state.db,
+            &yahoo,
+            &state.openrouter_client,
+            &state.weekend,
+            &state.maintenance,
+        )
+        .await
+        {
+            Ok(report) => info!(
+                "🗓️  On-demand weekend run: {} backfilled, {} AI summaries",
+                report.backfilled, report.ai_summaries
+            ),
+            Err(e) => warn!("On-demand weekend run failed: {}", e),
+        }
+    });
+    Json(json!({ "success": true, "started": true }))
+}
+
+/// Request body for `PUT /api/cache/pins/:symbol`
+#[derive(Debug, Default, Deserialize)]
+pub struct PinStockRequest {
+    /// Override TTL in seconds. Omit to keep the symbol cached until unpinned.
+    pub ttl_secs: Option<u64>,
+}
+
+async fn get_cache_stats(State(state): State<AdminState>) -> impl IntoResponse {
+    Json(json!({
+        "success": true,
+        "stats": state.cache.stats()
+    }))
+}
+
+async fn list_cache_pins(State(state): State<AdminState>) -> impl IntoResponse {
+    match state.db.get_cache_pins().await {
+        Ok(pins) => Json(json!({
+            "success": true,
+            "count": pins.len(),
+            "pins": pins
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Pin a symbol in the stock cache (never evicted, or with a custom TTL) and
+/// warm it from the database so the next read is a cache hit.
+async fn pin_cached_stock(
+    State(state): State<AdminState>,
+    Path(symbol): Path<String>,
+    body: Option<Json<PinStockRequest>>,
+) -> impl IntoResponse {
+    let symbol = crate::symbols::normalize_symbol_key(&symbol);
+    let request = body.map(|Json(b)| b).unwrap_or_default();
+    if symbol.is_empty() {
+        return Json(json!({ "success": false, "error": "symbol is required" }));
+    }
+    if request.ttl_secs == Some(0) {
+        return Json(json!({ "success": false, "error": "ttl_secs must be greater than 0" }));
+    }
+
+    let pin = CachePin {
+        symbol: symbol.clone(),
+        ttl_secs: request.ttl_secs,
+        pinned_at: Utc::now(),
+    };
+    if let Err(e) = state.db.save_cache_pin(&pin).await {
+        return Json(json!({ "success": false, "error": e.to_string() }));
+    }
+
+    // Pinning reads the current value through to Mongo, so this also warms it.
+    state.cache.pin_stock(&symbol, pin.ttl()).await;
+    let warmed = state.cache.get_stock(&symbol).await.is_some();
+
+    Json(json!({
+        "success": true,
+        "pin": pin,
+        "warmed": warmed
+    }))
+}
+
+async fn unpin_cached_stock(
+    State(state): State<AdminState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = crate::symbols::normalize_symbol_key(&symbol);
+    match state.db.delete_cache_pin(&symbol).await {
+        Ok(deleted) => {
+            state.cache.unpin_stock(&symbol).await;
+            Json(json!({
+                "success": true,
+                "symbol": symbol,
+                "deleted": deleted
+            }))
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IngestQuery {
+    /// Shared secret, for senders that can't set the header.
+    pub token: Option<String>,
+}
+
+/// Accept a signal or note from an external system. The body is read as
+/// text and parsed as JSON, since TradingView posts its alert message with
+/// `Content-Type: text/plain`.
+async fn ingest_signal(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<IngestQuery>,
+    body: String,
+) -> impl IntoResponse {
+    match serde_json::from_str::<SignalInput>(&body) {
+        Ok(input) => accept_signal(&state, &headers, query, input).await,
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("invalid signal body: {}", e)
+        })),
+    }
+}
+
+/// Accept a TradingView alert body as is (see `tradingview.rs`).
+async fn ingest_tradingview(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Query(query): Query<IngestQuery>,
+    body: String,
+) -> impl IntoResponse {
+    match crate::tradingview::parse_alert(&body) {
+        Ok(input) => accept_signal(&state, &headers, query, input).await,
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Check the ingest token, then store `input` and merge it into the
+/// symbol's analysis.
+async fn accept_signal(
+    state: &AdminState,
+    headers: &HeaderMap,
+    query: IngestQuery,
+    input: SignalInput,
+) -> Json<serde_json::Value> {
+    if let Some(expected) = &state.ingest_token {
+        let provided = headers
+            .get(ingest::TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .or(query.token.as_deref())
+            .or(input.token.as_deref());
+        if !provided.is_some_and(|p| ingest::token_matches(expected, p)) {
+            return Json(json!({
+                "success": false,
+                "error": "missing or invalid ingest token"
+            }));
+        }
+    }
+    let mut signal = match input.into_signal(Utc::now()) {
+        Ok(signal) => signal,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    };
+    signal.symbol = state.cache.resolve_symbol(&signal.symbol);
+
+    let saved = match state.db.save_external_signal(&signal).await {
+        Ok(saved) => saved,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    };
+    let active = state.signals.record(saved.clone()).await;
+    let symbol = saved.symbol.clone();
+    info!(
+        "📨 External signal '{}' for {} from {}",
+        saved.signal, symbol, saved.source
+    );
+
+    // Merge into the stored analysis now rather than waiting for the next
+    // cycle, and re-run the rules that watch for external signals.
+    let analysis_updated = match mongodb::bson::to_bson(&active) {
+        Ok(list) => match state
+            .db
+            .update_analysis_fields(&symbol, mongodb::bson::doc! { "external_signals": list })
+            .await
+        {
+            Ok(updated) => updated,
+            Err(e) => {
+                warn!("Failed to store external signals for {}: {}", symbol, e);
+                false
+            }
+        },
+        Err(e) => {
+            warn!("Failed to encode external signals for {}: {}", symbol, e);
+            false
+        }
+    };
+    if analysis_updated {
+        state.cache.invalidate_stock(&symbol).await;
+        if let Some(analysis) = state.cache.get_stock(&symbol).await {
+            let engine = state.alert_engine.clone();
+            tokio::spawn(async move {
+                if let Err(e) = engine.evaluate_external_signal(&analysis).await {
+                    warn!(
+                        "notifications: external signal evaluation failed for {}: {}",
+                        analysis.symbol, e
+                    );
+                }
+            });
+        }
+    }
+
+    Json(json!({
+        "success": true,
+        "signal": saved,
+        "active_signals": active.len(),
+        "analysis_updated": analysis_updated
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExternalSignalsQuery {
+    pub symbol: Option<String>,
+    /// Look back this many hours; defaults to the inbox TTL.
+    pub hours: Option<i64>,
+    /// Older name for `page_size`.
+    pub limit: Option<u32>,
+}
+
+/// Stored external signals, newest first.
+async fn list_external_signals(
+    State(state): State<AdminState>,
+    uri: Uri,
+    Query(query): Query<ExternalSignalsQuery>,
+    Query(mut paging): Query<PageQuery>,
+) -> impl IntoResponse {
+    let hours = query
+        .hours
+        .unwrap_or_else(|| state.signals.ttl().num_hours())
+        .clamp(1, 24 * 90);
+    paging.page_size = paging.page_size.or(query.limit);
+    let page = paging.resolve(100, 1000);
+    let symbol = query
+        .symbol
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| state.cache.resolve_symbol(s));
+    let since = Utc::now() - ChronoDuration::hours(hours);
+
+    let signals = state.db.get_external_signals(
+        since,
+        symbol.as_deref(),
+        page.offset(),
+        page.page_size as i64,
+    );
+    let total = state.db.count_external_signals(since, symbol.as_deref());
+    match futures::try_join!(signals, total) {
+        Ok((signals, total)) => (
+            page.headers(&uri, total),
+            Json(json!({
+                "success": true,
+                "since": since,
+                "count": signals.len(),
+                "signals": signals,
+                "pagination": page.json(total)
+            })),
+        )
+            .into_response(),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+        .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::{send, state};
+    use super::*;
+    use axum::http::{Method, StatusCode};
+
+    #[tokio::test]
+    async fn read_only_toggle_blocks_mutations_across_routers() {
+        let state = state().await;
+        let app = || super::super::create_router(state.clone());
+
+        let (status, body) = send(
+            app(),
+            Method::PUT,
+            crate::maintenance::TOGGLE_PATH,
+            Some(r#"{"read_only": true, "reason": "migration"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["maintenance"]["read_only"], true);
+
+        let (status, _) = send(app(), Method::PUT, "/api/stocks/AAPL/notes", Some("{}")).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let (status, _) = send(app(), Method::POST, "/api/stocks/filter", Some("{}")).await;
+        assert_ne!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let (_, body) = send(app(), Method::GET, crate::maintenance::TOGGLE_PATH, None).await;
+        assert_eq!(body["maintenance"]["reason"], "migration");
+    }
+
+    #[tokio::test]
+    async fn ingest_checks_the_token_first() {
+        let mut state = state().await;
+        state.ingest_token = Some("secret".into());
+        let app = router().with_state(state);
+        let (status, body) = send(
+            app,
+            Method::POST,
+            "/api/ingest/signal",
+            Some(r#"{"symbol": "AAPL", "signal": "buy"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["error"], "missing or invalid ingest token");
+    }
+
+    #[tokio::test]
+    async fn export_rejects_unknown_collections_and_formats() {
+        let state = state().await;
+        let app = || router().with_state(state.clone());
+        let (status, body) = send(
+            app(),
+            Method::GET,
+            "/api/admin/export?collections=watchlists",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("watchlists"));
+        let (status, _) = send(app(), Method::GET, "/api/admin/export?format=zip", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
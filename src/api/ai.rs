@@ -0,0 +1,240 @@
+//! On-demand OpenRouter analysis of a stored stock, plain or streamed, and
+//! the summaries stored by the weekend run.
+
+use super::AppState;
+use crate::{
+    cache::CacheLayer,
+    db::MongoDB,
+    openrouter::{OpenRouterClient, StreamEvent},
+};
+use axum::{
+    extract::{FromRef, Path, State},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json,
+    },
+    routing::get,
+    Router,
+};
+use serde_json::json;
+use std::convert::Infallible;
+use tracing::warn;
+
+pub(super) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/stocks/:symbol/ai-analysis", get(get_ai_analysis))
+        .route(
+            "/api/stocks/:symbol/ai-analysis/stream",
+            get(stream_ai_analysis),
+        )
+        .route("/api/stocks/:symbol/ai-summary", get(get_ai_summary))
+        .route("/api/ai/status", get(get_ai_status))
+        .route("/api/ai/models", get(get_ai_models))
+}
+
+/// Slice of `AppState` the AI handlers use.
+#[derive(Clone)]
+pub(super) struct AiState {
+    db: MongoDB,
+    cache: CacheLayer,
+    openrouter_client: OpenRouterClient,
+}
+
+impl FromRef<AppState> for AiState {
+    fn from_ref(state: &AppState) -> Self {
+        Self {
+            db: state.db.clone(),
+            cache: state.cache.clone(),
+            openrouter_client: state.openrouter_client.clone(),
+        }
+    }
+}
+
+/// On-demand AI analysis endpoint
+async fn get_ai_analysis(
+    State(state): State<AiState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    // Check if OpenRouter is enabled
+    if !state.openrouter_client.is_enabled() {
+        return Json(json!({
+            "success": false,
+            "error": "AI analysis is not enabled. Set OPENROUTER_API_KEY_STOCKS environment variable."
+        }));
+    }
+
+    // First, get the stock analysis from cache or database
+    let analysis = match state.cache.lookup_stock(&symbol).await {
+        Ok(Some((analysis, _))) => analysis,
+        Ok(None) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("No analysis found for {}. Wait for the analysis cycle to complete.", symbol)
+            }));
+        }
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Database error: {}", e)
+            }));
+        }
+    };
+
+    // Run AI analysis
+    match state.openrouter_client.analyze_stock(&analysis).await {
+        Ok(ai_response) => Json(json!({
+            "success": true,
+            "symbol": ai_response.symbol,
+            "analysis": ai_response.analysis,
+            "model_used": ai_response.model_used,
+            "generated_at": ai_response.generated_at,
+            "stock_data": {
+                "price": analysis.price,
+                "rsi": analysis.rsi,
+                "sma_20": analysis.sma_20,
+                "sma_50": analysis.sma_50,
+                "is_oversold": analysis.is_oversold,
+                "is_overbought": analysis.is_overbought,
+            }
+        })),
+        Err(e) => {
+            warn!("AI analysis failed for {}: {}", symbol, e);
+            Json(json!({
+                "success": false,
+                "error": format!("AI analysis failed: {}", e)
+            }))
+        }
+    }
+}
+
+/// The summary the weekend run last stored for a large-cap name; no model
+/// call, so it works with OpenRouter disabled.
+async fn get_ai_summary(
+    State(state): State<AiState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    match state.db.get_ai_summary(&symbol).await {
+        Ok(summary) => Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "summary": summary
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Database error: {}", e)
+        })),
+    }
+}
+
+/// Stream AI analysis via Server-Sent Events for real-time updates
+async fn stream_ai_analysis(
+    State(state): State<AiState>,
+    Path(symbol): Path<String>,
+) -> Sse<std::pin::Pin<Box<dyn futures::Stream<Item = Result<Event, Infallible>> + Send>>> {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    use futures::stream::StreamExt;
+
+    // Helper to create error stream
+    fn error_stream(
+        msg: String,
+    ) -> std::pin::Pin<Box<dyn futures::Stream<Item = Result<Event, Infallible>> + Send>> {
+        Box::pin(futures::stream::once(async move {
+            Ok::<_, Infallible>(
+                Event::default()
+                    .event("error")
+                    .data(format!(r#"{{"type":"error","message":"{}"}}"#, msg)),
+            )
+        }))
+    }
+
+    // Check if OpenRouter is enabled
+    if !state.openrouter_client.is_enabled() {
+        return Sse::new(error_stream(
+            "AI analysis is not enabled. Set OPENROUTER_API_KEY_STOCKS environment variable."
+                .to_string(),
+        ))
+        .keep_alive(KeepAlive::default());
+    }
+
+    // First, get the stock analysis from cache or database
+    let Some(analysis) = state.cache.get_stock(&symbol).await else {
+        return Sse::new(error_stream(format!(
+            "No analysis found for {}. Wait for the analysis cycle to complete.",
+            symbol
+        )))
+        .keep_alive(KeepAlive::default());
+    };
+
+    // Create the streaming response
+    match state
+        .openrouter_client
+        .analyze_stock_streaming(&analysis)
+        .await
+    {
+        Ok(event_stream) => {
+            let sse_stream = event_stream.map(|event: StreamEvent| {
+                let data = serde_json::to_string(&event).unwrap_or_default();
+                let event_type = match &event {
+                    StreamEvent::Status { .. } => "status",
+                    StreamEvent::ModelInfo { .. } => "model_info",
+                    StreamEvent::Content { .. } => "content",
+                    StreamEvent::Done { .. } => "done",
+                    StreamEvent::Error { .. } => "error",
+                };
+                Ok::<_, Infallible>(Event::default().event(event_type).data(data))
+            });
+            let boxed: std::pin::Pin<
+                Box<dyn futures::Stream<Item = Result<Event, Infallible>> + Send>,
+            > = Box::pin(sse_stream);
+            Sse::new(boxed).keep_alive(KeepAlive::default())
+        }
+        Err(e) => Sse::new(error_stream(format!("Failed to start streaming: {}", e)))
+            .keep_alive(KeepAlive::default()),
+    }
+}
+
+/// Get AI system status
+async fn get_ai_status(State(state): State<AiState>) -> impl IntoResponse {
+    let enabled = state.openrouter_client.is_enabled();
+    let current_model = if enabled {
+        state.openrouter_client.current_model().await
+    } else {
+        None
+    };
+    let available_models = crate::openrouter::get_free_models().await;
+
+    Json(json!({
+        "enabled": enabled,
+        "current_model": current_model,
+        "available_models_count": available_models.len(),
+    }))
+}
+
+/// Get list of available AI models
+async fn get_ai_models() -> impl IntoResponse {
+    let models = crate::openrouter::get_free_models().await;
+    let count = models.len();
+    Json(json!({
+        "models": models,
+        "count": count,
+        "description": "Free models available on OpenRouter with automatic fallback on rate limits"
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::{send, state};
+    use super::*;
+    use axum::http::{Method, StatusCode};
+
+    #[tokio::test]
+    async fn analysis_is_refused_while_openrouter_is_disabled() {
+        let app = router().with_state(state().await);
+        let (status, body) = send(app, Method::GET, "/api/stocks/AAPL/ai-analysis", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("not enabled"));
+    }
+}
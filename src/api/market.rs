@@ -0,0 +1,1313 @@
+//! Market-wide views: summary, quotes, news, sectors, earnings calendar,
+//! analytics, indexes, screens and themes.
+
+use super::{pagination::PageQuery, persist_earnings, AppState};
+use crate::{
+    cache::CacheLayer,
+    db::MongoDB,
+    indexes::{IndexDataProvider, IndexHeatmapData, StockHeatmapItem},
+    indicators::TechnicalIndicators,
+    maintenance::MaintenanceMode,
+    models::StockFilter,
+    screens::Screen,
+    storage::Storage,
+    themes::{Theme, ThemeInput, ThemeMembershipChange},
+    yahoo::{PriceAdjustment, YahooFinanceClient, BATCH_QUOTE_LIMIT},
+};
+use axum::{
+    extract::{FromRef, Path, Query, State},
+    http::Uri,
+    response::{IntoResponse, Json},
+    routing::{get, post, put},
+    Router,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::warn;
+
+pub(super) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/market-summary", get(get_market_summary))
+        .route("/api/quotes", get(get_quotes))
+        .route("/api/news", get(get_all_news))
+        .route("/api/sectors", get(get_sector_performance))
+        .route("/api/sectors/etfs", get(get_sector_etfs))
+        .route("/api/signals/performance", get(get_signal_performance))
+        .route("/api/events/52w", get(get_week52_events))
+        .route("/api/earnings", get(get_earnings_calendar))
+        .route("/api/analytics/correlation", get(get_correlation_matrix))
+        .route("/api/analytics/what-if", get(get_what_if))
+        // Index/Fund heatmap endpoints
+        .route("/api/indexes", get(get_indexes))
+        .route("/api/indexes/:index_id", get(get_index_detail))
+        .route("/api/indexes/:index_id/heatmap", get(get_index_heatmap))
+        .route(
+            "/api/indexes/:index_id/performance",
+            get(get_index_performance),
+        )
+        .route(
+            "/api/indexes/:index_id/contributors",
+            get(get_index_contributors),
+        )
+        .route("/api/screens", get(list_screens))
+        .route("/api/screens/:name", get(get_screen))
+        .route("/api/themes", get(list_themes))
+        .route("/api/themes/:theme_id", get(get_theme))
+        .route("/api/themes/:theme_id/heatmap", get(get_theme_heatmap))
+        .route(
+            "/api/themes/:theme_id/performance",
+            get(get_theme_performance),
+        )
+        .route(
+            "/api/admin/themes/:theme_id",
+            put(save_theme).delete(delete_theme),
+        )
+        .route(
+            "/api/admin/themes/:theme_id/symbols",
+            post(update_theme_symbols),
+        )
+}
+
+/// Slice of `AppState` the market handlers use.
+#[derive(Clone)]
+pub(super) struct MarketState {
+    db: MongoDB,
+    storage: Storage,
+    cache: CacheLayer,
+    yahoo_client: YahooFinanceClient,
+    maintenance: MaintenanceMode,
+}
+
+impl FromRef<AppState> for MarketState {
+    fn from_ref(state: &AppState) -> Self {
+        Self {
+            db: state.db.clone(),
+            storage: state.storage.clone(),
+            cache: state.cache.clone(),
+            yahoo_client: state.yahoo_client.clone(),
+            maintenance: state.maintenance.clone(),
+        }
+    }
+}
+
+/// Query parameters for market summary endpoint
+#[derive(Debug, Deserialize)]
+pub struct MarketSummaryQuery {
+    pub min_market_cap: Option<f64>,
+    pub max_price_change_percent: Option<f64>,
+    /// Summarize the analysis history as of this time.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Get market summary with top gainers, losers, and key highlights
+async fn get_market_summary(
+    State(state): State<MarketState>,
+    Query(query): Query<MarketSummaryQuery>,
+) -> impl IntoResponse {
+    match state
+        .storage
+        .get_market_summary(
+            10,
+            query.min_market_cap,
+            query.max_price_change_percent,
+            query.as_of,
+        )
+        .await
+    {
+        Ok(summary) => Json(json!({
+            "success": true,
+            "summary": summary,
+            "filters_applied": {
+                "min_market_cap": query.min_market_cap,
+                "max_price_change_percent": query.max_price_change_percent,
+                "as_of": query.as_of
+            }
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Query parameters for news endpoint
+#[derive(Debug, Deserialize)]
+pub struct NewsQuery {
+    pub sector: Option<String>,
+    pub search: Option<String>,
+}
+
+/// Get aggregated news from all stocks
+async fn get_all_news(
+    State(state): State<MarketState>,
+    uri: Uri,
+    Query(query): Query<NewsQuery>,
+    Query(paging): Query<PageQuery>,
+) -> impl IntoResponse {
+    let page = paging.resolve(50, 100);
+
+    match state
+        .db
+        .get_all_news(query.sector, query.search, page.page, page.page_size)
+        .await
+    {
+        Ok((news, total)) => (
+            page.headers(&uri, total),
+            Json(json!({
+                "success": true,
+                "news": news,
+                "pagination": page.json(total)
+            })),
+        )
+            .into_response(),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+        .into_response(),
+    }
+}
+
+/// Get sector performance aggregation
+#[derive(Debug, Deserialize)]
+pub struct SignalPerformanceQuery {
+    /// Only count signals from the last `days` calendar days.
+    pub days: Option<i64>,
+}
+
+async fn get_signal_performance(
+    State(state): State<MarketState>,
+    Query(query): Query<SignalPerformanceQuery>,
+) -> impl IntoResponse {
+    let since = query.days.filter(|d| *d > 0).map(|d| {
+        (Utc::now() - chrono::Duration::days(d))
+            .format("%Y-%m-%d")
+            .to_string()
+    });
+
+    match state.db.get_signals(since.as_deref()).await {
+        Ok(records) => Json(json!({
+            "success": true,
+            "since": since,
+            "total_signals": records.len(),
+            "strategies": crate::signals::leaderboard(&records)
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Week52EventsQuery {
+    /// `high` or `low`; both when omitted.
+    pub kind: Option<String>,
+    pub days: Option<i64>,
+    /// Older name for `page_size`.
+    pub limit: Option<u32>,
+}
+
+async fn get_week52_events(
+    State(state): State<MarketState>,
+    uri: Uri,
+    Query(query): Query<Week52EventsQuery>,
+    Query(mut paging): Query<PageQuery>,
+) -> impl IntoResponse {
+    let kind = match query.kind.as_deref().filter(|k| !k.is_empty()) {
+        Some(kind) => match kind.parse::<crate::highs_lows::Week52Kind>() {
+            Ok(kind) => Some(kind),
+            Err(e) => {
+                return Json(json!({
+                    "success": false,
+                    "error": e.to_string()
+                }))
+                .into_response()
+            }
+        },
+        None => None,
+    };
+    let days = query.days.unwrap_or(7).clamp(1, 365);
+    paging.page_size = paging.page_size.or(query.limit);
+    let page = paging.resolve(200, 1000);
+    let since = (Utc::now() - chrono::Duration::days(days))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    match state.db.get_week52_events(&since, kind).await {
+        Ok(events) => {
+            let breadth = crate::highs_lows::breadth(&events);
+            let total = events.len() as u64;
+            (
+                page.headers(&uri, total),
+                Json(json!({
+                    "success": true,
+                    "since": since,
+                    "total_events": total,
+                    "breadth": breadth,
+                    "events": events
+                        .into_iter()
+                        .skip(page.offset() as usize)
+                        .take(page.page_size as usize)
+                        .collect::<Vec<_>>(),
+                    "pagination": page.json(total)
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+        .into_response(),
+    }
+}
+
+async fn get_sector_performance(State(state): State<MarketState>) -> impl IntoResponse {
+    match state.db.get_sector_performance().await {
+        Ok(sectors) => Json(json!({
+            "success": true,
+            "sectors": sectors
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Sector → proxy ETF mapping plus each ETF's trailing returns from the
+/// latest analysis cycle
+async fn get_sector_etfs(State(state): State<MarketState>) -> impl IntoResponse {
+    let mapping: Vec<_> = crate::sectors::SECTOR_ETFS
+        .iter()
+        .map(|(sector, etf)| json!({ "sector": sector, "etf": etf }))
+        .collect();
+    match state.db.get_sector_etfs().await {
+        Ok(etfs) => Json(json!({
+            "success": true,
+            "mapping": mapping,
+            "etfs": etfs
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Query parameters for earnings calendar
+#[derive(Debug, Deserialize)]
+pub struct EarningsQuery {
+    pub days_ahead: Option<u32>,
+}
+
+/// Get earnings calendar for top stocks
+async fn get_earnings_calendar(
+    State(state): State<MarketState>,
+    Query(query): Query<EarningsQuery>,
+) -> impl IntoResponse {
+    let days_ahead = query.days_ahead.unwrap_or(30);
+    let cutoff = Utc::now() + ChronoDuration::days(days_ahead as i64);
+
+    // Get top stocks by market cap
+    let filter = StockFilter {
+        min_price: None,
+        max_price: None,
+        min_volume: None,
+        min_market_cap: Some(10_000_000_000.0), // Only large caps for earnings calendar
+        max_market_cap: None,
+        min_rsi: None,
+        max_rsi: None,
+        sectors: None,
+        only_oversold: None,
+        only_overbought: None,
+        only_stoch_oversold: None,
+        only_stoch_overbought: None,
+        cross_signal: None,
+        supertrend_uptrend: None,
+        symbol_search: None,
+        min_stochastic_k: None,
+        max_stochastic_k: None,
+        min_williams_r: None,
+        max_williams_r: None,
+        min_cci: None,
+        max_cci: None,
+        min_mfi: None,
+        max_mfi: None,
+        min_bandwidth: None,
+        max_bandwidth: None,
+        max_abs_price_change_percent: None,
+        primary_class_only: None,
+        index: None,
+        theme: None,
+        tags: None,
+        asset_type: None,
+        return_period: None,
+        min_return_pct: None,
+        max_return_pct: None,
+        percentile_metric: None,
+        min_percentile: None,
+        max_percentile: None,
+        min_rs_rank: None,
+        as_of: None,
+        sort_by: Some("market_cap".to_string()),
+        sort_order: Some("desc".to_string()),
+        page: Some(1),
+        page_size: Some(100),
+    };
+
+    let stocks = match state.storage.get_latest_analyses(filter).await {
+        Ok(s) => s,
+        Err(e) => {
+            return Json(json!({ "success": false, "error": e.to_string() }));
+        }
+    };
+
+    let cache = state.cache.clone();
+    let yahoo = state.yahoo_client.clone();
+    let db = state.db.clone();
+    let maintenance = state.maintenance.clone();
+    let results = stream::iter(stocks)
+        .map(|stock| {
+            let cache = cache.clone();
+            let yahoo = yahoo.clone();
+            let db = db.clone();
+            let maintenance = maintenance.clone();
+            async move {
+                let data = if let Some(cached) = cache.get_earnings(&stock.symbol).await {
+                    cached
+                } else {
+                    match yahoo.get_earnings_data(&stock.symbol).await {
+                        Ok(data) => {
+                            persist_earnings(&db, &cache, &maintenance, &stock.symbol, &data).await;
+                            data
+                        }
+                        Err(e) => {
+                            warn!("Failed to fetch earnings for {}: {}", stock.symbol, e);
+                            return (None, Some(stock.symbol));
+                        }
+                    }
+                };
+
+                let Some(date) = data.earnings_date.as_ref() else {
+                    return (None, None);
+                };
+                if *date > cutoff {
+                    return (None, None);
+                }
+
+                (
+                    Some(json!({
+                        "symbol": stock.symbol,
+                        "sector": stock.sector,
+                        "market_cap": stock.market_cap,
+                        "price": stock.price,
+                        "earnings": data
+                    })),
+                    None,
+                )
+            }
+        })
+        .buffer_unordered(5)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut earnings = Vec::new();
+    let mut failed_symbols = Vec::new();
+    for (row, failed) in results {
+        if let Some(row) = row {
+            earnings.push(row);
+        }
+        if let Some(symbol) = failed {
+            failed_symbols.push(symbol);
+        }
+    }
+
+    // Sort by earnings date ascending
+    earnings.sort_by(|a, b| {
+        let date_a = a
+            .get("earnings")
+            .and_then(|e| e.get("earnings_date"))
+            .and_then(|d| d.as_str());
+        let date_b = b
+            .get("earnings")
+            .and_then(|e| e.get("earnings_date"))
+            .and_then(|d| d.as_str());
+        date_a.cmp(&date_b)
+    });
+
+    Json(json!({
+        "success": true,
+        "earnings": earnings,
+        "count": earnings.len(),
+        "days_ahead": days_ahead,
+        "failed_symbols": failed_symbols
+    }))
+}
+
+/// Most symbols `/api/quotes` accepts per request.
+const MAX_QUOTE_SYMBOLS: usize = 100;
+
+/// Query parameters for `/api/quotes`
+#[derive(Debug, Deserialize)]
+pub struct QuotesQuery {
+    pub symbols: String, // Comma-separated
+}
+
+/// Near-current prices between analysis cycles. Served from the short-TTL
+/// quote cache; misses are fetched from Yahoo's batch quote endpoint.
+async fn get_quotes(
+    State(state): State<MarketState>,
+    Query(query): Query<QuotesQuery>,
+) -> impl IntoResponse {
+    let mut symbols: Vec<String> = Vec::new();
+    for symbol in query.symbols.split(',') {
+        let symbol = state.cache.resolve_symbol(symbol);
+        if !symbol.is_empty() && !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+    if symbols.is_empty() {
+        return Json(json!({ "success": false, "error": "symbols is required" }));
+    }
+    if symbols.len() > MAX_QUOTE_SYMBOLS {
+        return Json(json!({
+            "success": false,
+            "error": format!("at most {} symbols per request", MAX_QUOTE_SYMBOLS)
+        }));
+    }
+
+    let mut quotes = std::collections::HashMap::new();
+    let mut misses = Vec::new();
+    for symbol in &symbols {
+        match state.cache.get_quote(symbol).await {
+            Some(quote) => {
+                quotes.insert(symbol.clone(), quote);
+            }
+            None => misses.push(symbol.clone()),
+        }
+    }
+    let cached = quotes.len();
+
+    let mut errors = Vec::new();
+    for chunk in misses.chunks(BATCH_QUOTE_LIMIT) {
+        match state.yahoo_client.get_batch_quotes(chunk).await {
+            Ok(fetched) => {
+                for quote in fetched {
+                    state.cache.set_quote(quote.clone()).await;
+                    quotes.insert(quote.symbol.clone(), quote);
+                }
+            }
+            Err(e) => {
+                warn!("Batch quote fetch failed: {}", e);
+                errors.push(e.to_string());
+            }
+        }
+    }
+
+    if quotes.is_empty() && !errors.is_empty() {
+        return Json(json!({ "success": false, "error": errors.join("; ") }));
+    }
+    let missing: Vec<&String> = symbols
+        .iter()
+        .filter(|s| !quotes.contains_key(*s))
+        .collect();
+    let ordered: Vec<_> = symbols.iter().filter_map(|s| quotes.get(s)).collect();
+    Json(json!({
+        "success": true,
+        "count": ordered.len(),
+        "cached": cached,
+        "quotes": ordered,
+        "missing": missing
+    }))
+}
+
+/// Query parameters for correlation matrix
+#[derive(Debug, Deserialize)]
+pub struct CorrelationQuery {
+    pub symbols: String, // Comma-separated
+    pub days: Option<i64>,
+}
+
+/// Get correlation matrix for a set of symbols
+async fn get_correlation_matrix(
+    State(state): State<MarketState>,
+    Query(query): Query<CorrelationQuery>,
+) -> impl IntoResponse {
+    let symbols: Vec<String> = query
+        .symbols
+        .split(',')
+        .map(crate::symbols::normalize_symbol_key)
+        .filter(|s| !s.is_empty())
+        .take(20) // Max 20 symbols
+        .collect();
+
+    if symbols.len() < 2 {
+        return Json(json!({
+            "success": false,
+            "error": "Need at least 2 symbols for correlation"
+        }));
+    }
+
+    let days = query.days.unwrap_or(90);
+    let requested_symbols = symbols.clone();
+
+    // Fetch historical prices with bounded concurrency.
+    let yahoo = state.yahoo_client.clone();
+    let history_results = stream::iter(symbols.iter().cloned())
+        .map(|symbol| {
+            let yahoo = yahoo.clone();
+            async move {
+                match yahoo
+                    .get_historical_prices(&symbol, days, PriceAdjustment::Adjusted)
+                    .await
+                {
+                    Ok(prices) => {
+                        let closes: Vec<f64> = prices.iter().map(|p| p.close).collect();
+                        (symbol, Some(closes), None)
+                    }
+                    Err(e) => {
+                        warn!("Failed to fetch history for {}: {}", symbol, e);
+                        let err = e.to_string();
+                        (symbol, None, Some(err))
+                    }
+                }
+            }
+        })
+        .buffer_unordered(5)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut price_map: std::collections::HashMap<String, Vec<f64>> =
+        std::collections::HashMap::new();
+    let mut failed_symbols = Vec::new();
+    for (symbol, closes, err) in history_results {
+        if let Some(closes) = closes {
+            price_map.insert(symbol, closes);
+        } else {
+            failed_symbols.push(
+                json!({ "symbol": symbol, "error": err.unwrap_or_else(|| "unknown".to_string()) }),
+            );
+        }
+    }
+
+    // Only keep symbols we have data for
+    let valid_symbols: Vec<String> = symbols
+        .into_iter()
+        .filter(|s| price_map.contains_key(s))
+        .collect();
+
+    let n = valid_symbols.len();
+    let mut matrix = vec![vec![0.0f64; n]; n];
+
+    for i in 0..n {
+        for j in 0..n {
+            if i == j {
+                matrix[i][j] = 1.0;
+            } else if j > i {
+                let corr = TechnicalIndicators::calculate_correlation(
+                    &price_map[&valid_symbols[i]],
+                    &price_map[&valid_symbols[j]],
+                )
+                .unwrap_or(0.0);
+                matrix[i][j] = corr;
+                matrix[j][i] = corr;
+            }
+        }
+    }
+
+    Json(json!({
+        "success": true,
+        "requested_symbols": requested_symbols,
+        "symbols": valid_symbols,
+        "matrix": matrix,
+        "days": days,
+        "failed_symbols": failed_symbols
+    }))
+}
+
+/// Query parameters for the what-if simulator
+#[derive(Debug, Deserialize)]
+pub struct WhatIfQuery {
+    pub symbol: String,
+    /// Purchase date, `YYYY-MM-DD`.
+    pub date: String,
+    /// Dollars invested (default 1000).
+    pub amount: Option<f64>,
+}
+
+/// Longest lookback Yahoo is asked for (~25 years).
+const WHAT_IF_MAX_DAYS: i64 = 9_200;
+
+/// Simulate buying `amount` of `symbol` on a past date and holding until today
+async fn get_what_if(
+    State(state): State<MarketState>,
+    Query(query): Query<WhatIfQuery>,
+) -> impl IntoResponse {
+    let symbol = crate::symbols::normalize_symbol_key(&query.symbol);
+    let amount = query.amount.unwrap_or(1000.0);
+    if symbol.is_empty() || !amount.is_finite() || amount <= 0.0 {
+        return Json(json!({
+            "success": false,
+            "error": "symbol and a positive amount are required"
+        }));
+    }
+    let Ok(start) = chrono::NaiveDate::parse_from_str(&query.date, "%Y-%m-%d") else {
+        return Json(json!({
+            "success": false,
+            "error": "date must be YYYY-MM-DD"
+        }));
+    };
+    let days = (Utc::now().date_naive() - start).num_days();
+    if days <= 0 || days > WHAT_IF_MAX_DAYS {
+        return Json(json!({
+            "success": false,
+            "error": format!("date must be in the past and within {} days", WHAT_IF_MAX_DAYS)
+        }));
+    }
+
+    // Pad for weekends/holidays so the start date's session is included.
+    let (prices, dividends) = match state
+        .yahoo_client
+        .get_history_with_dividends(&symbol, days + 7)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Failed to fetch history for {}: {}", symbol, e)
+            }))
+        }
+    };
+
+    match crate::analytics::what_if(&symbol, &prices, &dividends, start, amount) {
+        Some(result) => Json(json!({ "success": true, "result": result })),
+        None => Json(json!({
+            "success": false,
+            "error": format!("No price history for {} on or after {}", symbol, query.date)
+        })),
+    }
+}
+
+/// Query parameters for index heatmap endpoint
+#[derive(Debug, Deserialize)]
+pub struct IndexHeatmapQuery {
+    /// Time period: "1d", "1w", "1m", "6m", "1y"
+    pub period: Option<String>,
+}
+
+/// Get list of available indexes
+async fn get_indexes() -> impl IntoResponse {
+    let indexes = IndexDataProvider::get_indexes();
+    Json(json!({
+        "success": true,
+        "indexes": indexes
+    }))
+}
+
+/// Query parameters for `/api/indexes/:index_id/performance`
+#[derive(Debug, Deserialize)]
+pub struct IndexPerformanceQuery {
+    pub days: Option<i64>,
+}
+
+/// Daily equal- vs cap-weighted returns for an index, with the compounded
+/// divergence over the window as a breadth signal.
+async fn get_index_performance(
+    State(state): State<MarketState>,
+    Path(index_id): Path<String>,
+    Query(query): Query<IndexPerformanceQuery>,
+) -> impl IntoResponse {
+    if IndexDataProvider::get_index_info(&index_id).is_none() {
+        return Json(json!({
+            "success": false,
+            "error": format!("Index '{}' not found", index_id)
+        }));
+    }
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+
+    match state.db.get_index_performance(&index_id, days).await {
+        Ok(history) => {
+            let (equal, cap) = crate::indexes::cumulative_returns(&history);
+            Json(json!({
+                "success": true,
+                "index_id": index_id,
+                "days": history.len(),
+                "latest": history.last(),
+                "cumulative": {
+                    "equal_weight_return_pct": equal,
+                    "cap_weight_return_pct": cap,
+                    "divergence_pct": equal - cap
+                },
+                "history": history
+            }))
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Query parameters for `/api/indexes/:index_id/contributors`
+#[derive(Debug, Deserialize)]
+pub struct IndexContributorsQuery {
+    /// `YYYY-MM-DD`; defaults to the latest recorded day.
+    pub date: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// The constituents that moved a cap-weighted index most on a trading day,
+/// in percentage points and (when the index level is known) index points.
+async fn get_index_contributors(
+    State(state): State<MarketState>,
+    Path(index_id): Path<String>,
+    Query(query): Query<IndexContributorsQuery>,
+) -> impl IntoResponse {
+    if IndexDataProvider::get_index_info(&index_id).is_none() {
+        return Json(json!({
+            "success": false,
+            "error": format!("Index '{}' not found", index_id)
+        }));
+    }
+    let date = match query
+        .date
+        .as_deref()
+        .map(|d| d.parse::<chrono::NaiveDate>())
+    {
+        None => None,
+        Some(Ok(date)) => Some(date),
+        Some(Err(_)) => {
+            return Json(json!({
+                "success": false,
+                "error": "date must be YYYY-MM-DD"
+            }))
+        }
+    };
+    let limit = query
+        .limit
+        .unwrap_or(10)
+        .clamp(1, crate::indexes::STORED_CONTRIBUTORS);
+
+    match state.db.get_index_contributors(&index_id, date).await {
+        Ok(Some(contributors)) => Json(json!({
+            "success": true,
+            "contributors": contributors.truncated(limit)
+        })),
+        Ok(None) => Json(json!({
+            "success": false,
+            "error": match date {
+                Some(date) => format!("No contributors recorded for {} on {}", index_id, date),
+                None => format!("No contributors recorded for {} yet", index_id),
+            }
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get details for a specific index
+async fn get_index_detail(Path(index_id): Path<String>) -> impl IntoResponse {
+    match IndexDataProvider::get_index_info(&index_id) {
+        Some(info) => {
+            let symbols = IndexDataProvider::get_index_symbols(&index_id).unwrap_or_default();
+            Json(json!({
+                "success": true,
+                "index": {
+                    "id": info.id,
+                    "name": info.name,
+                    "description": info.description,
+                    "symbol_count": info.symbol_count,
+                    "symbols": symbols
+                }
+            }))
+        }
+        None => Json(json!({
+            "success": false,
+            "error": format!("Index '{}' not found. Available indexes: sp500, nasdaq100, dow30, russell2000", index_id)
+        })),
+    }
+}
+
+/// Pre-computed screens and when each was last refreshed. Rows are left
+/// out; fetch them with `/api/screens/:name`.
+async fn list_screens(State(state): State<MarketState>) -> impl IntoResponse {
+    let stored = match state.db.get_screen_results().await {
+        Ok(results) => results,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    };
+    let screens: Vec<_> = Screen::ALL
+        .into_iter()
+        .map(|screen| {
+            let result = stored.iter().find(|r| r.name == screen.name());
+            json!({
+                "name": screen.name(),
+                "description": screen.description(),
+                "metric": screen.metric(),
+                "total": result.map(|r| r.total),
+                "computed_at": result.map(|r| r.computed_at)
+            })
+        })
+        .collect();
+    Json(json!({
+        "success": true,
+        "screens": screens
+    }))
+}
+
+/// Query parameters for `/api/screens/:name`
+#[derive(Debug, Deserialize)]
+pub struct ScreenQuery {
+    pub limit: Option<usize>,
+}
+
+/// A screen's stored result from the end of the last cycle.
+async fn get_screen(
+    State(state): State<MarketState>,
+    Path(name): Path<String>,
+    Query(query): Query<ScreenQuery>,
+) -> impl IntoResponse {
+    let Some(screen) = Screen::from_name(&name) else {
+        let names: Vec<_> = Screen::ALL.iter().map(|s| s.name()).collect();
+        return Json(json!({
+            "success": false,
+            "error": format!("Screen '{}' not found. Available screens: {}", name, names.join(", "))
+        }));
+    };
+    match state.db.get_screen_result(screen.name()).await {
+        Ok(Some(mut result)) => {
+            if let Some(limit) = query.limit {
+                result.rows.truncate(limit);
+            }
+            Json(json!({
+                "success": true,
+                "screen": result
+            }))
+        }
+        Ok(None) => Json(json!({
+            "success": false,
+            "error": format!("Screen '{}' has not been computed yet; it is refreshed at the end of every analysis cycle", screen.name())
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Every theme with its members.
+async fn list_themes(State(state): State<MarketState>) -> impl IntoResponse {
+    match state.db.get_themes().await {
+        Ok(themes) => Json(json!({
+            "success": true,
+            "count": themes.len(),
+            "themes": themes
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Look up a theme, turning "missing" into the API's error shape.
+async fn find_theme(state: &MarketState, theme_id: &str) -> Result<Theme, serde_json::Value> {
+    match state.db.get_theme(&theme_id.to_lowercase()).await {
+        Ok(Some(theme)) => Ok(theme),
+        Ok(None) => Err(json!({
+            "success": false,
+            "error": format!("Theme '{}' not found", theme_id)
+        })),
+        Err(e) => Err(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+async fn get_theme(
+    State(state): State<MarketState>,
+    Path(theme_id): Path<String>,
+) -> impl IntoResponse {
+    match find_theme(&state, &theme_id).await {
+        Ok(theme) => Json(json!({
+            "success": true,
+            "theme": theme
+        })),
+        Err(error) => Json(error),
+    }
+}
+
+/// Same as the index heatmap, over a theme's members.
+async fn get_theme_heatmap(
+    State(state): State<MarketState>,
+    Path(theme_id): Path<String>,
+    Query(query): Query<IndexHeatmapQuery>,
+) -> impl IntoResponse {
+    let period = query.period.unwrap_or_else(|| "1d".to_string());
+    match find_theme(&state, &theme_id).await {
+        Ok(theme) => build_heatmap(&state, theme.id, theme.name, theme.symbols, period).await,
+        Err(error) => Json(error),
+    }
+}
+
+/// Daily equal- vs cap-weighted returns for a theme; same shape as
+/// `/api/indexes/:index_id/performance`.
+async fn get_theme_performance(
+    State(state): State<MarketState>,
+    Path(theme_id): Path<String>,
+    Query(query): Query<IndexPerformanceQuery>,
+) -> impl IntoResponse {
+    let theme = match find_theme(&state, &theme_id).await {
+        Ok(theme) => theme,
+        Err(error) => return Json(error),
+    };
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+
+    match state.db.get_theme_performance(&theme.id, days).await {
+        Ok(history) => {
+            let (equal, cap) = crate::indexes::cumulative_returns(&history);
+            Json(json!({
+                "success": true,
+                "theme_id": theme.id,
+                "days": history.len(),
+                "latest": history.last(),
+                "cumulative": {
+                    "equal_weight_return_pct": equal,
+                    "cap_weight_return_pct": cap,
+                    "divergence_pct": equal - cap
+                },
+                "history": history
+            }))
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Create or replace a theme.
+async fn save_theme(
+    State(state): State<MarketState>,
+    Path(theme_id): Path<String>,
+    Json(input): Json<ThemeInput>,
+) -> impl IntoResponse {
+    let theme = match Theme::from_input(&theme_id, &input) {
+        Ok(theme) => theme,
+        Err(error) => {
+            return Json(json!({
+                "success": false,
+                "error": error
+            }))
+        }
+    };
+    match state.db.save_theme(&theme).await {
+        Ok(()) => {
+            state.cache.invalidate_all_lists().await;
+            Json(json!({
+                "success": true,
+                "theme": theme
+            }))
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Add and/or remove members without replacing the whole theme.
+async fn update_theme_symbols(
+    State(state): State<MarketState>,
+    Path(theme_id): Path<String>,
+    Json(change): Json<ThemeMembershipChange>,
+) -> impl IntoResponse {
+    let mut theme = match find_theme(&state, &theme_id).await {
+        Ok(theme) => theme,
+        Err(error) => return Json(error),
+    };
+    if !theme.apply(&change) {
+        return Json(json!({
+            "success": true,
+            "changed": false,
+            "theme": theme
+        }));
+    }
+    match state.db.save_theme(&theme).await {
+        Ok(()) => {
+            state.cache.invalidate_all_lists().await;
+            Json(json!({
+                "success": true,
+                "changed": true,
+                "theme": theme
+            }))
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+async fn delete_theme(
+    State(state): State<MarketState>,
+    Path(theme_id): Path<String>,
+) -> impl IntoResponse {
+    match state.db.delete_theme(&theme_id.to_lowercase()).await {
+        Ok(deleted) => {
+            state.cache.invalidate_all_lists().await;
+            Json(json!({
+                "success": true,
+                "deleted": deleted
+            }))
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get heatmap data for an index with performance calculations
+async fn get_index_heatmap(
+    State(state): State<MarketState>,
+    Path(index_id): Path<String>,
+    Query(query): Query<IndexHeatmapQuery>,
+) -> impl IntoResponse {
+    let period = query.period.unwrap_or_else(|| "1d".to_string());
+
+    // Get index info and symbols
+    let Some(info) = IndexDataProvider::get_index_info(&index_id) else {
+        return Json(json!({
+            "success": false,
+            "error": format!("Index '{}' not found", index_id)
+        }));
+    };
+
+    let Some(symbols) = IndexDataProvider::get_index_symbols(&index_id) else {
+        return Json(json!({
+            "success": false,
+            "error": format!("No symbols found for index '{}'", index_id)
+        }));
+    };
+    build_heatmap(&state, info.id, info.name, symbols, period).await
+}
+
+/// Heatmap of `symbols` over `period`, cap-weighted, shared by indexes and
+/// themes.
+async fn build_heatmap(
+    state: &MarketState,
+    id: String,
+    name: String,
+    symbols: Vec<String>,
+    period: String,
+) -> Json<serde_json::Value> {
+    // Convert period to number of days for historical data fetch
+    let days: i64 = match period.as_str() {
+        "1d" => 2, // Need at least 2 days to get previous close
+        "1w" => 7,
+        "1m" => 30,
+        "6m" => 180,
+        "1y" => 365,
+        _ => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Invalid period '{}'. Valid periods: 1d, 1w, 1m, 6m, 1y", period)
+            }));
+        }
+    };
+
+    // Fetch stock data from database
+    let mut stocks: Vec<StockHeatmapItem> = Vec::new();
+    let mut total_market_cap: f64 = 0.0;
+    let mut weighted_change: f64 = 0.0;
+
+    // Get all analyses at once for efficiency
+    let lookup: Vec<String> = symbols
+        .iter()
+        .map(|s| crate::symbols::normalize_symbol_key(s))
+        .collect();
+    let all_stocks = match state.db.get_analyses_by_symbols(&lookup).await {
+        Ok(s) => s,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Database error: {}", e)
+            }));
+        }
+    };
+
+    // Create a lookup map for quick access
+    let stock_map: std::collections::HashMap<String, _> = all_stocks
+        .into_iter()
+        .map(|s| (s.symbol.clone(), s))
+        .collect();
+
+    // Match index symbols with database stocks and calculate period performance.
+    // Longer periods need Yahoo calls, so fetch those with bounded concurrency.
+    let symbol_count = symbols.len();
+    let stock_inputs: Vec<_> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let lookup_symbol = crate::symbols::normalize_symbol_key(symbol);
+            stock_map
+                .get(&lookup_symbol)
+                .cloned()
+                .map(|stock| (lookup_symbol, stock))
+        })
+        .collect();
+    let yahoo = state.yahoo_client.clone();
+    let period_for_tasks = period.clone();
+    let rows = stream::iter(stock_inputs)
+        .map(|(lookup_symbol, stock)| {
+            let yahoo = yahoo.clone();
+            let period = period_for_tasks.clone();
+            async move {
+                let mut used_fallback = false;
+                let change_percent = if period == "1d" {
+                    stock.price_change_percent.unwrap_or(0.0)
+                } else {
+                    match yahoo
+                        .get_historical_prices(&lookup_symbol, days, PriceAdjustment::Adjusted)
+                        .await
+                    {
+                        Ok(prices) if prices.len() >= 2 => {
+                            let first_price = prices.first().map(|p| p.close).unwrap_or(0.0);
+                            let last_price = prices.last().map(|p| p.close).unwrap_or(0.0);
+                            if first_price > 0.0 {
+                                ((last_price - first_price) / first_price) * 100.0
+                            } else {
+                                used_fallback = true;
+                                stock.price_change_percent.unwrap_or(0.0)
+                            }
+                        }
+                        Ok(_) | Err(_) => {
+                            used_fallback = true;
+                            stock.price_change_percent.unwrap_or(0.0)
+                        }
+                    }
+                };
+
+                let market_cap = stock.market_cap.unwrap_or(0) as f64;
+                (
+                    StockHeatmapItem {
+                        symbol: lookup_symbol,
+                        name: None,
+                        price: stock.price,
+                        change_percent,
+                        contribution: 0.0,
+                        market_cap: Some(market_cap),
+                        sector: stock.sector.clone(),
+                    },
+                    used_fallback,
+                )
+            }
+        })
+        .buffer_unordered(5)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut fallback_symbols = Vec::new();
+    for (item, used_fallback) in rows {
+        total_market_cap += item.market_cap.unwrap_or(0.0);
+        if used_fallback {
+            fallback_symbols.push(item.symbol.clone());
+        }
+        stocks.push(item);
+    }
+
+    // Calculate weighted index performance and individual contributions
+    for stock in &mut stocks {
+        if let Some(market_cap) = stock.market_cap {
+            if total_market_cap > 0.0 {
+                let weight = market_cap / total_market_cap;
+                let contribution = weight * stock.change_percent;
+                stock.contribution = contribution;
+                weighted_change += contribution;
+            }
+        }
+    }
+
+    // Sort by market cap descending for heatmap display
+    stocks.sort_by(|a, b| {
+        b.market_cap
+            .unwrap_or(0.0)
+            .partial_cmp(&a.market_cap.unwrap_or(0.0))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let heatmap_data = IndexHeatmapData {
+        index_id: id,
+        index_name: name,
+        period: period.clone(),
+        index_performance: weighted_change,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        stocks,
+    };
+
+    Json(json!({
+        "success": true,
+        "heatmap": heatmap_data,
+        "stats": {
+            "total_constituents": symbol_count,
+            "stocks_with_data": heatmap_data.stocks.len(),
+            "total_market_cap": total_market_cap,
+            "period": period,
+            "fallback_symbols": fallback_symbols
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::{send, state};
+    use super::*;
+    use axum::http::{Method, StatusCode};
+
+    #[tokio::test]
+    async fn indexes_are_served_without_the_database() {
+        let state = state().await;
+        let app = || router().with_state(state.clone());
+
+        let (status, body) = send(app(), Method::GET, "/api/indexes", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["indexes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|index| index["id"] == "dow30"));
+
+        let (_, body) = send(app(), Method::GET, "/api/indexes/dow30", None).await;
+        assert_eq!(body["success"], true);
+        assert!(!body["index"]["symbols"].as_array().unwrap().is_empty());
+
+        let (_, body) = send(app(), Method::GET, "/api/indexes/ftse", None).await;
+        assert_eq!(body["success"], false);
+    }
+
+    #[tokio::test]
+    async fn quotes_check_the_symbol_list_before_fetching() {
+        let state = state().await;
+        let app = || router().with_state(state.clone());
+
+        let (_, body) = send(app(), Method::GET, "/api/quotes?symbols=,,", None).await;
+        assert_eq!(body["error"], "symbols is required");
+
+        let symbols: Vec<String> = (0..=MAX_QUOTE_SYMBOLS).map(|i| format!("S{i}")).collect();
+        let uri = format!("/api/quotes?symbols={}", symbols.join(","));
+        let (_, body) = send(app(), Method::GET, &uri, None).await;
+        assert_eq!(body["error"], "at most 100 symbols per request");
+    }
+}
@@ -0,0 +1,297 @@
+//! HTTP API.
+//!
+//! `create_router` merges one router per area, each in its own module:
+//!
+//! - `stocks`: stored analyses and per-symbol extras
+//! - `market`: market-wide views, indexes, screens and themes
+//! - `ai`: on-demand AI analysis
+//! - `admin`: operator endpoints and signal ingest
+//! - `ws`: cycle progress and the `/ws` socket
+//!
+//! Listing endpoints share `pagination` for paging parameters, the
+//! `pagination` body object and the `Link` / `X-Total-Count` headers.
+//!
+//! plus the notifications routes (`notifications::api`). Handlers take the
+//! slice of `AppState` they need (`State<StocksState>` and so on), which
+//! axum extracts through `FromRef`.
+
+mod admin;
+mod ai;
+mod market;
+pub(crate) mod pagination;
+mod stocks;
+mod ws;
+
+use crate::{
+    archive::RawArchive,
+    backup::BackupSettings,
+    cache::CacheLayer,
+    change_feed::AnalysisFeed,
+    db::MongoDB,
+    indicators::IndicatorConfig,
+    ingest::SignalInbox,
+    intraday::IntradayRelay,
+    maintenance::MaintenanceMode,
+    models::{AnalysisProgress, EarningsData},
+    nasdaq::NasdaqClient,
+    notes::SymbolNotes,
+    notifications::AlertEngine,
+    openrouter::OpenRouterClient,
+    storage::Storage,
+    weekend::WeekendSettings,
+    yahoo::YahooFinanceClient,
+};
+use axum::{
+    extract::State,
+    response::{IntoResponse, Json},
+    routing::get,
+    Router,
+};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: MongoDB,
+    /// Latest analyses (see `storage.rs`); usually the same database as `db`.
+    pub storage: Storage,
+    pub cache: CacheLayer,
+    pub progress: Arc<RwLock<AnalysisProgress>>,
+    pub yahoo_client: YahooFinanceClient,
+    pub openrouter_client: OpenRouterClient,
+    pub nasdaq_client: NasdaqClient,
+    pub alert_engine: AlertEngine,
+    pub intraday: IntradayRelay,
+    /// Saved analyses from the `stock_analysis` change stream (see
+    /// `change_feed.rs`).
+    pub analysis_feed: AnalysisFeed,
+    /// Default for `?tz=` (see `timezone.rs`).
+    pub api_timezone: chrono_tz::Tz,
+    /// `None` when `BACKUP_DIR` is unset.
+    pub backups: Option<BackupSettings>,
+    /// `None` when `RAW_ARCHIVE_DIR` is unset.
+    pub raw_archive: Option<RawArchive>,
+    /// External signals shared with the analysis engine (see `ingest.rs`).
+    pub signals: SignalInbox,
+    /// User notes and tags shared with the analysis engine (see `notes.rs`).
+    pub notes: SymbolNotes,
+    /// `INGEST_TOKEN`; `None` accepts unauthenticated signals.
+    pub ingest_token: Option<String>,
+    /// Read-only switch (see `maintenance.rs`).
+    pub maintenance: MaintenanceMode,
+    /// Weekend deep-analysis jobs (see `weekend.rs`).
+    pub weekend: WeekendSettings,
+    /// Indicator periods, for replays (see `indicators.rs`).
+    pub indicators: IndicatorConfig,
+    /// `HISTORY_RETENTION_DAYS` (see `retention.rs`); 0 keeps all.
+    pub history_retention_days: u32,
+}
+
+pub fn create_router(state: AppState) -> Router {
+    let router: Router<AppState> = Router::new()
+        .route("/", get(root))
+        .route("/health", get(health))
+        .merge(stocks::router())
+        .merge(market::router())
+        .merge(ai::router())
+        .merge(admin::router())
+        .merge(ws::router());
+
+    let api_timezone = state.api_timezone;
+    let responses = state.cache.responses().clone();
+    let maintenance = state.maintenance.clone();
+    crate::notifications::api::mount(router)
+        .layer(axum::middleware::from_fn_with_state(
+            responses,
+            crate::response_cache::serve_cached,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            api_timezone,
+            crate::timezone::localize_timestamps,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            maintenance,
+            crate::maintenance::reject_mutations,
+        ))
+        .with_state(state)
+}
+
+async fn root(State(state): State<AppState>) -> impl IntoResponse {
+    let maintenance = state.maintenance.status();
+    Json(json!({
+        "name": "Auto Stock Analyser API",
+        "version": "0.1.0",
+        "status": if maintenance.read_only { "read_only" } else { "running" },
+        "maintenance": maintenance
+    }))
+}
+
+async fn health(State(state): State<AppState>) -> impl IntoResponse {
+    let db_ok = state.storage.get_analysis_count().await;
+    let count = db_ok.as_ref().copied().unwrap_or(0);
+    let progress = state.progress.read().await;
+    let status = if db_ok.is_ok() { "healthy" } else { "degraded" };
+
+    Json(json!({
+        "status": status,
+        "database": if db_ok.is_ok() { "connected" } else { "error" },
+        "total_analyses": count,
+        "last_cycle_started": progress.last_cycle_started,
+        "last_cycle_completed": progress.last_cycle_completed,
+        "last_successful_cycle": progress.last_successful_cycle,
+        "last_error": progress.last_error,
+        "mode": progress.mode,
+        "degraded_reason": progress.degraded_reason,
+        "maintenance": state.maintenance.status(),
+        "yahoo_budget": state.yahoo_client.budget_stats()
+    }))
+}
+
+/// Cache freshly fetched earnings and store them on the symbol's analysis,
+/// without rewriting the rest of it. These are GET handlers, which
+/// read-only mode lets through, so while it is on only the cache is updated.
+async fn persist_earnings(
+    db: &MongoDB,
+    cache: &CacheLayer,
+    maintenance: &MaintenanceMode,
+    symbol: &str,
+    data: &EarningsData,
+) {
+    cache.set_earnings(symbol.to_string(), data.clone()).await;
+    if maintenance.is_read_only() {
+        return;
+    }
+    let fields = match mongodb::bson::to_bson(data) {
+        Ok(earnings) => mongodb::bson::doc! { "earnings": earnings },
+        Err(e) => {
+            warn!("Failed to encode earnings for {}: {}", symbol, e);
+            return;
+        }
+    };
+    match db.update_analysis_fields(symbol, fields).await {
+        Ok(true) => cache.invalidate_stock(symbol).await,
+        Ok(false) => {}
+        Err(e) => warn!("Failed to store earnings for {}: {}", symbol, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::state;
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn read_only_earnings_fetch_only_updates_the_cache() {
+        let state = state().await;
+        let data = EarningsData {
+            earnings_date: None,
+            eps_estimate: Some(1.5),
+            revenue_estimate: None,
+        };
+        let persist = |symbol| {
+            let write =
+                persist_earnings(&state.db, &state.cache, &state.maintenance, symbol, &data);
+            tokio::time::timeout(Duration::from_millis(50), write)
+        };
+
+        // A write waits out the unreachable database's server selection.
+        assert!(persist("AAPL").await.is_err());
+
+        state.maintenance.set(true, None);
+        assert!(
+            persist("MSFT").await.is_ok(),
+            "read-only mode must not reach the database"
+        );
+        assert!(state.cache.get_earnings("MSFT").await.is_some());
+    }
+}
+
+/// State and a request helper shared by the per-router tests. The database
+/// never answers, so tests stick to routes that work (or fail cleanly)
+/// without it.
+#[cfg(test)]
+mod test_support {
+    use super::{AnalysisProgress, AppState, Arc, RwLock};
+    use axum::{
+        body::Body,
+        http::{Method, StatusCode},
+        Router,
+    };
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    pub(super) async fn state() -> AppState {
+        let db = crate::db::MongoDB::unreachable().await;
+        let storage: crate::storage::Storage = Arc::new(db.clone());
+        AppState {
+            cache: crate::cache::CacheLayer::new(300, 60, 16).with_read_through(storage.clone()),
+            progress: Arc::new(RwLock::new(AnalysisProgress {
+                total_stocks: 0,
+                analyzed: 0,
+                current_symbol: None,
+                cycle_start: chrono::Utc::now(),
+                errors: 0,
+                last_cycle_started: None,
+                last_cycle_completed: None,
+                last_successful_cycle: None,
+                last_error: None,
+                mode: Default::default(),
+                degraded_reason: None,
+                saved_symbols: Vec::new(),
+            })),
+            yahoo_client: crate::yahoo::YahooFinanceClient::new(),
+            openrouter_client: crate::openrouter::OpenRouterClient::new(None, false),
+            nasdaq_client: crate::nasdaq::NasdaqClient::new(0),
+            alert_engine: crate::notifications::AlertEngine::new(db.clone(), false, None)
+                .await
+                .unwrap(),
+            intraday: crate::intraday::IntradayRelay::new(),
+            analysis_feed: crate::change_feed::AnalysisFeed::new(),
+            api_timezone: chrono_tz::UTC,
+            backups: None,
+            raw_archive: None,
+            signals: crate::ingest::SignalInbox::new(chrono::Duration::hours(24)),
+            notes: Default::default(),
+            ingest_token: None,
+            maintenance: Default::default(),
+            weekend: crate::weekend::WeekendSettings {
+                hour_ny: None,
+                backfill_years: 0,
+                ai_summaries: 0,
+                delay: std::time::Duration::ZERO,
+            },
+            indicators: Default::default(),
+            history_retention_days: 0,
+            db,
+            storage,
+        }
+    }
+
+    /// Send one request through `router` and decode the JSON reply
+    /// (`Value::Null` for an empty or non-JSON body).
+    pub(super) async fn send(
+        router: Router,
+        method: Method,
+        uri: &str,
+        body: Option<&str>,
+    ) -> (StatusCode, Value) {
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+}
@@ -0,0 +1,211 @@
+//! Shared pagination for listing endpoints.
+//!
+//! `/api/stocks`, `/api/stocks/filter`, `/api/news`, `/api/ingest/signals`,
+//! `/api/events/52w`, `/api/alerts/history` and `/api/runs` all take `page` /
+//! `page_size`, or the opaque `cursor` from a previous response, and answer
+//! with:
+//!
+//! - a `pagination` object (`page`, `page_size`, `total`, `total_pages`,
+//!   `next_cursor`, which is `null` on the last page);
+//! - `X-Total-Count: <total>`;
+//! - an RFC 5988 `Link` header with `first`, `prev`, `next` and `last`
+//!   relations. The links keep the request's other query parameters, so a
+//!   client can follow `rel="next"` without knowing the endpoint.
+
+use axum::http::{header, HeaderMap, HeaderValue, Uri};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Response header carrying the number of items across all pages.
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Query parameters shared by paginated endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// `next_cursor` from a previous page; wins over `page`.
+    pub cursor: Option<String>,
+}
+
+impl PageQuery {
+    /// The requested page, `page_size` defaulting to `default_size` and
+    /// clamped to `1..=max_size`. Unreadable cursors fall back to `page`.
+    pub fn resolve(&self, default_size: u32, max_size: u32) -> Page {
+        let page = self
+            .cursor
+            .as_deref()
+            .and_then(decode_cursor)
+            .or(self.page)
+            .unwrap_or(1)
+            .max(1);
+        let page_size = self.page_size.unwrap_or(default_size).clamp(1, max_size);
+        Page { page, page_size }
+    }
+}
+
+/// One 1-based page of a listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Page {
+    pub fn offset(&self) -> u64 {
+        (self.page as u64 - 1) * self.page_size as u64
+    }
+
+    pub fn total_pages(&self, total: u64) -> u64 {
+        total.div_ceil(self.page_size as u64)
+    }
+
+    fn has_next(&self, total: u64) -> bool {
+        (self.page as u64) < self.total_pages(total)
+    }
+
+    /// The `pagination` object of a listing response.
+    pub fn json(&self, total: u64) -> Value {
+        json!({
+            "page": self.page,
+            "page_size": self.page_size,
+            "total": total,
+            "total_pages": self.total_pages(total),
+            "next_cursor": self.has_next(total).then(|| encode_cursor(self.page + 1)),
+        })
+    }
+
+    /// `X-Total-Count` and `Link` for this page of the listing at `uri`.
+    pub fn headers(&self, uri: &Uri, total: u64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(TOTAL_COUNT_HEADER, HeaderValue::from(total));
+
+        let last = self.total_pages(total).max(1) as u32;
+        let mut links = vec![(1, "first")];
+        if self.page > 1 {
+            links.push(((self.page - 1).min(last), "prev"));
+        }
+        if self.has_next(total) {
+            links.push((self.page + 1, "next"));
+        }
+        links.push((last, "last"));
+        let link = links
+            .into_iter()
+            .map(|(page, rel)| format!("<{}>; rel=\"{}\"", self.link(uri, page), rel))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Ok(value) = HeaderValue::from_str(&link) {
+            headers.insert(header::LINK, value);
+        }
+        headers
+    }
+
+    /// `uri` with its paging parameters replaced by `page`.
+    fn link(&self, uri: &Uri, page: u32) -> String {
+        let mut params: Vec<&str> = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| {
+                let key = pair.split('=').next().unwrap_or_default();
+                !pair.is_empty() && !matches!(key, "page" | "page_size" | "cursor" | "limit")
+            })
+            .collect();
+        let paging = format!("page={}&page_size={}", page, self.page_size);
+        params.push(&paging);
+        format!("{}?{}", uri.path(), params.join("&"))
+    }
+}
+
+/// Cursors are opaque to clients; today they carry the page number.
+fn encode_cursor(page: u32) -> String {
+    format!("p{}", page)
+}
+
+fn decode_cursor(cursor: &str) -> Option<u32> {
+    cursor.strip_prefix('p')?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefers_cursor_and_clamps_size() {
+        let query = PageQuery {
+            page: Some(2),
+            page_size: Some(500),
+            cursor: Some("p4".to_string()),
+        };
+        assert_eq!(
+            query.resolve(50, 100),
+            Page {
+                page: 4,
+                page_size: 100
+            }
+        );
+        let query = PageQuery {
+            page: Some(0),
+            page_size: None,
+            cursor: Some("garbage".to_string()),
+        };
+        assert_eq!(
+            query.resolve(50, 100),
+            Page {
+                page: 1,
+                page_size: 50
+            }
+        );
+    }
+
+    #[test]
+    fn test_json_reports_next_cursor_until_the_last_page() {
+        let page = Page {
+            page: 2,
+            page_size: 10,
+        };
+        assert_eq!(page.offset(), 10);
+        let body = page.json(25);
+        assert_eq!(body["total_pages"], 3);
+        assert_eq!(body["next_cursor"], "p3");
+        assert_eq!(
+            Page {
+                page: 3,
+                page_size: 10
+            }
+            .json(25)["next_cursor"],
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_headers_link_keeps_other_parameters() {
+        let uri: Uri = "/api/news?sector=Tech&page=2&page_size=10&cursor=p2"
+            .parse()
+            .unwrap();
+        let page = Page {
+            page: 2,
+            page_size: 10,
+        };
+        let headers = page.headers(&uri, 25);
+        assert_eq!(headers[TOTAL_COUNT_HEADER], "25");
+        assert_eq!(
+            headers[header::LINK],
+            "</api/news?sector=Tech&page=1&page_size=10>; rel=\"first\", \
+             </api/news?sector=Tech&page=1&page_size=10>; rel=\"prev\", \
+             </api/news?sector=Tech&page=3&page_size=10>; rel=\"next\", \
+             </api/news?sector=Tech&page=3&page_size=10>; rel=\"last\""
+        );
+
+        let empty = Page {
+            page: 1,
+            page_size: 50,
+        }
+        .headers(&"/api/stocks".parse().unwrap(), 0);
+        assert_eq!(
+            empty[header::LINK],
+            "</api/stocks?page=1&page_size=50>; rel=\"first\", \
+             </api/stocks?page=1&page_size=50>; rel=\"last\""
+        );
+    }
+}
@@ -0,0 +1,819 @@
+//! Stored analyses: listing, filtering, one symbol's analysis and the
+//! per-symbol extras (history, profile, earnings, insiders, notes,
+//! seasonality, analysis history).
+
+use super::{pagination::PageQuery, persist_earnings, AppState};
+use crate::{
+    cache::{CacheLayer, StockSource},
+    db::MongoDB,
+    format,
+    maintenance::MaintenanceMode,
+    models::{HistoricalPrice, StockFilter},
+    nasdaq::NasdaqClient,
+    notes::{NoteInput, SymbolNote, SymbolNotes},
+    storage::Storage,
+    validation::ValidatedJson,
+    yahoo::{PriceAdjustment, YahooFinanceClient},
+};
+use axum::{
+    extract::{FromRef, Path, Query, State},
+    http::Uri,
+    response::{IntoResponse, Json},
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::warn;
+
+pub(super) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/stocks", get(get_stocks))
+        .route("/api/stocks/filter", post(filter_stocks))
+        .route("/api/stocks/:symbol", get(get_stock_by_symbol))
+        .route("/api/stocks/:symbol/history", get(get_stock_history))
+        .route("/api/stocks/:symbol/profile", get(get_stock_profile))
+        .route(
+            "/api/stocks/:symbol/notes",
+            get(get_stock_notes).put(save_stock_notes),
+        )
+        .route("/api/stocks/:symbol/insiders", get(get_insider_trades))
+        .route("/api/stocks/:symbol/earnings", get(get_stock_earnings))
+        .route(
+            "/api/stocks/:symbol/seasonality",
+            get(get_stock_seasonality),
+        )
+        .route(
+            "/api/stocks/:symbol/analysis-history",
+            get(get_analysis_history),
+        )
+        .route("/api/search", get(search_symbols))
+        .route("/api/tags", get(list_tags))
+        .route("/api/symbols/aliases", get(get_symbol_aliases))
+}
+
+/// Slice of `AppState` the stock handlers use.
+#[derive(Clone)]
+pub(super) struct StocksState {
+    db: MongoDB,
+    storage: Storage,
+    cache: CacheLayer,
+    yahoo_client: YahooFinanceClient,
+    nasdaq_client: NasdaqClient,
+    notes: SymbolNotes,
+    maintenance: MaintenanceMode,
+}
+
+impl FromRef<AppState> for StocksState {
+    fn from_ref(state: &AppState) -> Self {
+        Self {
+            db: state.db.clone(),
+            storage: state.storage.clone(),
+            cache: state.cache.clone(),
+            yahoo_client: state.yahoo_client.clone(),
+            nasdaq_client: state.nasdaq_client.clone(),
+            notes: state.notes.clone(),
+            maintenance: state.maintenance.clone(),
+        }
+    }
+}
+
+/// `?humanize=true` adds `market_cap_display` / `volume_display` to stocks.
+#[derive(Debug, Default, Deserialize)]
+pub struct HumanizeQuery {
+    #[serde(default)]
+    pub humanize: bool,
+}
+
+/// Renamed tickers and the symbol each now resolves to.
+async fn get_symbol_aliases(State(state): State<StocksState>) -> impl IntoResponse {
+    match state.db.get_symbol_aliases().await {
+        Ok(aliases) => Json(json!({
+            "success": true,
+            "count": aliases.len(),
+            "aliases": aliases
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// `?as_of=<RFC 3339>` answers from the analysis history instead of the
+/// live analyses (see `StockFilter::as_of`).
+#[derive(Debug, Default, Deserialize)]
+pub struct AsOfQuery {
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// `?asset_type=etf,spac` or `?asset_type=all`; equities only when unset.
+#[derive(Debug, Default, Deserialize)]
+pub struct AssetTypeQuery {
+    pub asset_type: Option<String>,
+}
+
+async fn get_stocks(
+    State(state): State<StocksState>,
+    uri: Uri,
+    Query(fmt): Query<HumanizeQuery>,
+    Query(at): Query<AsOfQuery>,
+    Query(kind): Query<AssetTypeQuery>,
+    Query(paging): Query<PageQuery>,
+) -> impl IntoResponse {
+    let page = paging.resolve(50, 200);
+    let filter = StockFilter {
+        min_price: None,
+        max_price: None,
+        min_volume: None,
+        min_market_cap: None,
+        max_market_cap: None,
+        min_rsi: None,
+        max_rsi: None,
+        sectors: None,
+        only_oversold: None,
+        only_overbought: None,
+        only_stoch_oversold: None,
+        only_stoch_overbought: None,
+        cross_signal: None,
+        supertrend_uptrend: None,
+        symbol_search: None,
+        min_stochastic_k: None,
+        max_stochastic_k: None,
+        min_williams_r: None,
+        max_williams_r: None,
+        min_cci: None,
+        max_cci: None,
+        min_mfi: None,
+        max_mfi: None,
+        min_bandwidth: None,
+        max_bandwidth: None,
+        max_abs_price_change_percent: None,
+        primary_class_only: None,
+        index: None,
+        theme: None,
+        tags: None,
+        asset_type: kind.asset_type.clone(),
+        return_period: None,
+        min_return_pct: None,
+        max_return_pct: None,
+        percentile_metric: None,
+        min_percentile: None,
+        max_percentile: None,
+        min_rs_rank: None,
+        as_of: at.as_of,
+        sort_by: Some("market_cap".to_string()),
+        sort_order: Some("desc".to_string()),
+        page: Some(page.page),
+        page_size: Some(page.page_size),
+    };
+    let count_filter = StockFilter {
+        as_of: at.as_of,
+        asset_type: kind.asset_type,
+        ..Default::default()
+    };
+
+    match state.storage.get_latest_analyses(filter).await {
+        Ok(stocks) => {
+            let total = state
+                .storage
+                .get_filtered_count(count_filter)
+                .await
+                .unwrap_or(stocks.len() as u64);
+            (
+                page.headers(&uri, total),
+                Json(json!({
+                    "success": true,
+                    "count": stocks.len(),
+                    "stocks": format::stocks_json(&stocks, fmt.humanize),
+                    "pagination": page.json(total)
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+        .into_response(),
+    }
+}
+
+async fn filter_stocks(
+    State(state): State<StocksState>,
+    uri: Uri,
+    Query(fmt): Query<HumanizeQuery>,
+    Query(paging): Query<PageQuery>,
+    ValidatedJson(mut filter): ValidatedJson<StockFilter>,
+) -> impl IntoResponse {
+    // Paging in the query (as in `Link` URLs) overrides the body's.
+    let page = PageQuery {
+        page: paging.page.or(filter.page),
+        page_size: paging.page_size.or(filter.page_size),
+        cursor: paging.cursor,
+    }
+    .resolve(50, 200);
+    filter.page = Some(page.page);
+    filter.page_size = Some(page.page_size);
+
+    // Clone filter for counting
+    let count_filter = StockFilter {
+        min_price: filter.min_price,
+        max_price: filter.max_price,
+        min_volume: filter.min_volume,
+        min_market_cap: filter.min_market_cap,
+        max_market_cap: filter.max_market_cap,
+        min_rsi: filter.min_rsi,
+        max_rsi: filter.max_rsi,
+        sectors: filter.sectors.clone(),
+        only_oversold: filter.only_oversold,
+        only_overbought: filter.only_overbought,
+        only_stoch_oversold: filter.only_stoch_oversold,
+        only_stoch_overbought: filter.only_stoch_overbought,
+        cross_signal: filter.cross_signal,
+        supertrend_uptrend: filter.supertrend_uptrend,
+        symbol_search: filter.symbol_search.clone(),
+        min_stochastic_k: filter.min_stochastic_k,
+        max_stochastic_k: filter.max_stochastic_k,
+        min_williams_r: filter.min_williams_r,
+        max_williams_r: filter.max_williams_r,
+        min_cci: filter.min_cci,
+        max_cci: filter.max_cci,
+        min_mfi: filter.min_mfi,
+        max_mfi: filter.max_mfi,
+        min_bandwidth: filter.min_bandwidth,
+        max_bandwidth: filter.max_bandwidth,
+        max_abs_price_change_percent: filter.max_abs_price_change_percent,
+        primary_class_only: filter.primary_class_only,
+        index: filter.index.clone(),
+        theme: filter.theme.clone(),
+        tags: filter.tags.clone(),
+        asset_type: filter.asset_type.clone(),
+        return_period: filter.return_period.clone(),
+        min_return_pct: filter.min_return_pct,
+        max_return_pct: filter.max_return_pct,
+        percentile_metric: filter.percentile_metric.clone(),
+        min_percentile: filter.min_percentile,
+        max_percentile: filter.max_percentile,
+        min_rs_rank: filter.min_rs_rank,
+        as_of: filter.as_of,
+        sort_by: None,
+        sort_order: None,
+        page: None,
+        page_size: None,
+    };
+
+    // Try cache first
+    let cache_key = format!("{:?}", filter);
+    if let Some(cached) = state.cache.get_list(&cache_key).await {
+        let total = state
+            .storage
+            .get_filtered_count(count_filter)
+            .await
+            .unwrap_or(cached.len() as u64);
+
+        return (
+            page.headers(&uri, total),
+            Json(json!({
+                "success": true,
+                "count": cached.len(),
+                "stocks": format::stocks_json(cached.iter(), fmt.humanize),
+                "cached": true,
+                "pagination": page.json(total)
+            })),
+        )
+            .into_response();
+    }
+
+    // Get total count for pagination
+    let total = state
+        .storage
+        .get_filtered_count(count_filter)
+        .await
+        .unwrap_or(0);
+
+    match state.storage.get_latest_analyses(filter).await {
+        Ok(stocks) => {
+            // Cache the results
+            let stocks = Arc::new(stocks);
+            state.cache.set_list(cache_key, stocks.clone()).await;
+
+            (
+                page.headers(&uri, total),
+                Json(json!({
+                    "success": true,
+                    "count": stocks.len(),
+                    "stocks": format::stocks_json(stocks.iter(), fmt.humanize),
+                    "cached": false,
+                    "pagination": page.json(total)
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+        .into_response(),
+    }
+}
+
+/// Get a single stock by symbol
+async fn get_stock_by_symbol(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+    Query(fmt): Query<HumanizeQuery>,
+) -> impl IntoResponse {
+    match state.cache.lookup_stock(&symbol).await {
+        Ok(Some((analysis, source))) => Json(json!({
+            "success": true,
+            "share_classes": crate::share_classes::siblings(&analysis.symbol),
+            "stock": if fmt.humanize {
+                format::humanized_stock(&analysis)
+            } else {
+                json!(*analysis)
+            },
+            "cached": source == StockSource::Cache
+        })),
+        Ok(None) => Json(json!({
+            "success": false,
+            "error": format!("Stock '{}' not found. It may not have been analyzed yet or failed during analysis.", symbol)
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+async fn get_stock_notes(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    let note = state.notes.get(&symbol).await;
+    Json(json!({
+        "success": true,
+        "symbol": symbol,
+        "note": note
+    }))
+}
+
+/// Month-of-year returns from the weekend backfill (see `weekend.rs`).
+async fn get_stock_seasonality(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    match state.db.get_seasonality(&symbol).await {
+        Ok(seasonality) => Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "seasonality": seasonality
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Database error: {}", e)
+        })),
+    }
+}
+
+/// Query parameters for a symbol's analysis history
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    /// Ticker or company-name fragment.
+    pub q: Option<String>,
+    /// 1 to 50 (default 10).
+    pub limit: Option<usize>,
+}
+
+/// Ticker search box: symbols and company names from the latest screener
+/// fetch matching `q`, best match first (exact ticker, ticker prefix, name
+/// word prefix, then other name matches).
+async fn search_symbols(
+    State(state): State<StocksState>,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    let q = query.q.unwrap_or_default();
+    if q.trim().is_empty() {
+        return Json(json!({
+            "success": false,
+            "error": "q is required"
+        }));
+    }
+    let limit = query.limit.unwrap_or(10);
+    if !(1..=50).contains(&limit) {
+        return Json(json!({
+            "success": false,
+            "error": "limit must be between 1 and 50"
+        }));
+    }
+    match state.db.search_stocks(&q, limit).await {
+        Ok(results) => Json(json!({
+            "success": true,
+            "query": q.trim(),
+            "count": results.len(),
+            "results": results
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Database error: {}", e)
+        })),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalysisHistoryQuery {
+    /// Days back from now, 1 to 3650 (default 30).
+    pub days: Option<i64>,
+}
+
+/// How a symbol's saved analyses (price, RSI, SMAs, MACD, volume) changed
+/// over the last `days`, from `analysis_history`, oldest first.
+async fn get_analysis_history(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<AnalysisHistoryQuery>,
+) -> impl IntoResponse {
+    let days = query.days.unwrap_or(30);
+    if !(1..=3650).contains(&days) {
+        return Json(json!({
+            "success": false,
+            "error": "days must be between 1 and 3650"
+        }));
+    }
+    let symbol = state.cache.resolve_symbol(&symbol);
+    let since = chrono::Utc::now() - chrono::Duration::days(days);
+    match state.db.get_analysis_history(&symbol, since).await {
+        Ok(points) => Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "days": days,
+            "count": points.len(),
+            "history": points
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Database error: {}", e)
+        })),
+    }
+}
+
+/// Replace a symbol's notes and tags. Symbols that haven't been analyzed yet
+/// can be annotated too; the notes appear on their first analysis.
+async fn save_stock_notes(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+    ValidatedJson(input): ValidatedJson<NoteInput>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    if symbol.is_empty() {
+        return Json(json!({
+            "success": false,
+            "error": "symbol is required"
+        }));
+    }
+    let note = SymbolNote::from_input(&symbol, &input);
+    if let Err(e) = state.db.save_symbol_note(&note).await {
+        return Json(json!({
+            "success": false,
+            "error": e.to_string()
+        }));
+    }
+    state.notes.set(note.clone()).await;
+
+    // Stamp the stored analysis now rather than waiting for the next cycle.
+    let fields = mongodb::bson::doc! {
+        "notes": if note.notes.is_empty() {
+            mongodb::bson::Bson::Null
+        } else {
+            mongodb::bson::Bson::String(note.notes.clone())
+        },
+        "tags": note.tags.clone(),
+    };
+    match state.db.update_analysis_fields(&symbol, fields).await {
+        Ok(true) => {
+            state.cache.invalidate_stock(&symbol).await;
+            state.cache.invalidate_all_lists().await;
+        }
+        Ok(false) => {}
+        Err(e) => warn!(
+            "Failed to store notes on the analysis for {}: {}",
+            symbol, e
+        ),
+    }
+
+    Json(json!({
+        "success": true,
+        "symbol": symbol,
+        "note": (!note.is_empty()).then_some(note)
+    }))
+}
+
+/// Every tag in use, with how many symbols carry it.
+async fn list_tags(State(state): State<StocksState>) -> impl IntoResponse {
+    let tags = state.notes.tag_counts().await;
+    Json(json!({
+        "success": true,
+        "count": tags.len(),
+        "tags": tags
+    }))
+}
+
+/// Query parameters for stock history
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    /// Also return dividends and a dividends-reinvested close series.
+    #[serde(default)]
+    pub total_return: bool,
+    /// Back-adjust the bars for splits and dividends.
+    #[serde(default)]
+    pub adjusted: bool,
+    /// `regular` (default) or `heikin_ashi`.
+    pub candle_type: Option<String>,
+}
+
+async fn get_stock_history(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    let heikin_ashi = match query.candle_type.as_deref().map(str::trim) {
+        None | Some("") | Some("regular") => false,
+        Some("heikin_ashi") => true,
+        Some(other) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Unknown candle_type '{}' (use regular or heikin_ashi)", other)
+            }));
+        }
+    };
+    let candles = |history: Vec<HistoricalPrice>| {
+        if heikin_ashi {
+            crate::analytics::heikin_ashi(&history)
+        } else {
+            history
+        }
+    };
+
+    if query.total_return {
+        return match state
+            .yahoo_client
+            .get_history_with_dividends(&symbol, 90)
+            .await
+        {
+            Ok((history, dividends)) => Json(json!({
+                "success": true,
+                "symbol": symbol,
+                "total_return_index": crate::analytics::total_return_index(&history, &dividends),
+                "history": candles(history),
+                "dividends": dividends,
+            })),
+            Err(e) => Json(json!({
+                "success": false,
+                "error": e.to_string()
+            })),
+        };
+    }
+
+    // Fetch from Yahoo Finance (90 days of historical data)
+    let adjustment = if query.adjusted {
+        PriceAdjustment::Adjusted
+    } else {
+        PriceAdjustment::Raw
+    };
+    match state
+        .yahoo_client
+        .fetch_historical_data(&symbol, 90, adjustment)
+        .await
+    {
+        Ok(history) => Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "history": candles(history),
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get company profile from Yahoo Finance (description, industry, website, etc.)
+async fn get_stock_profile(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    let cache_key = symbol.to_uppercase();
+
+    if let Some(profile) = state.cache.get_company_profile(&cache_key).await {
+        return Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "profile": profile,
+            "cached": true,
+        }));
+    }
+
+    match state.yahoo_client.get_company_profile(&cache_key).await {
+        Ok(profile) => {
+            state
+                .cache
+                .set_company_profile(cache_key, profile.clone())
+                .await;
+            Json(json!({
+                "success": true,
+                "symbol": symbol,
+                "profile": profile,
+                "cached": false,
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to fetch company profile for {}: {}", symbol, e);
+            Json(json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+/// Get insider trades for a stock
+async fn get_insider_trades(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    // Check cache
+    if let Some(cached) = state.cache.get_insiders(&symbol).await {
+        return Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "trades": cached,
+            "cached": true
+        }));
+    }
+
+    match state.nasdaq_client.get_insider_trades(&symbol, 20).await {
+        Ok(trades) => {
+            state
+                .cache
+                .set_insiders(symbol.clone(), trades.clone())
+                .await;
+            Json(json!({
+                "success": true,
+                "symbol": symbol,
+                "trades": trades,
+                "cached": false
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to fetch insider trades for {}: {}", symbol, e);
+            Json(json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+/// Get earnings data for a single stock
+async fn get_stock_earnings(
+    State(state): State<StocksState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let symbol = state.cache.resolve_symbol(&symbol);
+    // Check cache
+    if let Some(cached) = state.cache.get_earnings(&symbol).await {
+        return Json(json!({
+            "success": true,
+            "symbol": symbol,
+            "earnings": cached,
+            "cached": true
+        }));
+    }
+
+    match state.yahoo_client.get_earnings_data(&symbol).await {
+        Ok(data) => {
+            persist_earnings(&state.db, &state.cache, &state.maintenance, &symbol, &data).await;
+            Json(json!({
+                "success": true,
+                "symbol": symbol,
+                "earnings": data,
+                "cached": false
+            }))
+        }
+        Err(e) => {
+            warn!("Failed to fetch earnings for {}: {}", symbol, e);
+            Json(json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::{send, state};
+    use super::*;
+    use axum::http::{Method, StatusCode};
+
+    #[tokio::test]
+    async fn notes_are_validated_and_served_from_the_shared_store() {
+        let state = state().await;
+        state
+            .notes
+            .set(SymbolNote::from_input(
+                "AAPL",
+                &NoteInput {
+                    notes: "reports Thursday".into(),
+                    tags: vec!["Earnings Play".into()],
+                },
+            ))
+            .await;
+        let app = || router().with_state(state.clone());
+
+        let (status, body) = send(
+            app(),
+            Method::PUT,
+            "/api/stocks/AAPL/notes",
+            Some(r#"{"tags": ["no/slashes"]}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["errors"][0]["field"], "tags");
+
+        let (status, body) = send(app(), Method::GET, "/api/stocks/aapl/notes", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["symbol"], "AAPL");
+        assert_eq!(body["note"]["tags"], json!(["earnings-play"]));
+
+        let (_, body) = send(app(), Method::GET, "/api/tags", None).await;
+        assert_eq!(
+            body["tags"],
+            json!([{ "tag": "earnings-play", "symbols": 1 }])
+        );
+    }
+
+    #[tokio::test]
+    async fn filter_reports_a_bad_percentile_filter() {
+        let app = router().with_state(state().await);
+        let (status, body) = send(
+            app,
+            Method::POST,
+            "/api/stocks/filter",
+            Some(r#"{"min_percentile": 90}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], false);
+        assert!(body["error"]
+            .as_str()
+            .unwrap()
+            .contains("percentile_metric is required"));
+    }
+
+    #[tokio::test]
+    async fn history_rejects_an_unknown_candle_type() {
+        let app = router().with_state(state().await);
+        let (status, body) = send(
+            app,
+            Method::GET,
+            "/api/stocks/AAPL/history?candle_type=renko",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("renko"));
+    }
+
+    #[tokio::test]
+    async fn analysis_history_rejects_out_of_range_days() {
+        let app = router().with_state(state().await);
+        let (status, body) = send(
+            app,
+            Method::GET,
+            "/api/stocks/AAPL/analysis-history?days=0",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("days"));
+    }
+
+    #[tokio::test]
+    async fn search_requires_a_query() {
+        let app = router().with_state(state().await);
+        let (status, body) = send(app, Method::GET, "/api/search?q=%20", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains('q'));
+    }
+}
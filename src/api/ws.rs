@@ -0,0 +1,447 @@
+//! Cycle progress, polled over HTTP or pushed over the `/ws` socket along
+//! with intraday candles for subscribed symbols, and the log of past cycles
+//! (see `runs.rs`).
+//!
+//! By default the socket pushes the full progress snapshot every 2 seconds.
+//! `/ws?diff=true` switches the connection to differential updates: a
+//! `keyframe` with the whole snapshot on connect and every
+//! `KEYFRAME_EVERY_TICKS` ticks, and in between a `delta` carrying only the
+//! changed fields plus the symbols saved since the last message. Ticks with
+//! nothing new send nothing.
+//!
+//! Saved analyses are pushed as they are written (see `change_feed.rs`):
+//! every one with `/ws?analyses=true`, otherwise those of the symbols the
+//! connection subscribed to for candles.
+
+use super::{pagination::PageQuery, AppState};
+use crate::{
+    change_feed::AnalysisFeed,
+    db::MongoDB,
+    intraday::{IntradayRelay, MAX_SUBSCRIPTIONS_PER_CLIENT},
+    models::{AnalysisProgress, SymbolCycleStatus},
+    runs,
+};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        FromRef, Query, State, WebSocketUpgrade,
+    },
+    http::Uri,
+    response::{IntoResponse, Json},
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+
+pub(super) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/progress", get(get_progress))
+        .route("/api/progress/symbols", get(get_progress_symbols))
+        .route("/api/runs", get(get_runs))
+        .route("/ws", get(websocket_handler))
+}
+
+/// Slice of `AppState` the progress and socket handlers use.
+#[derive(Clone)]
+pub(super) struct LiveState {
+    db: MongoDB,
+    progress: Arc<RwLock<AnalysisProgress>>,
+    intraday: IntradayRelay,
+    analysis_feed: AnalysisFeed,
+}
+
+impl FromRef<AppState> for LiveState {
+    fn from_ref(state: &AppState) -> Self {
+        Self {
+            db: state.db.clone(),
+            progress: state.progress.clone(),
+            intraday: state.intraday.clone(),
+            analysis_feed: state.analysis_feed.clone(),
+        }
+    }
+}
+
+async fn get_progress(State(state): State<LiveState>) -> impl IntoResponse {
+    let progress = state.progress.read().await;
+    Json(json!({
+        "total_stocks": progress.total_stocks,
+        "analyzed": progress.analyzed,
+        "current_symbol": progress.current_symbol,
+        "cycle_start": progress.cycle_start,
+        "errors": progress.errors,
+        "last_cycle_started": progress.last_cycle_started,
+        "last_cycle_completed": progress.last_cycle_completed,
+        "last_successful_cycle": progress.last_successful_cycle,
+        "last_error": progress.last_error,
+        "mode": progress.mode,
+        "degraded_reason": progress.degraded_reason,
+        "completion_percentage": if progress.total_stocks > 0 {
+            progress.analyzed as f64 / progress.total_stocks as f64 * 100.0
+        } else {
+            0.0
+        }
+    }))
+}
+
+/// Query parameters for `/api/progress/symbols`
+#[derive(Debug, Deserialize)]
+pub struct ProgressSymbolsQuery {
+    pub status: Option<SymbolCycleStatus>,
+}
+
+/// Per-symbol state of the current (or last) analysis cycle.
+async fn get_progress_symbols(
+    State(state): State<LiveState>,
+    Query(query): Query<ProgressSymbolsQuery>,
+) -> impl IntoResponse {
+    match state.db.get_cycle_symbols(query.status).await {
+        Ok(symbols) => Json(json!({
+            "success": true,
+            "cycle_start": state.progress.read().await.cycle_start,
+            "count": symbols.len(),
+            "symbols": symbols
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Query parameters for `/api/runs`
+#[derive(Debug, Deserialize)]
+pub struct RunsQuery {
+    /// Only runs started at or after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Recorded analysis cycles, most recent first, with a summary of the page.
+async fn get_runs(
+    State(state): State<LiveState>,
+    uri: Uri,
+    Query(query): Query<RunsQuery>,
+    Query(paging): Query<PageQuery>,
+) -> impl IntoResponse {
+    let page = paging.resolve(50, 500);
+    let list = state
+        .db
+        .get_analysis_runs(query.since, page.offset(), page.page_size as i64);
+    let total = state.db.count_analysis_runs(query.since);
+    match futures::try_join!(list, total) {
+        Ok((list, total)) => (
+            page.headers(&uri, total),
+            Json(json!({
+                "success": true,
+                "count": list.len(),
+                "summary": runs::summarize(&list),
+                "runs": list,
+                "pagination": page.json(total)
+            })),
+        )
+            .into_response(),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Database error: {}", e)
+        }))
+        .into_response(),
+    }
+}
+
+/// Query parameters for `/ws`
+#[derive(Debug, Default, Deserialize)]
+pub struct WsQuery {
+    /// Send keyframes and deltas instead of a full snapshot every tick.
+    #[serde(default)]
+    pub diff: bool,
+    /// Push every saved analysis, not only subscribed symbols'.
+    #[serde(default)]
+    pub analyses: bool,
+}
+
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<LiveState>,
+    Query(query): Query<WsQuery>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| websocket_connection(socket, state, query))
+}
+
+/// Ticks between keyframes on a differential connection (one a minute), so
+/// a client that mis-applied a delta is corrected soon after.
+const KEYFRAME_EVERY_TICKS: u32 = 30;
+
+/// Per-connection state for differential progress updates.
+#[derive(Debug, Default)]
+struct ProgressDiffer {
+    /// Fields as of the last message sent; `None` before the first keyframe.
+    sent: Option<Map<String, Value>>,
+    /// How many of the cycle's `saved_symbols` were already sent.
+    symbols_sent: usize,
+    cycle_start: Option<chrono::DateTime<chrono::Utc>>,
+    ticks_since_keyframe: u32,
+}
+
+impl ProgressDiffer {
+    /// The next message for `progress`, or `None` when nothing changed.
+    fn next(&mut self, progress: &AnalysisProgress) -> Option<Value> {
+        let Ok(Value::Object(fields)) = serde_json::to_value(progress) else {
+            return None;
+        };
+        if self.cycle_start != Some(progress.cycle_start) {
+            self.cycle_start = Some(progress.cycle_start);
+            self.symbols_sent = 0;
+        }
+        // Symbols saved before the client connected aren't replayed; the
+        // keyframe's `analyzed` count covers them.
+        let first = self.sent.is_none();
+        let saved = progress
+            .saved_symbols
+            .get(self.symbols_sent..)
+            .filter(|_| !first)
+            .unwrap_or_default()
+            .to_vec();
+        self.symbols_sent = progress.saved_symbols.len();
+
+        self.ticks_since_keyframe += 1;
+        if first || self.ticks_since_keyframe >= KEYFRAME_EVERY_TICKS {
+            self.sent = Some(fields.clone());
+            self.ticks_since_keyframe = 0;
+            return Some(json!({
+                "type": "keyframe",
+                "progress": fields,
+                "analyzed_symbols": saved,
+            }));
+        }
+        let sent = self.sent.as_mut()?;
+        let changed: Map<String, Value> = fields
+            .into_iter()
+            .filter(|(key, value)| sent.get(key) != Some(value))
+            .collect();
+        if changed.is_empty() && saved.is_empty() {
+            return None;
+        }
+        sent.extend(changed.clone());
+        Some(json!({
+            "type": "delta",
+            "changed": changed,
+            "analyzed_symbols": saved,
+        }))
+    }
+}
+
+/// Client → server WebSocket message.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum WsCommand {
+    Subscribe { symbols: Vec<String> },
+    Unsubscribe { symbols: Vec<String> },
+}
+
+async fn websocket_connection(mut socket: WebSocket, state: LiveState, query: WsQuery) {
+    info!("WebSocket client connected");
+    let mut differ = query.diff.then(ProgressDiffer::default);
+    let progress_message =
+        |progress: &AnalysisProgress, differ: &mut Option<ProgressDiffer>| match differ {
+            Some(differ) => differ.next(progress).map(|msg| msg.to_string()),
+            None => Some(serde_json::to_string(progress).unwrap()),
+        };
+
+    // Send initial progress
+    let progress = state.progress.read().await;
+    let msg = progress_message(&progress, &mut differ).unwrap_or_default();
+    drop(progress);
+    if socket.send(Message::Text(msg)).await.is_err() {
+        return;
+    }
+
+    let mut candles = state.intraday.subscribe();
+    let mut analyses = state.analysis_feed.subscribe();
+    let mut subscribed: Vec<String> = Vec::new();
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(2));
+    ticker.tick().await;
+
+    loop {
+        let outgoing = tokio::select! {
+            // Progress updates every 2 seconds
+            _ = ticker.tick() => {
+                let progress = state.progress.read().await;
+                progress_message(&progress, &mut differ)
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    Some(handle_ws_command(&text, &mut subscribed, &state.intraday).await)
+                }
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => None,
+            },
+            candle = candles.recv() => match candle {
+                Ok(c) if subscribed.contains(&c.symbol) => {
+                    Some(json!({ "type": "candle", "candle": c }).to_string())
+                }
+                // Other symbols, or a lagging client: the next candle
+                // update catches it up. The sender lives in AppState, so the
+                // channel never closes while this task runs.
+                Ok(_) | Err(_) => None,
+            },
+            update = analyses.recv() => match update {
+                Ok(u) if query.analyses || subscribed.contains(&u.symbol) => Some(u.message),
+                // A lagging client misses analyses in between; the next
+                // save of each symbol is pushed as usual.
+                Ok(_) | Err(_) => None,
+            },
+        };
+
+        if let Some(msg) = outgoing {
+            if socket.send(Message::Text(msg)).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    state.intraday.remove_symbols(&subscribed).await;
+    info!("WebSocket client disconnected");
+}
+
+/// Apply a subscribe/unsubscribe command and build the reply.
+async fn handle_ws_command(
+    text: &str,
+    subscribed: &mut Vec<String>,
+    relay: &IntradayRelay,
+) -> String {
+    let command: WsCommand = match serde_json::from_str(text) {
+        Ok(c) => c,
+        Err(e) => return json!({ "type": "error", "error": e.to_string() }).to_string(),
+    };
+    match command {
+        WsCommand::Subscribe { symbols } => {
+            let mut added = Vec::new();
+            for s in symbols {
+                let s = crate::symbols::normalize_symbol_key(&s);
+                if s.is_empty() || subscribed.contains(&s) || added.contains(&s) {
+                    continue;
+                }
+                if subscribed.len() + added.len() >= MAX_SUBSCRIPTIONS_PER_CLIENT {
+                    break;
+                }
+                added.push(s);
+            }
+            relay.add_symbols(&added).await;
+            subscribed.extend(added);
+        }
+        WsCommand::Unsubscribe { symbols } => {
+            let keys: Vec<String> = symbols
+                .iter()
+                .map(|s| crate::symbols::normalize_symbol_key(s))
+                .collect();
+            let removed: Vec<String> = subscribed
+                .iter()
+                .filter(|s| keys.contains(s))
+                .cloned()
+                .collect();
+            subscribed.retain(|s| !removed.contains(s));
+            relay.remove_symbols(&removed).await;
+        }
+    }
+    json!({
+        "type": "subscribed",
+        "symbols": subscribed,
+        "max_symbols": MAX_SUBSCRIPTIONS_PER_CLIENT,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::{send, state};
+    use super::*;
+    use axum::http::{Method, StatusCode};
+
+    #[tokio::test]
+    async fn progress_is_polled_and_the_socket_needs_an_upgrade() {
+        let state = state().await;
+        state.progress.write().await.total_stocks = 42;
+        let app = || router().with_state(state.clone());
+
+        let (status, body) = send(app(), Method::GET, "/api/progress", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_stocks"], 42);
+
+        let (status, _) = send(app(), Method::GET, "/ws", None).await;
+        assert!(status.is_client_error());
+    }
+
+    #[tokio::test]
+    async fn runs_reject_an_unreadable_since() {
+        let app = router().with_state(state().await);
+        let (status, _) = send(app, Method::GET, "/api/runs?since=yesterday", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    fn progress() -> AnalysisProgress {
+        AnalysisProgress {
+            total_stocks: 100,
+            analyzed: 10,
+            current_symbol: Some("AAPL".to_string()),
+            cycle_start: "2025-06-02T14:00:00Z".parse().unwrap(),
+            errors: 0,
+            last_cycle_started: None,
+            last_cycle_completed: None,
+            last_successful_cycle: None,
+            last_error: None,
+            mode: Default::default(),
+            degraded_reason: None,
+            saved_symbols: vec!["AAPL".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_differ_sends_keyframe_then_changed_fields_only() {
+        let mut differ = ProgressDiffer::default();
+        let mut p = progress();
+
+        let first = differ.next(&p).unwrap();
+        assert_eq!(first["type"], "keyframe");
+        assert_eq!(first["progress"]["total_stocks"], 100);
+        assert_eq!(first["analyzed_symbols"], json!([]));
+
+        assert!(differ.next(&p).is_none());
+
+        p.analyzed = 12;
+        p.current_symbol = Some("MSFT".to_string());
+        p.saved_symbols
+            .extend(["NVDA".to_string(), "MSFT".to_string()]);
+        let delta = differ.next(&p).unwrap();
+        assert_eq!(delta["type"], "delta");
+        assert_eq!(
+            delta["changed"],
+            json!({ "analyzed": 12, "current_symbol": "MSFT" })
+        );
+        assert_eq!(delta["analyzed_symbols"], json!(["NVDA", "MSFT"]));
+
+        // A new cycle restarts the saved-symbol cursor.
+        p.cycle_start = "2025-06-02T15:00:00Z".parse().unwrap();
+        p.saved_symbols = vec!["TSLA".to_string()];
+        let delta = differ.next(&p).unwrap();
+        assert_eq!(delta["analyzed_symbols"], json!(["TSLA"]));
+        assert!(delta["changed"].get("cycle_start").is_some());
+    }
+
+    #[test]
+    fn test_differ_sends_periodic_keyframes() {
+        let mut differ = ProgressDiffer::default();
+        let mut p = progress();
+        differ.next(&p);
+        for tick in 1..KEYFRAME_EVERY_TICKS {
+            p.analyzed += 1;
+            assert_eq!(differ.next(&p).unwrap()["type"], "delta", "tick {}", tick);
+        }
+        let keyframe = differ.next(&p).unwrap();
+        assert_eq!(keyframe["type"], "keyframe");
+        assert_eq!(keyframe["progress"]["analyzed"], p.analyzed);
+    }
+}
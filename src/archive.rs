@@ -0,0 +1,404 @@
+//! Optional archive of raw upstream responses.
+//!
+//! With `RAW_ARCHIVE_DIR` set, Yahoo and NASDAQ response bodies are written
+//! unchanged and gzip-compressed to
+//! `RAW_ARCHIVE_DIR/<YYYY-MM-DD>/<source>/<kind>/<SYMBOL>-<HHMMSSffffff>.json.gz`
+//! (UTC), with the request URL in the gzip header comment. `zcat` gives back
+//! the body the parser saw, so payloads can be reprocessed after a parser
+//! fix, or inspected when a symbol showed odd data on a given day.
+//!
+//! `RAW_ARCHIVE_SAMPLE_RATE` keeps a share of symbols (`1` keeps all).
+//! Symbols are picked per day, so a sampled symbol's responses for that day
+//! are archived together. Days older than `RAW_ARCHIVE_RETENTION_DAYS` are
+//! deleted once a day. Writes run on the blocking pool and failures are only
+//! logged; archiving never fails a fetch. For an off-site copy, sync the
+//! directory to S3 or similar.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::{Compression, GzBuilder};
+use serde::Serialize;
+use tracing::{info, warn};
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+const ENTRY_SUFFIX: &str = ".json.gz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Yahoo,
+    Nasdaq,
+}
+
+impl Source {
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Yahoo => "yahoo",
+            Source::Nasdaq => "nasdaq",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveSettings {
+    pub dir: PathBuf,
+    /// Share of symbols archived each day, 0-1.
+    pub sample_rate: f64,
+    /// Days kept; `0` keeps everything.
+    pub retention_days: u32,
+}
+
+/// Archived responses of one day.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ArchiveDay {
+    pub date: String,
+    pub files: u64,
+    pub size_bytes: u64,
+}
+
+/// One archived response, as read back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivedResponse {
+    pub url: String,
+    pub body: String,
+}
+
+/// Where one archived response lives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    pub source: String,
+    pub kind: String,
+    /// File-name form of the symbol (see [`file_symbol`]).
+    pub symbol: String,
+    pub fetched_at: DateTime<Utc>,
+    pub path: PathBuf,
+}
+
+#[derive(Clone)]
+pub struct RawArchive {
+    settings: Arc<ArchiveSettings>,
+}
+
+impl RawArchive {
+    pub fn new(settings: ArchiveSettings) -> Self {
+        Self {
+            settings: Arc::new(settings),
+        }
+    }
+
+    pub fn settings(&self) -> &ArchiveSettings {
+        &self.settings
+    }
+
+    /// Queue `body` for writing if `symbol` is sampled today. `symbol` is
+    /// `_batch` / `_universe` for multi-symbol responses.
+    pub fn record(&self, source: Source, kind: &str, symbol: &str, url: &str, body: &[u8]) {
+        let now = Utc::now();
+        if !sampled(symbol, now.date_naive(), self.settings.sample_rate) {
+            return;
+        }
+        let path = entry_path(&self.settings.dir, source, kind, symbol, now);
+        let url = url.to_string();
+        let body = body.to_vec();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = write_entry(&path, &url, &body) {
+                warn!("Failed to archive {}: {:#}", path.display(), e);
+            }
+        });
+    }
+
+    /// Archived days, newest first.
+    pub fn days(&self) -> Result<Vec<ArchiveDay>> {
+        let mut days = Vec::new();
+        for date in day_dirs(&self.settings.dir)? {
+            let (files, size_bytes) = dir_usage(&self.settings.dir.join(&date))?;
+            days.push(ArchiveDay {
+                date,
+                files,
+                size_bytes,
+            });
+        }
+        days.sort_by(|a, b| b.date.cmp(&a.date));
+        Ok(days)
+    }
+
+    /// Every archived response of `date`, oldest first.
+    pub fn entries(&self, date: NaiveDate) -> Result<Vec<ArchiveEntry>> {
+        let day = self.settings.dir.join(date.format(DAY_FORMAT).to_string());
+        let mut entries = Vec::new();
+        if !day.exists() {
+            return Ok(entries);
+        }
+        for source in fs::read_dir(&day)? {
+            let source = source?;
+            for kind in fs::read_dir(source.path())? {
+                let kind = kind?;
+                for file in fs::read_dir(kind.path())? {
+                    let path = file?.path();
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    let Some((symbol, time)) = name
+                        .strip_suffix(ENTRY_SUFFIX)
+                        .and_then(|stem| stem.rsplit_once('-'))
+                    else {
+                        continue;
+                    };
+                    let Ok(time) = NaiveTime::parse_from_str(time, "%H%M%S%6f") else {
+                        continue;
+                    };
+                    entries.push(ArchiveEntry {
+                        source: source.file_name().to_string_lossy().into_owned(),
+                        kind: kind.file_name().to_string_lossy().into_owned(),
+                        symbol: symbol.to_string(),
+                        fetched_at: date.and_time(time).and_utc(),
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+        entries.sort_by_key(|entry| entry.fetched_at);
+        Ok(entries)
+    }
+
+    /// `symbol`'s archived responses on `date`, oldest first. Multi-symbol
+    /// responses (`_batch`, `_universe`) are listed under those names.
+    pub fn symbol_entries(&self, date: NaiveDate, symbol: &str) -> Result<Vec<ArchiveEntry>> {
+        let symbol = file_symbol(symbol);
+        let mut entries = self.entries(date)?;
+        entries.retain(|entry| entry.symbol == symbol);
+        Ok(entries)
+    }
+
+    /// Delete days older than the retention window. Returns how many went.
+    pub fn prune(&self, today: NaiveDate) -> Result<usize> {
+        let expired = expired_days(
+            day_dirs(&self.settings.dir)?,
+            today,
+            self.settings.retention_days,
+        );
+        for date in &expired {
+            fs::remove_dir_all(self.settings.dir.join(date))?;
+        }
+        Ok(expired.len())
+    }
+
+    /// Prune once now and then daily.
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(24 * 3600));
+            loop {
+                ticker.tick().await;
+                let archive = self.clone();
+                let pruned =
+                    tokio::task::spawn_blocking(move || archive.prune(Utc::now().date_naive()))
+                        .await;
+                match pruned {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(removed)) => info!("🗄️  Pruned {} days of raw archive", removed),
+                    Ok(Err(e)) => warn!("Raw archive pruning failed: {}", e),
+                    Err(e) => warn!("Raw archive pruning panicked: {}", e),
+                }
+            }
+        });
+    }
+}
+
+/// Read an archived response back.
+pub fn read_entry(path: &Path) -> Result<ArchivedResponse> {
+    let mut decoder = GzDecoder::new(File::open(path)?);
+    let mut body = String::new();
+    decoder
+        .read_to_string(&mut body)
+        .with_context(|| format!("reading {}", path.display()))?;
+    let url = decoder
+        .header()
+        .and_then(|h| h.comment())
+        .map(|c| String::from_utf8_lossy(c).into_owned())
+        .ok_or_else(|| anyhow!("{} has no URL comment", path.display()))?;
+    Ok(ArchivedResponse { url, body })
+}
+
+/// Whether `symbol`'s responses are archived on `date`. Stable within a
+/// day, so a symbol is kept or skipped as a whole.
+fn sampled(symbol: &str, date: NaiveDate, rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    if rate <= 0.0 {
+        return false;
+    }
+    let mut hasher = DefaultHasher::new();
+    (symbol, date).hash(&mut hasher);
+    (hasher.finish() as f64 / u64::MAX as f64) < rate
+}
+
+/// File-name-safe form of a symbol (`BRK/B` → `BRK_B`). Stored symbol
+/// keys are already safe and come through unchanged.
+pub fn file_symbol(symbol: &str) -> String {
+    symbol
+        .chars()
+        .map(|c| match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '.' | '-' | '^' | '=' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+fn entry_path(dir: &Path, source: Source, kind: &str, symbol: &str, at: DateTime<Utc>) -> PathBuf {
+    dir.join(at.format(DAY_FORMAT).to_string())
+        .join(source.name())
+        .join(kind)
+        .join(format!(
+            "{}-{}{}",
+            file_symbol(symbol),
+            at.format("%H%M%S%6f"),
+            ENTRY_SUFFIX
+        ))
+}
+
+fn write_entry(path: &Path, url: &str, body: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = GzBuilder::new()
+        .comment(url.as_bytes())
+        .write(BufWriter::new(File::create(path)?), Compression::default());
+    out.write_all(body)?;
+    out.finish()?.flush()?;
+    Ok(())
+}
+
+/// Names of the `YYYY-MM-DD` directories under `dir`.
+fn day_dirs(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut days = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && NaiveDate::parse_from_str(&name, DAY_FORMAT).is_ok() {
+            days.push(name);
+        }
+    }
+    Ok(days)
+}
+
+/// Files and bytes under `dir`, recursively.
+fn dir_usage(dir: &Path) -> Result<(u64, u64)> {
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            let (f, b) = dir_usage(&entry.path())?;
+            files += f;
+            bytes += b;
+        } else {
+            files += 1;
+            bytes += entry.metadata()?.len();
+        }
+    }
+    Ok((files, bytes))
+}
+
+/// Days more than `retention_days` before `today`.
+fn expired_days(days: Vec<String>, today: NaiveDate, retention_days: u32) -> Vec<String> {
+    if retention_days == 0 {
+        return Vec::new();
+    }
+    let oldest_kept = today - chrono::Duration::days(retention_days as i64 - 1);
+    days.into_iter()
+        .filter(|name| {
+            NaiveDate::parse_from_str(name, DAY_FORMAT).is_ok_and(|date| date < oldest_kept)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "raw-archive-{}-{}-{}",
+            name,
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_entries_round_trip_with_url() {
+        let dir = scratch_dir("roundtrip");
+        let at = Utc.with_ymd_and_hms(2025, 6, 2, 14, 30, 5).unwrap();
+        let path = entry_path(&dir, Source::Yahoo, "chart", "BRK/B", at);
+        assert!(path.ends_with("2025-06-02/yahoo/chart/BRK_B-143005000000.json.gz"));
+
+        let url = "https://query2.finance.yahoo.com/v8/finance/chart/BRK-B?interval=1d";
+        write_entry(&path, url, br#"{"chart":{"result":[]}}"#).unwrap();
+        let entry = read_entry(&path).unwrap();
+        assert_eq!(entry.url, url);
+        assert_eq!(entry.body, r#"{"chart":{"result":[]}}"#);
+
+        // A symbol that shares the prefix isn't listed.
+        let other = entry_path(&dir, Source::Nasdaq, "news", "BRK/B/X", at);
+        write_entry(&other, "https://api.nasdaq.com/", b"{}").unwrap();
+
+        let archive = RawArchive::new(ArchiveSettings {
+            dir: dir.clone(),
+            sample_rate: 1.0,
+            retention_days: 7,
+        });
+        let days = archive.days().unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!((days[0].date.as_str(), days[0].files), ("2025-06-02", 2));
+        let entries = archive.symbol_entries(at.date_naive(), "BRK/B").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            (entries[0].source.as_str(), entries[0].kind.as_str()),
+            ("yahoo", "chart")
+        );
+        assert_eq!(entries[0].fetched_at, at);
+        assert_eq!(entries[0].path, path);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_sampling_is_per_symbol_and_day() {
+        let day = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        assert!(sampled("AAPL", day, 1.0));
+        assert!(!sampled("AAPL", day, 0.0));
+        let kept = (0..1000)
+            .filter(|i| sampled(&format!("S{}", i), day, 0.25))
+            .count();
+        assert!((150..350).contains(&kept), "kept {}", kept);
+        assert_eq!(sampled("AAPL", day, 0.5), sampled("AAPL", day, 0.5));
+    }
+
+    #[test]
+    fn test_retention_drops_old_days_only() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+        let days = vec![
+            "2025-06-10".to_string(),
+            "2025-06-04".to_string(),
+            "2025-06-03".to_string(),
+            "2025-05-01".to_string(),
+        ];
+        assert_eq!(
+            expired_days(days.clone(), today, 7),
+            vec!["2025-06-03".to_string(), "2025-05-01".to_string()]
+        );
+        assert!(expired_days(days, today, 0).is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! What kind of security a symbol is.
+//!
+//! The screener and detail lookups hand us ETFs, warrants, rights, units and
+//! SPAC shells alongside common stock. Their RSI and daily moves are not
+//! comparable with operating companies', so every analysis is tagged with an
+//! [`AssetType`] and only equities feed the pre-computed screens, the market
+//! summary leaders and stock filters unless `asset_type` asks for others.
+//!
+//! [`classify`] checks, in order: NASDAQ's asset class (`ETF`), the symbol
+//! suffix (`FOO.WS`, `FOO-U`, `FOO/R`, or a five-letter NASDAQ symbol ending
+//! in `W`, `U` or `R`), then the company name (`... ETF`, `... Warrants`,
+//! `... Acquisition Corp`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetType {
+    #[default]
+    Equity,
+    Etf,
+    Warrant,
+    Right,
+    Unit,
+    /// Blank-check company that hasn't completed its acquisition.
+    Spac,
+}
+
+impl AssetType {
+    pub const ALL: [AssetType; 6] = [
+        AssetType::Equity,
+        AssetType::Etf,
+        AssetType::Warrant,
+        AssetType::Right,
+        AssetType::Unit,
+        AssetType::Spac,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AssetType::Equity => "equity",
+            AssetType::Etf => "etf",
+            AssetType::Warrant => "warrant",
+            AssetType::Right => "right",
+            AssetType::Unit => "unit",
+            AssetType::Spac => "spac",
+        }
+    }
+
+    pub fn is_equity(&self) -> bool {
+        *self == AssetType::Equity
+    }
+}
+
+impl FromStr for AssetType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized = s.trim().to_ascii_lowercase();
+        AssetType::ALL
+            .into_iter()
+            .find(|t| t.name() == normalized)
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown asset_type '{}' (use equity, etf, warrant, right, unit, spac or all)",
+                    s.trim()
+                )
+            })
+    }
+}
+
+impl fmt::Display for AssetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse an `asset_type` filter: comma-separated types, or `all` for no
+/// restriction (`None`). Unset or empty means equities only.
+pub fn parse_filter(value: Option<&str>) -> Result<Option<Vec<AssetType>>> {
+    let value = value.map(str::trim).unwrap_or_default();
+    if value.is_empty() {
+        return Ok(Some(vec![AssetType::Equity]));
+    }
+    if value.eq_ignore_ascii_case("all") {
+        return Ok(None);
+    }
+    value
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
+}
+
+/// Type implied by a separator suffix (`FOO.WS`, `FOO-U`, `FOO/R`).
+/// Single-letter share classes such as `BRK-B` are common stock.
+pub fn suffix_type(symbol: &str) -> Option<AssetType> {
+    let upper = symbol.to_ascii_uppercase();
+    let idx = upper.rfind(['.', '-', '/'])?;
+    match &upper[idx + 1..] {
+        "W" | "WS" | "WSA" | "WSB" => Some(AssetType::Warrant),
+        "U" | "UN" => Some(AssetType::Unit),
+        "R" | "RT" => Some(AssetType::Right),
+        _ => None,
+    }
+}
+
+/// Type implied by NASDAQ's fifth-letter codes (`ABCDW`, `ABCDU`, `ABCDR`).
+fn fifth_letter_type(symbol: &str) -> Option<AssetType> {
+    let upper = symbol.trim().to_ascii_uppercase();
+    if upper.len() != 5 || !upper.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    match upper.chars().last()? {
+        'W' => Some(AssetType::Warrant),
+        'U' => Some(AssetType::Unit),
+        'R' => Some(AssetType::Right),
+        _ => None,
+    }
+}
+
+fn name_type(name: &str) -> Option<AssetType> {
+    let name = format!(" {} ", name.to_ascii_lowercase().replace(['.', ','], " "));
+    let has = |word: &str| name.contains(&format!(" {} ", word));
+    if has("etf") || has("etn") || name.contains(" exchange traded fund") {
+        Some(AssetType::Etf)
+    } else if has("warrant") || has("warrants") {
+        Some(AssetType::Warrant)
+    } else if has("right") || has("rights") {
+        Some(AssetType::Right)
+    } else if has("unit") || has("units") {
+        Some(AssetType::Unit)
+    } else if name.contains(" acquisition corp")
+        || name.contains(" acquisition co ")
+        || name.contains(" acquisition company")
+        || name.contains(" blank check")
+    {
+        Some(AssetType::Spac)
+    } else {
+        None
+    }
+}
+
+/// Classify `symbol` from its NASDAQ asset class (`STOCKS`, `ETF`, ...)
+/// and company name, where known.
+pub fn classify(symbol: &str, name: Option<&str>, asset_class: Option<&str>) -> AssetType {
+    if asset_class.is_some_and(|class| class.trim().eq_ignore_ascii_case("etf")) {
+        return AssetType::Etf;
+    }
+    suffix_type(symbol)
+        .or_else(|| fifth_letter_type(symbol))
+        .or_else(|| name.and_then(name_type))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_by_symbol_suffix() {
+        assert_eq!(classify("FOO.WS", None, None), AssetType::Warrant);
+        assert_eq!(classify("FOO-U", None, None), AssetType::Unit);
+        assert_eq!(classify("FOO/R", None, None), AssetType::Right);
+        assert_eq!(classify("ABCDW", None, None), AssetType::Warrant);
+        assert_eq!(classify("ABCDU", None, None), AssetType::Unit);
+        assert_eq!(classify("BRK-B", None, None), AssetType::Equity);
+        assert_eq!(classify("GOOGL", None, None), AssetType::Equity);
+        assert_eq!(classify("AAPL", None, None), AssetType::Equity);
+    }
+
+    #[test]
+    fn test_classify_by_asset_class_and_name() {
+        assert_eq!(
+            classify("QQQ", Some("Invesco QQQ Trust, Series 1"), Some("ETF")),
+            AssetType::Etf
+        );
+        assert_eq!(
+            classify("SPY", Some("SPDR S&P 500 ETF Trust"), Some("STOCKS")),
+            AssetType::Etf
+        );
+        assert_eq!(
+            classify(
+                "CCIX",
+                Some("Churchill Capital Corp IX Acquisition Corp."),
+                None
+            ),
+            AssetType::Spac
+        );
+        assert_eq!(
+            classify(
+                "XYZ",
+                Some("XYZ Holdings Class A Ordinary Shares"),
+                Some("STOCKS")
+            ),
+            AssetType::Equity
+        );
+        // "Rightside" and "United" are not rights or units.
+        assert_eq!(
+            classify("RS", Some("Rightside United Group"), None),
+            AssetType::Equity
+        );
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(parse_filter(None).unwrap(), Some(vec![AssetType::Equity]));
+        assert_eq!(parse_filter(Some(" all ")).unwrap(), None);
+        assert_eq!(
+            parse_filter(Some("ETF, spac")).unwrap(),
+            Some(vec![AssetType::Etf, AssetType::Spac])
+        );
+        assert!(parse_filter(Some("bond")).is_err());
+    }
+}
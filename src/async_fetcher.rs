@@ -4,7 +4,8 @@
 //! with semaphore-based rate limiting and progress tracking.
 
 use crate::models::HistoricalPrice;
-use crate::yahoo::YahooFinanceClient;
+use crate::yahoo::{PriceAdjustment, YahooFinanceClient};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -72,6 +73,21 @@ pub struct FetcherConfig {
     pub delay_between_requests_ms: u64,
     /// Number of days of historical data to fetch
     pub days: i64,
+    /// Per-symbol overrides of `days`, e.g. only the days since a symbol's
+    /// stored bars (see `incremental.rs`)
+    pub days_by_symbol: Arc<HashMap<String, i64>>,
+    /// Raw or split/dividend-adjusted bars
+    pub adjustment: PriceAdjustment,
+}
+
+impl FetcherConfig {
+    /// Days of history to fetch for `symbol`
+    pub fn days_for(&self, symbol: &str) -> i64 {
+        self.days_by_symbol
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.days)
+    }
 }
 
 impl Default for FetcherConfig {
@@ -80,6 +96,8 @@ impl Default for FetcherConfig {
             concurrency: 5,
             delay_between_requests_ms: 500,
             days: 30,
+            days_by_symbol: Arc::default(),
+            adjustment: PriceAdjustment::default(),
         }
     }
 }
@@ -123,79 +141,42 @@ impl AsyncStockFetcher {
     pub fn fetch_batch_streaming(
         &self,
         symbols: Vec<String>,
+    ) -> (mpsc::Receiver<FetchResult>, tokio::task::JoinHandle<()>) {
+        self.fetch_lanes_streaming(vec![symbols], self.config.concurrency)
+    }
+
+    /// Like [`Self::fetch_batch_streaming`], but every lane is fetched at
+    /// the same time with its own `lane_concurrency` permits, so a long
+    /// lane can't hold up a short one. Results share one channel; aborting
+    /// the handle stops every lane.
+    pub fn fetch_lanes_streaming(
+        &self,
+        lanes: Vec<Vec<String>>,
+        lane_concurrency: usize,
     ) -> (mpsc::Receiver<FetchResult>, tokio::task::JoinHandle<()>) {
         let (tx, rx) = mpsc::channel(100); // Buffer up to 100 results
-        let client = Arc::clone(&self.client);
-        let config = self.config.clone();
-        let total = symbols.len();
+        let total = lanes.iter().map(Vec::len).sum();
+        let completed = Arc::new(AtomicUsize::new(0));
+        let lanes: Vec<_> = lanes
+            .into_iter()
+            .map(|symbols| {
+                stream_lane(
+                    Arc::clone(&self.client),
+                    FetcherConfig {
+                        concurrency: lane_concurrency.max(1),
+                        ..self.config.clone()
+                    },
+                    symbols,
+                    tx.clone(),
+                    Arc::clone(&completed),
+                    total,
+                )
+            })
+            .collect();
+        drop(tx);
 
         let handle = tokio::spawn(async move {
-            let semaphore = Arc::new(Semaphore::new(config.concurrency));
-            let completed = Arc::new(AtomicUsize::new(0));
-            let mut handles = Vec::new();
-
-            for (idx, symbol) in symbols.into_iter().enumerate() {
-                let permit = semaphore.clone().acquire_owned().await.unwrap();
-                let client = Arc::clone(&client);
-                let tx = tx.clone();
-                let completed = Arc::clone(&completed);
-                let days = config.days;
-                let delay_ms = config.delay_between_requests_ms;
-
-                let handle = tokio::spawn(async move {
-                    // Stagger requests slightly based on index
-                    if idx > 0 && delay_ms > 0 {
-                        sleep(Duration::from_millis(delay_ms * (idx as u64 % 3))).await;
-                    }
-
-                    let result = client.get_historical_prices(&symbol, days).await;
-
-                    // Release permit immediately after request completes
-                    drop(permit);
-
-                    let fetch_result = match result {
-                        Ok(prices) => {
-                            debug!("✅ Fetched {} prices for {}", prices.len(), symbol);
-                            FetchResult::Success { symbol, prices }
-                        }
-                        Err(e) => {
-                            let error_msg = e.to_string();
-                            let is_rate_limited =
-                                error_msg.contains("429") || error_msg.contains("Rate limited");
-                            if is_rate_limited {
-                                warn!("⚠️  Rate limited: {}", symbol);
-                            } else {
-                                warn!("❌ Failed {}: {}", symbol, error_msg);
-                            }
-                            FetchResult::Failed {
-                                symbol,
-                                error: error_msg,
-                                is_rate_limited,
-                            }
-                        }
-                    };
-
-                    // Send result through channel (ignore send errors if receiver dropped)
-                    let _ = tx.send(fetch_result).await;
-
-                    let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
-                    if done % 50 == 0 || done == total {
-                        info!("Fetch progress: {}/{} completed", done, total);
-                    }
-                });
-
-                handles.push(handle);
-
-                // Small delay between spawning tasks
-                if delay_ms > 0 {
-                    sleep(Duration::from_millis(delay_ms)).await;
-                }
-            }
-
-            // Wait for all tasks to complete
-            for handle in handles {
-                let _ = handle.await;
-            }
+            futures::future::join_all(lanes).await;
         });
 
         (rx, handle)
@@ -220,7 +201,8 @@ impl AsyncStockFetcher {
             let failed = Arc::clone(&failed);
             let rate_limit_errors = Arc::clone(&rate_limit_errors);
             let completed = Arc::clone(&completed);
-            let days = self.config.days;
+            let days = self.config.days_for(&symbol);
+            let adjustment = self.config.adjustment;
             let delay_ms = self.config.delay_between_requests_ms;
 
             let handle = tokio::spawn(async move {
@@ -229,7 +211,9 @@ impl AsyncStockFetcher {
                     sleep(Duration::from_millis(delay_ms * (idx as u64 % 3))).await;
                 }
 
-                let result = client.get_historical_prices(&symbol, days).await;
+                let result = client
+                    .get_historical_prices(&symbol, days, adjustment)
+                    .await;
 
                 // Release permit immediately after request completes
                 drop(permit);
@@ -308,6 +292,86 @@ impl AsyncStockFetcher {
     }
 }
 
+/// Fetch one lane's symbols under its own semaphore, sending each result as
+/// it completes.
+async fn stream_lane(
+    client: Arc<YahooFinanceClient>,
+    config: FetcherConfig,
+    symbols: Vec<String>,
+    tx: mpsc::Sender<FetchResult>,
+    completed: Arc<AtomicUsize>,
+    total: usize,
+) {
+    let semaphore = Arc::new(Semaphore::new(config.concurrency));
+    let mut handles = Vec::new();
+
+    for (idx, symbol) in symbols.into_iter().enumerate() {
+        let permit = semaphore.clone().acquire_owned().await.unwrap();
+        let client = Arc::clone(&client);
+        let tx = tx.clone();
+        let completed = Arc::clone(&completed);
+        let days = config.days_for(&symbol);
+        let adjustment = config.adjustment;
+        let delay_ms = config.delay_between_requests_ms;
+
+        let handle = tokio::spawn(async move {
+            // Stagger requests slightly based on index
+            if idx > 0 && delay_ms > 0 {
+                sleep(Duration::from_millis(delay_ms * (idx as u64 % 3))).await;
+            }
+
+            let result = client
+                .get_historical_prices(&symbol, days, adjustment)
+                .await;
+
+            // Release permit immediately after request completes
+            drop(permit);
+
+            let fetch_result = match result {
+                Ok(prices) => {
+                    debug!("✅ Fetched {} prices for {}", prices.len(), symbol);
+                    FetchResult::Success { symbol, prices }
+                }
+                Err(e) => {
+                    let error_msg = e.to_string();
+                    let is_rate_limited =
+                        error_msg.contains("429") || error_msg.contains("Rate limited");
+                    if is_rate_limited {
+                        warn!("⚠️  Rate limited: {}", symbol);
+                    } else {
+                        warn!("❌ Failed {}: {}", symbol, error_msg);
+                    }
+                    FetchResult::Failed {
+                        symbol,
+                        error: error_msg,
+                        is_rate_limited,
+                    }
+                }
+            };
+
+            // Send result through channel (ignore send errors if receiver dropped)
+            let _ = tx.send(fetch_result).await;
+
+            let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+            if done % 50 == 0 || done == total {
+                info!("Fetch progress: {}/{} completed", done, total);
+            }
+        });
+
+        handles.push(handle);
+
+        // Small delay between spawning tasks
+        if delay_ms > 0 {
+            sleep(Duration::from_millis(delay_ms)).await;
+        }
+    }
+
+    // Wait for all tasks to complete
+    for handle in handles {
+        let _ = handle.await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
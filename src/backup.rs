@@ -0,0 +1,301 @@
+//! Scheduled database backups and restore.
+//!
+//! A backup is a directory `BACKUP_DIR/<YYYYMMDDTHHMMSSZ>/` holding one
+//! `<collection>.ndjson.gz` per exported collection, one canonical Extended
+//! JSON document per line so dates and ObjectIds round-trip, plus a
+//! `manifest.json` with the document counts. It is written under a
+//! `.partial` name and renamed when complete, so an interrupted run is never
+//! listed or restored. Backups run every `BACKUP_INTERVAL_HOURS` and on
+//! `POST /api/admin/backups`; the oldest beyond `BACKUP_RETENTION` are
+//! deleted. `auto_analyser_2 restore <backup>` loads one back.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::stream::StreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Collection;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::db::MongoDB;
+
+const NAME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const MANIFEST: &str = "manifest.json";
+const DUMP_SUFFIX: &str = ".ndjson.gz";
+/// Documents per `insert_many` on restore.
+const RESTORE_BATCH: usize = 1000;
+
+/// Only one backup writes at a time, whether scheduled or on demand.
+static BACKUP_RUNNING: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+#[derive(Debug, Clone)]
+pub struct BackupSettings {
+    pub dir: PathBuf,
+    /// Backups kept after each run; `0` keeps all of them.
+    pub retention: usize,
+    /// `Duration::ZERO` only backs up on demand.
+    pub interval: Duration,
+    /// Collections to export; empty exports every collection.
+    pub collections: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CollectionDump {
+    pub collection: String,
+    pub documents: u64,
+    pub size_bytes: u64,
+}
+
+/// `manifest.json` of a completed backup.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupManifest {
+    pub name: String,
+    pub database: String,
+    pub created_at: DateTime<Utc>,
+    pub collections: Vec<CollectionDump>,
+}
+
+impl BackupManifest {
+    pub fn size_bytes(&self) -> u64 {
+        self.collections.iter().map(|c| c.size_bytes).sum()
+    }
+}
+
+fn backup_name(at: DateTime<Utc>) -> String {
+    at.format(NAME_FORMAT).to_string()
+}
+
+fn is_backup_name(name: &str) -> bool {
+    NaiveDateTime::parse_from_str(name, NAME_FORMAT).is_ok()
+}
+
+/// Backups to delete so only the newest `keep` remain. Names sort
+/// chronologically.
+fn expired(mut names: Vec<String>, keep: usize) -> Vec<String> {
+    if keep == 0 {
+        return Vec::new();
+    }
+    names.retain(|name| is_backup_name(name));
+    names.sort_unstable_by(|a, b| b.cmp(a));
+    names.into_iter().skip(keep).collect()
+}
+
+async fn export_collection(db: &MongoDB, collection: &str, path: &Path) -> Result<u64> {
+    let source: Collection<Document> = db.database().collection(collection);
+    let mut out = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::default());
+    let mut documents = 0;
+    let mut cursor = source.find(doc! {}).await?;
+    while let Some(row) = cursor.next().await {
+        let json = Bson::Document(row?).into_canonical_extjson();
+        serde_json::to_writer(&mut out, &json)?;
+        out.write_all(b"\n")?;
+        documents += 1;
+    }
+    out.finish()?.flush()?;
+    Ok(documents)
+}
+
+/// Export the configured collections into a new backup.
+pub async fn run_backup(db: &MongoDB, settings: &BackupSettings) -> Result<BackupManifest> {
+    let _running = BACKUP_RUNNING
+        .try_lock()
+        .map_err(|_| anyhow!("a backup is already running"))?;
+
+    let created_at = Utc::now();
+    let name = backup_name(created_at);
+    let partial = settings.dir.join(format!("{}.partial", name));
+    fs::create_dir_all(&partial).with_context(|| format!("creating {}", partial.display()))?;
+
+    let mut collections = if settings.collections.is_empty() {
+        db.database().list_collection_names().await?
+    } else {
+        settings.collections.clone()
+    };
+    collections.retain(|c| !c.starts_with("system."));
+    collections.sort();
+
+    let mut manifest = BackupManifest {
+        name: name.clone(),
+        database: db.database().name().to_string(),
+        created_at,
+        collections: Vec::new(),
+    };
+    for collection in collections {
+        let path = partial.join(format!("{}{}", collection, DUMP_SUFFIX));
+        let documents = export_collection(db, &collection, &path)
+            .await
+            .with_context(|| format!("exporting {}", collection))?;
+        manifest.collections.push(CollectionDump {
+            collection,
+            documents,
+            size_bytes: fs::metadata(&path)?.len(),
+        });
+    }
+    fs::write(
+        partial.join(MANIFEST),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    fs::rename(&partial, settings.dir.join(&name))?;
+    Ok(manifest)
+}
+
+/// Completed backups, newest first.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupManifest>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Ok(text) = fs::read_to_string(path.join(MANIFEST)) else {
+            continue;
+        };
+        match serde_json::from_str::<BackupManifest>(&text) {
+            Ok(manifest) => backups.push(manifest),
+            Err(e) => warn!("Unreadable backup manifest in {}: {}", path.display(), e),
+        }
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+/// Delete backups beyond the retention count. Returns how many were removed.
+pub fn prune(settings: &BackupSettings) -> Result<usize> {
+    let names = list_backups(&settings.dir)?
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    let expired = expired(names, settings.retention);
+    for name in &expired {
+        fs::remove_dir_all(settings.dir.join(name))?;
+    }
+    Ok(expired.len())
+}
+
+/// A backup directory, given as a path or as a name under `dir`.
+pub fn resolve_backup(dir: Option<&Path>, backup: &str) -> Result<PathBuf> {
+    let candidates = std::iter::once(PathBuf::from(backup)).chain(dir.map(|d| d.join(backup)));
+    for path in candidates {
+        if path.join(MANIFEST).is_file() {
+            return Ok(path);
+        }
+    }
+    Err(anyhow!("no backup manifest found for '{}'", backup))
+}
+
+/// Replace each backed-up collection's documents with the backup's.
+/// Indexes are left in place. Returns the manifest that was restored.
+pub async fn restore(db: &MongoDB, path: &Path) -> Result<BackupManifest> {
+    let manifest: BackupManifest = serde_json::from_str(&fs::read_to_string(path.join(MANIFEST))?)?;
+    for dump in &manifest.collections {
+        let target: Collection<Document> = db.database().collection(&dump.collection);
+        target.delete_many(doc! {}).await?;
+
+        let file = File::open(path.join(format!("{}{}", dump.collection, DUMP_SUFFIX)))?;
+        let mut batch = Vec::with_capacity(RESTORE_BATCH);
+        for line in BufReader::new(GzDecoder::new(file)).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let json: serde_json::Value = serde_json::from_str(&line)?;
+            match Bson::try_from(json)? {
+                Bson::Document(doc) => batch.push(doc),
+                other => return Err(anyhow!("{}: not a document: {}", dump.collection, other)),
+            }
+            if batch.len() == RESTORE_BATCH {
+                target.insert_many(std::mem::take(&mut batch)).await?;
+            }
+        }
+        if !batch.is_empty() {
+            target.insert_many(batch).await?;
+        }
+        info!(
+            "♻️  Restored {} documents into {}",
+            dump.documents, dump.collection
+        );
+    }
+    Ok(manifest)
+}
+
+/// Back up and prune on the configured interval for the life of the
+/// process. The first backup runs one interval after startup.
+pub fn spawn(db: MongoDB, settings: BackupSettings) {
+    if settings.interval.is_zero() {
+        return;
+    }
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + settings.interval;
+        let mut ticker = tokio::time::interval_at(start, settings.interval);
+        loop {
+            ticker.tick().await;
+            match run_backup(&db, &settings).await {
+                Ok(manifest) => info!(
+                    "💾 Backup {}: {} collections, {} bytes",
+                    manifest.name,
+                    manifest.collections.len(),
+                    manifest.size_bytes()
+                ),
+                Err(e) => {
+                    warn!("Backup failed: {}", e);
+                    continue;
+                }
+            }
+            match prune(&settings) {
+                Ok(0) => {}
+                Ok(removed) => info!("💾 Pruned {} old backups", removed),
+                Err(e) => warn!("Backup pruning failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_retention_keeps_newest() {
+        let names = [
+            "20250601T000000Z",
+            "20250603T000000Z",
+            "not-a-backup",
+            "20250602T000000Z",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(expired(names.clone(), 2), vec!["20250601T000000Z"]);
+        assert!(expired(names.clone(), 0).is_empty());
+        assert!(expired(names, 5).is_empty());
+
+        let at = Utc.with_ymd_and_hms(2025, 6, 2, 14, 30, 5).unwrap();
+        assert_eq!(backup_name(at), "20250602T143005Z");
+        assert!(is_backup_name(&backup_name(at)));
+    }
+
+    #[test]
+    fn test_extended_json_round_trip() {
+        let original = doc! {
+            "_id": mongodb::bson::oid::ObjectId::new(),
+            "symbol": "AAPL",
+            "price": 190.5,
+            "volume": 1_000_000_i64,
+            "analyzed_at": mongodb::bson::DateTime::from_millis(1_748_874_600_000),
+        };
+        let line =
+            serde_json::to_string(&Bson::Document(original.clone()).into_canonical_extjson())
+                .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(Bson::try_from(json).unwrap(), Bson::Document(original));
+    }
+}
@@ -113,6 +113,7 @@ async fn run_test(concurrency: usize, delay_ms: u64, num_symbols: usize) -> Test
         concurrency,
         delay_between_requests_ms: delay_ms,
         days: 7, // Short range for faster tests
+        ..Default::default()
     };
 
     let fetcher = AsyncStockFetcher::new(config);
@@ -1,35 +1,166 @@
-use crate::models::{CompanyProfile, EarningsData, InsiderTrade, NasdaqNewsItem, StockAnalysis};
-use moka::future::Cache;
-use std::sync::Arc;
-use std::time::Duration;
+use crate::models::{
+    CompanyProfile, EarningsData, InsiderTrade, LiveQuote, NasdaqNewsItem, StockAnalysis,
+};
+use crate::response_cache::{ResponseCache, RouteTtls};
+use crate::storage::Storage;
+use moka::{future::Cache, Expiry};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
+/// Default freshness window for batch quotes.
+const DEFAULT_QUOTE_TTL: Duration = Duration::from_secs(15);
+
+/// Per-entry expiry for the stock cache: the default TTL unless the symbol
+/// has a TTL override registered through [`CacheLayer::pin_stock`].
+struct StockExpiry {
+    default_ttl: Duration,
+    overrides: Arc<RwLock<HashMap<String, Duration>>>,
+}
+
+impl StockExpiry {
+    fn ttl_for(&self, symbol: &str) -> Duration {
+        self.overrides
+            .read()
+            .ok()
+            .and_then(|o| o.get(symbol).copied())
+            .unwrap_or(self.default_ttl)
+    }
+}
+
+impl Expiry<String, Arc<StockAnalysis>> for StockExpiry {
+    fn expire_after_create(
+        &self,
+        key: &String,
+        _value: &Arc<StockAnalysis>,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(self.ttl_for(key))
+    }
+
+    // Re-inserting a symbol restarts its TTL, matching `time_to_live`.
+    fn expire_after_update(
+        &self,
+        key: &String,
+        _value: &Arc<StockAnalysis>,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(self.ttl_for(key))
+    }
+}
+
+/// Approximate in-memory weight of a cached value: its serialized JSON size.
+/// `StockAnalysis` size is dominated by the optional news array, so counting
+/// entries alone says little about memory use.
+fn serialized_weight<T: Serialize>(value: &T) -> u32 {
+    serde_json::to_vec(value)
+        .map(|bytes| bytes.len().clamp(1, u32::MAX as usize) as u32)
+        .unwrap_or(1)
+}
+
+/// Share of the memory budget given to each weighed cache. The remaining
+/// caches (earnings, profiles, insiders, quotes) are small and entry-bounded.
+const STOCK_CACHE_BUDGET_SHARE: f64 = 0.5;
+const LIST_CACHE_BUDGET_SHARE: f64 = 0.3;
+const NEWS_CACHE_BUDGET_SHARE: f64 = 0.1;
+const RESPONSE_CACHE_BUDGET_SHARE: f64 = 0.1;
+
+/// Where a stock read was served from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StockSource {
+    Cache,
+    Database,
+}
+
+#[derive(Default)]
+struct StockReadMetrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    db_reads: AtomicU64,
+    db_errors: AtomicU64,
+}
+
+/// Snapshot of stock-cache counters for `/api/cache/stats`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub stock_hits: u64,
+    pub stock_misses: u64,
+    pub db_reads: u64,
+    pub db_errors: u64,
+    pub hit_rate: f64,
+    pub stock_entries: u64,
+    pub stock_weighted_bytes: u64,
+    pub pinned_entries: usize,
+    pub read_through: bool,
+    /// Response cache (see `response_cache.rs`).
+    pub response_hits: u64,
+    pub response_misses: u64,
+    pub response_bypasses: u64,
+    pub response_entries: u64,
+    pub response_weighted_bytes: u64,
+}
+
+/// Analyses are held behind `Arc` so cache reads and the handlers that
+/// serialize them share one copy instead of cloning the news-heavy document.
 #[derive(Clone)]
 pub struct CacheLayer {
-    stock_cache: Arc<Cache<String, StockAnalysis>>,
-    list_cache: Arc<Cache<String, Vec<StockAnalysis>>>,
+    stock_cache: Arc<Cache<String, Arc<StockAnalysis>>>,
+    /// TTL overrides for pinned symbols, read by `StockExpiry`.
+    stock_ttl_overrides: Arc<RwLock<HashMap<String, Duration>>>,
+    /// Symbols pinned without a TTL live here instead of in moka, so neither
+    /// expiry nor capacity pressure can evict them.
+    pinned_stocks: Arc<RwLock<HashMap<String, Option<Arc<StockAnalysis>>>>>,
+    /// Repository consulted on a stock-cache miss; see [`Self::with_read_through`].
+    read_through: Option<Storage>,
+    stock_metrics: Arc<StockReadMetrics>,
+    list_cache: Arc<Cache<String, Arc<Vec<StockAnalysis>>>>,
     news_cache: Arc<Cache<String, Vec<NasdaqNewsItem>>>,
     earnings_cache: Arc<Cache<String, EarningsData>>,
     company_profile_cache: Arc<Cache<String, CompanyProfile>>,
     insider_cache: Arc<Cache<String, Vec<InsiderTrade>>>,
-    generic_cache: Arc<Cache<String, String>>,
+    /// Whole responses of expensive read endpoints, served by the
+    /// `response_cache::serve_cached` middleware.
+    response_cache: ResponseCache,
+    /// Near-current prices from Yahoo's batch quote endpoint, for
+    /// `/api/quotes`. Short TTL; see [`Self::with_quote_ttl`].
+    quote_cache: Arc<Cache<String, LiveQuote>>,
+    /// Renamed ticker → current ticker (see `renames.rs`). Consulted by
+    /// [`Self::resolve_symbol`] and every symbol lookup.
+    symbol_aliases: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl CacheLayer {
-    pub fn new(ttl_secs: u64, news_ttl_secs: u64) -> Self {
+    /// `memory_budget_mb` bounds the stock, list, news and response caches by
+    /// serialized size; entries are evicted by weight once it is reached.
+    pub fn new(ttl_secs: u64, news_ttl_secs: u64, memory_budget_mb: u64) -> Self {
+        let budget_bytes = (memory_budget_mb * 1024 * 1024) as f64;
+
+        let stock_ttl_overrides = Arc::new(RwLock::new(HashMap::new()));
         let stock_cache = Cache::builder()
-            .time_to_live(Duration::from_secs(ttl_secs))
-            .max_capacity(10_000)
+            .expire_after(StockExpiry {
+                default_ttl: Duration::from_secs(ttl_secs),
+                overrides: stock_ttl_overrides.clone(),
+            })
+            .weigher(|_key: &String, value: &Arc<StockAnalysis>| serialized_weight(value.as_ref()))
+            .max_capacity((budget_bytes * STOCK_CACHE_BUDGET_SHARE) as u64)
             .build();
 
         let list_cache = Cache::builder()
             .time_to_live(Duration::from_secs(ttl_secs / 2))
-            .max_capacity(100)
+            .weigher(|_key: &String, value: &Arc<Vec<StockAnalysis>>| {
+                serialized_weight(value.as_ref())
+            })
+            .max_capacity((budget_bytes * LIST_CACHE_BUDGET_SHARE) as u64)
             .build();
 
         // News cache with separate TTL (default 15 minutes)
         let news_cache = Cache::builder()
             .time_to_live(Duration::from_secs(news_ttl_secs))
-            .max_capacity(1_000)
+            .weigher(|_key: &String, value: &Vec<NasdaqNewsItem>| serialized_weight(value))
+            .max_capacity((budget_bytes * NEWS_CACHE_BUDGET_SHARE) as u64)
             .build();
 
         // Earnings cache with long TTL (1 day)
@@ -50,45 +181,243 @@ impl CacheLayer {
             .max_capacity(5_000)
             .build();
 
-        // Generic string cache for computed results (5 minutes)
-        let generic_cache = Cache::builder()
-            .time_to_live(Duration::from_secs(300))
-            .max_capacity(100)
-            .build();
+        // No routes until `with_response_routes`
+        let response_cache = ResponseCache::new(
+            RouteTtls::default(),
+            (budget_bytes * RESPONSE_CACHE_BUDGET_SHARE) as u64,
+        );
 
         CacheLayer {
             stock_cache: Arc::new(stock_cache),
+            stock_ttl_overrides,
+            pinned_stocks: Arc::new(RwLock::new(HashMap::new())),
+            read_through: None,
+            stock_metrics: Arc::new(StockReadMetrics::default()),
             list_cache: Arc::new(list_cache),
             news_cache: Arc::new(news_cache),
             earnings_cache: Arc::new(earnings_cache),
             company_profile_cache: Arc::new(company_profile_cache),
             insider_cache: Arc::new(insider_cache),
-            generic_cache: Arc::new(generic_cache),
+            response_cache,
+            quote_cache: Arc::new(Self::build_quote_cache(DEFAULT_QUOTE_TTL)),
+            symbol_aliases: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
-    pub async fn get_stock(&self, symbol: &str) -> Option<StockAnalysis> {
-        self.stock_cache.get(symbol).await
+    /// Override how long batch quotes stay fresh (default 15s).
+    pub fn with_quote_ttl(mut self, ttl: Duration) -> Self {
+        self.quote_cache = Arc::new(Self::build_quote_cache(ttl));
+        self
+    }
+
+    fn build_quote_cache(ttl: Duration) -> Cache<String, LiveQuote> {
+        Cache::builder()
+            .time_to_live(ttl)
+            .max_capacity(20_000)
+            .build()
     }
 
-    pub async fn set_stock(&self, symbol: String, analysis: StockAnalysis) {
+    /// Cache responses of the given routes (`RESPONSE_CACHE_TTLS`).
+    pub fn with_response_routes(mut self, routes: RouteTtls) -> Self {
+        self.response_cache = self.response_cache.with_routes(routes);
+        self
+    }
+
+    pub fn responses(&self) -> &ResponseCache {
+        &self.response_cache
+    }
+
+    /// Read stock misses through to storage, caching whatever comes back.
+    pub fn with_read_through(mut self, storage: Storage) -> Self {
+        self.read_through = Some(storage);
+        self
+    }
+
+    /// Cache-then-database lookup for a single symbol. Database errors are
+    /// surfaced so callers can tell "not analyzed yet" from "DB is down".
+    pub async fn lookup_stock(
+        &self,
+        symbol: &str,
+    ) -> anyhow::Result<Option<(Arc<StockAnalysis>, StockSource)>> {
+        let key = self.resolve_symbol(symbol);
+        let cached = match self.pinned_stock(&key) {
+            Some(pinned) => pinned,
+            None => self.stock_cache.get(&key).await,
+        };
+        if let Some(analysis) = cached {
+            self.stock_metrics.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some((analysis, StockSource::Cache)));
+        }
+        self.stock_metrics.misses.fetch_add(1, Ordering::Relaxed);
+
+        let Some(storage) = &self.read_through else {
+            return Ok(None);
+        };
+        match storage.get_analysis_by_symbol(&key).await {
+            Ok(found) => {
+                self.stock_metrics.db_reads.fetch_add(1, Ordering::Relaxed);
+                let Some(analysis) = found else {
+                    return Ok(None);
+                };
+                let analysis = Arc::new(analysis);
+                self.set_stock(analysis.symbol.clone(), analysis.clone())
+                    .await;
+                Ok(Some((analysis, StockSource::Database)))
+            }
+            Err(e) => {
+                self.stock_metrics.db_errors.fetch_add(1, Ordering::Relaxed);
+                Err(e)
+            }
+        }
+    }
+
+    /// Like [`Self::lookup_stock`], but logs database errors and treats them as a miss.
+    pub async fn get_stock(&self, symbol: &str) -> Option<Arc<StockAnalysis>> {
+        match self.lookup_stock(symbol).await {
+            Ok(found) => found.map(|(analysis, _)| analysis),
+            Err(e) => {
+                tracing::warn!("Cache read-through failed for {}: {}", symbol, e);
+                None
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.stock_metrics.hits.load(Ordering::Relaxed);
+        let misses = self.stock_metrics.misses.load(Ordering::Relaxed);
+        let responses = self.response_cache.stats();
+        CacheStats {
+            stock_hits: hits,
+            stock_misses: misses,
+            db_reads: self.stock_metrics.db_reads.load(Ordering::Relaxed),
+            db_errors: self.stock_metrics.db_errors.load(Ordering::Relaxed),
+            hit_rate: if hits + misses > 0 {
+                hits as f64 / (hits + misses) as f64
+            } else {
+                0.0
+            },
+            stock_entries: self.stock_cache.entry_count(),
+            stock_weighted_bytes: self.stock_cache.weighted_size(),
+            pinned_entries: self.pinned_stocks.read().map(|p| p.len()).unwrap_or(0),
+            read_through: self.read_through.is_some(),
+            response_hits: responses.hits,
+            response_misses: responses.misses,
+            response_bypasses: responses.bypasses,
+            response_entries: responses.entries,
+            response_weighted_bytes: responses.weighted_bytes,
+        }
+    }
+
+    pub async fn set_stock(&self, symbol: String, analysis: Arc<StockAnalysis>) {
+        if let Ok(mut pinned) = self.pinned_stocks.write() {
+            if let Some(slot) = pinned.get_mut(&symbol) {
+                *slot = Some(analysis);
+                return;
+            }
+        }
         self.stock_cache.insert(symbol, analysis).await;
     }
 
-    pub async fn get_list(&self, cache_key: &str) -> Option<Vec<StockAnalysis>> {
+    /// `Some(entry)` when `symbol` is pinned without a TTL (the entry itself
+    /// may still be empty until the first write), `None` otherwise.
+    fn pinned_stock(&self, symbol: &str) -> Option<Option<Arc<StockAnalysis>>> {
+        self.pinned_stocks.read().ok()?.get(symbol).cloned()
+    }
+
+    /// Pin `symbol` in the stock cache. With `ttl: None` the entry is never
+    /// evicted; with `Some(ttl)` it stays in moka but expires after `ttl`
+    /// instead of the default stock TTL. Re-pinning replaces the old policy.
+    pub async fn pin_stock(&self, symbol: &str, ttl: Option<Duration>) {
+        let current = self.get_stock(symbol).await;
+        self.stock_cache.invalidate(symbol).await;
+        if let Ok(mut pinned) = self.pinned_stocks.write() {
+            pinned.remove(symbol);
+            if ttl.is_none() {
+                pinned.insert(symbol.to_string(), current.clone());
+            }
+        }
+        if let Ok(mut overrides) = self.stock_ttl_overrides.write() {
+            match ttl {
+                Some(ttl) => overrides.insert(symbol.to_string(), ttl),
+                None => overrides.remove(symbol),
+            };
+        }
+        if let (Some(_), Some(analysis)) = (ttl, current) {
+            self.stock_cache.insert(symbol.to_string(), analysis).await;
+        }
+    }
+
+    /// Drop any pin / TTL override for `symbol`. A cached value is kept and
+    /// falls back to the default TTL.
+    pub async fn unpin_stock(&self, symbol: &str) {
+        let pinned = self
+            .pinned_stocks
+            .write()
+            .ok()
+            .and_then(|mut p| p.remove(symbol))
+            .flatten();
+        let had_override = self
+            .stock_ttl_overrides
+            .write()
+            .ok()
+            .and_then(|mut o| o.remove(symbol))
+            .is_some();
+        let current = match pinned {
+            Some(analysis) => Some(analysis),
+            None if had_override => self.stock_cache.get(symbol).await,
+            None => None,
+        };
+        if let Some(analysis) = current {
+            self.stock_cache.insert(symbol.to_string(), analysis).await;
+        }
+    }
+
+    pub async fn get_list(&self, cache_key: &str) -> Option<Arc<Vec<StockAnalysis>>> {
         self.list_cache.get(cache_key).await
     }
 
-    pub async fn set_list(&self, cache_key: String, analyses: Vec<StockAnalysis>) {
+    pub async fn set_list(&self, cache_key: String, analyses: Arc<Vec<StockAnalysis>>) {
         self.list_cache.insert(cache_key, analyses).await;
     }
 
     pub async fn invalidate_stock(&self, symbol: &str) {
+        if let Ok(mut pinned) = self.pinned_stocks.write() {
+            if let Some(slot) = pinned.get_mut(symbol) {
+                *slot = None;
+            }
+        }
         self.stock_cache.invalidate(symbol).await;
     }
 
+    /// Normalize `symbol` and follow a rename alias, if any.
+    pub fn resolve_symbol(&self, symbol: &str) -> String {
+        let key = crate::symbols::normalize_symbol_key(symbol);
+        self.symbol_aliases
+            .read()
+            .ok()
+            .and_then(|aliases| aliases.get(&key).cloned())
+            .unwrap_or(key)
+    }
+
+    /// Register `old → new`. Aliases already pointing at `old` are re-pointed
+    /// so chains of renames resolve in one step.
+    pub fn add_symbol_alias(&self, old: &str, new: &str) {
+        if let Ok(mut aliases) = self.symbol_aliases.write() {
+            for target in aliases.values_mut() {
+                if target == old {
+                    *target = new.to_string();
+                }
+            }
+            aliases.remove(new);
+            aliases.insert(old.to_string(), new.to_string());
+        }
+    }
+
+    /// Drop cached lists and responses once the data behind them changes
+    /// (end of a cycle, theme edits).
     pub async fn invalidate_all_lists(&self) {
         self.list_cache.invalidate_all();
+        self.response_cache.invalidate_all();
     }
 
     // News cache methods
@@ -113,6 +442,14 @@ impl CacheLayer {
         self.earnings_cache.insert(symbol, data).await;
     }
 
+    pub async fn get_quote(&self, symbol: &str) -> Option<LiveQuote> {
+        self.quote_cache.get(symbol).await
+    }
+
+    pub async fn set_quote(&self, quote: LiveQuote) {
+        self.quote_cache.insert(quote.symbol.clone(), quote).await;
+    }
+
     // Company profile cache methods
     pub async fn get_company_profile(&self, symbol: &str) -> Option<CompanyProfile> {
         self.company_profile_cache.get(symbol).await
@@ -130,13 +467,122 @@ impl CacheLayer {
     pub async fn set_insiders(&self, symbol: String, trades: Vec<InsiderTrade>) {
         self.insider_cache.insert(symbol, trades).await;
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weight_tracks_serialized_size() {
+        let light = StockAnalysis::sample("AAPL", 1.0);
+        let mut heavy = light.clone();
+        heavy.news = Some(
+            (0..50)
+                .map(|i| NasdaqNewsItem {
+                    title: format!("Headline number {}", i),
+                    url: "https://example.com/a/very/long/article/path".to_string(),
+                    publisher: Some("Publisher".to_string()),
+                    created: None,
+                    ago: None,
+                })
+                .collect(),
+        );
+        assert!(serialized_weight(&heavy) > 10 * serialized_weight(&light));
+    }
+
+    #[tokio::test]
+    async fn lookup_without_repository_counts_miss() {
+        let cache = CacheLayer::new(300, 60, 16);
+        cache
+            .set_stock("AAPL".into(), Arc::new(StockAnalysis::sample("AAPL", 1.0)))
+            .await;
+
+        let (hit, source) = cache.lookup_stock("aapl").await.unwrap().unwrap();
+        assert_eq!(hit.symbol, "AAPL");
+        assert_eq!(source, StockSource::Cache);
+        assert!(cache.lookup_stock("MSFT").await.unwrap().is_none());
 
-    // Generic cache methods (for computed JSON results like sector perf, correlation)
-    pub async fn get_generic(&self, key: &str) -> Option<String> {
-        self.generic_cache.get(key).await
+        let stats = cache.stats();
+        assert_eq!((stats.stock_hits, stats.stock_misses), (1, 1));
+        assert!(!stats.read_through);
     }
 
-    pub async fn set_generic(&self, key: String, value: String) {
-        self.generic_cache.insert(key, value).await;
+    #[tokio::test]
+    async fn reads_share_the_cached_allocation() {
+        let cache = CacheLayer::new(300, 60, 16);
+        let stored = Arc::new(StockAnalysis::sample("AAPL", 1.0));
+        cache.set_stock("AAPL".into(), stored.clone()).await;
+        let read = cache.get_stock("AAPL").await.unwrap();
+        assert!(Arc::ptr_eq(&stored, &read));
+
+        let list = Arc::new(vec![StockAnalysis::sample("AAPL", 1.0)]);
+        cache.set_list("all".into(), list.clone()).await;
+        assert!(Arc::ptr_eq(&list, &cache.get_list("all").await.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn quotes_expire_after_quote_ttl() {
+        let cache = CacheLayer::new(300, 60, 16).with_quote_ttl(Duration::from_secs(1));
+        cache
+            .set_quote(LiveQuote {
+                symbol: "AAPL".into(),
+                price: 190.5,
+                day_volume: Some(1_000.0),
+                quoted_at: chrono::Utc::now(),
+            })
+            .await;
+        assert_eq!(cache.get_quote("AAPL").await.map(|q| q.price), Some(190.5));
+
+        tokio::time::sleep(Duration::from_millis(1_200)).await;
+        assert!(cache.get_quote("AAPL").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn memory_budget_evicts_by_weight() {
+        // 1 MB budget => ~512 KB for stocks; 2_000 entries of ~1 KB+ cannot fit.
+        let cache = CacheLayer::new(300, 60, 1);
+        for i in 0..2_000 {
+            let symbol = format!("SYM{}", i);
+            let mut a = StockAnalysis::sample(&symbol, i as f64);
+            a.sector = Some("x".repeat(1_000));
+            cache.set_stock(symbol, Arc::new(a)).await;
+        }
+        cache.stock_cache.run_pending_tasks().await;
+        assert!(cache.stock_cache.weighted_size() <= 512 * 1024);
+        assert!(cache.stock_cache.entry_count() < 2_000);
+    }
+
+    #[tokio::test]
+    async fn pinned_symbol_outlives_default_ttl() {
+        let cache = CacheLayer::new(1, 60, 16);
+        cache.pin_stock("AAPL", None).await;
+        cache
+            .set_stock("AAPL".into(), Arc::new(StockAnalysis::sample("AAPL", 1.0)))
+            .await;
+        cache
+            .set_stock("MSFT".into(), Arc::new(StockAnalysis::sample("MSFT", 2.0)))
+            .await;
+
+        tokio::time::sleep(Duration::from_millis(1_200)).await;
+
+        assert_eq!(cache.get_stock("AAPL").await.map(|a| a.price), Some(1.0));
+        assert!(cache.get_stock("MSFT").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn ttl_override_and_unpin_keep_current_value() {
+        let cache = CacheLayer::new(1, 60, 16);
+        cache
+            .set_stock("NVDA".into(), Arc::new(StockAnalysis::sample("NVDA", 3.0)))
+            .await;
+        cache.pin_stock("NVDA", Some(Duration::from_secs(60))).await;
+
+        tokio::time::sleep(Duration::from_millis(1_200)).await;
+        assert_eq!(cache.get_stock("NVDA").await.map(|a| a.price), Some(3.0));
+
+        cache.pin_stock("NVDA", None).await;
+        cache.unpin_stock("NVDA").await;
+        assert_eq!(cache.get_stock("NVDA").await.map(|a| a.price), Some(3.0));
     }
 }
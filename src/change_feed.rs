@@ -0,0 +1,163 @@
+//! Saved analyses pushed to WebSocket clients as they land in MongoDB.
+//!
+//! A background task watches `stock_analysis` through a change stream and
+//! broadcasts each inserted, replaced or updated document (without news) to
+//! connections opened with `/ws?analyses=true`. Because it follows the
+//! database rather than the engine, writes from the notes and ingest
+//! handlers and from other instances sharing the database are pushed too.
+//!
+//! Change streams need a replica set; a single-node one is enough
+//! (`mongod --replSet rs0`, then `rs.initiate()` once). Against a
+//! standalone server the watcher logs that once and stops, and the socket
+//! carries progress and candles only. Other errors restart the stream from
+//! the last resume token, so a brief disconnect drops nothing. When the
+//! oplog no longer reaches back to that token, the stream restarts from now
+//! and the writes in between are not pushed.
+
+use std::time::Duration;
+
+use futures::stream::StreamExt;
+use mongodb::bson::{self, doc, Document};
+use mongodb::change_stream::event::ResumeToken;
+use mongodb::error::{Error, ErrorKind};
+use mongodb::options::FullDocumentType;
+use serde_json::json;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::db::MongoDB;
+use crate::models::StockAnalysis;
+
+/// Server error for `$changeStream` on a standalone `mongod`.
+const NOT_A_REPLICA_SET: i32 = 40573;
+
+/// Server errors for a resume token the stream can't continue from:
+/// `ChangeStreamFatalError` and `ChangeStreamHistoryLost`.
+const RESUME_POINT_LOST: [i32; 2] = [280, 286];
+
+/// Wait before reopening a change stream that failed.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// One saved analysis, ready to send.
+#[derive(Debug, Clone)]
+pub struct AnalysisUpdate {
+    pub symbol: String,
+    /// The `{"type":"analysis",..}` socket message, serialized once for
+    /// every connection.
+    pub message: String,
+}
+
+impl AnalysisUpdate {
+    pub fn new(analysis: &StockAnalysis) -> Self {
+        Self {
+            symbol: analysis.symbol.clone(),
+            message: json!({ "type": "analysis", "analysis": analysis }).to_string(),
+        }
+    }
+}
+
+/// Change stream stages: writes only, and no news in the full document.
+pub fn watch_pipeline() -> Vec<Document> {
+    vec![
+        doc! { "$match": { "operationType": { "$in": ["insert", "replace", "update"] } } },
+        doc! { "$project": { "fullDocument.news": 0 } },
+    ]
+}
+
+fn is_standalone(error: &Error) -> bool {
+    matches!(error.kind.as_ref(), ErrorKind::Command(e) if e.code == NOT_A_REPLICA_SET)
+}
+
+fn lost_resume_point(error: &Error) -> bool {
+    matches!(error.kind.as_ref(), ErrorKind::Command(e) if RESUME_POINT_LOST.contains(&e.code))
+}
+
+/// Broadcast channel of saved analyses.
+#[derive(Clone)]
+pub struct AnalysisFeed {
+    tx: broadcast::Sender<AnalysisUpdate>,
+}
+
+impl Default for AnalysisFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisFeed {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(1024);
+        Self { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AnalysisUpdate> {
+        self.tx.subscribe()
+    }
+
+    /// Watch `stock_analysis` for the life of the process.
+    pub fn spawn_watcher(&self, db: MongoDB) {
+        let feed = self.clone();
+        tokio::spawn(async move {
+            let mut resume: Option<ResumeToken> = None;
+            loop {
+                match feed.watch(&db, &mut resume).await {
+                    Err(e) if is_standalone(&e) => {
+                        info!("📡 MongoDB is not a replica set; live analysis pushes are off");
+                        return;
+                    }
+                    Err(e) if resume.is_some() && lost_resume_point(&e) => {
+                        warn!(
+                            "Analysis change stream lost its resume point, dropping missed events: {}",
+                            e
+                        );
+                        resume = None;
+                    }
+                    Err(e) => warn!("Analysis change stream failed: {}", e),
+                    Ok(()) => debug!("Analysis change stream closed"),
+                }
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        });
+    }
+
+    /// Forward events until the stream ends or fails, keeping `resume` at
+    /// the last event seen.
+    async fn watch(&self, db: &MongoDB, resume: &mut Option<ResumeToken>) -> Result<(), Error> {
+        let mut stream = db
+            .analysis_collection()
+            .clone_with_type::<Document>()
+            .watch()
+            .pipeline(watch_pipeline())
+            .full_document(FullDocumentType::UpdateLookup)
+            .resume_after(resume.clone())
+            .await?;
+        while let Some(event) = stream.next().await {
+            let event = event?;
+            *resume = stream.resume_token();
+            // An update whose document was deleted before the lookup has none.
+            let Some(document) = event.full_document else {
+                continue;
+            };
+            // Unreadable documents are reported by full reads (`repair.rs`).
+            let Ok(analysis) = bson::from_document::<StockAnalysis>(document) else {
+                continue;
+            };
+            // No receivers is fine: no client may have asked for analyses.
+            let _ = self.tx.send(AnalysisUpdate::new(&analysis));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_pipeline_keeps_resume_fields() {
+        let pipeline = watch_pipeline();
+        let project = pipeline[1].get_document("$project").unwrap();
+        // The driver needs `_id`, `operationType` and `ns` to resume.
+        assert_eq!(project, &doc! { "fullDocument.news": 0 });
+    }
+}
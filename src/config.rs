@@ -1,18 +1,57 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::archive::ArchiveSettings;
+use crate::backup::BackupSettings;
+use crate::db::{parse_read_preference, parse_write_concern, MongoSettings};
+use crate::indicators::IndicatorConfig;
+use crate::lanes::LaneMode;
+use crate::pipeline::{PipelineStages, Stage};
+use crate::response_cache::{RouteTtls, DEFAULT_ROUTE_TTLS};
+use crate::weekend::WeekendSettings;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub mongodb_uri: String,
     pub database_name: String,
+    /// Keep the latest analyses in PostgreSQL instead of `stock_analysis`
+    /// (builds with the `postgres` feature). Configurable via `POSTGRES_URL`.
+    pub postgres_url: Option<String>,
     pub server_host: String,
     pub server_port: u16,
     pub analysis_interval_secs: u64,
     pub cache_ttl_secs: u64,
+    /// Number of analyses (largest market caps first) loaded into the stock
+    /// cache at startup, on top of watchlisted and pinned symbols. Everything
+    /// else is cached lazily on first read. `0` loads the whole collection.
+    /// Configurable via `CACHE_WARM_LIMIT`.
+    pub cache_warm_limit: usize,
+    /// Memory budget (MiB) for the stock, list and news caches, measured by
+    /// serialized entry size. Configurable via `CACHE_MEMORY_BUDGET_MB`.
+    pub cache_memory_budget_mb: u64,
+    /// Read routes whose responses are cached and for how long (see
+    /// `response_cache.rs`). Configurable via `RESPONSE_CACHE_TTLS`; empty
+    /// disables response caching.
+    pub response_cache_ttls: RouteTtls,
     pub yahoo_request_delay_ms: u64,
     pub yahoo_concurrency: usize,
+    /// Average Yahoo requests per minute across the cycle and the API (see
+    /// `rate_budget.rs`). `0` leaves requests unmetered. Configurable via
+    /// `YAHOO_BUDGET_PER_MIN`.
+    pub yahoo_budget_per_min: u32,
+    /// Requests allowed back to back once the budget has refilled.
+    /// Configurable via `YAHOO_BUDGET_BURST`.
+    pub yahoo_budget_burst: u32,
+    /// Budget tokens background work leaves for API-triggered requests.
+    /// Configurable via `YAHOO_INTERACTIVE_RESERVE`.
+    pub yahoo_interactive_reserve: u32,
     pub nasdaq_request_delay_ms: u64,
     pub news_cache_ttl_secs: u64,
+    /// How long `/api/quotes` serves a batch quote before refetching.
+    /// Configurable via `QUOTE_CACHE_TTL_SECS`.
+    pub quote_cache_ttl_secs: u64,
     pub OPENROUTER_API_KEY_STOCKS: Option<String>,
     pub openrouter_enabled: bool,
     /// Minimum market cap to accept a stock into the analysis pipeline.
@@ -30,9 +69,29 @@ pub struct Config {
     /// Base URL of the frontend, used to render "view stock" links inside
     /// Discord embeds. e.g. `http://localhost:5173`. Optional.
     pub public_base_url: Option<String>,
+    /// Minutes after the 16:00 New York close the end-of-day watchlist recap
+    /// is sent (see `notifications/recap.rs`); `None` (empty) disables it.
+    /// Configurable via `WATCHLIST_RECAP_MINUTES_AFTER_CLOSE`.
+    pub watchlist_recap_minutes_after_close: Option<u32>,
+    /// Start in read-only maintenance mode (see `maintenance.rs`).
+    /// Configurable via `READ_ONLY_MODE`.
+    pub read_only_mode: bool,
     /// Optional Canadian listings to include alongside the US-primary universe.
     /// Use Yahoo suffixes like `.TO` and `.V`. Configurable via `CANADIAN_SYMBOLS`.
     pub canadian_symbols: Vec<String>,
+    /// Path to the full Russell 2000 constituent list (one ticker per line,
+    /// or a holdings CSV with the ticker first). Unset keeps the embedded
+    /// top 200. Configurable via `RUSSELL2000_FILE`.
+    pub russell2000_file: Option<String>,
+    /// Hours between fetches of current S&P 500 / NASDAQ 100 membership
+    /// (see `constituents.rs`). Configurable via `CONSTITUENT_REFRESH_HOURS`.
+    /// Set to 0 to keep the stored or embedded lists.
+    pub constituent_refresh_hours: u64,
+    /// Russell 2000 members outside the main universe (below
+    /// `MIN_MARKET_CAP_USD` or missing from the screener) queued at the end
+    /// of each cycle, rotating through the list. Configurable via
+    /// `RUSSELL_CHUNK_SIZE`. Set to 0 to disable.
+    pub russell_chunk_size: usize,
     /// Per-symbol Yahoo circuit breaker: number of consecutive non-rate-limit
     /// fetch failures before the breaker opens for that symbol. Configurable
     /// via `YAHOO_CIRCUIT_FAILURES`. Set to 0 to disable the breaker entirely.
@@ -40,6 +99,139 @@ pub struct Config {
     /// Number of subsequent cycles to skip a symbol after the breaker opens,
     /// before it is probed again. Configurable via `YAHOO_CIRCUIT_SKIP_CYCLES`.
     pub yahoo_circuit_skip_cycles: u32,
+    /// Port for the gRPC facade (see `proto/analyser.proto`). The gRPC server
+    /// binds to `SERVER_HOST` on this port; leave `GRPC_PORT` unset to disable it.
+    pub grpc_port: Option<u16>,
+    /// Seconds between batch-quote polls for symbols subscribed over the
+    /// WebSocket (market hours only). `0` disables the intraday relay.
+    /// Configurable via `INTRADAY_POLL_SECS`.
+    pub intraday_poll_secs: u64,
+    /// Width of the synthesized intraday candles. Configurable via
+    /// `INTRADAY_CANDLE_SECS`.
+    pub intraday_candle_secs: u64,
+    /// UTC hour the nightly beta/correlation/RS-rank batch runs at (see
+    /// `cross_section.rs`); `None` (empty) disables the schedule.
+    /// Configurable via `CROSS_SECTION_HOUR_UTC`.
+    pub cross_section_hour_utc: Option<u32>,
+    /// New York hour on Saturdays the weekend deep-analysis run starts at
+    /// (see `weekend.rs`); `None` (empty) leaves it to
+    /// `POST /api/admin/weekend`. Configurable via `WEEKEND_HOUR_NY`.
+    pub weekend_hour_ny: Option<u32>,
+    /// Years of daily closes the weekend run backfills per symbol. `0` skips
+    /// the backfill and seasonality. Configurable via `WEEKEND_BACKFILL_YEARS`.
+    pub weekend_backfill_years: u32,
+    /// Largest names by market cap the weekend run stores AI summaries for
+    /// (needs OpenRouter). `0` disables. Configurable via
+    /// `WEEKEND_AI_SUMMARIES`.
+    pub weekend_ai_summaries: usize,
+    /// Timezone API timestamps are rendered in when a request doesn't pass
+    /// `?tz=`. Configurable via `API_TIMEZONE` (IANA name).
+    pub api_timezone: chrono_tz::Tz,
+    /// Enabled analysis pipeline stages (see `pipeline.rs`). Configurable via
+    /// `ANALYSIS_STAGES`, a comma-separated list or `all`.
+    pub analysis_stages: PipelineStages,
+    /// Per-stage timeout for the network-bound stages (technicals, news,
+    /// fundamentals). A stage that overruns is skipped and recorded in the
+    /// analysis' `warnings`. Configurable via `STAGE_TIMEOUT_SECS`.
+    pub stage_timeout_secs: u64,
+    /// Upstream error rate (0–1] over the last `DEGRADE_WINDOW` calls that
+    /// switches the engine into degraded mode. Configurable via
+    /// `DEGRADE_ERROR_RATE`.
+    pub degrade_error_rate: f64,
+    /// Calls per upstream the error rate is measured over. Configurable via
+    /// `DEGRADE_WINDOW`.
+    pub degrade_window: usize,
+    /// How long degraded mode lasts once tripped. Configurable via
+    /// `DEGRADE_COOLDOWN_SECS`.
+    pub degrade_cooldown_secs: u64,
+    /// Yahoo delay multiplier for cycles that start degraded. Configurable
+    /// via `DEGRADED_DELAY_MULTIPLIER`.
+    pub degraded_delay_multiplier: u64,
+    /// How the cycle's queue is split into per-sector lanes (see `lanes.rs`):
+    /// `off`, `round_robin` or `parallel`. Configurable via `SECTOR_LANES`.
+    pub sector_lanes: LaneMode,
+    /// Consecutive failed analyses before a symbol is dead-lettered and left
+    /// out of cycles until requeued. `0` disables the dead-letter queue.
+    /// Configurable via `DEAD_LETTER_THRESHOLD`.
+    pub dead_letter_threshold: u32,
+    /// MongoDB commands at or over this many milliseconds are logged with
+    /// their filter. Configurable via `SLOW_QUERY_MS`. Set to 0 to disable
+    /// the log (timings are still collected for `/api/admin/db/stats`).
+    pub slow_query_ms: u64,
+    /// MongoDB connection pool bounds. Configurable via `MONGO_MAX_POOL_SIZE`
+    /// and `MONGO_MIN_POOL_SIZE`; unset keeps the URI or driver default.
+    pub mongo_max_pool_size: Option<u32>,
+    pub mongo_min_pool_size: Option<u32>,
+    /// MongoDB connect and server-selection timeouts in milliseconds.
+    /// Configurable via `MONGO_CONNECT_TIMEOUT_MS` and
+    /// `MONGO_SERVER_SELECTION_TIMEOUT_MS`.
+    pub mongo_connect_timeout_ms: Option<u64>,
+    pub mongo_server_selection_timeout_ms: Option<u64>,
+    /// Default read preference (`primary`, `primaryPreferred`, `secondary`,
+    /// `secondaryPreferred`, `nearest`). Configurable via
+    /// `MONGO_READ_PREFERENCE`.
+    pub mongo_read_preference: Option<String>,
+    /// Default write concern (`majority`, a node count or a tag set).
+    /// Configurable via `MONGO_WRITE_CONCERN`.
+    pub mongo_write_concern: Option<String>,
+    /// Serve the heavy list queries (filters, market summary, sectors, news)
+    /// from secondaries when available. Configurable via
+    /// `MONGO_SECONDARY_LIST_READS`.
+    pub mongo_secondary_list_reads: bool,
+    /// Check `stock_analysis` for documents from older schema versions at
+    /// startup, repairing or quarantining them (see `repair.rs`).
+    /// Configurable via `STARTUP_REPAIR`.
+    pub startup_repair: bool,
+    /// Directory for database backups (see `backup.rs`). Unset disables
+    /// backups. Configurable via `BACKUP_DIR`.
+    pub backup_dir: Option<String>,
+    /// Seed loaded into an empty database on startup (see `seed.rs`): a
+    /// path, `http(s)://` URL or `s3://bucket/key`; `None` (empty) disables.
+    /// Configurable via `SEED_SNAPSHOT`.
+    pub seed_snapshot: Option<String>,
+    /// Hours between scheduled backups; `0` only backs up on demand.
+    /// Configurable via `BACKUP_INTERVAL_HOURS`.
+    pub backup_interval_hours: u64,
+    /// Backups kept after each run; `0` keeps all. Configurable via
+    /// `BACKUP_RETENTION`.
+    pub backup_retention: usize,
+    /// Days of `analysis_history` snapshots, events, signals and daily
+    /// performance rows kept (see `retention.rs`). `0` keeps them all.
+    /// Configurable via `HISTORY_RETENTION_DAYS`.
+    pub history_retention_days: u32,
+    /// Comma-separated collections to back up; unset backs up every
+    /// collection. Configurable via `BACKUP_COLLECTIONS`.
+    pub backup_collections: Vec<String>,
+    /// Shared secret `POST /api/ingest/signal` requires (header, `?token=` or
+    /// body field). Unset accepts unauthenticated signals. Configurable via
+    /// `INGEST_TOKEN`.
+    pub ingest_token: Option<String>,
+    /// Hours an external signal stays on its symbol's analysis (see
+    /// `ingest.rs`). Configurable via `INGEST_SIGNAL_TTL_HOURS`.
+    pub ingest_signal_ttl_hours: i64,
+    /// Directory raw Yahoo/NASDAQ responses are archived to (see
+    /// `archive.rs`). Unset disables archiving. Configurable via
+    /// `RAW_ARCHIVE_DIR`.
+    pub raw_archive_dir: Option<String>,
+    /// Share of symbols whose responses are archived each day, 0-1.
+    /// Configurable via `RAW_ARCHIVE_SAMPLE_RATE`.
+    pub raw_archive_sample_rate: f64,
+    /// Days of raw responses kept; `0` keeps all. Configurable via
+    /// `RAW_ARCHIVE_RETENTION_DAYS`.
+    pub raw_archive_retention_days: u32,
+    /// RSI, SMA and MACD periods (see `indicators.rs`). Configurable via
+    /// `INDICATOR_RSI_PERIOD`, `INDICATOR_SMA_SHORT`, `INDICATOR_SMA_LONG`,
+    /// `INDICATOR_MACD_FAST`, `INDICATOR_MACD_SLOW` and
+    /// `INDICATOR_MACD_SIGNAL`; unset keeps 14, 20/50 and 12/26/9.
+    pub indicators: IndicatorConfig,
+    /// Keep each symbol's bars and RSI/MACD state between cycles and fetch
+    /// only the days since (see `incremental.rs`). Configurable via
+    /// `INCREMENTAL_FETCH`.
+    pub incremental_fetch: bool,
+    /// Annual risk-free rate in percent (e.g. the 3-month T-bill yield)
+    /// that Sharpe and Sortino ratios are measured against. Configurable
+    /// via `RISK_FREE_RATE_PCT`.
+    pub risk_free_rate_pct: f64,
 }
 
 impl Config {
@@ -47,7 +239,11 @@ impl Config {
         dotenv::dotenv().ok();
 
         let OPENROUTER_API_KEY_STOCKS = env::var("OPENROUTER_API_KEY_STOCKS").ok();
+        let analysis_stages = PipelineStages::parse(
+            &env::var("ANALYSIS_STAGES").unwrap_or_else(|_| "all".to_string()),
+        )?;
         let openrouter_enabled = OPENROUTER_API_KEY_STOCKS.is_some()
+            && analysis_stages.enabled(Stage::Ai)
             && env::var("OPENROUTER_ENABLED")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
@@ -58,6 +254,7 @@ impl Config {
                 .unwrap_or_else(|_| "mongodb://localhost:27017".to_string()),
             database_name: env::var("DATABASE_NAME")
                 .unwrap_or_else(|_| "stock_analyzer".to_string()),
+            postgres_url: env::var("POSTGRES_URL").ok().filter(|s| !s.is_empty()),
             server_host: env::var("SERVER_HOST")
                 .unwrap_or_else(|_| "127.0.0.1".to_string()),
             server_port: env::var("SERVER_PORT")
@@ -69,15 +266,38 @@ impl Config {
             cache_ttl_secs: env::var("CACHE_TTL_SECS")
                 .unwrap_or_else(|_| "300".to_string())
                 .parse()?,
+            cache_warm_limit: env::var("CACHE_WARM_LIMIT")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()?,
+            cache_memory_budget_mb: env::var("CACHE_MEMORY_BUDGET_MB")
+                .unwrap_or_else(|_| "512".to_string())
+                .parse()?,
+            response_cache_ttls: RouteTtls::parse(
+                &env::var("RESPONSE_CACHE_TTLS")
+                    .unwrap_or_else(|_| DEFAULT_ROUTE_TTLS.to_string()),
+            )
+            .map_err(|e| anyhow!("RESPONSE_CACHE_TTLS: {}", e))?,
             yahoo_request_delay_ms: env::var("YAHOO_REQUEST_DELAY_MS")
                 .unwrap_or_else(|_| "100".to_string())
                 .parse()?,
             yahoo_concurrency: env::var("YAHOO_CONCURRENCY")
                 .unwrap_or_else(|_| "5".to_string())
                 .parse()?,
+            yahoo_budget_per_min: env::var("YAHOO_BUDGET_PER_MIN")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
+            yahoo_budget_burst: env::var("YAHOO_BUDGET_BURST")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()?,
+            yahoo_interactive_reserve: env::var("YAHOO_INTERACTIVE_RESERVE")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
             nasdaq_request_delay_ms: env::var("NASDAQ_REQUEST_DELAY_MS")
                 .unwrap_or_else(|_| "500".to_string())
                 .parse()?,
+            quote_cache_ttl_secs: env::var("QUOTE_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()?,
             news_cache_ttl_secs: env::var("NEWS_CACHE_TTL_SECS")
                 .unwrap_or_else(|_| "900".to_string()) // 15 minutes
                 .parse()?,
@@ -92,17 +312,186 @@ impl Config {
                 .parse()
                 .unwrap_or(true),
             public_base_url: env::var("PUBLIC_BASE_URL").ok().filter(|s| !s.is_empty()),
+            watchlist_recap_minutes_after_close: Some(
+                env::var("WATCHLIST_RECAP_MINUTES_AFTER_CLOSE")
+                    .unwrap_or_else(|_| "30".to_string()),
+            )
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.trim().parse())
+            .transpose()?,
+            read_only_mode: env::var("READ_ONLY_MODE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
             canadian_symbols: crate::symbols::parse_symbol_list(
                 &env::var("CANADIAN_SYMBOLS").unwrap_or_else(|_| {
                     "SHOP.TO,RY.TO,TD.TO,BNS.TO,BMO.TO,CM.TO,ENB.TO,CNQ.TO,CNR.TO,CP.TO,TRI.TO,ATD.TO,SU.TO,BAM.TO,BN.TO,WCN.TO,CSU.TO,IMO.TO,ABX.TO,TECK-B.TO".to_string()
                 }),
             ),
+            russell2000_file: env::var("RUSSELL2000_FILE").ok().filter(|s| !s.is_empty()),
+            constituent_refresh_hours: env::var("CONSTITUENT_REFRESH_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()?,
+            russell_chunk_size: env::var("RUSSELL_CHUNK_SIZE")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()?,
             yahoo_circuit_failure_threshold: env::var("YAHOO_CIRCUIT_FAILURES")
                 .unwrap_or_else(|_| "5".to_string())
                 .parse()?,
             yahoo_circuit_skip_cycles: env::var("YAHOO_CIRCUIT_SKIP_CYCLES")
                 .unwrap_or_else(|_| "12".to_string())
                 .parse()?,
+            grpc_port: env::var("GRPC_PORT")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse())
+                .transpose()?,
+            intraday_poll_secs: env::var("INTRADAY_POLL_SECS")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()?,
+            intraday_candle_secs: env::var("INTRADAY_CANDLE_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()?,
+            cross_section_hour_utc: Some(
+                env::var("CROSS_SECTION_HOUR_UTC").unwrap_or_else(|_| "6".to_string()),
+            )
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.trim().parse())
+            .transpose()?,
+            weekend_hour_ny: Some(
+                env::var("WEEKEND_HOUR_NY").unwrap_or_else(|_| "10".to_string()),
+            )
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.trim().parse())
+            .transpose()?,
+            weekend_backfill_years: env::var("WEEKEND_BACKFILL_YEARS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            weekend_ai_summaries: env::var("WEEKEND_AI_SUMMARIES")
+                .unwrap_or_else(|_| "25".to_string())
+                .parse()?,
+            api_timezone: env::var("API_TIMEZONE")
+                .unwrap_or_else(|_| "UTC".to_string())
+                .parse()
+                .map_err(|e| anyhow!("API_TIMEZONE: {}", e))?,
+            analysis_stages,
+            stage_timeout_secs: env::var("STAGE_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()?,
+            degrade_error_rate: env::var("DEGRADE_ERROR_RATE")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()?,
+            degrade_window: env::var("DEGRADE_WINDOW")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()?,
+            degrade_cooldown_secs: env::var("DEGRADE_COOLDOWN_SECS")
+                .unwrap_or_else(|_| "900".to_string())
+                .parse()?,
+            degraded_delay_multiplier: env::var("DEGRADED_DELAY_MULTIPLIER")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()?,
+            sector_lanes: env::var("SECTOR_LANES")
+                .unwrap_or_else(|_| "round_robin".to_string())
+                .parse()?,
+            dead_letter_threshold: env::var("DEAD_LETTER_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            slow_query_ms: env::var("SLOW_QUERY_MS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()?,
+            mongo_max_pool_size: env::var("MONGO_MAX_POOL_SIZE")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse())
+                .transpose()?,
+            mongo_min_pool_size: env::var("MONGO_MIN_POOL_SIZE")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse())
+                .transpose()?,
+            mongo_connect_timeout_ms: env::var("MONGO_CONNECT_TIMEOUT_MS")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse())
+                .transpose()?,
+            mongo_server_selection_timeout_ms: env::var("MONGO_SERVER_SELECTION_TIMEOUT_MS")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse())
+                .transpose()?,
+            mongo_read_preference: env::var("MONGO_READ_PREFERENCE")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            mongo_write_concern: env::var("MONGO_WRITE_CONCERN")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            mongo_secondary_list_reads: env::var("MONGO_SECONDARY_LIST_READS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            startup_repair: env::var("STARTUP_REPAIR")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            backup_dir: env::var("BACKUP_DIR").ok().filter(|s| !s.is_empty()),
+            seed_snapshot: Some(
+                env::var("SEED_SNAPSHOT")
+                    .unwrap_or_else(|_| crate::seed::DEFAULT_SEED_PATH.to_string()),
+            )
+            .filter(|s| !s.trim().is_empty()),
+            backup_interval_hours: env::var("BACKUP_INTERVAL_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()?,
+            backup_retention: env::var("BACKUP_RETENTION")
+                .unwrap_or_else(|_| "7".to_string())
+                .parse()?,
+            history_retention_days: env::var("HISTORY_RETENTION_DAYS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
+            backup_collections: env::var("BACKUP_COLLECTIONS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            ingest_token: env::var("INGEST_TOKEN").ok().filter(|s| !s.is_empty()),
+            ingest_signal_ttl_hours: env::var("INGEST_SIGNAL_TTL_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()?,
+            raw_archive_dir: env::var("RAW_ARCHIVE_DIR").ok().filter(|s| !s.is_empty()),
+            raw_archive_sample_rate: env::var("RAW_ARCHIVE_SAMPLE_RATE")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()?,
+            raw_archive_retention_days: env::var("RAW_ARCHIVE_RETENTION_DAYS")
+                .unwrap_or_else(|_| "14".to_string())
+                .parse()?,
+            indicators: IndicatorConfig {
+                rsi_period: env::var("INDICATOR_RSI_PERIOD")
+                    .unwrap_or_else(|_| "14".to_string())
+                    .parse()?,
+                sma_short: env::var("INDICATOR_SMA_SHORT")
+                    .unwrap_or_else(|_| "20".to_string())
+                    .parse()?,
+                sma_long: env::var("INDICATOR_SMA_LONG")
+                    .unwrap_or_else(|_| "50".to_string())
+                    .parse()?,
+                macd_fast: env::var("INDICATOR_MACD_FAST")
+                    .unwrap_or_else(|_| "12".to_string())
+                    .parse()?,
+                macd_slow: env::var("INDICATOR_MACD_SLOW")
+                    .unwrap_or_else(|_| "26".to_string())
+                    .parse()?,
+                macd_signal: env::var("INDICATOR_MACD_SIGNAL")
+                    .unwrap_or_else(|_| "9".to_string())
+                    .parse()?,
+            },
+            incremental_fetch: env::var("INCREMENTAL_FETCH")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            risk_free_rate_pct: env::var("RISK_FREE_RATE_PCT")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
             OPENROUTER_API_KEY_STOCKS,
             openrouter_enabled,
         };
@@ -115,15 +504,65 @@ impl Config {
         if self.server_port == 0 {
             bail!("SERVER_PORT must be greater than 0");
         }
+        if self.grpc_port == Some(0) {
+            bail!("GRPC_PORT must be greater than 0");
+        }
+        if self.grpc_port == Some(self.server_port) {
+            bail!("GRPC_PORT must differ from SERVER_PORT");
+        }
+        if self.ingest_signal_ttl_hours <= 0 {
+            bail!("INGEST_SIGNAL_TTL_HOURS must be greater than 0");
+        }
+        if self.cross_section_hour_utc.is_some_and(|h| h > 23) {
+            bail!("CROSS_SECTION_HOUR_UTC must be between 0 and 23");
+        }
+        if self.weekend_hour_ny.is_some_and(|h| h > 23) {
+            bail!("WEEKEND_HOUR_NY must be between 0 and 23");
+        }
+        if self.weekend_backfill_years > 50 {
+            bail!("WEEKEND_BACKFILL_YEARS must be at most 50");
+        }
+        if self
+            .watchlist_recap_minutes_after_close
+            .is_some_and(|m| m > 480)
+        {
+            bail!("WATCHLIST_RECAP_MINUTES_AFTER_CLOSE must be at most 480");
+        }
+        if self.intraday_poll_secs > 0 && self.intraday_candle_secs < self.intraday_poll_secs {
+            bail!("INTRADAY_CANDLE_SECS must be at least INTRADAY_POLL_SECS");
+        }
+        if self.stage_timeout_secs == 0 {
+            bail!("STAGE_TIMEOUT_SECS must be greater than 0");
+        }
+        if !(self.degrade_error_rate > 0.0 && self.degrade_error_rate <= 1.0) {
+            bail!("DEGRADE_ERROR_RATE must be in (0, 1]");
+        }
+        if self.degrade_window == 0 {
+            bail!("DEGRADE_WINDOW must be greater than 0");
+        }
+        if self.degraded_delay_multiplier == 0 {
+            bail!("DEGRADED_DELAY_MULTIPLIER must be greater than 0");
+        }
         if self.analysis_interval_secs == 0 {
             bail!("ANALYSIS_INTERVAL_SECS must be greater than 0");
         }
         if self.cache_ttl_secs == 0 {
             bail!("CACHE_TTL_SECS must be greater than 0");
         }
+        if self.quote_cache_ttl_secs == 0 {
+            bail!("QUOTE_CACHE_TTL_SECS must be greater than 0");
+        }
+        if self.cache_memory_budget_mb == 0 {
+            bail!("CACHE_MEMORY_BUDGET_MB must be greater than 0");
+        }
         if self.yahoo_concurrency == 0 {
             bail!("YAHOO_CONCURRENCY must be greater than 0");
         }
+        if self.yahoo_budget_per_min > 0
+            && self.yahoo_interactive_reserve >= self.yahoo_budget_burst
+        {
+            bail!("YAHOO_INTERACTIVE_RESERVE must be less than YAHOO_BUDGET_BURST");
+        }
         if self.min_market_cap_usd < 0.0 || !self.min_market_cap_usd.is_finite() {
             bail!("MIN_MARKET_CAP_USD must be a finite non-negative number");
         }
@@ -132,6 +571,73 @@ impl Config {
         {
             bail!("MAX_ABS_PRICE_CHANGE_PCT must be a finite positive number");
         }
+        if let (Some(min), Some(max)) = (self.mongo_min_pool_size, self.mongo_max_pool_size) {
+            if min > max {
+                bail!("MONGO_MIN_POOL_SIZE must not exceed MONGO_MAX_POOL_SIZE");
+            }
+        }
+        if !(0.0..=1.0).contains(&self.raw_archive_sample_rate) {
+            bail!("RAW_ARCHIVE_SAMPLE_RATE must be between 0 and 1");
+        }
+        if !(-100.0..=100.0).contains(&self.risk_free_rate_pct) {
+            bail!("RISK_FREE_RATE_PCT must be between -100 and 100");
+        }
+        self.indicators.validate()?;
+        self.mongo_settings()?;
         Ok(())
     }
+
+    /// `None` when `BACKUP_DIR` is unset.
+    pub fn backup_settings(&self) -> Option<BackupSettings> {
+        Some(BackupSettings {
+            dir: PathBuf::from(self.backup_dir.as_ref()?),
+            retention: self.backup_retention,
+            interval: Duration::from_secs(self.backup_interval_hours * 3600),
+            collections: self.backup_collections.clone(),
+        })
+    }
+
+    /// `None` when `RAW_ARCHIVE_DIR` is unset.
+    pub fn archive_settings(&self) -> Option<ArchiveSettings> {
+        Some(ArchiveSettings {
+            dir: PathBuf::from(self.raw_archive_dir.as_ref()?),
+            sample_rate: self.raw_archive_sample_rate,
+            retention_days: self.raw_archive_retention_days,
+        })
+    }
+
+    pub fn weekend_settings(&self) -> WeekendSettings {
+        WeekendSettings {
+            hour_ny: self.weekend_hour_ny,
+            backfill_years: self.weekend_backfill_years,
+            ai_summaries: self.weekend_ai_summaries,
+            delay: Duration::from_millis(self.yahoo_request_delay_ms),
+        }
+    }
+
+    /// Connection settings for `MongoDB::new`.
+    pub fn mongo_settings(&self) -> Result<MongoSettings> {
+        Ok(MongoSettings {
+            slow_query: Duration::from_millis(self.slow_query_ms),
+            max_pool_size: self.mongo_max_pool_size,
+            min_pool_size: self.mongo_min_pool_size,
+            connect_timeout: self.mongo_connect_timeout_ms.map(Duration::from_millis),
+            server_selection_timeout: self
+                .mongo_server_selection_timeout_ms
+                .map(Duration::from_millis),
+            read_preference: self
+                .mongo_read_preference
+                .as_deref()
+                .map(parse_read_preference)
+                .transpose()
+                .map_err(|e| anyhow!("MONGO_READ_PREFERENCE: {}", e))?,
+            write_concern: self
+                .mongo_write_concern
+                .as_deref()
+                .map(parse_write_concern)
+                .transpose()
+                .map_err(|e| anyhow!("MONGO_WRITE_CONCERN: {}", e))?,
+            secondary_list_reads: self.mongo_secondary_list_reads,
+        })
+    }
 }
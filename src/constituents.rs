@@ -0,0 +1,219 @@
+//! Current S&P 500 and NASDAQ 100 membership.
+//!
+//! The embedded lists in `indexes.rs` go stale as constituents change. The
+//! [`ConstituentUpdater`] fetches current membership every
+//! `CONSTITUENT_REFRESH_HOURS`, stores it in the `constituents` collection
+//! and swaps it into [`IndexDataProvider`], so heatmaps, index performance
+//! and `StockAnalysis::indexes` follow the change from the next cycle on.
+//!
+//! At startup the last stored lists are loaded before anything reads
+//! membership. A failed fetch, or one that returns implausibly few symbols
+//! (a changed page, a partial response), keeps whatever is in place, which
+//! offline is the embedded list.
+
+use std::time::Duration as StdDuration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::db::MongoDB;
+use crate::indexes::{parse_constituent_list, IndexDataProvider};
+use crate::maintenance::MaintenanceMode;
+
+/// NASDAQ's own NASDAQ 100 listing.
+const NASDAQ100_URL: &str = "https://api.nasdaq.com/api/quote/list-type/nasdaq100";
+/// Community-maintained S&P 500 constituents, refreshed from Wikipedia.
+const SP500_URL: &str =
+    "https://raw.githubusercontent.com/datasets/s-and-p-500-companies/main/data/constituents.csv";
+
+/// Indexes refreshed, with the fewest symbols a fetched list may have.
+const SOURCES: [(&str, &str, usize); 2] =
+    [("sp500", SP500_URL, 400), ("nasdaq100", NASDAQ100_URL, 90)];
+
+/// One index's fetched membership, persisted in `constituents`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstituentList {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub index_id: String,
+    /// Normalized tickers, in source order.
+    pub symbols: Vec<String>,
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NasdaqListResponse {
+    data: Option<NasdaqListData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NasdaqListData {
+    data: Option<NasdaqListTable>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NasdaqListTable {
+    #[serde(default)]
+    rows: Vec<NasdaqListRow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NasdaqListRow {
+    symbol: Option<String>,
+}
+
+/// Tickers from NASDAQ's `list-type` response (`data.data.rows[].symbol`).
+pub fn parse_nasdaq_list(body: &str) -> Result<Vec<String>> {
+    let response: NasdaqListResponse = serde_json::from_str(body)?;
+    let rows = response
+        .data
+        .and_then(|data| data.data)
+        .map(|table| table.rows)
+        .ok_or_else(|| anyhow!("NASDAQ list response has no rows"))?;
+    let text = rows
+        .into_iter()
+        .filter_map(|row| row.symbol)
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(parse_constituent_list(&text))
+}
+
+/// Parse `body` fetched for `index_id` and reject lists shorter than
+/// `min_symbols`.
+fn parse_source(index_id: &str, body: &str, min_symbols: usize) -> Result<Vec<String>> {
+    let symbols = match index_id {
+        "nasdaq100" => parse_nasdaq_list(body)?,
+        _ => parse_constituent_list(body),
+    };
+    if symbols.len() < min_symbols {
+        bail!(
+            "{}: only {} symbols, expected at least {}",
+            index_id,
+            symbols.len(),
+            min_symbols
+        );
+    }
+    Ok(symbols)
+}
+
+/// Fetches, stores and applies current index membership.
+#[derive(Clone)]
+pub struct ConstituentUpdater {
+    client: reqwest::Client,
+    db: MongoDB,
+}
+
+impl ConstituentUpdater {
+    pub fn new(db: MongoDB) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+            .timeout(StdDuration::from_secs(30))
+            .build()
+            .expect("Failed to create constituents HTTP client");
+        Self { client, db }
+    }
+
+    /// Apply the lists stored by earlier refreshes. Returns how many
+    /// indexes were loaded.
+    pub async fn load_saved(&self) -> Result<usize> {
+        let mut loaded = 0;
+        for list in self.db.get_constituents().await? {
+            if IndexDataProvider::set_constituents(&list.index_id, list.symbols) {
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    async fn fetch(&self, url: &str) -> Result<String> {
+        let response = self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/json, text/csv, */*")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("{} returned {}", url, response.status());
+        }
+        Ok(response.text().await?)
+    }
+
+    /// Fetch every source, storing and applying each list that passes the
+    /// sanity check. Returns the ids of the indexes updated.
+    pub async fn refresh(&self) -> Vec<String> {
+        let mut updated = Vec::new();
+        for (index_id, url, min_symbols) in SOURCES {
+            let symbols = match self.fetch(url).await {
+                Ok(body) => parse_source(index_id, &body, min_symbols),
+                Err(e) => Err(e),
+            };
+            let symbols = match symbols {
+                Ok(symbols) => symbols,
+                Err(e) => {
+                    warn!("Keeping current {} constituents: {}", index_id, e);
+                    continue;
+                }
+            };
+            let list = ConstituentList {
+                id: None,
+                index_id: index_id.to_string(),
+                symbols,
+                source: url.to_string(),
+                updated_at: Utc::now(),
+            };
+            if let Err(e) = self.db.save_constituents(&list).await {
+                warn!("Failed to save {} constituents: {}", index_id, e);
+            }
+            if IndexDataProvider::set_constituents(index_id, list.symbols) {
+                updated.push(index_id.to_string());
+            }
+        }
+        updated
+    }
+
+    /// Refresh now and then every `hours` for the life of the process.
+    /// Skipped while read-only mode is on, since refreshes write.
+    pub fn spawn(self, hours: u64, maintenance: MaintenanceMode) {
+        tokio::spawn(async move {
+            loop {
+                if maintenance.is_read_only() {
+                    info!("📋 Constituent refresh skipped: read-only mode");
+                } else {
+                    let updated = self.refresh().await;
+                    if !updated.is_empty() {
+                        info!("📋 Index constituents refreshed: {}", updated.join(", "));
+                    }
+                }
+                tokio::time::sleep(StdDuration::from_secs(hours * 3600)).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nasdaq_list() {
+        let body = r#"{"data":{"data":{"headers":{},"rows":[
+            {"symbol":"AAPL","companyName":"Apple Inc."},
+            {"symbol":"brk.b"},
+            {"symbol":null},
+            {"symbol":"AAPL"}
+        ]}},"status":{"rCode":200}}"#;
+        assert_eq!(parse_nasdaq_list(body).unwrap(), vec!["AAPL", "BRK-B"]);
+        assert!(parse_nasdaq_list(r#"{"data":null}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_source_rejects_short_lists() {
+        let csv = "Symbol,Security,GICS Sector\nMMM,3M,Industrials\nAOS,A. O. Smith,Industrials\n";
+        assert_eq!(parse_source("sp500", csv, 2).unwrap(), vec!["MMM", "AOS"]);
+        assert!(parse_source("sp500", csv, 400).is_err());
+    }
+}
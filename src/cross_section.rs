@@ -0,0 +1,316 @@
+//! Nightly cross-sectional statistics: beta, benchmark correlation and RS rank.
+//!
+//! These need a year of daily closes per symbol and, for the RS rank, the
+//! whole universe at once, so they stay out of the per-symbol analysis. The
+//! cycle only stores each symbol's closes in `price_history`; once a day at
+//! `CROSS_SECTION_HOUR_UTC` (or on `POST /api/admin/cross-section`) [`run`]
+//! computes every symbol's `CrossSectionStats` from that stored history,
+//! saves them in `cross_section_stats` and on the analysis documents. The
+//! engine reloads them at the start of each cycle and copies them onto
+//! fresh analyses.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use mongodb::bson::{doc, oid::ObjectId};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::analysis::HISTORY_DAYS;
+use crate::db::MongoDB;
+use crate::indicators::TechnicalIndicators;
+use crate::maintenance::MaintenanceMode;
+use crate::models::{CrossSectionStats, HistoricalPrice};
+use crate::yahoo::{PriceAdjustment, YahooFinanceClient};
+
+pub const BENCHMARK: &str = "SPY";
+/// Daily returns beta and correlation are measured over.
+const RETURN_SESSIONS: usize = 252;
+/// Fewer overlapping returns than this gives no beta or correlation.
+const MIN_RETURNS: usize = 60;
+/// Closes kept per symbol in `price_history`.
+const HISTORY_SESSIONS: usize = 260;
+/// `(sessions back, weight)` of the RS score, most recent quarter doubled.
+const RS_WEIGHTS: [(usize, f64); 4] = [(63, 0.4), (126, 0.2), (189, 0.2), (252, 0.2)];
+
+/// Only one batch runs at a time, whether scheduled or on demand.
+static BATCH_RUNNING: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Daily closes for one symbol, oldest first. Persisted in `price_history`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PriceHistory {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub symbol: String,
+    /// `YYYY-MM-DD`, parallel to `closes`.
+    pub dates: Vec<String>,
+    pub closes: Vec<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PriceHistory {
+    pub fn from_prices(symbol: &str, prices: &[HistoricalPrice]) -> Self {
+        let recent = &prices[prices.len().saturating_sub(HISTORY_SESSIONS)..];
+        Self {
+            id: None,
+            symbol: symbol.to_string(),
+            dates: recent
+                .iter()
+                .map(|p| p.date.date_naive().format("%Y-%m-%d").to_string())
+                .collect(),
+            closes: recent.iter().map(|p| p.close).collect(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Daily returns of `stock` and `benchmark` over the dates both have,
+/// the last `RETURN_SESSIONS` of them.
+fn aligned_returns(stock: &PriceHistory, benchmark: &PriceHistory) -> (Vec<f64>, Vec<f64>) {
+    let bench: HashMap<&str, f64> = benchmark
+        .dates
+        .iter()
+        .map(String::as_str)
+        .zip(benchmark.closes.iter().copied())
+        .collect();
+    let common: Vec<(f64, f64)> = stock
+        .dates
+        .iter()
+        .zip(&stock.closes)
+        .filter_map(|(date, &close)| Some((close, *bench.get(date.as_str())?)))
+        .collect();
+    let (mut stock_returns, mut bench_returns) = (Vec::new(), Vec::new());
+    for pair in common.windows(2) {
+        let ((s0, b0), (s1, b1)) = (pair[0], pair[1]);
+        if s0 > 0.0 && b0 > 0.0 {
+            stock_returns.push(s1 / s0 - 1.0);
+            bench_returns.push(b1 / b0 - 1.0);
+        }
+    }
+    let skip = stock_returns.len().saturating_sub(RETURN_SESSIONS);
+    (stock_returns.split_off(skip), bench_returns.split_off(skip))
+}
+
+fn beta(stock: &[f64], benchmark: &[f64]) -> Option<f64> {
+    let n = stock.len().min(benchmark.len());
+    if n < MIN_RETURNS {
+        return None;
+    }
+    let mean = |xs: &[f64]| xs.iter().sum::<f64>() / n as f64;
+    let (mean_s, mean_b) = (mean(&stock[..n]), mean(&benchmark[..n]));
+    let (mut cov, mut var) = (0.0, 0.0);
+    for i in 0..n {
+        cov += (stock[i] - mean_s) * (benchmark[i] - mean_b);
+        var += (benchmark[i] - mean_b).powi(2);
+    }
+    (var > 0.0).then(|| cov / var)
+}
+
+/// Weighted 3/6/9/12-month return, or `None` under a year of closes.
+fn rs_score(closes: &[f64]) -> Option<f64> {
+    let last = *closes.last()?;
+    RS_WEIGHTS
+        .iter()
+        .try_fold(0.0, |score, &(sessions, weight)| {
+            let base = *closes.get(closes.len().checked_sub(sessions + 1)?)?;
+            (base > 0.0).then(|| score + weight * (last / base - 1.0))
+        })
+}
+
+/// 1-99 percentile of each score, highest score 99.
+fn rs_ranks(scores: &[(String, f64)]) -> HashMap<String, u8> {
+    let mut sorted: Vec<&(String, f64)> = scores.iter().collect();
+    sorted.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let n = sorted.len() as f64;
+    sorted
+        .into_iter()
+        .enumerate()
+        .map(|(i, (symbol, _))| {
+            let rank = ((i + 1) as f64 / n * 99.0).ceil().clamp(1.0, 99.0);
+            (symbol.clone(), rank as u8)
+        })
+        .collect()
+}
+
+/// Stats for every symbol with stored history.
+fn compute(
+    histories: &[PriceHistory],
+    benchmark: &PriceHistory,
+    now: DateTime<Utc>,
+) -> Vec<CrossSectionStats> {
+    let scores: Vec<(String, f64)> = histories
+        .iter()
+        .filter_map(|h| Some((h.symbol.clone(), rs_score(&h.closes)?)))
+        .collect();
+    let ranks = rs_ranks(&scores);
+    histories
+        .iter()
+        .filter_map(|history| {
+            let as_of = history.dates.last()?.clone();
+            let (stock, bench) = aligned_returns(history, benchmark);
+            let correlation = if stock.len() >= MIN_RETURNS {
+                TechnicalIndicators::calculate_correlation(&stock, &bench)
+            } else {
+                None
+            };
+            Some(CrossSectionStats {
+                symbol: history.symbol.clone(),
+                beta: beta(&stock, &bench),
+                correlation,
+                rs_rank: ranks.get(&history.symbol).copied(),
+                benchmark: BENCHMARK.to_string(),
+                as_of,
+                computed_at: now,
+            })
+        })
+        .collect()
+}
+
+/// Refresh the benchmark's history, then recompute and store every symbol's
+/// stats. Returns how many symbols were updated.
+pub async fn run(db: &MongoDB, yahoo: &YahooFinanceClient) -> Result<usize> {
+    let _running = BATCH_RUNNING
+        .try_lock()
+        .map_err(|_| anyhow!("the cross-section batch is already running"))?;
+
+    let prices = yahoo
+        .get_historical_prices(BENCHMARK, HISTORY_DAYS, PriceAdjustment::Adjusted)
+        .await?;
+    if prices.is_empty() {
+        return Err(anyhow!("no {} history", BENCHMARK));
+    }
+    let benchmark = PriceHistory::from_prices(BENCHMARK, &prices);
+    db.save_price_history(&benchmark).await?;
+
+    let histories: Vec<PriceHistory> = db
+        .get_price_histories()
+        .await?
+        .into_iter()
+        .filter(|h| h.symbol != BENCHMARK)
+        .collect();
+    let stats = compute(&histories, &benchmark, Utc::now());
+    for entry in &stats {
+        db.save_cross_section_stats(entry).await?;
+        db.update_analysis_fields(
+            &entry.symbol,
+            doc! { "cross_section": mongodb::bson::to_bson(entry)? },
+        )
+        .await?;
+    }
+    Ok(stats.len())
+}
+
+/// Time until the next `hour`:00 UTC.
+fn until_next_run(now: DateTime<Utc>, hour: u32) -> std::time::Duration {
+    let at = NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or(NaiveTime::MIN);
+    let mut next = now.date_naive().and_time(at).and_utc();
+    if next <= now {
+        next += Duration::days(1);
+    }
+    (next - now).to_std().unwrap_or_default()
+}
+
+/// Run the batch every day at `hour`:00 UTC for the life of the process.
+/// Skipped while read-only mode is on.
+pub fn spawn(db: MongoDB, yahoo: YahooFinanceClient, hour: u32, maintenance: MaintenanceMode) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(until_next_run(Utc::now(), hour)).await;
+            if maintenance.is_read_only() {
+                info!("📐 Cross-section batch skipped: read-only mode");
+                continue;
+            }
+            match run(&db, &yahoo).await {
+                Ok(updated) => info!("📐 Cross-section stats updated for {} symbols", updated),
+                Err(e) => warn!("Cross-section batch failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn history(symbol: &str, closes: &[f64]) -> PriceHistory {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        PriceHistory {
+            id: None,
+            symbol: symbol.to_string(),
+            dates: (0..closes.len())
+                .map(|i| {
+                    (start + Duration::days(i as i64))
+                        .format("%Y-%m-%d")
+                        .to_string()
+                })
+                .collect(),
+            closes: closes.to_vec(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Closes compounding the given daily returns from 100.
+    fn compound(returns: impl Iterator<Item = f64>) -> Vec<f64> {
+        let mut close = 100.0;
+        std::iter::once(close)
+            .chain(returns.map(|r| {
+                close *= 1.0 + r;
+                close
+            }))
+            .collect()
+    }
+
+    #[test]
+    fn test_beta_and_correlation_against_benchmark() {
+        let bench_returns: Vec<f64> = (0..120).map(|i| ((i % 7) as f64 - 3.0) / 100.0).collect();
+        let benchmark = history(BENCHMARK, &compound(bench_returns.iter().copied()));
+        let levered = history("LEV", &compound(bench_returns.iter().map(|r| r * 2.0)));
+
+        let stats = compute(&[levered], &benchmark, Utc::now());
+        assert_eq!(stats.len(), 1);
+        assert!((stats[0].beta.unwrap() - 2.0).abs() < 1e-9);
+        assert!((stats[0].correlation.unwrap() - 1.0).abs() < 1e-9);
+        // Under a year of closes: no RS rank.
+        assert_eq!(stats[0].rs_rank, None);
+
+        // Too little overlap with the benchmark for a meaningful beta.
+        let short = history("NEW", &compound(bench_returns.iter().take(20).copied()));
+        let stats = compute(&[short], &benchmark, Utc::now());
+        assert_eq!(stats[0].beta, None);
+        assert_eq!(stats[0].correlation, None);
+    }
+
+    #[test]
+    fn test_rs_rank_orders_the_universe() {
+        let rising = |daily: f64| compound(std::iter::repeat_n(daily, 260));
+        let histories = [
+            history("FAST", &rising(0.004)),
+            history("SLOW", &rising(0.001)),
+            history("DOWN", &rising(-0.002)),
+        ];
+        let benchmark = history(BENCHMARK, &rising(0.001));
+        let ranks: HashMap<String, Option<u8>> = compute(&histories, &benchmark, Utc::now())
+            .into_iter()
+            .map(|s| (s.symbol, s.rs_rank))
+            .collect();
+        assert_eq!(ranks["FAST"], Some(99));
+        assert_eq!(ranks["SLOW"], Some(66));
+        assert_eq!(ranks["DOWN"], Some(33));
+    }
+
+    #[test]
+    fn test_until_next_run() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 2, 4, 30, 0).unwrap();
+        assert_eq!(
+            until_next_run(now, 6),
+            std::time::Duration::from_secs(90 * 60)
+        );
+        assert_eq!(
+            until_next_run(now, 4),
+            std::time::Duration::from_secs(23 * 3600 + 30 * 60)
+        );
+    }
+}
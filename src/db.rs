@@ -1,14 +1,65 @@
+use crate::asset_types::{self, AssetType};
+use crate::constituents::ConstituentList;
+use crate::cross_section::PriceHistory;
+use crate::highs_lows::{Week52Event, Week52Kind};
+use crate::incremental::IndicatorState;
+use crate::indexes::{IndexContributors, IndexPerformance};
+use crate::ingest::ExternalSignal;
 use crate::models::{
-    AggregatedNewsItem, MarketSummary, SectorPerformance, Stock, StockAnalysis, StockFilter,
+    AIAnalysisResponse, AggregatedNewsItem, AnalysisHistoryPoint, CachePin, CrossSectionStats,
+    CrossSignal, DeadLetter, FailureRecord, MarketSummary, Seasonality, SectorPerformance, Stock,
+    StockAnalysis, StockFilter, SymbolAlias, SymbolCycleStatus, SymbolMatch, SymbolProgress,
+    UniverseName,
 };
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use crate::notes::SymbolNote;
+use crate::percentiles::{PercentileMetric, PercentileRanks};
+use crate::query_profiler::QueryProfiler;
+use crate::repair::{self, CorruptDocument};
+use crate::runs::AnalysisRun;
+use crate::screens::ScreenResult;
+use crate::sectors::SectorEtfSnapshot;
+use crate::share_classes;
+use crate::signals::SignalRecord;
+use crate::themes::Theme;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use futures::stream::StreamExt;
 use mongodb::{
-    bson::{doc, Bson, Document, Regex},
-    options::{ClientOptions, FindOptions, ServerApi, ServerApiVersion},
+    bson::{doc, Bson, Document, RawDocumentBuf, Regex},
+    event::EventHandler,
+    options::{
+        Acknowledgment, ClientOptions, CollectionOptions, FindOptions, ReadPreference,
+        ReturnDocument, SelectionCriteria, ServerApi, ServerApiVersion, WriteConcern,
+    },
     Client, Collection, Database,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::IntoFuture;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Weekend deep-analysis collections holding one document per symbol.
+const SYMBOL_KEYED_WEEKEND_COLLECTIONS: [&str; 3] =
+    ["long_price_history", "seasonality", "ai_summaries"];
+
+/// Per-symbol collections `prune_orphaned_symbol_docs` clears of symbols
+/// without an analysis, and `migrate_symbol` carries over to a new ticker.
+const ORPHAN_PRUNED_COLLECTIONS: [&str; 6] = [
+    "price_history",
+    "indicator_state",
+    "long_price_history",
+    "cross_section_stats",
+    "seasonality",
+    "ai_summaries",
+];
+
+/// Failures kept per symbol in `dead_letters`.
+const DEAD_LETTER_HISTORY: usize = 10;
+
+/// Listing upserts `replace_stock_listings` sends at once.
+const LISTING_UPSERTS_IN_FLIGHT: usize = 16;
 
 /// Escape regex metacharacters so the `symbol_search` filter only ever does
 /// substring matching. Symbols are alphanumeric in practice but we treat the
@@ -57,6 +108,53 @@ fn insert_range(filter_doc: &mut Document, field: &str, min: Option<f64>, max: O
     filter_doc.insert(field, range);
 }
 
+/// Fields `get_latest_analyses` can sort by. Checked against the
+/// `stock_analysis` indexes at startup.
+const SORT_FIELDS: &[&str] = &[
+    "market_cap",
+    "price_change_percent",
+    "rsi",
+    "price",
+    "analyzed_at",
+    "volume",
+    "performance.return_1w_pct",
+    "performance.return_1m_pct",
+    "performance.return_3m_pct",
+    "performance.return_ytd_pct",
+    "sharpe_ratio",
+    "sortino_ratio",
+];
+
+/// `StockFilter::return_period` values and the field each filters on.
+const RETURN_PERIODS: &[(&str, &str)] = &[
+    ("1w", "performance.return_1w_pct"),
+    ("1m", "performance.return_1m_pct"),
+    ("3m", "performance.return_3m_pct"),
+    ("ytd", "performance.return_ytd_pct"),
+];
+
+/// The return field for `period`, `1m` when unset. `None` for an unknown
+/// period.
+fn return_field(period: Option<&str>) -> Option<&'static str> {
+    let period = period
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .unwrap_or("1m");
+    RETURN_PERIODS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(period))
+        .map(|(_, field)| *field)
+}
+
+/// Whether an index can serve an unfiltered sort on `field`: one whose key
+/// pattern leads with it (MongoDB walks an index either way, so direction
+/// doesn't matter).
+fn sort_has_index(index_keys: &[Document], field: &str) -> bool {
+    index_keys
+        .iter()
+        .any(|keys| keys.keys().next().is_some_and(|first| first == field))
+}
+
 fn allowed_sort_field(sort_by: Option<&str>) -> &'static str {
     match sort_by {
         Some("price") => "price",
@@ -64,11 +162,73 @@ fn allowed_sort_field(sort_by: Option<&str>) -> &'static str {
         Some("rsi") => "rsi",
         Some("analyzed_at") => "analyzed_at",
         Some("volume") => "volume",
+        Some("return_1w_pct") => "performance.return_1w_pct",
+        Some("return_1m_pct") => "performance.return_1m_pct",
+        Some("return_3m_pct") => "performance.return_3m_pct",
+        Some("return_ytd_pct") => "performance.return_ytd_pct",
+        Some("sharpe_ratio") => "sharpe_ratio",
+        Some("sortino_ratio") => "sortino_ratio",
         Some("market_cap") | None => "market_cap",
         Some(_) => "market_cap",
     }
 }
 
+/// Sort document, skip and limit for one page of a `StockFilter` listing.
+pub(crate) fn list_page(filter: &StockFilter) -> (Document, u64, i64) {
+    let sort_field = allowed_sort_field(filter.sort_by.as_deref());
+    let sort_order = if filter.sort_order.as_deref() == Some("asc") {
+        1
+    } else {
+        -1
+    };
+    let page = filter.page.unwrap_or(1).max(1) as i64;
+    let page_size = filter.page_size.unwrap_or(50).min(200) as i64;
+    let skip = (page - 1) * page_size;
+    (doc! { sort_field: sort_order }, skip as u64, page_size)
+}
+
+/// Reject the filter values `build_filter_doc` would otherwise ignore or
+/// fall back on.
+pub(crate) fn check_filter(filter: &StockFilter) -> Result<()> {
+    if return_field(filter.return_period.as_deref()).is_none() {
+        return Err(anyhow!(
+            "Unknown return_period '{}' (use 1w, 1m, 3m or ytd)",
+            filter.return_period.as_deref().unwrap_or_default()
+        ));
+    }
+    let percentile_bound = filter.min_percentile.is_some() || filter.max_percentile.is_some();
+    match filter
+        .percentile_metric
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        Some(name) if PercentileMetric::from_name(name).is_none() => {
+            return Err(anyhow!(
+                "Unknown percentile_metric '{}' (use rsi, price_change_percent, volume_ratio, pe_ratio, return_1m_pct, return_3m_pct or rs_rank)",
+                name
+            ));
+        }
+        None if percentile_bound => {
+            return Err(anyhow!(
+                "percentile_metric is required with min_percentile / max_percentile"
+            ));
+        }
+        _ => {}
+    }
+    asset_types::parse_filter(filter.asset_type.as_deref())?;
+    Ok(())
+}
+
+/// The theme id a filter asks for, if any; resolved by the storage backend.
+pub(crate) fn filter_theme(filter: &StockFilter) -> Option<&str> {
+    filter
+        .theme
+        .as_ref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+}
+
 fn price_change_summary_filter(min: f64, max: Option<f64>) -> Document {
     let mut d = doc! { "$exists": true, "$ne": Bson::Null };
     d.insert("$gt", min);
@@ -111,6 +271,14 @@ pub(crate) fn build_filter_doc(filter: &StockFilter) -> Document {
         filter.min_stochastic_k,
         filter.max_stochastic_k,
     );
+    insert_range(
+        &mut filter_doc,
+        "williams_r",
+        filter.min_williams_r,
+        filter.max_williams_r,
+    );
+    insert_range(&mut filter_doc, "cci", filter.min_cci, filter.max_cci);
+    insert_range(&mut filter_doc, "mfi", filter.min_mfi, filter.max_mfi);
     insert_range(
         &mut filter_doc,
         "bollinger.bandwidth",
@@ -118,6 +286,34 @@ pub(crate) fn build_filter_doc(filter: &StockFilter) -> Document {
         filter.max_bandwidth,
     );
 
+    if let Some(field) = return_field(filter.return_period.as_deref()) {
+        insert_range(
+            &mut filter_doc,
+            field,
+            filter.min_return_pct,
+            filter.max_return_pct,
+        );
+    }
+
+    if let Some(metric) = filter
+        .percentile_metric
+        .as_deref()
+        .and_then(PercentileMetric::from_name)
+    {
+        insert_range(
+            &mut filter_doc,
+            &format!("percentiles.{}", metric.name()),
+            filter.min_percentile,
+            filter.max_percentile,
+        );
+    }
+    insert_range(
+        &mut filter_doc,
+        "percentiles.rs_rank",
+        filter.min_rs_rank,
+        None,
+    );
+
     if let Some(sectors) = &filter.sectors {
         if !sectors.is_empty() {
             filter_doc.insert("sector", doc! { "$in": sectors.clone() });
@@ -129,6 +325,28 @@ pub(crate) fn build_filter_doc(filter: &StockFilter) -> Document {
     if let Some(true) = filter.only_overbought {
         filter_doc.insert("is_overbought", true);
     }
+    if let Some(true) = filter.only_stoch_oversold {
+        filter_doc.insert("stoch_oversold", true);
+    }
+    if let Some(true) = filter.only_stoch_overbought {
+        filter_doc.insert("stoch_overbought", true);
+    }
+    match filter.cross_signal {
+        Some(CrossSignal::GoldenCross) => {
+            filter_doc.insert("cross_signal", "golden_cross");
+        }
+        Some(CrossSignal::DeathCross) => {
+            filter_doc.insert("cross_signal", "death_cross");
+        }
+        // Analyses saved before crosses were detected have no field.
+        Some(CrossSignal::None) => {
+            filter_doc.insert("cross_signal", doc! { "$in": ["none", Bson::Null] });
+        }
+        None => {}
+    }
+    if let Some(uptrend) = filter.supertrend_uptrend {
+        filter_doc.insert("supertrend.uptrend", uptrend);
+    }
 
     if let Some(q) = filter
         .symbol_search
@@ -145,6 +363,39 @@ pub(crate) fn build_filter_doc(filter: &StockFilter) -> Document {
         );
     }
 
+    if let Some(true) = filter.primary_class_only {
+        let exclude = share_classes::secondary_classes();
+        let symbol_filter = match filter_doc.remove("symbol") {
+            Some(search) => doc! { "$regex": search, "$nin": exclude },
+            None => doc! { "$nin": exclude },
+        };
+        filter_doc.insert("symbol", symbol_filter);
+    }
+
+    if let Some(index) = filter
+        .index
+        .as_ref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        filter_doc.insert("indexes", index.to_lowercase());
+    }
+
+    if let Some(tags) = &filter.tags {
+        let tags = crate::notes::normalize_tags(tags);
+        if !tags.is_empty() {
+            filter_doc.insert("tags", doc! { "$in": tags });
+        }
+    }
+
+    // Invalid values are rejected by `MongoDB::filter_doc`; here they fall
+    // back to the equities-only default.
+    let asset_types = asset_types::parse_filter(filter.asset_type.as_deref())
+        .unwrap_or_else(|_| Some(vec![AssetType::Equity]));
+    if let Some(types) = asset_types {
+        filter_doc.insert("asset_type", asset_type_clause(&types));
+    }
+
     // Cap |price_change_percent| to drop runaway gainers/losers from the feed.
     if let Some(max_abs) = filter.max_abs_price_change_percent {
         let max_abs = max_abs.abs();
@@ -157,20 +408,170 @@ pub(crate) fn build_filter_doc(filter: &StockFilter) -> Document {
     filter_doc
 }
 
+/// Match analyses of any of `types`. Analyses saved before classification
+/// have no `asset_type` and count as equities.
+pub(crate) fn asset_type_clause(types: &[AssetType]) -> Document {
+    let mut values: Vec<Bson> = types.iter().map(|t| Bson::from(t.name())).collect();
+    if types.contains(&AssetType::Equity) {
+        values.push(Bson::Null);
+    }
+    doc! { "$in": values }
+}
+
+/// Restrict `filter_doc` to `symbols`. Goes through `$and` so it composes
+/// with the symbol search and share-class filters.
+pub(crate) fn scope_to_symbols(filter_doc: &mut Document, symbols: &[String]) {
+    filter_doc.insert("$and", vec![doc! { "symbol": { "$in": symbols } }]);
+}
+
+/// External signals received since `since`, optionally for one symbol.
+fn external_signals_filter(since: DateTime<Utc>, symbol: Option<&str>) -> Document {
+    // `received_at` is stored as an RFC 3339 string, which sorts
+    // chronologically.
+    let mut filter = doc! {
+        "received_at": {
+            "$gte": since.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+        }
+    };
+    if let Some(symbol) = symbol {
+        filter.insert("symbol", crate::symbols::normalize_symbol_key(symbol));
+    }
+    filter
+}
+
+/// Optimistic-concurrency field on `stock_analysis` documents; see
+/// `MongoDB::save_analysis`.
+const VERSION_FIELD: &str = "version";
+
+pub(crate) fn analysis_version(analysis: &StockAnalysis) -> i64 {
+    analysis.analyzed_at.timestamp_micros()
+}
+
+/// Matches `symbol` only when its stored version is at most `version`.
+/// Documents written before versioning have none and always match.
+fn stale_guard(symbol: &str, version: i64) -> Document {
+    doc! {
+        "symbol": symbol,
+        "$or": [
+            { VERSION_FIELD: { "$lte": version } },
+            { VERSION_FIELD: { "$exists": false } },
+        ],
+    }
+}
+
+/// Fields `update_analysis_fields` may not set: identity, and the fields
+/// `save_analysis` orders writes by.
+const PROTECTED_FIELDS: &[&str] = &["_id", "symbol", VERSION_FIELD, "analyzed_at"];
+
+fn partial_update(fields: Document) -> Result<Document> {
+    if fields.is_empty() {
+        return Err(anyhow!("partial update has no fields"));
+    }
+    if let Some(key) = fields.keys().find(|key| {
+        let top = key.split('.').next().unwrap_or(key);
+        key.starts_with('$') || PROTECTED_FIELDS.contains(&top)
+    }) {
+        return Err(anyhow!("field '{}' can't be set by a partial update", key));
+    }
+    Ok(doc! { "$set": fields })
+}
+
+/// Connection tuning from `Config`. `None` keeps the value from the URI, or
+/// the driver default.
+#[derive(Debug, Clone, Default)]
+pub struct MongoSettings {
+    /// Commands at or over this are logged; `Duration::ZERO` disables the log.
+    pub slow_query: Duration,
+    pub max_pool_size: Option<u32>,
+    pub min_pool_size: Option<u32>,
+    pub connect_timeout: Option<Duration>,
+    pub server_selection_timeout: Option<Duration>,
+    pub read_preference: Option<ReadPreference>,
+    pub write_concern: Option<WriteConcern>,
+    /// Send the heavy list queries (filters, market summary, sectors, news)
+    /// to secondaries when one is available.
+    pub secondary_list_reads: bool,
+}
+
+/// `primary`, `primaryPreferred`, `secondary`, `secondaryPreferred` or
+/// `nearest` (case-insensitive; `_` and `-` are ignored).
+pub fn parse_read_preference(mode: &str) -> Result<ReadPreference> {
+    let normalized: String = mode
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    Ok(match normalized.as_str() {
+        "primary" => ReadPreference::Primary,
+        "primarypreferred" => ReadPreference::PrimaryPreferred { options: None },
+        "secondary" => ReadPreference::Secondary { options: None },
+        "secondarypreferred" => ReadPreference::SecondaryPreferred { options: None },
+        "nearest" => ReadPreference::Nearest { options: None },
+        _ => return Err(anyhow!("unknown read preference '{}'", mode)),
+    })
+}
+
+/// `majority`, a node count (at least 1), or a custom tag-set name.
+pub fn parse_write_concern(w: &str) -> Result<WriteConcern> {
+    let w = w.trim();
+    let acknowledgment = if w.eq_ignore_ascii_case("majority") {
+        Acknowledgment::Majority
+    } else if let Ok(nodes) = w.parse::<u32>() {
+        if nodes == 0 {
+            return Err(anyhow!("unacknowledged writes (w=0) are not supported"));
+        }
+        Acknowledgment::Nodes(nodes)
+    } else if !w.is_empty() {
+        Acknowledgment::Custom(w.to_string())
+    } else {
+        return Err(anyhow!("empty write concern"));
+    };
+    Ok(WriteConcern::builder().w(acknowledgment).build())
+}
+
 #[derive(Clone)]
 pub struct MongoDB {
     client: Client,
     database: Database,
+    profiler: Arc<QueryProfiler>,
+    secondary_list_reads: bool,
 }
 
 impl MongoDB {
-    pub async fn new(uri: &str, database_name: &str) -> Result<Self> {
+    /// Connect and create indexes.
+    pub async fn new(uri: &str, database_name: &str, settings: MongoSettings) -> Result<Self> {
         let mut client_options = ClientOptions::parse(uri).await?;
 
         // Set the server API version
         let server_api = ServerApi::builder().version(ServerApiVersion::V1).build();
         client_options.server_api = Some(server_api);
 
+        if settings.max_pool_size.is_some() {
+            client_options.max_pool_size = settings.max_pool_size;
+        }
+        if settings.min_pool_size.is_some() {
+            client_options.min_pool_size = settings.min_pool_size;
+        }
+        if settings.connect_timeout.is_some() {
+            client_options.connect_timeout = settings.connect_timeout;
+        }
+        if settings.server_selection_timeout.is_some() {
+            client_options.server_selection_timeout = settings.server_selection_timeout;
+        }
+        if let Some(read_preference) = settings.read_preference {
+            client_options.selection_criteria =
+                Some(SelectionCriteria::ReadPreference(read_preference));
+        }
+        if settings.write_concern.is_some() {
+            client_options.write_concern = settings.write_concern;
+        }
+
+        // Time every command for the slow-query log and /api/admin/db/stats
+        let profiler = Arc::new(QueryProfiler::new(settings.slow_query));
+        let handler = Arc::clone(&profiler);
+        client_options.command_event_handler =
+            Some(EventHandler::callback(move |event| handler.on_event(event)));
+
         let client = Client::with_options(client_options)?;
 
         // Test connection
@@ -184,7 +585,52 @@ impl MongoDB {
         // Create indexes
         Self::create_indexes(&database).await?;
 
-        Ok(MongoDB { client, database })
+        Ok(MongoDB {
+            client,
+            database,
+            profiler,
+            secondary_list_reads: settings.secondary_list_reads,
+        })
+    }
+
+    /// A handle whose server never answers, for router tests and `--demo`:
+    /// nothing is pinged or indexed, and every query fails after a short
+    /// server selection timeout.
+    pub async fn unreachable() -> Self {
+        let options = ClientOptions::parse("mongodb://127.0.0.1:9/?serverSelectionTimeoutMS=100")
+            .await
+            .unwrap();
+        let client = Client::with_options(options).unwrap();
+        MongoDB {
+            database: client.database("auto_analyser_test"),
+            client,
+            profiler: Arc::new(QueryProfiler::new(Duration::ZERO)),
+            secondary_list_reads: false,
+        }
+    }
+
+    /// Per-query timings since startup (or the last reset).
+    pub fn profiler(&self) -> &QueryProfiler {
+        &self.profiler
+    }
+
+    /// Warn about every `SORT_FIELDS` entry no index leads with: sorting by
+    /// it means an in-memory sort over the whole filtered set.
+    async fn check_sort_indexes(collection: &Collection<StockAnalysis>) -> Result<()> {
+        let mut cursor = collection.list_indexes().await?;
+        let mut index_keys = Vec::new();
+        while let Some(index) = cursor.next().await {
+            index_keys.push(index?.keys);
+        }
+        for field in SORT_FIELDS {
+            if !sort_has_index(&index_keys, field) {
+                warn!(
+                    "No index covers sorting stock_analysis by `{}`; those list queries sort in memory",
+                    field
+                );
+            }
+        }
+        Ok(())
     }
 
     async fn create_indexes(database: &Database) -> Result<()> {
@@ -208,227 +654,1587 @@ impl MongoDB {
             )
             .await?;
 
-        Ok(())
-    }
+        // One signal per strategy/symbol/day; also serves the open-signal lookup
+        let signals_collection: Collection<SignalRecord> = database.collection("signals");
+        signals_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1, "strategy": 1, "signal_date": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-    pub fn analysis_collection(&self) -> Collection<StockAnalysis> {
-        self.database.collection("stock_analysis")
-    }
+        // One stored close series and one stats row per symbol
+        let price_history_collection: Collection<PriceHistory> =
+            database.collection("price_history");
+        price_history_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
+        let indicator_state_collection: Collection<IndicatorState> =
+            database.collection("indicator_state");
+        indicator_state_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
+        let cross_section_collection: Collection<CrossSectionStats> =
+            database.collection("cross_section_stats");
+        cross_section_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-    /// Raw handle to the Mongo database — exposed so sibling modules (e.g.
-    /// `notifications::repo`) can register their own collections without
-    /// cluttering `MongoDB` with notification-specific accessors.
-    pub fn database(&self) -> &Database {
-        &self.database
-    }
+        // Fetched index membership, one document per index
+        database
+            .collection::<Document>("constituents")
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "index_id": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-    pub fn stocks_collection(&self) -> Collection<Stock> {
-        self.database.collection("stocks")
-    }
+        // Screener listings: symbol prefixes and `/api/search` text matches
+        let stocks_collection: Collection<Stock> = database.collection("stocks");
+        stocks_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1 })
+                    .build(),
+            )
+            .await?;
+        stocks_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": "text", "name": "text" })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .weights(doc! { "symbol": 10, "name": 1 })
+                            .name("symbol_name_text".to_string())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-    pub async fn save_analysis(&self, analysis: &StockAnalysis) -> Result<()> {
-        let collection = self.analysis_collection();
+        // Weekend deep-analysis output, one document per symbol
+        for name in SYMBOL_KEYED_WEEKEND_COLLECTIONS {
+            database
+                .collection::<Document>(name)
+                .create_index(
+                    mongodb::IndexModel::builder()
+                        .keys(doc! { "symbol": 1 })
+                        .options(
+                            mongodb::options::IndexOptions::builder()
+                                .unique(true)
+                                .build(),
+                        )
+                        .build(),
+                )
+                .await?;
+        }
 
-        // Update or insert
-        collection
-            .update_one(
-                doc! { "symbol": &analysis.symbol },
-                doc! { "$set": mongodb::bson::to_document(analysis)? },
+        // One 52-week event per kind/symbol/day; also serves the date range scan
+        let week52_collection: Collection<Week52Event> = database.collection("week52_events");
+        week52_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1, "kind": 1, "date": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
+        week52_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "date": -1 })
+                    .build(),
             )
-            .upsert(true)
             .await?;
 
-        Ok(())
-    }
+        // External signals by symbol, and the startup scan of recent ones
+        let external_signals: Collection<ExternalSignal> = database.collection("external_signals");
+        external_signals
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1, "received_at": -1 })
+                    .build(),
+            )
+            .await?;
+        external_signals
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "received_at": -1 })
+                    .build(),
+            )
+            .await?;
 
-    /// Get analysis for a specific symbol
-    pub async fn get_analysis_by_symbol(&self, symbol: &str) -> Result<Option<StockAnalysis>> {
-        let collection = self.analysis_collection();
-        let symbol = crate::symbols::normalize_symbol_key(symbol);
+        // Index membership tags, for `StockFilter::index`
+        analysis_collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "indexes": 1 })
+                    .build(),
+            )
+            .await?;
 
-        match collection.find_one(doc! { "symbol": symbol }).await? {
-            Some(analysis) => Ok(Some(analysis)),
-            None => Ok(None),
+        // Compound indexes for the common filter/sort combinations: the
+        // default market-cap sort, sector screens sorted by size, RSI ranges
+        // over recent analyses, gainers/losers, trailing-return and
+        // risk-adjusted leaders.
+        for keys in [
+            doc! { "market_cap": -1 },
+            doc! { "sector": 1, "market_cap": -1 },
+            doc! { "rsi": 1, "analyzed_at": -1 },
+            doc! { "price_change_percent": -1 },
+            doc! { "performance.return_1w_pct": -1 },
+            doc! { "performance.return_1m_pct": -1 },
+            doc! { "performance.return_3m_pct": -1 },
+            doc! { "performance.return_ytd_pct": -1 },
+            doc! { "sharpe_ratio": -1 },
+            doc! { "sortino_ratio": -1 },
+        ] {
+            analysis_collection
+                .create_index(mongodb::IndexModel::builder().keys(keys).build())
+                .await?;
         }
-    }
+        Self::check_sort_indexes(&analysis_collection).await?;
 
-    pub async fn get_latest_analyses(&self, filter: StockFilter) -> Result<Vec<StockAnalysis>> {
-        let collection = self.analysis_collection();
-        let filter_doc = build_filter_doc(&filter);
+        let cycle_symbols: Collection<SymbolProgress> = database.collection("cycle_symbols");
+        cycle_symbols
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
+        cycle_symbols
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "status": 1 })
+                    .build(),
+            )
+            .await?;
 
-        // Build sort document
-        let sort_field = allowed_sort_field(filter.sort_by.as_deref());
-        let sort_order = if filter.sort_order.as_deref() == Some("asc") {
-            1
-        } else {
-            -1
-        };
-        let sort_doc = doc! { sort_field: sort_order };
+        let index_performance: Collection<IndexPerformance> =
+            database.collection("index_performance");
+        index_performance
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "index_id": 1, "date": -1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-        // Pagination
-        let page = filter.page.unwrap_or(1).max(1) as i64;
-        let page_size = filter.page_size.unwrap_or(50).min(200) as i64;
-        let skip = (page - 1) * page_size;
+        let index_contributors: Collection<IndexContributors> =
+            database.collection("index_contributors");
+        index_contributors
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "index_id": 1, "date": -1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-        let find_options = FindOptions::builder()
-            .sort(sort_doc)
-            .skip(skip as u64)
-            .limit(page_size)
-            .build();
+        let screen_results: Collection<ScreenResult> = database.collection("screen_results");
+        screen_results
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "name": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-        let mut cursor = collection
-            .find(filter_doc)
-            .with_options(find_options)
+        let themes: Collection<Theme> = database.collection("themes");
+        themes
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "id": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
             .await?;
 
-        let mut results = Vec::new();
-        while let Some(doc) = cursor.next().await {
-            if let Ok(analysis) = doc {
-                results.push(analysis);
-            }
-        }
-        Ok(results)
-    }
+        let symbol_notes: Collection<SymbolNote> = database.collection("symbol_notes");
+        symbol_notes
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-    /// Get total count for a filter (for pagination)
-    pub async fn get_filtered_count(&self, filter: StockFilter) -> Result<u64> {
-        let collection = self.analysis_collection();
-        let filter_doc = build_filter_doc(&filter);
-        Ok(collection.count_documents(filter_doc).await?)
-    }
+        let theme_performance: Collection<IndexPerformance> =
+            database.collection("theme_performance");
+        theme_performance
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "index_id": 1, "date": -1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
 
-    /// Get market summary with top gainers, losers, and highlights
-    /// Accepts optional filters for minimum market cap and maximum price change percent
-    pub async fn get_market_summary(
-        &self,
-        limit: usize,
-        min_market_cap: Option<f64>,
-        max_price_change_percent: Option<f64>,
-    ) -> Result<MarketSummary> {
-        let collection = self.analysis_collection();
-        let limit_i64 = limit as i64;
+        // Point-in-time reads walk one symbol's snapshots newest first
+        let analysis_history: Collection<Document> = database.collection("analysis_history");
+        analysis_history
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1, VERSION_FIELD: -1 })
+                    .build(),
+            )
+            .await?;
+        // Retention (see `retention.rs`) deletes by age across all symbols
+        analysis_history
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { VERSION_FIELD: 1 })
+                    .build(),
+            )
+            .await?;
+
+        let analysis_runs: Collection<AnalysisRun> = database.collection("analysis_runs");
+        analysis_runs
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "started_at": -1 })
+                    .build(),
+            )
+            .await?;
+
+        let dead_letters: Collection<DeadLetter> = database.collection("dead_letters");
+        dead_letters
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "symbol": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub fn analysis_collection(&self) -> Collection<StockAnalysis> {
+        self.database.collection("stock_analysis")
+    }
+
+    /// `stock_analysis` for the heavy list queries: secondary-preferred when
+    /// `MONGO_SECONDARY_LIST_READS` is on, so they can read slightly stale
+    /// data in exchange for keeping load off the primary.
+    fn list_collection(&self) -> Collection<StockAnalysis> {
+        if !self.secondary_list_reads {
+            return self.analysis_collection();
+        }
+        self.database.collection_with_options(
+            "stock_analysis",
+            CollectionOptions::builder()
+                .selection_criteria(SelectionCriteria::ReadPreference(
+                    ReadPreference::SecondaryPreferred { options: None },
+                ))
+                .build(),
+        )
+    }
+
+    /// One snapshot per saved analysis (news dropped), carrying the same
+    /// `version` as `stock_analysis`; read by `as_of` queries.
+    fn analysis_history_collection(&self) -> Collection<Document> {
+        self.database.collection("analysis_history")
+    }
+
+    /// Where a list query reads: the live analyses, or the history as of
+    /// `as_of`.
+    fn list_source(&self, as_of: Option<DateTime<Utc>>) -> ListSource {
+        match as_of {
+            None => ListSource::Live(self.list_collection()),
+            Some(as_of) => ListSource::AsOf {
+                history: self.analysis_history_collection(),
+                version: as_of.timestamp_micros(),
+            },
+        }
+    }
+
+    /// Raw handle to the Mongo database — exposed so sibling modules (e.g.
+    /// `notifications::repo`) can register their own collections without
+    /// cluttering `MongoDB` with notification-specific accessors.
+    pub fn database(&self) -> &Database {
+        &self.database
+    }
+
+    pub fn stocks_collection(&self) -> Collection<Stock> {
+        self.database.collection("stocks")
+    }
+
+    pub fn cache_pins_collection(&self) -> Collection<CachePin> {
+        self.database.collection("cache_pins")
+    }
+
+    pub fn signals_collection(&self) -> Collection<SignalRecord> {
+        self.database.collection("signals")
+    }
+
+    pub fn week52_events_collection(&self) -> Collection<Week52Event> {
+        self.database.collection("week52_events")
+    }
+
+    pub fn external_signals_collection(&self) -> Collection<ExternalSignal> {
+        self.database.collection("external_signals")
+    }
+
+    pub fn sector_etfs_collection(&self) -> Collection<SectorEtfSnapshot> {
+        self.database.collection("sector_etfs")
+    }
+
+    pub async fn save_sector_etf(&self, snapshot: &SectorEtfSnapshot) -> Result<()> {
+        self.sector_etfs_collection()
+            .replace_one(doc! { "etf": &snapshot.etf }, snapshot)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_sector_etfs(&self) -> Result<Vec<SectorEtfSnapshot>> {
+        let mut cursor = self.sector_etfs_collection().find(doc! {}).await?;
+        let mut etfs = Vec::new();
+        while let Some(etf) = cursor.next().await {
+            etfs.push(etf?);
+        }
+        Ok(etfs)
+    }
+
+    pub fn price_history_collection(&self) -> Collection<PriceHistory> {
+        self.database.collection("price_history")
+    }
+
+    pub async fn save_price_history(&self, history: &PriceHistory) -> Result<()> {
+        self.price_history_collection()
+            .replace_one(doc! { "symbol": &history.symbol }, history)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_price_histories(&self) -> Result<Vec<PriceHistory>> {
+        let mut cursor = self.price_history_collection().find(doc! {}).await?;
+        let mut histories = Vec::new();
+        while let Some(history) = cursor.next().await {
+            histories.push(history?);
+        }
+        Ok(histories)
+    }
+
+    /// Bars and RSI/MACD state kept for incremental fetches.
+    pub fn indicator_state_collection(&self) -> Collection<IndicatorState> {
+        self.database.collection("indicator_state")
+    }
+
+    pub async fn save_indicator_state(&self, state: &IndicatorState) -> Result<()> {
+        self.indicator_state_collection()
+            .replace_one(doc! { "symbol": &state.symbol }, state)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_indicator_states(
+        &self,
+        symbols: &[String],
+    ) -> Result<HashMap<String, IndicatorState>> {
+        let mut cursor = self
+            .indicator_state_collection()
+            .find(doc! { "symbol": { "$in": symbols } })
+            .await?;
+        let mut states = HashMap::new();
+        while let Some(state) = cursor.next().await {
+            let state = state?;
+            states.insert(state.symbol.clone(), state);
+        }
+        Ok(states)
+    }
+
+    pub fn cross_section_collection(&self) -> Collection<CrossSectionStats> {
+        self.database.collection("cross_section_stats")
+    }
+
+    pub async fn save_cross_section_stats(&self, stats: &CrossSectionStats) -> Result<()> {
+        self.cross_section_collection()
+            .replace_one(doc! { "symbol": &stats.symbol }, stats)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_cross_section_stats(&self) -> Result<Vec<CrossSectionStats>> {
+        let mut cursor = self.cross_section_collection().find(doc! {}).await?;
+        let mut stats = Vec::new();
+        while let Some(entry) = cursor.next().await {
+            stats.push(entry?);
+        }
+        Ok(stats)
+    }
+
+    pub fn constituents_collection(&self) -> Collection<ConstituentList> {
+        self.database.collection("constituents")
+    }
+
+    pub async fn save_constituents(&self, list: &ConstituentList) -> Result<()> {
+        self.constituents_collection()
+            .replace_one(doc! { "index_id": &list.index_id }, list)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_constituents(&self) -> Result<Vec<ConstituentList>> {
+        let mut cursor = self.constituents_collection().find(doc! {}).await?;
+        let mut lists = Vec::new();
+        while let Some(list) = cursor.next().await {
+            lists.push(list?);
+        }
+        Ok(lists)
+    }
+
+    /// Multi-year closes from the weekend backfill, kept apart from the
+    /// year the cycle maintains in `price_history`.
+    pub fn long_price_history_collection(&self) -> Collection<PriceHistory> {
+        self.database.collection("long_price_history")
+    }
+
+    pub async fn save_long_price_history(&self, history: &PriceHistory) -> Result<()> {
+        self.long_price_history_collection()
+            .replace_one(doc! { "symbol": &history.symbol }, history)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_long_price_history(&self, symbol: &str) -> Result<Option<PriceHistory>> {
+        Ok(self
+            .long_price_history_collection()
+            .find_one(doc! { "symbol": symbol })
+            .await?)
+    }
+
+    pub fn seasonality_collection(&self) -> Collection<Seasonality> {
+        self.database.collection("seasonality")
+    }
+
+    pub async fn save_seasonality(&self, seasonality: &Seasonality) -> Result<()> {
+        self.seasonality_collection()
+            .replace_one(doc! { "symbol": &seasonality.symbol }, seasonality)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_seasonality(&self, symbol: &str) -> Result<Option<Seasonality>> {
+        Ok(self
+            .seasonality_collection()
+            .find_one(doc! { "symbol": symbol })
+            .await?)
+    }
+
+    pub fn ai_summaries_collection(&self) -> Collection<AIAnalysisResponse> {
+        self.database.collection("ai_summaries")
+    }
+
+    pub async fn save_ai_summary(&self, summary: &AIAnalysisResponse) -> Result<()> {
+        self.ai_summaries_collection()
+            .replace_one(doc! { "symbol": &summary.symbol }, summary)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_ai_summary(&self, symbol: &str) -> Result<Option<AIAnalysisResponse>> {
+        Ok(self
+            .ai_summaries_collection()
+            .find_one(doc! { "symbol": symbol })
+            .await?)
+    }
+
+    /// Every symbol with a stored analysis.
+    pub async fn get_analysis_symbols(&self) -> Result<Vec<String>> {
+        let values = self
+            .analysis_collection()
+            .distinct("symbol", doc! {})
+            .await?;
+        Ok(values
+            .into_iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// Delete per-symbol documents left behind by symbols that no longer
+    /// have an analysis (delisted, or renamed before aliases existed).
+    /// Returns how many were removed.
+    pub async fn prune_orphaned_symbol_docs(&self) -> Result<u64> {
+        let mut symbols = self.get_analysis_symbols().await?;
+        if symbols.is_empty() {
+            // An empty universe is more likely a bad read than a real state.
+            return Ok(0);
+        }
+        // The cross-section benchmark is stored but never analysed.
+        symbols.push(crate::cross_section::BENCHMARK.to_string());
+        let orphaned = doc! { "symbol": { "$nin": &symbols } };
+        let mut removed = 0;
+        for name in ORPHAN_PRUNED_COLLECTIONS {
+            removed += self
+                .database
+                .collection::<Document>(name)
+                .delete_many(orphaned.clone())
+                .await?
+                .deleted_count;
+        }
+        Ok(removed)
+    }
+
+    /// Run `compact` on `collection` to return space freed by deletes and
+    /// rewrites. Hosted tiers that don't allow it answer with an error.
+    pub async fn compact(&self, collection: &str) -> Result<()> {
+        self.database
+            .run_command(doc! { "compact": collection })
+            .await?;
+        Ok(())
+    }
+
+    pub fn index_performance_collection(&self) -> Collection<IndexPerformance> {
+        self.database.collection("index_performance")
+    }
+
+    /// Upsert an index's row for its trading date.
+    pub async fn save_index_performance(&self, perf: &IndexPerformance) -> Result<()> {
+        save_performance(&self.index_performance_collection(), perf).await
+    }
+
+    /// The latest `days` daily rows for an index, oldest first.
+    pub async fn get_index_performance(
+        &self,
+        index_id: &str,
+        days: i64,
+    ) -> Result<Vec<IndexPerformance>> {
+        performance_history(&self.index_performance_collection(), index_id, days).await
+    }
+
+    /// Daily theme returns; `index_id` holds the theme id.
+    pub fn theme_performance_collection(&self) -> Collection<IndexPerformance> {
+        self.database.collection("theme_performance")
+    }
+
+    pub async fn save_theme_performance(&self, perf: &IndexPerformance) -> Result<()> {
+        save_performance(&self.theme_performance_collection(), perf).await
+    }
+
+    /// The latest `days` daily rows for a theme, oldest first.
+    pub async fn get_theme_performance(
+        &self,
+        theme_id: &str,
+        days: i64,
+    ) -> Result<Vec<IndexPerformance>> {
+        performance_history(&self.theme_performance_collection(), theme_id, days).await
+    }
+
+    pub fn screen_results_collection(&self) -> Collection<ScreenResult> {
+        self.database.collection("screen_results")
+    }
+
+    /// Replace a screen's stored result.
+    pub async fn save_screen_result(&self, result: &ScreenResult) -> Result<()> {
+        self.screen_results_collection()
+            .replace_one(doc! { "name": &result.name }, result)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_screen_result(&self, name: &str) -> Result<Option<ScreenResult>> {
+        Ok(self
+            .screen_results_collection()
+            .find_one(doc! { "name": name })
+            .await?)
+    }
+
+    pub async fn get_screen_results(&self) -> Result<Vec<ScreenResult>> {
+        let mut cursor = self.screen_results_collection().find(doc! {}).await?;
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?);
+        }
+        Ok(results)
+    }
+
+    pub fn themes_collection(&self) -> Collection<Theme> {
+        self.database.collection("themes")
+    }
+
+    /// Insert `defaults` when no theme exists yet. Returns how many were
+    /// seeded.
+    pub async fn seed_themes(&self, defaults: &[Theme]) -> Result<usize> {
+        let collection = self.themes_collection();
+        if collection.count_documents(doc! {}).await? > 0 {
+            return Ok(0);
+        }
+        collection.insert_many(defaults).await?;
+        Ok(defaults.len())
+    }
+
+    pub async fn get_themes(&self) -> Result<Vec<Theme>> {
+        let mut cursor = self
+            .themes_collection()
+            .find(doc! {})
+            .sort(doc! { "id": 1 })
+            .await?;
+        let mut themes = Vec::new();
+        while let Some(theme) = cursor.next().await {
+            themes.push(theme?);
+        }
+        Ok(themes)
+    }
+
+    pub async fn get_theme(&self, id: &str) -> Result<Option<Theme>> {
+        Ok(self.themes_collection().find_one(doc! { "id": id }).await?)
+    }
+
+    /// Create or replace a theme.
+    pub async fn save_theme(&self, theme: &Theme) -> Result<()> {
+        self.themes_collection()
+            .replace_one(doc! { "id": &theme.id }, theme)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove a theme and its performance history. Returns whether it
+    /// existed.
+    pub async fn delete_theme(&self, id: &str) -> Result<bool> {
+        let deleted = self
+            .themes_collection()
+            .delete_one(doc! { "id": id })
+            .await?
+            .deleted_count;
+        self.theme_performance_collection()
+            .delete_many(doc! { "index_id": id })
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    pub fn symbol_notes_collection(&self) -> Collection<SymbolNote> {
+        self.database.collection("symbol_notes")
+    }
+
+    pub async fn get_symbol_notes(&self) -> Result<Vec<SymbolNote>> {
+        let mut cursor = self.symbol_notes_collection().find(doc! {}).await?;
+        let mut notes = Vec::new();
+        while let Some(note) = cursor.next().await {
+            notes.push(note?);
+        }
+        Ok(notes)
+    }
+
+    /// Create or replace a symbol's note; an empty note is deleted instead.
+    pub async fn save_symbol_note(&self, note: &SymbolNote) -> Result<()> {
+        let collection = self.symbol_notes_collection();
+        if note.is_empty() {
+            collection
+                .delete_one(doc! { "symbol": &note.symbol })
+                .await?;
+        } else {
+            collection
+                .replace_one(doc! { "symbol": &note.symbol }, note)
+                .upsert(true)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub fn index_contributors_collection(&self) -> Collection<IndexContributors> {
+        self.database.collection("index_contributors")
+    }
+
+    /// Upsert an index's contributors for their trading date.
+    pub async fn save_index_contributors(&self, contributors: &IndexContributors) -> Result<()> {
+        self.index_contributors_collection()
+            .replace_one(
+                doc! {
+                    "index_id": &contributors.index_id,
+                    "date": mongodb::bson::to_bson(&contributors.date)?,
+                },
+                contributors,
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// An index's contributors on `date`, or on its latest recorded day.
+    pub async fn get_index_contributors(
+        &self,
+        index_id: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<Option<IndexContributors>> {
+        let mut filter = doc! { "index_id": index_id };
+        if let Some(date) = date {
+            filter.insert("date", mongodb::bson::to_bson(&date)?);
+        }
+        Ok(self
+            .index_contributors_collection()
+            .find_one(filter)
+            .sort(doc! { "date": -1 })
+            .await?)
+    }
+
+    pub fn cycle_symbols_collection(&self) -> Collection<SymbolProgress> {
+        self.database.collection("cycle_symbols")
+    }
+
+    /// Replace the per-symbol state with a fresh cycle's.
+    pub async fn reset_cycle_symbols(&self, entries: &[SymbolProgress]) -> Result<()> {
+        let collection = self.cycle_symbols_collection();
+        collection.delete_many(doc! {}).await?;
+        for chunk in entries.chunks(1000) {
+            collection.insert_many(chunk).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn set_cycle_symbol_status(
+        &self,
+        symbol: &str,
+        status: SymbolCycleStatus,
+        error: Option<&str>,
+    ) -> Result<()> {
+        self.cycle_symbols_collection()
+            .update_one(
+                doc! { "symbol": symbol },
+                doc! { "$set": {
+                    "status": mongodb::bson::to_bson(&status)?,
+                    "error": error,
+                    "updated_at": mongodb::bson::to_bson(&Utc::now())?,
+                } },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Current cycle's symbols, optionally only those in `status`.
+    pub async fn get_cycle_symbols(
+        &self,
+        status: Option<SymbolCycleStatus>,
+    ) -> Result<Vec<SymbolProgress>> {
+        let filter = match status {
+            Some(status) => doc! { "status": mongodb::bson::to_bson(&status)? },
+            None => doc! {},
+        };
+        let mut cursor = self
+            .cycle_symbols_collection()
+            .find(filter)
+            .sort(doc! { "symbol": 1 })
+            .await?;
+        let mut entries = Vec::new();
+        while let Some(entry) = cursor.next().await {
+            entries.push(entry?);
+        }
+        Ok(entries)
+    }
+
+    /// Insert signals that aren't already recorded for their strategy, symbol
+    /// and day. Re-detecting the same signal later in the day is a no-op, so
+    /// the entry price stays at the first observation.
+    pub async fn record_signals(&self, signals: &[SignalRecord]) -> Result<()> {
+        for signal in signals {
+            self.signals_collection()
+                .update_one(
+                    doc! {
+                        "symbol": &signal.symbol,
+                        "strategy": mongodb::bson::to_bson(&signal.strategy)?,
+                        "signal_date": &signal.signal_date,
+                    },
+                    doc! { "$setOnInsert": mongodb::bson::to_document(signal)? },
+                )
+                .upsert(true)
+                .await?;
+        }
+        Ok(())
+    }
 
-        // Build base filter document with optional market cap filter
-        let mut base_filter = Document::new();
-        if let Some(min_mc) = min_market_cap {
-            base_filter.insert("market_cap", doc! { "$gte": min_mc });
+    /// Signals for `symbol` still missing at least one forward return.
+    pub async fn get_open_signals(&self, symbol: &str) -> Result<Vec<SignalRecord>> {
+        let mut cursor = self
+            .signals_collection()
+            .find(doc! {
+                "symbol": symbol,
+                "$or": [
+                    { "return_5d": { "$exists": false } },
+                    { "return_20d": { "$exists": false } },
+                ],
+            })
+            .await?;
+        let mut results = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            if let Ok(signal) = doc {
+                results.push(signal);
+            }
+        }
+        Ok(results)
+    }
+
+    pub async fn update_signal_returns(&self, signal: &SignalRecord) -> Result<()> {
+        let Some(id) = signal.id else {
+            return Ok(());
+        };
+        let mut set = Document::new();
+        if let Some(r) = signal.return_5d {
+            set.insert("return_5d", r);
+        }
+        if let Some(r) = signal.return_20d {
+            set.insert("return_20d", r);
+        }
+        if set.is_empty() {
+            return Ok(());
+        }
+        self.signals_collection()
+            .update_one(doc! { "_id": id }, doc! { "$set": set })
+            .await?;
+        Ok(())
+    }
+
+    /// Signals dated on or after `since` (`YYYY-MM-DD`), or all of them.
+    pub async fn get_signals(&self, since: Option<&str>) -> Result<Vec<SignalRecord>> {
+        let filter = match since {
+            Some(date) => doc! { "signal_date": { "$gte": date } },
+            None => doc! {},
+        };
+        let mut cursor = self.signals_collection().find(filter).await?;
+        let mut results = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            if let Ok(signal) = doc {
+                results.push(signal);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Upsert 52-week events by kind, symbol and day. Later cycles on the
+    /// same day overwrite the price with the day's latest extreme.
+    pub async fn record_week52_events(&self, events: &[Week52Event]) -> Result<()> {
+        for event in events {
+            self.week52_events_collection()
+                .replace_one(
+                    doc! {
+                        "symbol": &event.symbol,
+                        "kind": mongodb::bson::to_bson(&event.kind)?,
+                        "date": &event.date,
+                    },
+                    event,
+                )
+                .upsert(true)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 52-week events dated on or after `since` (`YYYY-MM-DD`), optionally of
+    /// one kind, newest first.
+    pub async fn get_week52_events(
+        &self,
+        since: &str,
+        kind: Option<Week52Kind>,
+    ) -> Result<Vec<Week52Event>> {
+        let mut filter = doc! { "date": { "$gte": since } };
+        if let Some(kind) = kind {
+            filter.insert("kind", mongodb::bson::to_bson(&kind)?);
+        }
+        let mut cursor = self
+            .week52_events_collection()
+            .find(filter)
+            .sort(doc! { "date": -1, "symbol": 1 })
+            .await?;
+        let mut results = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            if let Ok(event) = doc {
+                results.push(event);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Store an external signal, returning it with its new `_id`.
+    pub async fn save_external_signal(&self, signal: &ExternalSignal) -> Result<ExternalSignal> {
+        let result = self
+            .external_signals_collection()
+            .insert_one(signal)
+            .await?;
+        let mut saved = signal.clone();
+        saved.id = result.inserted_id.as_object_id();
+        Ok(saved)
+    }
+
+    /// External signals received at or after `since`, newest first,
+    /// optionally for one symbol.
+    pub async fn get_external_signals(
+        &self,
+        since: DateTime<Utc>,
+        symbol: Option<&str>,
+        skip: u64,
+        limit: i64,
+    ) -> Result<Vec<ExternalSignal>> {
+        let mut cursor = self
+            .external_signals_collection()
+            .find(external_signals_filter(since, symbol))
+            .sort(doc! { "received_at": -1 })
+            .skip(skip)
+            .limit(limit)
+            .await?;
+        let mut results = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            if let Ok(signal) = doc {
+                results.push(signal);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Number of signals `get_external_signals` pages through.
+    pub async fn count_external_signals(
+        &self,
+        since: DateTime<Utc>,
+        symbol: Option<&str>,
+    ) -> Result<u64> {
+        Ok(self
+            .external_signals_collection()
+            .count_documents(external_signals_filter(since, symbol))
+            .await?)
+    }
+
+    pub fn universe_names_collection(&self) -> Collection<UniverseName> {
+        self.database.collection("universe_names")
+    }
+
+    pub fn symbol_aliases_collection(&self) -> Collection<SymbolAlias> {
+        self.database.collection("symbol_aliases")
+    }
+
+    /// Symbol → normalized company name from the previous universe fetch.
+    pub async fn get_universe_names(&self) -> Result<HashMap<String, String>> {
+        let mut cursor = self.universe_names_collection().find(doc! {}).await?;
+        let mut names = HashMap::new();
+        while let Some(entry) = cursor.next().await {
+            let entry = entry?;
+            names.insert(entry.symbol, entry.name);
+        }
+        Ok(names)
+    }
+
+    pub async fn replace_universe_names(&self, names: &HashMap<String, String>) -> Result<()> {
+        let collection = self.universe_names_collection();
+        collection.delete_many(doc! {}).await?;
+        let entries: Vec<UniverseName> = names
+            .iter()
+            .map(|(symbol, name)| UniverseName {
+                symbol: symbol.clone(),
+                name: name.clone(),
+            })
+            .collect();
+        for chunk in entries.chunks(1000) {
+            collection.insert_many(chunk).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the `stocks` listings with this screener universe: upsert
+    /// each listing by symbol, then drop the symbols that left it, so search
+    /// never sees an empty or half-written collection. An empty universe (a
+    /// failed parse) leaves the previous listings in place.
+    pub async fn replace_stock_listings(&self, listings: &[Stock]) -> Result<()> {
+        if listings.is_empty() {
+            return Ok(());
+        }
+        let collection = self.stocks_collection();
+        for chunk in listings.chunks(LISTING_UPSERTS_IN_FLIGHT) {
+            futures::future::try_join_all(chunk.iter().map(|stock| {
+                collection
+                    .replace_one(doc! { "symbol": &stock.symbol }, stock)
+                    .upsert(true)
+                    .into_future()
+            }))
+            .await?;
+        }
+        let symbols: Vec<&str> = listings.iter().map(|s| s.symbol.as_str()).collect();
+        collection
+            .delete_many(doc! { "symbol": { "$nin": symbols } })
+            .await?;
+        Ok(())
+    }
+
+    /// Listings matching `query`, best first (see [`rank_symbol_matches`]).
+    /// Symbol and name-word prefixes are matched by regex, since the text
+    /// index only matches whole (stemmed) words; the text index adds its
+    /// relevance score and matches words anywhere in the name.
+    pub async fn search_stocks(&self, query: &str, limit: usize) -> Result<Vec<SymbolMatch>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let collection = self.stocks_collection().clone_with_type::<Document>();
+        let escaped = escape_regex(query);
+        let prefix_filter = doc! {
+            "$or": [
+                { "symbol": { "$regex": format!("^{}", escape_regex(&query.to_uppercase())) } },
+                { "name": { "$regex": format!("(^|\\s){}", escaped), "$options": "i" } },
+            ]
+        };
+        let text_filter = doc! { "$text": { "$search": query } };
+        let candidates = (limit * 4).max(50) as i64;
+
+        let mut found: HashMap<String, (Stock, f64)> = HashMap::new();
+        let mut cursor = collection
+            .find(prefix_filter)
+            .projection(doc! { "_id": 0 })
+            .sort(doc! { "market_cap": -1 })
+            .limit(candidates)
+            .await?;
+        while let Some(document) = cursor.next().await {
+            let stock: Stock = mongodb::bson::from_document(document?)?;
+            found.insert(stock.symbol.clone(), (stock, 0.0));
+        }
+        let mut cursor = collection
+            .find(text_filter)
+            .projection(doc! { "_id": 0, "score": { "$meta": "textScore" } })
+            .sort(doc! { "score": { "$meta": "textScore" } })
+            .limit(candidates)
+            .await?;
+        while let Some(document) = cursor.next().await {
+            let document = document?;
+            let score = document.get_f64("score").unwrap_or(0.0);
+            let stock: Stock = mongodb::bson::from_document(document)?;
+            found
+                .entry(stock.symbol.clone())
+                .and_modify(|entry| entry.1 = score)
+                .or_insert((stock, score));
         }
+        Ok(rank_symbol_matches(
+            query,
+            found.into_values().collect(),
+            limit,
+        ))
+    }
 
-        // Build filter for gainers (positive change, within max threshold if set)
-        let mut gainers_filter = base_filter.clone();
-        gainers_filter.insert(
-            "price_change_percent",
-            price_change_summary_filter(0.0, max_price_change_percent),
-        );
+    pub async fn get_symbol_aliases(&self) -> Result<Vec<SymbolAlias>> {
+        let mut cursor = self.symbol_aliases_collection().find(doc! {}).await?;
+        let mut aliases = Vec::new();
+        while let Some(alias) = cursor.next().await {
+            aliases.push(alias?);
+        }
+        Ok(aliases)
+    }
 
-        // Top gainers (sorted by price_change_percent desc)
-        let gainers_options = FindOptions::builder()
-            .sort(doc! { "price_change_percent": -1 })
-            .limit(limit_i64)
-            .build();
-        let mut gainers_cursor = collection
-            .find(gainers_filter)
-            .with_options(gainers_options)
+    /// Store `alias` and re-point older aliases that targeted its old symbol.
+    pub async fn save_symbol_alias(&self, alias: &SymbolAlias) -> Result<()> {
+        let collection = self.symbol_aliases_collection();
+        collection
+            .update_many(
+                doc! { "new_symbol": &alias.old_symbol },
+                doc! { "$set": { "new_symbol": &alias.new_symbol } },
+            )
             .await?;
-        let mut top_gainers = Vec::new();
-        while let Some(doc) = gainers_cursor.next().await {
-            if let Ok(analysis) = doc {
-                top_gainers.push(analysis);
+        collection
+            .delete_many(doc! { "old_symbol": &alias.new_symbol })
+            .await?;
+        collection
+            .replace_one(doc! { "old_symbol": &alias.old_symbol }, alias)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Move a renamed symbol's analysis, history, notes, signals, 52-week
+    /// events and cache pin to `new`, and drop its dead-letter streak. A
+    /// per-symbol document already stored under `new` (its analysis, price
+    /// history, notes, ...) wins over the old one.
+    pub async fn migrate_symbol(&self, old: &str, new: &str) -> Result<()> {
+        let rename = doc! { "$set": { "symbol": new } };
+        for name in ["stock_analysis", "symbol_notes"]
+            .into_iter()
+            .chain(ORPHAN_PRUNED_COLLECTIONS)
+        {
+            let collection = self.database.collection::<Document>(name);
+            if collection.find_one(doc! { "symbol": new }).await?.is_some() {
+                collection.delete_many(doc! { "symbol": old }).await?;
+            } else {
+                collection
+                    .update_many(doc! { "symbol": old }, rename.clone())
+                    .await?;
             }
         }
+        self.analysis_history_collection()
+            .update_many(doc! { "symbol": old }, rename.clone())
+            .await?;
+        // Events are unique per symbol, kind and date; keep `new`'s copy.
+        let events = self.week52_events_collection();
+        let mut cursor = events.find(doc! { "symbol": new }).await?;
+        while let Some(event) = cursor.next().await {
+            let event = event?;
+            events
+                .delete_one(doc! {
+                    "symbol": old,
+                    "kind": mongodb::bson::to_bson(&event.kind)?,
+                    "date": &event.date,
+                })
+                .await?;
+        }
+        events
+            .update_many(doc! { "symbol": old }, rename.clone())
+            .await?;
+        self.signals_collection()
+            .update_many(doc! { "symbol": old }, rename.clone())
+            .await?;
+        self.external_signals_collection()
+            .update_many(doc! { "symbol": old }, rename.clone())
+            .await?;
+        self.cache_pins_collection()
+            .update_many(doc! { "symbol": old }, rename)
+            .await?;
+        // The old ticker's failures were most likely the rename itself.
+        self.dead_letters_collection()
+            .delete_many(doc! { "symbol": old })
+            .await?;
+        Ok(())
+    }
 
-        // Build filter for losers (negative change, within max threshold if set)
-        let mut losers_filter = base_filter.clone();
-        losers_filter.insert(
-            "price_change_percent",
-            negative_price_change_summary_filter(0.0, max_price_change_percent.map(|p| -p)),
-        );
+    pub fn dead_letters_collection(&self) -> Collection<DeadLetter> {
+        self.database.collection("dead_letters")
+    }
 
-        // Top losers (sorted by price_change_percent asc)
-        let losers_options = FindOptions::builder()
-            .sort(doc! { "price_change_percent": 1 })
-            .limit(limit_i64)
-            .build();
-        let mut losers_cursor = collection
-            .find(losers_filter)
-            .with_options(losers_options)
+    /// Append a failure to `symbol`'s streak, dead-lettering it once the
+    /// streak reaches `threshold`. Returns true when this failure moved the
+    /// symbol into the dead-letter queue.
+    pub async fn record_analysis_failure(
+        &self,
+        symbol: &str,
+        error: &str,
+        threshold: u32,
+    ) -> Result<bool> {
+        let record = FailureRecord {
+            error: error.to_string(),
+            failed_at: Utc::now(),
+        };
+        let collection = self.dead_letters_collection();
+        let entry = collection
+            .find_one_and_update(
+                doc! { "symbol": symbol },
+                doc! {
+                    "$inc": { "consecutive_failures": 1 },
+                    "$push": { "errors": {
+                        "$each": [mongodb::bson::to_bson(&record)?],
+                        "$slice": -(DEAD_LETTER_HISTORY as i32),
+                    } },
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
             .await?;
-        let mut top_losers = Vec::new();
-        while let Some(doc) = losers_cursor.next().await {
-            if let Ok(analysis) = doc {
-                top_losers.push(analysis);
-            }
+        let Some(entry) = entry else {
+            return Ok(false);
+        };
+        if entry.dead_lettered_at.is_some() || entry.consecutive_failures < threshold {
+            return Ok(false);
         }
+        collection
+            .update_one(
+                doc! { "symbol": symbol },
+                doc! { "$set": { "dead_lettered_at": mongodb::bson::to_bson(&Utc::now())? } },
+            )
+            .await?;
+        Ok(true)
+    }
 
-        // Most oversold (RSI < 30, sorted by RSI asc) - apply market cap filter
-        let mut oversold_filter = base_filter.clone();
-        oversold_filter.insert("rsi", doc! { "$lt": 30.0, "$exists": true });
-        let oversold_options = FindOptions::builder()
-            .sort(doc! { "rsi": 1 })
-            .limit(limit_i64)
-            .build();
-        let mut oversold_cursor = collection
-            .find(oversold_filter)
-            .with_options(oversold_options)
+    /// Reset a symbol's failure streak after a successful analysis.
+    pub async fn clear_analysis_failures(&self, symbol: &str) -> Result<()> {
+        self.dead_letters_collection()
+            .delete_one(doc! { "symbol": symbol, "dead_lettered_at": Bson::Null })
             .await?;
-        let mut most_oversold = Vec::new();
-        while let Some(doc) = oversold_cursor.next().await {
-            if let Ok(analysis) = doc {
-                most_oversold.push(analysis);
-            }
+        Ok(())
+    }
+
+    /// Dead-lettered symbols, most recent first.
+    pub async fn get_dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        let mut cursor = self
+            .dead_letters_collection()
+            .find(doc! { "dead_lettered_at": { "$ne": Bson::Null } })
+            .sort(doc! { "dead_lettered_at": -1 })
+            .await?;
+        let mut entries = Vec::new();
+        while let Some(entry) = cursor.next().await {
+            entries.push(entry?);
+        }
+        Ok(entries)
+    }
+
+    pub async fn get_dead_lettered_symbols(&self) -> Result<HashSet<String>> {
+        Ok(self
+            .get_dead_letters()
+            .await?
+            .into_iter()
+            .map(|entry| entry.symbol)
+            .collect())
+    }
+
+    /// Return dead-lettered symbols to normal cycles: one symbol, or all of
+    /// them when `symbol` is `None`. Returns how many were requeued.
+    pub async fn requeue_dead_letters(&self, symbol: Option<&str>) -> Result<u64> {
+        let mut filter = doc! { "dead_lettered_at": { "$ne": Bson::Null } };
+        if let Some(symbol) = symbol {
+            filter.insert("symbol", symbol);
+        }
+        let res = self.dead_letters_collection().delete_many(filter).await?;
+        Ok(res.deleted_count)
+    }
+
+    pub async fn get_cache_pins(&self) -> Result<Vec<CachePin>> {
+        let mut cursor = self.cache_pins_collection().find(doc! {}).await?;
+        let mut pins = Vec::new();
+        while let Some(pin) = cursor.next().await {
+            pins.push(pin?);
+        }
+        Ok(pins)
+    }
+
+    pub async fn save_cache_pin(&self, pin: &CachePin) -> Result<()> {
+        self.cache_pins_collection()
+            .replace_one(doc! { "symbol": &pin.symbol }, pin)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_cache_pin(&self, symbol: &str) -> Result<bool> {
+        let res = self
+            .cache_pins_collection()
+            .delete_one(doc! { "symbol": symbol })
+            .await?;
+        Ok(res.deleted_count > 0)
+    }
+
+    /// Upsert `analysis` unless the stored one is fresher. Each document
+    /// carries a `version` (`analyzed_at` in microseconds); an update only
+    /// applies over an equal or older version, so a slow writer can't
+    /// clobber a newer analysis saved in the meantime. Returns `false` when
+    /// the write was rejected as stale.
+    pub async fn save_analysis(&self, analysis: &StockAnalysis) -> Result<bool> {
+        let collection = self.analysis_collection();
+        let version = analysis_version(analysis);
+        let mut document = mongodb::bson::to_document(analysis)?;
+        document.insert(VERSION_FIELD, version);
+        let guard = stale_guard(&analysis.symbol, version);
+
+        let update = doc! { "$set": document.clone() };
+        if collection
+            .update_one(guard.clone(), update.clone())
+            .await?
+            .matched_count
+            > 0
+        {
+            return Ok(true);
         }
+        // No document at or below this version: either the symbol is new,
+        // or a fresher analysis is stored and this one is stale.
+        let inserted = collection
+            .update_one(
+                doc! { "symbol": &analysis.symbol },
+                doc! { "$setOnInsert": document },
+            )
+            .upsert(true)
+            .await?
+            .upserted_id
+            .is_some();
+        if inserted {
+            return Ok(true);
+        }
+        // A concurrent first insert may have landed between the two calls.
+        Ok(collection.update_one(guard, update).await?.matched_count > 0)
+    }
+
+    /// Append `analysis` to `analysis_history` so `as_of` queries can
+    /// reconstruct what was known when it was saved. News is left out; it
+    /// dominates the document size and no screen reads it.
+    pub async fn record_analysis_snapshot(&self, analysis: &StockAnalysis) -> Result<()> {
+        let mut document = mongodb::bson::to_document(analysis)?;
+        document.remove("_id");
+        document.remove("news");
+        document.insert(VERSION_FIELD, analysis_version(analysis));
+        self.analysis_history_collection()
+            .insert_one(document)
+            .await?;
+        Ok(())
+    }
 
-        // Most overbought (RSI > 70, sorted by RSI desc) - apply market cap filter
-        let mut overbought_filter = base_filter.clone();
-        overbought_filter.insert("rsi", doc! { "$gt": 70.0, "$exists": true });
-        let overbought_options = FindOptions::builder()
-            .sort(doc! { "rsi": -1 })
-            .limit(limit_i64)
+    /// `symbol`'s snapshots saved since `since`, oldest first, reduced to
+    /// the fields `/api/stocks/:symbol/analysis-history` charts.
+    pub async fn get_analysis_history(
+        &self,
+        symbol: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AnalysisHistoryPoint>> {
+        let options = FindOptions::builder()
+            .sort(doc! { VERSION_FIELD: 1 })
+            .projection(doc! {
+                "_id": 0,
+                "analyzed_at": 1,
+                "price": 1,
+                "price_change_percent": 1,
+                "rsi": 1,
+                "sma_20": 1,
+                "sma_50": 1,
+                "macd": 1,
+                "volume": 1,
+            })
             .build();
-        let mut overbought_cursor = collection
-            .find(overbought_filter)
-            .with_options(overbought_options)
+        let mut cursor = self
+            .analysis_history_collection()
+            .find(doc! {
+                "symbol": symbol,
+                VERSION_FIELD: { "$gte": since.timestamp_micros() },
+            })
+            .with_options(options)
             .await?;
-        let mut most_overbought = Vec::new();
-        while let Some(doc) = overbought_cursor.next().await {
-            if let Ok(analysis) = doc {
-                most_overbought.push(analysis);
+        let mut points = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            match mongodb::bson::from_document(doc?) {
+                Ok(point) => points.push(point),
+                Err(e) => warn!("Skipping unreadable {} snapshot: {}", symbol, e),
             }
         }
+        Ok(points)
+    }
 
-        // Mega cap highlights (>$200B market cap, sorted by market cap desc)
-        // Note: This section ignores the min_market_cap filter since it's specifically for mega caps
-        let mega_cap_options = FindOptions::builder()
-            .sort(doc! { "market_cap": -1 })
-            .limit(limit_i64)
-            .build();
-        let mut mega_cursor = collection
-            .find(doc! { "market_cap": { "$gte": 200_000_000_000.0 } })
-            .with_options(mega_cap_options)
+    /// Delete every document in `collection` matching `filter`; used by
+    /// `retention.rs`. Returns how many went.
+    pub async fn delete_matching(&self, collection: &str, filter: Document) -> Result<u64> {
+        let result = self
+            .database
+            .collection::<Document>(collection)
+            .delete_many(filter)
             .await?;
-        let mut mega_cap_highlights = Vec::new();
-        while let Some(doc) = mega_cursor.next().await {
-            if let Ok(analysis) = doc {
-                mega_cap_highlights.push(analysis);
+        Ok(result.deleted_count)
+    }
+
+    /// Document count and sizes of every collection, largest on disk first.
+    pub async fn collection_stats(&self) -> Result<Vec<CollectionStats>> {
+        let mut stats = Vec::new();
+        for name in self.database.list_collection_names().await? {
+            let mut cursor = self
+                .database
+                .collection::<Document>(&name)
+                .aggregate(vec![doc! { "$collStats": { "storageStats": {} } }])
+                .await?;
+            if let Some(doc) = cursor.next().await {
+                stats.push(CollectionStats::from_coll_stats(name, &doc?));
             }
         }
+        stats.sort_by(|a, b| {
+            b.storage_bytes
+                .cmp(&a.storage_bytes)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(stats)
+    }
 
-        // Get total stock count (with market cap filter if applied)
-        let total_stocks = if min_market_cap.is_some() {
-            collection.count_documents(base_filter).await? as usize
-        } else {
-            collection.count_documents(doc! {}).await? as usize
-        };
+    /// Write a replayed analysis (see `replay.rs`) into `analysis_history`.
+    /// `fields` are set on the symbol's first snapshot saved within `window`
+    /// after `analysis.analyzed_at`, i.e. the one the cycle wrote from the
+    /// same responses; with none, the whole analysis is added as a snapshot
+    /// of its own. Both carry `replayed_at`, and a second replay of the same
+    /// responses updates rather than duplicates. Returns `true` when an
+    /// existing snapshot was updated.
+    pub async fn merge_replayed_snapshot(
+        &self,
+        analysis: &StockAnalysis,
+        fields: &[&str],
+        window: chrono::Duration,
+    ) -> Result<bool> {
+        let version = analysis_version(analysis);
+        let mut document = mongodb::bson::to_document(analysis)?;
+        document.remove("_id");
+        document.remove("news");
+        document.insert(VERSION_FIELD, version);
+        let replayed_at = mongodb::bson::DateTime::now();
+
+        let mut set = Document::new();
+        for field in fields {
+            set.insert(*field, document.get(*field).cloned().unwrap_or(Bson::Null));
+        }
+        set.insert("replayed_at", replayed_at);
+        let window_end = version.saturating_add(window.num_microseconds().unwrap_or(i64::MAX));
+        let merged = self
+            .analysis_history_collection()
+            .find_one_and_update(
+                doc! {
+                    "symbol": &analysis.symbol,
+                    VERSION_FIELD: { "$gte": version, "$lte": window_end },
+                },
+                doc! { "$set": set },
+            )
+            .sort(doc! { VERSION_FIELD: 1 })
+            .await?;
+        if merged.is_some() {
+            return Ok(true);
+        }
+        document.insert("replayed_at", replayed_at);
+        self.analysis_history_collection()
+            .insert_one(document)
+            .await?;
+        Ok(false)
+    }
 
-        Ok(MarketSummary {
-            total_stocks,
-            top_gainers,
-            top_losers,
-            most_oversold,
-            most_overbought,
-            mega_cap_highlights,
-            generated_at: Utc::now(),
-        })
+    /// Set only `fields` on the stored analysis for `symbol`, leaving the
+    /// rest as the last full save wrote it. For refreshers that compute a
+    /// subset (price, news, earnings). Returns `false` when the symbol has
+    /// no stored analysis; a partial update never creates one.
+    pub async fn update_analysis_fields(&self, symbol: &str, fields: Document) -> Result<bool> {
+        let symbol = crate::symbols::normalize_symbol_key(symbol);
+        let result = self
+            .analysis_collection()
+            .update_one(doc! { "symbol": symbol }, partial_update(fields)?)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Get analysis for a specific symbol
+    pub async fn get_analysis_by_symbol(&self, symbol: &str) -> Result<Option<StockAnalysis>> {
+        let collection = self.analysis_collection();
+        let symbol = crate::symbols::normalize_symbol_key(symbol);
+
+        match collection.find_one(doc! { "symbol": symbol }).await? {
+            Some(analysis) => Ok(Some(analysis)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_latest_analyses(&self, filter: StockFilter) -> Result<Vec<StockAnalysis>> {
+        let source = self.list_source(filter.as_of);
+        let filter_doc = self.filter_doc(&filter).await?;
+        let (sort_doc, skip, page_size) = list_page(&filter);
+        source.find(filter_doc, sort_doc, skip, page_size).await
+    }
+
+    /// Get total count for a filter (for pagination)
+    pub async fn get_filtered_count(&self, filter: StockFilter) -> Result<u64> {
+        let filter_doc = self.filter_doc(&filter).await?;
+        self.list_source(filter.as_of).count(filter_doc).await
+    }
+
+    /// `build_filter_doc` plus the parts that need a lookup (themes).
+    pub(crate) async fn filter_doc(&self, filter: &StockFilter) -> Result<Document> {
+        check_filter(filter)?;
+        let mut filter_doc = build_filter_doc(filter);
+        if let Some(id) = filter_theme(filter) {
+            let theme = self
+                .get_theme(&id.to_lowercase())
+                .await?
+                .ok_or_else(|| anyhow!("Theme '{}' not found", id))?;
+            scope_to_symbols(&mut filter_doc, &theme.symbols);
+        }
+        Ok(filter_doc)
+    }
+
+    /// Get market summary with top gainers, losers, and highlights in one
+    /// aggregation (see [`market_summary_stages`]).
+    /// Accepts optional filters for minimum market cap and maximum price change percent,
+    /// and `as_of` to summarize the analysis history at that time
+    pub async fn get_market_summary(
+        &self,
+        limit: usize,
+        min_market_cap: Option<f64>,
+        max_price_change_percent: Option<f64>,
+        as_of: Option<DateTime<Utc>>,
+    ) -> Result<MarketSummary> {
+        // Over-fetch so collapsing share classes (GOOG/GOOGL) still fills
+        // each list.
+        let stages =
+            market_summary_stages((limit * 2) as i64, min_market_cap, max_price_change_percent);
+        let facets = self
+            .list_source(as_of)
+            .aggregate(stages)
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        Ok(market_summary_from_facets(facets, limit))
     }
 
     pub async fn get_analysis_count(&self) -> Result<u64> {
@@ -456,154 +2262,647 @@ impl MongoDB {
         Ok(None)
     }
 
-    /// Get sector performance aggregation
+    /// Per-sector count, average RSI and change, total market cap and
+    /// leaders, grouped server-side (see [`sector_performance_pipeline`]).
     pub async fn get_sector_performance(&self) -> Result<Vec<SectorPerformance>> {
-        let collection = self.analysis_collection();
+        let mut cursor = self
+            .list_collection()
+            .aggregate(sector_performance_pipeline())
+            .allow_disk_use(true)
+            .await?;
+        let mut results = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            match mongodb::bson::from_document(doc?) {
+                Ok(sector) => results.push(sector),
+                Err(e) => warn!("Skipping unreadable sector aggregate: {}", e),
+            }
+        }
+        Ok(results)
+    }
 
-        // Get all analyses grouped by sector
-        let mut sector_map: std::collections::HashMap<String, Vec<StockAnalysis>> =
-            std::collections::HashMap::new();
+    /// Get aggregated news from all stocks
+    pub async fn get_all_news(
+        &self,
+        sector: Option<String>,
+        search: Option<String>,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<AggregatedNewsItem>, u64)> {
+        let collection = self.list_collection();
 
-        let mut cursor = collection
-            .find(doc! { "sector": { "$exists": true, "$ne": null } })
-            .await?;
+        let mut filter_doc =
+            doc! { "news": { "$exists": true, "$ne": null, "$not": { "$size": 0 } } };
+        if let Some(ref s) = sector {
+            filter_doc.insert("sector", s);
+        }
+
+        let mut cursor = collection.find(filter_doc).await?;
 
+        let mut all_news: Vec<AggregatedNewsItem> = Vec::new();
         while let Some(doc) = cursor.next().await {
             if let Ok(analysis) = doc {
-                if let Some(ref sector) = analysis.sector {
-                    sector_map.entry(sector.clone()).or_default().push(analysis);
+                if let Some(news_items) = analysis.news {
+                    for item in news_items {
+                        // Apply search filter
+                        if let Some(ref query) = search {
+                            let q = query.to_lowercase();
+                            if !item.title.to_lowercase().contains(&q)
+                                && !analysis.symbol.to_lowercase().contains(&q)
+                            {
+                                continue;
+                            }
+                        }
+                        all_news.push(AggregatedNewsItem {
+                            symbol: analysis.symbol.clone(),
+                            sector: analysis.sector.clone(),
+                            title: item.title,
+                            url: item.url,
+                            publisher: item.publisher,
+                            created: item.created,
+                            ago: item.ago,
+                        });
+                    }
                 }
             }
         }
 
-        let mut results = Vec::new();
-        for (sector, mut stocks) in sector_map {
-            let stock_count = stocks.len() as u32;
-            let avg_change_percent = stocks
-                .iter()
-                .filter_map(|s| s.price_change_percent)
-                .sum::<f64>()
-                / stocks
-                    .iter()
-                    .filter(|s| s.price_change_percent.is_some())
-                    .count()
-                    .max(1) as f64;
-            let avg_rsi = stocks.iter().filter_map(|s| s.rsi).sum::<f64>()
-                / stocks.iter().filter(|s| s.rsi.is_some()).count().max(1) as f64;
-
-            // Sort by price_change_percent for top/bottom
-            stocks.sort_by(|a, b| {
-                b.price_change_percent
-                    .unwrap_or(0.0)
-                    .partial_cmp(&a.price_change_percent.unwrap_or(0.0))
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
-
-            let top_performers: Vec<StockAnalysis> = stocks.iter().take(3).cloned().collect();
-            let bottom_performers: Vec<StockAnalysis> =
-                stocks.iter().rev().take(3).cloned().collect();
-
-            results.push(SectorPerformance {
-                sector,
-                stock_count,
-                avg_change_percent,
-                avg_rsi,
-                top_performers,
-                bottom_performers,
-            });
-        }
-
-        // Sort sectors by avg_change_percent descending
-        results.sort_by(|a, b| {
-            b.avg_change_percent
-                .partial_cmp(&a.avg_change_percent)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        // Sort by created date descending (most recent first)
+        all_news.sort_by(|a, b| b.created.cmp(&a.created));
+
+        let total = all_news.len() as u64;
+        let skip = ((page - 1) * page_size) as usize;
+        let paginated: Vec<AggregatedNewsItem> = all_news
+            .into_iter()
+            .skip(skip)
+            .take(page_size as usize)
+            .collect();
+
+        Ok((paginated, total))
+    }
+
+    /// Largest `limit` analyses by market cap, for bounded cache warm-up.
+    pub async fn get_top_analyses_by_market_cap(&self, limit: i64) -> Result<Vec<StockAnalysis>> {
+        let mut cursor = self
+            .analysis_collection()
+            .find(doc! {})
+            .sort(doc! { "market_cap": -1 })
+            .limit(limit)
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            if let Ok(analysis) = doc {
+                results.push(analysis);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Analyses for an explicit set of (already normalized) symbols.
+    pub async fn get_analyses_by_symbols(&self, symbols: &[String]) -> Result<Vec<StockAnalysis>> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut cursor = self
+            .analysis_collection()
+            .find(doc! { "symbol": { "$in": symbols } })
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            if let Ok(analysis) = doc {
+                results.push(analysis);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Stored `percentiles` of every analysis that has them, by symbol.
+    pub async fn get_percentile_ranks(&self) -> Result<HashMap<String, PercentileRanks>> {
+        let collection: Collection<Document> = self.database.collection("stock_analysis");
+        let mut cursor = collection
+            .find(doc! { "percentiles": { "$exists": true } })
+            .projection(doc! { "_id": 0, "symbol": 1, "percentiles": 1 })
+            .await?;
+        let mut ranks = HashMap::new();
+        while let Some(row) = cursor.next().await {
+            let row = row?;
+            let (Ok(symbol), Ok(percentiles)) =
+                (row.get_str("symbol"), row.get_document("percentiles"))
+            else {
+                continue;
+            };
+            if let Ok(parsed) = mongodb::bson::from_document(percentiles.clone()) {
+                ranks.insert(symbol.to_string(), parsed);
+            }
+        }
+        Ok(ranks)
+    }
+
+    /// Get all analyses from the database
+    /// Every stored analysis, newest first, read leniently: documents that
+    /// no longer deserialize are repaired in memory where possible, and all
+    /// of them are recorded in `corrupt_documents` (see `repair.rs`).
+    pub async fn get_all_analyses(&self) -> Result<Vec<StockAnalysis>> {
+        let collection = self
+            .analysis_collection()
+            .clone_with_type::<RawDocumentBuf>();
+        let mut cursor = collection
+            .find(doc! {})
+            .sort(doc! { "analyzed_at": -1 })
+            .await?;
+
+        let mut results = Vec::new();
+        let mut corrupt = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            let (analysis, report) = repair::read_lenient("stock_analysis", &doc?);
+            results.extend(analysis);
+            corrupt.extend(report);
+        }
+        if !corrupt.is_empty() {
+            let recovered = corrupt.iter().filter(|c| c.recovered).count();
+            warn!(
+                "🩺 {} stock_analysis documents failed to deserialize ({} recovered); see /api/admin/corrupt-documents",
+                corrupt.len(),
+                recovered
+            );
+        }
+        if let Err(e) = self
+            .replace_corrupt_documents("stock_analysis", &corrupt)
+            .await
+        {
+            warn!("Failed to record corrupt documents: {}", e);
+        }
+        Ok(results)
+    }
+
+    pub fn corrupt_documents_collection(&self) -> Collection<CorruptDocument> {
+        self.database.collection("corrupt_documents")
+    }
+
+    /// Replace the corrupt-document report for `source` with the latest
+    /// full read's findings.
+    pub async fn replace_corrupt_documents(
+        &self,
+        source: &str,
+        documents: &[CorruptDocument],
+    ) -> Result<()> {
+        let collection = self.corrupt_documents_collection();
+        collection.delete_many(doc! { "source": source }).await?;
+        if !documents.is_empty() {
+            collection.insert_many(documents).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_corrupt_documents(&self) -> Result<Vec<CorruptDocument>> {
+        let mut cursor = self
+            .corrupt_documents_collection()
+            .find(doc! {})
+            .sort(doc! { "source": 1, "symbol": 1 })
+            .await?;
+        let mut documents = Vec::new();
+        while let Some(document) = cursor.next().await {
+            documents.push(document?);
+        }
+        Ok(documents)
+    }
+
+    pub fn analysis_runs_collection(&self) -> Collection<AnalysisRun> {
+        self.database.collection("analysis_runs")
+    }
 
-        Ok(results)
+    pub async fn save_analysis_run(&self, run: &AnalysisRun) -> Result<()> {
+        self.analysis_runs_collection().insert_one(run).await?;
+        Ok(())
     }
 
-    /// Get aggregated news from all stocks
-    pub async fn get_all_news(
+    /// Runs started at or after `since`, most recent first.
+    pub async fn get_analysis_runs(
         &self,
-        sector: Option<String>,
-        search: Option<String>,
-        page: u32,
-        page_size: u32,
-    ) -> Result<(Vec<AggregatedNewsItem>, u64)> {
-        let collection = self.analysis_collection();
+        since: Option<DateTime<Utc>>,
+        skip: u64,
+        limit: i64,
+    ) -> Result<Vec<AnalysisRun>> {
+        let mut cursor = self
+            .analysis_runs_collection()
+            .find(analysis_runs_filter(since)?)
+            .sort(doc! { "started_at": -1 })
+            .skip(skip)
+            .limit(limit)
+            .await?;
+        let mut runs = Vec::new();
+        while let Some(run) = cursor.next().await {
+            runs.push(run?);
+        }
+        Ok(runs)
+    }
 
-        let mut filter_doc =
-            doc! { "news": { "$exists": true, "$ne": null, "$not": { "$size": 0 } } };
-        if let Some(ref s) = sector {
-            filter_doc.insert("sector", s);
+    /// Number of runs `get_analysis_runs` pages through.
+    pub async fn count_analysis_runs(&self, since: Option<DateTime<Utc>>) -> Result<u64> {
+        Ok(self
+            .analysis_runs_collection()
+            .count_documents(analysis_runs_filter(since)?)
+            .await?)
+    }
+}
+
+fn analysis_runs_filter(since: Option<DateTime<Utc>>) -> Result<Document> {
+    Ok(match since {
+        Some(since) => doc! { "started_at": { "$gte": mongodb::bson::to_bson(&since)? } },
+        None => doc! {},
+    })
+}
+
+/// Upsert a performance row for its id and trading date.
+async fn save_performance(
+    collection: &Collection<IndexPerformance>,
+    perf: &IndexPerformance,
+) -> Result<()> {
+    collection
+        .replace_one(
+            doc! {
+                "index_id": &perf.index_id,
+                "date": mongodb::bson::to_bson(&perf.date)?,
+            },
+            perf,
+        )
+        .upsert(true)
+        .await?;
+    Ok(())
+}
+
+/// The latest `days` performance rows for an id, oldest first.
+/// Up to `limit` analyses with a positive `field`, highest first.
+/// One `$facet` stage computing every market summary list and the total
+/// in a single round-trip. Only equities lead (ETFs, warrants and SPACs
+/// never do), `min_market_cap` applies to every list but the mega caps, and
+/// news is dropped before the lists are built.
+pub(crate) fn market_summary_stages(
+    limit: i64,
+    min_market_cap: Option<f64>,
+    max_price_change_percent: Option<f64>,
+) -> Vec<Document> {
+    let mut base = Document::new();
+    if let Some(min_mc) = min_market_cap {
+        base.insert("market_cap", doc! { "$gte": min_mc });
+    }
+    let list = |filter: Document, sort: Document| -> Vec<Document> {
+        let mut matched = base.clone();
+        matched.extend(filter);
+        vec![
+            doc! { "$match": matched },
+            doc! { "$sort": sort },
+            doc! { "$limit": limit },
+        ]
+    };
+    let gains_by = |field: &str| list(doc! { field: { "$gt": 0.0 } }, doc! { field: -1 });
+    vec![
+        doc! { "$match": { "asset_type": asset_type_clause(&[AssetType::Equity]) } },
+        doc! { "$project": { "news": 0 } },
+        doc! { "$facet": {
+            "top_gainers": list(
+                doc! { "price_change_percent": price_change_summary_filter(0.0, max_price_change_percent) },
+                doc! { "price_change_percent": -1 },
+            ),
+            "top_losers": list(
+                doc! { "price_change_percent": negative_price_change_summary_filter(
+                    0.0,
+                    max_price_change_percent.map(|p| -p),
+                ) },
+                doc! { "price_change_percent": 1 },
+            ),
+            "most_oversold": list(doc! { "rsi": { "$lt": 30.0, "$exists": true } }, doc! { "rsi": 1 }),
+            "most_overbought": list(doc! { "rsi": { "$gt": 70.0, "$exists": true } }, doc! { "rsi": -1 }),
+            "mega_cap_highlights": [
+                { "$match": { "market_cap": { "$gte": 200_000_000_000.0 } } },
+                { "$sort": { "market_cap": -1 } },
+                { "$limit": limit },
+            ],
+            "top_weekly_gainers": gains_by("performance.return_1w_pct"),
+            "top_monthly_gainers": gains_by("performance.return_1m_pct"),
+            "top_ytd_gainers": gains_by("performance.return_ytd_pct"),
+            "total": [{ "$match": base.clone() }, { "$count": "n" }],
+        } },
+    ]
+}
+
+/// The `MarketSummary` from the single document `market_summary_stages`
+/// produces, with share classes collapsed and each list cut to `limit`.
+pub(crate) fn market_summary_from_facets(mut facets: Document, limit: usize) -> MarketSummary {
+    let mut list = |name: &str| -> Vec<StockAnalysis> {
+        match facets.remove(name) {
+            Some(Bson::Array(rows)) => rows
+                .into_iter()
+                .filter_map(|row| match row {
+                    Bson::Document(doc) => mongodb::bson::from_document(doc).ok(),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
         }
+    };
+    let top_gainers = list("top_gainers");
+    let top_losers = list("top_losers");
+    let most_oversold = list("most_oversold");
+    let most_overbought = list("most_overbought");
+    let mega_cap_highlights = list("mega_cap_highlights");
+    let top_weekly_gainers = list("top_weekly_gainers");
+    let top_monthly_gainers = list("top_monthly_gainers");
+    let top_ytd_gainers = list("top_ytd_gainers");
+    let total_stocks = match facets.get_array("total").ok().and_then(|t| t.first()) {
+        Some(Bson::Document(total)) => match total.get("n") {
+            Some(Bson::Int32(n)) => *n as usize,
+            Some(Bson::Int64(n)) => *n as usize,
+            _ => 0,
+        },
+        _ => 0,
+    };
+
+    let leaders = |rows: Vec<StockAnalysis>| {
+        let mut rows = share_classes::dedupe_by_company(rows, |a| a.symbol.as_str());
+        rows.truncate(limit);
+        rows
+    };
+
+    MarketSummary {
+        total_stocks,
+        top_gainers: leaders(top_gainers),
+        top_losers: leaders(top_losers),
+        most_oversold: leaders(most_oversold),
+        most_overbought: leaders(most_overbought),
+        mega_cap_highlights: leaders(mega_cap_highlights),
+        top_weekly_gainers: leaders(top_weekly_gainers),
+        top_monthly_gainers: leaders(top_monthly_gainers),
+        top_ytd_gainers: leaders(top_ytd_gainers),
+        generated_at: Utc::now(),
+    }
+}
 
-        let mut cursor = collection.find(filter_doc).await?;
+/// What list queries (`get_latest_analyses`, counts, the market summary)
+/// read from.
+enum ListSource {
+    Live(Collection<StockAnalysis>),
+    /// Each symbol's newest `analysis_history` snapshot with a `version` at
+    /// or below this one.
+    AsOf {
+        history: Collection<Document>,
+        version: i64,
+    },
+}
 
-        let mut all_news: Vec<AggregatedNewsItem> = Vec::new();
-        while let Some(doc) = cursor.next().await {
-            if let Ok(analysis) = doc {
-                if let Some(news_items) = analysis.news {
-                    for item in news_items {
-                        // Apply search filter
-                        if let Some(ref query) = search {
-                            let q = query.to_lowercase();
-                            if !item.title.to_lowercase().contains(&q)
-                                && !analysis.symbol.to_lowercase().contains(&q)
-                            {
-                                continue;
-                            }
-                        }
-                        all_news.push(AggregatedNewsItem {
-                            symbol: analysis.symbol.clone(),
-                            sector: analysis.sector.clone(),
-                            title: item.title,
-                            url: item.url,
-                            publisher: item.publisher,
-                            created: item.created,
-                            ago: item.ago,
-                        });
+impl ListSource {
+    /// Rows matching `filter`, sorted, skipped and limited like a `find`.
+    /// Rows that don't deserialize are skipped.
+    async fn find(
+        &self,
+        filter: Document,
+        sort: Document,
+        skip: u64,
+        limit: i64,
+    ) -> Result<Vec<StockAnalysis>> {
+        let mut rows = Vec::new();
+        match self {
+            ListSource::Live(collection) => {
+                let options = FindOptions::builder()
+                    .sort(sort)
+                    .skip(skip)
+                    .limit(limit)
+                    .build();
+                let mut cursor = collection.find(filter).with_options(options).await?;
+                while let Some(doc) = cursor.next().await {
+                    if let Ok(analysis) = doc {
+                        rows.push(analysis);
+                    }
+                }
+            }
+            ListSource::AsOf { history, version } => {
+                let mut pipeline = as_of_stages(*version);
+                pipeline.push(doc! { "$match": filter });
+                pipeline.push(doc! { "$sort": sort });
+                if skip > 0 {
+                    pipeline.push(doc! { "$skip": skip as i64 });
+                }
+                pipeline.push(doc! { "$limit": limit });
+                let mut cursor = history.aggregate(pipeline).allow_disk_use(true).await?;
+                while let Some(doc) = cursor.next().await {
+                    if let Ok(analysis) = mongodb::bson::from_document(doc?) {
+                        rows.push(analysis);
                     }
                 }
             }
         }
+        Ok(rows)
+    }
 
-        // Sort by created date descending (most recent first)
-        all_news.sort_by(|a, b| b.created.cmp(&a.created));
-
-        let total = all_news.len() as u64;
-        let skip = ((page - 1) * page_size) as usize;
-        let paginated: Vec<AggregatedNewsItem> = all_news
-            .into_iter()
-            .skip(skip)
-            .take(page_size as usize)
-            .collect();
+    /// Run `stages` over the live analyses or the snapshots as of the
+    /// version.
+    async fn aggregate(&self, stages: Vec<Document>) -> Result<Vec<Document>> {
+        let mut cursor = match self {
+            ListSource::Live(collection) => {
+                collection
+                    .clone_with_type::<Document>()
+                    .aggregate(stages)
+                    .allow_disk_use(true)
+                    .await?
+            }
+            ListSource::AsOf { history, version } => {
+                let mut pipeline = as_of_stages(*version);
+                pipeline.extend(stages);
+                history.aggregate(pipeline).allow_disk_use(true).await?
+            }
+        };
+        let mut docs = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            docs.push(doc?);
+        }
+        Ok(docs)
+    }
 
-        Ok((paginated, total))
+    async fn count(&self, filter: Document) -> Result<u64> {
+        match self {
+            ListSource::Live(collection) => Ok(collection.count_documents(filter).await?),
+            ListSource::AsOf { history, version } => {
+                let mut pipeline = as_of_stages(*version);
+                pipeline.push(doc! { "$match": filter });
+                pipeline.push(doc! { "$count": "n" });
+                let mut cursor = history.aggregate(pipeline).allow_disk_use(true).await?;
+                match cursor.next().await {
+                    Some(doc) => Ok(doc?.get_i32("n").map(|n| n as u64).unwrap_or(0)),
+                    None => Ok(0),
+                }
+            }
+        }
     }
+}
 
-    /// Get all analyses from the database
-    pub async fn get_all_analyses(&self) -> Result<Vec<StockAnalysis>> {
-        let collection = self.analysis_collection();
-        let mut cursor = collection
-            .find(doc! {})
-            .sort(doc! { "analyzed_at": -1 })
-            .await?;
+/// One collection's size, from `$collStats`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CollectionStats {
+    pub name: String,
+    pub count: u64,
+    /// Uncompressed size of the documents.
+    pub size_bytes: u64,
+    /// Space the documents take on disk.
+    pub storage_bytes: u64,
+    pub index_bytes: u64,
+    /// Days kept for the collections `retention.rs` prunes, when enabled.
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+}
 
-        let mut results = Vec::new();
-        while let Some(doc) = cursor.next().await {
-            if let Ok(analysis) = doc {
-                results.push(analysis);
+impl CollectionStats {
+    /// Read a `$collStats` result, whose sizes may come back as any numeric
+    /// BSON type. Missing values read as 0.
+    fn from_coll_stats(name: String, doc: &Document) -> Self {
+        let storage = doc.get_document("storageStats").ok();
+        let number = |key: &str| -> u64 {
+            match storage.and_then(|s| s.get(key)) {
+                Some(Bson::Int32(n)) => (*n).max(0) as u64,
+                Some(Bson::Int64(n)) => (*n).max(0) as u64,
+                Some(Bson::Double(n)) => n.max(0.0) as u64,
+                _ => 0,
             }
+        };
+        Self {
+            count: number("count"),
+            size_bytes: number("size"),
+            storage_bytes: number("storageSize"),
+            index_bytes: number("totalIndexSize"),
+            retention_days: None,
+            name,
         }
-        Ok(results)
     }
 }
 
+/// Members shown per sector in `top_performers` / `bottom_performers`.
+const SECTOR_PERFORMERS: i32 = 3;
+
+/// One `SectorPerformance` document per sector, best average change first.
+/// A missing change ranks as 0% and a sector with no RSI averages 0.
+fn sector_performance_pipeline() -> Vec<Document> {
+    let mover = doc! { "symbol": "$symbol", "price_change_percent": "$price_change_percent" };
+    vec![
+        doc! { "$match": { "sector": { "$exists": true, "$nin": [null, ""] } } },
+        doc! { "$project": { "news": 0 } },
+        doc! { "$set": { "change_rank": { "$ifNull": ["$price_change_percent", 0.0] } } },
+        doc! { "$group": {
+            "_id": "$sector",
+            "stock_count": { "$sum": 1 },
+            "avg_change_percent": { "$avg": "$price_change_percent" },
+            "avg_rsi": { "$avg": "$rsi" },
+            "total_market_cap": { "$sum": "$market_cap" },
+            "top_gainer": { "$top": { "sortBy": { "change_rank": -1 }, "output": mover.clone() } },
+            "top_loser": { "$top": { "sortBy": { "change_rank": 1 }, "output": mover } },
+            "top_performers": { "$topN": {
+                "n": SECTOR_PERFORMERS,
+                "sortBy": { "change_rank": -1 },
+                "output": "$$ROOT",
+            } },
+            "bottom_performers": { "$topN": {
+                "n": SECTOR_PERFORMERS,
+                "sortBy": { "change_rank": 1 },
+                "output": "$$ROOT",
+            } },
+        } },
+        doc! { "$set": {
+            "sector": "$_id",
+            "avg_change_percent": { "$ifNull": ["$avg_change_percent", 0.0] },
+            "avg_rsi": { "$ifNull": ["$avg_rsi", 0.0] },
+            "total_market_cap": { "$toDouble": "$total_market_cap" },
+        } },
+        doc! { "$unset": ["_id", "top_performers.change_rank", "bottom_performers.change_rank"] },
+        doc! { "$sort": { "avg_change_percent": -1, "sector": 1 } },
+    ]
+}
+
+/// Order search candidates, each with its text score: an exact symbol,
+/// then symbol prefixes (shortest first), then names with a word starting
+/// with `query`, then text-only matches; ties go to the higher text score,
+/// then the larger market cap.
+fn rank_symbol_matches(
+    query: &str,
+    candidates: Vec<(Stock, f64)>,
+    limit: usize,
+) -> Vec<SymbolMatch> {
+    let symbol_query = query.trim().to_uppercase();
+    let name_query = query.trim().to_lowercase();
+    let tier = |stock: &Stock| {
+        if stock.symbol == symbol_query {
+            0
+        } else if stock.symbol.starts_with(&symbol_query) {
+            1
+        } else if stock
+            .name
+            .to_lowercase()
+            .split_whitespace()
+            .any(|word| word.starts_with(&name_query))
+        {
+            2
+        } else {
+            3
+        }
+    };
+    let mut ranked: Vec<(u8, Stock, f64)> = candidates
+        .into_iter()
+        .map(|(stock, score)| (tier(&stock), stock, score))
+        .collect();
+    ranked.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then_with(|| match a.0 {
+                1 => a.1.symbol.len().cmp(&b.1.symbol.len()),
+                _ => std::cmp::Ordering::Equal,
+            })
+            .then_with(|| b.2.total_cmp(&a.2))
+            .then_with(|| b.1.market_cap.cmp(&a.1.market_cap))
+            .then_with(|| a.1.symbol.cmp(&b.1.symbol))
+    });
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, stock, _)| SymbolMatch {
+            symbol: stock.symbol,
+            name: stock.name,
+            asset_type: stock.asset_type,
+            price: stock.price,
+            market_cap: stock.market_cap,
+        })
+        .collect()
+}
+
+/// Aggregation stages turning `analysis_history` into one document per
+/// symbol: its newest snapshot with a `version` at or below `version`.
+fn as_of_stages(version: i64) -> Vec<Document> {
+    vec![
+        doc! { "$match": { VERSION_FIELD: { "$lte": version } } },
+        doc! { "$sort": { "symbol": 1, VERSION_FIELD: -1 } },
+        doc! { "$group": { "_id": "$symbol", "latest": { "$first": "$$ROOT" } } },
+        doc! { "$replaceRoot": { "newRoot": "$latest" } },
+    ]
+}
+
+async fn performance_history(
+    collection: &Collection<IndexPerformance>,
+    id: &str,
+    days: i64,
+) -> Result<Vec<IndexPerformance>> {
+    let mut cursor = collection
+        .find(doc! { "index_id": id })
+        .sort(doc! { "date": -1 })
+        .limit(days)
+        .await?;
+    let mut rows = Vec::new();
+    while let Some(row) = cursor.next().await {
+        rows.push(row?);
+    }
+    rows.reverse();
+    Ok(rows)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
 
     fn empty_filter() -> StockFilter {
         StockFilter {
@@ -617,12 +2916,35 @@ mod tests {
             sectors: None,
             only_oversold: None,
             only_overbought: None,
+            only_stoch_oversold: None,
+            only_stoch_overbought: None,
+            cross_signal: None,
+            supertrend_uptrend: None,
             symbol_search: None,
             min_stochastic_k: None,
             max_stochastic_k: None,
+            min_williams_r: None,
+            max_williams_r: None,
+            min_cci: None,
+            max_cci: None,
+            min_mfi: None,
+            max_mfi: None,
             min_bandwidth: None,
             max_bandwidth: None,
             max_abs_price_change_percent: None,
+            primary_class_only: None,
+            index: None,
+            theme: None,
+            tags: None,
+            asset_type: Some("all".to_string()),
+            return_period: None,
+            min_return_pct: None,
+            max_return_pct: None,
+            percentile_metric: None,
+            min_percentile: None,
+            max_percentile: None,
+            min_rs_rank: None,
+            as_of: None,
             sort_by: None,
             sort_order: None,
             page: None,
@@ -640,6 +2962,188 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_filter_doc_asset_type() {
+        let mut f = empty_filter();
+        f.asset_type = None;
+        let d = build_filter_doc(&f);
+        assert_eq!(
+            d,
+            doc! { "asset_type": { "$in": [Bson::from("equity"), Bson::Null] } }
+        );
+
+        f.asset_type = Some("etf,spac".to_string());
+        let d = build_filter_doc(&f);
+        assert_eq!(d, doc! { "asset_type": { "$in": ["etf", "spac"] } });
+    }
+
+    #[test]
+    fn test_partial_update_rejects_protected_fields() {
+        let update = partial_update(doc! { "price": 191.2, "news": [] }).unwrap();
+        assert_eq!(update, doc! { "$set": { "price": 191.2, "news": [] } });
+
+        assert!(partial_update(doc! {}).is_err());
+        assert!(partial_update(doc! { "symbol": "MSFT" }).is_err());
+        assert!(partial_update(doc! { "analyzed_at.x": 1 }).is_err());
+        assert!(partial_update(doc! { "$unset": { "news": "" } }).is_err());
+    }
+
+    #[test]
+    fn test_stale_guard() {
+        let guard = stale_guard("AAPL", 1_748_874_600_000_000);
+        assert_eq!(guard.get_str("symbol").unwrap(), "AAPL");
+        let branches = guard.get_array("$or").unwrap();
+        assert_eq!(
+            branches[0].as_document().unwrap(),
+            &doc! { "version": { "$lte": 1_748_874_600_000_000_i64 } }
+        );
+        assert_eq!(branches.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_mongo_settings() {
+        assert!(matches!(
+            parse_read_preference("secondary_preferred").unwrap(),
+            ReadPreference::SecondaryPreferred { .. }
+        ));
+        assert!(matches!(
+            parse_read_preference("primaryPreferred").unwrap(),
+            ReadPreference::PrimaryPreferred { .. }
+        ));
+        assert!(parse_read_preference("fastest").is_err());
+
+        assert_eq!(
+            parse_write_concern("majority").unwrap().w,
+            Some(Acknowledgment::Majority)
+        );
+        assert_eq!(
+            parse_write_concern("2").unwrap().w,
+            Some(Acknowledgment::Nodes(2))
+        );
+        assert!(parse_write_concern("0").is_err());
+    }
+
+    #[test]
+    fn test_sort_index_coverage() {
+        let indexes = vec![
+            doc! { "_id": 1 },
+            doc! { "sector": 1, "market_cap": -1 },
+            doc! { "rsi": 1, "analyzed_at": -1 },
+        ];
+        assert!(sort_has_index(&indexes, "rsi"));
+        // A compound index only serves an unfiltered sort on its first key.
+        assert!(!sort_has_index(&indexes, "market_cap"));
+        assert!(!sort_has_index(&indexes, "analyzed_at"));
+    }
+
+    #[test]
+    fn test_return_filter_and_sort() {
+        let mut f = empty_filter();
+        f.min_return_pct = Some(5.0);
+        let d = build_filter_doc(&f);
+        assert_eq!(
+            d.get_document("performance.return_1m_pct").unwrap(),
+            &doc! { "$gte": 5.0 }
+        );
+
+        f.return_period = Some("YTD".into());
+        f.max_return_pct = Some(50.0);
+        let d = build_filter_doc(&f);
+        assert_eq!(
+            d.get_document("performance.return_ytd_pct").unwrap(),
+            &doc! { "$gte": 5.0, "$lte": 50.0 }
+        );
+        assert!(return_field(Some("2y")).is_none());
+        assert_eq!(
+            allowed_sort_field(Some("return_1w_pct")),
+            "performance.return_1w_pct"
+        );
+        assert_eq!(allowed_sort_field(Some("sortino_ratio")), "sortino_ratio");
+    }
+
+    #[test]
+    fn test_index_membership() {
+        let mut f = empty_filter();
+        f.index = Some(" SP500 ".into());
+        let d = build_filter_doc(&f);
+        assert_eq!(d.get_str("indexes").unwrap(), "sp500");
+    }
+
+    #[test]
+    fn test_scope_to_symbols_keeps_symbol_search() {
+        let mut f = empty_filter();
+        f.symbol_search = Some("nv".to_string());
+        let mut d = build_filter_doc(&f);
+        scope_to_symbols(&mut d, &["NVDA".to_string(), "AMD".to_string()]);
+        assert!(d.get("symbol").is_some());
+        let scoped = d.get_array("$and").unwrap()[0].as_document().unwrap();
+        let symbols = scoped
+            .get_document("symbol")
+            .unwrap()
+            .get_array("$in")
+            .unwrap();
+        assert_eq!(symbols.len(), 2);
+    }
+
+    #[test]
+    fn test_primary_class_only_combines_with_symbol_search() {
+        let mut f = empty_filter();
+        f.primary_class_only = Some(true);
+        let d = build_filter_doc(&f);
+        let symbol = d.get_document("symbol").unwrap();
+        let excluded = symbol.get_array("$nin").unwrap();
+        assert!(excluded.contains(&Bson::String("GOOG".into())));
+        assert!(symbol.get("$regex").is_none());
+
+        f.symbol_search = Some("goo".to_string());
+        let d = build_filter_doc(&f);
+        let symbol = d.get_document("symbol").unwrap();
+        assert!(symbol.get("$regex").is_some());
+        assert!(symbol.get("$nin").is_some());
+    }
+
+    #[test]
+    fn test_percentile_filter_ranges_on_the_chosen_rank() {
+        let mut f = empty_filter();
+        f.percentile_metric = Some("price_change_percent".to_string());
+        f.min_percentile = Some(90.0);
+        let d = build_filter_doc(&f);
+        let range = d.get_document("percentiles.price_change_percent").unwrap();
+        assert_eq!(range.get_f64("$gte").unwrap(), 90.0);
+        assert!(range.get("$lte").is_none());
+
+        f.percentile_metric = Some("beta".to_string());
+        assert!(build_filter_doc(&f)
+            .keys()
+            .all(|k| !k.starts_with("percentiles")));
+    }
+
+    #[test]
+    fn test_min_rs_rank_combines_with_a_percentile_range() {
+        let mut f = empty_filter();
+        f.min_rs_rank = Some(80.0);
+        f.percentile_metric = Some("rsi".to_string());
+        f.max_percentile = Some(30.0);
+        let d = build_filter_doc(&f);
+        let rs = d.get_document("percentiles.rs_rank").unwrap();
+        assert_eq!(rs.get_f64("$gte").unwrap(), 80.0);
+        assert!(rs.get("$lte").is_none());
+        let rsi = d.get_document("percentiles.rsi").unwrap();
+        assert_eq!(rsi.get_f64("$lte").unwrap(), 30.0);
+    }
+
+    #[test]
+    fn test_tags_filter_matches_any_normalized_tag() {
+        let mut f = empty_filter();
+        f.tags = Some(vec!["Earnings Play".to_string(), "bad/tag".to_string()]);
+        let d = build_filter_doc(&f);
+        let tags = d.get_document("tags").unwrap().get_array("$in").unwrap();
+        assert_eq!(tags, &vec![Bson::String("earnings-play".into())]);
+
+        f.tags = Some(Vec::new());
+        assert!(build_filter_doc(&f).get("tags").is_none());
+    }
+
     #[test]
     fn test_price_range_merges_gte_and_lte() {
         // Regression: prior code called filter_doc.insert("price", ...) twice,
@@ -715,6 +3219,27 @@ mod tests {
         assert_eq!(bw.get_f64("$lte").unwrap(), 40.0);
     }
 
+    #[test]
+    fn test_oscillator_ranges() {
+        let mut f = empty_filter();
+        f.max_williams_r = Some(-80.0);
+        f.max_cci = Some(-100.0);
+        f.min_mfi = Some(10.0);
+        f.max_mfi = Some(20.0);
+        let d = build_filter_doc(&f);
+
+        let williams = d.get_document("williams_r").unwrap();
+        assert!(williams.get("$gte").is_none());
+        assert_eq!(williams.get_f64("$lte").unwrap(), -80.0);
+        assert_eq!(
+            d.get_document("cci").unwrap().get_f64("$lte").unwrap(),
+            -100.0
+        );
+        let mfi = d.get_document("mfi").unwrap();
+        assert_eq!(mfi.get_f64("$gte").unwrap(), 10.0);
+        assert_eq!(mfi.get_f64("$lte").unwrap(), 20.0);
+    }
+
     #[test]
     fn test_volume_only_has_gte() {
         let mut f = empty_filter();
@@ -734,6 +3259,96 @@ mod tests {
         assert_eq!(arr.len(), 2);
     }
 
+    #[test]
+    fn test_collection_stats_read_any_numeric_type() {
+        let doc = doc! {
+            "ns": "stocks.analysis_history",
+            "storageStats": {
+                "count": 1200_i32,
+                "size": 3_500_000_000_i64,
+                "storageSize": 1.2e9,
+                "totalIndexSize": 40_960_i64,
+            },
+        };
+        let stats = CollectionStats::from_coll_stats("analysis_history".to_string(), &doc);
+        assert_eq!(
+            stats,
+            CollectionStats {
+                name: "analysis_history".to_string(),
+                count: 1200,
+                size_bytes: 3_500_000_000,
+                storage_bytes: 1_200_000_000,
+                index_bytes: 40_960,
+                retention_days: None,
+            }
+        );
+        let empty = CollectionStats::from_coll_stats("x".to_string(), &doc! {});
+        assert_eq!(empty.count, 0);
+    }
+
+    #[test]
+    fn test_sector_performance_pipeline_output_reads_back() {
+        let stages = sector_performance_pipeline();
+        let group = stages
+            .iter()
+            .find_map(|stage| stage.get_document("$group").ok())
+            .unwrap();
+        for field in [
+            "stock_count",
+            "avg_rsi",
+            "total_market_cap",
+            "top_gainer",
+            "top_loser",
+        ] {
+            assert!(group.contains_key(field), "missing {}", field);
+        }
+        // What the final stage emits for a sector: `$sum` counts as Int32.
+        let row = doc! {
+            "sector": "Technology",
+            "stock_count": 2,
+            "avg_change_percent": 1.5,
+            "avg_rsi": 55.0,
+            "total_market_cap": 5.0e12,
+            "top_gainer": { "symbol": "NVDA", "price_change_percent": 3.0 },
+            "top_loser": { "symbol": "AAPL", "price_change_percent": null },
+            "top_performers": [],
+            "bottom_performers": [],
+        };
+        let sector: SectorPerformance = mongodb::bson::from_document(row).unwrap();
+        assert_eq!(sector.stock_count, 2);
+        assert_eq!(sector.top_gainer.unwrap().symbol, "NVDA");
+        assert_eq!(sector.top_loser.unwrap().price_change_percent, None);
+    }
+
+    #[test]
+    fn test_as_of_stages_pick_latest_snapshot_per_symbol() {
+        let as_of = Utc.with_ymd_and_hms(2025, 3, 14, 20, 0, 0).unwrap();
+        let stages = as_of_stages(as_of.timestamp_micros());
+        let bound = stages[0]
+            .get_document("$match")
+            .unwrap()
+            .get_document(VERSION_FIELD)
+            .unwrap();
+        assert_eq!(bound.get_i64("$lte").unwrap(), as_of.timestamp_micros());
+        // Newest first within a symbol, so `$first` is the latest snapshot.
+        let sort = stages[1].get_document("$sort").unwrap();
+        assert_eq!(
+            sort.keys().collect::<Vec<_>>(),
+            vec!["symbol", VERSION_FIELD]
+        );
+        assert_eq!(sort.get_i32(VERSION_FIELD).unwrap(), -1);
+        assert_eq!(
+            stages[2]
+                .get_document("$group")
+                .unwrap()
+                .get_str("_id")
+                .unwrap(),
+            "$symbol"
+        );
+        // Filters then apply to the snapshot fields as they do to live rows.
+        assert!(stages[3].contains_key("$replaceRoot"));
+    }
+
     #[test]
     fn test_empty_sectors_skipped() {
         let mut f = empty_filter();
@@ -756,6 +3371,40 @@ mod tests {
         assert!(d2.get("is_oversold").is_none());
     }
 
+    #[test]
+    fn test_cross_signal_filter() {
+        let mut f = empty_filter();
+        f.cross_signal = Some(CrossSignal::GoldenCross);
+        assert_eq!(
+            build_filter_doc(&f),
+            doc! { "cross_signal": "golden_cross" }
+        );
+        f.cross_signal = Some(CrossSignal::None);
+        assert_eq!(
+            build_filter_doc(&f),
+            doc! { "cross_signal": { "$in": ["none", Bson::Null] } }
+        );
+    }
+
+    #[test]
+    fn test_supertrend_filter() {
+        let mut f = empty_filter();
+        f.supertrend_uptrend = Some(true);
+        assert_eq!(build_filter_doc(&f), doc! { "supertrend.uptrend": true });
+        // Rows without a SuperTrend match neither direction.
+        f.supertrend_uptrend = Some(false);
+        assert_eq!(build_filter_doc(&f), doc! { "supertrend.uptrend": false });
+    }
+
+    #[test]
+    fn test_stochastic_flags() {
+        let mut f = empty_filter();
+        f.only_stoch_oversold = Some(true);
+        f.only_stoch_overbought = Some(false);
+        let d = build_filter_doc(&f);
+        assert_eq!(d, doc! { "stoch_oversold": true });
+    }
+
     #[test]
     fn test_max_abs_price_change_percent_caps_both_sides() {
         let mut f = empty_filter();
@@ -805,4 +3454,93 @@ mod tests {
         assert_eq!(pct.get_f64("$lt").unwrap(), 0.0);
         assert_eq!(pct.get_f64("$gte").unwrap(), -25.0);
     }
+
+    #[test]
+    fn test_rank_symbol_matches() {
+        let listing = |symbol: &str, name: &str, market_cap: u64| Stock {
+            id: None,
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            price: 1.0,
+            market_cap: Some(market_cap),
+            volume: None,
+            sector: None,
+            asset_type: AssetType::Equity,
+            last_updated: Utc::now(),
+        };
+        let candidates = vec![
+            (listing("MAPP", "Mapp Biotech", 5), 1.1),
+            (listing("APPLX", "Applex Ltd", 1), 0.0),
+            (listing("AAPL", "Apple Inc. Common Stock", 3_000), 0.0),
+            (listing("APPL", "Appl Holdings", 2), 0.0),
+            (listing("APP", "AppLovin Corporation", 100), 0.0),
+        ];
+        let symbols = |matches: Vec<SymbolMatch>| -> Vec<String> {
+            matches.into_iter().map(|m| m.symbol).collect()
+        };
+        assert_eq!(
+            symbols(rank_symbol_matches("appl", candidates.clone(), 10)),
+            vec!["APPL", "APPLX", "AAPL", "APP", "MAPP"]
+        );
+        assert_eq!(
+            symbols(rank_symbol_matches(" app ", candidates, 2)),
+            vec!["APP", "APPL"]
+        );
+    }
+
+    #[test]
+    fn test_market_summary_is_one_facet_over_equities() {
+        let stages = market_summary_stages(20, Some(1e10), Some(25.0));
+        assert_eq!(stages.len(), 3);
+        assert!(stages[0]
+            .get_document("$match")
+            .unwrap()
+            .contains_key("asset_type"));
+        let facet = stages[2].get_document("$facet").unwrap();
+        assert_eq!(
+            facet.keys().collect::<Vec<_>>(),
+            vec![
+                "top_gainers",
+                "top_losers",
+                "most_oversold",
+                "most_overbought",
+                "mega_cap_highlights",
+                "top_weekly_gainers",
+                "top_monthly_gainers",
+                "top_ytd_gainers",
+                "total",
+            ]
+        );
+        let first_match = |name: &str| {
+            facet.get_array(name).unwrap()[0]
+                .as_document()
+                .unwrap()
+                .get_document("$match")
+                .unwrap()
+                .clone()
+        };
+        // The market cap floor applies everywhere except the mega caps.
+        let gainers = first_match("top_gainers");
+        assert_eq!(
+            gainers.get_document("market_cap").unwrap(),
+            &doc! { "$gte": 1e10 }
+        );
+        assert_eq!(
+            gainers
+                .get_document("price_change_percent")
+                .unwrap()
+                .get_f64("$lte")
+                .unwrap(),
+            25.0
+        );
+        assert_eq!(
+            first_match("mega_cap_highlights"),
+            doc! { "market_cap": { "$gte": 200_000_000_000.0 } }
+        );
+        assert_eq!(
+            first_match("total"),
+            doc! { "market_cap": { "$gte": 1e10 } }
+        );
+        assert!(first_match("top_ytd_gainers").contains_key("performance.return_ytd_pct"));
+    }
 }
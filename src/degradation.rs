@@ -0,0 +1,194 @@
+//! Automatic degraded mode when upstreams are failing.
+//!
+//! Outcomes of Yahoo price fetches and NASDAQ stage calls are tracked over a
+//! sliding window. When either upstream's error rate reaches the threshold the
+//! engine switches to degraded mode for a cooldown period: the NASDAQ stages
+//! and earnings are skipped (the previous values are carried over), sector ETF
+//! refreshes pause, and cycles that start degraded use a longer Yahoo delay.
+//! A Yahoo trip also ends the current cycle early rather than burning through
+//! the rest of the universe on failures. The mode clears itself once the
+//! cooldown has passed; if the upstream is still failing it trips again.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::EngineMode;
+use crate::pipeline::Stage;
+
+/// Stages skipped while degraded.
+pub const DEGRADED_SKIPPED_STAGES: [Stage; 3] =
+    [Stage::Technicals, Stage::News, Stage::Fundamentals];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upstream {
+    Yahoo,
+    Nasdaq,
+}
+
+impl Upstream {
+    pub fn name(self) -> &'static str {
+        match self {
+            Upstream::Yahoo => "yahoo",
+            Upstream::Nasdaq => "nasdaq",
+        }
+    }
+
+    /// The upstream a pipeline stage's outcome is attributed to, if tracked.
+    pub fn of_stage(stage: Stage) -> Option<Upstream> {
+        match stage {
+            Stage::Technicals | Stage::News => Some(Upstream::Nasdaq),
+            _ => None,
+        }
+    }
+}
+
+/// Thresholds for entering degraded mode.
+#[derive(Debug, Clone)]
+pub struct DegradationPolicy {
+    /// Error rate (0–1] over a full window that trips degraded mode.
+    pub error_rate: f64,
+    /// Number of most recent outcomes per upstream the rate is computed over.
+    pub window: usize,
+    /// How long degraded mode lasts once tripped.
+    pub cooldown: Duration,
+    /// Yahoo delay multiplier for cycles that start degraded.
+    pub delay_multiplier: u64,
+}
+
+#[derive(Default)]
+struct State {
+    yahoo: VecDeque<bool>,
+    nasdaq: VecDeque<bool>,
+    /// When degraded mode was (last) tripped, and why.
+    tripped: Option<(Instant, String)>,
+}
+
+pub struct DegradationMonitor {
+    policy: DegradationPolicy,
+    state: Mutex<State>,
+}
+
+impl DegradationMonitor {
+    pub fn new(policy: DegradationPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn policy(&self) -> &DegradationPolicy {
+        &self.policy
+    }
+
+    /// Record one upstream call. Returns `Some(reason)` when this outcome
+    /// tripped (or re-tripped) degraded mode.
+    pub fn record(&self, upstream: Upstream, ok: bool) -> Option<String> {
+        self.record_at(upstream, ok, Instant::now())
+    }
+
+    fn record_at(&self, upstream: Upstream, ok: bool, now: Instant) -> Option<String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let window = match upstream {
+            Upstream::Yahoo => &mut state.yahoo,
+            Upstream::Nasdaq => &mut state.nasdaq,
+        };
+        window.push_back(ok);
+        if window.len() > self.policy.window {
+            window.pop_front();
+        }
+        if window.len() < self.policy.window {
+            return None;
+        }
+        let failed = window.iter().filter(|ok| !**ok).count();
+        let rate = failed as f64 / window.len() as f64;
+        if rate < self.policy.error_rate {
+            return None;
+        }
+
+        let reason = format!(
+            "{} error rate {:.0}% over last {} calls",
+            upstream.name(),
+            rate * 100.0,
+            window.len()
+        );
+        state.yahoo.clear();
+        state.nasdaq.clear();
+        state.tripped = Some((now, reason.clone()));
+        Some(reason)
+    }
+
+    /// Current mode and, when degraded, the reason it tripped.
+    pub fn mode(&self) -> (EngineMode, Option<String>) {
+        self.mode_at(Instant::now())
+    }
+
+    fn mode_at(&self, now: Instant) -> (EngineMode, Option<String>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match &state.tripped {
+            Some((since, reason)) if now.duration_since(*since) < self.policy.cooldown => {
+                (EngineMode::Degraded, Some(reason.clone()))
+            }
+            Some(_) => {
+                state.tripped = None;
+                (EngineMode::Normal, None)
+            }
+            None => (EngineMode::Normal, None),
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.mode().0 == EngineMode::Degraded
+    }
+
+    /// Whether `stage` should be skipped right now.
+    pub fn skips(&self, stage: Stage) -> bool {
+        DEGRADED_SKIPPED_STAGES.contains(&stage) && self.is_degraded()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> DegradationMonitor {
+        DegradationMonitor::new(DegradationPolicy {
+            error_rate: 0.5,
+            window: 4,
+            cooldown: Duration::from_secs(60),
+            delay_multiplier: 4,
+        })
+    }
+
+    #[test]
+    fn trips_on_full_window_and_recovers_after_cooldown() {
+        let m = monitor();
+        let t0 = Instant::now();
+        // Not enough samples yet, even though all failed.
+        for _ in 0..3 {
+            assert!(m.record_at(Upstream::Nasdaq, false, t0).is_none());
+        }
+        // Failures on one upstream don't mix with the other's window.
+        assert!(m.record_at(Upstream::Yahoo, false, t0).is_none());
+        assert_eq!(m.mode_at(t0).0, EngineMode::Normal);
+
+        let reason = m.record_at(Upstream::Nasdaq, true, t0).unwrap();
+        assert_eq!(reason, "nasdaq error rate 75% over last 4 calls");
+        assert_eq!(m.mode_at(t0), (EngineMode::Degraded, Some(reason)));
+
+        assert_eq!(
+            m.mode_at(t0 + Duration::from_secs(61)),
+            (EngineMode::Normal, None)
+        );
+    }
+
+    #[test]
+    fn healthy_window_stays_normal() {
+        let m = monitor();
+        let t0 = Instant::now();
+        for ok in [true, false, true, true, true, false] {
+            assert!(m.record_at(Upstream::Yahoo, ok, t0).is_none());
+        }
+        assert_eq!(m.mode_at(t0).0, EngineMode::Normal);
+    }
+}
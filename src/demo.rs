@@ -0,0 +1,117 @@
+//! `auto_analyser_2 --demo`: the API over a bundled dataset, without MongoDB.
+//!
+//! Analyses come from `seed/demo_analyses.json` (compiled in) through a
+//! `MemoryStorage`, so the stored-analysis routes (`/api/stocks`,
+//! `/api/stocks/:symbol`, `/api/stocks/filter`, `/api/market-summary`,
+//! `/health`) work on a fresh checkout. Nothing else is started: no analysis
+//! cycle, background jobs, gRPC facade or change feed. Routes over other
+//! collections get a `MongoDB` handle that never connects and answer with
+//! database errors; live Yahoo and NASDAQ lookups still go out to those
+//! services.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+
+use crate::api::AppState;
+use crate::cache::CacheLayer;
+use crate::config::Config;
+use crate::db::MongoDB;
+use crate::models::{AnalysisProgress, EngineMode, StockAnalysis};
+use crate::storage::{MemoryStorage, Storage};
+
+const FIXTURE: &str = include_str!("../seed/demo_analyses.json");
+
+/// The bundled analyses.
+pub fn fixture() -> Result<Vec<StockAnalysis>> {
+    Ok(serde_json::from_str(FIXTURE)?)
+}
+
+/// Application state backed by the fixture.
+pub async fn state(config: &Config) -> Result<AppState> {
+    let analyses = fixture()?;
+    tracing::info!(
+        "🧪 Demo mode: serving {} bundled analyses without MongoDB",
+        analyses.len()
+    );
+    let storage: Storage = Arc::new(MemoryStorage::new(analyses));
+    let db = MongoDB::unreachable().await;
+    let cache = CacheLayer::new(
+        config.cache_ttl_secs,
+        config.news_cache_ttl_secs,
+        config.cache_memory_budget_mb,
+    )
+    .with_read_through(storage.clone())
+    .with_quote_ttl(std::time::Duration::from_secs(config.quote_cache_ttl_secs));
+    let progress = Arc::new(RwLock::new(AnalysisProgress {
+        total_stocks: 0,
+        analyzed: 0,
+        current_symbol: None,
+        cycle_start: chrono::Utc::now(),
+        errors: 0,
+        last_cycle_started: None,
+        last_cycle_completed: None,
+        last_successful_cycle: None,
+        last_error: None,
+        mode: EngineMode::Normal,
+        degraded_reason: None,
+        saved_symbols: Vec::new(),
+    }));
+
+    Ok(AppState {
+        alert_engine: crate::notifications::AlertEngine::new(db.clone(), false, None).await?,
+        db,
+        storage,
+        cache,
+        progress,
+        yahoo_client: crate::yahoo::YahooFinanceClient::new(),
+        openrouter_client: crate::openrouter::OpenRouterClient::new(None, false),
+        nasdaq_client: crate::nasdaq::NasdaqClient::new(config.nasdaq_request_delay_ms),
+        intraday: crate::intraday::IntradayRelay::new(),
+        analysis_feed: crate::change_feed::AnalysisFeed::new(),
+        api_timezone: config.api_timezone,
+        backups: None,
+        raw_archive: None,
+        signals: crate::ingest::SignalInbox::new(chrono::Duration::hours(
+            config.ingest_signal_ttl_hours,
+        )),
+        notes: Default::default(),
+        ingest_token: config.ingest_token.clone(),
+        maintenance: crate::maintenance::MaintenanceMode::new(config.read_only_mode),
+        weekend: config.weekend_settings(),
+        indicators: config.indicators,
+        history_retention_days: config.history_retention_days,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StockFilter;
+    use crate::storage::StorageBackend;
+
+    #[tokio::test]
+    async fn fixture_loads_into_memory_storage() {
+        let analyses = fixture().unwrap();
+        let total = analyses.len() as u64;
+        let storage = MemoryStorage::new(analyses);
+        assert_eq!(storage.get_analysis_count().await.unwrap(), total);
+        // The default listing keeps to equities, leaving out SPY and QQQ.
+        let equities = storage
+            .get_filtered_count(StockFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(equities, total - 2);
+        let summary = storage
+            .get_market_summary(5, None, None, None)
+            .await
+            .unwrap();
+        assert!(!summary.top_gainers.is_empty() && !summary.most_oversold.is_empty());
+        assert!(storage
+            .get_analysis_by_symbol("aapl")
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
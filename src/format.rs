@@ -0,0 +1,87 @@
+//! Human-readable number formatting for API responses.
+//!
+//! Endpoints that return stocks accept `?humanize=true`, which adds
+//! `*_display` strings next to the raw numbers (`market_cap_display: "2.8T"`)
+//! so thin clients don't have to reimplement the formatting.
+
+use serde_json::Value;
+
+use crate::models::StockAnalysis;
+
+const SUFFIXES: &[(f64, &str)] = &[(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+
+/// Order-of-magnitude format with one decimal: `2.8T`, `51.2M`, `950`.
+pub fn humanize(value: f64) -> String {
+    if !value.is_finite() {
+        return "-".to_string();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    let abs = value.abs();
+    for (i, &(scale, suffix)) in SUFFIXES.iter().enumerate() {
+        if abs >= scale {
+            let scaled = round1(abs / scale);
+            // 999.96B rounds to 1000.0B; promote to the next unit instead.
+            if scaled >= 1000.0 && i > 0 {
+                let (up, up_suffix) = SUFFIXES[i - 1];
+                return format!("{}{:.1}{}", sign, round1(abs / up), up_suffix);
+            }
+            return format!("{}{:.1}{}", sign, scaled, suffix);
+        }
+    }
+    if round1(abs) >= 1000.0 {
+        return format!("{}1.0K", sign);
+    }
+    if abs.fract() == 0.0 {
+        format!("{}{}", sign, abs as u64)
+    } else {
+        format!("{}{:.1}", sign, abs)
+    }
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// Serialize a stock and add `market_cap_display` / `volume_display`.
+pub fn humanized_stock(stock: &StockAnalysis) -> Value {
+    let mut value = serde_json::to_value(stock).unwrap_or(Value::Null);
+    if let Value::Object(map) = &mut value {
+        if let Some(cap) = stock.market_cap {
+            map.insert("market_cap_display".into(), humanize(cap as f64).into());
+        }
+        if let Some(volume) = stock.volume {
+            map.insert("volume_display".into(), humanize(volume as f64).into());
+        }
+    }
+    value
+}
+
+/// Serialize stocks, humanized when requested.
+pub fn stocks_json<'a, I>(stocks: I, humanize: bool) -> Value
+where
+    I: IntoIterator<Item = &'a StockAnalysis>,
+{
+    if humanize {
+        Value::Array(stocks.into_iter().map(humanized_stock).collect())
+    } else {
+        serde_json::to_value(stocks.into_iter().collect::<Vec<_>>()).unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn humanizes_by_order_of_magnitude() {
+        assert_eq!(humanize(2.8e12), "2.8T");
+        assert_eq!(humanize(51_234_567.0), "51.2M");
+        assert_eq!(humanize(1_500.0), "1.5K");
+        assert_eq!(humanize(950.0), "950");
+        assert_eq!(humanize(12.34), "12.3");
+        assert_eq!(humanize(-3.2e9), "-3.2B");
+        assert_eq!(humanize(999_960_000_000.0), "1.0T");
+        assert_eq!(humanize(999.97), "1.0K");
+        assert_eq!(humanize(f64::NAN), "-");
+    }
+}
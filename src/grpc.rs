@@ -0,0 +1,298 @@
+//! tonic gRPC facade over the analysis store.
+//!
+//! Exposes the same read paths as the REST API (`/api/stocks/*`) with
+//! protobuf contracts and a server-streaming variant of the filter endpoint.
+//! The contract lives in `proto/analyser.proto`; `build.rs` generates the
+//! bindings into [`pb`].
+
+use crate::{
+    cache::CacheLayer,
+    models::{HistoricalPrice, StockAnalysis, StockFilter},
+    storage::Storage,
+    yahoo::{PriceAdjustment, YahooFinanceClient},
+};
+use std::{net::SocketAddr, pin::Pin};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+pub mod pb {
+    tonic::include_proto!("analyser.v1");
+}
+
+use pb::analyser_server::{Analyser, AnalyserServer};
+
+/// Page size used when walking the collection for `StreamAnalyses`.
+const STREAM_PAGE_SIZE: u32 = 200;
+const DEFAULT_HISTORY_DAYS: i64 = 90;
+
+#[derive(Clone)]
+pub struct AnalyserService {
+    storage: Storage,
+    cache: CacheLayer,
+    yahoo_client: YahooFinanceClient,
+}
+
+impl AnalyserService {
+    pub fn new(storage: Storage, cache: CacheLayer, yahoo_client: YahooFinanceClient) -> Self {
+        Self {
+            storage,
+            cache,
+            yahoo_client,
+        }
+    }
+
+    pub fn into_server(self) -> AnalyserServer<Self> {
+        AnalyserServer::new(self)
+    }
+}
+
+/// Serve the gRPC facade until the process exits. Spawned next to the axum
+/// server from `main`; a bind failure is logged rather than taking the REST
+/// API down with it.
+pub async fn serve(addr: SocketAddr, service: AnalyserService) {
+    info!("🛰️  gRPC server listening on {}", addr);
+    if let Err(e) = tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve(addr)
+        .await
+    {
+        warn!("gRPC server stopped: {}", e);
+    }
+}
+
+#[tonic::async_trait]
+impl Analyser for AnalyserService {
+    async fn get_analysis(
+        &self,
+        request: Request<pb::GetAnalysisRequest>,
+    ) -> Result<Response<pb::StockAnalysis>, Status> {
+        let symbol = crate::symbols::normalize_symbol_key(&request.into_inner().symbol);
+        if symbol.is_empty() {
+            return Err(Status::invalid_argument("symbol is required"));
+        }
+
+        match self.cache.lookup_stock(&symbol).await {
+            Ok(Some((analysis, _))) => Ok(Response::new((*analysis).clone().into())),
+            Ok(None) => Err(Status::not_found(format!(
+                "Stock '{}' not found. It may not have been analyzed yet.",
+                symbol
+            ))),
+            Err(e) => Err(Status::internal(e.to_string())),
+        }
+    }
+
+    async fn filter_stocks(
+        &self,
+        request: Request<pb::StockFilter>,
+    ) -> Result<Response<pb::FilterStocksResponse>, Status> {
+        let filter: StockFilter = request.into_inner().into();
+        let count_filter = StockFilter {
+            sort_by: None,
+            sort_order: None,
+            page: None,
+            page_size: None,
+            ..filter.clone()
+        };
+
+        let total = self
+            .storage
+            .get_filtered_count(count_filter)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let stocks = self
+            .storage
+            .get_latest_analyses(filter)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(pb::FilterStocksResponse {
+            stocks: stocks.into_iter().map(Into::into).collect(),
+            total,
+        }))
+    }
+
+    type StreamAnalysesStream =
+        Pin<Box<dyn Stream<Item = Result<pb::StockAnalysis, Status>> + Send + 'static>>;
+
+    async fn stream_analyses(
+        &self,
+        request: Request<pb::StockFilter>,
+    ) -> Result<Response<Self::StreamAnalysesStream>, Status> {
+        let base: StockFilter = request.into_inner().into();
+        let storage = self.storage.clone();
+        let (tx, rx) = mpsc::channel(STREAM_PAGE_SIZE as usize);
+
+        tokio::spawn(async move {
+            let mut page = 1;
+            loop {
+                let filter = StockFilter {
+                    page: Some(page),
+                    page_size: Some(STREAM_PAGE_SIZE),
+                    ..base.clone()
+                };
+                let batch = match storage.get_latest_analyses(filter).await {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        let _ = tx.send(Err(Status::internal(e.to_string()))).await;
+                        return;
+                    }
+                };
+                let done = batch.len() < STREAM_PAGE_SIZE as usize;
+                for analysis in batch {
+                    if tx.send(Ok(analysis.into())).await.is_err() {
+                        // Client went away.
+                        return;
+                    }
+                }
+                if done {
+                    return;
+                }
+                page += 1;
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn get_history(
+        &self,
+        request: Request<pb::GetHistoryRequest>,
+    ) -> Result<Response<pb::GetHistoryResponse>, Status> {
+        let request = request.into_inner();
+        let symbol = crate::symbols::normalize_symbol_key(&request.symbol);
+        if symbol.is_empty() {
+            return Err(Status::invalid_argument("symbol is required"));
+        }
+        let days = if request.days > 0 {
+            request.days
+        } else {
+            DEFAULT_HISTORY_DAYS
+        };
+
+        let history = self
+            .yahoo_client
+            .fetch_historical_data(&symbol, days, PriceAdjustment::Raw)
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?;
+
+        Ok(Response::new(pb::GetHistoryResponse {
+            symbol,
+            history: history.into_iter().map(Into::into).collect(),
+        }))
+    }
+}
+
+impl From<StockAnalysis> for pb::StockAnalysis {
+    // Still filling the deprecated double fields for older clients.
+    #[allow(deprecated)]
+    fn from(a: StockAnalysis) -> Self {
+        Self {
+            symbol: a.symbol,
+            price: a.price,
+            price_change: a.price_change,
+            price_change_percent: a.price_change_percent,
+            rsi: a.rsi,
+            sma_20: a.sma_20,
+            sma_50: a.sma_50,
+            macd: a.macd.map(|m| pb::Macd {
+                macd_line: m.macd_line,
+                signal_line: m.signal_line,
+                histogram: m.histogram,
+            }),
+            volume: a.volume.map(|v| v as f64),
+            market_cap: a.market_cap.map(|c| c as f64),
+            volume_shares: a.volume,
+            market_cap_usd: a.market_cap,
+            sector: a.sector,
+            is_oversold: a.is_oversold,
+            is_overbought: a.is_overbought,
+            stoch_oversold: a.stoch_oversold,
+            stoch_overbought: a.stoch_overbought,
+            analyzed_at: a.analyzed_at.to_rfc3339(),
+            asset_type: a.asset_type.to_string(),
+        }
+    }
+}
+
+impl From<HistoricalPrice> for pb::HistoricalPrice {
+    fn from(p: HistoricalPrice) -> Self {
+        Self {
+            date: p.date.to_rfc3339(),
+            open: p.open,
+            high: p.high,
+            low: p.low,
+            close: p.close,
+            volume: p.volume,
+        }
+    }
+}
+
+impl From<pb::StockFilter> for StockFilter {
+    fn from(f: pb::StockFilter) -> Self {
+        StockFilter {
+            min_price: f.min_price,
+            max_price: f.max_price,
+            min_volume: f.min_volume,
+            min_market_cap: f.min_market_cap,
+            max_market_cap: f.max_market_cap,
+            min_rsi: f.min_rsi,
+            max_rsi: f.max_rsi,
+            sectors: (!f.sectors.is_empty()).then_some(f.sectors),
+            only_oversold: f.only_oversold,
+            only_overbought: f.only_overbought,
+            only_stoch_oversold: f.only_stoch_oversold,
+            only_stoch_overbought: f.only_stoch_overbought,
+            symbol_search: f.symbol_search,
+            max_abs_price_change_percent: f.max_abs_price_change_percent,
+            sort_by: f.sort_by,
+            sort_order: f.sort_order,
+            page: f.page,
+            page_size: f.page_size,
+            asset_type: f.asset_type,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn empty_sector_list_maps_to_none() {
+        let filter: StockFilter = pb::StockFilter::default().into();
+        assert!(filter.sectors.is_none());
+        assert!(filter.page.is_none());
+
+        let filter: StockFilter = pb::StockFilter {
+            sectors: vec!["Technology".to_string()],
+            min_rsi: Some(30.0),
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(filter.sectors, Some(vec!["Technology".to_string()]));
+        assert_eq!(filter.min_rsi, Some(30.0));
+    }
+
+    #[test]
+    fn analysis_converts_with_rfc3339_timestamp() {
+        let analysis = StockAnalysis {
+            price_change: Some(1.5),
+            price_change_percent: Some(0.8),
+            rsi: Some(55.0),
+            volume: Some(1_000_000),
+            sector: Some("Technology".to_string()),
+            analyzed_at: Utc.with_ymd_and_hms(2025, 1, 2, 15, 30, 0).unwrap(),
+            ..StockAnalysis::sample("AAPL", 190.0)
+        };
+
+        let message: pb::StockAnalysis = analysis.into();
+        assert_eq!(message.symbol, "AAPL");
+        assert_eq!(message.rsi, Some(55.0));
+        assert!(message.macd.is_none());
+        assert_eq!(message.analyzed_at, "2025-01-02T15:30:00+00:00");
+    }
+}
@@ -0,0 +1,222 @@
+//! New 52-week high and low events.
+//!
+//! Every analysis cycle checks each symbol's latest daily bar against the
+//! previous year of bars and records a `Week52Event` when it trades above the
+//! prior 52-week high or below the prior 52-week low (at most one per
+//! kind/symbol/day). Counting the events per day gives the new-highs vs
+//! new-lows breadth series served with them at `/api/events/52w`.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::models::HistoricalPrice;
+
+/// Lookback the latest bar is compared against.
+const WINDOW_DAYS: i64 = 365;
+/// History must reach back this far, or a symbol with a few months of bars
+/// would report every move as a 52-week extreme.
+const MIN_COVERAGE_DAYS: i64 = 358;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Week52Kind {
+    High,
+    Low,
+}
+
+impl std::str::FromStr for Week52Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "high" | "highs" => Ok(Week52Kind::High),
+            "low" | "lows" => Ok(Week52Kind::Low),
+            other => Err(anyhow::anyhow!("unknown 52-week event kind '{}'", other)),
+        }
+    }
+}
+
+/// One symbol setting a new 52-week high or low on one market day.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Week52Event {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub symbol: String,
+    pub kind: Week52Kind,
+    /// Date of the daily bar that set the extreme (`YYYY-MM-DD`).
+    pub date: String,
+    /// The bar's high for a new high, its low for a new low.
+    pub price: f64,
+    pub close: f64,
+    /// The 52-week extreme that was broken.
+    pub previous_extreme: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// New-high and new-low counts for one market day.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Week52Breadth {
+    pub date: String,
+    pub new_highs: u32,
+    pub new_lows: u32,
+}
+
+/// Events the latest bar in `prices` (oldest first) sets.
+pub fn detect(symbol: &str, prices: &[HistoricalPrice], now: DateTime<Utc>) -> Vec<Week52Event> {
+    let Some((latest, earlier)) = prices.split_last() else {
+        return Vec::new();
+    };
+    let covered = earlier
+        .first()
+        .is_some_and(|first| first.date <= latest.date - Duration::days(MIN_COVERAGE_DAYS));
+    if !covered {
+        return Vec::new();
+    }
+
+    let window_start = latest.date - Duration::days(WINDOW_DAYS);
+    let window = earlier
+        .iter()
+        .filter(|p| p.date >= window_start && p.high.is_finite() && p.low.is_finite());
+    let (mut high, mut low) = (f64::NEG_INFINITY, f64::INFINITY);
+    for bar in window {
+        high = high.max(bar.high);
+        low = low.min(bar.low);
+    }
+
+    let date = latest.date.date_naive().format("%Y-%m-%d").to_string();
+    let event = |kind, price, previous_extreme| Week52Event {
+        id: None,
+        symbol: symbol.to_string(),
+        kind,
+        date: date.clone(),
+        price,
+        close: latest.close,
+        previous_extreme,
+        recorded_at: now,
+    };
+    let mut events = Vec::new();
+    if high.is_finite() && latest.high > high {
+        events.push(event(Week52Kind::High, latest.high, high));
+    }
+    if low.is_finite() && latest.low > 0.0 && latest.low < low {
+        events.push(event(Week52Kind::Low, latest.low, low));
+    }
+    events
+}
+
+/// Daily new-high/new-low counts, newest day first.
+pub fn breadth(events: &[Week52Event]) -> Vec<Week52Breadth> {
+    let mut days: BTreeMap<&str, Week52Breadth> = BTreeMap::new();
+    for event in events {
+        let day = days.entry(&event.date).or_insert_with(|| Week52Breadth {
+            date: event.date.clone(),
+            ..Default::default()
+        });
+        match event.kind {
+            Week52Kind::High => day.new_highs += 1,
+            Week52Kind::Low => day.new_lows += 1,
+        }
+    }
+    days.into_values().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn bars(days: i64, price: impl Fn(i64) -> f64) -> Vec<HistoricalPrice> {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 20, 0, 0).unwrap();
+        (0..days)
+            .map(|i| {
+                let close = price(i);
+                HistoricalPrice {
+                    date: start + Duration::days(i),
+                    open: close,
+                    high: close + 1.0,
+                    low: close - 1.0,
+                    close,
+                    volume: 1_000.0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_new_high_and_low() {
+        let now = Utc::now();
+        let mut prices = bars(380, |_| 100.0);
+        prices.last_mut().unwrap().high = 105.0;
+        let events = detect("AAPL", &prices, now);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, Week52Kind::High);
+        assert_eq!(events[0].price, 105.0);
+        assert_eq!(events[0].previous_extreme, 101.0);
+        assert_eq!(events[0].date, "2025-06-17");
+
+        let mut prices = bars(380, |_| 100.0);
+        prices.last_mut().unwrap().low = 90.0;
+        let events = detect("AAPL", &prices, now);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, Week52Kind::Low);
+        assert_eq!(events[0].previous_extreme, 99.0);
+
+        // Matching the prior extreme isn't a new one.
+        assert!(detect("AAPL", &bars(380, |_| 100.0), now).is_empty());
+    }
+
+    #[test]
+    fn test_ignores_bars_outside_the_window_and_short_history() {
+        let now = Utc::now();
+        // A spike 370 days before the latest bar has rolled out of the window.
+        let mut prices = bars(380, |i| if i == 9 { 200.0 } else { 100.0 });
+        prices.last_mut().unwrap().high = 105.0;
+        assert_eq!(detect("AAPL", &prices, now)[0].previous_extreme, 101.0);
+
+        // Six months of history can't establish a 52-week extreme.
+        let mut prices = bars(180, |_| 100.0);
+        prices.last_mut().unwrap().high = 105.0;
+        assert!(detect("AAPL", &prices, now).is_empty());
+        assert!(detect("AAPL", &[], now).is_empty());
+    }
+
+    #[test]
+    fn test_breadth_counts_per_day() {
+        let now = Utc::now();
+        let event = |symbol: &str, kind, date: &str| Week52Event {
+            id: None,
+            symbol: symbol.to_string(),
+            kind,
+            date: date.to_string(),
+            price: 1.0,
+            close: 1.0,
+            previous_extreme: 1.0,
+            recorded_at: now,
+        };
+        let events = [
+            event("AAPL", Week52Kind::High, "2025-06-16"),
+            event("MSFT", Week52Kind::High, "2025-06-17"),
+            event("NVDA", Week52Kind::High, "2025-06-17"),
+            event("INTC", Week52Kind::Low, "2025-06-17"),
+        ];
+        let days = breadth(&events);
+        assert_eq!(
+            days,
+            vec![
+                Week52Breadth {
+                    date: "2025-06-17".to_string(),
+                    new_highs: 2,
+                    new_lows: 1,
+                },
+                Week52Breadth {
+                    date: "2025-06-16".to_string(),
+                    new_highs: 1,
+                    new_lows: 0,
+                },
+            ]
+        );
+    }
+}
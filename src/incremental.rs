@@ -0,0 +1,253 @@
+//! Incremental fetches with carried-forward RSI/MACD state.
+//!
+//! A full refresh reads `analysis::HISTORY_DAYS` of bars from Yahoo for every
+//! symbol, every cycle. With `INCREMENTAL_FETCH` on, the cycle keeps each
+//! symbol's bar window and its RSI/MACD state in `indicator_state`, and next
+//! time asks Yahoo only for the days since the last stored bar plus
+//! [`OVERLAP_DAYS`]. [`IndicatorState::advance`] splices that delta onto the
+//! window and folds the new closes into the state; the other indicators
+//! still read the merged window.
+//!
+//! The latest bar may still be forming, so the state only ever holds the
+//! bars before it and [`IndicatorState::latest`] applies it to a copy.
+//!
+//! A symbol goes back to a full fetch when it has no state, the
+//! `INDICATOR_*` periods changed, its last bar is more than
+//! [`MAX_DELTA_DAYS`] old, the delta doesn't reach the stored window, or an
+//! overlapping completed close moved by more than [`REVISION_TOLERANCE`]
+//! (a split or adjustment rewrote the history).
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::indicators::{IndicatorConfig, MacdState, RsiState};
+use crate::models::{HistoricalPrice, MACDIndicator};
+
+/// Bars kept per symbol, about a year of sessions.
+const WINDOW_SESSIONS: usize = 260;
+/// Calendar days re-fetched before the last stored bar.
+pub const OVERLAP_DAYS: i64 = 5;
+/// Older windows are refreshed in full rather than patched.
+pub const MAX_DELTA_DAYS: i64 = 30;
+/// Relative change of an overlapping completed close treated as a revision.
+pub const REVISION_TOLERANCE: f64 = 0.005;
+
+/// One symbol's bars and RSI/MACD state, persisted in `indicator_state`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorState {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub symbol: String,
+    /// Periods the state was seeded with.
+    pub periods: IndicatorConfig,
+    /// Recent daily bars, oldest first.
+    pub bars: Vec<HistoricalPrice>,
+    /// RSI through the second-to-last bar of `bars`.
+    pub rsi: Option<RsiState>,
+    /// MACD through the second-to-last bar of `bars`.
+    pub macd: Option<MacdState>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn day(bar: &HistoricalPrice) -> NaiveDate {
+    bar.date.date_naive()
+}
+
+impl IndicatorState {
+    /// State for a full history fetch, oldest bar first.
+    pub fn seed(symbol: &str, periods: IndicatorConfig, prices: &[HistoricalPrice]) -> Self {
+        let mut state = Self {
+            id: None,
+            symbol: symbol.to_string(),
+            periods,
+            bars: Vec::new(),
+            rsi: None,
+            macd: None,
+            updated_at: Utc::now(),
+        };
+        state.reseed(prices);
+        state
+    }
+
+    /// Rebuild the state from `prices`, read in full so it matches a batch
+    /// computation over the same bars.
+    fn reseed(&mut self, prices: &[HistoricalPrice]) {
+        let completed = &prices[..prices.len().saturating_sub(1)];
+        self.rsi = RsiState::seed(completed, self.periods.rsi_period as usize);
+        self.macd = MacdState::seed(
+            completed,
+            self.periods.macd_fast as usize,
+            self.periods.macd_slow as usize,
+            self.periods.macd_signal as usize,
+        );
+        self.bars = prices[prices.len().saturating_sub(WINDOW_SESSIONS)..].to_vec();
+        self.updated_at = Utc::now();
+    }
+
+    /// Days to fetch to bring this state up to date with `periods`, or
+    /// `None` when it needs a full refresh.
+    pub fn delta_days(&self, periods: &IndicatorConfig, now: DateTime<Utc>) -> Option<i64> {
+        if self.periods != *periods || self.bars.len() < 2 {
+            return None;
+        }
+        let age = (now - self.bars.last()?.date).num_days();
+        (age <= MAX_DELTA_DAYS).then_some(age.max(0) + OVERLAP_DAYS)
+    }
+
+    /// Splice a delta fetch onto the stored bars and fold the newly
+    /// completed closes into the state. Errors, leaving the state as it
+    /// was, when the delta calls for a full refresh instead.
+    pub fn advance(&mut self, fresh: &[HistoricalPrice]) -> Result<()> {
+        let (Some(first), Some(last_stored)) = (fresh.first(), self.bars.last()) else {
+            bail!("{}: nothing to splice", self.symbol);
+        };
+        if day(first) > day(last_stored) {
+            bail!(
+                "{}: delta starts {} after the last stored bar {}",
+                self.symbol,
+                day(first),
+                day(last_stored)
+            );
+        }
+        // Bars up to here are already folded into the state.
+        let folded_through = day(&self.bars[self.bars.len() - 2]);
+        for bar in fresh.iter().filter(|bar| day(bar) <= folded_through) {
+            let Some(stored) = self.bars.iter().find(|stored| day(stored) == day(bar)) else {
+                continue;
+            };
+            if stored.close != 0.0
+                && ((bar.close - stored.close) / stored.close).abs() > REVISION_TOLERANCE
+            {
+                bail!(
+                    "{}: close on {} revised from {} to {}",
+                    self.symbol,
+                    day(bar),
+                    stored.close,
+                    bar.close
+                );
+            }
+        }
+
+        let mut merged: Vec<HistoricalPrice> = self
+            .bars
+            .iter()
+            .filter(|stored| day(stored) < day(first))
+            .cloned()
+            .collect();
+        merged.extend_from_slice(fresh);
+
+        if self.rsi.is_none() || self.macd.is_none() {
+            // Too few bars to seed last time; the window is all there is.
+            self.reseed(&merged);
+            return Ok(());
+        }
+        let completed = &merged[..merged.len() - 1];
+        for bar in completed.iter().filter(|bar| day(bar) > folded_through) {
+            if let Some(rsi) = &mut self.rsi {
+                rsi.update(bar.close);
+            }
+            if let Some(macd) = &mut self.macd {
+                macd.update(bar.close);
+            }
+        }
+        self.bars = merged[merged.len().saturating_sub(WINDOW_SESSIONS)..].to_vec();
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// RSI and MACD at the latest bar, which may still be forming.
+    pub fn latest(&self) -> (Option<f64>, Option<MACDIndicator>) {
+        let Some(close) = self.bars.last().map(|bar| bar.close) else {
+            return (None, None);
+        };
+        let rsi = match self.rsi.clone() {
+            Some(mut rsi) => Some(rsi.update(close)),
+            None => self.periods.rsi(&self.bars),
+        };
+        let macd = match self.macd.clone() {
+            Some(mut macd) => Some(macd.update(close)),
+            None => self.periods.macd(&self.bars),
+        };
+        (rsi, macd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn bars(start: DateTime<Utc>, closes: &[f64]) -> Vec<HistoricalPrice> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| HistoricalPrice {
+                date: start + Duration::days(i as i64),
+                open: close,
+                high: close * 1.01,
+                low: close * 0.99,
+                close,
+                volume: 1_000.0,
+            })
+            .collect()
+    }
+
+    fn closes(n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| 100.0 + (i as f64 * 0.4).sin() * 4.0 + i as f64 * 0.05)
+            .collect()
+    }
+
+    #[test]
+    fn test_advance_matches_a_full_recompute() {
+        let start = Utc::now() - Duration::days(100);
+        let all = bars(start, &closes(100));
+        let periods = IndicatorConfig::default();
+
+        // The last stored bar was still forming when it was fetched.
+        let mut seen = all[..90].to_vec();
+        seen[89].close *= 1.02;
+        let mut state = IndicatorState::seed("AAPL", periods, &seen);
+        assert_eq!(
+            state.delta_days(&periods, all[89].date + Duration::days(3)),
+            Some(3 + OVERLAP_DAYS)
+        );
+        state.advance(&all[86..]).unwrap();
+
+        assert_eq!(state.bars.len(), 100);
+        let (rsi, macd) = state.latest();
+        let expected_rsi = periods.rsi(&all).unwrap();
+        let expected_macd = periods.macd(&all).unwrap();
+        assert!((rsi.unwrap() - expected_rsi).abs() < 1e-9);
+        assert!((macd.unwrap().histogram - expected_macd.histogram).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_advance_rejects_gaps_and_revisions() {
+        let start = Utc::now() - Duration::days(60);
+        let all = bars(start, &closes(60));
+        let periods = IndicatorConfig::default();
+        let mut state = IndicatorState::seed("AAPL", periods, &all[..50]);
+
+        // Starts after the stored window ends.
+        assert!(state.advance(&all[55..]).is_err());
+
+        // A completed close halved, e.g. by a split adjustment.
+        let mut split = all[45..].to_vec();
+        split[0].close /= 2.0;
+        assert!(state.advance(&split).is_err());
+        assert_eq!(state.bars.len(), 50);
+
+        let changed = IndicatorConfig {
+            rsi_period: 7,
+            ..periods
+        };
+        assert_eq!(state.delta_days(&changed, Utc::now()), None);
+        assert_eq!(
+            state.delta_days(&periods, all[49].date + Duration::days(MAX_DELTA_DAYS + 1)),
+            None
+        );
+    }
+}
@@ -1,10 +1,45 @@
 //! Index Constituents and Heatmap Data Provider
 //!
 //! Provides embedded lists of index constituents (S&P 500, NASDAQ 100, Dow 30, Russell 2000)
-//! and calculates performance data for heatmap visualization.
+//! and calculates performance data for heatmap visualization and daily
+//! equal- vs cap-weighted index returns. The S&P 500 and NASDAQ 100 lists
+//! are replaced at runtime by `constituents.rs` when it can fetch current
+//! membership.
 
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 
+use crate::models::StockAnalysis;
+
+/// Full Russell 2000 constituent list, loaded at startup from
+/// `RUSSELL2000_FILE`. When unset the embedded top 200 is used.
+static RUSSELL2000_FULL: OnceCell<Vec<&'static str>> = OnceCell::new();
+
+/// Index id → constituents fetched at runtime, overriding the embedded list.
+static LIVE: Lazy<RwLock<HashMap<String, Vec<String>>>> = Lazy::new(Default::default);
+
+/// Normalized symbol → ids of the indexes it belongs to, in `get_indexes`
+/// order. Rebuilt whenever a live list is set.
+static MEMBERSHIP: Lazy<RwLock<HashMap<String, Vec<String>>>> =
+    Lazy::new(|| RwLock::new(build_membership()));
+
+fn build_membership() -> HashMap<String, Vec<String>> {
+    let mut membership: HashMap<String, Vec<String>> = HashMap::new();
+    for index in IndexDataProvider::get_indexes() {
+        for symbol in IndexDataProvider::get_index_symbols(&index.id).unwrap_or_default() {
+            membership
+                .entry(crate::symbols::normalize_symbol_key(&symbol))
+                .or_default()
+                .push(index.id.clone());
+        }
+    }
+    membership
+}
+
 /// Information about an available index
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexInfo {
@@ -37,6 +72,224 @@ pub struct StockHeatmapItem {
     pub sector: Option<String>,
 }
 
+/// One index's return for a trading day, computed from constituent
+/// analyses. Persisted in `index_performance`, one row per index and date;
+/// later cycles on the same day overwrite it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexPerformance {
+    pub index_id: String,
+    pub date: NaiveDate,
+    /// Mean daily change of the constituents.
+    pub equal_weight_return_pct: f64,
+    /// Market-cap-weighted daily change.
+    pub cap_weight_return_pct: f64,
+    /// Equal minus cap weight. Positive when the average stock beats the
+    /// giants (broad participation); negative when a few mega caps carry
+    /// the index.
+    pub divergence_pct: f64,
+    /// Constituents with a daily change.
+    pub constituents: usize,
+    pub advancers: usize,
+    pub decliners: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IndexPerformance {
+    /// `None` when no constituent has both a daily change and a market cap.
+    pub fn from_constituents(
+        index_id: &str,
+        date: NaiveDate,
+        constituents: &[StockAnalysis],
+    ) -> Option<Self> {
+        let changes: Vec<(f64, Option<f64>)> = constituents
+            .iter()
+            .filter_map(|s| Some((s.price_change_percent?, s.market_cap.map(|c| c as f64))))
+            .filter(|(pct, _)| pct.is_finite())
+            .collect();
+        let (cap_sum, weighted) = changes
+            .iter()
+            .filter_map(|(pct, cap)| cap.filter(|c| *c > 0.0).map(|c| (c, c * pct)))
+            .fold((0.0, 0.0), |(caps, w), (c, cw)| (caps + c, w + cw));
+        if changes.is_empty() || cap_sum <= 0.0 {
+            return None;
+        }
+
+        let equal = changes.iter().map(|(pct, _)| pct).sum::<f64>() / changes.len() as f64;
+        let cap = weighted / cap_sum;
+        Some(Self {
+            index_id: index_id.to_string(),
+            date,
+            equal_weight_return_pct: equal,
+            cap_weight_return_pct: cap,
+            divergence_pct: equal - cap,
+            constituents: changes.len(),
+            advancers: changes.iter().filter(|(pct, _)| *pct > 0.0).count(),
+            decliners: changes.iter().filter(|(pct, _)| *pct < 0.0).count(),
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+/// How many contributors are kept per side in `index_contributors`.
+pub const STORED_CONTRIBUTORS: usize = 25;
+
+/// One constituent's share of an index's cap-weighted daily move.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Contribution {
+    pub symbol: String,
+    pub change_percent: f64,
+    /// Share of the index's total market cap, in %.
+    pub weight_pct: f64,
+    /// Percentage points of the index return (weight × change).
+    pub contribution_pct: f64,
+    /// Index points, when the index's previous close is known.
+    pub contribution_points: Option<f64>,
+}
+
+/// The largest positive and negative contributors to an index's move on a
+/// trading day. Persisted in `index_contributors`, one row per index and
+/// date; later cycles on the same day overwrite it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexContributors {
+    pub index_id: String,
+    pub date: NaiveDate,
+    /// Cap-weighted daily change; the sum of every contribution.
+    pub index_return_pct: f64,
+    /// Index level the point contributions are measured against.
+    pub previous_close: Option<f64>,
+    /// Index points moved (sum of every point contribution).
+    pub index_points: Option<f64>,
+    /// Positive contributions, largest first.
+    pub top: Vec<Contribution>,
+    /// Negative contributions, largest drag first.
+    pub bottom: Vec<Contribution>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IndexContributors {
+    /// Keeps up to `limit` contributors per side. `None` when no constituent
+    /// has both a daily change and a market cap.
+    pub fn from_constituents(
+        index_id: &str,
+        date: NaiveDate,
+        constituents: &[StockAnalysis],
+        previous_close: Option<f64>,
+        limit: usize,
+    ) -> Option<Self> {
+        let weighted: Vec<(&str, f64, f64)> = constituents
+            .iter()
+            .filter_map(|s| {
+                let pct = s.price_change_percent.filter(|p| p.is_finite())?;
+                let cap = s.market_cap.filter(|c| *c > 0)?;
+                Some((s.symbol.as_str(), pct, cap as f64))
+            })
+            .collect();
+        let cap_sum: f64 = weighted.iter().map(|(_, _, cap)| cap).sum();
+        if weighted.is_empty() || cap_sum <= 0.0 {
+            return None;
+        }
+        let previous_close = previous_close.filter(|c| *c > 0.0);
+
+        let mut contributions: Vec<Contribution> = weighted
+            .into_iter()
+            .map(|(symbol, pct, cap)| {
+                let weight = cap / cap_sum;
+                let contribution_pct = weight * pct;
+                Contribution {
+                    symbol: symbol.to_string(),
+                    change_percent: pct,
+                    weight_pct: weight * 100.0,
+                    contribution_pct,
+                    contribution_points: previous_close.map(|c| c * contribution_pct / 100.0),
+                }
+            })
+            .collect();
+        contributions.sort_by(|a, b| b.contribution_pct.total_cmp(&a.contribution_pct));
+
+        let index_return_pct: f64 = contributions.iter().map(|c| c.contribution_pct).sum();
+        let top = contributions
+            .iter()
+            .filter(|c| c.contribution_pct > 0.0)
+            .take(limit)
+            .cloned()
+            .collect();
+        let bottom = contributions
+            .iter()
+            .rev()
+            .filter(|c| c.contribution_pct < 0.0)
+            .take(limit)
+            .cloned()
+            .collect();
+        Some(Self {
+            index_id: index_id.to_string(),
+            date,
+            index_return_pct,
+            previous_close,
+            index_points: previous_close.map(|c| c * index_return_pct / 100.0),
+            top,
+            bottom,
+            updated_at: Utc::now(),
+        })
+    }
+
+    /// The same row with at most `limit` contributors per side.
+    pub fn truncated(mut self, limit: usize) -> Self {
+        self.top.truncate(limit);
+        self.bottom.truncate(limit);
+        self
+    }
+}
+
+/// Compounded `(equal, cap)` return in % over consecutive daily rows.
+pub fn cumulative_returns(rows: &[IndexPerformance]) -> (f64, f64) {
+    let compound = |daily: &dyn Fn(&IndexPerformance) -> f64| {
+        (rows
+            .iter()
+            .fold(1.0, |acc, r| acc * (1.0 + daily(r) / 100.0))
+            - 1.0)
+            * 100.0
+    };
+    (
+        compound(&|r| r.equal_weight_return_pct),
+        compound(&|r| r.cap_weight_return_pct),
+    )
+}
+
+/// New York session date for `now`; `None` on weekends, when daily changes
+/// still describe Friday. Holidays are not modelled.
+pub fn trading_date(now: DateTime<Utc>) -> Option<NaiveDate> {
+    let ny = now.with_timezone(&chrono_tz::America::New_York);
+    (!matches!(ny.weekday(), Weekday::Sat | Weekday::Sun)).then(|| ny.date_naive())
+}
+
+/// Tickers from a constituent file: one per line, or a CSV (such as an
+/// iShares IWM holdings export) whose first column is the ticker. Header,
+/// preamble and cash rows are skipped; tickers are normalized and
+/// de-duplicated in file order.
+pub fn parse_constituent_list(text: &str) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    text.lines()
+        .filter_map(|line| {
+            let field = line.split(',').next()?.trim().trim_matches('"').trim();
+            let valid = field.len() <= 6
+                && field.starts_with(|c: char| c.is_ascii_alphabetic())
+                && field
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+            if !valid
+                || matches!(
+                    field.to_ascii_uppercase().as_str(),
+                    "TICKER" | "SYMBOL" | "USD"
+                )
+            {
+                return None;
+            }
+            Some(crate::symbols::normalize_symbol_key(field))
+        })
+        .filter(|symbol| seen.insert(symbol.clone()))
+        .collect()
+}
+
 /// Provider for index constituent data
 pub struct IndexDataProvider;
 
@@ -48,40 +301,124 @@ impl IndexDataProvider {
                 id: "sp500".to_string(),
                 name: "S&P 500".to_string(),
                 description: "500 largest US companies by market cap".to_string(),
-                symbol_count: SP500_SYMBOLS.len(),
+                symbol_count: Self::symbol_count("sp500"),
             },
             IndexInfo {
                 id: "nasdaq100".to_string(),
                 name: "NASDAQ 100".to_string(),
                 description: "100 largest non-financial NASDAQ companies".to_string(),
-                symbol_count: NASDAQ100_SYMBOLS.len(),
+                symbol_count: Self::symbol_count("nasdaq100"),
             },
             IndexInfo {
                 id: "dow30".to_string(),
                 name: "Dow Jones 30".to_string(),
                 description: "30 large-cap blue-chip companies".to_string(),
-                symbol_count: DOW30_SYMBOLS.len(),
+                symbol_count: Self::symbol_count("dow30"),
             },
             IndexInfo {
                 id: "russell2000".to_string(),
                 name: "Russell 2000".to_string(),
-                description: "2000 small-cap US companies (top 200 shown)".to_string(),
-                symbol_count: RUSSELL2000_TOP_SYMBOLS.len(),
+                description: if RUSSELL2000_FULL.get().is_some() {
+                    "2000 small-cap US companies".to_string()
+                } else {
+                    "2000 small-cap US companies (top 200 shown)".to_string()
+                },
+                symbol_count: Self::symbol_count("russell2000"),
             },
         ]
     }
 
-    /// Get symbols for a specific index
-    pub fn get_index_symbols(index_id: &str) -> Option<Vec<&'static str>> {
+    /// Get symbols for a specific index: the live list when one has been
+    /// set, otherwise the embedded one.
+    pub fn get_index_symbols(index_id: &str) -> Option<Vec<String>> {
+        if let Some(symbols) = LIVE.read().ok()?.get(index_id) {
+            return Some(symbols.clone());
+        }
+        Self::embedded_symbols(index_id)
+            .map(|symbols| symbols.iter().map(|s| s.to_string()).collect())
+    }
+
+    fn embedded_symbols(index_id: &str) -> Option<&'static [&'static str]> {
         match index_id {
-            "sp500" => Some(SP500_SYMBOLS.to_vec()),
-            "nasdaq100" => Some(NASDAQ100_SYMBOLS.to_vec()),
-            "dow30" => Some(DOW30_SYMBOLS.to_vec()),
-            "russell2000" => Some(RUSSELL2000_TOP_SYMBOLS.to_vec()),
+            "sp500" => Some(SP500_SYMBOLS),
+            "nasdaq100" => Some(NASDAQ100_SYMBOLS),
+            "dow30" => Some(DOW30_SYMBOLS),
+            "russell2000" => Some(Self::russell2000_symbols()),
             _ => None,
         }
     }
 
+    fn symbol_count(index_id: &str) -> usize {
+        let live = LIVE
+            .read()
+            .ok()
+            .and_then(|live| live.get(index_id).map(Vec::len));
+        live.or_else(|| Self::embedded_symbols(index_id).map(<[_]>::len))
+            .unwrap_or(0)
+    }
+
+    /// Replace an index's constituents with a fetched list and rebuild the
+    /// membership lookup. Returns `false` for an unknown index or an empty
+    /// list, leaving the current one in place.
+    pub fn set_constituents(index_id: &str, symbols: Vec<String>) -> bool {
+        if symbols.is_empty() || Self::embedded_symbols(index_id).is_none() {
+            return false;
+        }
+        let Ok(mut live) = LIVE.write() else {
+            return false;
+        };
+        live.insert(index_id.to_string(), symbols);
+        drop(live);
+        let membership = build_membership();
+        if let Ok(mut current) = MEMBERSHIP.write() {
+            *current = membership;
+        }
+        true
+    }
+
+    fn russell2000_symbols() -> &'static [&'static str] {
+        RUSSELL2000_FULL
+            .get()
+            .map(Vec::as_slice)
+            .unwrap_or(RUSSELL2000_TOP_SYMBOLS)
+    }
+
+    /// Replace the embedded Russell 2000 subset with the full constituent
+    /// list. Must run before the first membership lookup; returns `false`
+    /// when a list was already loaded.
+    pub fn load_russell2000(symbols: Vec<String>) -> bool {
+        let symbols = symbols
+            .into_iter()
+            .map(|s| &*Box::leak(s.into_boxed_str()))
+            .collect();
+        RUSSELL2000_FULL.set(symbols).is_ok()
+    }
+
+    /// Yahoo ticker of the index itself, for its level.
+    pub fn level_ticker(index_id: &str) -> Option<&'static str> {
+        match index_id {
+            "sp500" => Some("^GSPC"),
+            "nasdaq100" => Some("^NDX"),
+            "dow30" => Some("^DJI"),
+            "russell2000" => Some("^RUT"),
+            _ => None,
+        }
+    }
+
+    /// Ids of the indexes `symbol` is a constituent of, e.g.
+    /// `["sp500", "nasdaq100"]` for AAPL.
+    pub fn indexes_for(symbol: &str) -> Vec<String> {
+        MEMBERSHIP
+            .read()
+            .ok()
+            .and_then(|membership| {
+                membership
+                    .get(&crate::symbols::normalize_symbol_key(symbol))
+                    .cloned()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get index info by ID
     pub fn get_index_info(index_id: &str) -> Option<IndexInfo> {
         Self::get_indexes().into_iter().find(|i| i.id == index_id)
@@ -160,7 +497,8 @@ pub static DOW30_SYMBOLS: &[&str] = &[
     "TRV", "UNH", "V", "WMT",
 ];
 
-/// Russell 2000 top symbols (top 200 by market cap for feasibility)
+/// Russell 2000 top symbols (top 200 by market cap), used unless the full
+/// list is loaded from `RUSSELL2000_FILE`
 /// Source: https://en.wikipedia.org/wiki/Russell_2000_Index
 pub static RUSSELL2000_TOP_SYMBOLS: &[&str] = &[
     "ACIW", "AGCO", "AIT", "ALKS", "AMED", "AMKR", "AMSF", "APPF", "ASGN", "AZEK", "BCO", "BDC",
@@ -200,8 +538,8 @@ mod tests {
     fn test_get_index_symbols() {
         let sp500 = IndexDataProvider::get_index_symbols("sp500").unwrap();
         assert!(sp500.len() > 400);
-        assert!(sp500.contains(&"AAPL"));
-        assert!(sp500.contains(&"MSFT"));
+        assert!(sp500.iter().any(|s| s == "AAPL"));
+        assert!(sp500.iter().any(|s| s == "MSFT"));
 
         let nasdaq = IndexDataProvider::get_index_symbols("nasdaq100").unwrap();
         assert!(nasdaq.len() >= 100);
@@ -212,6 +550,129 @@ mod tests {
         assert!(IndexDataProvider::get_index_symbols("invalid").is_none());
     }
 
+    #[test]
+    fn test_indexes_for() {
+        assert_eq!(
+            IndexDataProvider::indexes_for("aapl"),
+            vec!["sp500", "nasdaq100", "dow30"]
+        );
+        assert_eq!(IndexDataProvider::indexes_for("BRK-B"), vec!["sp500"]);
+        assert!(IndexDataProvider::indexes_for("ZZZZ").is_empty());
+    }
+
+    #[test]
+    fn test_set_constituents_overrides_the_embedded_list() {
+        let mut symbols = IndexDataProvider::get_index_symbols("russell2000").unwrap();
+        symbols.push("ZZZT".to_string());
+        let count = symbols.len();
+        assert!(IndexDataProvider::set_constituents("russell2000", symbols));
+        assert_eq!(IndexDataProvider::indexes_for("zzzt"), vec!["russell2000"]);
+        assert_eq!(
+            IndexDataProvider::get_index_info("russell2000")
+                .unwrap()
+                .symbol_count,
+            count
+        );
+
+        assert!(!IndexDataProvider::set_constituents(
+            "ftse100",
+            vec!["ZZZT".to_string()]
+        ));
+        assert!(!IndexDataProvider::set_constituents("sp500", Vec::new()));
+        assert!(IndexDataProvider::get_index_symbols("sp500").unwrap().len() > 400);
+    }
+
+    fn constituent(symbol: &str, pct: Option<f64>, cap: Option<f64>) -> StockAnalysis {
+        StockAnalysis {
+            price_change_percent: pct,
+            market_cap: cap.map(|c| c as u64),
+            analyzed_at: "2025-06-02T20:00:00Z".parse().unwrap(),
+            ..StockAnalysis::sample(symbol, 100.0)
+        }
+    }
+
+    #[test]
+    fn test_equal_vs_cap_weight() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let rows = vec![
+            constituent("MEGA", Some(3.0), Some(3e12)),
+            constituent("SMALL1", Some(-1.0), Some(1e10)),
+            constituent("SMALL2", Some(-2.0), Some(1e10)),
+            constituent("NODATA", None, Some(1e10)),
+        ];
+        let perf = IndexPerformance::from_constituents("sp500", date, &rows).unwrap();
+        assert_eq!(perf.constituents, 3);
+        assert_eq!((perf.advancers, perf.decliners), (1, 2));
+        assert!(perf.equal_weight_return_pct.abs() < 1e-9);
+        assert!(perf.cap_weight_return_pct > 2.9);
+        assert!(perf.divergence_pct < -2.9);
+
+        assert!(IndexPerformance::from_constituents("sp500", date, &[]).is_none());
+
+        let (equal, cap) = cumulative_returns(&[perf.clone(), perf]);
+        assert!(equal.abs() < 1e-9);
+        assert!(cap > 5.8);
+    }
+
+    #[test]
+    fn test_contributors_split_index_move() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let rows = vec![
+            constituent("MEGA", Some(2.0), Some(3e12)),
+            constituent("MID", Some(-4.0), Some(1e12)),
+            constituent("FLAT", Some(0.0), Some(1e12)),
+            constituent("NOCAP", Some(9.0), None),
+        ];
+        let c =
+            IndexContributors::from_constituents("sp500", date, &rows, Some(5000.0), 10).unwrap();
+        // 0.6 × 2% − 0.2 × 4% = 0.4%
+        assert!((c.index_return_pct - 0.4).abs() < 1e-9);
+        assert!((c.index_points.unwrap() - 20.0).abs() < 1e-9);
+
+        assert_eq!(c.top.len(), 1);
+        assert_eq!(c.top[0].symbol, "MEGA");
+        assert!((c.top[0].weight_pct - 60.0).abs() < 1e-9);
+        assert!((c.top[0].contribution_pct - 1.2).abs() < 1e-9);
+        assert!((c.top[0].contribution_points.unwrap() - 60.0).abs() < 1e-9);
+        assert_eq!(c.bottom.len(), 1);
+        assert_eq!(c.bottom[0].symbol, "MID");
+        assert!((c.bottom[0].contribution_pct + 0.8).abs() < 1e-9);
+
+        let no_level =
+            IndexContributors::from_constituents("sp500", date, &rows, None, 10).unwrap();
+        assert!(no_level.top[0].contribution_points.is_none());
+        assert!(
+            IndexContributors::from_constituents("sp500", date, &rows[3..], None, 10).is_none()
+        );
+    }
+
+    #[test]
+    fn test_parse_constituent_list() {
+        let csv = "iShares Russell 2000 ETF\n\
+                   Fund Holdings as of,\"Jun 02, 2025\"\n\
+                   \n\
+                   Ticker,Name,Sector\n\
+                   \"FTAI\",\"FTAI AVIATION LTD\",\"Industrials\"\n\
+                   \"brk.b\",\"BERKSHIRE\",\"Financials\"\n\
+                   \"USD\",\"USD CASH\",\"Cash\"\n\
+                   \"-\",\"FUTURES\",\"Cash\"\n\
+                   FTAI,duplicate,Industrials\n";
+        assert_eq!(parse_constituent_list(csv), vec!["FTAI", "BRK-B"]);
+        assert_eq!(parse_constituent_list("SFM\nCRDO\n"), vec!["SFM", "CRDO"]);
+    }
+
+    #[test]
+    fn test_trading_date_skips_weekends() {
+        let saturday = "2025-06-07T15:00:00Z".parse().unwrap();
+        assert!(trading_date(saturday).is_none());
+        // 01:00 UTC Tuesday is still Monday in New York.
+        let late_monday = "2025-06-03T01:00:00Z".parse().unwrap();
+        assert_eq!(
+            trading_date(late_monday),
+            NaiveDate::from_ymd_opt(2025, 6, 2)
+        );
+    }
+
     #[test]
     fn test_get_index_info() {
         let sp500 = IndexDataProvider::get_index_info("sp500").unwrap();
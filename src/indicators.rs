@@ -1,271 +1,107 @@
-use crate::models::{BollingerBands, HistoricalPrice, MACDIndicator, StochasticOscillator};
-
-pub struct TechnicalIndicators;
+//! Indicator math lives in the `auto-analyser-indicators` crate
+//! (`indicators/`); this module re-exports it, makes the server's price
+//! bars usable as its input and holds the configurable periods.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::models::HistoricalPrice;
+
+pub use auto_analyser_indicators::{Bar, MacdState, RsiState, TechnicalIndicators};
+
+/// Periods of the headline indicators, set with the `INDICATOR_*`
+/// variables. `sma_20` / `sma_50` keep their names whatever the periods,
+/// so every analysis stores the periods it used in `indicator_periods`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndicatorConfig {
+    pub rsi_period: u32,
+    /// Period of the `sma_20` field.
+    pub sma_short: u32,
+    /// Period of the `sma_50` field.
+    pub sma_long: u32,
+    pub macd_fast: u32,
+    pub macd_slow: u32,
+    pub macd_signal: u32,
+}
 
-impl TechnicalIndicators {
-    /// Calculate RSI (Relative Strength Index) using Wilder's Smoothing
-    /// This matches TradingView's RSI calculation
-    pub fn calculate_rsi(prices: &[HistoricalPrice], period: usize) -> Option<f64> {
-        if prices.len() < period + 1 {
-            return None;
+impl Default for IndicatorConfig {
+    fn default() -> Self {
+        Self {
+            rsi_period: 14,
+            sma_short: 20,
+            sma_long: 50,
+            macd_fast: 12,
+            macd_slow: 26,
+            macd_signal: 9,
         }
+    }
+}
 
-        // Calculate price changes
-        let mut changes = Vec::new();
-        for i in 1..prices.len() {
-            changes.push(prices[i].close - prices[i - 1].close);
+impl IndicatorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if [
+            self.rsi_period,
+            self.sma_short,
+            self.sma_long,
+            self.macd_fast,
+            self.macd_slow,
+            self.macd_signal,
+        ]
+        .contains(&0)
+        {
+            bail!("INDICATOR_* periods must be greater than 0");
         }
-
-        if changes.len() < period {
-            return None;
+        if self.sma_short >= self.sma_long {
+            bail!("INDICATOR_SMA_SHORT must be less than INDICATOR_SMA_LONG");
         }
-
-        // Calculate initial average gain and loss using SMA for first period
-        let mut gains = Vec::new();
-        let mut losses = Vec::new();
-
-        for &change in &changes[..period] {
-            if change > 0.0 {
-                gains.push(change);
-                losses.push(0.0);
-            } else {
-                gains.push(0.0);
-                losses.push(change.abs());
-            }
+        if self.macd_fast >= self.macd_slow {
+            bail!("INDICATOR_MACD_FAST must be less than INDICATOR_MACD_SLOW");
         }
-
-        let mut avg_gain: f64 = gains.iter().sum::<f64>() / period as f64;
-        let mut avg_loss: f64 = losses.iter().sum::<f64>() / period as f64;
-
-        // Apply Wilder's Smoothing for remaining periods
-        for &change in &changes[period..] {
-            let gain = if change > 0.0 { change } else { 0.0 };
-            let loss = if change < 0.0 { change.abs() } else { 0.0 };
-
-            // Wilder's smoothing: (previous_avg * (period - 1) + current_value) / period
-            avg_gain = (avg_gain * (period - 1) as f64 + gain) / period as f64;
-            avg_loss = (avg_loss * (period - 1) as f64 + loss) / period as f64;
+        // Fetched windows hold about 260 sessions (`analysis::HISTORY_DAYS`).
+        if self.sma_long > 250 || self.macd_slow + self.macd_signal > 250 || self.rsi_period > 250 {
+            bail!("INDICATOR_* periods must fit in 250 daily bars");
         }
-
-        // Calculate RSI
-        if avg_loss == 0.0 {
-            if avg_gain == 0.0 {
-                return Some(50.0); // No movement
-            }
-            return Some(100.0); // All gains, no losses
-        }
-
-        if avg_gain == 0.0 {
-            return Some(0.0); // All losses, no gains
-        }
-
-        let rs = avg_gain / avg_loss;
-        let rsi = 100.0 - (100.0 / (1.0 + rs));
-
-        Some(rsi)
+        Ok(())
     }
 
-    /// Calculate Simple Moving Average
-    pub fn calculate_sma(prices: &[HistoricalPrice], period: usize) -> Option<f64> {
-        if prices.len() < period {
-            return None;
-        }
-
-        let sum: f64 = prices.iter().rev().take(period).map(|p| p.close).sum();
-        Some(sum / period as f64)
+    pub fn rsi<B: Bar>(&self, prices: &[B]) -> Option<f64> {
+        TechnicalIndicators::calculate_rsi(prices, self.rsi_period as usize)
     }
 
-    /// Calculate MACD (Moving Average Convergence Divergence) with a real
-    /// signal line computed as EMA(9) of the MACD series.
-    ///
-    /// Requires at least 34 bars (`26 + 9 - 1`) so the signal EMA has enough
-    /// MACD samples to seed itself.
-    pub fn calculate_macd(prices: &[HistoricalPrice]) -> Option<MACDIndicator> {
-        const FAST: usize = 12;
-        const SLOW: usize = 26;
-        const SIGNAL: usize = 9;
-
-        if prices.len() < SLOW + SIGNAL - 1 {
-            return None;
-        }
-
-        let closes: Vec<f64> = prices.iter().map(|p| p.close).collect();
-        let ema_fast = ema_series(&closes, FAST);
-        let ema_slow = ema_series(&closes, SLOW);
-
-        // `ema_fast` starts at index FAST-1 in `closes`; `ema_slow` at SLOW-1.
-        // Align `ema_fast` forward by `SLOW - FAST` so the two series start on
-        // the same bar.
-        let offset = SLOW - FAST;
-        let macd_series: Vec<f64> = ema_slow
-            .iter()
-            .enumerate()
-            .map(|(i, &slow)| ema_fast[i + offset] - slow)
-            .collect();
-
-        if macd_series.len() < SIGNAL {
-            return None;
-        }
-
-        let signal_series = ema_series(&macd_series, SIGNAL);
-        let macd_line = *macd_series.last()?;
-        let signal_line = *signal_series.last()?;
-        let histogram = macd_line - signal_line;
-
-        Some(MACDIndicator {
-            macd_line,
-            signal_line,
-            histogram,
-        })
+    pub fn sma_short<B: Bar>(&self, prices: &[B]) -> Option<f64> {
+        TechnicalIndicators::calculate_sma(prices, self.sma_short as usize)
     }
 
-    /// Calculate Exponential Moving Average — chronological, seeded with the
-    /// SMA of the first `period` samples. Returns `None` if `prices.len() < period`.
-    fn calculate_ema(prices: &[HistoricalPrice], period: usize) -> Option<f64> {
-        if prices.len() < period {
-            return None;
-        }
-        let closes: Vec<f64> = prices.iter().map(|p| p.close).collect();
-        ema_series(&closes, period).last().copied()
-    }
-
-    /// Calculate Bollinger Bands
-    pub fn calculate_bollinger_bands(
-        prices: &[HistoricalPrice],
-        period: usize,
-        std_dev_multiplier: f64,
-    ) -> Option<BollingerBands> {
-        if prices.len() < period {
-            return None;
-        }
-
-        let recent: Vec<f64> = prices.iter().rev().take(period).map(|p| p.close).collect();
-        let middle_band = recent.iter().sum::<f64>() / period as f64;
-
-        let variance = recent
-            .iter()
-            .map(|x| (x - middle_band).powi(2))
-            .sum::<f64>()
-            / period as f64;
-        let std_dev = variance.sqrt();
-
-        let upper_band = middle_band + std_dev_multiplier * std_dev;
-        let lower_band = middle_band - std_dev_multiplier * std_dev;
-        let bandwidth = if middle_band > 0.0 {
-            (upper_band - lower_band) / middle_band * 100.0
-        } else {
-            0.0
-        };
-
-        Some(BollingerBands {
-            upper_band,
-            lower_band,
-            middle_band,
-            bandwidth,
-        })
-    }
-
-    /// Calculate Stochastic Oscillator (%K and %D)
-    pub fn calculate_stochastic(
-        prices: &[HistoricalPrice],
-        k_period: usize,
-        d_period: usize,
-    ) -> Option<StochasticOscillator> {
-        let needed = k_period + d_period - 1;
-        if prices.len() < needed {
-            return None;
-        }
-
-        // Calculate multiple %K values for the D period
-        let mut k_values = Vec::with_capacity(d_period);
-
-        for i in 0..d_period {
-            let end = prices.len() - i;
-            let start = if end >= k_period { end - k_period } else { 0 };
-            let window = &prices[start..end];
-
-            let highest_high = window
-                .iter()
-                .map(|p| p.high)
-                .fold(f64::NEG_INFINITY, f64::max);
-            let lowest_low = window.iter().map(|p| p.low).fold(f64::INFINITY, f64::min);
-            let close = window.last()?.close;
-
-            let range = highest_high - lowest_low;
-            let k = if range > 0.0 {
-                ((close - lowest_low) / range) * 100.0
-            } else {
-                50.0
-            };
-            k_values.push(k);
-        }
-
-        let k_line = k_values[0]; // Most recent %K
-        let d_line = k_values.iter().sum::<f64>() / k_values.len() as f64;
-
-        Some(StochasticOscillator { k_line, d_line })
+    pub fn sma_long<B: Bar>(&self, prices: &[B]) -> Option<f64> {
+        TechnicalIndicators::calculate_sma(prices, self.sma_long as usize)
     }
 
-    /// Calculate Pearson correlation coefficient between two price series
-    pub fn calculate_correlation(prices_a: &[f64], prices_b: &[f64]) -> Option<f64> {
-        let n = prices_a.len().min(prices_b.len());
-        if n < 2 {
-            return None;
-        }
-
-        let a = &prices_a[..n];
-        let b = &prices_b[..n];
-
-        let mean_a = a.iter().sum::<f64>() / n as f64;
-        let mean_b = b.iter().sum::<f64>() / n as f64;
-
-        let mut cov = 0.0;
-        let mut var_a = 0.0;
-        let mut var_b = 0.0;
-
-        for i in 0..n {
-            let da = a[i] - mean_a;
-            let db = b[i] - mean_b;
-            cov += da * db;
-            var_a += da * da;
-            var_b += db * db;
-        }
-
-        let denom = (var_a * var_b).sqrt();
-        if denom == 0.0 {
-            return None;
-        }
+    pub fn macd<B: Bar>(&self, prices: &[B]) -> Option<crate::models::MACDIndicator> {
+        TechnicalIndicators::calculate_macd_with(
+            prices,
+            self.macd_fast as usize,
+            self.macd_slow as usize,
+            self.macd_signal as usize,
+        )
+    }
+}
 
-        Some(cov / denom)
+impl Bar for HistoricalPrice {
+    fn close(&self) -> f64 {
+        self.close
     }
 
-    /// Determine if stock is oversold (RSI < 30)
-    pub fn is_oversold(rsi: Option<f64>) -> bool {
-        rsi.map_or(false, |r| r < 30.0)
+    fn high(&self) -> f64 {
+        self.high
     }
 
-    /// Determine if stock is overbought (RSI > 70)
-    pub fn is_overbought(rsi: Option<f64>) -> bool {
-        rsi.map_or(false, |r| r > 70.0)
+    fn low(&self) -> f64 {
+        self.low
     }
-}
 
-/// Compute the EMA series for `closes`, seeded with the SMA of the first
-/// `period` values. The returned vector has length `closes.len() - period + 1`
-/// (empty if there aren't enough samples). Iterates chronologically.
-fn ema_series(closes: &[f64], period: usize) -> Vec<f64> {
-    if closes.len() < period || period == 0 {
-        return Vec::new();
-    }
-    let k = 2.0 / (period as f64 + 1.0);
-    let mut out = Vec::with_capacity(closes.len() - period + 1);
-    let mut ema: f64 = closes[..period].iter().sum::<f64>() / period as f64;
-    out.push(ema);
-    for &c in &closes[period..] {
-        ema = (c - ema) * k + ema;
-        out.push(ema);
-    }
-    out
+    fn volume(&self) -> f64 {
+        self.volume
+    }
 }
 
 #[cfg(test)]
@@ -443,6 +279,48 @@ mod tests {
         assert!(TechnicalIndicators::calculate_macd(&prices34).is_some());
     }
 
+    #[test]
+    fn test_indicator_config_periods() {
+        let prices = create_test_prices((0..60).map(|i| 100.0 + (i % 7) as f64).collect());
+        let default = IndicatorConfig::default();
+        assert_eq!(
+            default.sma_short(&prices),
+            TechnicalIndicators::calculate_sma(&prices, 20)
+        );
+        assert_eq!(
+            default.macd(&prices).map(|m| m.macd_line),
+            TechnicalIndicators::calculate_macd(&prices).map(|m| m.macd_line)
+        );
+
+        let custom = IndicatorConfig {
+            sma_short: 5,
+            macd_fast: 5,
+            macd_slow: 35,
+            macd_signal: 5,
+            ..default
+        };
+        assert!(custom.validate().is_ok());
+        assert_eq!(
+            custom.sma_short(&prices),
+            TechnicalIndicators::calculate_sma(&prices, 5)
+        );
+        assert!(custom.macd(&prices[..38]).is_none());
+        assert!(custom.macd(&prices[..39]).is_some());
+
+        assert!(IndicatorConfig {
+            rsi_period: 0,
+            ..default
+        }
+        .validate()
+        .is_err());
+        assert!(IndicatorConfig {
+            sma_short: 50,
+            ..default
+        }
+        .validate()
+        .is_err());
+    }
+
     #[test]
     fn test_ema_calculation_chronological() {
         // 13 bars rising 100 → 112. Initial SMA(12) = mean(100..111) = 105.5.
@@ -712,4 +590,50 @@ mod tests {
             stoch.k_line
         );
     }
+
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn prop_rsi_stays_within_bounds(
+            closes in prop::collection::vec(0.01f64..100_000.0, 2..300),
+            period in 1usize..30,
+        ) {
+            let prices = create_test_prices(closes);
+            if let Some(rsi) = TechnicalIndicators::calculate_rsi(&prices, period) {
+                prop_assert!((0.0..=100.0).contains(&rsi), "rsi {}", rsi);
+            }
+        }
+
+        #[test]
+        fn prop_sma_lies_within_its_window(
+            closes in prop::collection::vec(0.01f64..100_000.0, 1..300),
+            period in 1usize..60,
+        ) {
+            let window: Vec<f64> = closes.iter().rev().take(period).copied().collect();
+            let prices = create_test_prices(closes.clone());
+            match TechnicalIndicators::calculate_sma(&prices, period) {
+                Some(sma) => {
+                    let min = window.iter().copied().fold(f64::INFINITY, f64::min);
+                    let max = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                    let tolerance = max * 1e-9;
+                    prop_assert!(sma >= min - tolerance && sma <= max + tolerance);
+                }
+                None => prop_assert!(closes.len() < period),
+            }
+        }
+
+        #[test]
+        fn prop_bands_and_stochastic_are_ordered(
+            closes in prop::collection::vec(0.01f64..100_000.0, 20..200),
+        ) {
+            let prices = create_test_prices(closes);
+            let bands = TechnicalIndicators::calculate_bollinger_bands(&prices, 20, 2.0).unwrap();
+            prop_assert!(bands.lower_band <= bands.middle_band && bands.middle_band <= bands.upper_band);
+            if let Some(stoch) = TechnicalIndicators::calculate_stochastic(&prices, 14, 3) {
+                prop_assert!((0.0..=100.0).contains(&stoch.k_line));
+                prop_assert!((0.0..=100.0).contains(&stoch.d_line));
+            }
+        }
+    }
 }
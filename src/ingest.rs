@@ -0,0 +1,406 @@
+//! External signal inbox.
+//!
+//! `POST /api/ingest/signal` accepts signals and notes pushed by outside
+//! systems (TradingView alert webhooks, custom scripts). Each one is stored in
+//! `external_signals` and kept in a process-local `SignalInbox`; the signals
+//! received within `INGEST_SIGNAL_TTL_HOURS` are copied onto the symbol's
+//! `StockAnalysis::external_signals`, where the `external_signal` alert
+//! condition matches them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio::sync::RwLock;
+
+/// Active signals kept per symbol, newest first.
+pub const MAX_ACTIVE_PER_SYMBOL: usize = 10;
+const MAX_SIGNAL_LEN: usize = 64;
+const MAX_SOURCE_LEN: usize = 64;
+const MAX_STRATEGY_LEN: usize = 128;
+const MAX_MESSAGE_LEN: usize = 2000;
+/// `source` when the sender doesn't name itself.
+const DEFAULT_SOURCE: &str = "webhook";
+/// Header carrying `INGEST_TOKEN`.
+pub const TOKEN_HEADER: &str = "x-ingest-token";
+
+/// Compare a provided token against `INGEST_TOKEN` without short-circuiting
+/// on the first differing byte.
+pub fn token_matches(expected: &str, provided: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), provided.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// One signal or note pushed by an external system.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExternalSignal {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub symbol: String,
+    /// Who sent it, e.g. `tradingview`. Lowercased.
+    pub source: String,
+    /// Short label such as `buy`, `sell` or `breakout`. Lowercased; `note`
+    /// when the sender only passed a message.
+    pub signal: String,
+    /// Strategy or indicator that produced it, e.g. a TradingView strategy
+    /// name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Price the sender saw when the signal fired.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Body of `POST /api/ingest/signal`. The aliases match the field names
+/// TradingView alert templates usually use (`{{ticker}}`, `{{close}}`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignalInput {
+    #[serde(alias = "ticker")]
+    pub symbol: String,
+    #[serde(default, alias = "action", skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(
+        default,
+        alias = "note",
+        alias = "text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub message: Option<String>,
+    /// Accepts a number or a numeric string, since template placeholders
+    /// are often quoted.
+    #[serde(
+        default,
+        alias = "close",
+        deserialize_with = "lenient_price",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub price: Option<f64>,
+    /// Shared secret, for senders that can't set the `X-Ingest-Token` header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn lenient_price<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f64),
+        Text(String),
+    }
+    match Option::<Raw>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Raw::Number(n)) => Ok(Some(n)),
+        Some(Raw::Text(s)) if s.trim().is_empty() => Ok(None),
+        Some(Raw::Text(s)) => s
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("invalid price '{}'", s))),
+    }
+}
+
+impl SignalInput {
+    /// Validate and normalize into a signal received at `now`.
+    pub fn into_signal(self, now: DateTime<Utc>) -> Result<ExternalSignal> {
+        let symbol = crate::symbols::normalize_symbol_key(&self.symbol);
+        if symbol.is_empty() {
+            return Err(anyhow!("symbol is required"));
+        }
+        let message = self
+            .message
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty());
+        if message
+            .as_ref()
+            .is_some_and(|m| m.chars().count() > MAX_MESSAGE_LEN)
+        {
+            return Err(anyhow!(
+                "message is longer than {} characters",
+                MAX_MESSAGE_LEN
+            ));
+        }
+        let signal = match self.signal.map(|s| s.trim().to_lowercase()) {
+            Some(s) if !s.is_empty() => s,
+            _ if message.is_some() => "note".to_string(),
+            _ => return Err(anyhow!("signal or message is required")),
+        };
+        if signal.chars().count() > MAX_SIGNAL_LEN {
+            return Err(anyhow!(
+                "signal is longer than {} characters",
+                MAX_SIGNAL_LEN
+            ));
+        }
+        let source = self
+            .source
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| DEFAULT_SOURCE.to_string());
+        if source.chars().count() > MAX_SOURCE_LEN {
+            return Err(anyhow!(
+                "source is longer than {} characters",
+                MAX_SOURCE_LEN
+            ));
+        }
+        let strategy = self
+            .strategy
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        if strategy
+            .as_ref()
+            .is_some_and(|s| s.chars().count() > MAX_STRATEGY_LEN)
+        {
+            return Err(anyhow!(
+                "strategy is longer than {} characters",
+                MAX_STRATEGY_LEN
+            ));
+        }
+        if self.price.is_some_and(|p| !p.is_finite() || p <= 0.0) {
+            return Err(anyhow!("price must be a positive number"));
+        }
+        Ok(ExternalSignal {
+            id: None,
+            symbol,
+            source,
+            signal,
+            strategy,
+            message,
+            price: self.price,
+            received_at: now,
+        })
+    }
+}
+
+/// Signals in `signals` received within `ttl` of `now`, newest first, capped
+/// at `MAX_ACTIVE_PER_SYMBOL`.
+pub fn active_signals(
+    signals: &[ExternalSignal],
+    now: DateTime<Utc>,
+    ttl: Duration,
+) -> Vec<ExternalSignal> {
+    let cutoff = now - ttl;
+    let mut active: Vec<ExternalSignal> = signals
+        .iter()
+        .filter(|s| s.received_at >= cutoff)
+        .cloned()
+        .collect();
+    active.sort_by_key(|s| std::cmp::Reverse(s.received_at));
+    active.truncate(MAX_ACTIVE_PER_SYMBOL);
+    active
+}
+
+/// Recent external signals keyed by symbol, shared by the API (which
+/// records them) and the analysis engine (which copies them onto fresh
+/// analyses).
+#[derive(Clone)]
+pub struct SignalInbox {
+    signals: Arc<RwLock<HashMap<String, Vec<ExternalSignal>>>>,
+    ttl: Duration,
+}
+
+impl SignalInbox {
+    pub fn new(ttl: Duration) -> Self {
+        SignalInbox {
+            signals: Arc::new(RwLock::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// How long a signal stays on its symbol's analysis.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Add signals loaded from the database at startup.
+    pub async fn load(&self, signals: Vec<ExternalSignal>) {
+        let now = Utc::now();
+        let mut inbox = self.signals.write().await;
+        for signal in signals {
+            inbox.entry(signal.symbol.clone()).or_default().push(signal);
+        }
+        for list in inbox.values_mut() {
+            *list = active_signals(list, now, self.ttl);
+        }
+        inbox.retain(|_, list| !list.is_empty());
+    }
+
+    /// Record `signal` and return its symbol's active signals.
+    pub async fn record(&self, signal: ExternalSignal) -> Vec<ExternalSignal> {
+        let mut inbox = self.signals.write().await;
+        let list = inbox.entry(signal.symbol.clone()).or_default();
+        list.push(signal);
+        *list = active_signals(list, Utc::now(), self.ttl);
+        list.clone()
+    }
+
+    /// Active signals for `symbol`, newest first.
+    pub async fn active(&self, symbol: &str) -> Vec<ExternalSignal> {
+        let now = Utc::now();
+        {
+            let inbox = self.signals.read().await;
+            match inbox.get(symbol) {
+                None => return Vec::new(),
+                Some(list) if list.iter().all(|s| s.received_at >= now - self.ttl) => {
+                    return list.clone()
+                }
+                Some(_) => {}
+            }
+        }
+        // Prune expired signals so the map doesn't grow without bound.
+        let mut inbox = self.signals.write().await;
+        let active = inbox
+            .get(symbol)
+            .map(|list| active_signals(list, now, self.ttl))
+            .unwrap_or_default();
+        if active.is_empty() {
+            inbox.remove(symbol);
+        } else {
+            inbox.insert(symbol.to_string(), active.clone());
+        }
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 15, hour, 0, 0).unwrap()
+    }
+
+    fn signal(symbol: &str, label: &str, received_at: DateTime<Utc>) -> ExternalSignal {
+        ExternalSignal {
+            id: None,
+            symbol: symbol.to_string(),
+            source: DEFAULT_SOURCE.to_string(),
+            signal: label.to_string(),
+            strategy: None,
+            message: None,
+            price: None,
+            received_at,
+        }
+    }
+
+    #[test]
+    fn tradingview_body_is_normalized() {
+        let input: SignalInput = serde_json::from_value(serde_json::json!({
+            "ticker": "brk.b",
+            "action": " BUY ",
+            "source": "TradingView",
+            "close": "412.50",
+        }))
+        .unwrap();
+        let s = input.into_signal(at(12)).unwrap();
+        assert_eq!(s.symbol, "BRK-B");
+        assert_eq!(s.signal, "buy");
+        assert_eq!(s.source, "tradingview");
+        assert_eq!(s.price, Some(412.5));
+        assert_eq!(s.received_at, at(12));
+    }
+
+    #[test]
+    fn message_alone_becomes_a_note() {
+        let input = SignalInput {
+            symbol: "AAPL".into(),
+            message: Some("Guidance raised on the call".into()),
+            ..Default::default()
+        };
+        let s = input.into_signal(at(12)).unwrap();
+        assert_eq!(s.signal, "note");
+        assert_eq!(s.source, "webhook");
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let empty = SignalInput {
+            symbol: "AAPL".into(),
+            ..Default::default()
+        };
+        assert!(empty.into_signal(at(12)).is_err());
+
+        let no_symbol = SignalInput {
+            symbol: "  ".into(),
+            signal: Some("buy".into()),
+            ..Default::default()
+        };
+        assert!(no_symbol.into_signal(at(12)).is_err());
+
+        let bad_price = SignalInput {
+            symbol: "AAPL".into(),
+            signal: Some("buy".into()),
+            price: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(bad_price.into_signal(at(12)).is_err());
+
+        let long_signal = SignalInput {
+            symbol: "AAPL".into(),
+            signal: Some("x".repeat(MAX_SIGNAL_LEN + 1)),
+            ..Default::default()
+        };
+        assert!(long_signal.into_signal(at(12)).is_err());
+
+        let text_price: Result<SignalInput, _> =
+            serde_json::from_value(serde_json::json!({ "symbol": "AAPL", "price": "abc" }));
+        assert!(text_price.is_err());
+    }
+
+    #[test]
+    fn token_must_match_exactly() {
+        assert!(token_matches("s3cret", "s3cret"));
+        assert!(!token_matches("s3cret", "s3cre"));
+        assert!(!token_matches("s3cret", "S3cret"));
+        assert!(!token_matches("s3cret", ""));
+    }
+
+    #[test]
+    fn active_signals_drop_expired_and_sort_newest_first() {
+        let signals = vec![
+            signal("AAPL", "old", at(1)),
+            signal("AAPL", "sell", at(10)),
+            signal("AAPL", "buy", at(11)),
+        ];
+        let active = active_signals(&signals, at(12), Duration::hours(6));
+        let labels: Vec<_> = active.iter().map(|s| s.signal.as_str()).collect();
+        assert_eq!(labels, vec!["buy", "sell"]);
+    }
+
+    #[test]
+    fn active_signals_are_capped() {
+        let signals: Vec<_> = (0..MAX_ACTIVE_PER_SYMBOL as u32 + 3)
+            .map(|i| signal("AAPL", "buy", at(i)))
+            .collect();
+        let active = active_signals(&signals, at(23), Duration::hours(24));
+        assert_eq!(active.len(), MAX_ACTIVE_PER_SYMBOL);
+        assert_eq!(active[0].received_at, at(MAX_ACTIVE_PER_SYMBOL as u32 + 2));
+    }
+
+    #[tokio::test]
+    async fn inbox_records_per_symbol() {
+        let inbox = SignalInbox::new(Duration::hours(24));
+        let now = Utc::now();
+        inbox
+            .load(vec![
+                signal("AAPL", "sell", now - Duration::hours(2)),
+                signal("MSFT", "stale", now - Duration::hours(30)),
+            ])
+            .await;
+        let active = inbox.record(signal("AAPL", "buy", now)).await;
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].signal, "buy");
+        assert_eq!(inbox.active("AAPL").await.len(), 2);
+        assert!(inbox.active("MSFT").await.is_empty());
+        assert!(inbox.active("TSLA").await.is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+//! Intraday candle relay for WebSocket subscribers.
+//!
+//! Clients send `{"action":"subscribe","symbols":[..]}` on `/ws`. While the US
+//! market is open, a background task polls Yahoo's batch quote endpoint for
+//! every subscribed symbol and folds the quotes into fixed-width candles.
+//! Each update to the candle in progress is broadcast; the WebSocket handler
+//! forwards only the symbols that client asked for.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, warn};
+
+use crate::models::{IntradayCandle, LiveQuote};
+use crate::yahoo::{YahooFinanceClient, BATCH_QUOTE_LIMIT};
+
+/// Most symbols a single WebSocket client may subscribe to.
+pub const MAX_SUBSCRIPTIONS_PER_CLIENT: usize = 20;
+
+/// Regular session, New York time (holidays are not modelled).
+pub fn is_market_hours(now: DateTime<Utc>) -> bool {
+    let ny = now.with_timezone(&chrono_tz::America::New_York);
+    if matches!(ny.weekday(), Weekday::Sat | Weekday::Sun) {
+        return false;
+    }
+    let open = NaiveTime::from_hms_opt(9, 30, 0).expect("valid time");
+    let close = NaiveTime::from_hms_opt(16, 0, 0).expect("valid time");
+    (open..close).contains(&ny.time())
+}
+
+/// Folds quotes for one symbol into candles `interval_secs` wide.
+#[derive(Debug, Clone)]
+pub struct CandleBuilder {
+    interval_secs: i64,
+    current: Option<IntradayCandle>,
+    /// Session volume when the current candle opened.
+    volume_at_open: Option<f64>,
+}
+
+impl CandleBuilder {
+    pub fn new(interval_secs: i64) -> Self {
+        Self {
+            interval_secs: interval_secs.max(1),
+            current: None,
+            volume_at_open: None,
+        }
+    }
+
+    /// Apply a quote and return the updated candle in progress. Quotes older
+    /// than the current candle are ignored.
+    pub fn apply(&mut self, quote: &LiveQuote) -> Option<IntradayCandle> {
+        let ts = quote.quoted_at.timestamp();
+        let bucket = ts - ts.rem_euclid(self.interval_secs);
+        let start = DateTime::from_timestamp(bucket, 0)?;
+
+        match &mut self.current {
+            Some(c) if c.start == start => {
+                c.high = c.high.max(quote.price);
+                c.low = c.low.min(quote.price);
+                c.close = quote.price;
+                if let (Some(open_vol), Some(vol)) = (self.volume_at_open, quote.day_volume) {
+                    c.volume = (vol - open_vol).max(0.0);
+                }
+            }
+            Some(c) if c.start > start => return None,
+            _ => {
+                // The previous candle's last cumulative volume is the best
+                // estimate of where this one starts.
+                self.volume_at_open = quote.day_volume;
+                self.current = Some(IntradayCandle {
+                    symbol: quote.symbol.clone(),
+                    start,
+                    interval_secs: self.interval_secs,
+                    open: quote.price,
+                    high: quote.price,
+                    low: quote.price,
+                    close: quote.price,
+                    volume: 0.0,
+                });
+            }
+        }
+        self.current.clone()
+    }
+}
+
+/// Shared subscription registry plus the candle broadcast channel.
+#[derive(Clone)]
+pub struct IntradayRelay {
+    /// Symbol → number of connected clients subscribed to it.
+    subscriptions: Arc<RwLock<HashMap<String, usize>>>,
+    tx: broadcast::Sender<IntradayCandle>,
+}
+
+impl Default for IntradayRelay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntradayRelay {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(1024);
+        Self {
+            subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            tx,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<IntradayCandle> {
+        self.tx.subscribe()
+    }
+
+    pub async fn add_symbols(&self, symbols: &[String]) {
+        let mut subs = self.subscriptions.write().await;
+        for s in symbols {
+            *subs.entry(s.clone()).or_default() += 1;
+        }
+    }
+
+    pub async fn remove_symbols(&self, symbols: &[String]) {
+        let mut subs = self.subscriptions.write().await;
+        for s in symbols {
+            if let Some(n) = subs.get_mut(s) {
+                *n -= 1;
+                if *n == 0 {
+                    subs.remove(s);
+                }
+            }
+        }
+    }
+
+    pub async fn subscribed_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.subscriptions.read().await.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Poll batch quotes every `poll_secs` during market hours and broadcast
+    /// candles `candle_secs` wide. `poll_secs == 0` leaves the relay idle.
+    pub fn spawn_poller(&self, yahoo: YahooFinanceClient, poll_secs: u64, candle_secs: u64) {
+        if poll_secs == 0 {
+            return;
+        }
+        let relay = self.clone();
+        tokio::spawn(async move {
+            let mut builders: HashMap<String, CandleBuilder> = HashMap::new();
+            let mut ticker = tokio::time::interval(Duration::from_secs(poll_secs));
+            loop {
+                ticker.tick().await;
+                let symbols = relay.subscribed_symbols().await;
+                builders.retain(|s, _| symbols.contains(s));
+                if symbols.is_empty() || !is_market_hours(Utc::now()) {
+                    continue;
+                }
+                for chunk in symbols.chunks(BATCH_QUOTE_LIMIT) {
+                    let quotes = match yahoo.get_batch_quotes(chunk).await {
+                        Ok(q) => q,
+                        Err(e) => {
+                            warn!("intraday batch quote failed: {}", e);
+                            continue;
+                        }
+                    };
+                    for quote in &quotes {
+                        let candle = builders
+                            .entry(quote.symbol.clone())
+                            .or_insert_with(|| CandleBuilder::new(candle_secs as i64))
+                            .apply(quote);
+                        // No receivers is fine: every client may have left
+                        // between polls.
+                        if let Some(candle) = candle {
+                            let _ = relay.tx.send(candle);
+                        }
+                    }
+                    debug!("intraday relay: {} quotes", quotes.len());
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn quote(secs: i64, price: f64, volume: f64) -> LiveQuote {
+        let base = Utc.with_ymd_and_hms(2025, 6, 2, 14, 0, 0).unwrap();
+        LiveQuote {
+            symbol: "AAPL".to_string(),
+            price,
+            day_volume: Some(volume),
+            quoted_at: base + chrono::Duration::seconds(secs),
+        }
+    }
+
+    #[test]
+    fn builds_candles_from_successive_quotes() {
+        let mut b = CandleBuilder::new(60);
+        b.apply(&quote(0, 100.0, 1_000.0));
+        b.apply(&quote(15, 102.0, 1_400.0));
+        let c = b.apply(&quote(45, 99.0, 1_500.0)).unwrap();
+        assert_eq!((c.open, c.high, c.low, c.close), (100.0, 102.0, 99.0, 99.0));
+        assert_eq!(c.volume, 500.0);
+
+        let next = b.apply(&quote(60, 101.0, 1_600.0)).unwrap();
+        assert_eq!(next.start, c.start + chrono::Duration::seconds(60));
+        assert_eq!(next.open, 101.0);
+        assert_eq!(next.volume, 0.0);
+
+        // Stale quote from the previous minute.
+        assert!(b.apply(&quote(50, 500.0, 1_700.0)).is_none());
+    }
+
+    #[test]
+    fn market_hours_follow_new_york_session() {
+        // 13:45 UTC = 09:45 EDT, Monday.
+        assert!(is_market_hours(
+            Utc.with_ymd_and_hms(2025, 6, 2, 13, 45, 0).unwrap()
+        ));
+        // 20:00 UTC = 16:00 EDT: closed.
+        assert!(!is_market_hours(
+            Utc.with_ymd_and_hms(2025, 6, 2, 20, 0, 0).unwrap()
+        ));
+        // Saturday.
+        assert!(!is_market_hours(
+            Utc.with_ymd_and_hms(2025, 6, 7, 15, 0, 0).unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn subscriptions_are_reference_counted() {
+        let relay = IntradayRelay::new();
+        let aapl = vec!["AAPL".to_string()];
+        relay.add_symbols(&aapl).await;
+        relay.add_symbols(&aapl).await;
+        relay.remove_symbols(&aapl).await;
+        assert_eq!(relay.subscribed_symbols().await, aapl);
+        relay.remove_symbols(&aapl).await;
+        assert!(relay.subscribed_symbols().await.is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-sector lanes for the analysis cycle.
+//!
+//! Processing the universe in screener order refreshes one sector after
+//! another, so a sector near the end of the list is almost a whole cycle
+//! stale by the time it comes round. `SECTOR_LANES` splits the queued
+//! symbols into one lane per sector (keeping each lane in universe order)
+//! and either interleaves them round-robin into a single queue or fetches
+//! every lane in parallel with its own share of `YAHOO_CONCURRENCY`. Either
+//! way each sector is refreshed throughout the cycle, and the engine clears
+//! the list caches every 1/lanes of the queue so sector aggregates follow.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+/// Lane symbols with no stored sector yet fall into.
+pub const UNKNOWN_SECTOR: &str = "Unknown";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LaneMode {
+    /// One queue in universe order.
+    Off,
+    /// One queue taking a symbol from each sector in turn.
+    #[default]
+    RoundRobin,
+    /// One fetcher per sector, each with its own concurrency.
+    Parallel,
+}
+
+impl LaneMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            LaneMode::Off => "off",
+            LaneMode::RoundRobin => "round_robin",
+            LaneMode::Parallel => "parallel",
+        }
+    }
+}
+
+impl FromStr for LaneMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized = s.trim().to_ascii_lowercase().replace('-', "_");
+        [LaneMode::Off, LaneMode::RoundRobin, LaneMode::Parallel]
+            .into_iter()
+            .find(|mode| mode.name() == normalized)
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown sector lane mode '{}' (expected off, round_robin or parallel)",
+                    s.trim()
+                )
+            })
+    }
+}
+
+impl fmt::Display for LaneMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Symbols of one sector, in universe order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lane {
+    pub sector: String,
+    pub symbols: Vec<String>,
+}
+
+/// Group `(symbol, sector)` pairs into lanes. Lanes are ordered by their
+/// first symbol, so the sector of the largest queued name goes first.
+pub fn partition(symbols: Vec<(String, Option<String>)>) -> Vec<Lane> {
+    let mut lanes: Vec<Lane> = Vec::new();
+    for (symbol, sector) in symbols {
+        let sector = sector
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| UNKNOWN_SECTOR.to_string());
+        match lanes.iter_mut().find(|lane| lane.sector == sector) {
+            Some(lane) => lane.symbols.push(symbol),
+            None => lanes.push(Lane {
+                sector,
+                symbols: vec![symbol],
+            }),
+        }
+    }
+    lanes
+}
+
+/// One symbol from each lane in turn until every lane is drained.
+pub fn round_robin(lanes: &[Lane]) -> Vec<String> {
+    let longest = lanes.iter().map(|l| l.symbols.len()).max().unwrap_or(0);
+    (0..longest)
+        .flat_map(|i| lanes.iter().filter_map(move |lane| lane.symbols.get(i)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued(pairs: &[(&str, Option<&str>)]) -> Vec<(String, Option<String>)> {
+        pairs
+            .iter()
+            .map(|(symbol, sector)| (symbol.to_string(), sector.map(str::to_string)))
+            .collect()
+    }
+
+    #[test]
+    fn test_partition_keeps_universe_order() {
+        let lanes = partition(queued(&[
+            ("AAPL", Some("Technology")),
+            ("JPM", Some("Finance")),
+            ("MSFT", Some("Technology")),
+            ("NEWCO", None),
+            ("BAC", Some("Finance")),
+            ("ODD", Some(" ")),
+        ]));
+        let summary: Vec<(&str, Vec<&str>)> = lanes
+            .iter()
+            .map(|l| {
+                (
+                    l.sector.as_str(),
+                    l.symbols.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Technology", vec!["AAPL", "MSFT"]),
+                ("Finance", vec!["JPM", "BAC"]),
+                (UNKNOWN_SECTOR, vec!["NEWCO", "ODD"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_robin_interleaves_uneven_lanes() {
+        let lanes = partition(queued(&[
+            ("A1", Some("A")),
+            ("A2", Some("A")),
+            ("A3", Some("A")),
+            ("B1", Some("B")),
+            ("C1", Some("C")),
+            ("C2", Some("C")),
+        ]));
+        assert_eq!(
+            round_robin(&lanes),
+            vec!["A1", "B1", "C1", "A2", "C2", "A3"]
+        );
+        assert!(round_robin(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_lane_mode_parse() {
+        assert_eq!(
+            "round-robin".parse::<LaneMode>().unwrap(),
+            LaneMode::RoundRobin
+        );
+        assert_eq!(
+            " PARALLEL ".parse::<LaneMode>().unwrap(),
+            LaneMode::Parallel
+        );
+        assert_eq!("off".parse::<LaneMode>().unwrap(), LaneMode::Off);
+        assert!("sideways".parse::<LaneMode>().is_err());
+    }
+}
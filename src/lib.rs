@@ -7,16 +7,57 @@
 //! - NASDAQ data integration
 
 pub mod analysis;
+pub mod analytics;
 pub mod api;
+pub mod archive;
+pub mod asset_types;
 pub mod async_fetcher;
+pub mod backup;
 pub mod cache;
+pub mod change_feed;
 pub mod config;
+pub mod constituents;
+pub mod cross_section;
 pub mod db;
+pub mod degradation;
+pub mod demo;
+pub mod format;
+pub mod grpc;
+pub mod highs_lows;
+pub mod incremental;
 pub mod indexes;
 pub mod indicators;
+pub mod ingest;
+pub mod intraday;
+pub mod lanes;
+pub mod maintenance;
 pub mod models;
 pub mod nasdaq;
+pub mod notes;
 pub mod notifications;
 pub mod openrouter;
+pub mod percentiles;
+pub mod pipeline;
+pub mod query_profiler;
+pub mod rate_budget;
+pub mod renames;
+pub mod repair;
+pub mod replay;
+pub mod response_cache;
+pub mod retention;
+pub mod runs;
+pub mod screens;
+pub mod sectors;
+pub mod seed;
+pub mod share_classes;
+pub mod signals;
+pub mod storage;
 pub mod symbols;
+pub mod themes;
+pub mod timezone;
+pub mod tradingview;
+pub mod transfer;
+pub mod units;
+pub mod validation;
+pub mod weekend;
 pub mod yahoo;
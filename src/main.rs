@@ -1,16 +1,57 @@
 mod analysis;
+mod analytics;
 mod api;
+mod archive;
+mod asset_types;
 mod async_fetcher;
+mod backup;
 mod cache;
+mod change_feed;
 mod config;
+mod constituents;
+mod cross_section;
 mod db;
+mod degradation;
+mod demo;
+mod format;
+mod grpc;
+mod highs_lows;
+mod incremental;
 mod indexes;
 mod indicators;
+mod ingest;
+mod intraday;
+mod lanes;
+mod maintenance;
 mod models;
 mod nasdaq;
+mod notes;
 mod notifications;
 mod openrouter;
+mod percentiles;
+mod pipeline;
+mod query_profiler;
+mod rate_budget;
+mod renames;
+mod repair;
+mod replay;
+mod response_cache;
+mod retention;
+mod runs;
+mod screens;
+mod sectors;
+mod seed;
+mod share_classes;
+mod signals;
+mod storage;
 mod symbols;
+mod themes;
+mod timezone;
+mod tradingview;
+mod transfer;
+mod units;
+mod validation;
+mod weekend;
 mod yahoo;
 
 use analysis::AnalysisEngine;
@@ -18,9 +59,12 @@ use api::{create_router, AppState};
 use cache::CacheLayer;
 use config::Config;
 use db::MongoDB;
+use degradation::DegradationPolicy;
+use indexes::IndexDataProvider;
 use nasdaq::NasdaqClient;
 use notifications::AlertEngine;
 use openrouter::OpenRouterClient;
+use rate_budget::RequestBudget;
 use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use yahoo::YahooFinanceClient;
@@ -42,21 +86,234 @@ async fn main() -> anyhow::Result<()> {
     let config = Config::from_env()?;
     tracing::info!("Configuration loaded");
 
+    // The full Russell 2000 list must be in place before index membership
+    // is first looked up.
+    if let Some(path) = &config.russell2000_file {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("RUSSELL2000_FILE {}: {}", path, e))?;
+        let symbols = indexes::parse_constituent_list(&text);
+        if symbols.is_empty() {
+            anyhow::bail!("RUSSELL2000_FILE {} has no tickers", path);
+        }
+        tracing::info!(
+            "Loaded {} Russell 2000 constituents from {}",
+            symbols.len(),
+            path
+        );
+        IndexDataProvider::load_russell2000(symbols);
+    }
+
+    // `auto_analyser_2 --demo` serves a bundled dataset without MongoDB
+    if std::env::args().any(|arg| arg == "--demo") {
+        let app_state = demo::state(&config).await?;
+        return serve(&config, app_state).await;
+    }
+
     // Connect to MongoDB
     tracing::info!("Connecting to MongoDB at {}...", config.mongodb_uri);
-    let db = MongoDB::new(&config.mongodb_uri, &config.database_name).await?;
+    let db = MongoDB::new(
+        &config.mongodb_uri,
+        &config.database_name,
+        config.mongo_settings()?,
+    )
+    .await?;
     tracing::info!("✅ Connected to MongoDB database: {}", config.database_name);
+    let storage = storage::configured(&config, &db).await?;
+
+    // Fetched index membership replaces the embedded lists before the
+    // engine tags analyses with it
+    let constituent_updater = constituents::ConstituentUpdater::new(db.clone());
+    match constituent_updater.load_saved().await {
+        Ok(0) => {}
+        Ok(loaded) => tracing::info!("📋 Loaded stored constituents for {} indexes", loaded),
+        Err(e) => tracing::warn!("Failed to load stored constituents: {}", e),
+    }
+
+    // `auto_analyser_2 restore <backup>` loads a backup and exits
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("restore") {
+        let Some(name) = args.get(2) else {
+            anyhow::bail!("usage: auto_analyser_2 restore <backup name or path>");
+        };
+        let dir = config.backup_dir.as_deref().map(std::path::Path::new);
+        let path = backup::resolve_backup(dir, name)?;
+        let manifest = backup::restore(&db, &path).await?;
+        tracing::info!(
+            "♻️  Restored backup {} ({} collections) into {}",
+            manifest.name,
+            manifest.collections.len(),
+            config.database_name
+        );
+        return Ok(());
+    }
+
+    // `auto_analyser_2 seed-export <file>` writes a first-run seed and exits
+    if args.get(1).map(String::as_str) == Some("seed-export") {
+        let path = args
+            .get(2)
+            .map(String::as_str)
+            .unwrap_or(seed::DEFAULT_SEED_PATH);
+        let summary = seed::export(&db, std::path::Path::new(path)).await?;
+        tracing::info!(
+            "🌱 Wrote seed {} ({} documents)",
+            path,
+            summary.values().sum::<u64>()
+        );
+        return Ok(());
+    }
+
+    // `auto_analyser_2 replay <from> <to> [SYMBOL,...]` recomputes history
+    // from the raw response archive and exits
+    if args.get(1).map(String::as_str) == Some("replay") {
+        let (Some(from), Some(to)) = (args.get(2), args.get(3)) else {
+            anyhow::bail!(
+                "usage: auto_analyser_2 replay <from YYYY-MM-DD> <to YYYY-MM-DD> [SYMBOL,...]"
+            );
+        };
+        let Some(settings) = config.archive_settings() else {
+            anyhow::bail!("replay needs RAW_ARCHIVE_DIR");
+        };
+        let request = replay::ReplayRequest {
+            from: from.parse()?,
+            to: to.parse()?,
+            symbols: args
+                .get(4)
+                .map(|list| symbols::parse_symbol_list(list))
+                .unwrap_or_default(),
+        };
+        let report = replay::run(
+            &db,
+            &archive::RawArchive::new(settings),
+            &request,
+            &config.indicators,
+        )
+        .await?;
+        tracing::info!(
+            "⏪ Replayed {} charts over {} days: {} snapshots updated, {} added, {} skipped, {} failed",
+            report.charts,
+            report.days,
+            report.merged,
+            report.inserted,
+            report.skipped,
+            report.failures
+        );
+        return Ok(());
+    }
+
+    // Seeding, repair and the default themes all write, so read-only mode
+    // skips them and the database stays untouched for backups and migrations.
+    let startup_writes = !config.read_only_mode;
+    if !startup_writes {
+        tracing::warn!("🚧 Read-only mode: skipping the startup seed, repair and default themes");
+    }
+
+    // A fresh database is populated from the seed snapshot, if one is
+    // available, so the dashboard isn't empty during the first cycle.
+    if let Some(snapshot) = config.seed_snapshot.as_ref().filter(|_| startup_writes) {
+        let source = seed::SeedSource::parse(snapshot);
+        if source.available() {
+            match seed::seed_if_empty(&db, &source).await {
+                Ok(Some(summary)) => tracing::info!(
+                    "🌱 Seeded {} documents from {}: {:?}",
+                    summary.values().sum::<u64>(),
+                    source,
+                    summary
+                ),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to load seed {}: {}", source, e),
+            }
+        } else if snapshot != seed::DEFAULT_SEED_PATH {
+            tracing::warn!("SEED_SNAPSHOT {} not found", snapshot);
+        }
+    }
+
+    // Fix documents from older schema versions before anything reads them.
+    if config.startup_repair && startup_writes {
+        match repair::run(&db).await {
+            Ok(report) => tracing::info!("🩺 Startup repair: {}", report),
+            Err(e) => tracing::warn!("Startup repair failed: {}", e),
+        }
+    }
+
+    if startup_writes {
+        match db.seed_themes(&themes::default_themes()).await {
+            Ok(0) => {}
+            Ok(seeded) => tracing::info!("Seeded {} default themes", seeded),
+            Err(e) => tracing::warn!("Failed to seed themes: {}", e),
+        }
+    }
 
     // Initialize cache
-    let cache = CacheLayer::new(config.cache_ttl_secs, config.news_cache_ttl_secs);
+    let cache = CacheLayer::new(
+        config.cache_ttl_secs,
+        config.news_cache_ttl_secs,
+        config.cache_memory_budget_mb,
+    )
+    .with_read_through(storage.clone())
+    .with_quote_ttl(std::time::Duration::from_secs(config.quote_cache_ttl_secs))
+    .with_response_routes(config.response_cache_ttls.clone());
     tracing::info!(
-        "Cache layer initialized with TTL: {}s (news: {}s)",
+        "Cache layer initialized with TTL: {}s (news: {}s), memory budget: {} MiB",
         config.cache_ttl_secs,
-        config.news_cache_ttl_secs
+        config.news_cache_ttl_secs,
+        config.cache_memory_budget_mb
     );
+    if !config.response_cache_ttls.is_empty() {
+        tracing::info!("Response cache routes: {}", config.response_cache_ttls);
+    }
+
+    // Re-apply cache pins saved through the API
+    match db.get_cache_pins().await {
+        Ok(pins) => {
+            for pin in &pins {
+                cache.pin_stock(&pin.symbol, pin.ttl()).await;
+            }
+            if !pins.is_empty() {
+                tracing::info!("📌 Restored {} cache pins", pins.len());
+            }
+        }
+        Err(e) => tracing::warn!("Failed to load cache pins: {}", e),
+    }
+
+    // Renamed tickers resolve to their current symbol in every lookup
+    match db.get_symbol_aliases().await {
+        Ok(aliases) => {
+            for alias in &aliases {
+                cache.add_symbol_alias(&alias.old_symbol, &alias.new_symbol);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to load symbol aliases: {}", e),
+    }
+
+    tracing::info!("Analysis pipeline stages: {}", config.analysis_stages);
 
     // Initialize Yahoo Finance client
-    let yahoo_client = YahooFinanceClient::new();
+    let mut yahoo_client = YahooFinanceClient::new();
+    if config.yahoo_budget_per_min > 0 {
+        yahoo_client = yahoo_client.with_budget(std::sync::Arc::new(RequestBudget::new(
+            config.yahoo_budget_per_min as f64,
+            config.yahoo_budget_burst,
+            config.yahoo_interactive_reserve,
+        )));
+        tracing::info!(
+            "Yahoo request budget: {}/min, burst {}, {} reserved for API requests",
+            config.yahoo_budget_per_min,
+            config.yahoo_budget_burst,
+            config.yahoo_interactive_reserve
+        );
+    }
+    // Optional archive of raw upstream responses
+    let raw_archive = config.archive_settings().map(archive::RawArchive::new);
+    if let Some(archive) = &raw_archive {
+        yahoo_client = yahoo_client.with_archive(archive.clone());
+        tracing::info!(
+            "Archiving raw upstream responses to {} (sample rate {}, {} days kept)",
+            archive.settings().dir.display(),
+            archive.settings().sample_rate,
+            archive.settings().retention_days
+        );
+        archive.clone().spawn();
+    }
     tracing::info!("Yahoo Finance client initialized");
 
     // Initialize OpenRouter client
@@ -88,14 +345,114 @@ async fn main() -> anyhow::Result<()> {
     )
     .await?;
 
+    // Read-only switch for migrations and backups
+    let maintenance = maintenance::MaintenanceMode::new(config.read_only_mode);
+    if config.read_only_mode {
+        tracing::warn!("🚧 Starting in read-only mode (READ_ONLY_MODE=true)");
+    }
+
+    let backups = config.backup_settings();
+    if let Some(settings) = &backups {
+        tracing::info!(
+            "💾 Backups to {} every {}h, keeping {}",
+            settings.dir.display(),
+            config.backup_interval_hours,
+            settings.retention
+        );
+        backup::spawn(db.clone(), settings.clone());
+    }
+
+    // Age out old analysis snapshots
+    if config.history_retention_days > 0 {
+        tracing::info!(
+            "🧹 Keeping {} days of analysis history",
+            config.history_retention_days
+        );
+        retention::spawn(
+            db.clone(),
+            config.history_retention_days,
+            maintenance.clone(),
+        );
+    }
+
+    // Daily valuation history for tracked positions
+    notifications::valuation::spawn(
+        alert_engine.repo().clone(),
+        cache.clone(),
+        maintenance.clone(),
+    );
+
+    // End-of-day watchlist recap to opted-in channels
+    if let Some(minutes) = config.watchlist_recap_minutes_after_close {
+        tracing::info!("📬 Watchlist recap {} min after the NY close", minutes);
+        notifications::recap::spawn(alert_engine.clone(), cache.clone(), minutes);
+    }
+
+    // Current S&P 500 / NASDAQ 100 membership
+    if config.constituent_refresh_hours > 0 {
+        tracing::info!(
+            "📋 Index constituents refreshed every {}h",
+            config.constituent_refresh_hours
+        );
+        constituent_updater.spawn(config.constituent_refresh_hours, maintenance.clone());
+    }
+
+    // Nightly beta / correlation / RS rank over the stored closes
+    if let Some(hour) = config.cross_section_hour_utc {
+        tracing::info!("📐 Cross-section batch daily at {:02}:00 UTC", hour);
+        cross_section::spawn(
+            db.clone(),
+            yahoo_client.background(),
+            hour,
+            maintenance.clone(),
+        );
+    }
+
+    // Saturday backfills, seasonality, AI summaries and database upkeep
+    let weekend = config.weekend_settings();
+    if let Some(hour) = weekend.hour_ny {
+        tracing::info!(
+            "🗓️  Weekend deep analysis Saturdays at {:02}:00 New York",
+            hour
+        );
+        weekend::spawn(
+            db.clone(),
+            yahoo_client.background(),
+            openrouter_client.clone(),
+            weekend.clone(),
+            maintenance.clone(),
+        );
+    }
+
+    // External signals still within their TTL ride along on analyses
+    let signals = ingest::SignalInbox::new(chrono::Duration::hours(config.ingest_signal_ttl_hours));
+    match db
+        .get_external_signals(chrono::Utc::now() - signals.ttl(), None, 0, 10_000)
+        .await
+    {
+        Ok(recent) => signals.load(recent).await,
+        Err(e) => tracing::warn!("Failed to load external signals: {}", e),
+    }
+    if config.ingest_token.is_none() {
+        tracing::warn!("INGEST_TOKEN is unset; /api/ingest/signal accepts unauthenticated signals");
+    }
+
+    // User notes and tags ride along on analyses too
+    let notes = notes::SymbolNotes::default();
+    match db.get_symbol_notes().await {
+        Ok(saved) => notes.load(saved).await,
+        Err(e) => tracing::warn!("Failed to load symbol notes: {}", e),
+    }
+
     // Create analysis engine
     let analysis_engine = AnalysisEngine::new(
         db.clone(),
+        storage.clone(),
         cache.clone(),
         config.analysis_interval_secs,
         config.yahoo_request_delay_ms,
         config.yahoo_concurrency,
-        yahoo_client.clone(),
+        yahoo_client.background(),
         config.nasdaq_request_delay_ms,
         config.min_market_cap_usd,
         config.max_abs_price_change_percent,
@@ -103,6 +460,24 @@ async fn main() -> anyhow::Result<()> {
         Some(alert_engine.clone()),
         config.yahoo_circuit_failure_threshold,
         config.yahoo_circuit_skip_cycles,
+        config.analysis_stages.clone(),
+        std::time::Duration::from_secs(config.stage_timeout_secs),
+        DegradationPolicy {
+            error_rate: config.degrade_error_rate,
+            window: config.degrade_window,
+            cooldown: std::time::Duration::from_secs(config.degrade_cooldown_secs),
+            delay_multiplier: config.degraded_delay_multiplier,
+        },
+        config.dead_letter_threshold,
+        config.russell_chunk_size,
+        signals.clone(),
+        notes.clone(),
+        maintenance.clone(),
+        config.sector_lanes,
+        raw_archive.clone(),
+        config.indicators,
+        config.incremental_fetch,
+        config.risk_free_rate_pct,
     );
     let progress = analysis_engine.get_progress();
     tracing::info!(
@@ -118,10 +493,13 @@ async fn main() -> anyhow::Result<()> {
 
     // Load existing data from MongoDB and populate cache
     tracing::info!("📥 Loading existing stock data from database...");
-    match analysis_engine.load_existing_data().await {
+    match analysis_engine
+        .load_existing_data(config.cache_warm_limit)
+        .await
+    {
         Ok(count) => {
             if count > 0 {
-                tracing::info!("✅ Loaded {} stock analyses from database", count);
+                tracing::info!("✅ Warmed cache with {} stock analyses", count);
             } else {
                 tracing::info!("📊 No existing data found. Will perform initial analysis.");
             }
@@ -140,20 +518,72 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // Create NASDAQ client for API endpoints
-    let nasdaq_client = NasdaqClient::new(config.nasdaq_request_delay_ms);
+    let mut nasdaq_client = NasdaqClient::new(config.nasdaq_request_delay_ms);
+    if let Some(archive) = &raw_archive {
+        nasdaq_client = nasdaq_client.with_archive(archive.clone());
+    }
+
+    // Optional gRPC facade alongside the REST API
+    if let Some(grpc_port) = config.grpc_port {
+        let grpc_addr = format!("{}:{}", config.server_host, grpc_port).parse()?;
+        let service =
+            grpc::AnalyserService::new(storage.clone(), cache.clone(), yahoo_client.clone());
+        tokio::spawn(grpc::serve(grpc_addr, service));
+    }
+
+    // Intraday candle relay for WebSocket subscribers
+    let intraday = intraday::IntradayRelay::new();
+    intraday.spawn_poller(
+        yahoo_client.background(),
+        config.intraday_poll_secs,
+        config.intraday_candle_secs,
+    );
+
+    // Saved analyses for WebSocket clients, from the change stream
+    let analysis_feed = change_feed::AnalysisFeed::new();
+    analysis_feed.spawn_watcher(db.clone());
 
     // Create application state
     let app_state = AppState {
         db: db.clone(),
+        storage,
         cache: cache.clone(),
         progress,
         yahoo_client,
         openrouter_client,
         nasdaq_client,
         alert_engine,
+        intraday,
+        analysis_feed,
+        api_timezone: config.api_timezone,
+        backups,
+        raw_archive,
+        signals,
+        notes,
+        ingest_token: config.ingest_token.clone(),
+        maintenance,
+        weekend,
+        indicators: config.indicators,
+        history_retention_days: config.history_retention_days,
     };
 
-    // Build API router with CORS
+    tracing::info!(
+        "🔄 Analysis interval: {}s ({}h)",
+        config.analysis_interval_secs,
+        config.analysis_interval_secs / 3600
+    );
+
+    // Run server
+    serve(&config, app_state).await?;
+
+    // Wait for analysis engine (runs forever)
+    analysis_handle.await?;
+
+    Ok(())
+}
+
+/// Build the API router with CORS and serve it until the process exits.
+async fn serve(config: &Config, app_state: AppState) -> anyhow::Result<()> {
     let app = create_router(app_state).layer(
         CorsLayer::new()
             .allow_origin(Any)
@@ -161,24 +591,13 @@ async fn main() -> anyhow::Result<()> {
             .allow_headers(Any),
     );
 
-    // Start HTTP server
     let addr = format!("{}:{}", config.server_host, config.server_port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
     tracing::info!("🌐 Server listening on http://{}", addr);
     tracing::info!("📡 WebSocket endpoint: ws://{}/ws", addr);
     tracing::info!("📊 API docs: http://{}/", addr);
-    tracing::info!(
-        "🔄 Analysis interval: {}s ({}h)",
-        config.analysis_interval_secs,
-        config.analysis_interval_secs / 3600
-    );
 
-    // Run server
     axum::serve(listener, app).await?;
-
-    // Wait for analysis engine (runs forever)
-    analysis_handle.await?;
-
     Ok(())
 }
@@ -0,0 +1,201 @@
+//! Read-only maintenance mode.
+//!
+//! While on, every mutating request (anything but `GET`/`HEAD`/`OPTIONS`) is
+//! rejected with `503 Service Unavailable`, the analysis engine finishes no
+//! further cycles, the nightly cross-section batch and hourly valuation
+//! snapshots are skipped, and the earnings GETs cache what they fetch
+//! without storing it (`api::persist_earnings`), so the database can be
+//! migrated or backed up without writes racing it. Toggled via
+//! `PUT /api/admin/maintenance` (or `READ_ONLY_MODE=true` at startup, which
+//! also skips the startup seed, repair and theme seeding in `main.rs`) and
+//! reported by `/` and `/health`.
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// The toggle itself.
+pub const TOGGLE_PATH: &str = "/api/admin/maintenance";
+
+/// Non-`GET` routes that keep working in read-only mode: the toggle, manual
+/// backups, and the filter search (a read sent as `POST`).
+const ALLOWED_WHILE_READ_ONLY: [&str; 3] =
+    [TOGGLE_PATH, "/api/admin/backups", "/api/stocks/filter"];
+
+/// How often paused background work checks whether the mode was lifted.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub read_only: bool,
+    /// Operator note shown to clients, e.g. "migrating to v3".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When read-only mode was switched on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Body of `PUT /api/admin/maintenance`.
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceInput {
+    pub read_only: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Process-wide switch shared by the router and the background tasks.
+#[derive(Clone, Default)]
+pub struct MaintenanceMode {
+    status: Arc<RwLock<MaintenanceStatus>>,
+}
+
+impl MaintenanceMode {
+    pub fn new(read_only: bool) -> Self {
+        let mode = Self::default();
+        if read_only {
+            mode.set(true, Some("READ_ONLY_MODE".to_string()));
+        }
+        mode
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        self.status.read().expect("maintenance lock").clone()
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.status.read().expect("maintenance lock").read_only
+    }
+
+    /// Switch the mode. `since` is kept when read-only mode is already on
+    /// and only the reason changes.
+    pub fn set(&self, read_only: bool, reason: Option<String>) -> MaintenanceStatus {
+        let mut status = self.status.write().expect("maintenance lock");
+        *status = if read_only {
+            MaintenanceStatus {
+                read_only,
+                reason: reason.filter(|r| !r.trim().is_empty()),
+                since: status
+                    .since
+                    .filter(|_| status.read_only)
+                    .or(Some(Utc::now())),
+            }
+        } else {
+            MaintenanceStatus::default()
+        };
+        status.clone()
+    }
+
+    /// Resolve once read-only mode is off.
+    pub async fn wait_until_writable(&self) {
+        while self.is_read_only() {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Whether a request may run in read-only mode.
+fn allowed(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || ALLOWED_WHILE_READ_ONLY.contains(&path)
+}
+
+/// Middleware: reject mutations with 503 while read-only mode is on.
+pub async fn reject_mutations(
+    State(mode): State<MaintenanceMode>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if allowed(req.method(), req.uri().path()) {
+        return next.run(req).await;
+    }
+    let status = mode.status();
+    if !status.read_only {
+        return next.run(req).await;
+    }
+    let error = match &status.reason {
+        Some(reason) => format!("the API is in read-only mode: {}", reason),
+        None => "the API is in read-only mode".to_string(),
+    };
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, "60")],
+        Json(json!({ "success": false, "error": error, "maintenance": status })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn toggling_keeps_since_until_switched_off() {
+        let mode = MaintenanceMode::new(false);
+        assert_eq!(mode.status(), MaintenanceStatus::default());
+
+        let on = mode.set(true, Some("migration".into()));
+        assert!(on.read_only && mode.is_read_only());
+        let again = mode.set(true, Some("  ".into()));
+        assert_eq!((again.since, again.reason), (on.since, None));
+
+        assert_eq!(
+            mode.set(false, Some("ignored".into())),
+            MaintenanceStatus::default()
+        );
+        assert!(MaintenanceMode::new(true).is_read_only());
+    }
+
+    #[tokio::test]
+    async fn rejects_only_mutations_while_read_only() {
+        let mode = MaintenanceMode::new(false);
+        let ok = || async { "ok" };
+        let app = Router::new()
+            .route("/api/watchlists", get(ok).post(ok))
+            .route("/api/stocks/filter", axum::routing::post(ok))
+            .route(TOGGLE_PATH, axum::routing::put(ok))
+            .layer(axum::middleware::from_fn_with_state(
+                mode.clone(),
+                reject_mutations,
+            ));
+        let status = |method: Method, path: &'static str| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .body(Body::empty());
+                app.oneshot(req.unwrap()).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(
+            status(Method::POST, "/api/watchlists").await,
+            StatusCode::OK
+        );
+
+        mode.set(true, None);
+        assert_eq!(status(Method::GET, "/api/watchlists").await, StatusCode::OK);
+        assert_eq!(
+            status(Method::POST, "/api/watchlists").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status(Method::POST, "/api/stocks/filter").await,
+            StatusCode::OK
+        );
+        assert_eq!(status(Method::PUT, TOGGLE_PATH).await, StatusCode::OK);
+    }
+}
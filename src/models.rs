@@ -1,6 +1,13 @@
 use chrono::{DateTime, Utc};
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::asset_types::AssetType;
+use crate::indicators::IndicatorConfig;
+use crate::ingest::ExternalSignal;
+use crate::percentiles::PercentileRanks;
+use crate::units;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Stock {
@@ -9,9 +16,16 @@ pub struct Stock {
     pub symbol: String,
     pub name: String,
     pub price: f64,
-    pub market_cap: Option<f64>,
-    pub volume: Option<f64>,
+    /// Dollars.
+    #[serde(default, deserialize_with = "units::opt_u64")]
+    pub market_cap: Option<u64>,
+    /// Shares.
+    #[serde(default, deserialize_with = "units::opt_u64")]
+    pub volume: Option<u64>,
     pub sector: Option<String>,
+    /// See `asset_types.rs`; older documents read as equity.
+    #[serde(default)]
+    pub asset_type: AssetType,
     pub last_updated: DateTime<Utc>,
 }
 
@@ -26,46 +40,245 @@ pub struct StockAnalysis {
     pub rsi: Option<f64>,
     pub sma_20: Option<f64>,
     pub sma_50: Option<f64>,
+    /// 200-day SMA, whatever the configured periods. Missing on analyses
+    /// saved before it was stored.
+    #[serde(default)]
+    pub sma_200: Option<f64>,
     pub macd: Option<MACDIndicator>,
-    pub volume: Option<f64>,
-    pub market_cap: Option<f64>,
+    /// Shares traded in the latest session. Older documents stored a float;
+    /// see `units.rs`.
+    #[serde(default, deserialize_with = "units::opt_u64")]
+    pub volume: Option<u64>,
+    /// Dollars.
+    #[serde(default, deserialize_with = "units::opt_u64")]
+    pub market_cap: Option<u64>,
     pub sector: Option<String>,
     pub is_oversold: bool,
     pub is_overbought: bool,
+    /// Stochastic %K below 20 / above 80. Missing on analyses saved before
+    /// the flags existed.
+    #[serde(default)]
+    pub stoch_oversold: bool,
+    #[serde(default)]
+    pub stoch_overbought: bool,
     pub analyzed_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bollinger: Option<BollingerBands>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stochastic: Option<StochasticOscillator>,
+    /// Williams %R(14), 0 to -100; below -80 is oversold, above -20 overbought.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub williams_r: Option<f64>,
+    /// Commodity Channel Index(20); beyond ±100 is stretched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cci: Option<f64>,
+    /// Money Flow Index(14), 0-100; below 20 is oversold, above 80 overbought.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfi: Option<f64>,
+    /// Volume-weighted average price over the fetched window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vwap: Option<f64>,
+    /// Latest on-balance volume and its 20-day slope.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obv: Option<OnBalanceVolume>,
+    /// Latest SMA 50 / SMA 200 crossing within the fetched window, and the
+    /// session it happened on.
+    #[serde(default)]
+    pub cross_signal: CrossSignal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cross_date: Option<DateTime<Utc>>,
+    /// SuperTrend(10, 3) at the latest bar, and the session its current
+    /// direction began on when that is within the fetched window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supertrend: Option<SuperTrend>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supertrend_flip_date: Option<DateTime<Utc>>,
+    /// Nearest swing-pivot levels below and above the price.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_resistance: Option<SupportResistance>,
+    /// Periods `rsi`, `sma_20`, `sma_50` and `macd` were computed with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indicator_periods: Option<IndicatorConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub earnings: Option<EarningsData>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub technicals: Option<NasdaqTechnicals>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub news: Option<Vec<NasdaqNewsItem>>,
+    /// Trailing returns versus the sector's proxy ETF (see `sectors.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector_relative: Option<SectorRelative>,
+    /// Trailing 1-week / 1-month / 3-month / YTD returns (see
+    /// `analytics::performance_returns`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub performance: Option<PerformanceReturns>,
+    /// Annualised Sharpe / Sortino ratio of daily returns over the fetched
+    /// window, net of `RISK_FREE_RATE_PCT` (see `analytics::risk_ratios`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sharpe_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sortino_ratio: Option<f64>,
+    /// Beta, benchmark correlation and RS rank from the nightly batch (see
+    /// `cross_section.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_section: Option<CrossSectionStats>,
+    /// 1-99 ranks against the whole universe as of the last completed cycle
+    /// (see `percentiles.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentiles: Option<PercentileRanks>,
+    /// Signals pushed to `/api/ingest/signal` within the inbox TTL, newest
+    /// first (see `ingest.rs`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_signals: Vec<ExternalSignal>,
+    /// Optional pipeline stages that failed or timed out for this analysis,
+    /// e.g. `"news: timed out after 20s"`. The other fields are still valid.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Ids of the major indexes this symbol belongs to (see `indexes.rs`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<String>,
+    /// User notes and tags for this symbol (see `notes.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// ETF, warrant, right, unit or SPAC rather than common stock (see
+    /// `asset_types.rs`); older documents read as equity.
+    #[serde(default)]
+    pub asset_type: AssetType,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MACDIndicator {
-    pub macd_line: f64,
-    pub signal_line: f64,
-    pub histogram: f64,
+#[cfg(test)]
+impl StockAnalysis {
+    /// Test fixture: `symbol` at `price`, analyzed now, with nothing else set.
+    pub(crate) fn sample(symbol: &str, price: f64) -> Self {
+        StockAnalysis {
+            id: None,
+            symbol: symbol.to_string(),
+            price,
+            price_change: None,
+            price_change_percent: None,
+            rsi: None,
+            sma_20: None,
+            sma_50: None,
+            sma_200: None,
+            macd: None,
+            volume: None,
+            market_cap: None,
+            sector: None,
+            is_oversold: false,
+            is_overbought: false,
+            stoch_oversold: false,
+            stoch_overbought: false,
+            analyzed_at: Utc::now(),
+            bollinger: None,
+            stochastic: None,
+            williams_r: None,
+            cci: None,
+            mfi: None,
+            vwap: None,
+            obv: None,
+            cross_signal: Default::default(),
+            cross_date: None,
+            supertrend: None,
+            supertrend_flip_date: None,
+            support_resistance: None,
+            indicator_periods: None,
+            earnings: None,
+            technicals: None,
+            news: None,
+            sector_relative: None,
+            performance: None,
+            sharpe_ratio: None,
+            sortino_ratio: None,
+            cross_section: None,
+            percentiles: None,
+            external_signals: Vec::new(),
+            warnings: Vec::new(),
+            indexes: Vec::new(),
+            notes: None,
+            tags: Vec::new(),
+            asset_type: Default::default(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BollingerBands {
-    pub upper_band: f64,
-    pub lower_band: f64,
-    pub middle_band: f64,
-    pub bandwidth: f64,
+/// Stock return minus its sector ETF's return, in percentage points.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SectorRelative {
+    pub etf: String,
+    pub relative_1d_pct: Option<f64>,
+    pub relative_5d_pct: Option<f64>,
+    pub relative_20d_pct: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StochasticOscillator {
-    pub k_line: f64,
-    pub d_line: f64,
+/// Close-to-close returns in %, `None` when the history is too short.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceReturns {
+    pub return_1w_pct: Option<f64>,
+    pub return_1m_pct: Option<f64>,
+    pub return_3m_pct: Option<f64>,
+    /// Since the last close of the previous calendar year.
+    pub return_ytd_pct: Option<f64>,
 }
 
+/// Annualised risk-adjusted returns over the fetched window (see
+/// `analytics::risk_ratios`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskRatios {
+    pub sharpe_ratio: Option<f64>,
+    pub sortino_ratio: Option<f64>,
+}
+
+/// Cross-sectional statistics over the last year of daily closes, computed
+/// once a day for the whole universe.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CrossSectionStats {
+    pub symbol: String,
+    /// Beta of daily returns against the benchmark.
+    pub beta: Option<f64>,
+    /// Correlation of daily returns with the benchmark.
+    pub correlation: Option<f64>,
+    /// 1-99 percentile of the weighted 3/6/9/12-month return across the
+    /// universe; 99 is the strongest.
+    pub rs_rank: Option<u8>,
+    pub benchmark: String,
+    /// Date of the last close used (`YYYY-MM-DD`).
+    pub as_of: String,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Average return of one calendar month across the years of stored closes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MonthlySeasonality {
+    /// 1 = January.
+    pub month: u32,
+    pub avg_return_pct: f64,
+    /// Share of years the month closed higher, 0-100.
+    pub positive_pct: f64,
+    /// Years the month was observed in.
+    pub years: u32,
+}
+
+/// Month-of-year return profile from multi-year closes, recomputed by the
+/// weekend deep-analysis run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Seasonality {
+    pub symbol: String,
+    /// Observed months only, January first.
+    pub months: Vec<MonthlySeasonality>,
+    /// First and last close used (`YYYY-MM-DD`).
+    pub from: String,
+    pub to: String,
+    pub computed_at: DateTime<Utc>,
+}
+
+// Indicator results are defined next to the math in `auto-analyser-indicators`.
+pub use auto_analyser_indicators::{
+    BollingerBands, CrossSignal, MACDIndicator, OnBalanceVolume, StochasticOscillator, SuperTrend,
+    SupportResistance,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EarningsData {
     pub earnings_date: Option<DateTime<Utc>>,
@@ -90,10 +303,37 @@ pub struct SectorPerformance {
     pub stock_count: u32,
     pub avg_change_percent: f64,
     pub avg_rsi: f64,
+    /// Sum of the sector's market caps, in dollars.
+    #[serde(default)]
+    pub total_market_cap: f64,
+    pub top_gainer: Option<SectorMover>,
+    pub top_loser: Option<SectorMover>,
     pub top_performers: Vec<StockAnalysis>,
     pub bottom_performers: Vec<StockAnalysis>,
 }
 
+/// One `analysis_history` snapshot, as charted by
+/// `/api/stocks/:symbol/analysis-history`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisHistoryPoint {
+    pub analyzed_at: DateTime<Utc>,
+    pub price: f64,
+    pub price_change_percent: Option<f64>,
+    pub rsi: Option<f64>,
+    pub sma_20: Option<f64>,
+    pub sma_50: Option<f64>,
+    pub macd: Option<MACDIndicator>,
+    #[serde(default, deserialize_with = "units::opt_u64")]
+    pub volume: Option<u64>,
+}
+
+/// A sector's biggest mover on the day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorMover {
+    pub symbol: String,
+    pub price_change_percent: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedNewsItem {
     pub symbol: String,
@@ -115,37 +355,171 @@ pub struct HistoricalPrice {
     pub volume: f64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Latest trade from Yahoo's batch quote endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LiveQuote {
+    pub symbol: String,
+    pub price: f64,
+    /// Cumulative session volume so far.
+    pub day_volume: Option<f64>,
+    pub quoted_at: DateTime<Utc>,
+}
+
+/// OHLCV bar synthesized from successive `LiveQuote`s.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IntradayCandle {
+    pub symbol: String,
+    pub start: DateTime<Utc>,
+    pub interval_secs: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Approximate: growth of the session volume since the candle opened.
+    pub volume: f64,
+}
+
+/// Cash dividend per share, dated by its ex-dividend session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DividendEvent {
+    pub date: DateTime<Utc>,
+    pub amount: f64,
+}
+
+/// Stock split of `numerator`-for-`denominator` shares, dated by its first
+/// session on the new basis.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SplitEvent {
+    pub date: DateTime<Utc>,
+    pub numerator: f64,
+    pub denominator: f64,
+}
+
+impl SplitEvent {
+    /// New shares per old share, e.g. `4.0` for a 4-for-1 split.
+    pub fn ratio(&self) -> Option<f64> {
+        let ratio = self.numerator / self.denominator;
+        (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+    }
+}
+
+/// Body of `POST /api/stocks/filter`. Out-of-range values are rejected with
+/// a 422 (see `validation.rs`) rather than clamped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
 pub struct StockFilter {
+    #[validate(range(min = 0.0))]
     pub min_price: Option<f64>,
+    #[validate(range(min = 0.0))]
     pub max_price: Option<f64>,
+    #[validate(range(min = 0.0))]
     pub min_volume: Option<f64>,
+    #[validate(range(min = 0.0))]
     pub min_market_cap: Option<f64>,
+    #[validate(range(min = 0.0))]
     pub max_market_cap: Option<f64>,
+    #[validate(range(min = 0.0, max = 100.0))]
     pub min_rsi: Option<f64>,
+    #[validate(range(min = 0.0, max = 100.0))]
     pub max_rsi: Option<f64>,
     pub sectors: Option<Vec<String>>,
     pub only_oversold: Option<bool>,
     pub only_overbought: Option<bool>,
+    /// Only stochastic-oversold (%K < 20) / -overbought (%K > 80) rows.
+    pub only_stoch_oversold: Option<bool>,
+    pub only_stoch_overbought: Option<bool>,
+    /// Only rows whose latest SMA 50 / SMA 200 cross is this one.
+    pub cross_signal: Option<CrossSignal>,
+    /// `true` for rows in a SuperTrend uptrend, `false` for downtrends.
+    pub supertrend_uptrend: Option<bool>,
     /// Case-insensitive substring match on `symbol`. Lets the UI search the
     /// entire universe instead of just the rows already on the current page.
     pub symbol_search: Option<String>,
     // Stochastic / Bollinger filters
+    #[validate(range(min = 0.0, max = 100.0))]
     pub min_stochastic_k: Option<f64>,
+    #[validate(range(min = 0.0, max = 100.0))]
     pub max_stochastic_k: Option<f64>,
+    // Williams %R / CCI / MFI filters, so screens can ask several
+    // oscillators to agree
+    #[validate(range(min = -100.0, max = 0.0))]
+    pub min_williams_r: Option<f64>,
+    #[validate(range(min = -100.0, max = 0.0))]
+    pub max_williams_r: Option<f64>,
+    pub min_cci: Option<f64>,
+    pub max_cci: Option<f64>,
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub min_mfi: Option<f64>,
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub max_mfi: Option<f64>,
+    #[validate(range(min = 0.0))]
     pub min_bandwidth: Option<f64>,
+    #[validate(range(min = 0.0))]
     pub max_bandwidth: Option<f64>,
     /// Drop rows whose `|price_change_percent|` exceeds this threshold.
     /// Keeps runaway day-gainers out of the feed.
+    #[validate(range(min = 0.0))]
     pub max_abs_price_change_percent: Option<f64>,
+    /// Hide secondary share classes (GOOG when GOOGL is listed, FOX, ...).
+    pub primary_class_only: Option<bool>,
+    /// Only members of this index (`sp500`, `nasdaq100`, `dow30`,
+    /// `russell2000`), matched against `StockAnalysis::indexes`.
+    pub index: Option<String>,
+    /// Only members of this theme (see `themes.rs`). Unknown themes are an
+    /// error rather than an empty result.
+    pub theme: Option<String>,
+    /// Only symbols carrying at least one of these tags (see `notes.rs`).
+    pub tags: Option<Vec<String>>,
+    /// Comma-separated asset types (`equity`, `etf`, `warrant`, `right`,
+    /// `unit`, `spac`) or `all`; unset keeps to equities (see
+    /// `asset_types.rs`).
+    pub asset_type: Option<String>,
+    /// Which return `min_return_pct` / `max_return_pct` apply to: `1w`,
+    /// `1m` (default), `3m` or `ytd`.
+    pub return_period: Option<String>,
+    pub min_return_pct: Option<f64>,
+    pub max_return_pct: Option<f64>,
+    /// Which rank `min_percentile` / `max_percentile` apply to: `rsi`,
+    /// `price_change_percent`, `volume_ratio`, `pe_ratio`, `return_1m_pct`,
+    /// `return_3m_pct` or `rs_rank`. Required with either bound.
+    pub percentile_metric: Option<String>,
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub min_percentile: Option<f64>,
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub max_percentile: Option<f64>,
+    /// Minimum RS rating (`percentiles.rs_rank`, 1-99).
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub min_rs_rank: Option<f64>,
+    /// Screen what the analyser knew at this time: each symbol's latest
+    /// `analysis_history` snapshot at or before it, instead of the live
+    /// analyses.
+    pub as_of: Option<DateTime<Utc>>,
     // Sorting options
-    pub sort_by: Option<String>, // "market_cap", "price_change_percent", "rsi", "price"
+    pub sort_by: Option<String>, // "market_cap", "price_change_percent", "rsi", "price", "sharpe_ratio", ...
     pub sort_order: Option<String>, // "asc" or "desc"
     // Pagination
+    #[validate(range(min = 1))]
     pub page: Option<u32>,
+    #[validate(range(min = 1, max = 200))]
     pub page_size: Option<u32>,
 }
 
+/// A stock-cache pin, persisted so pins survive restarts. `ttl_secs: None`
+/// keeps the symbol cached until unpinned; `Some(n)` overrides the default
+/// stock TTL for that symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachePin {
+    pub symbol: String,
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    pub pinned_at: DateTime<Utc>,
+}
+
+impl CachePin {
+    pub fn ttl(&self) -> Option<std::time::Duration> {
+        self.ttl_secs.map(std::time::Duration::from_secs)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketSummary {
     pub total_stocks: usize,
@@ -154,9 +528,26 @@ pub struct MarketSummary {
     pub most_oversold: Vec<StockAnalysis>,
     pub most_overbought: Vec<StockAnalysis>,
     pub mega_cap_highlights: Vec<StockAnalysis>, // >$200B
+    /// Leaders by trailing return rather than daily change.
+    #[serde(default)]
+    pub top_weekly_gainers: Vec<StockAnalysis>,
+    #[serde(default)]
+    pub top_monthly_gainers: Vec<StockAnalysis>,
+    #[serde(default)]
+    pub top_ytd_gainers: Vec<StockAnalysis>,
     pub generated_at: DateTime<Utc>,
 }
 
+/// Whether the engine is running the full pipeline or the price-only
+/// fallback it switches to when upstreams are failing (see `degradation.rs`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineMode {
+    #[default]
+    Normal,
+    Degraded,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct AnalysisProgress {
     pub total_stocks: usize,
@@ -168,6 +559,91 @@ pub struct AnalysisProgress {
     pub last_cycle_completed: Option<DateTime<Utc>>,
     pub last_successful_cycle: Option<DateTime<Utc>>,
     pub last_error: Option<String>,
+    pub mode: EngineMode,
+    /// Why the engine is degraded; `None` in normal mode.
+    pub degraded_reason: Option<String>,
+    /// Symbols saved this cycle, in order. Not part of the snapshot; sent
+    /// to differential socket clients as they arrive.
+    #[serde(skip)]
+    pub saved_symbols: Vec<String>,
+}
+
+/// A ticker that was renamed; lookups of `old_symbol` resolve to
+/// `new_symbol`. Persisted in `symbol_aliases`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolAlias {
+    pub old_symbol: String,
+    pub new_symbol: String,
+    /// Normalized company name the two listings were matched on.
+    pub company_name: String,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// A `/api/search` hit from the screener listings in `stocks`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolMatch {
+    pub symbol: String,
+    /// Company name as the screener lists it.
+    pub name: String,
+    pub asset_type: AssetType,
+    /// Last sale at the most recent screener fetch.
+    pub price: f64,
+    /// Dollars.
+    pub market_cap: Option<u64>,
+}
+
+/// Normalized company name last seen for a symbol in the screener universe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseName {
+    pub symbol: String,
+    pub name: String,
+}
+
+/// Where a symbol stands in the current analysis cycle.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolCycleStatus {
+    /// Queued for fetching this cycle.
+    Pending,
+    Done,
+    Failed,
+    /// Not analyzed this cycle: still fresh, or its circuit breaker is open.
+    Skipped,
+    /// Excluded until requeued from the dead-letter queue.
+    DeadLettered,
+}
+
+/// One failed analysis attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureRecord {
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Failure streak for a symbol, persisted in `dead_letters`. Once
+/// `consecutive_failures` reaches `DEAD_LETTER_THRESHOLD` the symbol is
+/// dead-lettered and left out of cycles until requeued. A success before
+/// that clears the streak.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub symbol: String,
+    pub consecutive_failures: u32,
+    /// Most recent failures, oldest first.
+    #[serde(default)]
+    pub errors: Vec<FailureRecord>,
+    #[serde(default)]
+    pub dead_lettered_at: Option<DateTime<Utc>>,
+}
+
+/// Per-symbol state of the current cycle, persisted in `cycle_symbols`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolProgress {
+    pub symbol: String,
+    pub status: SymbolCycleStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub cycle_start: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 // NASDAQ Technicals (from /api/quote/{symbol}/info endpoint)
@@ -195,6 +671,11 @@ pub struct NasdaqTechnicals {
     pub last_sale_price: Option<f64>,
     pub net_change: Option<f64>,
     pub percentage_change: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company_name: Option<String>,
+    /// NASDAQ's asset class (`STOCKS`, `ETF`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_class: Option<String>,
 }
 
 // NASDAQ News Item
@@ -279,30 +760,6 @@ pub struct AIAnalysisResponse {
     pub generated_at: DateTime<Utc>,
 }
 
-// NASDAQ API response structures
-#[derive(Debug, Clone, Deserialize)]
-pub struct NasdaqResponse {
-    pub data: NasdaqData,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct NasdaqData {
-    pub table: NasdaqTable,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct NasdaqTable {
-    pub rows: Vec<NasdaqStock>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct NasdaqStock {
-    pub symbol: String,
-    pub name: String,
-    #[serde(rename = "marketCap")]
-    pub market_cap: String,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,9 +771,10 @@ mod tests {
             symbol: "AAPL".to_string(),
             name: "Apple Inc.".to_string(),
             price: 150.0,
-            market_cap: Some(2_500_000_000_000.0),
-            volume: Some(50_000_000.0),
+            market_cap: Some(2_500_000_000_000),
+            volume: Some(50_000_000),
             sector: Some("Technology".to_string()),
+            asset_type: AssetType::Equity,
             last_updated: Utc::now(),
         };
 
@@ -332,9 +790,6 @@ mod tests {
     #[test]
     fn test_stock_analysis_serialization() {
         let analysis = StockAnalysis {
-            id: None,
-            symbol: "MSFT".to_string(),
-            price: 350.0,
             price_change: Some(5.0),
             price_change_percent: Some(1.45),
             rsi: Some(65.5),
@@ -345,17 +800,10 @@ mod tests {
                 signal_line: 1.2,
                 histogram: 0.3,
             }),
-            volume: Some(25_000_000.0),
-            market_cap: Some(2_600_000_000_000.0),
+            volume: Some(25_000_000),
+            market_cap: Some(2_600_000_000_000),
             sector: Some("Technology".to_string()),
-            is_oversold: false,
-            is_overbought: false,
-            analyzed_at: Utc::now(),
-            bollinger: None,
-            stochastic: None,
-            earnings: None,
-            technicals: None,
-            news: None,
+            ..StockAnalysis::sample("MSFT", 350.0)
         };
 
         let json = serde_json::to_string(&analysis).unwrap();
@@ -430,37 +878,36 @@ mod tests {
             last_cycle_completed: None,
             last_successful_cycle: None,
             last_error: None,
+            mode: EngineMode::Degraded,
+            degraded_reason: Some("yahoo error rate 80% over last 50 calls".to_string()),
+            saved_symbols: vec!["MSFT".to_string()],
         };
 
         let json = serde_json::to_string(&progress).unwrap();
         assert!(json.contains("60"));
         assert!(json.contains("30"));
         assert!(json.contains("AAPL"));
+        assert!(json.contains(r#""mode":"degraded""#));
+    }
+
+    #[test]
+    fn dead_letter_defaults_missing_history() {
+        let entry: DeadLetter =
+            serde_json::from_str(r#"{"symbol":"XYZW","consecutive_failures":2}"#).unwrap();
+        assert!(entry.errors.is_empty());
+        assert!(entry.dead_lettered_at.is_none());
+        assert_eq!(
+            serde_json::to_string(&SymbolCycleStatus::DeadLettered).unwrap(),
+            r#""dead_lettered""#
+        );
     }
 
     #[test]
     fn test_oversold_flag() {
         let mut analysis = StockAnalysis {
-            id: None,
-            symbol: "TEST".to_string(),
-            price: 100.0,
-            price_change: None,
-            price_change_percent: None,
             rsi: Some(25.0),
-            sma_20: None,
-            sma_50: None,
-            macd: None,
-            volume: None,
-            market_cap: None,
-            sector: None,
             is_oversold: true,
-            is_overbought: false,
-            analyzed_at: Utc::now(),
-            bollinger: None,
-            stochastic: None,
-            earnings: None,
-            technicals: None,
-            news: None,
+            ..StockAnalysis::sample("TEST", 100.0)
         };
 
         assert!(analysis.is_oversold);
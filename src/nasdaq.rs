@@ -1,6 +1,10 @@
+use crate::archive::{RawArchive, Source};
 use crate::models::{InsiderTrade, NasdaqNewsItem, NasdaqTechnicals};
 use anyhow::{anyhow, Result};
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde::Deserialize;
+use std::borrow::Cow;
+use std::fmt;
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, warn};
@@ -10,6 +14,7 @@ use tracing::{debug, warn};
 pub struct NasdaqClient {
     client: reqwest::Client,
     delay_ms: u64,
+    archive: Option<RawArchive>,
 }
 
 // Response structures for NASDAQ API
@@ -23,6 +28,10 @@ struct NasdaqTechnicalsResponse {
 #[derive(Debug, Deserialize)]
 struct NasdaqTechnicalsData {
     symbol: Option<String>,
+    #[serde(rename = "companyName")]
+    company_name: Option<String>,
+    #[serde(rename = "assetClass")]
+    asset_class: Option<String>,
     #[serde(rename = "primaryData")]
     primary_data: Option<PrimaryData>,
     #[serde(rename = "summaryData")]
@@ -176,7 +185,23 @@ impl NasdaqClient {
             .build()
             .expect("Failed to create NASDAQ HTTP client");
 
-        NasdaqClient { client, delay_ms }
+        NasdaqClient {
+            client,
+            delay_ms,
+            archive: None,
+        }
+    }
+
+    /// Keep raw response bodies in `archive` (see `archive.rs`).
+    pub fn with_archive(mut self, archive: RawArchive) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    fn archive(&self, kind: &str, symbol: &str, url: &str, body: &str) {
+        if let Some(archive) = &self.archive {
+            archive.record(Source::Nasdaq, kind, symbol, url, body.as_bytes());
+        }
     }
 
     /// Fetch technical indicators for a stock from NASDAQ API
@@ -211,6 +236,7 @@ impl NasdaqClient {
                 e
             )
         })?;
+        self.archive("technicals", symbol, &url, &text);
 
         parse_technicals_response(&text, symbol)
     }
@@ -242,6 +268,7 @@ impl NasdaqClient {
             .text()
             .await
             .map_err(|e| anyhow!("Failed to read NASDAQ news body for {}: {}", symbol, e))?;
+        self.archive("news", symbol, &url, &text);
 
         parse_news_response(&text, symbol)
     }
@@ -278,6 +305,7 @@ impl NasdaqClient {
             .text()
             .await
             .map_err(|e| anyhow!("Failed to read NASDAQ insider body for {}: {}", symbol, e))?;
+        self.archive("insider", symbol, &url, &text);
 
         parse_insider_trades_response(&text, symbol)
     }
@@ -300,8 +328,8 @@ impl NasdaqClient {
                 let cleaned = v.replace('$', "");
                 let parts: Vec<&str> = cleaned.split('/').collect();
                 if parts.len() == 2 {
-                    let high = parts[0].trim().parse::<f64>().ok();
-                    let low = parts[1].trim().parse::<f64>().ok();
+                    let high = parse_finite(parts[0]);
+                    let low = parse_finite(parts[1]);
                     return (high, low);
                 }
             }
@@ -310,42 +338,44 @@ impl NasdaqClient {
     }
 
     fn parse_dollar_value(value: &Option<String>) -> Option<f64> {
-        value.as_ref().and_then(|v| {
-            v.replace('$', "")
-                .replace(',', "")
-                .trim()
-                .parse::<f64>()
-                .ok()
-        })
+        value
+            .as_ref()
+            .and_then(|v| parse_finite(&v.replace('$', "").replace(',', "")))
     }
 
     fn parse_signed_number(value: &Option<String>) -> Option<f64> {
         value
             .as_ref()
-            .and_then(|v| v.replace(',', "").trim().parse::<f64>().ok())
+            .and_then(|v| parse_finite(&v.replace(',', "")))
     }
 
     fn parse_number_with_commas(value: &Option<String>) -> Option<f64> {
         value
             .as_ref()
-            .and_then(|v| v.replace(',', "").trim().parse::<f64>().ok())
+            .and_then(|v| parse_finite(&v.replace(',', "")))
     }
 
     fn parse_percentage(value: &Option<String>) -> Option<f64> {
         value
             .as_ref()
-            .and_then(|v| v.replace('%', "").trim().parse::<f64>().ok())
+            .and_then(|v| parse_finite(&v.replace('%', "")))
     }
 
     fn parse_json_number(value: &Option<serde_json::Value>) -> Option<f64> {
         value.as_ref().and_then(|v| match v {
             serde_json::Value::Number(n) => n.as_f64(),
-            serde_json::Value::String(s) => s.parse::<f64>().ok(),
+            serde_json::Value::String(s) => parse_finite(s),
             _ => None,
         })
     }
 }
 
+/// Rust's float parser accepts "inf" and "NaN", which NASDAQ placeholders
+/// can spell; those are treated as missing like any other junk.
+fn parse_finite(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
 // ============================================================================
 // Pure parsing functions (offline-testable)
 // ============================================================================
@@ -436,6 +466,8 @@ pub(crate) fn parse_technicals_response(text: &str, symbol: &str) -> Result<Nasd
         last_sale_price,
         net_change,
         percentage_change,
+        company_name: data.company_name,
+        asset_class: data.asset_class,
     })
 }
 
@@ -463,6 +495,100 @@ pub(crate) fn parse_news_response(text: &str, symbol: &str) -> Result<Vec<Nasdaq
         .collect())
 }
 
+/// One `/api/screener/stocks` row. Fields borrow from the response body
+/// unless the JSON string has escapes.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ScreenerRow<'a> {
+    #[serde(borrow)]
+    pub symbol: Cow<'a, str>,
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(borrow, default, rename = "lastsale")]
+    pub last_sale: Cow<'a, str>,
+    #[serde(borrow, rename = "marketCap")]
+    pub market_cap: Cow<'a, str>,
+}
+
+/// Walk a screener response (`data.table.rows`) and hand each row to
+/// `on_row` as soon as it is parsed, so the ~7000 rows are never collected
+/// or copied into owned strings. Returns the number of rows.
+pub(crate) fn parse_screener_rows<'a>(
+    body: &'a [u8],
+    on_row: impl FnMut(ScreenerRow<'a>),
+) -> Result<usize> {
+    let seed = Field("data", Field("table", Field("rows", Rows(on_row))));
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let count = seed
+        .deserialize(&mut deserializer)
+        .and_then(|count| deserializer.end().map(|_| count))
+        .map_err(|e| anyhow!("Failed to parse NASDAQ screener: {}", e))?;
+    Ok(count)
+}
+
+/// Deserializes one field of a JSON object with the inner seed and skips
+/// the rest.
+struct Field<S>(&'static str, S);
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Field<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> Visitor<'de> for Field<S> {
+    type Value = S::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an object with `{}`", self.0)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<S::Value, A::Error> {
+        let Field(name, seed) = self;
+        let mut seed = Some(seed);
+        let mut value = None;
+        while let Some(key) = map.next_key::<Cow<'de, str>>()? {
+            match seed.take() {
+                Some(inner) if key == name => value = Some(map.next_value_seed(inner)?),
+                other => {
+                    seed = other;
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        value.ok_or_else(|| de::Error::missing_field(name))
+    }
+}
+
+/// Feeds each element of a JSON array of rows to the callback, counting them.
+struct Rows<F>(F);
+
+impl<'de, F: FnMut(ScreenerRow<'de>)> DeserializeSeed<'de> for Rows<F> {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(ScreenerRow<'de>)> Visitor<'de> for Rows<F> {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of screener rows")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<usize, A::Error> {
+        let mut count = 0;
+        while let Some(row) = seq.next_element::<ScreenerRow<'de>>()? {
+            (self.0)(row);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
 /// Parse a NASDAQ insider trades response.
 pub(crate) fn parse_insider_trades_response(text: &str, symbol: &str) -> Result<Vec<InsiderTrade>> {
     let nasdaq_response: InsiderTradesResponse = serde_json::from_str(text).map_err(|e| {
@@ -922,4 +1048,118 @@ mod tests {
             .unwrap()
             .is_empty());
     }
+
+    // ---- Screener ------------------------------------------------------------
+
+    #[test]
+    fn test_parse_screener_rows_borrows_and_skips_other_fields() {
+        let body = br#"{
+            "data": {
+                "asOf": null,
+                "table": {
+                    "headers": {"symbol": "Symbol"},
+                    "rows": [
+                        {"symbol": "AAPL", "name": "Apple Inc.", "marketCap": "3,400,000,000,000", "pctchange": "0.5%"},
+                        {"symbol": "T", "name": "AT\u0026T Inc.", "marketCap": "150,000,000,000"}
+                    ]
+                }
+            },
+            "status": {"rCode": 200}
+        }"#;
+        let mut rows = Vec::new();
+        let count = parse_screener_rows(body, |row| rows.push(row)).unwrap();
+        assert_eq!(count, 2);
+        assert!(matches!(rows[0].symbol, Cow::Borrowed("AAPL")));
+        assert_eq!(rows[0].market_cap, "3,400,000,000,000");
+        // Escaped strings are unescaped into an owned copy.
+        assert!(matches!(&rows[1].name, Cow::Owned(name) if name == "AT&T Inc."));
+    }
+
+    #[test]
+    fn test_parse_screener_rows_rejects_malformed_bodies() {
+        for body in [
+            &br#"{"data": null}"#[..],
+            br#"{"data": {"table": {}}}"#,
+            br#"{"data": {"table": {"rows": [{"symbol": "AAPL"}]}}}"#,
+            br#"{"data": {"table": {"rows": []}}"#,
+        ] {
+            assert!(parse_screener_rows(body, |_| {}).is_err());
+        }
+        assert_eq!(
+            parse_screener_rows(br#"{"data": {"table": {"rows": []}}}"#, |_| {}).unwrap(),
+            0
+        );
+    }
+
+    // ---- Properties over arbitrary upstream strings ---------------------------
+
+    /// Thousands separators, as NASDAQ prints them ("1,234,567.89").
+    fn with_commas(value: f64) -> String {
+        let fixed = format!("{:.2}", value);
+        let (int, frac) = fixed.split_once('.').unwrap();
+        let digits: Vec<char> = int.chars().collect();
+        let mut grouped = String::new();
+        for (i, c) in digits.iter().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(*c);
+        }
+        format!("{}.{}", grouped, frac)
+    }
+
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn prop_parsers_never_yield_non_finite_values(raw in any::<String>()) {
+            let value = Some(raw.clone());
+            for parsed in [
+                NasdaqClient::parse_dollar_value(&value),
+                NasdaqClient::parse_signed_number(&value),
+                NasdaqClient::parse_number_with_commas(&value),
+                NasdaqClient::parse_percentage(&value),
+                NasdaqClient::parse_json_number(&Some(serde_json::json!(raw))),
+            ] {
+                prop_assert!(parsed.is_none_or(f64::is_finite));
+            }
+            let (high, low) = NasdaqClient::parse_high_low(&Some(LabelValue {
+                label: None,
+                value: value.clone(),
+            }));
+            prop_assert!(high.is_none_or(f64::is_finite));
+            prop_assert!(low.is_none_or(f64::is_finite));
+        }
+
+        #[test]
+        fn prop_placeholders_parse_as_missing(
+            junk in "(N/A|NA|--|-|inf|-inf|NaN|infinity)",
+            wrap in "[$%]?",
+        ) {
+            let value = Some(format!("{}{}", wrap, junk));
+            prop_assert_eq!(NasdaqClient::parse_dollar_value(&value), None);
+            prop_assert_eq!(NasdaqClient::parse_percentage(&value), None);
+            prop_assert_eq!(NasdaqClient::parse_number_with_commas(&value), None);
+        }
+
+        #[test]
+        fn prop_formatted_values_round_trip(
+            high in 0.0f64..1e7,
+            low in 0.0f64..1e7,
+            pct in -1000.0f64..1000.0,
+        ) {
+            let (h, l) = NasdaqClient::parse_high_low(&Some(LabelValue {
+                label: None,
+                value: Some(format!("${:.2}/${:.2}", high, low)),
+            }));
+            prop_assert!((h.unwrap() - high).abs() <= 0.005 + 1e-9);
+            prop_assert!((l.unwrap() - low).abs() <= 0.005 + 1e-9);
+
+            let dollars = NasdaqClient::parse_dollar_value(&Some(format!("${}", with_commas(high))));
+            prop_assert!((dollars.unwrap() - high).abs() <= 0.005 + 1e-9);
+
+            let percent = NasdaqClient::parse_percentage(&Some(format!(" {:.2}% ", pct)));
+            prop_assert!((percent.unwrap() - pct).abs() <= 0.005 + 1e-9);
+        }
+    }
 }
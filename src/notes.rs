@@ -0,0 +1,235 @@
+//! Per-symbol notes and tags.
+//!
+//! Free-form notes and arbitrary tags ("earnings-play", "avoid") are edited
+//! through `PUT /api/stocks/:symbol/notes` and stored one document per
+//! symbol in `symbol_notes`, apart from the analyses the engine rewrites
+//! every cycle. A process-local `SymbolNotes` copy is loaded at startup and
+//! the engine stamps it onto fresh analyses as `StockAnalysis::notes` /
+//! `tags`, so they come back with every analysis and `StockFilter::tags`
+//! can match on them directly.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use validator::{Validate, ValidationError};
+
+use crate::models::StockAnalysis;
+
+const MAX_NOTES_LEN: usize = 2000;
+const MAX_TAGS: usize = 20;
+const MAX_TAG_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SymbolNote {
+    /// Normalized symbol key.
+    pub symbol: String,
+    #[serde(default)]
+    pub notes: String,
+    /// Normalized tags, sorted.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body of `PUT /api/stocks/:symbol/notes`. Replaces both fields; sending
+/// both empty deletes the note.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct NoteInput {
+    #[serde(default)]
+    #[validate(length(max = 2000, message = "must be at most 2000 characters"))]
+    pub notes: String,
+    #[serde(default)]
+    #[validate(
+        length(max = 20, message = "must have at most 20 tags"),
+        custom(function = "valid_tags")
+    )]
+    pub tags: Vec<String>,
+}
+
+/// Tag count for `GET /api/tags`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TagCount {
+    pub tag: String,
+    pub symbols: usize,
+}
+
+/// Tags are lowercase ASCII letters, digits, dashes and underscores; inner
+/// whitespace becomes a dash (`Earnings Play` → `earnings-play`).
+pub fn normalize_tag(input: &str) -> Option<String> {
+    let tag = input
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_ascii_lowercase();
+    let valid = !tag.is_empty()
+        && tag.len() <= MAX_TAG_LEN
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(tag)
+}
+
+/// Normalized, de-duplicated and sorted; invalid tags are dropped.
+pub fn normalize_tags<'a>(tags: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    let tags: BTreeSet<String> = tags.into_iter().filter_map(|t| normalize_tag(t)).collect();
+    tags.into_iter().collect()
+}
+
+fn valid_tags(tags: &[String]) -> Result<(), ValidationError> {
+    match tags.iter().find(|t| normalize_tag(t).is_none()) {
+        Some(bad) => Err(crate::validation::error(
+            "tag",
+            format!(
+                "'{}' is not a valid tag (1-{} letters, digits, dashes or underscores)",
+                bad, MAX_TAG_LEN
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+impl SymbolNote {
+    pub fn from_input(symbol: &str, input: &NoteInput) -> Self {
+        Self {
+            symbol: crate::symbols::normalize_symbol_key(symbol),
+            notes: input.notes.trim().chars().take(MAX_NOTES_LEN).collect(),
+            tags: normalize_tags(input.tags.iter().take(MAX_TAGS)),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// No notes and no tags.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.tags.is_empty()
+    }
+}
+
+/// Notes keyed by symbol, shared by the API (which edits them) and the
+/// analysis engine (which copies them onto fresh analyses).
+#[derive(Clone, Default)]
+pub struct SymbolNotes {
+    notes: Arc<RwLock<HashMap<String, SymbolNote>>>,
+}
+
+impl SymbolNotes {
+    /// Add notes loaded from the database at startup.
+    pub async fn load(&self, notes: Vec<SymbolNote>) {
+        let mut map = self.notes.write().await;
+        for note in notes {
+            map.insert(note.symbol.clone(), note);
+        }
+    }
+
+    pub async fn get(&self, symbol: &str) -> Option<SymbolNote> {
+        self.notes.read().await.get(symbol).cloned()
+    }
+
+    /// Store `note`, or forget the symbol's note when it is empty.
+    pub async fn set(&self, note: SymbolNote) {
+        let mut map = self.notes.write().await;
+        if note.is_empty() {
+            map.remove(&note.symbol);
+        } else {
+            map.insert(note.symbol.clone(), note);
+        }
+    }
+
+    /// Copy the symbol's notes and tags onto `analysis`.
+    pub async fn stamp(&self, analysis: &mut StockAnalysis) {
+        let map = self.notes.read().await;
+        let note = map.get(&analysis.symbol);
+        analysis.notes = note.map(|n| n.notes.clone()).filter(|n| !n.is_empty());
+        analysis.tags = note.map(|n| n.tags.clone()).unwrap_or_default();
+    }
+
+    /// Every tag in use with how many symbols carry it, by tag.
+    pub async fn tag_counts(&self) -> Vec<TagCount> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for note in self.notes.read().await.values() {
+            for tag in &note.tags {
+                *counts.entry(tag.clone()).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(tag, symbols)| TagCount { tag, symbols })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_is_normalized_and_validated() {
+        let input = NoteInput {
+            notes: "  reports Thursday  ".into(),
+            tags: vec![
+                "Earnings Play".into(),
+                "avoid".into(),
+                "earnings-play".into(),
+            ],
+        };
+        assert!(input.validate().is_ok());
+        let note = SymbolNote::from_input("brk.b", &input);
+        assert_eq!(note.symbol, "BRK-B");
+        assert_eq!(note.notes, "reports Thursday");
+        assert_eq!(note.tags, vec!["avoid", "earnings-play"]);
+
+        let bad = NoteInput {
+            notes: String::new(),
+            tags: vec!["ok".into(), "no/slashes".into()],
+        };
+        let errors = crate::validation::field_errors(&bad.validate().unwrap_err());
+        assert_eq!(errors[0].field, "tags");
+        assert!(errors[0].message.contains("no/slashes"));
+        assert!(SymbolNote::from_input("AAPL", &NoteInput::default()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn store_stamps_analyses_and_counts_tags() {
+        let notes = SymbolNotes::default();
+        let note = |symbol: &str, tags: &[&str]| SymbolNote {
+            symbol: symbol.into(),
+            notes: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            updated_at: Utc::now(),
+        };
+        notes
+            .load(vec![
+                note("AAPL", &["earnings-play"]),
+                note("TSLA", &["avoid"]),
+            ])
+            .await;
+        notes
+            .set(SymbolNote {
+                notes: "watch guidance".into(),
+                ..note("NVDA", &["avoid", "earnings-play"])
+            })
+            .await;
+        notes.set(note("TSLA", &[])).await;
+        assert!(notes.get("TSLA").await.is_none());
+        assert_eq!(
+            notes.tag_counts().await,
+            vec![
+                TagCount {
+                    tag: "avoid".into(),
+                    symbols: 1
+                },
+                TagCount {
+                    tag: "earnings-play".into(),
+                    symbols: 2
+                },
+            ]
+        );
+
+        let mut analysis = StockAnalysis::sample("NVDA", 120.0);
+        notes.stamp(&mut analysis).await;
+        assert_eq!(analysis.notes.as_deref(), Some("watch guidance"));
+        assert_eq!(analysis.tags, vec!["avoid", "earnings-play"]);
+    }
+}
@@ -5,27 +5,37 @@
 //! `{ "success": true, ...payload }` on 200 or
 //! `{ "success": false, "error": "..." }` on 4xx/5xx.
 
+use std::collections::HashMap;
+
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Json},
-    routing::{delete, get, patch, post},
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use mongodb::bson::oid::ObjectId;
 use serde::Deserialize;
 use serde_json::json;
 
+use crate::api::pagination::PageQuery;
 use crate::api::AppState;
+use crate::notifications::brokers;
+use crate::notifications::lots;
 use crate::notifications::models::{
-    AddSymbolInput, CreateAlertRuleInput, CreateChannelInput, CreatePositionInput,
-    CreateWatchlistInput, PendingNotification, Position, PositionView, UpdateAlertRuleInput,
-    UpdateChannelInput, UpdatePositionInput, UpdateWatchlistInput,
+    AddSymbolInput, AlertAuditAction, AlertAuditEvent, CreateAlertRuleInput, CreateChannelInput,
+    CreatePositionInput, CreateWatchlistInput, LotMethod, PendingNotification, Position,
+    PositionView, SellPositionInput, SnoozeRuleInput, UpdateAlertRuleInput, UpdateChannelInput,
+    UpdatePositionInput, UpdateWatchlistInput,
 };
+use crate::notifications::rebalance::{self, CostModel, Holding, RebalanceTarget, TradeSide};
+use crate::notifications::recap::{self, WatchlistRecap};
+use crate::notifications::valuation;
+use crate::validation::ValidatedJson;
 
 /// Attach every notifications route to the given router.
 ///
-/// Split out of `api::create_router` so that file doesn't keep growing.
+/// Applied by `api::create_router` after merging the per-area routers.
 pub fn mount(router: Router<AppState>) -> Router<AppState> {
     router
         // Watchlists
@@ -44,6 +54,10 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/api/watchlists/:id/symbols/:symbol",
             delete(remove_watchlist_symbol),
         )
+        .route(
+            "/api/watchlists/:id/recap",
+            get(preview_watchlist_recap).post(send_watchlist_recap),
+        )
         // Rules
         .route("/api/alerts/rules", get(list_rules).post(create_rule))
         .route(
@@ -52,6 +66,16 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         )
         .route("/api/alerts/rules/:id/toggle", post(toggle_rule))
         .route("/api/alerts/rules/:id/test", post(test_rule))
+        .route(
+            "/api/alerts/rules/:id/snooze",
+            post(snooze_rule).delete(unsnooze_rule),
+        )
+        .route(
+            "/api/alerts/rules/:id/mute/:symbol",
+            put(mute_symbol).delete(unmute_symbol),
+        )
+        .route("/api/alerts/rules/:id/reset", post(reset_rule))
+        .route("/api/alerts/rules/:id/audit", get(list_rule_audit))
         // Channels
         .route(
             "/api/alerts/channels",
@@ -68,6 +92,15 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/api/alerts/history/:id/read", patch(mark_history_read))
         // Positions
         .route("/api/positions", get(list_positions).post(create_position))
+        .route("/api/positions/rebalance", get(rebalance_positions))
+        .route("/api/positions/sell", post(sell_position))
+        .route("/api/positions/import", post(import_positions))
+        .route(
+            "/api/positions/history",
+            get(position_history).post(snapshot_positions),
+        )
+        .route("/api/positions/realized", get(realized_gains))
+        .route("/api/positions/realized/export", get(export_realized_gains))
         .route(
             "/api/positions/:id",
             get(get_position)
@@ -202,6 +235,49 @@ async fn remove_watchlist_symbol(
     }
 }
 
+async fn watchlist_recap(state: &AppState, id: &str) -> Result<WatchlistRecap, Response> {
+    let oid = parse_oid(id).map_err(IntoResponse::into_response)?;
+    let wl = match state.alert_engine.repo().get_watchlist(&oid).await {
+        Ok(Some(wl)) => wl,
+        Ok(None) => return Err(err(StatusCode::NOT_FOUND, "not found").into_response()),
+        Err(e) => return Err(err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    };
+    recap::generate(&state.alert_engine, &state.cache, &wl)
+        .await
+        .map_err(|e| err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())
+}
+
+/// Today's end-of-day recap for one watchlist, without sending it.
+async fn preview_watchlist_recap(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match watchlist_recap(&state, &id).await {
+        Ok(recap) => Json(json!({ "success": true, "recap": recap })).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+/// Send today's recap to every `daily_recap` channel now.
+async fn send_watchlist_recap(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let recap = match watchlist_recap(&state, &id).await {
+        Ok(recap) => recap,
+        Err(resp) => return resp,
+    };
+    match state.alert_engine.dispatcher().dispatch_recap(&recap).await {
+        Ok(delivered) => Json(json!({
+            "success": true,
+            "recap": recap,
+            "delivered": delivered,
+        }))
+        .into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
 // ---------- rules ------------------------------------------------------------
 
 async fn list_rules(State(state): State<AppState>) -> impl IntoResponse {
@@ -213,20 +289,17 @@ async fn list_rules(State(state): State<AppState>) -> impl IntoResponse {
 
 async fn create_rule(
     State(state): State<AppState>,
-    Json(input): Json<CreateAlertRuleInput>,
+    ValidatedJson(input): ValidatedJson<CreateAlertRuleInput>,
 ) -> impl IntoResponse {
-    if input.name.trim().is_empty() {
-        return err(StatusCode::BAD_REQUEST, "name required").into_response();
-    }
-    if input.channel_ids.is_empty() {
-        return err(
-            StatusCode::BAD_REQUEST,
-            "at least one notification channel is required",
-        )
-        .into_response();
-    }
-    match state.alert_engine.repo().create_rule(input).await {
-        Ok(rule) => Json(json!({ "success": true, "rule": rule })).into_response(),
+    let repo = state.alert_engine.repo();
+    match repo.create_rule(input).await {
+        Ok(rule) => {
+            if let Some(id) = rule.id {
+                repo.record_audit(AlertAuditEvent::new(id, AlertAuditAction::Created))
+                    .await;
+            }
+            Json(json!({ "success": true, "rule": rule })).into_response()
+        }
         Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
@@ -246,50 +319,194 @@ async fn get_rule(State(state): State<AppState>, Path(id): Path<String>) -> impl
 async fn update_rule(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(input): Json<UpdateAlertRuleInput>,
+    ValidatedJson(input): ValidatedJson<UpdateAlertRuleInput>,
+) -> impl IntoResponse {
+    let oid = match parse_oid(&id) {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+    let repo = state.alert_engine.repo();
+    match repo.update_rule(&oid, input).await {
+        Ok(Some(r)) => {
+            repo.record_audit(AlertAuditEvent::new(oid, AlertAuditAction::Updated))
+                .await;
+            Json(json!({ "success": true, "rule": r })).into_response()
+        }
+        Ok(None) => err(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn delete_rule(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let oid = match parse_oid(&id) {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+    let repo = state.alert_engine.repo();
+    match repo.delete_rule(&oid).await {
+        Ok(true) => {
+            // The audit trail outlives the rule.
+            repo.record_audit(AlertAuditEvent::new(oid, AlertAuditAction::Deleted))
+                .await;
+            Json(json!({ "success": true })).into_response()
+        }
+        Ok(false) => err(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn toggle_rule(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let oid = match parse_oid(&id) {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+    let repo = state.alert_engine.repo();
+    match repo.toggle_rule(&oid).await {
+        Ok(Some(r)) => {
+            let action = if r.enabled {
+                AlertAuditAction::Enabled
+            } else {
+                AlertAuditAction::Disabled
+            };
+            repo.record_audit(AlertAuditEvent::new(oid, action)).await;
+            Json(json!({ "success": true, "rule": r })).into_response()
+        }
+        Ok(None) => err(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn snooze_rule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<SnoozeRuleInput>,
 ) -> impl IntoResponse {
     let oid = match parse_oid(&id) {
         Ok(v) => v,
         Err(e) => return e.into_response(),
     };
-    if matches!(input.channel_ids.as_ref(), Some(ids) if ids.is_empty()) {
+    let Some(until) = input.resolve(chrono::Utc::now()) else {
         return err(
             StatusCode::BAD_REQUEST,
-            "at least one notification channel is required",
+            "provide a future `until` timestamp or a positive `minutes`",
         )
         .into_response();
-    }
-    match state.alert_engine.repo().update_rule(&oid, input).await {
-        Ok(Some(r)) => Json(json!({ "success": true, "rule": r })).into_response(),
+    };
+    let repo = state.alert_engine.repo();
+    match repo.set_rule_snooze(&oid, Some(until)).await {
+        Ok(Some(r)) => {
+            repo.record_audit(
+                AlertAuditEvent::new(oid, AlertAuditAction::Snoozed)
+                    .with_detail(format!("until {}", until.to_rfc3339())),
+            )
+            .await;
+            Json(json!({ "success": true, "rule": r })).into_response()
+        }
         Ok(None) => err(StatusCode::NOT_FOUND, "not found").into_response(),
         Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-async fn delete_rule(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+async fn unsnooze_rule(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
     let oid = match parse_oid(&id) {
         Ok(v) => v,
         Err(e) => return e.into_response(),
     };
-    match state.alert_engine.repo().delete_rule(&oid).await {
-        Ok(true) => Json(json!({ "success": true })).into_response(),
-        Ok(false) => err(StatusCode::NOT_FOUND, "not found").into_response(),
+    let repo = state.alert_engine.repo();
+    match repo.set_rule_snooze(&oid, None).await {
+        Ok(Some(r)) => {
+            repo.record_audit(AlertAuditEvent::new(oid, AlertAuditAction::Unsnoozed))
+                .await;
+            Json(json!({ "success": true, "rule": r })).into_response()
+        }
+        Ok(None) => err(StatusCode::NOT_FOUND, "not found").into_response(),
         Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-async fn toggle_rule(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+async fn set_symbol_muted(state: &AppState, id: &str, symbol: &str, muted: bool) -> Response {
+    let oid = match parse_oid(id) {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+    let symbol = crate::symbols::normalize_symbol_key(symbol);
+    if symbol.is_empty() {
+        return err(StatusCode::BAD_REQUEST, "symbol required").into_response();
+    }
+    let repo = state.alert_engine.repo();
+    match repo.set_symbol_muted(&oid, &symbol, muted).await {
+        Ok(Some(r)) => {
+            let action = if muted {
+                AlertAuditAction::Muted
+            } else {
+                AlertAuditAction::Unmuted
+            };
+            repo.record_audit(AlertAuditEvent::new(oid, action).with_symbol(symbol))
+                .await;
+            Json(json!({ "success": true, "rule": r })).into_response()
+        }
+        Ok(None) => err(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn mute_symbol(
+    State(state): State<AppState>,
+    Path((id, symbol)): Path<(String, String)>,
+) -> impl IntoResponse {
+    set_symbol_muted(&state, &id, &symbol, true).await
+}
+
+async fn unmute_symbol(
+    State(state): State<AppState>,
+    Path((id, symbol)): Path<(String, String)>,
+) -> impl IntoResponse {
+    set_symbol_muted(&state, &id, &symbol, false).await
+}
+
+/// Zero the trigger counter, clear any snooze and re-enable the rule.
+async fn reset_rule(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
     let oid = match parse_oid(&id) {
         Ok(v) => v,
         Err(e) => return e.into_response(),
     };
-    match state.alert_engine.repo().toggle_rule(&oid).await {
-        Ok(Some(r)) => Json(json!({ "success": true, "rule": r })).into_response(),
+    let repo = state.alert_engine.repo();
+    match repo.reset_rule(&oid).await {
+        Ok(Some(r)) => {
+            repo.record_audit(AlertAuditEvent::new(oid, AlertAuditAction::Reset))
+                .await;
+            Json(json!({ "success": true, "rule": r })).into_response()
+        }
         Ok(None) => err(StatusCode::NOT_FOUND, "not found").into_response(),
         Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct AuditQuery {
+    #[serde(default = "default_audit_limit")]
+    limit: u32,
+}
+
+fn default_audit_limit() -> u32 {
+    100
+}
+
+async fn list_rule_audit(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(q): Query<AuditQuery>,
+) -> impl IntoResponse {
+    let oid = match parse_oid(&id) {
+        Ok(v) => v,
+        Err(e) => return e.into_response(),
+    };
+    match state.alert_engine.repo().list_audit(&oid, q.limit).await {
+        Ok(events) => Json(json!({ "success": true, "events": events })).into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct TestRuleInput {
     /// Optional — if omitted, the endpoint picks the first in-scope symbol
@@ -343,7 +560,7 @@ async fn test_rule(
         .into_response();
     }
 
-    let analysis = match state.db.get_analysis_by_symbol(&symbol).await {
+    let analysis = match state.storage.get_analysis_by_symbol(&symbol).await {
         Ok(Some(a)) => a,
         Ok(None) => {
             return err(
@@ -481,10 +698,6 @@ async fn test_channel(State(state): State<AppState>, Path(id): Path<String>) ->
 
 #[derive(Debug, Deserialize)]
 struct HistoryQuery {
-    #[serde(default)]
-    page: Option<u32>,
-    #[serde(default)]
-    page_size: Option<u32>,
     #[serde(default)]
     rule_id: Option<String>,
     #[serde(default)]
@@ -493,7 +706,9 @@ struct HistoryQuery {
 
 async fn list_history(
     State(state): State<AppState>,
+    uri: Uri,
     Query(q): Query<HistoryQuery>,
+    Query(paging): Query<PageQuery>,
 ) -> impl IntoResponse {
     let rule_id = match q.rule_id.as_deref() {
         Some(s) => match parse_oid(s) {
@@ -502,25 +717,22 @@ async fn list_history(
         },
         None => None,
     };
-    let page = q.page.unwrap_or(1);
-    let page_size = q.page_size.unwrap_or(50);
+    let page = paging.resolve(50, 200);
     match state
         .alert_engine
         .repo()
-        .list_history(page, page_size, rule_id, q.symbol)
+        .list_history(page.page, page.page_size, rule_id, q.symbol)
         .await
     {
-        Ok((items, total)) => Json(json!({
-            "success": true,
-            "history": items,
-            "pagination": {
-                "page": page,
-                "page_size": page_size,
-                "total": total,
-                "total_pages": ((total as f64) / (page_size.max(1) as f64)).ceil() as u32,
-            }
-        }))
-        .into_response(),
+        Ok((items, total)) => (
+            page.headers(&uri, total),
+            Json(json!({
+                "success": true,
+                "history": items,
+                "pagination": page.json(total)
+            })),
+        )
+            .into_response(),
         Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
@@ -565,8 +777,9 @@ async fn mark_history_read(
 // ---------- positions -------------------------------------------------------
 
 /// Build a `PositionView` for a single position by joining against the cached
-/// `StockAnalysis.price`. On cache miss, computed fields are `None` and the
-/// frontend renders dashes.
+/// `StockAnalysis.price` (read through to Mongo on a miss). When the symbol
+/// has never been analyzed, computed fields are `None` and the frontend
+/// renders dashes.
 async fn build_position_view(state: &AppState, position: Position) -> PositionView {
     let current_price = state
         .cache
@@ -589,9 +802,340 @@ async fn list_positions(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct RebalanceQuery {
+    #[serde(default)]
+    target: RebalanceTarget,
+    #[serde(default)]
+    commission_per_trade: f64,
+    #[serde(default = "default_slippage_bps")]
+    slippage_bps: f64,
+}
+
+fn default_slippage_bps() -> f64 {
+    5.0
+}
+
+/// Suggest whole-share trades that bring the tracked positions to the target
+/// allocation. Lots are merged per symbol; symbols without a cached price are
+/// reported under `unpriced` and left out of the plan.
+async fn rebalance_positions(
+    State(state): State<AppState>,
+    Query(q): Query<RebalanceQuery>,
+) -> impl IntoResponse {
+    if q.commission_per_trade < 0.0 || q.slippage_bps < 0.0 {
+        return err(StatusCode::BAD_REQUEST, "costs must be non-negative").into_response();
+    }
+    let positions = match state.alert_engine.repo().list_positions().await {
+        Ok(p) => p,
+        Err(e) => return err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let mut quantities: std::collections::BTreeMap<String, f64> = Default::default();
+    for p in positions {
+        *quantities.entry(p.symbol).or_default() += p.quantity;
+    }
+
+    let mut holdings = Vec::with_capacity(quantities.len());
+    let mut unpriced = Vec::new();
+    for (symbol, quantity) in quantities {
+        match state.cache.get_stock(&symbol).await {
+            Some(s) if s.price > 0.0 => holdings.push(Holding {
+                symbol,
+                quantity,
+                price: s.price,
+                sector: s.sector.clone(),
+            }),
+            _ => unpriced.push(symbol),
+        }
+    }
+
+    let costs = CostModel {
+        commission_per_trade: q.commission_per_trade,
+        slippage_bps: q.slippage_bps,
+    };
+    let plan = rebalance::plan(&holdings, q.target, costs);
+    Json(json!({ "success": true, "plan": plan, "unpriced": unpriced })).into_response()
+}
+
+async fn sell_position(
+    State(state): State<AppState>,
+    ValidatedJson(input): ValidatedJson<SellPositionInput>,
+) -> impl IntoResponse {
+    match state.alert_engine.repo().sell_position(input).await {
+        Ok(gains) => {
+            let summary = lots::summarize(&gains);
+            Json(json!({ "success": true, "gains": gains, "summary": summary })).into_response()
+        }
+        Err(e) => err(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportQuery {
+    /// Adapter id (`ibkr_flex`, `schwab_csv`); sniffed from the body if omitted.
+    broker: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+    /// Replay the trade log even when the file also lists open positions.
+    #[serde(default)]
+    trades: bool,
+}
+
+/// Import a broker export (raw file as the request body). By default the
+/// open-position snapshot becomes one lot per row; files with only a trade
+/// log (or `?trades=true`) are replayed in date order, buys opening lots and
+/// sells closing them FIFO. Rows imported before (see `import_key`) are
+/// reported under `already_imported` and skipped, so re-importing the same
+/// file changes nothing. `dry_run` returns the parsed rows untouched.
+async fn import_positions(
+    State(state): State<AppState>,
+    Query(q): Query<ImportQuery>,
+    body: String,
+) -> impl IntoResponse {
+    let adapter = match brokers::adapter_for(q.broker.as_deref(), &body) {
+        Ok(a) => a,
+        Err(e) => return err(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let import = match adapter.parse(&body) {
+        Ok(i) => i,
+        Err(e) => return err(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    if q.dry_run {
+        return Json(json!({ "success": true, "broker": adapter.id(), "import": import }))
+            .into_response();
+    }
+
+    let repo = state.alert_engine.repo();
+    let notes = Some(format!("Imported from {}", adapter.id()));
+    let replay_trades = q.trades || import.positions.is_empty();
+    let mut created = 0usize;
+    let mut gains = Vec::new();
+    let mut errors = Vec::new();
+    let mut already_imported = Vec::new();
+    // Identical rows in one file (two equal fills without a trade id) get
+    // numbered keys, so each is imported once.
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut row_key = |key: String| {
+        let n = occurrences.entry(key.clone()).or_default();
+        *n += 1;
+        if *n == 1 {
+            key
+        } else {
+            format!("{}#{}", key, n)
+        }
+    };
+
+    if replay_trades {
+        for t in import.trades {
+            let label = format!("{:?} {} {}", t.side, t.quantity, t.symbol);
+            let key = row_key(t.import_key(adapter.id()));
+            match repo.position_imported(&key).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    already_imported.push(label);
+                    continue;
+                }
+                Err(e) => {
+                    errors.push(format!("{}: {}", label, e));
+                    continue;
+                }
+            }
+            let result = match t.side {
+                TradeSide::Buy => repo
+                    .create_position(CreatePositionInput {
+                        symbol: t.symbol,
+                        quantity: t.quantity,
+                        cost_basis_per_share: t.price,
+                        opened_at: Some(t.traded_at),
+                        notes: notes.clone(),
+                    })
+                    .await
+                    .map(|_| created += 1),
+                TradeSide::Sell => repo
+                    .sell_position(SellPositionInput {
+                        symbol: t.symbol,
+                        quantity: t.quantity,
+                        price: t.price,
+                        method: LotMethod::Fifo,
+                        lot_ids: Vec::new(),
+                        sold_at: Some(t.traded_at),
+                    })
+                    .await
+                    .map(|g| gains.extend(g)),
+            };
+            let result = match result {
+                Ok(()) => repo.record_position_import(&key).await,
+                failed => failed,
+            };
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", label, e));
+            }
+        }
+    } else {
+        for p in import.positions {
+            let symbol = p.symbol.clone();
+            let key = row_key(p.import_key(adapter.id()));
+            match repo.position_imported(&key).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    already_imported.push(symbol);
+                    continue;
+                }
+                Err(e) => {
+                    errors.push(format!("{}: {}", symbol, e));
+                    continue;
+                }
+            }
+            let result = match repo
+                .create_position(CreatePositionInput {
+                    symbol: p.symbol,
+                    quantity: p.quantity,
+                    cost_basis_per_share: p.cost_basis_per_share,
+                    opened_at: p.opened_at,
+                    notes: notes.clone(),
+                })
+                .await
+            {
+                Ok(_) => {
+                    created += 1;
+                    repo.record_position_import(&key).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", symbol, e));
+            }
+        }
+    }
+
+    Json(json!({
+        "success": true,
+        "broker": adapter.id(),
+        "mode": if replay_trades { "trades" } else { "positions" },
+        "lots_created": created,
+        "realized": lots::summarize(&gains),
+        "skipped": import.skipped,
+        "already_imported": already_imported,
+        "errors": errors,
+    }))
+    .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct ValuationHistoryQuery {
+    #[serde(default = "default_valuation_days")]
+    days: i64,
+}
+
+fn default_valuation_days() -> i64 {
+    365
+}
+
+/// Daily valuation snapshots of the tracked positions, oldest first.
+async fn position_history(
+    State(state): State<AppState>,
+    Query(q): Query<ValuationHistoryQuery>,
+) -> impl IntoResponse {
+    if q.days <= 0 {
+        return err(StatusCode::BAD_REQUEST, "days must be positive").into_response();
+    }
+    let since = (chrono::Utc::now() - chrono::Duration::days(q.days))
+        .format("%Y-%m-%d")
+        .to_string();
+    match state
+        .alert_engine
+        .repo()
+        .list_valuation_snapshots(&since)
+        .await
+    {
+        Ok(snapshots) => Json(json!({ "success": true, "snapshots": snapshots })).into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Take (or refresh) today's snapshot now instead of waiting for the hourly task.
+async fn snapshot_positions(State(state): State<AppState>) -> impl IntoResponse {
+    match valuation::take_snapshot(state.alert_engine.repo(), &state.cache).await {
+        Ok(snapshot) => Json(json!({ "success": true, "snapshot": snapshot })).into_response(),
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RealizedQuery {
+    year: Option<i32>,
+}
+
+/// Realized gains (optionally for one tax year) next to the unrealized P&L
+/// of the lots still open.
+async fn realized_gains(
+    State(state): State<AppState>,
+    Query(q): Query<RealizedQuery>,
+) -> impl IntoResponse {
+    let repo = state.alert_engine.repo();
+    let gains: Vec<_> = match repo.list_realized_gains().await {
+        Ok(g) => g.into_iter().filter(|g| lots::in_year(g, q.year)).collect(),
+        Err(e) => return err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let positions = match repo.list_positions().await {
+        Ok(p) => p,
+        Err(e) => return err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let mut unrealized = 0.0;
+    let mut unpriced = Vec::new();
+    for p in positions {
+        let view = build_position_view(&state, p).await;
+        match view.unrealized_pnl {
+            Some(pnl) => unrealized += pnl,
+            None => unpriced.push(view.position.symbol),
+        }
+    }
+    Json(json!({
+        "success": true,
+        "year": q.year,
+        "realized": lots::summarize(&gains),
+        "unrealized_pnl": unrealized,
+        "unpriced": unpriced,
+        "gains": gains,
+    }))
+    .into_response()
+}
+
+async fn export_realized_gains(
+    State(state): State<AppState>,
+    Query(q): Query<RealizedQuery>,
+) -> impl IntoResponse {
+    match state.alert_engine.repo().list_realized_gains().await {
+        Ok(gains) => {
+            let mut rows: Vec<_> = gains
+                .into_iter()
+                .filter(|g| lots::in_year(g, q.year))
+                .collect();
+            rows.reverse();
+            let filename = match q.year {
+                Some(y) => format!("realized-gains-{}.csv", y),
+                None => "realized-gains.csv".to_string(),
+            };
+            (
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}\"", filename),
+                    ),
+                ],
+                lots::to_csv(&rows),
+            )
+                .into_response()
+        }
+        Err(e) => err(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
 async fn create_position(
     State(state): State<AppState>,
-    Json(input): Json<CreatePositionInput>,
+    ValidatedJson(input): ValidatedJson<CreatePositionInput>,
 ) -> impl IntoResponse {
     match state.alert_engine.repo().create_position(input).await {
         Ok(p) => {
@@ -620,7 +1164,7 @@ async fn get_position(State(state): State<AppState>, Path(id): Path<String>) ->
 async fn update_position(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(input): Json<UpdatePositionInput>,
+    ValidatedJson(input): ValidatedJson<UpdatePositionInput>,
 ) -> impl IntoResponse {
     let oid = match parse_oid(&id) {
         Ok(v) => v,
@@ -0,0 +1,235 @@
+//! Interactive Brokers Flex Query (XML) exports.
+//!
+//! Reads `<OpenPosition>` rows (lot-level rows win over summaries when both
+//! are present) and `<Trade>` rows. Only `assetCategory="STK"` is imported;
+//! options, FX and cash rows are reported as skipped.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+
+use super::{
+    date_at_midnight, parse_amount, BrokerAdapter, BrokerImport, ImportedPosition, ImportedTrade,
+};
+use crate::notifications::rebalance::TradeSide;
+
+pub struct IbkrFlex;
+
+impl BrokerAdapter for IbkrFlex {
+    fn id(&self) -> &'static str {
+        "ibkr_flex"
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        content.contains("<FlexQueryResponse") || content.contains("<FlexStatement")
+    }
+
+    fn parse(&self, content: &str) -> Result<BrokerImport> {
+        if !self.detect(content) {
+            return Err(anyhow!("not an IBKR Flex Query XML export"));
+        }
+        let mut out = BrokerImport::default();
+
+        let open = elements(content, "OpenPosition");
+        let has_lots = open
+            .iter()
+            .any(|a| a.get("levelOfDetail").map(String::as_str) == Some("LOT"));
+        for attrs in &open {
+            if has_lots && attrs.get("levelOfDetail").map(String::as_str) != Some("LOT") {
+                continue;
+            }
+            match position(attrs) {
+                Ok(p) => out.positions.push(p),
+                Err(e) => out.skipped.push(e.to_string()),
+            }
+        }
+
+        for attrs in elements(content, "Trade") {
+            match trade(&attrs) {
+                Ok(t) => out.trades.push(t),
+                Err(e) => out.skipped.push(e.to_string()),
+            }
+        }
+        out.trades.sort_by_key(|t| t.traded_at);
+        Ok(out)
+    }
+}
+
+fn stock_symbol(attrs: &HashMap<String, String>) -> Result<String> {
+    let symbol = attrs.get("symbol").cloned().unwrap_or_default();
+    match attrs.get("assetCategory").map(String::as_str) {
+        None | Some("STK") if !symbol.is_empty() => Ok(symbol),
+        category => Err(anyhow!(
+            "{}: unsupported asset category {}",
+            if symbol.is_empty() { "?" } else { &symbol },
+            category.unwrap_or("?")
+        )),
+    }
+}
+
+fn number(attrs: &HashMap<String, String>, key: &str, symbol: &str) -> Result<f64> {
+    attrs
+        .get(key)
+        .and_then(|v| parse_amount(v))
+        .ok_or_else(|| anyhow!("{}: missing or invalid {}", symbol, key))
+}
+
+/// Flex dates are `yyyyMMdd`, optionally followed by `;HHmmss`.
+fn flex_date(raw: &str) -> Option<NaiveDate> {
+    let date = raw.split([';', ' ']).next()?;
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .ok()
+}
+
+fn position(attrs: &HashMap<String, String>) -> Result<ImportedPosition> {
+    let symbol = stock_symbol(attrs)?;
+    let quantity = number(attrs, "position", &symbol)?;
+    let cost_basis_per_share = number(attrs, "costBasisPrice", &symbol)?;
+    let opened_at = attrs
+        .get("openDateTime")
+        .and_then(|d| flex_date(d))
+        .map(date_at_midnight);
+    Ok(ImportedPosition {
+        symbol,
+        quantity,
+        cost_basis_per_share,
+        opened_at,
+    })
+}
+
+fn trade(attrs: &HashMap<String, String>) -> Result<ImportedTrade> {
+    let symbol = stock_symbol(attrs)?;
+    let quantity = number(attrs, "quantity", &symbol)?;
+    let price = number(attrs, "tradePrice", &symbol)?;
+    let side = match attrs
+        .get("buySell")
+        .map(|s| s.to_ascii_uppercase())
+        .as_deref()
+    {
+        Some("BUY") => TradeSide::Buy,
+        Some("SELL") => TradeSide::Sell,
+        _ if quantity < 0.0 => TradeSide::Sell,
+        _ => TradeSide::Buy,
+    };
+    let traded_at = attrs
+        .get("tradeDate")
+        .or_else(|| attrs.get("dateTime"))
+        .and_then(|d| flex_date(d))
+        .map(date_at_midnight)
+        .ok_or_else(|| anyhow!("{}: missing or invalid tradeDate", symbol))?;
+    Ok(ImportedTrade {
+        symbol,
+        side,
+        quantity: quantity.abs(),
+        price,
+        traded_at,
+        trade_id: attrs.get("tradeID").filter(|id| !id.is_empty()).cloned(),
+    })
+}
+
+/// Attributes of every `<tag .../>` (or `<tag ...>`) element. Flex reports
+/// are flat attribute-only rows, so a scanner is enough — no XML parser.
+fn elements(xml: &str, tag: &str) -> Vec<HashMap<String, String>> {
+    let open = format!("<{}", tag);
+    let mut rows = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // Skip longer tag names sharing the prefix (`<Trades>`, `<TradeConfirm`).
+        if !after.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>') {
+            rest = after;
+            continue;
+        }
+        let end = after.find('>').unwrap_or(after.len());
+        rows.push(attributes(after[..end].trim_end_matches('/')));
+        rest = &after[end..];
+    }
+    rows
+}
+
+fn attributes(s: &str) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    let mut rest = s;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim().to_string();
+        let after = rest[eq + 1..].trim_start();
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(close) = after[1..].find(quote) else {
+            break;
+        };
+        attrs.insert(name, unescape(&after[1..1 + close]));
+        rest = &after[close + 2..];
+    }
+    attrs
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<FlexQueryResponse queryName="Portfolio" type="AF">
+<FlexStatements count="1">
+<FlexStatement accountId="U1234567" fromDate="20250101" toDate="20250630">
+<OpenPositions>
+<OpenPosition assetCategory="STK" symbol="AAPL" position="10" costBasisPrice="150.25" levelOfDetail="SUMMARY" />
+<OpenPosition assetCategory="STK" symbol="AAPL" position="4" costBasisPrice="140" openDateTime="20240115;093000" levelOfDetail="LOT" />
+<OpenPosition assetCategory="STK" symbol="AAPL" position="6" costBasisPrice="157.08" openDateTime="20250210;101500" levelOfDetail="LOT" />
+<OpenPosition assetCategory="OPT" symbol="AAPL  250620C00200000" position="1" costBasisPrice="3.1" levelOfDetail="LOT" />
+</OpenPositions>
+<Trades>
+<Trade assetCategory="STK" symbol="MSFT" buySell="SELL" quantity="-5" tradePrice="410.5" tradeDate="20250303" tradeID="4411" />
+<Trade assetCategory="STK" symbol="MSFT" buySell="BUY" quantity="5" tradePrice="380" tradeDate="20250102" description="MICROSOFT &amp; CO" />
+</Trades>
+</FlexStatement>
+</FlexStatements>
+</FlexQueryResponse>"#;
+
+    #[test]
+    fn parses_lots_and_trades_from_flex_xml() {
+        let import = IbkrFlex.parse(SAMPLE).unwrap();
+
+        // Lot rows replace the summary row; the option lot is skipped.
+        assert_eq!(import.positions.len(), 2);
+        assert_eq!(import.positions[0].quantity, 4.0);
+        assert_eq!(import.positions[0].cost_basis_per_share, 140.0);
+        assert_eq!(
+            import.positions[0].opened_at.unwrap().date_naive(),
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+        );
+        assert_eq!(import.skipped.len(), 1);
+
+        // Trades come back in date order with positive quantities.
+        assert_eq!(import.trades.len(), 2);
+        assert_eq!(import.trades[0].side, TradeSide::Buy);
+        assert_eq!(import.trades[1].side, TradeSide::Sell);
+        assert_eq!(import.trades[1].quantity, 5.0);
+        assert_eq!(import.trades[1].price, 410.5);
+        assert_eq!(import.trades[0].trade_id, None);
+        assert_eq!(import.trades[1].trade_id.as_deref(), Some("4411"));
+        assert_eq!(
+            import.trades[1].import_key("ibkr_flex"),
+            "ibkr_flex|trade|2025-03-03|MSFT|Sell|5|4411"
+        );
+        assert_eq!(
+            import.trades[0].import_key("ibkr_flex"),
+            "ibkr_flex|trade|2025-01-02|MSFT|Buy|5|380"
+        );
+    }
+
+    #[test]
+    fn rejects_non_flex_content() {
+        assert!(IbkrFlex.parse("Symbol,Quantity\nAAPL,1").is_err());
+    }
+}
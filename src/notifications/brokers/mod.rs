@@ -0,0 +1,181 @@
+//! Broker export importers.
+//!
+//! Each adapter turns one broker's export file into open lots and/or a trade
+//! log; `POST /api/positions/import` maps those onto tracked positions (buys
+//! open lots, sells go through FIFO lot accounting). There is no separate
+//! portfolio model — positions are the portfolio.
+//!
+//! New formats should:
+//! 1. Drop a file in this folder implementing `BrokerAdapter`.
+//! 2. Add it to `adapters` below.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::rebalance::TradeSide;
+
+pub mod ibkr;
+pub mod schwab;
+
+/// An open lot as reported by the broker.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportedPosition {
+    pub symbol: String,
+    pub quantity: f64,
+    pub cost_basis_per_share: f64,
+    /// `None` when the export doesn't carry an open date (imported as now).
+    pub opened_at: Option<DateTime<Utc>>,
+}
+
+impl ImportedPosition {
+    /// Identifies this row when the same export is imported again.
+    pub fn import_key(&self, broker: &str) -> String {
+        format!(
+            "{}|lot|{}|{}|{}|{}",
+            broker,
+            self.opened_at
+                .map(|at| at.date_naive().to_string())
+                .unwrap_or_default(),
+            self.symbol,
+            self.quantity,
+            self.cost_basis_per_share
+        )
+    }
+}
+
+/// One executed trade.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportedTrade {
+    pub symbol: String,
+    pub side: TradeSide,
+    /// Always positive; `side` carries the direction.
+    pub quantity: f64,
+    pub price: f64,
+    pub traded_at: DateTime<Utc>,
+    /// The broker's execution id (IBKR `tradeID`), when the export has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trade_id: Option<String>,
+}
+
+impl ImportedTrade {
+    /// Identifies this row when the same export is imported again: trade
+    /// date, symbol, side and quantity, plus the broker's trade id or, when
+    /// there is none, the price.
+    pub fn import_key(&self, broker: &str) -> String {
+        format!(
+            "{}|trade|{}|{}|{:?}|{}|{}",
+            broker,
+            self.traded_at.date_naive(),
+            self.symbol,
+            self.side,
+            self.quantity,
+            self.trade_id
+                .clone()
+                .unwrap_or_else(|| self.price.to_string())
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BrokerImport {
+    pub positions: Vec<ImportedPosition>,
+    pub trades: Vec<ImportedTrade>,
+    /// Rows the adapter recognised but couldn't use (options, cash, bad numbers).
+    pub skipped: Vec<String>,
+}
+
+pub trait BrokerAdapter: Send + Sync {
+    /// Stable id used in `?broker=`.
+    fn id(&self) -> &'static str;
+    /// Cheap sniff of the file contents, used when no broker is given.
+    fn detect(&self, content: &str) -> bool;
+    fn parse(&self, content: &str) -> Result<BrokerImport>;
+}
+
+pub fn adapters() -> Vec<Box<dyn BrokerAdapter>> {
+    vec![Box::new(ibkr::IbkrFlex), Box::new(schwab::SchwabCsv)]
+}
+
+/// Resolve the adapter by id, or sniff the content when `id` is `None`.
+pub fn adapter_for(id: Option<&str>, content: &str) -> Result<Box<dyn BrokerAdapter>> {
+    let mut all = adapters().into_iter();
+    match id {
+        Some(id) => all
+            .find(|a| a.id().eq_ignore_ascii_case(id))
+            .ok_or_else(|| anyhow!("unknown broker format '{}'", id)),
+        None => all
+            .find(|a| a.detect(content))
+            .ok_or_else(|| anyhow!("could not detect the broker format; pass ?broker=")),
+    }
+}
+
+/// Split one CSV line, honouring double quotes and `""` escapes.
+pub(crate) fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut cur = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cur.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut cur)),
+            _ => cur.push(c),
+        }
+    }
+    fields.push(cur);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Parse a broker-formatted number: `$1,234.50`, `(12.00)` → -12, `--` → None.
+pub(crate) fn parse_amount(raw: &str) -> Option<f64> {
+    let s = raw.trim();
+    let (negative, s) = match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, s),
+    };
+    let cleaned: String = s
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+    let v: f64 = cleaned.parse().ok().filter(|v: &f64| v.is_finite())?;
+    Some(if negative { -v } else { v })
+}
+
+/// Midnight UTC of a broker trade date.
+pub(crate) fn date_at_midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).expect("valid time").and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_and_amount_helpers_handle_broker_quirks() {
+        assert_eq!(
+            csv_fields(r#""AAPL","Apple, Inc.","1,000","$1.50""#),
+            vec!["AAPL", "Apple, Inc.", "1,000", "$1.50"]
+        );
+        assert_eq!(csv_fields(r#"a,"say ""hi""",c"#)[1], r#"say "hi""#);
+        assert_eq!(parse_amount("$1,234.50"), Some(1234.5));
+        assert_eq!(parse_amount("(12.00)"), Some(-12.0));
+        assert_eq!(parse_amount("--"), None);
+    }
+
+    #[test]
+    fn adapter_lookup_by_id_and_sniffing() {
+        assert_eq!(
+            adapter_for(Some("SCHWAB_CSV"), "").unwrap().id(),
+            "schwab_csv"
+        );
+        assert!(adapter_for(Some("nope"), "").is_err());
+        let flex = r#"<FlexQueryResponse queryName="x"><FlexStatements/></FlexQueryResponse>"#;
+        assert_eq!(adapter_for(None, flex).unwrap().id(), "ibkr_flex");
+        assert!(adapter_for(None, "hello").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Charles Schwab CSV exports.
+//!
+//! Handles both the "Positions" download (one row per holding, total cost
+//! basis, no open date) and the "Transactions" history download (Buy / Sell /
+//! Reinvest Shares rows). Preamble lines and the cash / account-total rows are
+//! ignored.
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+
+use super::{
+    csv_fields, date_at_midnight, parse_amount, BrokerAdapter, BrokerImport, ImportedPosition,
+    ImportedTrade,
+};
+use crate::notifications::rebalance::TradeSide;
+
+pub struct SchwabCsv;
+
+enum Layout {
+    Positions {
+        symbol: usize,
+        quantity: usize,
+        cost_basis: usize,
+    },
+    Transactions {
+        date: usize,
+        action: usize,
+        symbol: usize,
+        quantity: usize,
+        price: usize,
+    },
+}
+
+/// Find the header row and the columns we need.
+fn layout(content: &str) -> Option<(usize, Layout)> {
+    for (i, line) in content.lines().enumerate() {
+        let cols = csv_fields(line);
+        let find = |pred: &dyn Fn(&str) -> bool| cols.iter().position(|c| pred(c));
+        let Some(symbol) = find(&|c| c == "Symbol") else {
+            continue;
+        };
+        let quantity = find(&|c| c == "Quantity" || c.starts_with("Qty"));
+        if let (Some(date), Some(action), Some(quantity), Some(price)) = (
+            find(&|c| c == "Date"),
+            find(&|c| c == "Action"),
+            quantity,
+            find(&|c| c == "Price"),
+        ) {
+            return Some((
+                i,
+                Layout::Transactions {
+                    date,
+                    action,
+                    symbol,
+                    quantity,
+                    price,
+                },
+            ));
+        }
+        if let (Some(quantity), Some(cost_basis)) = (quantity, find(&|c| c == "Cost Basis")) {
+            return Some((
+                i,
+                Layout::Positions {
+                    symbol,
+                    quantity,
+                    cost_basis,
+                },
+            ));
+        }
+    }
+    None
+}
+
+/// Transaction dates may read `03/14/2025 as of 03/12/2025`; the trade date
+/// is the first one.
+fn trade_date(raw: &str) -> Option<NaiveDate> {
+    let first = raw.split_whitespace().next()?;
+    NaiveDate::parse_from_str(first, "%m/%d/%Y").ok()
+}
+
+impl BrokerAdapter for SchwabCsv {
+    fn id(&self) -> &'static str {
+        "schwab_csv"
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        layout(content).is_some()
+    }
+
+    fn parse(&self, content: &str) -> Result<BrokerImport> {
+        let (header, layout) =
+            layout(content).ok_or_else(|| anyhow!("no Schwab positions/transactions header"))?;
+        let mut out = BrokerImport::default();
+
+        for line in content.lines().skip(header + 1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let cols = csv_fields(line);
+            let col = |i: usize| cols.get(i).map(String::as_str).unwrap_or("");
+            match layout {
+                Layout::Positions {
+                    symbol,
+                    quantity,
+                    cost_basis,
+                } => {
+                    let sym = col(symbol);
+                    if sym.is_empty() || sym.contains(' ') {
+                        // "Cash & Cash Investments", "Account Total".
+                        continue;
+                    }
+                    match (parse_amount(col(quantity)), parse_amount(col(cost_basis))) {
+                        (Some(q), Some(cost)) if q != 0.0 => out.positions.push(ImportedPosition {
+                            symbol: sym.to_string(),
+                            quantity: q,
+                            cost_basis_per_share: (cost / q).abs(),
+                            opened_at: None,
+                        }),
+                        _ => out
+                            .skipped
+                            .push(format!("{}: missing quantity or cost basis", sym)),
+                    }
+                }
+                Layout::Transactions {
+                    date,
+                    action,
+                    symbol,
+                    quantity,
+                    price,
+                } => {
+                    let side = match col(action) {
+                        "Buy" | "Reinvest Shares" => TradeSide::Buy,
+                        "Sell" => TradeSide::Sell,
+                        // Dividends, interest, transfers: not trades.
+                        _ => continue,
+                    };
+                    let sym = col(symbol);
+                    match (
+                        trade_date(col(date)),
+                        parse_amount(col(quantity)),
+                        parse_amount(col(price)),
+                    ) {
+                        (Some(d), Some(q), Some(p)) if !sym.is_empty() && q != 0.0 => {
+                            out.trades.push(ImportedTrade {
+                                symbol: sym.to_string(),
+                                side,
+                                quantity: q.abs(),
+                                price: p,
+                                traded_at: date_at_midnight(d),
+                                trade_id: None,
+                            })
+                        }
+                        _ => out.skipped.push(format!(
+                            "{} {}: missing date, quantity or price",
+                            col(action),
+                            sym
+                        )),
+                    }
+                }
+            }
+        }
+        // Schwab lists newest first; replay oldest first.
+        out.trades.sort_by_key(|t| t.traded_at);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_positions_export() {
+        let csv = r#""Positions for account Individual ...123 as of 04:10 PM ET, 06/30/2025"
+
+"Symbol","Description","Qty (Quantity)","Price","Mkt Val (Market Value)","Cost Basis","Security Type"
+"AAPL","APPLE INC","1,000","$210.00","$210,000.00","$150,250.00","Equity"
+"VTI","VANGUARD TOTAL STOCK MARKET ETF","20","$300.00","$6,000.00","--","ETFs & Closed End Funds"
+"Cash & Cash Investments","--","--","--","$1,500.00","--","Cash and Money Market"
+"Account Total","--","--","--","$217,500.00","$150,250.00","--"
+"#;
+        let import = SchwabCsv.parse(csv).unwrap();
+        assert_eq!(import.positions.len(), 1);
+        assert_eq!(import.positions[0].symbol, "AAPL");
+        assert_eq!(import.positions[0].quantity, 1000.0);
+        assert!((import.positions[0].cost_basis_per_share - 150.25).abs() < 1e-9);
+        assert_eq!(import.skipped, vec!["VTI: missing quantity or cost basis"]);
+    }
+
+    #[test]
+    fn parses_transactions_export_oldest_first() {
+        let csv = r#""Date","Action","Symbol","Description","Quantity","Price","Fees & Comm","Amount"
+"03/14/2025 as of 03/12/2025","Sell","MSFT","MICROSOFT CORP","5","$410.50","$0.02","$2,052.48"
+"02/28/2025","Qualified Dividend","MSFT","MICROSOFT CORP","","","","$4.15"
+"01/02/2025","Buy","MSFT","MICROSOFT CORP","5","$380.00","","-$1,900.00"
+"#;
+        let import = SchwabCsv.parse(csv).unwrap();
+        assert!(import.positions.is_empty());
+        assert_eq!(import.trades.len(), 2);
+        assert_eq!(import.trades[0].side, TradeSide::Buy);
+        assert_eq!(
+            import.trades[1].traded_at.date_naive(),
+            NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()
+        );
+        assert_eq!(import.trades[1].price, 410.5);
+    }
+}
@@ -14,6 +14,10 @@ use tracing::{debug, warn};
 
 use super::{Channel, RenderedMessage};
 use crate::notifications::models::DiscordChannelConfig;
+use crate::notifications::recap::WatchlistRecap;
+
+/// Discord caps an embed at 25 fields.
+const MAX_EMBED_FIELDS: usize = 25;
 
 pub struct DiscordChannel {
     cfg: DiscordChannelConfig,
@@ -56,7 +60,7 @@ impl DiscordChannel {
         if let Some(mc) = msg.snapshot.market_cap {
             fields.push(json!({
                 "name": "Market cap",
-                "value": format_big(mc),
+                "value": format_big(mc as f64),
                 "inline": true,
             }));
         }
@@ -106,6 +110,79 @@ impl DiscordChannel {
         payload
     }
 
+    /// One embed for the whole watchlist: a field per symbol, biggest
+    /// movers first.
+    fn build_recap_payload(&self, recap: &WatchlistRecap) -> Value {
+        let shown = recap.entries.len().min(MAX_EMBED_FIELDS - 1);
+        let mut fields: Vec<Value> = recap.entries[..shown]
+            .iter()
+            .map(|e| {
+                let mut lines = vec![format!(
+                    "{} ({})",
+                    format_money(e.close),
+                    format_signed_pct(e.change_percent)
+                )];
+                if !e.signals.is_empty() {
+                    let signals: Vec<String> = e
+                        .signals
+                        .iter()
+                        .map(|s| {
+                            serde_json::to_value(s)
+                                .ok()
+                                .and_then(|v| v.as_str().map(|n| n.replace('_', " ")))
+                                .unwrap_or_default()
+                        })
+                        .collect();
+                    lines.push(format!("Signals: {}", signals.join(", ")));
+                }
+                if !e.alerts.is_empty() {
+                    lines.push(format!("Alerts: {}", e.alerts.join(", ")));
+                }
+                if let Some(news) = &e.top_news {
+                    lines.push(format!("[{}]({})", truncate(&news.title, 200), news.url));
+                }
+                json!({
+                    "name": e.symbol,
+                    "value": truncate(&lines.join("\n"), 1000),
+                    "inline": false,
+                })
+            })
+            .collect();
+        if recap.entries.len() > shown {
+            fields.push(json!({
+                "name": "More",
+                "value": format!("+{} more symbols", recap.entries.len() - shown),
+                "inline": false,
+            }));
+        }
+
+        let description = if recap.missing.is_empty() {
+            format!("{} symbols", recap.entries.len())
+        } else {
+            format!(
+                "{} symbols · no data yet for {}",
+                recap.entries.len(),
+                truncate(&recap.missing.join(", "), 500)
+            )
+        };
+        let mut payload = json!({
+            "embeds": [{
+                "title": format!("📊 {} — end-of-day recap {}", recap.watchlist, recap.date),
+                "description": description,
+                "color": 0x3b82f6,
+                "timestamp": recap.generated_at.to_rfc3339(),
+                "fields": fields,
+            }],
+        });
+        if let Some(u) = &self.cfg.username {
+            payload["username"] = json!(u);
+        }
+        if let Some(a) = &self.cfg.avatar_url {
+            payload["avatar_url"] = json!(a);
+        }
+        payload
+    }
+
     async fn post(&self, payload: &Value) -> Result<()> {
         // Three-attempt retry with `Retry-After` honoring. Discord's webhook
         // rate limit is per-webhook and usually lifts in <2s.
@@ -166,6 +243,12 @@ impl Channel for DiscordChannel {
         self.post(&payload).await
     }
 
+    async fn send_recap(&self, recap: &WatchlistRecap) -> Result<()> {
+        let payload = self.build_recap_payload(recap);
+        debug!("discord: sending recap for {}", recap.watchlist);
+        self.post(&payload).await
+    }
+
     async fn send_test(&self) -> Result<()> {
         let payload = json!({
             "username": self.cfg.username.clone().unwrap_or_else(|| "Auto Analyser".into()),
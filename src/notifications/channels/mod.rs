@@ -11,6 +11,7 @@ use chrono::{DateTime, Utc};
 
 use crate::models::StockAnalysis;
 use crate::notifications::models::{ChannelConfig, NotificationChannel};
+use crate::notifications::recap::WatchlistRecap;
 
 pub mod discord;
 
@@ -33,6 +34,8 @@ pub struct RenderedMessage {
 #[async_trait]
 pub trait Channel: Send + Sync {
     async fn send(&self, msg: &RenderedMessage) -> Result<()>;
+    /// Deliver an end-of-day watchlist recap (see `recap.rs`).
+    async fn send_recap(&self, recap: &WatchlistRecap) -> Result<()>;
     /// Send a plain "is this webhook wired up?" message. Default impl calls `send`.
     async fn send_test(&self) -> Result<()>;
 }
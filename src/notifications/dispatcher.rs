@@ -15,8 +15,10 @@ use tracing::{debug, warn};
 use crate::models::StockAnalysis;
 use crate::notifications::channels::{build_channel, Channel, RenderedMessage};
 use crate::notifications::models::{
-    DeliveryResult, NotificationChannel, NotificationHistory, PendingNotification,
+    AlertAuditAction, AlertAuditEvent, DeliveryResult, NotificationChannel, NotificationHistory,
+    PendingNotification,
 };
+use crate::notifications::recap::WatchlistRecap;
 use crate::notifications::repo::NotificationsRepo;
 
 pub struct Dispatcher {
@@ -140,6 +142,25 @@ impl Dispatcher {
                 self.repo
                     .mark_state_triggered(&rule_id, &pending.symbol, Utc::now())
                     .await?;
+                self.repo
+                    .record_audit(
+                        AlertAuditEvent::new(rule_id, AlertAuditAction::Triggered)
+                            .with_symbol(pending.symbol.clone())
+                            .with_detail(pending.matched_conditions.join(", ")),
+                    )
+                    .await;
+                if self.repo.record_rule_trigger(&rule_id).await? {
+                    self.repo
+                        .record_audit(
+                            AlertAuditEvent::new(rule_id, AlertAuditAction::Exhausted).with_detail(
+                                format!(
+                                    "reached max_triggers ({})",
+                                    pending.rule.max_triggers.unwrap_or_default()
+                                ),
+                            ),
+                        )
+                        .await;
+                }
             }
         }
         Ok(())
@@ -203,6 +224,27 @@ impl Dispatcher {
         Ok(out)
     }
 
+    /// Deliver a watchlist recap to every enabled channel flagged
+    /// `daily_recap`. Channel failures are reported, not raised.
+    pub async fn dispatch_recap(&self, recap: &WatchlistRecap) -> Result<Vec<DeliveryResult>> {
+        let mut out = Vec::new();
+        for ch in self.repo.list_recap_channels().await? {
+            let channel = build_channel(&ch, self.http.clone());
+            let res = channel.send_recap(recap).await;
+            if let Err(e) = &res {
+                warn!("channel {} recap failed: {}", ch.name, e);
+            }
+            out.push(DeliveryResult {
+                channel_id: ch.id.unwrap_or_default(),
+                channel_name: ch.name.clone(),
+                ok: res.is_ok(),
+                error: res.err().map(|e| e.to_string()),
+                sent_at: Utc::now(),
+            });
+        }
+        Ok(out)
+    }
+
     /// Send a generic "this webhook works" ping for one channel.
     pub async fn test_channel(&self, channel_id: &ObjectId) -> Result<()> {
         let channel = self
@@ -338,30 +380,16 @@ fn substitute(
 mod tests {
     use super::*;
     use crate::models::StockAnalysis;
-    use chrono::Utc;
 
     fn sample() -> StockAnalysis {
         StockAnalysis {
-            id: None,
-            symbol: "AAPL".into(),
-            price: 150.25,
             price_change: Some(-3.0),
             price_change_percent: Some(-1.95),
             rsi: Some(28.5),
-            sma_20: None,
-            sma_50: None,
-            macd: None,
-            volume: None,
-            market_cap: Some(3_000_000_000_000.0),
+            market_cap: Some(3_000_000_000_000),
             sector: Some("Technology".into()),
             is_oversold: true,
-            is_overbought: false,
-            analyzed_at: Utc::now(),
-            bollinger: None,
-            stochastic: None,
-            earnings: None,
-            technicals: None,
-            news: None,
+            ..StockAnalysis::sample("AAPL", 150.25)
         }
     }
 
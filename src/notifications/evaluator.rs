@@ -2,9 +2,9 @@
 //!
 //! Owned by `AlertEngine`. Called once per analysis cycle with the full set
 //! of freshly-computed `StockAnalysis` snapshots. Loads every enabled rule
-//! and its prior state, applies scope filtering, cooldowns, quiet hours, and
-//! hysteresis gates, and emits a flat list of `PendingNotification`s to be
-//! fanned out by the dispatcher.
+//! and its prior state, applies scope filtering, snoozes, mutes, trigger
+//! limits, cooldowns, quiet hours, and hysteresis gates, and emits a flat
+//! list of `PendingNotification`s to be fanned out by the dispatcher.
 
 use std::collections::{HashMap, HashSet};
 
@@ -38,7 +38,17 @@ impl Evaluator {
         &self,
         analyses: &[StockAnalysis],
     ) -> Result<Vec<PendingNotification>> {
-        let rules = self.repo.list_enabled_rules().await?;
+        self.evaluate_rules(analyses, |_| true).await
+    }
+
+    /// `evaluate_cycle` restricted to the enabled rules `include` accepts.
+    pub async fn evaluate_rules(
+        &self,
+        analyses: &[StockAnalysis],
+        include: impl Fn(&AlertRule) -> bool,
+    ) -> Result<Vec<PendingNotification>> {
+        let mut rules = self.repo.list_enabled_rules().await?;
+        rules.retain(|rule| include(rule));
         if rules.is_empty() {
             debug!("notifications: no enabled rules, skipping eval");
             return Ok(Vec::new());
@@ -72,15 +82,20 @@ impl Evaluator {
                 }
             };
 
-            if in_quiet_hours(&rule.quiet_hours, now) {
+            if rule.is_exhausted() {
+                debug!("rule {}: trigger limit reached, skipping", rule.name);
+                continue;
+            }
+
+            if in_quiet_hours(&rule.quiet_hours, now) || rule.is_snoozed(now) {
                 debug!(
-                    "rule {}: quiet hours, refreshing cross state only",
+                    "rule {}: quiet hours / snoozed, refreshing cross state only",
                     rule.name
                 );
                 let symbols = self
                     .resolve_scope(&rule.scope, &all_watched_set, &by_symbol)
                     .await?;
-                self.refresh_rule_cross_state(&rule_id, &symbols, &by_symbol)
+                self.refresh_rule_cross_state(&rule, &rule_id, &symbols, &by_symbol)
                     .await;
                 continue;
             }
@@ -92,11 +107,39 @@ impl Evaluator {
                 continue;
             }
 
+            // A rule with a trigger limit can't fan out past its remaining budget
+            // in a single cycle.
+            let remaining = rule
+                .max_triggers
+                .map(|max| max.saturating_sub(rule.trigger_count) as usize);
+            let first_pending = pending.len();
+
             for symbol in symbols {
                 let analysis = match by_symbol.get(symbol.as_str()) {
                     Some(a) => *a,
                     None => continue, // symbol not analyzed this cycle
                 };
+                // Muted and over-budget symbols keep their cross state current,
+                // like quiet hours, so unmuting doesn't compare against a
+                // stale histogram.
+                if rule.is_muted(&symbol)
+                    || remaining.is_some_and(|r| pending.len() - first_pending >= r)
+                {
+                    if let Err(e) = self
+                        .refresh_one_cross_state(
+                            &rule_id,
+                            analysis,
+                            rule.conditions.uses_trailing_stop(),
+                        )
+                        .await
+                    {
+                        warn!(
+                            "rule {} / {}: state refresh error: {}",
+                            rule.name, symbol, e
+                        );
+                    }
+                    continue;
+                }
 
                 if let Err(e) = self
                     .evaluate_one(&rule, &rule_id, analysis, &mut pending, now)
@@ -128,7 +171,7 @@ impl Evaluator {
             let symbols = self
                 .resolve_scope(&rule.scope, &all_watched_set, &by_symbol)
                 .await?;
-            self.refresh_rule_cross_state(&rule_id, &symbols, &by_symbol)
+            self.refresh_rule_cross_state(&rule, &rule_id, &symbols, &by_symbol)
                 .await;
         }
         Ok(())
@@ -136,6 +179,7 @@ impl Evaluator {
 
     async fn refresh_rule_cross_state(
         &self,
+        rule: &AlertRule,
         rule_id: &ObjectId,
         symbols: &[String],
         by_symbol: &HashMap<&str, &StockAnalysis>,
@@ -144,16 +188,22 @@ impl Evaluator {
             let Some(analysis) = by_symbol.get(symbol.as_str()).copied() else {
                 continue;
             };
-            if let Err(e) = self.refresh_one_cross_state(rule_id, analysis).await {
+            if let Err(e) = self
+                .refresh_one_cross_state(rule_id, analysis, rule.conditions.uses_trailing_stop())
+                .await
+            {
                 warn!("rule {} / {}: state refresh error: {}", rule_id, symbol, e);
             }
         }
     }
 
+    /// Also keeps the trailing-stop peak moving when `track_peak` is set, so a
+    /// rise during quiet hours or a snooze still raises the stop.
     async fn refresh_one_cross_state(
         &self,
         rule_id: &ObjectId,
         analysis: &StockAnalysis,
+        track_peak: bool,
     ) -> Result<()> {
         let mut state = self
             .repo
@@ -161,6 +211,9 @@ impl Evaluator {
             .await?
             .unwrap_or_else(|| AlertState::new(*rule_id, analysis.symbol.clone()));
         state.last_macd_histogram = analysis.macd.as_ref().map(|m| m.histogram);
+        if track_peak {
+            state.observe_price(analysis.price);
+        }
         self.repo.upsert_state(&state).await
     }
 
@@ -178,9 +231,15 @@ impl Evaluator {
             .await?
             .unwrap_or_else(|| AlertState::new(*rule_id, analysis.symbol.clone()));
 
+        if rule.conditions.uses_trailing_stop() {
+            state.observe_price(analysis.price);
+        }
+
         let ctx = EvalContext {
             analysis,
             prev_macd_histogram: state.last_macd_histogram,
+            peak_price: state.peak_price,
+            now,
         };
         let (matched, descs) = evaluate(&rule.conditions, &ctx);
 
@@ -0,0 +1,253 @@
+//! Tax-lot accounting for sells.
+//!
+//! Every `Position` is a lot: a quantity bought at one cost basis on one
+//! date. Selling picks lots by FIFO, LIFO or an explicit list of lot ids,
+//! shrinks or closes them, and records one `RealizedGain` per lot touched.
+//! Everything here is pure; `repo::record_sale` persists the result.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use super::models::{LotMethod, Position, RealizedGain, TaxTerm};
+
+/// Holding period after which a gain counts as long-term (US rules: more
+/// than one year).
+const LONG_TERM_DAYS: i64 = 365;
+
+/// Quantity taken from one lot by a sell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LotFill {
+    pub position_id: ObjectId,
+    pub quantity: f64,
+    /// Quantity left in the lot afterwards (0 = close it).
+    pub remaining: f64,
+}
+
+/// Choose which lots fill a sell of `quantity` shares of one symbol. Only
+/// long lots (positive quantity) with ids are eligible, and a specific-lot
+/// sell may name each lot once.
+pub fn select_lots(
+    lots: &[Position],
+    quantity: f64,
+    method: LotMethod,
+    specific: &[ObjectId],
+) -> Result<Vec<LotFill>> {
+    if !quantity.is_finite() || quantity <= 0.0 {
+        return Err(anyhow!("quantity must be a positive finite number"));
+    }
+
+    let mut ordered: Vec<&Position> = lots
+        .iter()
+        .filter(|p| p.id.is_some() && p.quantity > 0.0)
+        .collect();
+    match method {
+        LotMethod::Fifo => ordered.sort_by_key(|p| p.opened_at),
+        LotMethod::Lifo => ordered.sort_by_key(|p| std::cmp::Reverse(p.opened_at)),
+        LotMethod::Specific => {
+            if specific.is_empty() {
+                return Err(anyhow!("lot_ids required for specific-lot sells"));
+            }
+            let mut picked = Vec::with_capacity(specific.len());
+            for (i, id) in specific.iter().enumerate() {
+                if specific[..i].contains(id) {
+                    return Err(anyhow!("lot {} is listed more than once", id));
+                }
+                let lot = ordered
+                    .iter()
+                    .find(|p| p.id.as_ref() == Some(id))
+                    .ok_or_else(|| anyhow!("lot {} is not an open lot of this symbol", id))?;
+                picked.push(*lot);
+            }
+            ordered = picked;
+        }
+    }
+
+    let mut left = quantity;
+    let mut fills = Vec::new();
+    for lot in ordered {
+        if left <= f64::EPSILON {
+            break;
+        }
+        let take = lot.quantity.min(left);
+        left -= take;
+        fills.push(LotFill {
+            position_id: lot.id.expect("filtered above"),
+            quantity: take,
+            remaining: lot.quantity - take,
+        });
+    }
+    if left > 1e-9 {
+        return Err(anyhow!(
+            "not enough shares in the selected lots ({} short)",
+            left
+        ));
+    }
+    Ok(fills)
+}
+
+/// Realized gain for one fill.
+pub fn realize(
+    lot: &Position,
+    fill: &LotFill,
+    price: f64,
+    sold_at: DateTime<Utc>,
+    method: LotMethod,
+) -> RealizedGain {
+    let holding_days = (sold_at - lot.opened_at).num_days();
+    RealizedGain {
+        id: None,
+        symbol: lot.symbol.clone(),
+        position_id: fill.position_id,
+        quantity: fill.quantity,
+        cost_basis_per_share: lot.cost_basis_per_share,
+        proceeds_per_share: price,
+        realized_pnl: (price - lot.cost_basis_per_share) * fill.quantity,
+        opened_at: lot.opened_at,
+        sold_at,
+        holding_days,
+        term: if holding_days > LONG_TERM_DAYS {
+            TaxTerm::LongTerm
+        } else {
+            TaxTerm::ShortTerm
+        },
+        method,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RealizedSummary {
+    pub sales: usize,
+    pub proceeds: f64,
+    pub cost_basis: f64,
+    pub realized_pnl: f64,
+    pub short_term_pnl: f64,
+    pub long_term_pnl: f64,
+}
+
+pub fn summarize<'a>(gains: impl IntoIterator<Item = &'a RealizedGain>) -> RealizedSummary {
+    let mut s = RealizedSummary::default();
+    for g in gains {
+        s.sales += 1;
+        s.proceeds += g.proceeds_per_share * g.quantity;
+        s.cost_basis += g.cost_basis_per_share * g.quantity;
+        s.realized_pnl += g.realized_pnl;
+        match g.term {
+            TaxTerm::ShortTerm => s.short_term_pnl += g.realized_pnl,
+            TaxTerm::LongTerm => s.long_term_pnl += g.realized_pnl,
+        }
+    }
+    s
+}
+
+pub fn in_year(gain: &RealizedGain, year: Option<i32>) -> bool {
+    year.is_none_or(|y| gain.sold_at.year() == y)
+}
+
+/// Yearly realized-gains report, one row per lot sale, in a layout close to
+/// a Form 8949 worksheet.
+pub fn to_csv(gains: &[RealizedGain]) -> String {
+    let mut out = String::from(
+        "symbol,quantity,date_acquired,date_sold,proceeds,cost_basis,gain_loss,term,method\n",
+    );
+    for g in gains {
+        out.push_str(&format!(
+            "{},{},{},{},{:.2},{:.2},{:.2},{},{}\n",
+            g.symbol,
+            g.quantity,
+            g.opened_at.format("%Y-%m-%d"),
+            g.sold_at.format("%Y-%m-%d"),
+            g.proceeds_per_share * g.quantity,
+            g.cost_basis_per_share * g.quantity,
+            g.realized_pnl,
+            g.term.as_str(),
+            g.method.as_str(),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn lot(qty: f64, cost: f64, year: i32) -> Position {
+        let opened = Utc.with_ymd_and_hms(year, 1, 15, 15, 0, 0).unwrap();
+        Position {
+            id: Some(ObjectId::new()),
+            symbol: "AAPL".to_string(),
+            quantity: qty,
+            cost_basis_per_share: cost,
+            opened_at: opened,
+            notes: None,
+            created_at: opened,
+            updated_at: opened,
+        }
+    }
+
+    #[test]
+    fn fifo_and_lifo_pick_opposite_ends() {
+        let lots = vec![lot(10.0, 100.0, 2022), lot(10.0, 150.0, 2024)];
+
+        let fifo = select_lots(&lots, 15.0, LotMethod::Fifo, &[]).unwrap();
+        assert_eq!(fifo.len(), 2);
+        assert_eq!(fifo[0].position_id, lots[0].id.unwrap());
+        assert_eq!((fifo[0].quantity, fifo[0].remaining), (10.0, 0.0));
+        assert_eq!((fifo[1].quantity, fifo[1].remaining), (5.0, 5.0));
+
+        let lifo = select_lots(&lots, 5.0, LotMethod::Lifo, &[]).unwrap();
+        assert_eq!(lifo.len(), 1);
+        assert_eq!(lifo[0].position_id, lots[1].id.unwrap());
+    }
+
+    #[test]
+    fn specific_lots_and_oversell_are_validated() {
+        let lots = vec![lot(10.0, 100.0, 2022), lot(10.0, 150.0, 2024)];
+        let picked = select_lots(&lots, 4.0, LotMethod::Specific, &[lots[1].id.unwrap()]).unwrap();
+        assert_eq!(picked[0].position_id, lots[1].id.unwrap());
+
+        assert!(select_lots(&lots, 4.0, LotMethod::Specific, &[]).is_err());
+        assert!(select_lots(&lots, 4.0, LotMethod::Specific, &[ObjectId::new()]).is_err());
+        // Naming a lot twice would fill it twice.
+        let twice = [lots[0].id.unwrap(), lots[0].id.unwrap()];
+        assert!(select_lots(&lots, 15.0, LotMethod::Specific, &twice).is_err());
+        assert!(select_lots(&lots, 5.0, LotMethod::Specific, &twice).is_err());
+        assert!(select_lots(&lots, 25.0, LotMethod::Fifo, &[]).is_err());
+        assert!(select_lots(&lots, 0.0, LotMethod::Fifo, &[]).is_err());
+    }
+
+    #[test]
+    fn realized_gains_split_by_term_and_export() {
+        let old = lot(10.0, 100.0, 2022);
+        let new = lot(10.0, 150.0, 2025);
+        let sold_at = Utc.with_ymd_and_hms(2025, 6, 1, 15, 0, 0).unwrap();
+        let fill = |p: &Position| LotFill {
+            position_id: p.id.unwrap(),
+            quantity: 10.0,
+            remaining: 0.0,
+        };
+        let gains = vec![
+            realize(&old, &fill(&old), 140.0, sold_at, LotMethod::Fifo),
+            realize(&new, &fill(&new), 140.0, sold_at, LotMethod::Fifo),
+        ];
+        assert_eq!(gains[0].term, TaxTerm::LongTerm);
+        assert_eq!(gains[1].term, TaxTerm::ShortTerm);
+
+        let summary = summarize(&gains);
+        assert_eq!(summary.realized_pnl, 300.0);
+        assert_eq!(summary.long_term_pnl, 400.0);
+        assert_eq!(summary.short_term_pnl, -100.0);
+        assert!(gains.iter().all(|g| in_year(g, Some(2025))));
+        assert!(!in_year(&gains[0], Some(2024)));
+
+        let csv = to_csv(&gains);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("AAPL,10,2022-01-15,2025-06-01,1400.00,1000.00,400.00,long_term,fifo"));
+    }
+}
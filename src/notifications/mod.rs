@@ -15,12 +15,17 @@
 //! one symbol at a time.
 
 pub mod api;
+pub mod brokers;
 pub mod channels;
 pub mod dispatcher;
 pub mod evaluator;
+pub mod lots;
 pub mod models;
+pub mod rebalance;
+pub mod recap;
 pub mod repo;
 pub mod rules;
+pub mod valuation;
 
 use std::sync::Arc;
 
@@ -192,6 +197,31 @@ impl AlertEngine {
         self.inner.dispatcher.dispatch_all(pending).await
     }
 
+    /// Evaluate only the rules with an `external_signal` condition, for an
+    /// analysis that just received one. The other rules wait for the next
+    /// cycle, so their hysteresis counts don't advance twice.
+    pub async fn evaluate_external_signal(&self, analysis: &StockAnalysis) -> Result<()> {
+        if !self.inner.enabled {
+            return Ok(());
+        }
+        let pending = self
+            .inner
+            .evaluator
+            .evaluate_rules(std::slice::from_ref(analysis), |rule| {
+                rule.conditions.uses_external_signal()
+            })
+            .await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "🔔 {} notifications queued for dispatch ({}, external signal)",
+            pending.len(),
+            analysis.symbol
+        );
+        self.inner.dispatcher.dispatch_all(pending).await
+    }
+
     /// Test a rule end-to-end against a caller-supplied snapshot.
     pub async fn test_rule(&self, pending: PendingNotification) -> Result<Vec<DeliveryResult>> {
         self.inner.dispatcher.dispatch_test(pending).await
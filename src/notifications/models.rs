@@ -10,6 +10,7 @@
 use chrono::{DateTime, Utc};
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationErrors};
 
 use crate::models::StockAnalysis;
 
@@ -59,6 +60,9 @@ pub struct NotificationChannel {
     #[serde(flatten)]
     pub config: ChannelConfig,
     pub enabled: bool,
+    /// Also receives the end-of-day watchlist recap (see `recap.rs`).
+    #[serde(default)]
+    pub daily_recap: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -100,7 +104,7 @@ pub struct Position {
 
 /// Position + computed P&L for client responses. Computed fields are
 /// `Option` because the latest analysis may not be in the cache yet.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionView {
     #[serde(flatten)]
     pub position: Position,
@@ -135,6 +139,102 @@ impl PositionView {
     }
 }
 
+/// How a sell picks which lots (positions) to close.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LotMethod {
+    #[default]
+    Fifo,
+    Lifo,
+    /// Caller lists the lot ids to sell from, in order.
+    Specific,
+}
+
+impl LotMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LotMethod::Fifo => "fifo",
+            LotMethod::Lifo => "lifo",
+            LotMethod::Specific => "specific",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxTerm {
+    ShortTerm,
+    LongTerm,
+}
+
+impl TaxTerm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaxTerm::ShortTerm => "short_term",
+            TaxTerm::LongTerm => "long_term",
+        }
+    }
+}
+
+/// One lot's share of a sell. Persisted in `realized_gains`; the lot itself
+/// shrinks or is deleted from `positions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealizedGain {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub symbol: String,
+    /// The lot (position) the shares came from.
+    pub position_id: ObjectId,
+    pub quantity: f64,
+    pub cost_basis_per_share: f64,
+    pub proceeds_per_share: f64,
+    pub realized_pnl: f64,
+    pub opened_at: DateTime<Utc>,
+    pub sold_at: DateTime<Utc>,
+    pub holding_days: i64,
+    pub term: TaxTerm,
+    pub method: LotMethod,
+}
+
+/// A broker-export row already applied by `POST /api/positions/import`, so
+/// importing the same file again skips it. Persisted in `position_imports`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionImport {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    /// `ImportedTrade::import_key` / `ImportedPosition::import_key`.
+    pub key: String,
+    pub imported_at: DateTime<Utc>,
+}
+
+/// One symbol's line in a valuation snapshot (lots merged).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotHolding {
+    pub symbol: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub market_value: f64,
+    pub cost_basis: f64,
+}
+
+/// End-of-day value of all tracked positions. Persisted in
+/// `position_snapshots`, one document per market date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValuationSnapshot {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    /// New York market date (`YYYY-MM-DD`).
+    pub date: String,
+    pub total_value: f64,
+    /// Cost basis of the priced holdings only, so it lines up with `total_value`.
+    pub total_cost_basis: f64,
+    pub holdings: Vec<SnapshotHolding>,
+    /// Symbols with no cached price; left out of the totals.
+    #[serde(default)]
+    pub unpriced: Vec<String>,
+    pub taken_at: DateTime<Utc>,
+}
+
 // ---------------------------------------------------------------------------
 // Alert rules: conditions, scope, quiet hours
 // ---------------------------------------------------------------------------
@@ -197,6 +297,42 @@ pub enum Condition {
     DropFromHighPct {
         value: f64,
     },
+    /// Next earnings report falls within the next `days` calendar days
+    /// (0 = today). Driven by `StockAnalysis.earnings.earnings_date`.
+    EarningsWithinDays {
+        days: u32,
+    },
+    /// Ex-dividend date falls within the next `days` calendar days
+    /// (1 = tomorrow). Driven by `technicals.ex_dividend_date`.
+    ExDividendWithinDays {
+        days: u32,
+    },
+    /// Trailing stop: `(peak - price) / peak * 100 >= value`, where `peak` is
+    /// the highest price seen for this rule+symbol since the rule started
+    /// tracking it (via `alert_state.peak_price`).
+    TrailingStopPct {
+        value: f64,
+    },
+    /// `|price - sma| / sma * 100 <= within_pct` for the `period`-day SMA.
+    /// Only the configured short and long SMA periods (`INDICATOR_SMA_SHORT`,
+    /// `INDICATOR_SMA_LONG`) and 200 match; other periods never do.
+    PriceNearSma {
+        period: u32,
+        within_pct: f64,
+    },
+    /// `volume / technicals.average_volume >= value` — e.g. `1.5` for
+    /// "volume at least 1.5× average".
+    VolumeRatioAbove {
+        value: f64,
+    },
+    /// An external signal (see `ingest.rs`) arrived within the last
+    /// `within_minutes`, optionally only one labelled `signal` (e.g. `buy`,
+    /// case-insensitive).
+    ExternalSignal {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signal: Option<String>,
+        within_minutes: u32,
+    },
 }
 
 /// AND/OR/NOT tree of conditions. Stored as JSON under `conditions`.
@@ -209,6 +345,119 @@ pub enum ConditionGroup {
     Leaf { condition: Condition },
 }
 
+impl ConditionGroup {
+    /// True if any leaf in the tree satisfies `pred`.
+    pub fn any_condition(&self, pred: &impl Fn(&Condition) -> bool) -> bool {
+        match self {
+            ConditionGroup::And { children } | ConditionGroup::Or { children } => {
+                children.iter().any(|c| c.any_condition(pred))
+            }
+            ConditionGroup::Not { child } => child.any_condition(pred),
+            ConditionGroup::Leaf { condition } => pred(condition),
+        }
+    }
+
+    pub fn uses_trailing_stop(&self) -> bool {
+        self.any_condition(&|c| matches!(c, Condition::TrailingStopPct { .. }))
+    }
+
+    pub fn uses_external_signal(&self) -> bool {
+        self.any_condition(&|c| matches!(c, Condition::ExternalSignal { .. }))
+    }
+
+    pub fn uses_earnings(&self) -> bool {
+        self.any_condition(&|c| matches!(c, Condition::EarningsWithinDays { .. }))
+    }
+}
+
+/// Errors are reported by path, e.g. `children[1].condition.value`.
+impl Validate for ConditionGroup {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        match self {
+            ConditionGroup::And { children } | ConditionGroup::Or { children } => {
+                errors.merge_self("children", children.validate());
+            }
+            ConditionGroup::Not { child } => {
+                errors.merge_self("child", child.validate());
+            }
+            ConditionGroup::Leaf { condition } => {
+                errors.merge_self("condition", condition.validate());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Validate for Condition {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        use crate::validation::error;
+
+        let percent = |v: f64| {
+            (0.0..=100.0)
+                .contains(&v)
+                .then_some(())
+                .ok_or_else(|| error("range", "must be between 0 and 100"))
+        };
+        let non_negative = |v: f64| {
+            (v.is_finite() && v >= 0.0)
+                .then_some(())
+                .ok_or_else(|| error("range", "must be at least 0"))
+        };
+        let checks = match self {
+            Condition::RsiBelow { value }
+            | Condition::RsiAbove { value }
+            | Condition::StochasticKBelow { value }
+            | Condition::StochasticKAbove { value }
+            | Condition::TrailingStopPct { value } => vec![("value", percent(*value))],
+            Condition::PriceBelow { value }
+            | Condition::PriceAbove { value }
+            | Condition::BollingerBandwidthBelow { value }
+            | Condition::VolumeAbove { value }
+            | Condition::DropFromHighPct { value }
+            | Condition::VolumeRatioAbove { value } => vec![("value", non_negative(*value))],
+            Condition::Near52WeekLow { within_pct } | Condition::Near52WeekHigh { within_pct } => {
+                vec![("within_pct", non_negative(*within_pct))]
+            }
+            Condition::PriceNearSma { period, within_pct } => vec![
+                (
+                    "period",
+                    (*period > 0)
+                        .then_some(())
+                        .ok_or_else(|| error("range", "must be at least 1")),
+                ),
+                ("within_pct", non_negative(*within_pct)),
+            ],
+            Condition::SectorEquals { sector } => {
+                vec![("sector", crate::validation::not_blank(sector))]
+            }
+            Condition::ExternalSignal { within_minutes, .. } => vec![(
+                "within_minutes",
+                (*within_minutes > 0)
+                    .then_some(())
+                    .ok_or_else(|| error("range", "must be at least 1")),
+            )],
+            _ => Vec::new(),
+        };
+
+        let mut errors = ValidationErrors::new();
+        for (field, check) in checks {
+            if let Err(e) = check {
+                errors.add(field, e);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 /// What set of symbols this rule applies to.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -225,9 +474,11 @@ pub enum AlertScope {
 
 /// UTC quiet-hour window. Start/end are hours `0..24`. If `start_hour > end_hour`
 /// the window wraps midnight (e.g. 22..7).
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Validate)]
 pub struct QuietHours {
+    #[validate(range(max = 23))]
     pub start_hour: u8,
+    #[validate(range(max = 23))]
     pub end_hour: u8,
     /// Timezone name (e.g. "UTC", "America/New_York"). Stored for future use;
     /// current evaluation is in UTC.
@@ -256,6 +507,19 @@ pub struct AlertRule {
     pub message_template: Option<String>,
     #[serde(default = "default_require_consecutive")]
     pub require_consecutive: u32,
+    /// Suppress the rule until this instant. Cross-detection state keeps
+    /// updating while snoozed, same as quiet hours.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// Disable the rule after this many successful deliveries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_triggers: Option<u32>,
+    /// Successful deliveries since creation or the last reset.
+    #[serde(default)]
+    pub trigger_count: u32,
+    /// Symbols excluded from this rule regardless of scope.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub muted_symbols: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -264,6 +528,80 @@ fn default_require_consecutive() -> u32 {
     1
 }
 
+impl AlertRule {
+    pub fn is_snoozed(&self, now: DateTime<Utc>) -> bool {
+        self.snoozed_until.is_some_and(|until| until > now)
+    }
+
+    /// True once `trigger_count` has reached `max_triggers`.
+    pub fn is_exhausted(&self) -> bool {
+        self.max_triggers
+            .is_some_and(|max| self.trigger_count >= max)
+    }
+
+    pub fn is_muted(&self, symbol: &str) -> bool {
+        self.muted_symbols.iter().any(|s| s == symbol)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Alert audit trail (lifecycle changes + firings, per rule)
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertAuditAction {
+    Created,
+    Updated,
+    Enabled,
+    Disabled,
+    Snoozed,
+    Unsnoozed,
+    Muted,
+    Unmuted,
+    Triggered,
+    /// Auto-disabled after reaching `max_triggers`.
+    Exhausted,
+    Reset,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertAuditEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub rule_id: ObjectId,
+    pub action: AlertAuditAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl AlertAuditEvent {
+    pub fn new(rule_id: ObjectId, action: AlertAuditAction) -> Self {
+        AlertAuditEvent {
+            id: None,
+            rule_id,
+            action,
+            symbol: None,
+            detail: None,
+            at: Utc::now(),
+        }
+    }
+
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Alert state (per-rule-per-symbol cooldown / hysteresis / cross detection)
 // ---------------------------------------------------------------------------
@@ -283,6 +621,10 @@ pub struct AlertState {
     /// Histogram from the previous evaluation — needed for MACD cross detection.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_macd_histogram: Option<f64>,
+    /// Highest price observed for this rule+symbol. Only tracked for rules
+    /// with a trailing-stop condition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_price: Option<f64>,
 }
 
 impl AlertState {
@@ -295,6 +637,14 @@ impl AlertState {
             last_matched_at: None,
             consecutive_matches: 0,
             last_macd_histogram: None,
+            peak_price: None,
+        }
+    }
+
+    /// Raise the tracked peak to `price` if it's a new high.
+    pub fn observe_price(&mut self, price: f64) {
+        if price.is_finite() && price > 0.0 && self.peak_price.is_none_or(|p| price > p) {
+            self.peak_price = Some(price);
         }
     }
 }
@@ -349,44 +699,56 @@ pub struct PendingNotification {
 // API input DTOs (split from persisted model to avoid leaking _id/timestamps)
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateChannelInput {
     pub name: String,
     #[serde(flatten)]
     pub config: ChannelConfig,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    #[serde(default)]
+    pub daily_recap: bool,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateChannelInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<ChannelConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_recap: Option<bool>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateWatchlistInput {
     pub name: String,
     #[serde(default)]
     pub symbols: Vec<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateWatchlistInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub symbols: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddSymbolInput {
     pub symbol: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreatePositionInput {
+    #[validate(custom(function = "crate::validation::not_blank"))]
     pub symbol: String,
+    #[validate(custom(function = "crate::validation::non_zero"))]
     pub quantity: f64,
+    #[validate(range(min = 0.0))]
     pub cost_basis_per_share: f64,
     /// When the user opened the position. Defaults to `now()` if omitted.
     #[serde(default)]
@@ -395,44 +757,113 @@ pub struct CreatePositionInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct UpdatePositionInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::validation::non_zero"))]
     pub quantity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0.0))]
     pub cost_basis_per_share: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub opened_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SellPositionInput {
+    #[validate(custom(function = "crate::validation::not_blank"))]
+    pub symbol: String,
+    #[validate(range(exclusive_min = 0.0))]
+    pub quantity: f64,
+    /// Sale price per share.
+    #[validate(range(min = 0.0))]
+    pub price: f64,
+    #[serde(default)]
+    pub method: LotMethod,
+    /// Required for `method: specific`.
+    #[serde(default)]
+    pub lot_ids: Vec<ObjectId>,
+    /// Defaults to `now()`.
+    #[serde(default)]
+    pub sold_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateAlertRuleInput {
+    #[validate(custom(function = "crate::validation::not_blank"))]
     pub name: String,
     #[serde(default = "default_true")]
     pub enabled: bool,
     pub scope: AlertScope,
+    #[validate(nested)]
     pub conditions: ConditionGroup,
     #[serde(default)]
     pub cooldown_minutes: u32,
     #[serde(default)]
+    #[validate(nested)]
     pub quiet_hours: Option<QuietHours>,
     #[serde(default)]
+    #[validate(length(min = 1, message = "at least one notification channel is required"))]
     pub channel_ids: Vec<ObjectId>,
     #[serde(default)]
     pub message_template: Option<String>,
     #[serde(default = "default_require_consecutive")]
     pub require_consecutive: u32,
+    #[serde(default)]
+    pub max_triggers: Option<u32>,
+    #[serde(default)]
+    pub muted_symbols: Vec<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct UpdateAlertRuleInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::validation::not_blank"))]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub scope: Option<AlertScope>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
     pub conditions: Option<ConditionGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cooldown_minutes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
     pub quiet_hours: Option<Option<QuietHours>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "at least one notification channel is required"))]
     pub channel_ids: Option<Vec<ObjectId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub message_template: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub require_consecutive: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_triggers: Option<Option<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub muted_symbols: Option<Vec<String>>,
+}
+
+/// Body for `POST /api/alerts/rules/:id/snooze`. Exactly one of `until` or
+/// `minutes` is expected; `until` wins if both are present.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnoozeRuleInput {
+    pub until: Option<DateTime<Utc>>,
+    pub minutes: Option<u32>,
+}
+
+impl SnoozeRuleInput {
+    pub fn resolve(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.until
+            .or_else(|| {
+                self.minutes
+                    .map(|m| now + chrono::Duration::minutes(m as i64))
+            })
+            .filter(|until| *until > now)
+    }
 }
 
 fn default_true() -> bool {
@@ -457,6 +888,126 @@ mod tests {
         }
     }
 
+    fn rule() -> AlertRule {
+        let now = Utc::now();
+        AlertRule {
+            id: None,
+            name: "r".to_string(),
+            enabled: true,
+            scope: AlertScope::AllWatched,
+            conditions: ConditionGroup::Leaf {
+                condition: Condition::RsiBelow { value: 30.0 },
+            },
+            cooldown_minutes: 0,
+            quiet_hours: None,
+            channel_ids: vec![],
+            message_template: None,
+            require_consecutive: 1,
+            snoozed_until: None,
+            max_triggers: None,
+            trigger_count: 0,
+            muted_symbols: vec![],
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn rule_lifecycle_gates() {
+        let now = Utc::now();
+        let mut r = rule();
+        assert!(!r.is_snoozed(now));
+        assert!(!r.is_exhausted());
+
+        r.snoozed_until = Some(now + chrono::Duration::minutes(5));
+        assert!(r.is_snoozed(now));
+        assert!(!r.is_snoozed(now + chrono::Duration::minutes(6)));
+
+        r.max_triggers = Some(2);
+        r.trigger_count = 1;
+        assert!(!r.is_exhausted());
+        r.trigger_count = 2;
+        assert!(r.is_exhausted());
+
+        r.muted_symbols = vec!["TSLA".to_string()];
+        assert!(r.is_muted("TSLA"));
+        assert!(!r.is_muted("AAPL"));
+    }
+
+    #[test]
+    fn peak_price_only_ratchets_up() {
+        let mut state = AlertState::new(ObjectId::new(), "AAPL".to_string());
+        state.observe_price(100.0);
+        state.observe_price(120.0);
+        state.observe_price(90.0);
+        state.observe_price(0.0);
+        assert_eq!(state.peak_price, Some(120.0));
+    }
+
+    #[test]
+    fn finds_trailing_stop_in_nested_groups() {
+        let nested = ConditionGroup::And {
+            children: vec![
+                ConditionGroup::Leaf {
+                    condition: Condition::RsiBelow { value: 30.0 },
+                },
+                ConditionGroup::Not {
+                    child: Box::new(ConditionGroup::Leaf {
+                        condition: Condition::TrailingStopPct { value: 10.0 },
+                    }),
+                },
+            ],
+        };
+        assert!(nested.uses_trailing_stop());
+        assert!(!rule().conditions.uses_trailing_stop());
+        assert!(!nested.uses_earnings());
+        let earnings = ConditionGroup::Not {
+            child: Box::new(ConditionGroup::Leaf {
+                condition: Condition::EarningsWithinDays { days: 3 },
+            }),
+        };
+        assert!(earnings.uses_earnings());
+    }
+
+    #[test]
+    fn external_signal_label_is_optional() {
+        let any: Condition =
+            serde_json::from_str(r#"{"type":"external_signal","within_minutes":60}"#).unwrap();
+        assert_eq!(
+            any,
+            Condition::ExternalSignal {
+                signal: None,
+                within_minutes: 60
+            }
+        );
+        let group = ConditionGroup::Leaf { condition: any };
+        assert!(group.uses_external_signal());
+        assert!(!rule().conditions.uses_external_signal());
+    }
+
+    #[test]
+    fn snooze_input_resolves_minutes_and_rejects_past() {
+        let now = Utc::now();
+        let by_minutes = SnoozeRuleInput {
+            until: None,
+            minutes: Some(30),
+        };
+        assert_eq!(
+            by_minutes.resolve(now),
+            Some(now + chrono::Duration::minutes(30))
+        );
+        let past = SnoozeRuleInput {
+            until: Some(now - chrono::Duration::minutes(1)),
+            minutes: None,
+        };
+        assert!(past.resolve(now).is_none());
+        let empty = SnoozeRuleInput {
+            until: None,
+            minutes: None,
+        };
+        assert!(empty.resolve(now).is_none());
+    }
+
     #[test]
     fn position_view_with_price_computes_pnl() {
         let view = PositionView::from_position(position(10.0, 100.0), Some(110.0));
@@ -490,4 +1041,55 @@ mod tests {
         assert_eq!(view.unrealized_pnl, Some(500.0));
         assert!(view.unrealized_pnl_pct.is_none(), "no pct when basis is 0");
     }
+
+    #[test]
+    fn rule_input_validation_reports_paths() {
+        let input: CreateAlertRuleInput = serde_json::from_value(serde_json::json!({
+            "name": " ",
+            "scope": { "type": "all_watched" },
+            "conditions": { "op": "and", "children": [
+                { "op": "leaf", "condition": { "type": "rsi_below", "value": 30 } },
+                { "op": "not", "child":
+                    { "op": "leaf", "condition": { "type": "rsi_above", "value": 140 } } }
+            ]},
+            "quiet_hours": { "start_hour": 22, "end_hour": 24 }
+        }))
+        .unwrap();
+        let fields: Vec<String> = crate::validation::field_errors(&input.validate().unwrap_err())
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "channel_ids",
+                "conditions.children[1].child.condition.value",
+                "name",
+                "quiet_hours.end_hour",
+            ]
+        );
+
+        let update: UpdateAlertRuleInput =
+            serde_json::from_value(serde_json::json!({ "quiet_hours": null })).unwrap();
+        assert!(update.validate().is_ok());
+    }
+
+    #[test]
+    fn position_inputs_reject_bad_numbers() {
+        let sell: SellPositionInput = serde_json::from_value(
+            serde_json::json!({ "symbol": "AAPL", "quantity": 0, "price": -1 }),
+        )
+        .unwrap();
+        let fields: Vec<String> = crate::validation::field_errors(&sell.validate().unwrap_err())
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["price", "quantity"]);
+
+        let create: CreatePositionInput = serde_json::from_value(
+            serde_json::json!({ "symbol": "AAPL", "quantity": -5, "cost_basis_per_share": 10 }),
+        )
+        .unwrap();
+        assert!(create.validate().is_ok(), "short positions are allowed");
+    }
 }
@@ -0,0 +1,210 @@
+//! Rebalancing suggestions for tracked positions.
+//!
+//! Pure math over per-symbol holdings: the API layer aggregates position lots
+//! by symbol, joins prices and sectors from the stock cache, and hands the
+//! result to [`plan`]. Trades are whole shares, so the result lands close to
+//! (not exactly on) the target weights.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RebalanceTarget {
+    /// Every holding gets `1 / n` of the portfolio.
+    #[default]
+    EqualWeight,
+    /// Every sector gets `1 / sectors`, split equally among its holdings.
+    SectorNeutral,
+}
+
+/// One symbol's aggregate position.
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub symbol: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub sector: Option<String>,
+}
+
+/// Per-trade cost estimate: flat commission plus slippage on traded value.
+#[derive(Debug, Clone, Copy)]
+pub struct CostModel {
+    pub commission_per_trade: f64,
+    pub slippage_bps: f64,
+}
+
+impl CostModel {
+    fn estimate(&self, trade_value: f64) -> f64 {
+        self.commission_per_trade + trade_value.abs() * self.slippage_bps / 10_000.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceTrade {
+    pub symbol: String,
+    pub side: TradeSide,
+    pub shares: f64,
+    pub price: f64,
+    pub trade_value: f64,
+    pub current_weight_pct: f64,
+    pub target_weight_pct: f64,
+    pub estimated_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalancePlan {
+    pub target: RebalanceTarget,
+    pub total_value: f64,
+    pub trades: Vec<RebalanceTrade>,
+    pub estimated_total_cost: f64,
+    /// Traded value (buys + sells) as % of the portfolio.
+    pub turnover_pct: f64,
+}
+
+const UNKNOWN_SECTOR: &str = "Unknown";
+
+fn target_weights(holdings: &[Holding], target: RebalanceTarget) -> Vec<f64> {
+    let n = holdings.len() as f64;
+    match target {
+        RebalanceTarget::EqualWeight => vec![1.0 / n; holdings.len()],
+        RebalanceTarget::SectorNeutral => {
+            let mut per_sector: BTreeMap<&str, usize> = BTreeMap::new();
+            for h in holdings {
+                *per_sector
+                    .entry(h.sector.as_deref().unwrap_or(UNKNOWN_SECTOR))
+                    .or_default() += 1;
+            }
+            let sectors = per_sector.len() as f64;
+            holdings
+                .iter()
+                .map(|h| {
+                    let members = per_sector[h.sector.as_deref().unwrap_or(UNKNOWN_SECTOR)];
+                    1.0 / sectors / members as f64
+                })
+                .collect()
+        }
+    }
+}
+
+/// Trades that move `holdings` toward `target`. Holdings without a usable
+/// price should be filtered out by the caller; they can't be valued.
+pub fn plan(holdings: &[Holding], target: RebalanceTarget, costs: CostModel) -> RebalancePlan {
+    let total_value: f64 = holdings.iter().map(|h| h.quantity * h.price).sum();
+    let mut trades = Vec::new();
+
+    if total_value > 0.0 {
+        for (h, weight) in holdings.iter().zip(target_weights(holdings, target)) {
+            let current_value = h.quantity * h.price;
+            let delta_shares = ((total_value * weight - current_value) / h.price).trunc();
+            if delta_shares == 0.0 {
+                continue;
+            }
+            let trade_value = delta_shares.abs() * h.price;
+            trades.push(RebalanceTrade {
+                symbol: h.symbol.clone(),
+                side: if delta_shares > 0.0 {
+                    TradeSide::Buy
+                } else {
+                    TradeSide::Sell
+                },
+                shares: delta_shares.abs(),
+                price: h.price,
+                trade_value,
+                current_weight_pct: current_value / total_value * 100.0,
+                target_weight_pct: weight * 100.0,
+                estimated_cost: costs.estimate(trade_value),
+            });
+        }
+    }
+
+    let traded: f64 = trades.iter().map(|t| t.trade_value).sum();
+    RebalancePlan {
+        target,
+        total_value,
+        estimated_total_cost: trades.iter().map(|t| t.estimated_cost).sum(),
+        turnover_pct: if total_value > 0.0 {
+            traded / total_value * 100.0
+        } else {
+            0.0
+        },
+        trades,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(symbol: &str, quantity: f64, price: f64, sector: &str) -> Holding {
+        Holding {
+            symbol: symbol.to_string(),
+            quantity,
+            price,
+            sector: Some(sector.to_string()),
+        }
+    }
+
+    const FREE: CostModel = CostModel {
+        commission_per_trade: 0.0,
+        slippage_bps: 0.0,
+    };
+
+    #[test]
+    fn equal_weight_moves_value_from_winner_to_laggard() {
+        // 6000 in AAPL, 2000 in XOM → 4000 each.
+        let holdings = vec![
+            holding("AAPL", 30.0, 200.0, "Technology"),
+            holding("XOM", 20.0, 100.0, "Energy"),
+        ];
+        let plan = plan(&holdings, RebalanceTarget::EqualWeight, FREE);
+        assert_eq!(plan.total_value, 8000.0);
+        assert_eq!(plan.trades.len(), 2);
+        let aapl = &plan.trades[0];
+        assert_eq!((aapl.side, aapl.shares), (TradeSide::Sell, 10.0));
+        let xom = &plan.trades[1];
+        assert_eq!((xom.side, xom.shares), (TradeSide::Buy, 20.0));
+        assert_eq!(plan.turnover_pct, 50.0);
+    }
+
+    #[test]
+    fn sector_neutral_splits_sector_share_among_members() {
+        let holdings = vec![
+            holding("AAPL", 10.0, 100.0, "Technology"),
+            holding("MSFT", 10.0, 100.0, "Technology"),
+            holding("XOM", 10.0, 100.0, "Energy"),
+        ];
+        let plan = plan(&holdings, RebalanceTarget::SectorNeutral, FREE);
+        // Energy should be 50% (1500), each tech name 25% (750).
+        let xom = plan.trades.iter().find(|t| t.symbol == "XOM").unwrap();
+        assert_eq!((xom.side, xom.shares), (TradeSide::Buy, 5.0));
+        assert_eq!(xom.target_weight_pct, 50.0);
+        let aapl = plan.trades.iter().find(|t| t.symbol == "AAPL").unwrap();
+        assert_eq!((aapl.side, aapl.shares), (TradeSide::Sell, 2.0));
+    }
+
+    #[test]
+    fn balanced_portfolio_needs_no_trades_and_costs_add_up() {
+        let balanced = vec![holding("A", 10.0, 50.0, "X"), holding("B", 5.0, 100.0, "Y")];
+        assert!(plan(&balanced, RebalanceTarget::EqualWeight, FREE)
+            .trades
+            .is_empty());
+
+        let skewed = vec![holding("A", 30.0, 50.0, "X"), holding("B", 5.0, 100.0, "Y")];
+        let costs = CostModel {
+            commission_per_trade: 1.0,
+            slippage_bps: 10.0,
+        };
+        let plan = plan(&skewed, RebalanceTarget::EqualWeight, costs);
+        // Sell 10 A ($500), buy 5 B ($500): 2 × $1 + 0.1% of $1000.
+        assert!((plan.estimated_total_cost - 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,271 @@
+//! End-of-day watchlist recap.
+//!
+//! Shortly after the New York close, each watchlist is summarized: every
+//! symbol's close and day change, the built-in strategies (`signals.rs`) it
+//! fires, the alert rules that notified for it during the session, and its
+//! top news headline. The recap goes to every enabled channel flagged
+//! `daily_recap`. `GET /api/watchlists/:id/recap` previews one and
+//! `POST /api/watchlists/:id/recap` sends it immediately.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::America::New_York;
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::models::Watchlist;
+use super::AlertEngine;
+use crate::cache::CacheLayer;
+use crate::models::{NasdaqNewsItem, StockAnalysis};
+use crate::signals::Strategy;
+
+/// One watchlist symbol in the recap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecapEntry {
+    pub symbol: String,
+    pub close: f64,
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    /// Built-in strategies firing on the latest analysis.
+    pub signals: Vec<Strategy>,
+    /// Alert rules that notified for this symbol during the session.
+    pub alerts: Vec<String>,
+    pub top_news: Option<NasdaqNewsItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistRecap {
+    pub watchlist_id: Option<ObjectId>,
+    pub watchlist: String,
+    /// New York market date (`YYYY-MM-DD`).
+    pub date: String,
+    /// Biggest movers first.
+    pub entries: Vec<RecapEntry>,
+    /// Watchlist symbols with no analysis yet.
+    pub missing: Vec<String>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// The New York market date `now` falls on.
+pub fn market_date(now: DateTime<Utc>) -> NaiveDate {
+    now.with_timezone(&New_York).date_naive()
+}
+
+/// Midnight New York time at the start of `date`, in UTC.
+fn session_start(date: NaiveDate) -> DateTime<Utc> {
+    New_York
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+/// Assemble the recap for `watchlist` from the latest analyses (keyed by
+/// symbol) and the `(symbol, rule_name)` notifications sent that session.
+pub fn build(
+    watchlist: &Watchlist,
+    analyses: &HashMap<String, Arc<StockAnalysis>>,
+    triggered: &[(String, String)],
+    now: DateTime<Utc>,
+) -> WatchlistRecap {
+    let mut entries = Vec::new();
+    let mut missing = Vec::new();
+    for symbol in &watchlist.symbols {
+        let Some(a) = analyses.get(symbol) else {
+            missing.push(symbol.clone());
+            continue;
+        };
+        let alerts: BTreeSet<&str> = triggered
+            .iter()
+            .filter(|(s, _)| s == symbol)
+            .map(|(_, rule)| rule.as_str())
+            .collect();
+        entries.push(RecapEntry {
+            symbol: symbol.clone(),
+            close: a.price,
+            change: a.price_change,
+            change_percent: a.price_change_percent,
+            signals: Strategy::ALL.into_iter().filter(|s| s.fires(a)).collect(),
+            alerts: alerts.into_iter().map(str::to_string).collect(),
+            top_news: a.news.as_ref().and_then(|n| n.first().cloned()),
+        });
+    }
+    entries.sort_by(|a, b| {
+        let abs = |e: &RecapEntry| e.change_percent.map(f64::abs).unwrap_or(-1.0);
+        abs(b).total_cmp(&abs(a))
+    });
+
+    WatchlistRecap {
+        watchlist_id: watchlist.id,
+        watchlist: watchlist.name.clone(),
+        date: market_date(now).format("%Y-%m-%d").to_string(),
+        entries,
+        missing,
+        generated_at: now,
+    }
+}
+
+/// Build today's recap for `watchlist` from the stock cache and the
+/// notification history.
+pub async fn generate(
+    engine: &AlertEngine,
+    cache: &CacheLayer,
+    watchlist: &Watchlist,
+) -> anyhow::Result<WatchlistRecap> {
+    let now = Utc::now();
+    let triggered = engine
+        .repo()
+        .triggered_since(session_start(market_date(now)))
+        .await?;
+    let mut analyses = HashMap::new();
+    for symbol in &watchlist.symbols {
+        if let Some(a) = cache.get_stock(symbol).await {
+            analyses.insert(symbol.clone(), a);
+        }
+    }
+    Ok(build(watchlist, &analyses, &triggered, now))
+}
+
+/// Send today's recap of every non-empty watchlist. Returns how many
+/// recaps reached at least one channel.
+pub async fn send_all(engine: &AlertEngine, cache: &CacheLayer) -> anyhow::Result<usize> {
+    let mut sent = 0;
+    for watchlist in engine.repo().list_watchlists().await? {
+        if watchlist.symbols.is_empty() {
+            continue;
+        }
+        let recap = generate(engine, cache, &watchlist).await?;
+        let delivered = engine.dispatcher().dispatch_recap(&recap).await?;
+        if delivered.iter().any(|d| d.ok) {
+            sent += 1;
+        }
+    }
+    Ok(sent)
+}
+
+/// Time until the next weekday 16:00 New York plus `minutes_after_close`
+/// (holidays are not modelled).
+fn until_next_run(now: DateTime<Utc>, minutes_after_close: u32) -> std::time::Duration {
+    let close = NaiveTime::from_hms_opt(16, 0, 0).expect("valid time");
+    let mut date = market_date(now);
+    loop {
+        let at = New_York
+            .from_local_datetime(&date.and_time(close))
+            .earliest()
+            .map(|t| t.with_timezone(&Utc) + Duration::minutes(minutes_after_close as i64));
+        if let Some(at) = at {
+            if at > now && !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                return (at - now).to_std().unwrap_or_default();
+            }
+        }
+        date = date.succ_opt().unwrap_or(date);
+    }
+}
+
+/// Send the recap `minutes_after_close` after every weekday close for the
+/// life of the process. Does nothing while the engine is disabled.
+pub fn spawn(engine: AlertEngine, cache: CacheLayer, minutes_after_close: u32) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(until_next_run(Utc::now(), minutes_after_close)).await;
+            if !engine.is_enabled() {
+                continue;
+            }
+            match send_all(&engine, &cache).await {
+                Ok(0) => {}
+                Ok(sent) => info!("📬 Sent end-of-day recap for {} watchlists", sent),
+                Err(e) => warn!("end-of-day recap failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StochasticOscillator;
+
+    fn analysis(symbol: &str, price: f64, change_pct: f64) -> StockAnalysis {
+        let mut a = StockAnalysis::sample(symbol, price);
+        a.analyzed_at = "2025-06-02T20:05:00Z".parse().unwrap();
+        a.price_change_percent = Some(change_pct);
+        a
+    }
+
+    #[test]
+    fn recap_orders_movers_and_collects_signals_alerts_news() {
+        let watchlist = Watchlist {
+            id: None,
+            name: "Core".to_string(),
+            symbols: vec!["AAPL".into(), "MSFT".into(), "NEW".into()],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let mut aapl = analysis("AAPL", 200.0, 0.5);
+        aapl.is_oversold = true;
+        aapl.news = Some(vec![NasdaqNewsItem {
+            title: "Apple beats".into(),
+            url: "https://example.com/a".into(),
+            publisher: None,
+            created: None,
+            ago: None,
+        }]);
+        let mut msft = analysis("MSFT", 400.0, -3.2);
+        msft.stochastic = Some(StochasticOscillator {
+            k_line: 15.0,
+            d_line: 18.0,
+        });
+        let analyses = HashMap::from([
+            ("AAPL".to_string(), Arc::new(aapl)),
+            ("MSFT".to_string(), Arc::new(msft)),
+        ]);
+        let triggered = vec![
+            ("MSFT".to_string(), "Drawdown".to_string()),
+            ("MSFT".to_string(), "Drawdown".to_string()),
+            ("TSLA".to_string(), "Breakout".to_string()),
+        ];
+        // 20:30 UTC on 2025-06-02 is 16:30 in New York.
+        let now = Utc.with_ymd_and_hms(2025, 6, 2, 20, 30, 0).unwrap();
+
+        let recap = build(&watchlist, &analyses, &triggered, now);
+        assert_eq!(recap.date, "2025-06-02");
+        assert_eq!(recap.missing, vec!["NEW"]);
+        let symbols: Vec<&str> = recap.entries.iter().map(|e| e.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["MSFT", "AAPL"]);
+        assert_eq!(recap.entries[0].signals, vec![Strategy::StochasticOversold]);
+        assert_eq!(recap.entries[0].alerts, vec!["Drawdown"]);
+        assert_eq!(recap.entries[1].signals, vec![Strategy::RsiOversold]);
+        assert_eq!(
+            recap.entries[1].top_news.as_ref().map(|n| n.title.as_str()),
+            Some("Apple beats")
+        );
+    }
+
+    #[test]
+    fn schedule_follows_new_york_close_and_skips_weekends() {
+        // Friday 2025-06-06 15:00 New York (EDT) → 16:30 same day.
+        let now = Utc.with_ymd_and_hms(2025, 6, 6, 19, 0, 0).unwrap();
+        assert_eq!(
+            until_next_run(now, 30),
+            std::time::Duration::from_secs(90 * 60)
+        );
+
+        // Friday after the run → Monday 16:30 New York.
+        let now = Utc.with_ymd_and_hms(2025, 6, 6, 21, 0, 0).unwrap();
+        let next = now + Duration::from_std(until_next_run(now, 30)).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 6, 9, 20, 30, 0).unwrap());
+
+        // Winter (EST): 16:00 New York is 21:00 UTC.
+        let now = Utc.with_ymd_and_hms(2025, 1, 6, 12, 0, 0).unwrap();
+        let next = now + Duration::from_std(until_next_run(now, 0)).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 6, 21, 0, 0).unwrap());
+
+        assert_eq!(
+            session_start(NaiveDate::from_ymd_opt(2025, 6, 2).unwrap()),
+            Utc.with_ymd_and_hms(2025, 6, 2, 4, 0, 0).unwrap()
+        );
+    }
+}
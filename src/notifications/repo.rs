@@ -5,7 +5,7 @@
 //! `MongoDB` handle and exposes typed accessors for each collection.
 
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{DateTime, SecondsFormat, Utc};
 use futures::stream::StreamExt;
 use mongodb::{
     bson::{doc, oid::ObjectId, to_document, Document},
@@ -16,9 +16,10 @@ use mongodb::{
 use crate::db::MongoDB;
 
 use super::models::{
-    AlertRule, AlertState, CreateAlertRuleInput, CreateChannelInput, CreatePositionInput,
-    CreateWatchlistInput, NotificationChannel, NotificationHistory, Position, UpdateAlertRuleInput,
-    UpdateChannelInput, UpdatePositionInput, UpdateWatchlistInput, Watchlist,
+    AlertAuditEvent, AlertRule, AlertScope, AlertState, CreateAlertRuleInput, CreateChannelInput,
+    CreatePositionInput, CreateWatchlistInput, NotificationChannel, NotificationHistory, Position,
+    PositionImport, RealizedGain, SellPositionInput, UpdateAlertRuleInput, UpdateChannelInput,
+    UpdatePositionInput, UpdateWatchlistInput, ValuationSnapshot, Watchlist,
 };
 
 #[derive(Clone)]
@@ -57,6 +58,22 @@ impl NotificationsRepo {
         self.db.database().collection("positions")
     }
 
+    pub fn realized_gains(&self) -> Collection<RealizedGain> {
+        self.db.database().collection("realized_gains")
+    }
+
+    pub fn position_imports(&self) -> Collection<PositionImport> {
+        self.db.database().collection("position_imports")
+    }
+
+    pub fn audit(&self) -> Collection<AlertAuditEvent> {
+        self.db.database().collection("alert_audit")
+    }
+
+    pub fn valuation_snapshots(&self) -> Collection<ValuationSnapshot> {
+        self.db.database().collection("position_snapshots")
+    }
+
     // ----- indexes --------------------------------------------------------
 
     /// Create secondary indexes. Idempotent — Mongo ignores re-creations of
@@ -103,6 +120,29 @@ impl NotificationsRepo {
         self.positions()
             .create_index(IndexModel::builder().keys(doc! { "symbol": 1 }).build())
             .await?;
+        self.audit()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "rule_id": 1, "at": -1 })
+                    .build(),
+            )
+            .await?;
+        self.valuation_snapshots()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "date": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await?;
+        self.position_imports()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "key": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await?;
         Ok(())
     }
 
@@ -129,6 +169,7 @@ impl NotificationsRepo {
             name: input.name,
             config: input.config,
             enabled: input.enabled,
+            daily_recap: input.daily_recap,
             created_at: Utc::now(),
         };
         let res = self.channels().insert_one(&channel).await?;
@@ -157,6 +198,9 @@ impl NotificationsRepo {
         if let Some(enabled) = update.enabled {
             set.insert("enabled", enabled);
         }
+        if let Some(daily_recap) = update.daily_recap {
+            set.insert("daily_recap", daily_recap);
+        }
         if !set.is_empty() {
             self.channels()
                 .update_one(doc! { "_id": id }, doc! { "$set": set })
@@ -258,6 +302,36 @@ impl NotificationsRepo {
         Ok(res.deleted_count > 0)
     }
 
+    /// Move positions, realized gains and watchlist entries of a renamed
+    /// ticker to its new symbol.
+    pub async fn rename_symbol(&self, old: &str, new: &str) -> Result<()> {
+        let now = mongodb::bson::DateTime::from_chrono(Utc::now());
+        self.positions()
+            .update_many(
+                doc! { "symbol": old },
+                doc! { "$set": { "symbol": new, "updated_at": now } },
+            )
+            .await?;
+        self.realized_gains()
+            .update_many(doc! { "symbol": old }, doc! { "$set": { "symbol": new } })
+            .await?;
+        // `$addToSet` then `$pull` keeps a list that already holds the new
+        // symbol free of duplicates.
+        self.watchlists()
+            .update_many(
+                doc! { "symbols": old },
+                doc! { "$addToSet": { "symbols": new } },
+            )
+            .await?;
+        self.watchlists()
+            .update_many(
+                doc! { "symbols": old },
+                doc! { "$pull": { "symbols": old } },
+            )
+            .await?;
+        Ok(())
+    }
+
     // ----- positions ------------------------------------------------------
 
     pub async fn list_positions(&self) -> Result<Vec<Position>> {
@@ -268,6 +342,26 @@ impl NotificationsRepo {
         Ok(self.positions().find_one(doc! { "_id": id }).await?)
     }
 
+    /// Has a broker-export row with this key been imported before?
+    pub async fn position_imported(&self, key: &str) -> Result<bool> {
+        Ok(self
+            .position_imports()
+            .find_one(doc! { "key": key })
+            .await?
+            .is_some())
+    }
+
+    pub async fn record_position_import(&self, key: &str) -> Result<()> {
+        self.position_imports()
+            .insert_one(PositionImport {
+                id: None,
+                key: key.to_string(),
+                imported_at: Utc::now(),
+            })
+            .await?;
+        Ok(())
+    }
+
     pub async fn create_position(&self, input: CreatePositionInput) -> Result<Position> {
         let symbol = crate::symbols::normalize_symbol_key(&input.symbol);
         if symbol.is_empty() {
@@ -341,6 +435,106 @@ impl NotificationsRepo {
         Ok(res.deleted_count > 0)
     }
 
+    /// Sell shares of one symbol out of its lots. Lots are shrunk or deleted
+    /// and one `RealizedGain` is recorded per lot touched.
+    pub async fn sell_position(&self, input: SellPositionInput) -> Result<Vec<RealizedGain>> {
+        let symbol = crate::symbols::normalize_symbol_key(&input.symbol);
+        if !input.price.is_finite() || input.price < 0.0 {
+            return Err(anyhow!("price must be a non-negative finite number"));
+        }
+        let lots = collect(self.positions().find(doc! { "symbol": &symbol }).await?).await?;
+        let fills = super::lots::select_lots(&lots, input.quantity, input.method, &input.lot_ids)?;
+        let sold_at = input.sold_at.unwrap_or_else(Utc::now);
+
+        let mut gains = Vec::with_capacity(fills.len());
+        for fill in &fills {
+            let lot = lots
+                .iter()
+                .find(|p| p.id == Some(fill.position_id))
+                .ok_or_else(|| anyhow!("lot {} disappeared", fill.position_id))?;
+            if fill.remaining > 1e-9 {
+                self.positions()
+                    .update_one(
+                        doc! { "_id": fill.position_id },
+                        doc! { "$set": {
+                            "quantity": fill.remaining,
+                            "updated_at": mongodb::bson::DateTime::from_chrono(Utc::now()),
+                        } },
+                    )
+                    .await?;
+            } else {
+                self.positions()
+                    .delete_one(doc! { "_id": fill.position_id })
+                    .await?;
+            }
+            let mut gain = super::lots::realize(lot, fill, input.price, sold_at, input.method);
+            let res = self.realized_gains().insert_one(&gain).await?;
+            gain.id = res.inserted_id.as_object_id();
+            gains.push(gain);
+        }
+        Ok(gains)
+    }
+
+    /// Realized gains, newest sale first.
+    pub async fn list_realized_gains(&self) -> Result<Vec<RealizedGain>> {
+        let mut gains = collect(self.realized_gains().find(doc! {}).await?).await?;
+        gains.sort_by_key(|g| std::cmp::Reverse(g.sold_at));
+        Ok(gains)
+    }
+
+    /// Replace the snapshot for `snap.date` (one document per market date).
+    pub async fn upsert_valuation_snapshot(&self, snap: &ValuationSnapshot) -> Result<()> {
+        self.valuation_snapshots()
+            .replace_one(doc! { "date": &snap.date }, snap)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Snapshots dated on or after `since` (`YYYY-MM-DD`), oldest first.
+    pub async fn list_valuation_snapshots(&self, since: &str) -> Result<Vec<ValuationSnapshot>> {
+        let opts = FindOptions::builder().sort(doc! { "date": 1 }).build();
+        collect(
+            self.valuation_snapshots()
+                .find(doc! { "date": { "$gte": since } })
+                .with_options(opts)
+                .await?,
+        )
+        .await
+    }
+
+    /// `(symbol, rule_name)` of every notification sent since `since`.
+    pub async fn triggered_since(&self, since: DateTime<Utc>) -> Result<Vec<(String, String)>> {
+        // `created_at` is stored as an RFC 3339 string, which sorts
+        // chronologically.
+        let mut cursor = self
+            .history()
+            .clone_with_type::<Document>()
+            .find(doc! {
+                "created_at": { "$gte": since.to_rfc3339_opts(SecondsFormat::AutoSi, true) }
+            })
+            .projection(doc! { "symbol": 1, "rule_name": 1 })
+            .await?;
+        let mut out = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            let doc = doc?;
+            if let (Ok(symbol), Ok(rule)) = (doc.get_str("symbol"), doc.get_str("rule_name")) {
+                out.push((symbol.to_string(), rule.to_string()));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Channels opted into the end-of-day recap.
+    pub async fn list_recap_channels(&self) -> Result<Vec<NotificationChannel>> {
+        collect(
+            self.channels()
+                .find(doc! { "enabled": true, "daily_recap": true })
+                .await?,
+        )
+        .await
+    }
+
     /// Union of every symbol across every watchlist (normalized upper-case).
     pub async fn all_watched_symbols(&self) -> Result<Vec<String>> {
         let mut cursor = self.watchlists().find(doc! {}).await?;
@@ -355,6 +549,35 @@ impl NotificationsRepo {
         Ok(set.into_iter().collect())
     }
 
+    /// Symbols an earnings condition can fire on: every watchlisted symbol
+    /// plus the scope of each enabled rule with an `earnings_within_days`
+    /// condition. `None` when such a rule covers every analyzed stock.
+    pub async fn earnings_symbols(&self) -> Result<Option<std::collections::HashSet<String>>> {
+        let mut symbols: std::collections::HashSet<String> =
+            self.all_watched_symbols().await?.into_iter().collect();
+        for rule in self.list_enabled_rules().await? {
+            if !rule.conditions.uses_earnings() {
+                continue;
+            }
+            match rule.scope {
+                AlertScope::AllAnalyzed => return Ok(None),
+                AlertScope::AllWatched => {}
+                AlertScope::Watchlist { watchlist_id } => {
+                    if let Some(wl) = self.get_watchlist(&watchlist_id).await? {
+                        symbols.extend(wl.symbols);
+                    }
+                }
+                AlertScope::Symbols { symbols: listed } => symbols.extend(
+                    listed
+                        .iter()
+                        .map(|s| crate::symbols::normalize_symbol_key(s))
+                        .filter(|s| !s.is_empty()),
+                ),
+            }
+        }
+        Ok(Some(symbols))
+    }
+
     // ----- rules ----------------------------------------------------------
 
     pub async fn list_rules(&self) -> Result<Vec<AlertRule>> {
@@ -382,6 +605,10 @@ impl NotificationsRepo {
             channel_ids: input.channel_ids,
             message_template: input.message_template,
             require_consecutive: input.require_consecutive.max(1),
+            snoozed_until: None,
+            max_triggers: input.max_triggers,
+            trigger_count: 0,
+            muted_symbols: dedupe_upper(input.muted_symbols),
             created_at: now,
             updated_at: now,
         };
@@ -444,6 +671,19 @@ impl NotificationsRepo {
         if let Some(v) = update.require_consecutive {
             set.insert("require_consecutive", v.max(1) as i64);
         }
+        if let Some(opt) = update.max_triggers {
+            match opt {
+                Some(v) => {
+                    set.insert("max_triggers", v as i64);
+                }
+                None => {
+                    set.insert("max_triggers", mongodb::bson::Bson::Null);
+                }
+            }
+        }
+        if let Some(v) = update.muted_symbols {
+            set.insert("muted_symbols", dedupe_upper(v));
+        }
         self.rules()
             .update_one(doc! { "_id": id }, doc! { "$set": set })
             .await?;
@@ -464,6 +704,78 @@ impl NotificationsRepo {
         self.get_rule(id).await
     }
 
+    /// Snooze until `until`, or clear the snooze with `None`.
+    pub async fn set_rule_snooze(
+        &self,
+        id: &ObjectId,
+        until: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Option<AlertRule>> {
+        let value = match until {
+            Some(t) => mongodb::bson::Bson::DateTime(mongodb::bson::DateTime::from_chrono(t)),
+            None => mongodb::bson::Bson::Null,
+        };
+        self.rules()
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "snoozed_until": value, "updated_at": mongodb::bson::DateTime::from_chrono(Utc::now()) } },
+            )
+            .await?;
+        self.get_rule(id).await
+    }
+
+    pub async fn set_symbol_muted(
+        &self,
+        id: &ObjectId,
+        symbol: &str,
+        muted: bool,
+    ) -> Result<Option<AlertRule>> {
+        let symbol = crate::symbols::normalize_symbol_key(symbol);
+        let change = if muted {
+            doc! { "$addToSet": { "muted_symbols": &symbol } }
+        } else {
+            doc! { "$pull": { "muted_symbols": &symbol } }
+        };
+        self.rules().update_one(doc! { "_id": id }, change).await?;
+        self.get_rule(id).await
+    }
+
+    /// Zero the trigger counter, clear any snooze and re-enable the rule.
+    pub async fn reset_rule(&self, id: &ObjectId) -> Result<Option<AlertRule>> {
+        self.rules()
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": {
+                    "trigger_count": 0,
+                    "snoozed_until": mongodb::bson::Bson::Null,
+                    "enabled": true,
+                    "updated_at": mongodb::bson::DateTime::from_chrono(Utc::now()),
+                } },
+            )
+            .await?;
+        self.get_rule(id).await
+    }
+
+    /// Count a successful delivery. When the rule has hit its `max_triggers`
+    /// it is disabled; returns `true` in that case.
+    pub async fn record_rule_trigger(&self, id: &ObjectId) -> Result<bool> {
+        self.rules()
+            .update_one(doc! { "_id": id }, doc! { "$inc": { "trigger_count": 1 } })
+            .await?;
+        let res = self
+            .rules()
+            .update_one(
+                doc! {
+                    "_id": id,
+                    "enabled": true,
+                    "max_triggers": { "$type": "number" },
+                    "$expr": { "$gte": ["$trigger_count", "$max_triggers"] },
+                },
+                doc! { "$set": { "enabled": false, "updated_at": mongodb::bson::DateTime::from_chrono(Utc::now()) } },
+            )
+            .await?;
+        Ok(res.modified_count > 0)
+    }
+
     pub async fn delete_rule(&self, id: &ObjectId) -> Result<bool> {
         let res = self.rules().delete_one(doc! { "_id": id }).await?;
         // Also scrub per-rule state so a re-created rule doesn't inherit old cooldowns.
@@ -511,6 +823,35 @@ impl NotificationsRepo {
         Ok(())
     }
 
+    // ----- audit ----------------------------------------------------------
+
+    /// Best-effort: a failed audit write is logged, never surfaced, so it
+    /// can't fail the action being audited.
+    pub async fn record_audit(&self, event: AlertAuditEvent) {
+        if let Err(e) = self.audit().insert_one(&event).await {
+            tracing::warn!(
+                "notifications repo: failed to record {:?} audit for rule {}: {}",
+                event.action,
+                event.rule_id,
+                e
+            );
+        }
+    }
+
+    pub async fn list_audit(&self, rule_id: &ObjectId, limit: u32) -> Result<Vec<AlertAuditEvent>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "at": -1 })
+            .limit(limit.clamp(1, 500) as i64)
+            .build();
+        collect(
+            self.audit()
+                .find(doc! { "rule_id": rule_id })
+                .with_options(options)
+                .await?,
+        )
+        .await
+    }
+
     // ----- history --------------------------------------------------------
 
     pub async fn record_history(&self, entry: &NotificationHistory) -> Result<ObjectId> {
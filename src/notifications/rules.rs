@@ -4,6 +4,8 @@
 //! single `StockAnalysis` snapshot plus optional previous state (needed for
 //! MACD cross detection). Side-effect free, so it's trivially testable.
 
+use chrono::{DateTime, NaiveDate, Utc};
+
 use crate::models::StockAnalysis;
 use crate::notifications::models::{Condition, ConditionGroup};
 
@@ -12,6 +14,11 @@ pub struct EvalContext<'a> {
     pub analysis: &'a StockAnalysis,
     /// Previous cycle's MACD histogram for this rule+symbol, if any.
     pub prev_macd_histogram: Option<f64>,
+    /// Highest price seen for this rule+symbol, including the current one.
+    pub peak_price: Option<f64>,
+    /// Evaluation time. Calendar conditions count days from here, in US
+    /// market time (America/New_York).
+    pub now: DateTime<Utc>,
 }
 
 /// Evaluate the full tree. Returns (matched, per-leaf human descriptions of
@@ -182,6 +189,7 @@ fn eval_condition(c: &Condition, ctx: &EvalContext) -> Option<String> {
         }
         Condition::VolumeAbove { value } => a
             .volume
+            .map(|v| v as f64)
             .filter(|v| v > value)
             .map(|v| format!("Volume {:.0} > {}", v, value)),
         Condition::SectorEquals { sector } => match &a.sector {
@@ -203,46 +211,143 @@ fn eval_condition(c: &Condition, ctx: &EvalContext) -> Option<String> {
                 None
             }
         }
+        Condition::TrailingStopPct { value } => {
+            let peak = ctx.peak_price.filter(|p| *p > 0.0)?;
+            let drop_pct = ((peak - a.price) / peak) * 100.0;
+            (drop_pct >= *value)
+                .then(|| format!("Down {:.2}% from trailing peak (${:.2})", drop_pct, peak))
+        }
+        Condition::PriceNearSma { period, within_pct } => {
+            let sma = sma_for_period(a, *period).filter(|s| *s > 0.0)?;
+            let dist_pct = ((a.price - sma).abs() / sma) * 100.0;
+            (dist_pct <= *within_pct).then(|| {
+                format!(
+                    "Price within {:.2}% of SMA {} (${:.2})",
+                    dist_pct, period, sma
+                )
+            })
+        }
+        Condition::VolumeRatioAbove { value } => {
+            let volume = a.volume? as f64;
+            let avg = a
+                .technicals
+                .as_ref()
+                .and_then(|t| t.average_volume)
+                .filter(|v| *v > 0.0)?;
+            let ratio = volume / avg;
+            (ratio >= *value).then(|| format!("Volume {:.2}× average", ratio))
+        }
+        Condition::ExternalSignal {
+            signal,
+            within_minutes,
+        } => {
+            let cutoff = ctx.now - chrono::Duration::minutes(*within_minutes as i64);
+            let found = a.external_signals.iter().find(|s| {
+                s.received_at >= cutoff
+                    && signal
+                        .as_deref()
+                        .is_none_or(|want| s.signal.eq_ignore_ascii_case(want.trim()))
+            })?;
+            let minutes = (ctx.now - found.received_at).num_minutes().max(0);
+            let from = match &found.strategy {
+                Some(strategy) => format!("{}: {}", found.source, strategy),
+                None => found.source.clone(),
+            };
+            Some(format!(
+                "External signal '{}' from {} ({} min ago)",
+                found.signal, from, minutes
+            ))
+        }
+        Condition::EarningsWithinDays { days } => {
+            let date = a.earnings.as_ref().and_then(|e| e.earnings_date)?;
+            let until = days_until(ctx.now, market_date(date))?;
+            (until <= *days as i64).then(|| {
+                format!(
+                    "Earnings {} ({})",
+                    describe_days(until),
+                    market_date(date).format("%b %-d")
+                )
+            })
+        }
+        Condition::ExDividendWithinDays { days } => {
+            let raw = a.technicals.as_ref()?.ex_dividend_date.as_deref()?;
+            let date = parse_calendar_date(raw)?;
+            let until = days_until(ctx.now, date)?;
+            (until <= *days as i64).then(|| {
+                format!(
+                    "Ex-dividend {} ({})",
+                    describe_days(until),
+                    date.format("%b %-d")
+                )
+            })
+        }
+    }
+}
+
+/// The stored SMA computed over `period` days. `sma_20` and `sma_50` hold
+/// the configured short and long periods (analyses saved before periods
+/// were recorded used the defaults); `sma_200` is always 200 days.
+fn sma_for_period(a: &StockAnalysis, period: u32) -> Option<f64> {
+    let periods = a.indicator_periods.unwrap_or_default();
+    if period == periods.sma_short {
+        a.sma_20
+    } else if period == periods.sma_long {
+        a.sma_50
+    } else if period == 200 {
+        a.sma_200
+    } else {
+        None
+    }
+}
+
+fn market_date(at: DateTime<Utc>) -> NaiveDate {
+    at.with_timezone(&chrono_tz::America::New_York).date_naive()
+}
+
+/// Whole days from `now` (market date) until `date`; `None` if it's in the past.
+fn days_until(now: DateTime<Utc>, date: NaiveDate) -> Option<i64> {
+    let days = (date - market_date(now)).num_days();
+    (days >= 0).then_some(days)
+}
+
+fn describe_days(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        n => format!("in {} days", n),
     }
 }
 
+/// NASDAQ reports dates as `MM/DD/YYYY`, `Mon D, YYYY` or ISO depending on
+/// the endpoint; "N/A" and other junk parse to `None`.
+fn parse_calendar_date(raw: &str) -> Option<NaiveDate> {
+    let raw = raw.trim();
+    ["%Y-%m-%d", "%m/%d/%Y", "%b %d, %Y", "%B %d, %Y"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(raw, fmt).ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::indicators::IndicatorConfig;
+    use crate::ingest::ExternalSignal;
+    use crate::models::EarningsData;
     use crate::models::{
         BollingerBands, MACDIndicator, NasdaqTechnicals, StochasticOscillator, StockAnalysis,
     };
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
 
     fn base() -> StockAnalysis {
-        StockAnalysis {
-            id: None,
-            symbol: "AAPL".into(),
-            price: 100.0,
-            price_change: None,
-            price_change_percent: None,
-            rsi: None,
-            sma_20: None,
-            sma_50: None,
-            macd: None,
-            volume: None,
-            market_cap: None,
-            sector: None,
-            is_oversold: false,
-            is_overbought: false,
-            analyzed_at: Utc::now(),
-            bollinger: None,
-            stochastic: None,
-            earnings: None,
-            technicals: None,
-            news: None,
-        }
+        StockAnalysis::sample("AAPL", 100.0)
     }
 
     fn ctx<'a>(a: &'a StockAnalysis, prev: Option<f64>) -> EvalContext<'a> {
         EvalContext {
             analysis: a,
             prev_macd_histogram: prev,
+            peak_price: None,
+            now: Utc::now(),
         }
     }
 
@@ -306,6 +411,8 @@ mod tests {
     fn with_tech(hi: f64, lo: f64) -> NasdaqTechnicals {
         NasdaqTechnicals {
             exchange: None,
+            company_name: None,
+            asset_class: None,
             sector: None,
             industry: None,
             one_year_target: None,
@@ -501,7 +608,7 @@ mod tests {
     #[test]
     fn volume_above() {
         let mut a = base();
-        a.volume = Some(2_000_000.0);
+        a.volume = Some(2_000_000);
         assert!(
             evaluate(
                 &leaf(Condition::VolumeAbove { value: 1_000_000.0 }),
@@ -579,4 +686,215 @@ mod tests {
         };
         assert!(evaluate(&g, &ctx(&a, None)).0);
     }
+
+    fn at(a: &StockAnalysis, now: DateTime<Utc>) -> EvalContext<'_> {
+        EvalContext {
+            analysis: a,
+            prev_macd_histogram: None,
+            peak_price: None,
+            now,
+        }
+    }
+
+    #[test]
+    fn earnings_within_days() {
+        let mut a = base();
+        // Reports Jan 30 after the close (21:00 UTC = 16:00 New York).
+        a.earnings = Some(EarningsData {
+            earnings_date: Some(Utc.with_ymd_and_hms(2025, 1, 30, 21, 0, 0).unwrap()),
+            eps_estimate: None,
+            revenue_estimate: None,
+        });
+        let cond = leaf(Condition::EarningsWithinDays { days: 2 });
+
+        let (ok, m) = evaluate(
+            &cond,
+            &at(&a, Utc.with_ymd_and_hms(2025, 1, 28, 15, 0, 0).unwrap()),
+        );
+        assert!(ok);
+        assert_eq!(m, vec!["Earnings in 2 days (Jan 30)".to_string()]);
+        assert!(
+            !evaluate(
+                &cond,
+                &at(&a, Utc.with_ymd_and_hms(2025, 1, 27, 15, 0, 0).unwrap())
+            )
+            .0
+        );
+        // Already reported → never fires.
+        assert!(
+            !evaluate(
+                &cond,
+                &at(&a, Utc.with_ymd_and_hms(2025, 1, 31, 15, 0, 0).unwrap())
+            )
+            .0
+        );
+    }
+
+    #[test]
+    fn ex_dividend_tomorrow_in_any_nasdaq_format() {
+        let now = Utc.with_ymd_and_hms(2024, 11, 7, 15, 0, 0).unwrap();
+        for raw in ["2024-11-08", "11/08/2024", "Nov 8, 2024"] {
+            let mut a = base();
+            let mut tech = with_tech(200.0, 100.0);
+            tech.ex_dividend_date = Some(raw.to_string());
+            a.technicals = Some(tech);
+            let (ok, m) = evaluate(
+                &leaf(Condition::ExDividendWithinDays { days: 1 }),
+                &at(&a, now),
+            );
+            assert!(ok, "format {}", raw);
+            assert_eq!(m, vec!["Ex-dividend tomorrow (Nov 8)".to_string()]);
+        }
+    }
+
+    #[test]
+    fn ex_dividend_unparseable_never_fires() {
+        let mut a = base();
+        let mut tech = with_tech(200.0, 100.0);
+        tech.ex_dividend_date = Some("N/A".to_string());
+        a.technicals = Some(tech);
+        assert!(
+            !evaluate(
+                &leaf(Condition::ExDividendWithinDays { days: 30 }),
+                &ctx(&a, None)
+            )
+            .0
+        );
+    }
+
+    #[test]
+    fn trailing_stop_fires_below_peak() {
+        let mut a = base();
+        a.price = 88.0;
+        let cond = leaf(Condition::TrailingStopPct { value: 10.0 });
+        let mut c = ctx(&a, None);
+        assert!(!evaluate(&cond, &c).0, "no peak tracked yet");
+
+        c.peak_price = Some(100.0);
+        let (ok, m) = evaluate(&cond, &c);
+        assert!(ok);
+        assert_eq!(
+            m,
+            vec!["Down 12.00% from trailing peak ($100.00)".to_string()]
+        );
+
+        c.peak_price = Some(95.0);
+        assert!(!evaluate(&cond, &c).0);
+    }
+
+    #[test]
+    fn composite_oversold_near_sma_on_heavy_volume() {
+        let mut a = base();
+        a.rsi = Some(28.0);
+        a.price = 101.0;
+        a.sma_50 = Some(100.0);
+        a.volume = Some(3_000_000);
+        let mut tech = with_tech(200.0, 50.0);
+        tech.average_volume = Some(1_500_000.0);
+        a.technicals = Some(tech);
+
+        let g = ConditionGroup::And {
+            children: vec![
+                leaf(Condition::RsiBelow { value: 30.0 }),
+                leaf(Condition::PriceNearSma {
+                    period: 50,
+                    within_pct: 2.0,
+                }),
+                leaf(Condition::VolumeRatioAbove { value: 1.5 }),
+            ],
+        };
+        let (ok, m) = evaluate(&g, &ctx(&a, None));
+        assert!(ok);
+        assert_eq!(m.len(), 3);
+        assert_eq!(m[2], "Volume 2.00× average");
+
+        a.volume = Some(2_000_000);
+        assert!(
+            !evaluate(&g, &ctx(&a, None)).0,
+            "1.33× average is not enough"
+        );
+    }
+
+    #[test]
+    fn price_near_sma_unknown_period_never_matches() {
+        let mut a = base();
+        a.sma_20 = Some(a.price);
+        let cond = |period| {
+            leaf(Condition::PriceNearSma {
+                period,
+                within_pct: 1.0,
+            })
+        };
+        assert!(evaluate(&cond(20), &ctx(&a, None)).0);
+        assert!(!evaluate(&cond(13), &ctx(&a, None)).0);
+        assert!(!evaluate(&cond(200), &ctx(&a, None)).0);
+
+        a.sma_200 = Some(a.price * 1.005);
+        assert!(evaluate(&cond(200), &ctx(&a, None)).0);
+    }
+
+    #[test]
+    fn price_near_sma_follows_configured_periods() {
+        let mut a = base();
+        a.indicator_periods = Some(IndicatorConfig {
+            sma_short: 10,
+            sma_long: 30,
+            ..Default::default()
+        });
+        a.sma_20 = Some(a.price);
+        a.sma_50 = Some(a.price * 2.0);
+        let cond = |period| {
+            leaf(Condition::PriceNearSma {
+                period,
+                within_pct: 1.0,
+            })
+        };
+        assert!(evaluate(&cond(10), &ctx(&a, None)).0);
+        assert!(!evaluate(&cond(30), &ctx(&a, None)).0);
+        // Neither field holds a 20- or 50-day average here.
+        assert!(!evaluate(&cond(20), &ctx(&a, None)).0);
+        assert!(!evaluate(&cond(50), &ctx(&a, None)).0);
+
+        a.sma_50 = Some(a.price);
+        assert!(evaluate(&cond(30), &ctx(&a, None)).0);
+    }
+
+    #[test]
+    fn external_signal_within_window() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 15, 0, 0).unwrap();
+        let mut a = base();
+        a.external_signals = vec![ExternalSignal {
+            id: None,
+            symbol: "AAPL".into(),
+            source: "tradingview".into(),
+            signal: "buy".into(),
+            strategy: None,
+            message: None,
+            price: Some(100.0),
+            received_at: now - chrono::Duration::minutes(20),
+        }];
+        let cond = |signal: Option<&str>, within_minutes| {
+            leaf(Condition::ExternalSignal {
+                signal: signal.map(str::to_string),
+                within_minutes,
+            })
+        };
+
+        let (ok, m) = evaluate(&cond(Some("BUY"), 30), &at(&a, now));
+        assert!(ok);
+        assert_eq!(
+            m,
+            vec!["External signal 'buy' from tradingview (20 min ago)".to_string()]
+        );
+        assert!(evaluate(&cond(None, 30), &at(&a, now)).0);
+        assert!(!evaluate(&cond(Some("sell"), 30), &at(&a, now)).0);
+        assert!(!evaluate(&cond(None, 10), &at(&a, now)).0, "too old");
+        assert!(!evaluate(&cond(None, 30), &at(&base(), now)).0);
+
+        a.external_signals[0].strategy = Some("RSI Reversal".into());
+        assert_eq!(
+            evaluate(&cond(None, 30), &at(&a, now)).1,
+            vec!["External signal 'buy' from tradingview: RSI Reversal (20 min ago)".to_string()]
+        );
+    }
 }
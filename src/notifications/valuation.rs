@@ -0,0 +1,150 @@
+//! Daily valuation snapshots of the tracked positions.
+//!
+//! A background task prices every position from the stock cache once an hour
+//! and upserts that market date's snapshot, so the last write of the day is
+//! the closing valuation. `/api/positions/history` charts these rows instead
+//! of recomputing past values from lots and trades.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use super::models::{Position, SnapshotHolding, ValuationSnapshot};
+use super::repo::NotificationsRepo;
+use crate::cache::CacheLayer;
+use crate::maintenance::MaintenanceMode;
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Build the snapshot for `now` from open lots and current prices.
+pub fn snapshot(
+    positions: &[Position],
+    prices: &HashMap<String, f64>,
+    now: DateTime<Utc>,
+) -> ValuationSnapshot {
+    let mut merged: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for p in positions {
+        let entry = merged.entry(p.symbol.as_str()).or_default();
+        entry.0 += p.quantity;
+        entry.1 += p.quantity * p.cost_basis_per_share;
+    }
+
+    let mut holdings = Vec::with_capacity(merged.len());
+    let mut unpriced = Vec::new();
+    for (symbol, (quantity, cost_basis)) in merged {
+        match prices.get(symbol).copied().filter(|p| *p > 0.0) {
+            Some(price) => holdings.push(SnapshotHolding {
+                symbol: symbol.to_string(),
+                quantity,
+                price,
+                market_value: quantity * price,
+                cost_basis,
+            }),
+            None => unpriced.push(symbol.to_string()),
+        }
+    }
+
+    ValuationSnapshot {
+        id: None,
+        date: now
+            .with_timezone(&chrono_tz::America::New_York)
+            .date_naive()
+            .format("%Y-%m-%d")
+            .to_string(),
+        total_value: holdings.iter().map(|h| h.market_value).sum(),
+        total_cost_basis: holdings.iter().map(|h| h.cost_basis).sum(),
+        holdings,
+        unpriced,
+        taken_at: now,
+    }
+}
+
+/// Price the current positions and upsert today's snapshot. Returns `None`
+/// when there are no positions to value.
+pub async fn take_snapshot(
+    repo: &NotificationsRepo,
+    cache: &CacheLayer,
+) -> anyhow::Result<Option<ValuationSnapshot>> {
+    let positions = repo.list_positions().await?;
+    if positions.is_empty() {
+        return Ok(None);
+    }
+    let mut prices = HashMap::new();
+    for p in &positions {
+        if prices.contains_key(&p.symbol) {
+            continue;
+        }
+        if let Some(s) = cache.get_stock(&p.symbol).await {
+            prices.insert(p.symbol.clone(), s.price);
+        }
+    }
+    let snap = snapshot(&positions, &prices, Utc::now());
+    repo.upsert_valuation_snapshot(&snap).await?;
+    Ok(Some(snap))
+}
+
+/// Run [`take_snapshot`] hourly for the life of the process, except while
+/// read-only mode is on.
+pub fn spawn(repo: NotificationsRepo, cache: CacheLayer, maintenance: MaintenanceMode) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SNAPSHOT_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if maintenance.is_read_only() {
+                continue;
+            }
+            match take_snapshot(&repo, &cache).await {
+                Ok(Some(s)) => debug!(
+                    "valuation snapshot {}: {:.2} across {} holdings",
+                    s.date,
+                    s.total_value,
+                    s.holdings.len()
+                ),
+                Ok(None) => {}
+                Err(e) => warn!("valuation snapshot failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn lot(symbol: &str, qty: f64, cost: f64) -> Position {
+        let at = Utc.with_ymd_and_hms(2025, 1, 2, 15, 0, 0).unwrap();
+        Position {
+            id: None,
+            symbol: symbol.to_string(),
+            quantity: qty,
+            cost_basis_per_share: cost,
+            opened_at: at,
+            notes: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    #[test]
+    fn merges_lots_and_excludes_unpriced_symbols() {
+        let positions = vec![
+            lot("AAPL", 10.0, 100.0),
+            lot("AAPL", 5.0, 130.0),
+            lot("ZZZZ", 1.0, 50.0),
+        ];
+        let prices = HashMap::from([("AAPL".to_string(), 120.0)]);
+        // 01:30 UTC is still the previous day in New York.
+        let now = Utc.with_ymd_and_hms(2025, 6, 3, 1, 30, 0).unwrap();
+
+        let snap = snapshot(&positions, &prices, now);
+        assert_eq!(snap.date, "2025-06-02");
+        assert_eq!(snap.holdings.len(), 1);
+        assert_eq!(snap.holdings[0].quantity, 15.0);
+        assert_eq!(snap.total_value, 1800.0);
+        assert_eq!(snap.total_cost_basis, 1650.0);
+        assert_eq!(snap.unpriced, vec!["ZZZZ"]);
+    }
+}
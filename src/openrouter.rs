@@ -231,9 +231,10 @@ impl OpenRouterClient {
         );
 
         prompt.push_str(&format!("**Current Price:** ${:.2}\n", analysis.price));
+        let periods = analysis.indicator_periods.unwrap_or_default();
 
         if let Some(rsi) = analysis.rsi {
-            prompt.push_str(&format!("**RSI (14):** {:.2}", rsi));
+            prompt.push_str(&format!("**RSI ({}):** {:.2}", periods.rsi_period, rsi));
             if analysis.is_oversold {
                 prompt.push_str(" (OVERSOLD)");
             } else if analysis.is_overbought {
@@ -243,11 +244,26 @@ impl OpenRouterClient {
         }
 
         if let Some(sma_20) = analysis.sma_20 {
-            prompt.push_str(&format!("**SMA 20:** ${:.2}\n", sma_20));
+            prompt.push_str(&format!("**SMA {}:** ${:.2}\n", periods.sma_short, sma_20));
         }
 
         if let Some(sma_50) = analysis.sma_50 {
-            prompt.push_str(&format!("**SMA 50:** ${:.2}\n", sma_50));
+            prompt.push_str(&format!("**SMA {}:** ${:.2}\n", periods.sma_long, sma_50));
+        }
+
+        if let Some(ref levels) = analysis.support_resistance {
+            if let Some(support) = levels.support {
+                prompt.push_str(&format!(
+                    "**Support (nearest swing level below):** ${:.2}\n",
+                    support
+                ));
+            }
+            if let Some(resistance) = levels.resistance {
+                prompt.push_str(&format!(
+                    "**Resistance (nearest swing level above):** ${:.2}\n",
+                    resistance
+                ));
+            }
         }
 
         if let Some(ref macd) = analysis.macd {
@@ -296,7 +312,12 @@ impl OpenRouterClient {
 
         prompt.push_str("\nProvide a concise analysis (2-3 paragraphs) covering:\n");
         prompt.push_str("1. Current technical stance (bullish/bearish/neutral)\n");
-        prompt.push_str("2. Key support/resistance levels based on moving averages\n");
+        if analysis.support_resistance.is_some() {
+            prompt
+                .push_str("2. How price sits against the support/resistance levels given above\n");
+        } else {
+            prompt.push_str("2. Key levels to watch\n");
+        }
         prompt.push_str("3. Brief recommendation with risk factors\n");
 
         prompt
@@ -510,7 +531,7 @@ pub enum StreamEvent {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::MACDIndicator;
+    use crate::models::{MACDIndicator, SupportResistance};
 
     #[test]
     fn test_fallback_models_list() {
@@ -575,9 +596,6 @@ mod tests {
         let client = OpenRouterClient::new(Some("test-key".to_string()), true);
 
         let analysis = StockAnalysis {
-            id: None,
-            symbol: "AAPL".to_string(),
-            price: 175.50,
             price_change: Some(2.50),
             price_change_percent: Some(1.45),
             rsi: Some(45.0),
@@ -588,17 +606,14 @@ mod tests {
                 signal_line: 1.2,
                 histogram: 0.3,
             }),
-            volume: Some(50_000_000.0),
-            market_cap: Some(2_800_000_000_000.0),
+            volume: Some(50_000_000),
+            market_cap: Some(2_800_000_000_000),
             sector: Some("Technology".to_string()),
-            is_oversold: false,
-            is_overbought: false,
-            analyzed_at: Utc::now(),
-            bollinger: None,
-            stochastic: None,
-            earnings: None,
-            technicals: None,
-            news: None,
+            support_resistance: Some(SupportResistance {
+                support: Some(168.25),
+                resistance: None,
+            }),
+            ..StockAnalysis::sample("AAPL", 175.50)
         };
 
         let prompt = client.build_analysis_prompt(&analysis);
@@ -608,5 +623,8 @@ mod tests {
         assert!(prompt.contains("RSI"));
         assert!(prompt.contains("SMA 20"));
         assert!(prompt.contains("MACD"));
+        assert!(prompt.contains("Support (nearest swing level below):** $168.25"));
+        assert!(!prompt.contains("Resistance"));
+        assert!(!prompt.contains("based on moving averages"));
     }
 }
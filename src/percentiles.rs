@@ -0,0 +1,242 @@
+//! Cross-sectional percentile ranks.
+//!
+//! At the end of every cycle each analysis is ranked against the whole
+//! stored universe on a few metrics (RSI, day change, volume over average
+//! volume, P/E, 1- and 3-month return, RS rating). The 1-99 ranks are
+//! written to `StockAnalysis::percentiles`, so "top decile momentum" is a
+//! plain range query (`percentile_metric: "price_change_percent",
+//! min_percentile: 90`) rather than client-side math. The engine keeps last
+//! cycle's ranks and copies them onto fresh analyses.
+//!
+//! The RS rating (`rs_rank`, filtered with `min_rs_rank`) ranks the 1-month
+//! plus the 3-month return, which counts the latest month twice, the way
+//! IBD's rating doubles the latest quarter. It moves every cycle, unlike the
+//! nightly 12-month `cross_section.rs_rank`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::StockAnalysis;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PercentileMetric {
+    Rsi,
+    ChangePercent,
+    VolumeRatio,
+    PeRatio,
+    Return1m,
+    Return3m,
+    /// 1-month plus 3-month return.
+    RsRank,
+}
+
+impl PercentileMetric {
+    pub const ALL: [PercentileMetric; 7] = [
+        PercentileMetric::Rsi,
+        PercentileMetric::ChangePercent,
+        PercentileMetric::VolumeRatio,
+        PercentileMetric::PeRatio,
+        PercentileMetric::Return1m,
+        PercentileMetric::Return3m,
+        PercentileMetric::RsRank,
+    ];
+
+    /// Field name under `percentiles` and value of
+    /// `StockFilter::percentile_metric`.
+    pub fn name(self) -> &'static str {
+        match self {
+            PercentileMetric::Rsi => "rsi",
+            PercentileMetric::ChangePercent => "price_change_percent",
+            PercentileMetric::VolumeRatio => "volume_ratio",
+            PercentileMetric::PeRatio => "pe_ratio",
+            PercentileMetric::Return1m => "return_1m_pct",
+            PercentileMetric::Return3m => "return_3m_pct",
+            PercentileMetric::RsRank => "rs_rank",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|metric| metric.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The ranked value, or `None` when `stock` sits this metric out.
+    /// Negative P/E ratios (loss makers) aren't ranked.
+    fn value(self, stock: &StockAnalysis) -> Option<f64> {
+        let technicals = stock.technicals.as_ref();
+        let performance = stock.performance.as_ref();
+        let value = match self {
+            PercentileMetric::Rsi => stock.rsi,
+            PercentileMetric::ChangePercent => stock.price_change_percent,
+            PercentileMetric::VolumeRatio => {
+                let average = technicals?.average_volume.filter(|v| *v > 0.0)?;
+                Some(stock.volume? as f64 / average)
+            }
+            PercentileMetric::PeRatio => technicals?.pe_ratio.filter(|pe| *pe > 0.0),
+            PercentileMetric::Return1m => performance?.return_1m_pct,
+            PercentileMetric::Return3m => performance?.return_3m_pct,
+            PercentileMetric::RsRank => {
+                Some(performance?.return_1m_pct? + performance?.return_3m_pct?)
+            }
+        };
+        value.filter(|v| v.is_finite())
+    }
+}
+
+/// 1-99 rank of each metric across the universe; 99 is the highest value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PercentileRanks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsi: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_change_percent: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_ratio: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pe_ratio: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_1m_pct: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_3m_pct: Option<u8>,
+    /// RS rating: rank of the 1-month plus the 3-month return.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rs_rank: Option<u8>,
+}
+
+impl PercentileRanks {
+    fn set(&mut self, metric: PercentileMetric, rank: u8) {
+        let slot = match metric {
+            PercentileMetric::Rsi => &mut self.rsi,
+            PercentileMetric::ChangePercent => &mut self.price_change_percent,
+            PercentileMetric::VolumeRatio => &mut self.volume_ratio,
+            PercentileMetric::PeRatio => &mut self.pe_ratio,
+            PercentileMetric::Return1m => &mut self.return_1m_pct,
+            PercentileMetric::Return3m => &mut self.return_3m_pct,
+            PercentileMetric::RsRank => &mut self.rs_rank,
+        };
+        *slot = Some(rank);
+    }
+}
+
+/// Rank of each value by the share of the universe at or below it, so equal
+/// values share a rank.
+fn ranks(values: &mut [(&str, f64)]) -> Vec<(String, u8)> {
+    values.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let n = values.len() as f64;
+    let mut out = Vec::with_capacity(values.len());
+    let mut i = 0;
+    while i < values.len() {
+        let mut end = i;
+        while end + 1 < values.len() && values[end + 1].1 == values[i].1 {
+            end += 1;
+        }
+        let rank = ((end + 1) as f64 / n * 99.0).ceil().clamp(1.0, 99.0) as u8;
+        out.extend(values[i..=end].iter().map(|(s, _)| (s.to_string(), rank)));
+        i = end + 1;
+    }
+    out
+}
+
+/// Ranks for every analysis with at least one rankable metric.
+pub fn compute(analyses: &[StockAnalysis]) -> HashMap<String, PercentileRanks> {
+    let mut out: HashMap<String, PercentileRanks> = HashMap::new();
+    for metric in PercentileMetric::ALL {
+        let mut values: Vec<(&str, f64)> = analyses
+            .iter()
+            .filter_map(|a| Some((a.symbol.as_str(), metric.value(a)?)))
+            .collect();
+        for (symbol, rank) in ranks(&mut values) {
+            out.entry(symbol).or_default().set(metric, rank);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PerformanceReturns;
+
+    fn analysis(symbol: &str, rsi: f64, change: f64, pe: Option<f64>) -> StockAnalysis {
+        let mut a = StockAnalysis::sample(symbol, 10.0);
+        a.analyzed_at = "2025-06-02T20:05:00Z".parse().unwrap();
+        a.rsi = Some(rsi);
+        a.price_change_percent = Some(change);
+        a.volume = Some(2_000);
+        a.technicals = serde_json::from_value(serde_json::json!({
+            "average_volume": 1_000.0,
+            "pe_ratio": pe
+        }))
+        .ok();
+        a
+    }
+
+    #[test]
+    fn ranks_each_metric_across_the_universe() {
+        let analyses = vec![
+            analysis("A", 20.0, -3.0, Some(12.0)),
+            analysis("B", 50.0, 0.5, Some(-4.0)),
+            analysis("C", 80.0, 4.0, Some(30.0)),
+            analysis("D", 50.0, 1.0, None),
+        ];
+        let ranks = compute(&analyses);
+
+        assert_eq!(ranks["C"].price_change_percent, Some(99));
+        assert_eq!(ranks["A"].price_change_percent, Some(25));
+        // B and D tie on RSI and share the higher rank.
+        assert_eq!(ranks["B"].rsi, Some(75));
+        assert_eq!(ranks["D"].rsi, Some(75));
+        // Everyone trades 2x average volume.
+        assert_eq!(ranks["A"].volume_ratio, Some(99));
+        // Only positive P/E ratios are ranked.
+        assert_eq!(ranks["A"].pe_ratio, Some(50));
+        assert_eq!(ranks["C"].pe_ratio, Some(99));
+        assert_eq!(ranks["B"].pe_ratio, None);
+        assert_eq!(ranks["D"].pe_ratio, None);
+    }
+
+    #[test]
+    fn rs_rank_weights_the_latest_month_twice() {
+        let with_returns = |symbol: &str, r1m: Option<f64>, r3m: Option<f64>| {
+            let mut a = analysis(symbol, 50.0, 0.0, None);
+            a.performance = Some(PerformanceReturns {
+                return_1m_pct: r1m,
+                return_3m_pct: r3m,
+                ..Default::default()
+            });
+            a
+        };
+        let analyses = vec![
+            // Strongest over 3 months, but fading.
+            with_returns("A", Some(-5.0), Some(30.0)),
+            // Strongest last month.
+            with_returns("B", Some(20.0), Some(20.0)),
+            with_returns("C", Some(1.0), Some(2.0)),
+            // Too new for a 3-month return.
+            with_returns("D", Some(10.0), None),
+        ];
+        let ranks = compute(&analyses);
+
+        assert_eq!(ranks["A"].return_3m_pct, Some(99));
+        assert_eq!(ranks["B"].return_1m_pct, Some(99));
+        assert_eq!(ranks["B"].rs_rank, Some(99));
+        assert_eq!(ranks["A"].rs_rank, Some(66));
+        assert_eq!(ranks["C"].rs_rank, Some(33));
+        assert_eq!(ranks["D"].return_1m_pct, Some(75));
+        assert_eq!(ranks["D"].rs_rank, None);
+    }
+
+    #[test]
+    fn metric_names_round_trip() {
+        for metric in PercentileMetric::ALL {
+            assert_eq!(PercentileMetric::from_name(metric.name()), Some(metric));
+        }
+        assert_eq!(
+            PercentileMetric::from_name(" RSI "),
+            Some(PercentileMetric::Rsi)
+        );
+        assert_eq!(PercentileMetric::from_name("beta"), None);
+    }
+}
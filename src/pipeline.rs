@@ -0,0 +1,126 @@
+//! Optional stages of the per-symbol analysis pipeline.
+//!
+//! `prices → indicators → technicals → news → fundamentals → ai`. Prices are
+//! always fetched; every other stage can be switched off per deployment with
+//! `ANALYSIS_STAGES`, e.g. `prices,indicators` for a lightweight install that
+//! never calls NASDAQ. Disabled stages leave their fields on the analysis
+//! empty.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    /// Daily bars from Yahoo. Required.
+    Prices,
+    /// RSI, SMA, MACD, Bollinger, stochastic.
+    Indicators,
+    /// NASDAQ quote info: sector, primary quote, 52-week range. Also feeds
+    /// the sector-relative returns.
+    Technicals,
+    /// NASDAQ headlines for notable symbols.
+    News,
+    /// Earnings calendar for symbols an earnings alert can fire on.
+    Fundamentals,
+    /// On-demand OpenRouter analysis endpoints.
+    Ai,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 6] = [
+        Stage::Prices,
+        Stage::Indicators,
+        Stage::Technicals,
+        Stage::News,
+        Stage::Fundamentals,
+        Stage::Ai,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Prices => "prices",
+            Stage::Indicators => "indicators",
+            Stage::Technicals => "technicals",
+            Stage::News => "news",
+            Stage::Fundamentals => "fundamentals",
+            Stage::Ai => "ai",
+        }
+    }
+}
+
+impl FromStr for Stage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Stage::ALL
+            .into_iter()
+            .find(|stage| stage.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown analysis stage '{}' (expected one of: {})",
+                    s.trim(),
+                    Stage::ALL.map(Stage::name).join(", ")
+                )
+            })
+    }
+}
+
+/// The set of enabled stages. `Prices` is always included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineStages(BTreeSet<Stage>);
+
+impl Default for PipelineStages {
+    fn default() -> Self {
+        Self(Stage::ALL.into_iter().collect())
+    }
+}
+
+impl PipelineStages {
+    /// Parse a comma-separated list, or `all`.
+    pub fn parse(list: &str) -> Result<Self> {
+        if list.trim().eq_ignore_ascii_case("all") {
+            return Ok(Self::default());
+        }
+        let mut stages: BTreeSet<Stage> = list
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_>>()?;
+        stages.insert(Stage::Prices);
+        Ok(Self(stages))
+    }
+
+    pub fn enabled(&self, stage: Stage) -> bool {
+        self.0.contains(&stage)
+    }
+}
+
+impl fmt::Display for PipelineStages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.0.iter().map(|s| s.name()).collect();
+        f.write_str(&names.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stage_lists() {
+        let all = PipelineStages::parse("all").unwrap();
+        assert!(Stage::ALL.iter().all(|s| all.enabled(*s)));
+
+        let light = PipelineStages::parse("Indicators, fundamentals").unwrap();
+        assert!(light.enabled(Stage::Prices));
+        assert!(light.enabled(Stage::Indicators));
+        assert!(!light.enabled(Stage::Technicals));
+        assert!(!light.enabled(Stage::News));
+        assert_eq!(light.to_string(), "prices,indicators,fundamentals");
+
+        assert!(PipelineStages::parse("prices,quotes").is_err());
+    }
+}
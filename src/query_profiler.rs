@@ -0,0 +1,282 @@
+//! MongoDB query timing and slow-query log.
+//!
+//! Hooked into the driver's command monitoring (see `MongoDB::new`), so every
+//! query is timed, whichever `db.rs` or notifications repo method issued it.
+//! Queries are aggregated by command, collection and filter shape (the
+//! sorted top-level filter keys). That is the granularity an index is
+//! chosen at. Anything slower than `SLOW_QUERY_MS` is logged with its full
+//! filter document. The aggregates are served at `/api/admin/db/stats`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use mongodb::bson::{Bson, Document};
+use mongodb::event::command::CommandEvent;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Commands that read or write documents. Handshakes, pings and cursor
+/// cleanup are ignored.
+const PROFILED_COMMANDS: &[&str] = &[
+    "find",
+    "aggregate",
+    "count",
+    "distinct",
+    "insert",
+    "update",
+    "delete",
+    "findAndModify",
+    "getMore",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QueryKey {
+    command: String,
+    collection: String,
+    shape: String,
+}
+
+struct Pending {
+    key: QueryKey,
+    filter: Option<Document>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct QueryStat {
+    count: u64,
+    failures: u64,
+    slow: u64,
+    total: Duration,
+    max: Duration,
+}
+
+/// One aggregated row of `/api/admin/db/stats`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueryStatView {
+    pub command: String,
+    pub collection: String,
+    /// Sorted top-level filter keys, e.g. `{market_cap, sector}`.
+    pub shape: String,
+    pub count: u64,
+    pub failures: u64,
+    /// Calls at or over the slow-query threshold.
+    pub slow: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+}
+
+pub struct QueryProfiler {
+    /// `Duration::ZERO` disables the slow-query log; timings are still
+    /// aggregated.
+    slow_threshold: Duration,
+    pending: Mutex<HashMap<i32, Pending>>,
+    stats: Mutex<HashMap<QueryKey, QueryStat>>,
+}
+
+/// The filter a command runs with, if it has one.
+fn command_filter(command_name: &str, command: &Document) -> Option<Document> {
+    let first = |key: &str| {
+        command
+            .get_array(key)
+            .ok()?
+            .first()?
+            .as_document()?
+            .get_document("q")
+            .ok()
+            .cloned()
+    };
+    match command_name {
+        "find" => command.get_document("filter").ok().cloned(),
+        "count" | "distinct" | "findAndModify" => command.get_document("query").ok().cloned(),
+        "update" => first("updates"),
+        "delete" => first("deletes"),
+        "aggregate" => command
+            .get_array("pipeline")
+            .ok()?
+            .iter()
+            .filter_map(Bson::as_document)
+            .find_map(|stage| stage.get_document("$match").ok().cloned()),
+        _ => None,
+    }
+}
+
+fn filter_shape(filter: Option<&Document>) -> String {
+    let Some(filter) = filter else {
+        return "-".to_string();
+    };
+    let mut keys: Vec<&str> = filter.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    format!("{{{}}}", keys.join(", "))
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl QueryProfiler {
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self {
+            slow_threshold,
+            pending: Mutex::new(HashMap::new()),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Driver command-monitoring callback.
+    pub fn on_event(&self, event: CommandEvent) {
+        match event {
+            CommandEvent::Started(e) => self.started(e.request_id, &e.command_name, &e.command),
+            CommandEvent::Succeeded(e) => self.finished(e.request_id, e.duration, true),
+            CommandEvent::Failed(e) => self.finished(e.request_id, e.duration, false),
+            _ => {}
+        }
+    }
+
+    fn started(&self, request_id: i32, command_name: &str, command: &Document) {
+        if !PROFILED_COMMANDS.contains(&command_name) {
+            return;
+        }
+        // The collection is the value of the command's first key, except
+        // for getMore whose first value is the cursor id.
+        let collection = if command_name == "getMore" {
+            command.get_str("collection")
+        } else {
+            command.get_str(command_name)
+        }
+        .unwrap_or("-")
+        .to_string();
+        let filter = command_filter(command_name, command);
+        let key = QueryKey {
+            command: command_name.to_string(),
+            collection,
+            shape: filter_shape(filter.as_ref()),
+        };
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(request_id, Pending { key, filter });
+    }
+
+    fn finished(&self, request_id: i32, duration: Duration, ok: bool) {
+        let Some(pending) = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&request_id)
+        else {
+            return;
+        };
+        let slow = !self.slow_threshold.is_zero() && duration >= self.slow_threshold;
+        if slow {
+            warn!(
+                "🐢 Slow query: {} {} took {:.0}ms, filter: {}",
+                pending.key.command,
+                pending.key.collection,
+                millis(duration),
+                pending
+                    .filter
+                    .as_ref()
+                    .map(|f| f.to_string())
+                    .unwrap_or_else(|| "-".to_string())
+            );
+        }
+
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let stat = stats.entry(pending.key).or_default();
+        stat.count += 1;
+        stat.failures += u64::from(!ok);
+        stat.slow += u64::from(slow);
+        stat.total += duration;
+        stat.max = stat.max.max(duration);
+    }
+
+    /// Aggregates, most total time first.
+    pub fn snapshot(&self) -> Vec<QueryStatView> {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let mut rows: Vec<QueryStatView> = stats
+            .iter()
+            .map(|(key, stat)| QueryStatView {
+                command: key.command.clone(),
+                collection: key.collection.clone(),
+                shape: key.shape.clone(),
+                count: stat.count,
+                failures: stat.failures,
+                slow: stat.slow,
+                avg_ms: millis(stat.total) / stat.count.max(1) as f64,
+                max_ms: millis(stat.max),
+                total_ms: millis(stat.total),
+            })
+            .collect();
+        rows.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        rows
+    }
+
+    pub fn slow_threshold(&self) -> Duration {
+        self.slow_threshold
+    }
+
+    pub fn reset(&self) {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn test_aggregates_by_filter_shape() {
+        let profiler = QueryProfiler::new(Duration::from_millis(100));
+        let find = |sector: &str| {
+            doc! {
+                "find": "stock_analysis",
+                "filter": { "sector": sector, "market_cap": { "$gte": 1e9 } },
+                "sort": { "market_cap": -1 },
+            }
+        };
+        profiler.started(1, "find", &find("Technology"));
+        profiler.started(2, "find", &find("Energy"));
+        profiler.started(3, "hello", &doc! { "hello": 1 });
+        profiler.started(
+            4,
+            "update",
+            &doc! { "update": "stock_analysis", "updates": [{ "q": { "symbol": "AAPL" }, "u": {} }] },
+        );
+        profiler.finished(1, Duration::from_millis(20), true);
+        profiler.finished(2, Duration::from_millis(150), true);
+        profiler.finished(3, Duration::from_millis(1), true);
+        profiler.finished(4, Duration::from_millis(5), false);
+
+        let stats = profiler.snapshot();
+        assert_eq!(stats.len(), 2);
+        let find = &stats[0];
+        assert_eq!(
+            (find.command.as_str(), find.collection.as_str()),
+            ("find", "stock_analysis")
+        );
+        assert_eq!(find.shape, "{market_cap, sector}");
+        assert_eq!((find.count, find.slow), (2, 1));
+        assert!((find.avg_ms - 85.0).abs() < 1e-6);
+        assert!((find.max_ms - 150.0).abs() < 1e-6);
+
+        assert_eq!(stats[1].shape, "{symbol}");
+        assert_eq!(stats[1].failures, 1);
+
+        profiler.reset();
+        assert!(profiler.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_uses_first_match_stage() {
+        let command = doc! {
+            "aggregate": "stock_analysis",
+            "pipeline": [{ "$match": { "is_oversold": true } }, { "$group": { "_id": "$sector" } }],
+        };
+        let filter = command_filter("aggregate", &command);
+        assert_eq!(filter_shape(filter.as_ref()), "{is_oversold}");
+        assert_eq!(filter_shape(None), "-");
+    }
+}
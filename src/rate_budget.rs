@@ -0,0 +1,202 @@
+//! Outbound request budget shared by interactive and background Yahoo work.
+//!
+//! One token bucket covers every Yahoo request the process makes, so the
+//! analysis cycle and on-demand API fetches together stay under
+//! `YAHOO_BUDGET_PER_MIN`. The cycle, sector ETF snapshots and the intraday
+//! poller acquire as [`Priority::Background`], which may not take the last
+//! `YAHOO_INTERACTIVE_RESERVE` tokens and yields while an interactive request
+//! is waiting. API handlers acquire as [`Priority::Interactive`] and only
+//! wait when the bucket is empty.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Shortest pause between attempts while waiting for a token.
+const MIN_WAIT: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Interactive,
+    Background,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    interactive_waiting: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStats {
+    pub per_minute: f64,
+    pub available: f64,
+    pub interactive_granted: u64,
+    pub background_granted: u64,
+    /// Total time background requests spent waiting for a token.
+    pub background_wait_ms: u64,
+}
+
+pub struct RequestBudget {
+    rate_per_sec: f64,
+    burst: f64,
+    interactive_reserve: f64,
+    bucket: Mutex<Bucket>,
+    interactive_granted: AtomicU64,
+    background_granted: AtomicU64,
+    background_wait_ms: AtomicU64,
+}
+
+impl RequestBudget {
+    /// `per_minute` requests on average, at most `burst` back to back, of
+    /// which `interactive_reserve` are held back from background work.
+    pub fn new(per_minute: f64, burst: u32, interactive_reserve: u32) -> Self {
+        Self {
+            rate_per_sec: per_minute / 60.0,
+            burst: burst as f64,
+            interactive_reserve: interactive_reserve as f64,
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                refilled_at: Instant::now(),
+                interactive_waiting: 0,
+            }),
+            interactive_granted: AtomicU64::new(0),
+            background_granted: AtomicU64::new(0),
+            background_wait_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Take a token, or say how long to wait before trying again.
+    fn try_take(&self, bucket: &mut Bucket, priority: Priority, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.rate_per_sec).min(self.burst);
+        bucket.refilled_at = now;
+
+        let needed = match priority {
+            Priority::Interactive => 1.0,
+            Priority::Background if bucket.interactive_waiting > 0 => {
+                return Some(MIN_WAIT.max(Duration::from_secs_f64(1.0 / self.rate_per_sec)));
+            }
+            Priority::Background => 1.0 + self.interactive_reserve,
+        };
+        if bucket.tokens >= needed {
+            bucket.tokens -= 1.0;
+            return None;
+        }
+        let wait = Duration::from_secs_f64((needed - bucket.tokens) / self.rate_per_sec);
+        Some(wait.max(MIN_WAIT))
+    }
+
+    /// Wait for capacity to make one request.
+    pub async fn acquire(&self, priority: Priority) {
+        let started = Instant::now();
+        // Registered while an interactive request waits; dropping it (also
+        // when the request is cancelled) lets background work resume.
+        let mut waiting: Option<InteractiveWaiting> = None;
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+                let wait = self.try_take(&mut bucket, priority, Instant::now());
+                if wait.is_some() && priority == Priority::Interactive && waiting.is_none() {
+                    bucket.interactive_waiting += 1;
+                    waiting = Some(InteractiveWaiting(self));
+                }
+                wait
+            };
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => break,
+            }
+        }
+        match priority {
+            Priority::Interactive => self.interactive_granted.fetch_add(1, Ordering::Relaxed),
+            Priority::Background => {
+                let waited = started.elapsed().as_millis() as u64;
+                self.background_wait_ms.fetch_add(waited, Ordering::Relaxed);
+                self.background_granted.fetch_add(1, Ordering::Relaxed)
+            }
+        };
+    }
+
+    pub fn stats(&self) -> BudgetStats {
+        let available = self.bucket.lock().unwrap_or_else(|e| e.into_inner()).tokens;
+        BudgetStats {
+            per_minute: self.rate_per_sec * 60.0,
+            available,
+            interactive_granted: self.interactive_granted.load(Ordering::Relaxed),
+            background_granted: self.background_granted.load(Ordering::Relaxed),
+            background_wait_ms: self.background_wait_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct InteractiveWaiting<'a>(&'a RequestBudget);
+
+impl Drop for InteractiveWaiting<'_> {
+    fn drop(&mut self) {
+        let mut bucket = self.0.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.interactive_waiting = bucket.interactive_waiting.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_leaves_the_reserve_to_interactive() {
+        // 60/min = 1 token per second, burst 3, 2 reserved.
+        let budget = RequestBudget::new(60.0, 3, 2);
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 3.0,
+            refilled_at: start,
+            interactive_waiting: 0,
+        };
+
+        assert!(budget
+            .try_take(&mut bucket, Priority::Background, start)
+            .is_none());
+        let wait = budget
+            .try_take(&mut bucket, Priority::Background, start)
+            .unwrap();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        assert!(budget
+            .try_take(&mut bucket, Priority::Interactive, start)
+            .is_none());
+        assert!(budget
+            .try_take(&mut bucket, Priority::Interactive, start)
+            .is_none());
+        let wait = budget
+            .try_take(&mut bucket, Priority::Interactive, start)
+            .unwrap();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        // Refill caps at the burst size.
+        let later = start + Duration::from_secs(30);
+        assert!(budget
+            .try_take(&mut bucket, Priority::Background, later)
+            .is_none());
+        assert!((bucket.tokens - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_background_yields_to_waiting_interactive() {
+        let budget = RequestBudget::new(600.0, 10, 0);
+        let now = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 10.0,
+            refilled_at: now,
+            interactive_waiting: 1,
+        };
+        assert!(budget
+            .try_take(&mut bucket, Priority::Background, now)
+            .is_some());
+        assert!(budget
+            .try_take(&mut bucket, Priority::Interactive, now)
+            .is_none());
+    }
+}
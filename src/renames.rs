@@ -0,0 +1,127 @@
+//! Ticker rename detection.
+//!
+//! The NASDAQ screener carries no CIK, so renames are detected by company
+//! name: when a symbol drops out of the universe in the same cycle a new
+//! symbol appears under the same (normalized) company name, the old ticker is
+//! treated as renamed. Stored analyses, signals and positions are moved to
+//! the new symbol and the old one is kept as an alias for lookups. Renames
+//! that also change the company name (FB → META) are not caught.
+
+use std::collections::HashMap;
+
+/// Listing boilerplate NASDAQ appends to company names.
+const NAME_NOISE: &[&str] = &[
+    "common",
+    "stock",
+    "shares",
+    "ordinary",
+    "class",
+    "american",
+    "depositary",
+    "depository",
+    "ads",
+    "adr",
+    "inc",
+    "incorporated",
+    "corp",
+    "corporation",
+    "co",
+    "company",
+    "ltd",
+    "limited",
+    "plc",
+    "holdings",
+    "holding",
+    "group",
+    "the",
+    "sa",
+    "nv",
+    "ag",
+    "se",
+];
+
+/// Lowercased company name with punctuation and listing boilerplate removed,
+/// e.g. `"Meta Platforms, Inc. Class A Common Stock"` → `"meta platforms"`.
+/// Single-letter tokens (the class letter) are dropped too.
+pub fn normalize_company_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| w.len() > 1 && !NAME_NOISE.contains(w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `(old_symbol, new_symbol)` pairs between two universes, each given as
+/// symbol → normalized company name. Names shared by several vanished or
+/// several new symbols are ambiguous and skipped.
+pub fn detect_renames(
+    previous: &HashMap<String, String>,
+    current: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    let mut vanished: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (symbol, name) in previous {
+        if !name.is_empty() && !current.contains_key(symbol) {
+            vanished.entry(name).or_default().push(symbol);
+        }
+    }
+    let mut appeared: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (symbol, name) in current {
+        if !name.is_empty() && !previous.contains_key(symbol) {
+            appeared.entry(name).or_default().push(symbol);
+        }
+    }
+
+    let mut renames: Vec<(String, String)> = vanished
+        .into_iter()
+        .filter_map(
+            |(name, old)| match (old.as_slice(), appeared.get(name)?.as_slice()) {
+                ([old], [new]) => Some((old.to_string(), new.to_string())),
+                _ => None,
+            },
+        )
+        .collect();
+    renames.sort();
+    renames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn universe(rows: &[(&str, &str)]) -> HashMap<String, String> {
+        rows.iter()
+            .map(|(s, n)| (s.to_string(), normalize_company_name(n)))
+            .collect()
+    }
+
+    #[test]
+    fn normalizes_listing_boilerplate() {
+        assert_eq!(
+            normalize_company_name("Meta Platforms, Inc. Class A Common Stock"),
+            "meta platforms"
+        );
+        assert_eq!(
+            normalize_company_name("Block, Inc. Class A Common Stock"),
+            "block"
+        );
+    }
+
+    #[test]
+    fn detects_unambiguous_renames_only() {
+        let previous = universe(&[
+            ("SQ", "Block, Inc. Class A Common Stock"),
+            ("AAPL", "Apple Inc. Common Stock"),
+            ("OLD1", "Twin Co"),
+            ("OLD2", "Twin Co"),
+        ]);
+        let current = universe(&[
+            ("XYZ", "Block, Inc. Class A Common Stock"),
+            ("AAPL", "Apple Inc. Common Stock"),
+            ("NEW1", "Twin Co"),
+        ]);
+        assert_eq!(
+            detect_renames(&previous, &current),
+            vec![("SQ".to_string(), "XYZ".to_string())]
+        );
+    }
+}
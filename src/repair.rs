@@ -0,0 +1,472 @@
+//! Startup consistency repair for `stock_analysis`.
+//!
+//! Documents written by older versions can carry shapes `StockAnalysis` no
+//! longer reads: strings where numbers are expected, missing oversold /
+//! overbought flags, malformed nested blocks, news items without a title or
+//! URL. A single unreadable document fails every cursor that touches it
+//! (cache warm, market summary, filters), so each document is checked once at
+//! startup. Fixable fields are cleared or recomputed in place. Documents that
+//! are still unreadable (no `symbol`, `price` or `analyzed_at`), and older
+//! duplicates of the same symbol, are moved to the `quarantine` collection
+//! with the reason. Float market caps and volumes from before `units.rs` are
+//! rewritten as `Int64`, here and in `analysis_history`. The totals are
+//! logged as a repair report.
+//!
+//! Between repairs, [`read_lenient`] lets `MongoDB::get_all_analyses` keep
+//! going past documents that no longer deserialize: known legacy shapes are
+//! repaired in memory, and every failure is recorded in `corrupt_documents`
+//! (`GET /api/admin/corrupt-documents`) instead of being dropped silently.
+//! `POST /api/admin/repair` runs the repair pass on demand.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::stream::StreamExt;
+use mongodb::bson::{self, doc, Bson, Document, RawDocumentBuf};
+use mongodb::Collection;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::db::MongoDB;
+use crate::indicators::TechnicalIndicators;
+use crate::models::{
+    BollingerBands, CrossSectionStats, EarningsData, MACDIndicator, NasdaqNewsItem,
+    NasdaqTechnicals, OnBalanceVolume, PerformanceReturns, SectorRelative, StochasticOscillator,
+    StockAnalysis, SuperTrend, SupportResistance,
+};
+use crate::units;
+
+/// `Option<f64>` fields of `StockAnalysis`.
+const OPTIONAL_NUMBERS: &[&str] = &[
+    "price_change",
+    "price_change_percent",
+    "rsi",
+    "sma_20",
+    "sma_50",
+];
+
+/// Whole-unit `Option<u64>` fields, stored as `Int64`.
+const QUANTITIES: &[&str] = &["volume", "market_cap"];
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairReport {
+    pub scanned: usize,
+    /// Documents fixed in place.
+    pub repaired: usize,
+    /// Fields cleared or recomputed across repaired documents.
+    pub fields_fixed: usize,
+    /// News items dropped for lacking a title or URL.
+    pub news_items_removed: usize,
+    /// Float market caps and volumes rewritten as integers, across
+    /// `stock_analysis` and `analysis_history`.
+    pub units_migrated: u64,
+    /// Unreadable documents moved to `quarantine`.
+    pub quarantined: usize,
+    /// Older duplicates of a symbol moved to `quarantine`.
+    pub duplicates: usize,
+}
+
+impl fmt::Display for RepairReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} scanned, {} repaired ({} fields, {} news items, {} unit fields), {} quarantined, {} duplicates",
+            self.scanned,
+            self.repaired,
+            self.fields_fixed,
+            self.news_items_removed,
+            self.units_migrated,
+            self.quarantined,
+            self.duplicates
+        )
+    }
+}
+
+/// A document that reads cleanly after repair.
+#[derive(Debug)]
+struct Checked {
+    analysis: StockAnalysis,
+    fields_fixed: usize,
+    news_items_removed: usize,
+    units_migrated: usize,
+}
+
+fn number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Double(v) => Some(*v),
+        Bson::Int32(v) => Some(*v as f64),
+        Bson::Int64(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+/// Whether a nested value still deserializes as its model type.
+type ReadableCheck = fn(&Bson) -> bool;
+
+fn readable<T: DeserializeOwned>(value: &Bson) -> bool {
+    bson::from_bson::<T>(value.clone()).is_ok()
+}
+
+/// Fix `doc` in place where possible. `Err` carries the reason it can't be
+/// read even after repair.
+fn repair_document(doc: &mut Document) -> std::result::Result<Checked, String> {
+    let mut fields_fixed = 0;
+
+    for key in OPTIONAL_NUMBERS {
+        if doc
+            .get(key)
+            .is_some_and(|v| *v != Bson::Null && number(v).is_none())
+        {
+            doc.insert(*key, Bson::Null);
+            fields_fixed += 1;
+        }
+    }
+
+    let mut units_migrated = 0;
+    for key in QUANTITIES {
+        let Some(value) = doc
+            .get(key)
+            .filter(|v| !matches!(v, Bson::Null | Bson::Int64(_)))
+        else {
+            continue;
+        };
+        match number(value)
+            .and_then(units::to_u64)
+            .and_then(|v| i64::try_from(v).ok())
+        {
+            Some(whole) => {
+                doc.insert(*key, whole);
+                units_migrated += 1;
+            }
+            None => {
+                doc.insert(*key, Bson::Null);
+                fields_fixed += 1;
+            }
+        }
+    }
+
+    let nested: [(&str, ReadableCheck); 12] = [
+        ("macd", readable::<MACDIndicator>),
+        ("bollinger", readable::<BollingerBands>),
+        ("stochastic", readable::<StochasticOscillator>),
+        ("obv", readable::<OnBalanceVolume>),
+        ("supertrend", readable::<SuperTrend>),
+        ("support_resistance", readable::<SupportResistance>),
+        ("earnings", readable::<EarningsData>),
+        ("technicals", readable::<NasdaqTechnicals>),
+        ("sector_relative", readable::<SectorRelative>),
+        ("performance", readable::<PerformanceReturns>),
+        ("cross_section", readable::<CrossSectionStats>),
+        ("warnings", readable::<Vec<String>>),
+    ];
+    for (key, is_readable) in nested {
+        if doc
+            .get(key)
+            .is_some_and(|v| *v != Bson::Null && !is_readable(v))
+        {
+            doc.remove(key);
+            fields_fixed += 1;
+        }
+    }
+
+    let mut news_items_removed = 0;
+    match doc.get("news") {
+        Some(Bson::Array(items)) => {
+            let kept: Vec<Bson> = items
+                .iter()
+                .filter(|item| readable::<NasdaqNewsItem>(item))
+                .cloned()
+                .collect();
+            news_items_removed = items.len() - kept.len();
+            if news_items_removed > 0 {
+                doc.insert("news", kept);
+            }
+        }
+        Some(Bson::Null) | None => {}
+        Some(_) => {
+            doc.remove("news");
+            fields_fixed += 1;
+        }
+    }
+
+    let rsi = doc.get("rsi").and_then(number);
+    for (key, flag) in [
+        ("is_oversold", TechnicalIndicators::is_oversold(rsi)),
+        ("is_overbought", TechnicalIndicators::is_overbought(rsi)),
+    ] {
+        if !matches!(doc.get(key), Some(Bson::Boolean(_))) {
+            doc.insert(key, flag);
+            fields_fixed += 1;
+        }
+    }
+
+    let analysis = bson::from_document::<StockAnalysis>(doc.clone()).map_err(|e| e.to_string())?;
+    Ok(Checked {
+        analysis,
+        fields_fixed,
+        news_items_removed,
+        units_migrated,
+    })
+}
+
+/// A stored analysis that `StockAnalysis` no longer reads, as found by a
+/// lenient read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorruptDocument {
+    /// Collection the document was read from.
+    pub source: String,
+    pub document_id: String,
+    pub symbol: Option<String>,
+    /// Why the strict read failed.
+    pub error: String,
+    /// Whether repairing known legacy shapes in memory recovered it; the
+    /// rest are left out of the results until repaired or quarantined.
+    pub recovered: bool,
+    pub seen_at: DateTime<Utc>,
+}
+
+/// Read `raw` from `source` as an analysis. A document the strict read
+/// rejects is repaired in memory (see [`repair_document`]) and reported,
+/// whether or not the repair recovered it.
+pub fn read_lenient(
+    source: &str,
+    raw: &RawDocumentBuf,
+) -> (Option<StockAnalysis>, Option<CorruptDocument>) {
+    let error = match bson::from_slice::<StockAnalysis>(raw.as_bytes()) {
+        Ok(analysis) => return (Some(analysis), None),
+        Err(e) => e.to_string(),
+    };
+    let mut doc = Document::try_from(raw.as_ref()).unwrap_or_default();
+    let document_id = match doc.get("_id") {
+        Some(Bson::ObjectId(id)) => id.to_hex(),
+        Some(id) => id.to_string(),
+        None => String::new(),
+    };
+    let symbol = doc.get_str("symbol").ok().map(str::to_string);
+    let analysis = repair_document(&mut doc)
+        .ok()
+        .map(|checked| checked.analysis);
+    let report = CorruptDocument {
+        source: source.to_string(),
+        document_id,
+        symbol,
+        error,
+        recovered: analysis.is_some(),
+        seen_at: Utc::now(),
+    };
+    (analysis, Some(report))
+}
+
+/// Ids of every analysis except the newest per symbol.
+fn older_duplicates(entries: Vec<(String, DateTime<Utc>, Bson)>) -> Vec<Bson> {
+    let mut newest: HashMap<String, (DateTime<Utc>, Bson)> = HashMap::new();
+    let mut older = Vec::new();
+    for (symbol, analyzed_at, id) in entries {
+        match newest.get_mut(&symbol) {
+            Some(kept) if analyzed_at > kept.0 => {
+                older.push(std::mem::replace(kept, (analyzed_at, id)).1);
+            }
+            Some(_) => older.push(id),
+            None => {
+                newest.insert(symbol, (analyzed_at, id));
+            }
+        }
+    }
+    older
+}
+
+/// Scan `stock_analysis`, repair what can be repaired and quarantine the
+/// rest. Changes are applied after the scan so the cursor never sees its
+/// own writes.
+pub async fn run(db: &MongoDB) -> Result<RepairReport> {
+    let analyses: Collection<Document> = db.database().collection("stock_analysis");
+    let mut report = RepairReport::default();
+    let mut replacements: Vec<(Bson, Document)> = Vec::new();
+    let mut unreadable: Vec<(Bson, String)> = Vec::new();
+    let mut seen: Vec<(String, DateTime<Utc>, Bson)> = Vec::new();
+
+    let mut cursor = analyses.find(doc! {}).await?;
+    while let Some(doc) = cursor.next().await {
+        let mut doc = doc?;
+        report.scanned += 1;
+        let Some(id) = doc.get("_id").cloned() else {
+            continue;
+        };
+        match repair_document(&mut doc) {
+            Ok(checked) => {
+                if checked.fields_fixed + checked.news_items_removed + checked.units_migrated > 0 {
+                    report.repaired += 1;
+                    report.fields_fixed += checked.fields_fixed;
+                    report.news_items_removed += checked.news_items_removed;
+                    report.units_migrated += checked.units_migrated as u64;
+                    replacements.push((id.clone(), doc));
+                }
+                seen.push((checked.analysis.symbol, checked.analysis.analyzed_at, id));
+            }
+            Err(reason) => unreadable.push((id, reason)),
+        }
+    }
+
+    for (id, doc) in replacements {
+        analyses.replace_one(doc! { "_id": id }, doc).await?;
+    }
+    report.quarantined = unreadable.len();
+    let duplicates = older_duplicates(seen);
+    report.duplicates = duplicates.len();
+
+    let quarantine: Collection<Document> = db.database().collection("quarantine");
+    let moves = unreadable.into_iter().chain(
+        duplicates
+            .into_iter()
+            .map(|id| (id, "older duplicate of a newer analysis".to_string())),
+    );
+    for (id, reason) in moves {
+        let Some(original) = analyses.find_one(doc! { "_id": id.clone() }).await? else {
+            continue;
+        };
+        warn!("🩺 Quarantining stock_analysis {}: {}", id, reason);
+        quarantine
+            .insert_one(doc! {
+                "source": "stock_analysis",
+                "reason": reason,
+                "quarantined_at": bson::to_bson(&Utc::now())?,
+                "document": original,
+            })
+            .await?;
+        analyses.delete_one(doc! { "_id": id }).await?;
+    }
+
+    report.units_migrated += migrate_history_units(db).await?;
+    Ok(report)
+}
+
+/// Round float quantities in `analysis_history` to `Int64` server-side.
+/// Snapshots are never rewritten otherwise and the collection is too large
+/// to walk document by document. Negative values are left for `opt_u64` to
+/// read as `None`.
+async fn migrate_history_units(db: &MongoDB) -> Result<u64> {
+    let history: Collection<Document> = db.database().collection("analysis_history");
+    let mut migrated = 0;
+    for key in QUANTITIES {
+        let field = format!("${}", key);
+        let result = history
+            .update_many(
+                doc! { *key: { "$type": "double", "$gte": 0.0 } },
+                vec![doc! { "$set": { *key: { "$toLong": { "$round": [field, 0] } } } }],
+            )
+            .await?;
+        migrated += result.modified_count;
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_doc() -> Document {
+        doc! {
+            "_id": bson::oid::ObjectId::new(),
+            "symbol": "AAPL",
+            "price": 190,
+            "rsi": "N/A",
+            "volume": 1_000_000.0,
+            "macd": { "macd_line": 1.0 },
+            "analyzed_at": "2025-06-02T14:30:00Z",
+            "news": [
+                { "title": "Apple rises", "url": "https://example.com/a" },
+                { "title": "No link" },
+            ],
+        }
+    }
+
+    #[test]
+    fn repairs_legacy_fields() {
+        let mut doc = legacy_doc();
+        let checked = repair_document(&mut doc).unwrap();
+        // rsi cleared, macd dropped, both flags added.
+        assert_eq!(checked.fields_fixed, 4);
+        assert_eq!(checked.news_items_removed, 1);
+        assert_eq!(checked.analysis.rsi, None);
+        assert_eq!(checked.analysis.price, 190.0);
+        assert!(checked.analysis.macd.is_none());
+        assert!(!checked.analysis.is_oversold);
+        assert_eq!(checked.analysis.news.unwrap().len(), 1);
+
+        // A repaired document is left alone on the next pass.
+        let again = repair_document(&mut doc).unwrap();
+        assert_eq!(
+            again.fields_fixed + again.news_items_removed + again.units_migrated,
+            0
+        );
+    }
+
+    #[test]
+    fn migrates_float_quantities_to_int64() {
+        let mut doc = legacy_doc();
+        doc.insert("market_cap", 2.5e12);
+        let checked = repair_document(&mut doc).unwrap();
+        assert_eq!(checked.units_migrated, 2);
+        assert_eq!(doc.get("volume"), Some(&Bson::Int64(1_000_000)));
+        assert_eq!(doc.get("market_cap"), Some(&Bson::Int64(2_500_000_000_000)));
+        assert_eq!(checked.analysis.market_cap, Some(2_500_000_000_000));
+
+        let mut doc = legacy_doc();
+        doc.insert("volume", -3.0);
+        let checked = repair_document(&mut doc).unwrap();
+        assert_eq!(checked.units_migrated, 0);
+        assert_eq!(doc.get("volume"), Some(&Bson::Null));
+    }
+
+    #[test]
+    fn unreadable_documents_are_rejected() {
+        let mut doc = legacy_doc();
+        doc.remove("analyzed_at");
+        assert!(repair_document(&mut doc)
+            .unwrap_err()
+            .contains("analyzed_at"));
+    }
+
+    #[test]
+    fn lenient_reads_recover_legacy_shapes_and_report_failures() {
+        let raw = |doc: &Document| RawDocumentBuf::from_document(doc).unwrap();
+
+        let mut clean = legacy_doc();
+        repair_document(&mut clean).unwrap();
+        let (analysis, report) = read_lenient("stock_analysis", &raw(&clean));
+        assert_eq!(analysis.unwrap().symbol, "AAPL");
+        assert!(report.is_none());
+
+        let legacy = legacy_doc();
+        let (analysis, report) = read_lenient("stock_analysis", &raw(&legacy));
+        assert_eq!(analysis.unwrap().rsi, None);
+        let report = report.unwrap();
+        assert!(report.recovered);
+        assert_eq!(report.symbol.as_deref(), Some("AAPL"));
+        assert_eq!(
+            report.document_id,
+            legacy.get_object_id("_id").unwrap().to_hex()
+        );
+
+        let mut unreadable = legacy_doc();
+        unreadable.remove("analyzed_at");
+        let (analysis, report) = read_lenient("stock_analysis", &raw(&unreadable));
+        assert!(analysis.is_none());
+        assert!(!report.unwrap().recovered);
+    }
+
+    #[test]
+    fn keeps_newest_duplicate() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let older = older_duplicates(vec![
+            ("AAPL".into(), at("2025-06-01T00:00:00Z"), Bson::Int32(1)),
+            ("MSFT".into(), at("2025-06-01T00:00:00Z"), Bson::Int32(2)),
+            ("AAPL".into(), at("2025-06-03T00:00:00Z"), Bson::Int32(3)),
+            ("AAPL".into(), at("2025-06-02T00:00:00Z"), Bson::Int32(4)),
+        ]);
+        assert_eq!(older, vec![Bson::Int32(1), Bson::Int32(4)]);
+    }
+}
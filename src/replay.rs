@@ -0,0 +1,368 @@
+//! Recompute `analysis_history` from the raw response archive.
+//!
+//! [`run`] walks the archived days from `from` to `to` (see `archive.rs`)
+//! and re-runs parsing and the indicator pipeline over every archived Yahoo
+//! chart, together with the NASDAQ quote fetched right after it and the
+//! day's screener market cap. Each result is written to `analysis_history`
+//! at the time of the chart fetch: onto the snapshot the cycle saved from
+//! the same responses where there is one, as a new snapshot otherwise. So a
+//! parser fix or a new indicator can be backfilled over as many days as the
+//! archive keeps, and `as_of` queries see it.
+//!
+//! Charts too short for an analysis (five-day quote lookups) are skipped.
+//! Run with `POST /api/admin/archive/replay` or
+//! `auto_analyser_2 replay <from> <to> [SYMBOL,...]`; one run at a time.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::analysis;
+use crate::archive::{self, ArchiveEntry, RawArchive, Source};
+use crate::db::MongoDB;
+use crate::indicators::IndicatorConfig;
+use crate::models::{HistoricalPrice, NasdaqTechnicals};
+use crate::nasdaq;
+use crate::yahoo;
+
+/// How long after a chart fetch the cycle's NASDAQ quote and saved snapshot
+/// may land and still count as the same analysis.
+const MATCH_WINDOW_MINUTES: i64 = 10;
+
+/// Fields a replay sets on the cycle's snapshot. `technicals`, `sector` and
+/// `market_cap` are added when their responses were archived too.
+const REPLAYED_FIELDS: [&str; 27] = [
+    "price",
+    "price_change",
+    "price_change_percent",
+    "rsi",
+    "sma_20",
+    "sma_50",
+    "sma_200",
+    "macd",
+    "bollinger",
+    "stochastic",
+    "williams_r",
+    "cci",
+    "mfi",
+    "vwap",
+    "obv",
+    "cross_signal",
+    "cross_date",
+    "supertrend",
+    "supertrend_flip_date",
+    "support_resistance",
+    "indicator_periods",
+    "volume",
+    "is_oversold",
+    "is_overbought",
+    "stoch_oversold",
+    "stoch_overbought",
+    "performance",
+];
+
+/// Longest range one run covers.
+const MAX_DAYS: i64 = 366;
+
+static REPLAY_RUNNING: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Report of the last completed run, in memory only.
+static LAST_REPORT: Lazy<std::sync::RwLock<Option<ReplayReport>>> =
+    Lazy::new(|| std::sync::RwLock::new(None));
+
+pub fn is_running() -> bool {
+    REPLAY_RUNNING.try_lock().is_err()
+}
+
+pub fn last_report() -> Option<ReplayReport> {
+    LAST_REPORT.read().ok()?.clone()
+}
+
+/// Days (UTC, inclusive) and symbols to replay.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayRequest {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Empty replays every archived symbol.
+    #[serde(default)]
+    pub symbols: Vec<String>,
+}
+
+/// What one run did.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Days with archived responses.
+    pub days: usize,
+    /// Archived charts considered.
+    pub charts: usize,
+    /// Cycle snapshots updated in place.
+    pub merged: usize,
+    /// Snapshots added where the cycle saved none.
+    pub inserted: usize,
+    /// Charts with too little data for an analysis.
+    pub skipped: usize,
+    /// Unreadable responses and failed writes.
+    pub failures: usize,
+}
+
+/// One archived chart and the responses that go with it.
+#[derive(Debug, Clone, PartialEq)]
+struct PlannedChart {
+    symbol: String,
+    fetched_at: DateTime<Utc>,
+    chart: PathBuf,
+    technicals: Option<PathBuf>,
+}
+
+/// A day's charts in fetch order, each with the first NASDAQ technicals
+/// response for its symbol within the match window, and the day's last
+/// screener response.
+fn plan_day(
+    entries: &[ArchiveEntry],
+    symbols: &HashSet<String>,
+) -> (Vec<PlannedChart>, Option<PathBuf>) {
+    let window = Duration::minutes(MATCH_WINDOW_MINUTES);
+    let of = |source: Source, kind: &'static str| {
+        entries
+            .iter()
+            .filter(move |e| e.source == source.name() && e.kind == kind)
+    };
+    let wanted = |symbol: &str| symbols.is_empty() || symbols.contains(symbol);
+
+    let mut technicals: HashMap<&str, Vec<&ArchiveEntry>> = HashMap::new();
+    for entry in of(Source::Nasdaq, "technicals") {
+        technicals.entry(&entry.symbol).or_default().push(entry);
+    }
+    let charts = of(Source::Yahoo, "chart")
+        .filter(|chart| wanted(&chart.symbol))
+        .map(|chart| PlannedChart {
+            symbol: chart.symbol.clone(),
+            fetched_at: chart.fetched_at,
+            chart: chart.path.clone(),
+            technicals: technicals
+                .get(chart.symbol.as_str())
+                .and_then(|quotes| {
+                    quotes.iter().find(|q| {
+                        q.fetched_at >= chart.fetched_at
+                            && q.fetched_at <= chart.fetched_at + window
+                    })
+                })
+                .map(|q| q.path.clone()),
+        })
+        .collect();
+    let screener = of(Source::Nasdaq, "screener")
+        .next_back()
+        .map(|e| e.path.clone());
+    (charts, screener)
+}
+
+/// Screener market caps by symbol key.
+fn read_market_caps(path: &std::path::Path) -> Result<HashMap<String, f64>> {
+    let response = archive::read_entry(path)?;
+    let mut caps = HashMap::new();
+    nasdaq::parse_screener_rows(response.body.as_bytes(), |row| {
+        if let Some(cap) = analysis::parse_market_cap(&row.market_cap) {
+            caps.insert(crate::symbols::normalize_symbol_key(&row.symbol), cap);
+        }
+    })?;
+    Ok(caps)
+}
+
+/// Parse a planned chart and its NASDAQ quote. An unreadable quote only
+/// drops the quote.
+fn read_chart(planned: &PlannedChart) -> Result<(Vec<HistoricalPrice>, Option<NasdaqTechnicals>)> {
+    let chart = archive::read_entry(&planned.chart)?;
+    let prices = yahoo::parse_historical_prices(&chart.body, &planned.symbol)?;
+    let technicals = planned.technicals.as_deref().and_then(|path| {
+        archive::read_entry(path)
+            .and_then(|quote| nasdaq::parse_technicals_response(&quote.body, &planned.symbol))
+            .map_err(|e| debug!("Replay: ignoring NASDAQ quote {}: {}", path.display(), e))
+            .ok()
+    });
+    Ok((prices, technicals))
+}
+
+/// Replay `request` into `analysis_history` with the `indicators` periods
+/// configured now.
+pub async fn run(
+    db: &MongoDB,
+    archive: &RawArchive,
+    request: &ReplayRequest,
+    indicators: &IndicatorConfig,
+) -> Result<ReplayReport> {
+    if request.from > request.to {
+        bail!("from must not be after to");
+    }
+    if (request.to - request.from).num_days() >= MAX_DAYS {
+        bail!("at most {} days per replay", MAX_DAYS);
+    }
+    let _running = REPLAY_RUNNING
+        .try_lock()
+        .map_err(|_| anyhow!("a replay is already in progress"))?;
+
+    let symbols: HashSet<String> = request
+        .symbols
+        .iter()
+        .map(|s| crate::symbols::normalize_symbol_key(s))
+        .collect();
+    let started_at = Utc::now();
+    let mut report = ReplayReport {
+        started_at,
+        finished_at: started_at,
+        from: request.from,
+        to: request.to,
+        days: 0,
+        charts: 0,
+        merged: 0,
+        inserted: 0,
+        skipped: 0,
+        failures: 0,
+    };
+
+    let window = Duration::minutes(MATCH_WINDOW_MINUTES);
+    for date in request.from.iter_days().take_while(|d| *d <= request.to) {
+        let (charts, caps) = {
+            let archive = archive.clone();
+            let symbols = symbols.clone();
+            tokio::task::spawn_blocking(move || -> Result<_> {
+                let (charts, screener) = plan_day(&archive.entries(date)?, &symbols);
+                let caps = match screener.filter(|_| !charts.is_empty()) {
+                    Some(path) => read_market_caps(&path).unwrap_or_else(|e| {
+                        warn!("Replay {}: unreadable screener: {}", date, e);
+                        HashMap::new()
+                    }),
+                    None => HashMap::new(),
+                };
+                Ok((charts, caps))
+            })
+            .await??
+        };
+        if charts.is_empty() {
+            continue;
+        }
+        report.days += 1;
+        report.charts += charts.len();
+
+        for planned in charts {
+            let parsed = {
+                let planned = planned.clone();
+                tokio::task::spawn_blocking(move || read_chart(&planned)).await?
+            };
+            let (prices, technicals) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    debug!(
+                        "Replay: unreadable chart {}: {}",
+                        planned.chart.display(),
+                        e
+                    );
+                    report.failures += 1;
+                    continue;
+                }
+            };
+            let market_cap = caps.get(&planned.symbol).copied();
+            let mut fields = REPLAYED_FIELDS.to_vec();
+            if technicals.is_some() {
+                fields.extend(["technicals", "sector"]);
+            }
+            if market_cap.is_some() {
+                fields.push("market_cap");
+            }
+            let analysis = match analysis::replayed_analysis(
+                &planned.symbol,
+                &prices,
+                technicals,
+                market_cap,
+                planned.fetched_at,
+                indicators,
+            ) {
+                Ok(analysis) => analysis,
+                Err(_) => {
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+            match db.merge_replayed_snapshot(&analysis, &fields, window).await {
+                Ok(true) => report.merged += 1,
+                Ok(false) => report.inserted += 1,
+                Err(e) => {
+                    warn!("Replay: failed to write {}: {}", planned.symbol, e);
+                    report.failures += 1;
+                }
+            }
+        }
+        info!(
+            "⏪ Replayed {}: {} merged, {} inserted so far",
+            date, report.merged, report.inserted
+        );
+    }
+
+    report.finished_at = Utc::now();
+    if let Ok(mut last) = LAST_REPORT.write() {
+        *last = Some(report.clone());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(source: &str, kind: &str, symbol: &str, minute: u32) -> ArchiveEntry {
+        ArchiveEntry {
+            source: source.to_string(),
+            kind: kind.to_string(),
+            symbol: symbol.to_string(),
+            fetched_at: Utc.with_ymd_and_hms(2025, 6, 2, 14, minute, 0).unwrap(),
+            path: PathBuf::from(format!("{}-{}-{}-{}", source, kind, symbol, minute)),
+        }
+    }
+
+    #[test]
+    fn test_plan_day_pairs_charts_with_following_quotes() {
+        let entries = vec![
+            entry("nasdaq", "screener", "_universe", 0),
+            entry("nasdaq", "technicals", "AAPL", 1),
+            entry("yahoo", "chart", "AAPL", 2),
+            entry("nasdaq", "technicals", "AAPL", 3),
+            entry("yahoo", "chart", "MSFT", 4),
+            entry("nasdaq", "technicals", "MSFT", 20),
+            entry("yahoo", "quote", "_batch", 21),
+        ];
+        let (charts, screener) = plan_day(&entries, &HashSet::new());
+        assert_eq!(screener, Some(PathBuf::from("nasdaq-screener-_universe-0")));
+        assert_eq!(charts.len(), 2);
+        assert_eq!(charts[0].symbol, "AAPL");
+        assert_eq!(
+            charts[0].technicals,
+            Some(PathBuf::from("nasdaq-technicals-AAPL-3"))
+        );
+        // The MSFT quote came long after the chart, so it isn't paired.
+        assert_eq!(charts[1].technicals, None);
+    }
+
+    #[test]
+    fn test_plan_day_filters_symbols() {
+        let entries = vec![
+            entry("yahoo", "chart", "AAPL", 2),
+            entry("yahoo", "chart", "MSFT", 4),
+        ];
+        let only: HashSet<String> = ["MSFT".to_string()].into();
+        let (charts, screener) = plan_day(&entries, &only);
+        assert_eq!(screener, None);
+        assert_eq!(
+            charts.iter().map(|c| c.symbol.as_str()).collect::<Vec<_>>(),
+            vec!["MSFT"]
+        );
+    }
+}
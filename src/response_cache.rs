@@ -0,0 +1,577 @@
+//! Response caching for expensive read endpoints.
+//!
+//! A middleware in front of the router answers `GET`s on configured routes
+//! from a moka cache keyed by path and query, so handlers such as the market
+//! summary, sector performance and heatmaps no longer cache by hand. Each
+//! route carries its own TTL (`RESPONSE_CACHE_TTLS`, e.g.
+//! `/api/sectors=300,/api/indexes/*/heatmap=120`, where `*` matches one path
+//! segment). Only `200` responses that are not `{"success": false}` are
+//! stored.
+//!
+//! Clients skip the cache with `X-Cache-Bypass: true` or
+//! `Cache-Control: no-cache`; the fresh response still replaces the cached
+//! one. Every response on a cached route carries `X-Cache: HIT|MISS|BYPASS`.
+//!
+//! The hottest views — [`PRECOMPUTED_ROUTES`] requested without a query —
+//! are kept without a TTL instead: their bytes are stored under the current
+//! data version and served until [`ResponseCache::invalidate_all`] bumps it
+//! after a cycle. They carry an `ETag` of that version, and a matching
+//! `If-None-Match` is answered with `304 Not Modified`.
+
+use anyhow::{anyhow, Result};
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use moka::{future::Cache, Expiry};
+use serde::Deserialize;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Routes cached when `RESPONSE_CACHE_TTLS` is unset.
+pub const DEFAULT_ROUTE_TTLS: &str = "/api/market-summary=60,/api/sectors=300,\
+/api/indexes/*/heatmap=120,/api/themes/*/heatmap=120,/api/analytics/correlation=600";
+
+/// Routes whose default (query-less) view is kept until the data changes.
+pub const PRECOMPUTED_ROUTES: &[&str] = &["/api/stocks", "/api/market-summary"];
+
+/// Handler headers replayed on a hit, besides the body.
+const STORED_HEADERS: &[&str] = &["content-type", "link", "x-total-count"];
+
+/// Request header that skips the cached copy.
+pub const BYPASS_HEADER: &str = "x-cache-bypass";
+/// Response header reporting how the request was served.
+pub const STATUS_HEADER: &str = "x-cache";
+
+/// One `pattern=seconds` entry of `RESPONSE_CACHE_TTLS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteTtl {
+    segments: Vec<String>,
+    pub ttl: Duration,
+}
+
+impl RouteTtl {
+    fn matches(&self, path: &str) -> bool {
+        let mut parts = path.trim_matches('/').split('/');
+        self.segments
+            .iter()
+            .all(|s| parts.next().is_some_and(|p| s == "*" || s == p))
+            && parts.next().is_none()
+    }
+}
+
+impl fmt::Display for RouteTtl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}={}", self.segments.join("/"), self.ttl.as_secs())
+    }
+}
+
+/// Cached routes and their TTLs; the first matching pattern wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteTtls(Vec<RouteTtl>);
+
+impl RouteTtls {
+    /// Parse `pattern=seconds` pairs separated by commas. An empty list
+    /// disables response caching.
+    pub fn parse(list: &str) -> Result<Self> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|entry| {
+                let (pattern, secs) = entry
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected pattern=seconds, got '{}'", entry))?;
+                let pattern = pattern.trim();
+                if !pattern.starts_with('/') {
+                    return Err(anyhow!("route '{}' must start with '/'", pattern));
+                }
+                let secs: u64 = secs
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow!("invalid TTL '{}' for {}", secs.trim(), pattern))?;
+                if secs == 0 {
+                    return Err(anyhow!("TTL for {} must be greater than 0", pattern));
+                }
+                Ok(RouteTtl {
+                    segments: pattern
+                        .trim_matches('/')
+                        .split('/')
+                        .map(str::to_string)
+                        .collect(),
+                    ttl: Duration::from_secs(secs),
+                })
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    pub fn ttl_for(&self, path: &str) -> Option<Duration> {
+        self.0.iter().find(|r| r.matches(path)).map(|r| r.ttl)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for RouteTtls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self.0.iter().map(|r| r.to_string()).collect();
+        f.write_str(&entries.join(","))
+    }
+}
+
+#[derive(Clone)]
+struct CachedResponse {
+    /// The `STORED_HEADERS` the handler set.
+    headers: HeaderMap,
+    body: Bytes,
+    /// `None` for precomputed views, which live until the version changes.
+    ttl: Option<Duration>,
+}
+
+/// Each entry expires after its route's TTL.
+struct RouteExpiry;
+
+impl Expiry<String, CachedResponse> for RouteExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &CachedResponse,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        value.ttl
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &CachedResponse,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        value.ttl
+    }
+}
+
+#[derive(Default)]
+struct ResponseMetrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    bypasses: AtomicU64,
+}
+
+/// Snapshot of response-cache counters, reported in `CacheStats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub bypasses: u64,
+    pub entries: u64,
+    pub weighted_bytes: u64,
+}
+
+#[derive(Clone)]
+pub struct ResponseCache {
+    routes: Arc<RouteTtls>,
+    entries: Cache<String, CachedResponse>,
+    metrics: Arc<ResponseMetrics>,
+    /// Bumped on every invalidation; keys precomputed views so a response
+    /// rendered before an invalidation is never served after it.
+    version: Arc<AtomicU64>,
+}
+
+impl ResponseCache {
+    /// `max_bytes` bounds the cache by total body size.
+    pub fn new(routes: RouteTtls, max_bytes: u64) -> Self {
+        ResponseCache {
+            routes: Arc::new(routes),
+            entries: Cache::builder()
+                .expire_after(RouteExpiry)
+                .weigher(|key: &String, value: &CachedResponse| {
+                    (key.len() + value.body.len()).clamp(1, u32::MAX as usize) as u32
+                })
+                .max_capacity(max_bytes)
+                .build(),
+            metrics: Arc::new(ResponseMetrics::default()),
+            version: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Replace the cached routes, keeping the store and counters.
+    pub fn with_routes(mut self, routes: RouteTtls) -> Self {
+        self.routes = Arc::new(routes);
+        self
+    }
+
+    pub fn invalidate_all(&self) {
+        self.version.fetch_add(1, Ordering::AcqRel);
+        self.entries.invalidate_all();
+    }
+
+    /// Version of the data behind precomputed views.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// How a request is cached: precomputed until the data changes, for a
+    /// route TTL, or not at all.
+    fn policy(&self, path: &str, query: Option<&str>) -> Option<Option<Duration>> {
+        if query.is_none_or(str::is_empty) && PRECOMPUTED_ROUTES.contains(&path) {
+            return Some(None);
+        }
+        self.routes.ttl_for(path).map(Some)
+    }
+
+    pub fn stats(&self) -> ResponseCacheStats {
+        ResponseCacheStats {
+            hits: self.metrics.hits.load(Ordering::Relaxed),
+            misses: self.metrics.misses.load(Ordering::Relaxed),
+            bypasses: self.metrics.bypasses.load(Ordering::Relaxed),
+            entries: self.entries.entry_count(),
+            weighted_bytes: self.entries.weighted_size(),
+        }
+    }
+}
+
+/// Middleware: serve configured `GET` routes from the response cache.
+pub async fn serve_cached(
+    State(cache): State<ResponseCache>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let uri = request.uri();
+    let Some(ttl) = cache.policy(uri.path(), uri.query()) else {
+        return next.run(request).await;
+    };
+    // Precomputed views are keyed by the version they were rendered from.
+    let version = ttl.is_none().then(|| cache.version());
+    let key = match version {
+        Some(version) => format!("v{}:{}", version, uri.path()),
+        None => uri
+            .path_and_query()
+            .map(|pq| pq.as_str().to_string())
+            .unwrap_or_else(|| uri.path().to_string()),
+    };
+    let etag = version.map(etag_for);
+
+    let bypass = wants_bypass(request.headers());
+    if bypass {
+        cache.metrics.bypasses.fetch_add(1, Ordering::Relaxed);
+    } else if let Some(hit) = cache.entries.get(&key).await {
+        cache.metrics.hits.fetch_add(1, Ordering::Relaxed);
+        if let Some(etag) = &etag {
+            if if_none_match(request.headers(), etag) {
+                let response = StatusCode::NOT_MODIFIED.into_response();
+                return with_status(with_etag(response, etag), "HIT");
+            }
+        }
+        let mut response = Response::new(Body::from(hit.body));
+        response.headers_mut().extend(hit.headers);
+        if let Some(etag) = &etag {
+            response = with_etag(response, etag);
+        }
+        return with_status(response, "HIT");
+    } else {
+        cache.metrics.misses.fetch_add(1, Ordering::Relaxed);
+    }
+    let label = if bypass { "BYPASS" } else { "MISS" };
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return with_status(response, label);
+    }
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if !is_success(&bytes) {
+        return with_status(Response::from_parts(parts, Body::from(bytes)), label);
+    }
+    cache
+        .entries
+        .insert(
+            key,
+            CachedResponse {
+                headers: STORED_HEADERS
+                    .iter()
+                    .filter_map(|name| {
+                        let value = parts.headers.get(*name)?.clone();
+                        Some((HeaderName::from_static(name), value))
+                    })
+                    .collect(),
+                body: bytes.clone(),
+                ttl,
+            },
+        )
+        .await;
+    let mut response = Response::from_parts(parts, Body::from(bytes));
+    if let Some(etag) = &etag {
+        response = with_etag(response, etag);
+    }
+    with_status(response, label)
+}
+
+/// Weak, since the timezone middleware may still rewrite the cached body.
+fn etag_for(version: u64) -> HeaderValue {
+    HeaderValue::from_str(&format!("W/\"{}\"", version)).expect("ascii etag")
+}
+
+fn if_none_match(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let Ok(etag) = etag.to_str() else {
+        return false;
+    };
+    let bare = etag.trim_start_matches("W/");
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == bare)
+}
+
+fn with_etag(mut response: Response, etag: &HeaderValue) -> Response {
+    response.headers_mut().insert(header::ETAG, etag.clone());
+    response
+}
+
+fn wants_bypass(headers: &HeaderMap) -> bool {
+    let bypass_header = headers
+        .get(BYPASS_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true"));
+    let no_cache = headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|d| {
+            matches!(
+                d.trim().to_ascii_lowercase().as_str(),
+                "no-cache" | "no-store"
+            )
+        });
+    bypass_header || no_cache
+}
+
+/// Handlers report failures as `200 {"success": false}`; those are not cached.
+fn is_success(body: &[u8]) -> bool {
+    #[derive(Deserialize)]
+    struct Envelope {
+        success: Option<bool>,
+    }
+    serde_json::from_slice::<Envelope>(body).map_or(true, |e| e.success != Some(false))
+}
+
+fn with_status(mut response: Response, status: &'static str) -> Response {
+    response
+        .headers_mut()
+        .insert(STATUS_HEADER, HeaderValue::from_static(status));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, routing::get, Json, Router};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    #[test]
+    fn parses_and_matches_route_patterns() {
+        let routes = RouteTtls::parse(DEFAULT_ROUTE_TTLS).unwrap();
+        assert_eq!(routes.0.len(), 5);
+        assert_eq!(
+            routes.ttl_for("/api/sectors"),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            routes.ttl_for("/api/indexes/sp500/heatmap"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(routes.ttl_for("/api/sectors/etfs"), None);
+        assert_eq!(routes.ttl_for("/api/indexes/heatmap"), None);
+        assert_eq!(routes.ttl_for("/api/indexes/a/b/heatmap"), None);
+        assert_eq!(RouteTtls::parse(&routes.to_string()).unwrap(), routes);
+
+        assert!(RouteTtls::parse("").unwrap().is_empty());
+        assert!(RouteTtls::parse("/api/sectors").is_err());
+        assert!(RouteTtls::parse("api/sectors=60").is_err());
+        assert!(RouteTtls::parse("/api/sectors=0").is_err());
+        assert!(RouteTtls::parse("/api/sectors=soon").is_err());
+    }
+
+    #[test]
+    fn recognizes_bypass_requests() {
+        let mut headers = HeaderMap::new();
+        assert!(!wants_bypass(&headers));
+        headers.insert(BYPASS_HEADER, HeaderValue::from_static("TRUE"));
+        assert!(wants_bypass(&headers));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("max-age=0, no-cache"),
+        );
+        assert!(wants_bypass(&headers));
+    }
+
+    fn app(cache: ResponseCache) -> (Router, Arc<AtomicU64>) {
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = calls.clone();
+        let router = Router::new()
+            .route(
+                "/api/sectors",
+                get(move |Query(q): Query<HashMap<String, String>>| {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move { Json(json!({ "success": !q.contains_key("fail"), "calls": n })) }
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(cache, serve_cached));
+        (router, calls)
+    }
+
+    async fn call(router: &Router, uri: &str, bypass: bool) -> (String, Value) {
+        let mut request = Request::get(uri);
+        if bypass {
+            request = request.header(BYPASS_HEADER, "1");
+        }
+        let response = router
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.headers()[STATUS_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn caches_successful_responses_per_query() {
+        let cache = ResponseCache::new(RouteTtls::parse("/api/sectors=60").unwrap(), 1 << 20);
+        let (router, calls) = app(cache.clone());
+
+        assert_eq!(call(&router, "/api/sectors", false).await.0, "MISS");
+        let (status, body) = call(&router, "/api/sectors", false).await;
+        assert_eq!((status.as_str(), body["calls"].as_u64()), ("HIT", Some(1)));
+
+        // A bypass refreshes the stored copy.
+        let (status, body) = call(&router, "/api/sectors", true).await;
+        assert_eq!(
+            (status.as_str(), body["calls"].as_u64()),
+            ("BYPASS", Some(2))
+        );
+        assert_eq!(call(&router, "/api/sectors", false).await.1["calls"], 2);
+
+        assert_eq!(call(&router, "/api/sectors?x=1", false).await.0, "MISS");
+        assert_eq!(call(&router, "/api/sectors?fail", false).await.0, "MISS");
+        assert_eq!(call(&router, "/api/sectors?fail", false).await.0, "MISS");
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+        cache.invalidate_all();
+        assert_eq!(call(&router, "/api/sectors", false).await.0, "MISS");
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.bypasses), (2, 5, 1));
+    }
+
+    #[tokio::test]
+    async fn precomputed_views_live_until_the_version_changes() {
+        // No TTL configured for the route: only the default view is kept.
+        let cache = ResponseCache::new(RouteTtls::default(), 1 << 20);
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = calls.clone();
+        let router = Router::new()
+            .route(
+                "/api/market-summary",
+                get(move || {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move { Json(json!({ "success": true, "calls": n })) }
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                cache.clone(),
+                serve_cached,
+            ));
+
+        assert_eq!(call(&router, "/api/market-summary", false).await.0, "MISS");
+        assert_eq!(call(&router, "/api/market-summary", false).await.0, "HIT");
+        let filtered = router
+            .clone()
+            .oneshot(
+                Request::get("/api/market-summary?x=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(!filtered.headers().contains_key(STATUS_HEADER));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let conditional = |etag: &str| {
+            Request::get("/api/market-summary")
+                .header(header::IF_NONE_MATCH, etag)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let etag = format!("\"{}\"", cache.version());
+        let response = router.clone().oneshot(conditional(&etag)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        cache.invalidate_all();
+        let response = router.clone().oneshot(conditional(&etag)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::ETAG],
+            format!("W/\"{}\"", cache.version())
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn hits_keep_pagination_headers() {
+        let cache = ResponseCache::new(RouteTtls::default(), 1 << 20);
+        let router = Router::new()
+            .route(
+                "/api/stocks",
+                get(|| async {
+                    (
+                        [
+                            ("link", "</api/stocks?page=2>; rel=\"next\""),
+                            ("x-total-count", "120"),
+                        ],
+                        Json(json!({ "success": true })),
+                    )
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(cache, serve_cached));
+
+        for expected in ["MISS", "HIT"] {
+            let response = router
+                .clone()
+                .oneshot(Request::get("/api/stocks").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.headers()[STATUS_HEADER], expected);
+            assert_eq!(response.headers()["x-total-count"], "120");
+            assert_eq!(
+                response.headers()[header::LINK],
+                "</api/stocks?page=2>; rel=\"next\""
+            );
+            assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        }
+    }
+}
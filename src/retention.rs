@@ -0,0 +1,163 @@
+//! Retention for the append-only history collections.
+//!
+//! `analysis_history` grows by the size of the universe each cycle; the
+//! event, signal and daily performance collections grow more slowly but
+//! without bound. With `HISTORY_RETENTION_DAYS` set, documents in
+//! [`HISTORY_COLLECTIONS`] older than that are deleted once a day, so
+//! `as_of` queries and `/api/stocks/:symbol/analysis-history` can then only
+//! reach back that far.
+//!
+//! This is a pruning task rather than TTL indexes because none of these
+//! collections stores its age as a BSON date: snapshots carry `version`
+//! (microseconds), daily rows a `YYYY-MM-DD` string and the rest chrono's
+//! RFC 3339 strings, all of which order correctly as stored.
+//! `GET /api/admin/db/collections` shows what each collection takes up.
+
+use std::time::Duration as StdDuration;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use mongodb::bson::{doc, Document};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::db::MongoDB;
+use crate::maintenance::MaintenanceMode;
+
+/// How a collection stores the age retention compares against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgeField {
+    /// Microseconds since the epoch, like `analysis_history.version`.
+    Micros(&'static str),
+    /// A `YYYY-MM-DD` date.
+    Day(&'static str),
+    /// A chrono `DateTime<Utc>` serialized as an RFC 3339 string.
+    Timestamp(&'static str),
+}
+
+/// Collections pruned, with the field their age is read from. User data
+/// (positions, valuations, the alert audit trail) is never pruned.
+pub const HISTORY_COLLECTIONS: [(&str, AgeField); 9] = [
+    ("analysis_history", AgeField::Micros("version")),
+    ("week52_events", AgeField::Day("date")),
+    ("signals", AgeField::Day("signal_date")),
+    ("external_signals", AgeField::Timestamp("received_at")),
+    ("index_performance", AgeField::Day("date")),
+    ("theme_performance", AgeField::Day("date")),
+    ("index_contributors", AgeField::Day("date")),
+    ("notification_history", AgeField::Timestamp("created_at")),
+    ("analysis_runs", AgeField::Timestamp("started_at")),
+];
+
+/// Oldest time kept with `days` of retention; `None` keeps all.
+pub fn cutoff(now: DateTime<Utc>, days: u32) -> Option<DateTime<Utc>> {
+    (days > 0).then(|| now - Duration::days(days as i64))
+}
+
+/// Filter matching documents older than `before`. Documents missing the
+/// field (snapshots from before versioning) don't match and are kept.
+pub fn expired_filter(age: AgeField, before: DateTime<Utc>) -> Document {
+    match age {
+        AgeField::Micros(field) => doc! { field: { "$lt": before.timestamp_micros() } },
+        AgeField::Day(field) => {
+            doc! { field: { "$lt": before.format("%Y-%m-%d").to_string() } }
+        }
+        AgeField::Timestamp(field) => {
+            doc! { field: { "$lt": before.to_rfc3339_opts(SecondsFormat::Secs, true) } }
+        }
+    }
+}
+
+/// Documents removed from one collection by a pruning pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pruned {
+    pub collection: String,
+    pub deleted: u64,
+}
+
+/// Delete documents older than the retention window from every history
+/// collection. A failing collection is logged and skipped.
+pub async fn prune(db: &MongoDB, days: u32) -> Result<Vec<Pruned>> {
+    let Some(before) = cutoff(Utc::now(), days) else {
+        return Ok(Vec::new());
+    };
+    let mut pruned = Vec::new();
+    for (collection, age) in HISTORY_COLLECTIONS {
+        match db
+            .delete_matching(collection, expired_filter(age, before))
+            .await
+        {
+            Ok(deleted) => pruned.push(Pruned {
+                collection: collection.to_string(),
+                deleted,
+            }),
+            Err(e) => warn!("Pruning {} failed: {}", collection, e),
+        }
+    }
+    Ok(pruned)
+}
+
+/// Prune once now and then daily. Skipped while read-only mode is on.
+pub fn spawn(db: MongoDB, days: u32, maintenance: MaintenanceMode) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(StdDuration::from_secs(24 * 3600));
+        loop {
+            ticker.tick().await;
+            if maintenance.is_read_only() {
+                continue;
+            }
+            match prune(&db, days).await {
+                Ok(pruned) => {
+                    for p in pruned.iter().filter(|p| p.deleted > 0) {
+                        info!(
+                            "🧹 Pruned {} old documents from {}",
+                            p.deleted, p.collection
+                        );
+                    }
+                }
+                Err(e) => warn!("History pruning failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cutoff() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 31, 12, 0, 0).unwrap();
+        assert_eq!(cutoff(now, 0), None);
+        assert_eq!(
+            cutoff(now, 30),
+            Some(Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_expired_filter_matches_each_storage_format() {
+        let before = Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            expired_filter(AgeField::Micros("version"), before),
+            doc! { "version": { "$lt": before.timestamp_micros() } }
+        );
+        assert_eq!(
+            expired_filter(AgeField::Day("date"), before),
+            doc! { "date": { "$lt": "2025-03-01" } }
+        );
+        // Stored timestamps sort against the cutoff as strings.
+        let filter = expired_filter(AgeField::Timestamp("received_at"), before);
+        let bound = filter
+            .get_document("received_at")
+            .unwrap()
+            .get_str("$lt")
+            .unwrap()
+            .to_string();
+        let stored = serde_json::to_value(before - Duration::seconds(1)).unwrap();
+        assert!(stored.as_str().unwrap() < bound.as_str());
+        let stored = serde_json::to_value(before + Duration::seconds(1)).unwrap();
+        assert!(stored.as_str().unwrap() > bound.as_str());
+    }
+}
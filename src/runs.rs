@@ -0,0 +1,225 @@
+//! Audit log of analysis cycles, for tracking the pipeline's reliability
+//! over weeks rather than only the cycle in progress.
+//!
+//! Every cycle the engine finishes, ends early or fails is recorded in
+//! `analysis_runs` with its timing, how many symbols it attempted, skipped
+//! and saved, and its errors broken down by source. `GET /api/runs` lists
+//! recent runs with a summary of them. Cycles paused by read-only mode are
+//! not recorded, since recording one writes.
+//!
+//! `errors_by_source` keys are `yahoo` (price fetches), `nasdaq` (the
+//! screener listing, after which the cycle falls back to its cached list),
+//! `analysis` (bars that couldn't be analyzed), `database` (failed saves)
+//! and, for optional stages that failed or timed out without failing their
+//! symbol, the stage name (`technicals`, `news`, `fundamentals`, `ai`).
+//! Only symbol-level failures count towards `errors`, matching
+//! `/api/progress`.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::models::EngineMode;
+
+/// `errors_by_source` key for analyses that failed after a fetch.
+pub const ANALYSIS_SOURCE: &str = "analysis";
+/// `errors_by_source` key for analyses that couldn't be saved.
+pub const DATABASE_SOURCE: &str = "database";
+
+/// How a cycle ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Completed,
+    /// Stopped part way when degraded mode tripped; unprocessed symbols
+    /// were left for the next cycle.
+    EndedEarly,
+    Failed,
+}
+
+/// One analysis cycle, persisted in `analysis_runs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisRun {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub duration_secs: f64,
+    /// Engine mode when the cycle started.
+    pub mode: EngineMode,
+    /// Symbols listed for the cycle, including small caps.
+    pub universe: usize,
+    /// Symbols queued for analysis.
+    pub attempted: usize,
+    /// Symbols not queued: analyzed recently, circuit open or dead-lettered.
+    pub skipped: usize,
+    /// Queued symbols fetched or failed before the cycle ended.
+    pub processed: usize,
+    /// Symbols with a fresh analysis stored.
+    pub saved: usize,
+    /// Symbol-level failures (`yahoo`, `analysis` and `database`).
+    pub errors: usize,
+    pub errors_by_source: BTreeMap<String, usize>,
+    pub outcome: RunOutcome,
+    /// Why the cycle failed or ended early.
+    pub error: Option<String>,
+}
+
+impl AnalysisRun {
+    pub fn start(started_at: DateTime<Utc>, mode: EngineMode) -> Self {
+        Self {
+            id: None,
+            started_at,
+            completed_at: started_at,
+            duration_secs: 0.0,
+            mode,
+            universe: 0,
+            attempted: 0,
+            skipped: 0,
+            processed: 0,
+            saved: 0,
+            errors: 0,
+            errors_by_source: BTreeMap::new(),
+            outcome: RunOutcome::Completed,
+            error: None,
+        }
+    }
+
+    pub fn record_error(&mut self, source: &str) {
+        self.add_errors(source, 1);
+    }
+
+    pub fn add_errors(&mut self, source: &str, count: usize) {
+        if count > 0 {
+            *self.errors_by_source.entry(source.to_string()).or_default() += count;
+        }
+    }
+
+    /// Note that the cycle stopped before its queue was done.
+    pub fn end_early(&mut self, reason: impl Into<String>) {
+        self.outcome = RunOutcome::EndedEarly;
+        self.error = Some(reason.into());
+    }
+
+    /// Stamp the end time and outcome of the cycle.
+    pub fn finish(&mut self, completed_at: DateTime<Utc>, result: &anyhow::Result<()>) {
+        self.completed_at = completed_at;
+        self.duration_secs =
+            (completed_at - self.started_at).num_milliseconds().max(0) as f64 / 1000.0;
+        if let Err(e) = result {
+            self.outcome = RunOutcome::Failed;
+            self.error = Some(e.to_string());
+        }
+    }
+}
+
+/// Aggregates over a page of runs for `GET /api/runs`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub runs: usize,
+    pub completed: usize,
+    pub ended_early: usize,
+    pub failed: usize,
+    /// Share of runs that completed, 0–1; `None` with no runs.
+    pub success_rate: Option<f64>,
+    pub avg_duration_secs: Option<f64>,
+    /// Symbol-level errors per symbol processed, 0–1.
+    pub error_rate: Option<f64>,
+    pub errors_by_source: BTreeMap<String, usize>,
+}
+
+pub fn summarize(runs: &[AnalysisRun]) -> RunSummary {
+    let mut summary = RunSummary {
+        runs: runs.len(),
+        ..Default::default()
+    };
+    if runs.is_empty() {
+        return summary;
+    }
+    let (mut processed, mut errors, mut duration) = (0, 0, 0.0);
+    for run in runs {
+        match run.outcome {
+            RunOutcome::Completed => summary.completed += 1,
+            RunOutcome::EndedEarly => summary.ended_early += 1,
+            RunOutcome::Failed => summary.failed += 1,
+        }
+        processed += run.processed;
+        errors += run.errors;
+        duration += run.duration_secs;
+        for (source, count) in &run.errors_by_source {
+            *summary.errors_by_source.entry(source.clone()).or_default() += count;
+        }
+    }
+    summary.success_rate = Some(summary.completed as f64 / runs.len() as f64);
+    summary.avg_duration_secs = Some(duration / runs.len() as f64);
+    summary.error_rate = (processed > 0).then(|| errors as f64 / processed as f64);
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(outcome: RunOutcome, processed: usize, errors: &[(&str, usize)]) -> AnalysisRun {
+        let started = "2025-06-02T14:00:00Z".parse().unwrap();
+        let mut run = AnalysisRun::start(started, EngineMode::Normal);
+        run.processed = processed;
+        for (source, count) in errors {
+            run.add_errors(source, *count);
+        }
+        run.errors = errors
+            .iter()
+            .filter(|(source, _)| *source != "news")
+            .map(|(_, count)| count)
+            .sum();
+        let result = match outcome {
+            RunOutcome::Failed => Err(anyhow::anyhow!("fetch task failed")),
+            _ => Ok(()),
+        };
+        if outcome == RunOutcome::EndedEarly {
+            run.end_early("yahoo error rate 60%");
+        }
+        run.finish(started + chrono::Duration::seconds(90), &result);
+        run
+    }
+
+    #[test]
+    fn test_finish_stamps_duration_and_outcome() {
+        let failed = run(RunOutcome::Failed, 0, &[]);
+        assert_eq!(failed.duration_secs, 90.0);
+        assert_eq!(failed.outcome, RunOutcome::Failed);
+        assert_eq!(failed.error.as_deref(), Some("fetch task failed"));
+        let early = run(RunOutcome::EndedEarly, 10, &[]);
+        assert_eq!(early.outcome, RunOutcome::EndedEarly);
+        assert_eq!(early.error.as_deref(), Some("yahoo error rate 60%"));
+    }
+
+    #[test]
+    fn test_summarize() {
+        assert_eq!(summarize(&[]), RunSummary::default());
+        let runs = vec![
+            run(RunOutcome::Completed, 100, &[("yahoo", 3), ("news", 5)]),
+            run(RunOutcome::Completed, 50, &[("yahoo", 1), ("database", 1)]),
+            run(RunOutcome::EndedEarly, 40, &[("yahoo", 15), ("zero", 0)]),
+            run(RunOutcome::Failed, 10, &[]),
+        ];
+        let summary = summarize(&runs);
+        assert_eq!(
+            (summary.completed, summary.ended_early, summary.failed),
+            (2, 1, 1)
+        );
+        assert_eq!(summary.success_rate, Some(0.5));
+        assert_eq!(summary.avg_duration_secs, Some(90.0));
+        assert_eq!(summary.error_rate, Some(20.0 / 200.0));
+        assert_eq!(
+            summary.errors_by_source,
+            BTreeMap::from([
+                ("database".to_string(), 1),
+                ("news".to_string(), 5),
+                ("yahoo".to_string(), 19),
+            ])
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Pre-computed popular screens.
+//!
+//! Ad-hoc filters go through `POST /api/stocks/filter`. The screens below are
+//! asked for often enough that they are evaluated once at the end of every
+//! analysis cycle, over all stored analyses, and persisted in
+//! `screen_results`. `/api/screens/:name` is then a single keyed lookup.
+//! ETFs, warrants, rights, units and SPACs are left out.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::StockAnalysis;
+
+/// Rows kept per screen.
+pub const SCREEN_RESULT_LIMIT: usize = 100;
+/// Market cap floor for the large-cap screens.
+pub const LARGE_CAP_USD: u64 = 10_000_000_000;
+/// How close (in %) to the 52-week high/low counts as a new high/low.
+pub const NEAR_EXTREME_PCT: f64 = 1.0;
+/// Volume over NASDAQ's average volume that counts as a spike.
+pub const VOLUME_SPIKE_RATIO: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    OversoldLargeCaps,
+    OverboughtLargeCaps,
+    NewHighs,
+    NewLows,
+    VolumeSpikes,
+}
+
+impl Screen {
+    pub const ALL: [Screen; 5] = [
+        Screen::OversoldLargeCaps,
+        Screen::OverboughtLargeCaps,
+        Screen::NewHighs,
+        Screen::NewLows,
+        Screen::VolumeSpikes,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Screen::OversoldLargeCaps => "oversold-large-caps",
+            Screen::OverboughtLargeCaps => "overbought-large-caps",
+            Screen::NewHighs => "new-highs",
+            Screen::NewLows => "new-lows",
+            Screen::VolumeSpikes => "volume-spikes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|screen| screen.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Screen::OversoldLargeCaps => "RSI below 30 and market cap of at least $10B",
+            Screen::OverboughtLargeCaps => "RSI above 70 and market cap of at least $10B",
+            Screen::NewHighs => "Within 1% of the 52-week high",
+            Screen::NewLows => "Within 1% of the 52-week low",
+            Screen::VolumeSpikes => "Volume at least 2x the average volume",
+        }
+    }
+
+    /// What `ScreenRow::value` holds for this screen.
+    pub fn metric(self) -> &'static str {
+        match self {
+            Screen::OversoldLargeCaps | Screen::OverboughtLargeCaps => "rsi",
+            Screen::NewHighs => "pct_from_52w_high",
+            Screen::NewLows => "pct_from_52w_low",
+            Screen::VolumeSpikes => "volume_ratio",
+        }
+    }
+
+    /// Rows are ranked by `value`, highest first unless this is false.
+    fn descending(self) -> bool {
+        !matches!(self, Screen::OversoldLargeCaps | Screen::NewLows)
+    }
+
+    /// The ranking value for `stock`, or `None` when it doesn't pass.
+    fn value(self, stock: &StockAnalysis) -> Option<f64> {
+        let large_cap = stock.market_cap.is_some_and(|cap| cap >= LARGE_CAP_USD);
+        let technicals = stock.technicals.as_ref();
+        match self {
+            Screen::OversoldLargeCaps => stock.rsi.filter(|_| stock.is_oversold && large_cap),
+            Screen::OverboughtLargeCaps => stock.rsi.filter(|_| stock.is_overbought && large_cap),
+            Screen::NewHighs => {
+                let high = technicals?.fifty_two_week_high.filter(|h| *h > 0.0)?;
+                let pct = (stock.price / high - 1.0) * 100.0;
+                (pct >= -NEAR_EXTREME_PCT).then_some(pct)
+            }
+            Screen::NewLows => {
+                let low = technicals?.fifty_two_week_low.filter(|l| *l > 0.0)?;
+                let pct = (stock.price / low - 1.0) * 100.0;
+                (pct <= NEAR_EXTREME_PCT).then_some(pct)
+            }
+            Screen::VolumeSpikes => {
+                let average = technicals?.average_volume.filter(|v| *v > 0.0)?;
+                let ratio = stock.volume? as f64 / average;
+                (ratio >= VOLUME_SPIKE_RATIO).then_some(ratio)
+            }
+        }
+    }
+
+    /// Evaluate the screen over equities, keeping the best `limit` rows.
+    pub fn run(self, analyses: &[StockAnalysis], limit: usize) -> ScreenResult {
+        let mut rows: Vec<ScreenRow> = analyses
+            .iter()
+            .filter(|stock| stock.asset_type.is_equity())
+            .filter_map(|stock| {
+                let value = self.value(stock).filter(|v| v.is_finite())?;
+                Some(ScreenRow::new(stock, value))
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            let order = a.value.total_cmp(&b.value);
+            if self.descending() {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        let total = rows.len();
+        rows.truncate(limit);
+        ScreenResult {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            metric: self.metric().to_string(),
+            total,
+            rows,
+            computed_at: Utc::now(),
+        }
+    }
+}
+
+/// A compact row of a stored screen.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScreenRow {
+    pub symbol: String,
+    pub price: f64,
+    pub price_change_percent: Option<f64>,
+    pub market_cap: Option<u64>,
+    pub sector: Option<String>,
+    pub rsi: Option<f64>,
+    /// The screen's ranking value; see `ScreenResult::metric`.
+    pub value: f64,
+}
+
+impl ScreenRow {
+    fn new(stock: &StockAnalysis, value: f64) -> Self {
+        Self {
+            symbol: stock.symbol.clone(),
+            price: stock.price,
+            price_change_percent: stock.price_change_percent,
+            market_cap: stock.market_cap,
+            sector: stock.sector.clone(),
+            rsi: stock.rsi,
+            value,
+        }
+    }
+}
+
+/// One screen's result, replaced at the end of every cycle.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScreenResult {
+    pub name: String,
+    pub description: String,
+    pub metric: String,
+    /// Matches before truncating to `SCREEN_RESULT_LIMIT`.
+    pub total: usize,
+    pub rows: Vec<ScreenRow>,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_types::AssetType;
+
+    fn stock(symbol: &str, price: f64, cap: f64, rsi: f64) -> StockAnalysis {
+        StockAnalysis {
+            rsi: Some(rsi),
+            volume: Some(3_000_000),
+            market_cap: Some(cap as u64),
+            is_oversold: rsi < 30.0,
+            is_overbought: rsi > 70.0,
+            analyzed_at: "2025-06-02T20:00:00Z".parse().unwrap(),
+            technicals: serde_json::from_value(serde_json::json!({
+                "fifty_two_week_high": 100.0,
+                "fifty_two_week_low": 50.0,
+                "average_volume": 1_000_000.0
+            }))
+            .ok(),
+            ..StockAnalysis::sample(symbol, price)
+        }
+    }
+
+    #[test]
+    fn test_oversold_large_caps_rank_lowest_rsi_first() {
+        let analyses = vec![
+            stock("BIG1", 70.0, 5e11, 28.0),
+            stock("BIG2", 70.0, 5e11, 22.0),
+            stock("SMALL", 70.0, 5e8, 15.0),
+            stock("NEUTRAL", 70.0, 5e11, 50.0),
+            StockAnalysis {
+                asset_type: AssetType::Etf,
+                ..stock("ETF", 70.0, 5e11, 10.0)
+            },
+        ];
+        let result = Screen::OversoldLargeCaps.run(&analyses, 10);
+        let symbols: Vec<_> = result.rows.iter().map(|r| r.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["BIG2", "BIG1"]);
+        assert_eq!(result.total, 2);
+        assert_eq!(result.metric, "rsi");
+    }
+
+    #[test]
+    fn test_new_highs_lows_and_volume_spikes() {
+        let analyses = vec![
+            stock("HIGH", 99.5, 1e9, 60.0),
+            stock("LOW", 50.2, 1e9, 35.0),
+            stock("MID", 75.0, 1e9, 50.0),
+        ];
+        let highs = Screen::NewHighs.run(&analyses, 10);
+        assert_eq!(highs.rows.len(), 1);
+        assert_eq!(highs.rows[0].symbol, "HIGH");
+        assert!((highs.rows[0].value + 0.5).abs() < 1e-9);
+
+        let lows = Screen::NewLows.run(&analyses, 10);
+        assert_eq!(lows.rows[0].symbol, "LOW");
+
+        let spikes = Screen::VolumeSpikes.run(&analyses, 2);
+        assert_eq!(spikes.total, 3);
+        assert_eq!(spikes.rows.len(), 2);
+        assert!((spikes.rows[0].value - 3.0).abs() < 1e-9);
+
+        assert_eq!(Screen::from_name("New-Highs"), Some(Screen::NewHighs));
+        assert!(Screen::from_name("unknown").is_none());
+    }
+}
@@ -0,0 +1,140 @@
+//! Sector → SPDR sector ETF proxies.
+//!
+//! The analysis cycle fetches every proxy ETF's daily bars once per cycle and
+//! stores each stock's trailing returns relative to its sector's ETF on the
+//! analysis document (`sector_relative`). Sector names cover both the NASDAQ
+//! screener vocabulary and the GICS / Yahoo names.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{HistoricalPrice, SectorRelative};
+
+/// `(sector name, ETF)`. Several names can map to the same ETF.
+pub const SECTOR_ETFS: &[(&str, &str)] = &[
+    ("Technology", "XLK"),
+    ("Information Technology", "XLK"),
+    ("Finance", "XLF"),
+    ("Financials", "XLF"),
+    ("Financial Services", "XLF"),
+    ("Health Care", "XLV"),
+    ("Healthcare", "XLV"),
+    ("Consumer Discretionary", "XLY"),
+    ("Consumer Cyclical", "XLY"),
+    ("Consumer Staples", "XLP"),
+    ("Consumer Defensive", "XLP"),
+    ("Energy", "XLE"),
+    ("Industrials", "XLI"),
+    ("Basic Materials", "XLB"),
+    ("Materials", "XLB"),
+    ("Real Estate", "XLRE"),
+    ("Utilities", "XLU"),
+    ("Telecommunications", "XLC"),
+    ("Communication Services", "XLC"),
+];
+
+/// Distinct proxy ETFs, in first-seen order.
+pub fn proxy_etfs() -> Vec<&'static str> {
+    let mut etfs: Vec<&'static str> = Vec::new();
+    for (_, etf) in SECTOR_ETFS {
+        if !etfs.contains(etf) {
+            etfs.push(etf);
+        }
+    }
+    etfs
+}
+
+pub fn etf_for_sector(sector: &str) -> Option<&'static str> {
+    SECTOR_ETFS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(sector.trim()))
+        .map(|(_, etf)| *etf)
+}
+
+/// % change over the last `sessions` bars, if the series is long enough.
+pub fn trailing_return(prices: &[HistoricalPrice], sessions: usize) -> Option<f64> {
+    let last = prices.last()?;
+    let base = prices.get(prices.len().checked_sub(sessions + 1)?)?;
+    (base.close > 0.0).then(|| (last.close - base.close) / base.close * 100.0)
+}
+
+/// One proxy ETF's state as of the latest cycle. Persisted in `sector_etfs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorEtfSnapshot {
+    pub etf: String,
+    pub price: f64,
+    pub return_1d_pct: Option<f64>,
+    pub return_5d_pct: Option<f64>,
+    pub return_20d_pct: Option<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SectorEtfSnapshot {
+    pub fn from_prices(etf: &str, prices: &[HistoricalPrice]) -> Option<Self> {
+        Some(Self {
+            etf: etf.to_string(),
+            price: prices.last()?.close,
+            return_1d_pct: trailing_return(prices, 1),
+            return_5d_pct: trailing_return(prices, 5),
+            return_20d_pct: trailing_return(prices, 20),
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+/// Stock's trailing returns minus its sector ETF's over the same sessions.
+pub fn relative_to(stock: &[HistoricalPrice], etf: &SectorEtfSnapshot) -> SectorRelative {
+    let diff =
+        |sessions, etf_return: Option<f64>| Some(trailing_return(stock, sessions)? - etf_return?);
+    SectorRelative {
+        etf: etf.etf.clone(),
+        relative_1d_pct: diff(1, etf.return_1d_pct),
+        relative_5d_pct: diff(5, etf.return_5d_pct),
+        relative_20d_pct: diff(20, etf.return_20d_pct),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn bars(closes: &[f64]) -> Vec<HistoricalPrice> {
+        let start = Utc.with_ymd_and_hms(2025, 1, 2, 21, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| HistoricalPrice {
+                date: start + Duration::days(i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn maps_nasdaq_and_gics_names() {
+        assert_eq!(etf_for_sector("Technology"), Some("XLK"));
+        assert_eq!(etf_for_sector("financial services"), Some("XLF"));
+        assert_eq!(etf_for_sector("Miscellaneous"), None);
+        let etfs = proxy_etfs();
+        assert_eq!(etfs.len(), 11);
+        assert_eq!(etfs[0], "XLK");
+    }
+
+    #[test]
+    fn relative_returns_subtract_the_etf() {
+        let etf = SectorEtfSnapshot::from_prices("XLK", &bars(&[100.0; 7])).unwrap();
+        let mut closes = vec![100.0; 6];
+        closes.push(104.0);
+        let rel = relative_to(&bars(&closes), &etf);
+        assert_eq!(rel.etf, "XLK");
+        assert_eq!(rel.relative_1d_pct, Some(4.0));
+        assert_eq!(rel.relative_5d_pct, Some(4.0));
+        // Not enough bars for 20 sessions.
+        assert_eq!(rel.relative_20d_pct, None);
+    }
+}
@@ -0,0 +1,250 @@
+//! First-run seed data.
+//!
+//! A seed is one gzipped NDJSON file, one
+//! `{"collection": "<name>", "document": <canonical Extended JSON>}` per
+//! line. When the server starts against a database with no analyses it loads
+//! `SEED_SNAPSHOT` (a local path, an `http(s)://` URL, or an `s3://bucket/key`
+//! object fetched over HTTPS) into every collection that is still empty,
+//! before the cache is warmed. A new deployment then shows the snapshot's
+//! dashboard right away; the seeded analyses keep their `analyzed_at`, so the
+//! first cycle refreshes them like any other stale row.
+//! `auto_analyser_2 seed-export <file>` writes a seed from the current
+//! database.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::stream::StreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Collection;
+use serde::{Deserialize, Serialize};
+
+use crate::db::MongoDB;
+
+/// Where a bundled seed is looked for when `SEED_SNAPSHOT` is unset.
+pub const DEFAULT_SEED_PATH: &str = "seed/snapshot.ndjson.gz";
+
+/// Market data exported by `seed-export`. User data (watchlists, rules,
+/// positions, channels) and per-deployment bookkeeping are left out.
+pub const SEED_COLLECTIONS: [&str; 10] = [
+    "stock_analysis",
+    "price_history",
+    "sector_etfs",
+    "index_performance",
+    "index_contributors",
+    "theme_performance",
+    "screen_results",
+    "cross_section_stats",
+    "week52_events",
+    "signals",
+];
+
+/// Documents per `insert_many`.
+const INSERT_BATCH: usize = 1000;
+
+/// Documents loaded (or exported) per collection.
+pub type SeedSummary = BTreeMap<String, u64>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeedSource {
+    File(PathBuf),
+    Url(String),
+}
+
+impl SeedSource {
+    /// `s3://bucket/key` is read from the bucket's virtual-hosted HTTPS
+    /// endpoint, so the object must be public (or use a presigned `https://`
+    /// URL instead).
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Some(rest) = value.strip_prefix("s3://") {
+            let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+            return SeedSource::Url(format!("https://{}.s3.amazonaws.com/{}", bucket, key));
+        }
+        if value.starts_with("http://") || value.starts_with("https://") {
+            SeedSource::Url(value.to_string())
+        } else {
+            SeedSource::File(PathBuf::from(value))
+        }
+    }
+
+    /// `false` for a local file that doesn't exist.
+    pub fn available(&self) -> bool {
+        match self {
+            SeedSource::File(path) => path.is_file(),
+            SeedSource::Url(_) => true,
+        }
+    }
+
+    async fn open(&self) -> Result<Box<dyn Read + Send>> {
+        Ok(match self {
+            SeedSource::File(path) => {
+                Box::new(File::open(path).with_context(|| format!("opening {}", path.display()))?)
+            }
+            SeedSource::Url(url) => {
+                let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+                Box::new(Cursor::new(bytes))
+            }
+        })
+    }
+}
+
+impl fmt::Display for SeedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedSource::File(path) => write!(f, "{}", path.display()),
+            SeedSource::Url(url) => f.write_str(url),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SeedLine {
+    collection: String,
+    document: serde_json::Value,
+}
+
+pub(crate) fn write_line(out: &mut impl Write, collection: &str, document: Document) -> Result<()> {
+    let line = SeedLine {
+        collection: collection.to_string(),
+        document: Bson::Document(document).into_canonical_extjson(),
+    };
+    serde_json::to_writer(&mut *out, &line)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// `(collection, document)` for every line of a gzipped seed.
+fn read_lines(reader: impl Read) -> impl Iterator<Item = Result<(String, Document)>> {
+    parse_lines(BufReader::new(GzDecoder::new(reader)))
+}
+
+/// `(collection, document)` for every line of uncompressed seed NDJSON.
+pub(crate) fn parse_lines(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(String, Document)>> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .map(|line| {
+            let line: SeedLine = serde_json::from_str(&line?)?;
+            match Bson::try_from(line.document)? {
+                Bson::Document(document) => Ok((line.collection, document)),
+                other => Err(anyhow!("{}: not a document: {}", line.collection, other)),
+            }
+        })
+}
+
+/// Write every [`SEED_COLLECTIONS`] document to `path`.
+pub async fn export(db: &MongoDB, path: &Path) -> Result<SeedSummary> {
+    let partial = path.with_extension("partial");
+    let mut out = GzEncoder::new(BufWriter::new(File::create(&partial)?), Compression::best());
+    let mut summary = SeedSummary::new();
+    for collection in SEED_COLLECTIONS {
+        let source: Collection<Document> = db.database().collection(collection);
+        let mut cursor = source.find(doc! {}).await?;
+        let mut documents = 0;
+        while let Some(row) = cursor.next().await {
+            write_line(&mut out, collection, row?)?;
+            documents += 1;
+        }
+        summary.insert(collection.to_string(), documents);
+    }
+    out.finish()?.flush()?;
+    fs::rename(&partial, path)?;
+    Ok(summary)
+}
+
+/// Load `source` when the database has no analyses yet. Collections that
+/// already hold documents are left alone. `None` when nothing was seeded
+/// because the database was already populated.
+pub async fn seed_if_empty(db: &MongoDB, source: &SeedSource) -> Result<Option<SeedSummary>> {
+    if db.get_analysis_count().await? > 0 {
+        return Ok(None);
+    }
+
+    let mut empty: HashMap<String, bool> = HashMap::new();
+    let mut batches: HashMap<String, Vec<Document>> = HashMap::new();
+    let mut summary = SeedSummary::new();
+    for line in read_lines(source.open().await?) {
+        let (collection, document) = line?;
+        let target: Collection<Document> = db.database().collection(&collection);
+        if !empty.contains_key(&collection) {
+            let is_empty = target.estimated_document_count().await? == 0;
+            empty.insert(collection.clone(), is_empty);
+        }
+        if !empty[&collection] {
+            continue;
+        }
+        let batch = batches.entry(collection.clone()).or_default();
+        batch.push(document);
+        *summary.entry(collection).or_default() += 1;
+        if batch.len() == INSERT_BATCH {
+            target.insert_many(std::mem::take(batch)).await?;
+        }
+    }
+    for (collection, batch) in batches {
+        if !batch.is_empty() {
+            let target: Collection<Document> = db.database().collection(&collection);
+            target.insert_many(batch).await?;
+        }
+    }
+    Ok(Some(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_paths_urls_and_s3() {
+        assert_eq!(
+            SeedSource::parse("seed/snapshot.ndjson.gz"),
+            SeedSource::File(PathBuf::from("seed/snapshot.ndjson.gz"))
+        );
+        assert_eq!(
+            SeedSource::parse(" https://example.com/seed.ndjson.gz "),
+            SeedSource::Url("https://example.com/seed.ndjson.gz".into())
+        );
+        assert_eq!(
+            SeedSource::parse("s3://my-bucket/seeds/latest.ndjson.gz"),
+            SeedSource::Url("https://my-bucket.s3.amazonaws.com/seeds/latest.ndjson.gz".into())
+        );
+        assert!(!SeedSource::parse("/no/such/seed.ndjson.gz").available());
+    }
+
+    #[test]
+    fn lines_round_trip_through_gzip() {
+        let analysis = doc! {
+            "_id": mongodb::bson::oid::ObjectId::new(),
+            "symbol": "AAPL",
+            "price": 190.5,
+            "analyzed_at": "2025-06-02T20:05:00Z",
+        };
+        let history = doc! { "symbol": "AAPL", "closes": [1.0, 2.0], "volume": 5_i64 };
+
+        let mut out = GzEncoder::new(Vec::new(), Compression::default());
+        write_line(&mut out, "stock_analysis", analysis.clone()).unwrap();
+        out.write_all(b"\n").unwrap();
+        write_line(&mut out, "price_history", history.clone()).unwrap();
+        let bytes = out.finish().unwrap();
+
+        let lines: Vec<(String, Document)> = read_lines(Cursor::new(bytes))
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                ("stock_analysis".to_string(), analysis),
+                ("price_history".to_string(), history),
+            ]
+        );
+    }
+}
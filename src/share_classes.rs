@@ -0,0 +1,97 @@
+//! Listed share classes of the same company (GOOG/GOOGL, FOX/FOXA, ...).
+//!
+//! The screener lists every class as its own symbol, so screens and
+//! market-summary leaders show the same company twice. Each group below
+//! names its primary class first: the most liquid class, kept when
+//! duplicates are collapsed. Symbols use the stored key format (`BRK-B`).
+
+/// Sibling classes, primary first.
+pub const SHARE_CLASS_GROUPS: &[&[&str]] = &[
+    &["GOOGL", "GOOG"],
+    &["FOXA", "FOX"],
+    &["NWSA", "NWS"],
+    &["BRK-B", "BRK-A"],
+    &["BF-B", "BF-A"],
+    &["UAA", "UA"],
+    &["LBRDK", "LBRDA"],
+    &["LBTYK", "LBTYA"],
+    &["LILAK", "LILA"],
+    &["FWONK", "FWONA"],
+    &["BATRK", "BATRA"],
+    &["LEN", "LEN-B"],
+    &["HEI", "HEI-A"],
+    &["MOG-A", "MOG-B"],
+    &["Z", "ZG"],
+    &["BIO", "BIO-B"],
+    &["CWEN", "CWEN-A"],
+    &["GEF", "GEF-B"],
+    &["RUSHA", "RUSHB"],
+    &["MKC", "MKC-V"],
+    &["PARA", "PARAA"],
+];
+
+fn group_of(symbol: &str) -> Option<&'static [&'static str]> {
+    let key = crate::symbols::normalize_symbol_key(symbol);
+    SHARE_CLASS_GROUPS
+        .iter()
+        .copied()
+        .find(|group| group.contains(&key.as_str()))
+}
+
+/// Other listed classes of the same company, primary first.
+pub fn siblings(symbol: &str) -> Vec<&'static str> {
+    let key = crate::symbols::normalize_symbol_key(symbol);
+    group_of(symbol)
+        .map(|group| group.iter().copied().filter(|s| *s != key).collect())
+        .unwrap_or_default()
+}
+
+/// The primary class for `symbol`; the symbol itself when it has no siblings.
+pub fn primary_class(symbol: &str) -> String {
+    group_of(symbol)
+        .map(|group| group[0].to_string())
+        .unwrap_or_else(|| crate::symbols::normalize_symbol_key(symbol))
+}
+
+/// Every non-primary class, for excluding duplicates in Mongo queries.
+pub fn secondary_classes() -> Vec<&'static str> {
+    SHARE_CLASS_GROUPS
+        .iter()
+        .flat_map(|group| group[1..].iter().copied())
+        .collect()
+}
+
+/// Keep the first row seen for each company, preserving order. Use on
+/// already-ranked lists so the better-ranked class survives.
+pub fn dedupe_by_company<T>(items: Vec<T>, symbol: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen.insert(primary_class(symbol(item))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_sibling_classes() {
+        assert_eq!(siblings("goog"), vec!["GOOGL"]);
+        assert_eq!(siblings("BRK.B"), vec!["BRK-A"]);
+        assert!(siblings("AAPL").is_empty());
+        assert_eq!(primary_class("FOX"), "FOXA");
+        assert_eq!(primary_class("AAPL"), "AAPL");
+        assert!(secondary_classes().contains(&"GOOG"));
+        assert!(!secondary_classes().contains(&"GOOGL"));
+    }
+
+    #[test]
+    fn dedupe_keeps_best_ranked_class() {
+        let ranked = vec!["GOOG", "AAPL", "GOOGL", "FOXA", "FOX"];
+        assert_eq!(
+            dedupe_by_company(ranked, |s| s),
+            vec!["GOOG", "AAPL", "FOXA"]
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Built-in trading signals and their forward performance.
+//!
+//! Every analysis cycle checks each fresh `StockAnalysis` against a fixed set
+//! of strategies and records a `SignalRecord` per firing (at most one per
+//! strategy/symbol/day). Later cycles fill in the 5- and 20-session forward
+//! returns from the daily bars they already fetch, and `leaderboard` rolls the
+//! records up per strategy for `/api/signals/performance`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::indicators::TechnicalIndicators;
+use crate::models::{HistoricalPrice, StockAnalysis};
+
+/// Forward horizons, in trading sessions after the signal bar.
+pub const HORIZONS: [usize; 2] = [5, 20];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    RsiOversold,
+    RsiOverbought,
+    StochasticOversold,
+    StochasticOverbought,
+    BollingerLowerTouch,
+    BollingerUpperTouch,
+    /// MACD histogram positive with price above its 50-day SMA.
+    MacdTrendUp,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 7] = [
+        Strategy::RsiOversold,
+        Strategy::RsiOverbought,
+        Strategy::StochasticOversold,
+        Strategy::StochasticOverbought,
+        Strategy::BollingerLowerTouch,
+        Strategy::BollingerUpperTouch,
+        Strategy::MacdTrendUp,
+    ];
+
+    /// `1.0` for long (expects price to rise), `-1.0` for short.
+    pub fn direction(self) -> f64 {
+        match self {
+            Strategy::RsiOversold
+            | Strategy::StochasticOversold
+            | Strategy::BollingerLowerTouch
+            | Strategy::MacdTrendUp => 1.0,
+            Strategy::RsiOverbought
+            | Strategy::StochasticOverbought
+            | Strategy::BollingerUpperTouch => -1.0,
+        }
+    }
+
+    pub fn fires(self, a: &StockAnalysis) -> bool {
+        match self {
+            Strategy::RsiOversold => a.is_oversold,
+            Strategy::RsiOverbought => a.is_overbought,
+            Strategy::StochasticOversold => {
+                TechnicalIndicators::is_stoch_oversold(a.stochastic.as_ref())
+            }
+            Strategy::StochasticOverbought => {
+                TechnicalIndicators::is_stoch_overbought(a.stochastic.as_ref())
+            }
+            Strategy::BollingerLowerTouch => a
+                .bollinger
+                .as_ref()
+                .is_some_and(|b| a.price <= b.lower_band),
+            Strategy::BollingerUpperTouch => a
+                .bollinger
+                .as_ref()
+                .is_some_and(|b| a.price >= b.upper_band),
+            Strategy::MacdTrendUp => {
+                a.macd.as_ref().is_some_and(|m| m.histogram > 0.0)
+                    && a.sma_50.is_some_and(|sma| a.price > sma)
+            }
+        }
+    }
+}
+
+/// One strategy firing for one symbol on one market day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub strategy: Strategy,
+    pub symbol: String,
+    /// Date of the daily bar the signal fired on (`YYYY-MM-DD`).
+    pub signal_date: String,
+    pub entry_price: f64,
+    pub signaled_at: DateTime<Utc>,
+    /// Direction-adjusted % return after 5 sessions (positive = signal was right).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_5d: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_20d: Option<f64>,
+}
+
+impl SignalRecord {
+    /// Fill whichever forward returns the given bars now cover. Returns `true`
+    /// if anything changed.
+    pub fn fill_forward_returns(&mut self, prices: &[HistoricalPrice]) -> bool {
+        let Ok(date) = NaiveDate::parse_from_str(&self.signal_date, "%Y-%m-%d") else {
+            return false;
+        };
+        let Some(entry_idx) = prices.iter().position(|p| p.date.date_naive() >= date) else {
+            return false;
+        };
+        let direction = self.strategy.direction();
+        let mut changed = false;
+        for horizon in HORIZONS {
+            let slot = match horizon {
+                5 => &mut self.return_5d,
+                _ => &mut self.return_20d,
+            };
+            if slot.is_some() {
+                continue;
+            }
+            if let Some(exit) = prices.get(entry_idx + horizon) {
+                *slot = forward_return(self.entry_price, exit.close, direction);
+                changed |= slot.is_some();
+            }
+        }
+        changed
+    }
+}
+
+fn forward_return(entry: f64, exit: f64, direction: f64) -> Option<f64> {
+    (entry > 0.0 && exit.is_finite()).then(|| (exit - entry) / entry * 100.0 * direction)
+}
+
+/// Signals `analysis` fires right now, dated by its latest daily bar.
+pub fn detect(analysis: &StockAnalysis, latest_bar: &HistoricalPrice) -> Vec<SignalRecord> {
+    let signal_date = latest_bar.date.date_naive().format("%Y-%m-%d").to_string();
+    Strategy::ALL
+        .into_iter()
+        .filter(|s| s.fires(analysis))
+        .map(|strategy| SignalRecord {
+            id: None,
+            strategy,
+            symbol: analysis.symbol.clone(),
+            signal_date: signal_date.clone(),
+            entry_price: latest_bar.close,
+            signaled_at: analysis.analyzed_at,
+            return_5d: None,
+            return_20d: None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct HorizonPerformance {
+    /// Signals whose forward return at this horizon is known.
+    pub evaluated: u32,
+    pub avg_return_pct: Option<f64>,
+    /// Share of evaluated signals with a positive (direction-adjusted) return.
+    pub win_rate_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyPerformance {
+    pub strategy: Strategy,
+    pub signals: u32,
+    pub return_5d: HorizonPerformance,
+    pub return_20d: HorizonPerformance,
+}
+
+fn horizon_stats(returns: impl Iterator<Item = f64>) -> HorizonPerformance {
+    let (mut n, mut sum, mut wins) = (0u32, 0.0, 0u32);
+    for r in returns {
+        n += 1;
+        sum += r;
+        if r > 0.0 {
+            wins += 1;
+        }
+    }
+    if n == 0 {
+        return HorizonPerformance::default();
+    }
+    HorizonPerformance {
+        evaluated: n,
+        avg_return_pct: Some(sum / n as f64),
+        win_rate_pct: Some(wins as f64 / n as f64 * 100.0),
+    }
+}
+
+/// Per-strategy rollup, best 20-session average first (strategies with no
+/// evaluated signals last).
+pub fn leaderboard(records: &[SignalRecord]) -> Vec<StrategyPerformance> {
+    let mut rows: Vec<StrategyPerformance> = Strategy::ALL
+        .into_iter()
+        .map(|strategy| {
+            let mine = || records.iter().filter(move |r| r.strategy == strategy);
+            StrategyPerformance {
+                strategy,
+                signals: mine().count() as u32,
+                return_5d: horizon_stats(mine().filter_map(|r| r.return_5d)),
+                return_20d: horizon_stats(mine().filter_map(|r| r.return_20d)),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let key =
+            |p: &StrategyPerformance| p.return_20d.avg_return_pct.unwrap_or(f64::NEG_INFINITY);
+        key(b)
+            .partial_cmp(&key(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn bars(closes: &[f64]) -> Vec<HistoricalPrice> {
+        let start = Utc.with_ymd_and_hms(2025, 3, 3, 21, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| HistoricalPrice {
+                date: start + Duration::days(i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1_000.0,
+            })
+            .collect()
+    }
+
+    fn record(strategy: Strategy, r5: Option<f64>, r20: Option<f64>) -> SignalRecord {
+        SignalRecord {
+            id: None,
+            strategy,
+            symbol: "AAPL".to_string(),
+            signal_date: "2025-03-03".to_string(),
+            entry_price: 100.0,
+            signaled_at: Utc::now(),
+            return_5d: r5,
+            return_20d: r20,
+        }
+    }
+
+    #[test]
+    fn fills_horizons_as_bars_arrive() {
+        let mut closes = vec![100.0; 6];
+        closes[5] = 110.0;
+        let mut long = record(Strategy::RsiOversold, None, None);
+        assert!(long.fill_forward_returns(&bars(&closes)));
+        assert!((long.return_5d.unwrap() - 10.0).abs() < 1e-9);
+        assert!(long.return_20d.is_none());
+
+        closes.resize(21, 90.0);
+        assert!(long.fill_forward_returns(&bars(&closes)));
+        assert!((long.return_20d.unwrap() + 10.0).abs() < 1e-9);
+        assert!(!long.fill_forward_returns(&bars(&closes)));
+    }
+
+    #[test]
+    fn short_signals_profit_from_falling_prices() {
+        let mut closes = vec![100.0; 6];
+        closes[5] = 95.0;
+        let mut short = record(Strategy::RsiOverbought, None, None);
+        short.fill_forward_returns(&bars(&closes));
+        assert!((short.return_5d.unwrap() - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn leaderboard_ranks_by_20d_average() {
+        let records = vec![
+            record(Strategy::RsiOversold, Some(2.0), Some(4.0)),
+            record(Strategy::RsiOversold, Some(-1.0), Some(2.0)),
+            record(Strategy::MacdTrendUp, Some(1.0), Some(-3.0)),
+            record(Strategy::BollingerLowerTouch, Some(1.0), None),
+        ];
+        let board = leaderboard(&records);
+        assert_eq!(board.len(), Strategy::ALL.len());
+        assert_eq!(board[0].strategy, Strategy::RsiOversold);
+        assert_eq!(board[0].signals, 2);
+        assert_eq!(board[0].return_5d.win_rate_pct, Some(50.0));
+        assert_eq!(board[0].return_20d.avg_return_pct, Some(3.0));
+        assert_eq!(board[1].strategy, Strategy::MacdTrendUp);
+        let bollinger = board
+            .iter()
+            .find(|p| p.strategy == Strategy::BollingerLowerTouch)
+            .unwrap();
+        assert_eq!(bollinger.return_20d, HorizonPerformance::default());
+    }
+}
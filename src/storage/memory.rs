@@ -0,0 +1,568 @@
+//! `MemoryStorage`: the latest analyses in a map, for `--demo` and tests.
+//!
+//! Listings, counts and the market summary run the filter document and
+//! summary pipeline `MongoDB` would be sent (`db::build_filter_doc`,
+//! `db::market_summary_stages`) through a small evaluator over each
+//! analysis as BSON, so the two backends answer a `StockFilter` alike.
+//! The evaluator knows only the operators and stages those builders emit;
+//! anything else is an error rather than a silent mismatch.
+//!
+//! Themes resolve against `themes::default_themes`. There is no analysis
+//! history, so `as_of` reads are an error.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mongodb::bson::{self, Bson, Document, Regex};
+
+use super::StorageBackend;
+use crate::db::{
+    build_filter_doc, check_filter, filter_theme, list_page, market_summary_from_facets,
+    market_summary_stages, scope_to_symbols,
+};
+use crate::models::{MarketSummary, StockAnalysis, StockFilter};
+use crate::themes::{self, Theme};
+
+pub struct MemoryStorage {
+    /// Keyed by `symbol` as saved, like the `stock_analysis` upsert key.
+    analyses: RwLock<BTreeMap<String, StockAnalysis>>,
+    themes: Vec<Theme>,
+}
+
+impl MemoryStorage {
+    pub fn new(analyses: impl IntoIterator<Item = StockAnalysis>) -> Self {
+        Self {
+            analyses: RwLock::new(
+                analyses
+                    .into_iter()
+                    .map(|a| (a.symbol.clone(), a))
+                    .collect(),
+            ),
+            themes: themes::default_themes(),
+        }
+    }
+
+    /// Every stored analysis as the document `MongoDB` would hold.
+    fn documents(&self) -> Result<Vec<Document>> {
+        let analyses = self.analyses.read().unwrap_or_else(|e| e.into_inner());
+        analyses
+            .values()
+            .map(|a| Ok(bson::to_document(a)?))
+            .collect()
+    }
+
+    /// Documents matching `filter`, before sorting and paging.
+    fn matching(&self, filter: &StockFilter) -> Result<Vec<Document>> {
+        no_history(filter.as_of)?;
+        check_filter(filter)?;
+        let mut filter_doc = build_filter_doc(filter);
+        if let Some(id) = filter_theme(filter) {
+            let theme = self
+                .themes
+                .iter()
+                .find(|t| t.id == id.to_lowercase())
+                .ok_or_else(|| anyhow!("Theme '{}' not found", id))?;
+            scope_to_symbols(&mut filter_doc, &theme.symbols);
+        }
+        let mut rows = Vec::new();
+        for row in self.documents()? {
+            if matches(&row, &filter_doc)? {
+                rows.push(row);
+            }
+        }
+        Ok(rows)
+    }
+}
+
+fn no_history(as_of: Option<DateTime<Utc>>) -> Result<()> {
+    match as_of {
+        Some(_) => Err(anyhow!(
+            "as_of is not available without the analysis history"
+        )),
+        None => Ok(()),
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryStorage {
+    async fn save_analysis(&self, analysis: &StockAnalysis) -> Result<bool> {
+        let mut analyses = self.analyses.write().unwrap_or_else(|e| e.into_inner());
+        match analyses.get(&analysis.symbol) {
+            Some(stored) if stored.analyzed_at > analysis.analyzed_at => Ok(false),
+            _ => {
+                analyses.insert(analysis.symbol.clone(), analysis.clone());
+                Ok(true)
+            }
+        }
+    }
+
+    async fn get_analysis_by_symbol(&self, symbol: &str) -> Result<Option<StockAnalysis>> {
+        let symbol = crate::symbols::normalize_symbol_key(symbol);
+        let analyses = self.analyses.read().unwrap_or_else(|e| e.into_inner());
+        Ok(analyses.get(&symbol).cloned())
+    }
+
+    async fn get_latest_analyses(&self, filter: StockFilter) -> Result<Vec<StockAnalysis>> {
+        let mut rows = self.matching(&filter)?;
+        let (sort, skip, limit) = list_page(&filter);
+        sort_rows(&mut rows, &sort)?;
+        rows.into_iter()
+            .skip(skip as usize)
+            .take(limit as usize)
+            .map(|row| Ok(bson::from_document(row)?))
+            .collect()
+    }
+
+    async fn get_filtered_count(&self, filter: StockFilter) -> Result<u64> {
+        Ok(self.matching(&filter)?.len() as u64)
+    }
+
+    async fn get_market_summary(
+        &self,
+        limit: usize,
+        min_market_cap: Option<f64>,
+        max_price_change_percent: Option<f64>,
+        as_of: Option<DateTime<Utc>>,
+    ) -> Result<MarketSummary> {
+        no_history(as_of)?;
+        let stages =
+            market_summary_stages((limit * 2) as i64, min_market_cap, max_price_change_percent);
+        let facets = run_pipeline(self.documents()?, &stages)?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        Ok(market_summary_from_facets(facets, limit))
+    }
+
+    async fn get_analysis_count(&self) -> Result<u64> {
+        let analyses = self.analyses.read().unwrap_or_else(|e| e.into_inner());
+        Ok(analyses.len() as u64)
+    }
+}
+
+/// Run aggregation `stages` over `rows`.
+fn run_pipeline(mut rows: Vec<Document>, stages: &[Document]) -> Result<Vec<Document>> {
+    for stage in stages {
+        let (name, spec) = stage
+            .iter()
+            .next()
+            .ok_or_else(|| anyhow!("empty pipeline stage"))?;
+        rows = match (name.as_str(), spec) {
+            ("$match", Bson::Document(filter)) => {
+                let mut kept = Vec::new();
+                for row in rows {
+                    if matches(&row, filter)? {
+                        kept.push(row);
+                    }
+                }
+                kept
+            }
+            ("$sort", Bson::Document(sort)) => {
+                sort_rows(&mut rows, sort)?;
+                rows
+            }
+            ("$limit", limit) => {
+                let limit = limit
+                    .as_i64()
+                    .or_else(|| limit.as_i32().map(i64::from))
+                    .ok_or_else(|| anyhow!("$limit must be an integer"))?;
+                rows.truncate(limit.max(0) as usize);
+                rows
+            }
+            ("$project", Bson::Document(projection)) => {
+                for (field, keep) in projection {
+                    if matches!(keep, Bson::Int32(0) | Bson::Int64(0) | Bson::Boolean(false)) {
+                        for row in rows.iter_mut() {
+                            row.remove(field);
+                        }
+                    } else {
+                        return Err(anyhow!("only exclusion projections are supported"));
+                    }
+                }
+                rows
+            }
+            ("$count", Bson::String(field)) if !rows.is_empty() => {
+                let mut count = Document::new();
+                count.insert(field.as_str(), rows.len() as i64);
+                vec![count]
+            }
+            ("$count", Bson::String(_)) => Vec::new(),
+            ("$facet", Bson::Document(facets)) => {
+                let mut out = Document::new();
+                for (facet, pipeline) in facets {
+                    let Bson::Array(pipeline) = pipeline else {
+                        return Err(anyhow!("$facet {} is not a pipeline", facet));
+                    };
+                    let pipeline: Vec<Document> = pipeline
+                        .iter()
+                        .map(|s| s.as_document().cloned())
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| anyhow!("$facet {} is not a pipeline", facet))?;
+                    let result = run_pipeline(rows.clone(), &pipeline)?;
+                    out.insert(
+                        facet.as_str(),
+                        result.into_iter().map(Bson::Document).collect::<Vec<_>>(),
+                    );
+                }
+                vec![out]
+            }
+            (other, _) => return Err(anyhow!("unsupported pipeline stage {}", other)),
+        };
+    }
+    Ok(rows)
+}
+
+/// Whether `row` passes a query document.
+fn matches(row: &Document, filter: &Document) -> Result<bool> {
+    for (key, condition) in filter {
+        let passed = match key.as_str() {
+            "$and" | "$or" => {
+                let Bson::Array(clauses) = condition else {
+                    return Err(anyhow!("{} needs an array", key));
+                };
+                let mut results = Vec::with_capacity(clauses.len());
+                for clause in clauses {
+                    let clause = clause
+                        .as_document()
+                        .ok_or_else(|| anyhow!("{} clauses must be documents", key))?;
+                    results.push(matches(row, clause)?);
+                }
+                if key == "$and" {
+                    results.iter().all(|r| *r)
+                } else {
+                    results.iter().any(|r| *r)
+                }
+            }
+            op if op.starts_with('$') => return Err(anyhow!("unsupported query operator {}", op)),
+            path => field_matches(lookup(row, path), condition)?,
+        };
+        if !passed {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The value at a dotted path; `None` when any part is missing.
+fn lookup<'a>(row: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut parts = path.split('.');
+    let mut value = row.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_document()?.get(part)?;
+    }
+    Some(value)
+}
+
+fn field_matches(value: Option<&Bson>, condition: &Bson) -> Result<bool> {
+    let operators = match condition {
+        Bson::Document(doc) if doc.keys().next().is_some_and(|k| k.starts_with('$')) => doc,
+        Bson::RegularExpression(regex) => return Ok(regex_matches(value, regex)),
+        _ => return Ok(equals(value, condition)),
+    };
+    for (op, operand) in operators {
+        let passed = match op.as_str() {
+            "$in" | "$nin" => {
+                let Bson::Array(options) = operand else {
+                    return Err(anyhow!("{} needs an array", op));
+                };
+                let found = options.iter().any(|option| equals(value, option));
+                found == (op == "$in")
+            }
+            "$gt" => compare(value, operand) == Some(Ordering::Greater),
+            "$gte" => matches!(
+                compare(value, operand),
+                Some(Ordering::Greater | Ordering::Equal)
+            ),
+            "$lt" => compare(value, operand) == Some(Ordering::Less),
+            "$lte" => matches!(
+                compare(value, operand),
+                Some(Ordering::Less | Ordering::Equal)
+            ),
+            "$ne" => !equals(value, operand),
+            "$exists" => value.is_some() == operand.as_bool().unwrap_or(true),
+            "$regex" => match operand {
+                Bson::RegularExpression(regex) => regex_matches(value, regex),
+                _ => return Err(anyhow!("$regex needs a regular expression")),
+            },
+            other => return Err(anyhow!("unsupported query operator {}", other)),
+        };
+        if !passed {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// MongoDB equality: `null` also matches a missing field, and an array
+/// field matches when any element does.
+fn equals(value: Option<&Bson>, wanted: &Bson) -> bool {
+    match value {
+        None | Some(Bson::Null) => matches!(wanted, Bson::Null),
+        Some(Bson::Array(items)) if !matches!(wanted, Bson::Array(_)) => {
+            items.iter().any(|item| equals(Some(item), wanted))
+        }
+        Some(value) => compare_values(value, wanted) == Some(Ordering::Equal),
+    }
+}
+
+/// Comparison for range operators: only numbers against numbers and
+/// strings against strings, like MongoDB's type bracketing.
+fn compare(value: Option<&Bson>, bound: &Bson) -> Option<Ordering> {
+    compare_values(value?, bound)
+}
+
+fn compare_values(a: &Bson, b: &Bson) -> Option<Ordering> {
+    match (number(a), number(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        _ => match (a, b) {
+            (Bson::String(a), Bson::String(b)) => Some(a.cmp(b)),
+            (Bson::Boolean(a), Bson::Boolean(b)) => Some(a.cmp(b)),
+            (Bson::DateTime(a), Bson::DateTime(b)) => Some(a.cmp(b)),
+            _ => (a == b).then_some(Ordering::Equal),
+        },
+    }
+}
+
+fn number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Double(n) => Some(*n),
+        Bson::Int32(n) => Some(f64::from(*n)),
+        Bson::Int64(n) => Some(*n as f64),
+        _ => None,
+    }
+}
+
+/// `build_filter_doc` only sends escaped literal text (the symbol search),
+/// so the pattern is unescaped and matched as a substring.
+fn regex_matches(value: Option<&Bson>, regex: &Regex) -> bool {
+    let Some(Bson::String(text)) = value else {
+        return false;
+    };
+    let mut literal = String::with_capacity(regex.pattern.len());
+    let mut chars = regex.pattern.chars();
+    while let Some(c) = chars.next() {
+        literal.push(if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        });
+    }
+    if regex.options.contains('i') {
+        text.to_lowercase().contains(&literal.to_lowercase())
+    } else {
+        text.contains(&literal)
+    }
+}
+
+/// Sort by each field of `sort` in turn (`1` ascending, `-1` descending).
+/// Missing and `null` values sort first ascending, as in MongoDB.
+fn sort_rows(rows: &mut [Document], sort: &Document) -> Result<()> {
+    let mut keys = Vec::new();
+    for (field, order) in sort {
+        let descending = match order {
+            Bson::Int32(-1) | Bson::Int64(-1) => true,
+            Bson::Int32(1) | Bson::Int64(1) => false,
+            _ => return Err(anyhow!("sort order for {} must be 1 or -1", field)),
+        };
+        keys.push((field.as_str(), descending));
+    }
+    rows.sort_by(|a, b| {
+        for (field, descending) in &keys {
+            let ordering = sort_order(lookup(a, field), lookup(b, field));
+            let ordering = if *descending {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    });
+    Ok(())
+}
+
+fn sort_order(a: Option<&Bson>, b: Option<&Bson>) -> Ordering {
+    let rank = |v: Option<&Bson>| match v {
+        None | Some(Bson::Null) => 0,
+        Some(v) if number(v).is_some() => 1,
+        Some(Bson::String(_)) => 2,
+        Some(_) => 3,
+    };
+    match (a, b) {
+        (Some(a), Some(b)) if rank(Some(a)) == rank(Some(b)) => {
+            compare_values(a, b).unwrap_or(Ordering::Equal)
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_types::AssetType;
+    use crate::models::PerformanceReturns;
+
+    fn analysis(symbol: &str, price: f64, change: f64, rsi: f64, cap: u64) -> StockAnalysis {
+        StockAnalysis {
+            price_change: Some(price * change / 100.0),
+            price_change_percent: Some(change),
+            rsi: Some(rsi),
+            volume: Some(1_000_000),
+            market_cap: Some(cap),
+            sector: Some("Technology".to_string()),
+            is_oversold: rsi < 30.0,
+            is_overbought: rsi > 70.0,
+            analyzed_at: "2025-06-06T20:00:00Z".parse().unwrap(),
+            ..StockAnalysis::sample(symbol, price)
+        }
+    }
+
+    fn storage() -> MemoryStorage {
+        let mut spy = analysis("SPY", 540.0, 0.4, 55.0, 500_000_000_000);
+        spy.asset_type = AssetType::Etf;
+        let mut nvda = analysis("NVDA", 120.0, 3.5, 75.0, 3_000_000_000_000);
+        nvda.indexes = vec!["sp500".to_string(), "nasdaq100".to_string()];
+        nvda.performance = Some(PerformanceReturns {
+            return_1m_pct: Some(12.0),
+            ..Default::default()
+        });
+        let mut xom = analysis("XOM", 110.0, -2.0, 25.0, 450_000_000_000);
+        xom.sector = Some("Energy".to_string());
+        let mut small = analysis("SMAL", 4.0, -8.0, 40.0, 90_000_000);
+        small.market_cap = None;
+        MemoryStorage::new([
+            spy,
+            nvda,
+            xom,
+            small,
+            analysis("GOOGL", 170.0, 1.0, 50.0, 2_100_000_000_000),
+            analysis("GOOG", 171.0, 1.1, 51.0, 2_090_000_000_000),
+        ])
+    }
+
+    fn symbols(rows: &[StockAnalysis]) -> Vec<&str> {
+        rows.iter().map(|a| a.symbol.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn filters_like_the_mongo_query() {
+        let storage = storage();
+        // Equities only by default; a bound on a missing field excludes it.
+        let filter = StockFilter {
+            min_market_cap: Some(100_000_000.0),
+            sort_by: Some("market_cap".to_string()),
+            ..Default::default()
+        };
+        let rows = storage.get_latest_analyses(filter.clone()).await.unwrap();
+        assert_eq!(symbols(&rows), ["NVDA", "GOOGL", "GOOG", "XOM"]);
+        assert_eq!(storage.get_filtered_count(filter).await.unwrap(), 4);
+
+        let filter = StockFilter {
+            sectors: Some(vec!["Energy".to_string()]),
+            ..Default::default()
+        };
+        let rows = storage.get_latest_analyses(filter).await.unwrap();
+        assert_eq!(symbols(&rows), ["XOM"]);
+
+        // Array membership, symbol search and the asset type list.
+        let filter = StockFilter {
+            index: Some("SP500".to_string()),
+            ..Default::default()
+        };
+        let rows = storage.get_latest_analyses(filter).await.unwrap();
+        assert_eq!(symbols(&rows), ["NVDA"]);
+        let filter = StockFilter {
+            symbol_search: Some("go".to_string()),
+            primary_class_only: Some(true),
+            ..Default::default()
+        };
+        let rows = storage.get_latest_analyses(filter).await.unwrap();
+        assert_eq!(symbols(&rows), ["GOOGL"]);
+        let filter = StockFilter {
+            asset_type: Some("etf".to_string()),
+            ..Default::default()
+        };
+        let rows = storage.get_latest_analyses(filter).await.unwrap();
+        assert_eq!(symbols(&rows), ["SPY"]);
+
+        let filter = StockFilter {
+            min_return_pct: Some(5.0),
+            ..Default::default()
+        };
+        let rows = storage.get_latest_analyses(filter).await.unwrap();
+        assert_eq!(symbols(&rows), ["NVDA"]);
+    }
+
+    #[tokio::test]
+    async fn sorts_and_pages() {
+        let storage = storage();
+        let filter = StockFilter {
+            sort_by: Some("price_change_percent".to_string()),
+            sort_order: Some("asc".to_string()),
+            page: Some(2),
+            page_size: Some(2),
+            ..Default::default()
+        };
+        let rows = storage.get_latest_analyses(filter).await.unwrap();
+        assert_eq!(symbols(&rows), ["GOOGL", "GOOG"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_what_it_cannot_answer() {
+        let storage = storage();
+        let unknown_period = StockFilter {
+            return_period: Some("2y".to_string()),
+            ..Default::default()
+        };
+        assert!(storage.get_latest_analyses(unknown_period).await.is_err());
+        let unknown_theme = StockFilter {
+            theme: Some("nope".to_string()),
+            ..Default::default()
+        };
+        assert!(storage.get_filtered_count(unknown_theme).await.is_err());
+        let as_of = StockFilter {
+            as_of: Some(Utc::now()),
+            ..Default::default()
+        };
+        assert!(storage.get_latest_analyses(as_of).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn keeps_the_newest_analysis() {
+        let storage = storage();
+        let mut stale = analysis("NVDA", 1.0, 0.0, 50.0, 1);
+        stale.analyzed_at = "2025-06-05T20:00:00Z".parse().unwrap();
+        assert!(!storage.save_analysis(&stale).await.unwrap());
+        let mut fresh = stale.clone();
+        fresh.analyzed_at = "2025-06-07T20:00:00Z".parse().unwrap();
+        assert!(storage.save_analysis(&fresh).await.unwrap());
+        let stored = storage.get_analysis_by_symbol("nvda").await.unwrap();
+        assert_eq!(stored.map(|a| a.price), Some(1.0));
+        assert_eq!(storage.get_analysis_count().await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn summarizes_equities() {
+        let summary = storage()
+            .get_market_summary(10, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(summary.total_stocks, 5);
+        // Share classes collapse to the better-ranked one; SPY is an ETF.
+        assert_eq!(symbols(&summary.top_gainers), ["NVDA", "GOOG"]);
+        assert_eq!(symbols(&summary.top_losers), ["SMAL", "XOM"]);
+        assert_eq!(symbols(&summary.most_oversold), ["XOM"]);
+        assert_eq!(symbols(&summary.most_overbought), ["NVDA"]);
+        assert_eq!(
+            symbols(&summary.mega_cap_highlights),
+            ["NVDA", "GOOGL", "XOM"]
+        );
+        assert_eq!(symbols(&summary.top_monthly_gainers), ["NVDA"]);
+    }
+}
@@ -0,0 +1,108 @@
+//! Where analyses are stored, behind a trait.
+//!
+//! `StorageBackend` covers the reads and writes the API, the gRPC facade,
+//! the cache read-through and the analysis engine make against stored
+//! analyses: saving one, looking one up by symbol, filtered listings and
+//! their counts, the market summary and the total count. `MongoDB` is the
+//! production implementation, `PostgresBackend` (the `postgres` feature)
+//! takes its place when `POSTGRES_URL` is set, and [`MemoryStorage`] serves
+//! `--demo`; everything else (history, notes, notifications, runs, backups)
+//! still goes to `MongoDB` directly.
+//!
+//! Implementations keep `MongoDB`'s semantics: `save_analysis` returns
+//! `false` when a newer analysis of the symbol is already stored, symbol
+//! lookups normalize the key, and `as_of` reads the snapshot history.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::config::Config;
+use crate::db::MongoDB;
+use crate::models::{MarketSummary, StockAnalysis, StockFilter};
+
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
+
+pub use memory::MemoryStorage;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresBackend;
+
+/// Shared handle to the configured backend.
+pub type Storage = Arc<dyn StorageBackend>;
+
+/// The backend `config` selects: PostgreSQL when `POSTGRES_URL` is set,
+/// otherwise `db`.
+pub async fn configured(config: &Config, db: &MongoDB) -> Result<Storage> {
+    match &config.postgres_url {
+        #[cfg(feature = "postgres")]
+        Some(url) => {
+            let backend = PostgresBackend::connect(url, db.clone()).await?;
+            tracing::info!("✅ Storing analyses in PostgreSQL");
+            Ok(Arc::new(backend))
+        }
+        #[cfg(not(feature = "postgres"))]
+        Some(_) => Err(anyhow::anyhow!(
+            "POSTGRES_URL is set but this build lacks the postgres feature"
+        )),
+        None => Ok(Arc::new(db.clone())),
+    }
+}
+
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Store the latest analysis of a symbol. `false` when a newer one is
+    /// already stored and this one was dropped.
+    async fn save_analysis(&self, analysis: &StockAnalysis) -> Result<bool>;
+    async fn get_analysis_by_symbol(&self, symbol: &str) -> Result<Option<StockAnalysis>>;
+    /// One page of analyses matching `filter`, in its sort order.
+    async fn get_latest_analyses(&self, filter: StockFilter) -> Result<Vec<StockAnalysis>>;
+    /// Analyses matching `filter`, ignoring its paging.
+    async fn get_filtered_count(&self, filter: StockFilter) -> Result<u64>;
+    async fn get_market_summary(
+        &self,
+        limit: usize,
+        min_market_cap: Option<f64>,
+        max_price_change_percent: Option<f64>,
+        as_of: Option<DateTime<Utc>>,
+    ) -> Result<MarketSummary>;
+    /// Symbols with a stored analysis; may be an estimate.
+    async fn get_analysis_count(&self) -> Result<u64>;
+}
+
+#[async_trait]
+impl StorageBackend for MongoDB {
+    async fn save_analysis(&self, analysis: &StockAnalysis) -> Result<bool> {
+        MongoDB::save_analysis(self, analysis).await
+    }
+
+    async fn get_analysis_by_symbol(&self, symbol: &str) -> Result<Option<StockAnalysis>> {
+        MongoDB::get_analysis_by_symbol(self, symbol).await
+    }
+
+    async fn get_latest_analyses(&self, filter: StockFilter) -> Result<Vec<StockAnalysis>> {
+        MongoDB::get_latest_analyses(self, filter).await
+    }
+
+    async fn get_filtered_count(&self, filter: StockFilter) -> Result<u64> {
+        MongoDB::get_filtered_count(self, filter).await
+    }
+
+    async fn get_market_summary(
+        &self,
+        limit: usize,
+        min_market_cap: Option<f64>,
+        max_price_change_percent: Option<f64>,
+        as_of: Option<DateTime<Utc>>,
+    ) -> Result<MarketSummary> {
+        MongoDB::get_market_summary(self, limit, min_market_cap, max_price_change_percent, as_of)
+            .await
+    }
+
+    async fn get_analysis_count(&self) -> Result<u64> {
+        MongoDB::get_analysis_count(self).await
+    }
+}
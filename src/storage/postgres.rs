@@ -0,0 +1,615 @@
+//! `PostgresBackend`: the latest analyses in PostgreSQL, for deployments
+//! that keep them there (`postgres` feature, `POSTGRES_URL`).
+//!
+//! Each analysis is stored whole as JSONB in `stock_analysis(symbol,
+//! version, doc)`, `version` being the `analyzed_at` microseconds `MongoDB`
+//! keeps for its stale-write guard. Listings, counts and the market summary
+//! translate the filter document and summary pipeline `MongoDB` would be
+//! sent (`db::build_filter_doc`, `db::market_summary_stages`) into SQL over
+//! `doc`, values bound as parameters, so the backends answer a `StockFilter`
+//! alike. As in `MemoryStorage`, only the operators and stages those
+//! builders emit are translated; anything else is an error.
+//!
+//! Themes and the analysis history stay in MongoDB: theme filters resolve
+//! through it and `as_of` reads go to it.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::future::try_join_all;
+use mongodb::bson::{self, Bson, Document, Regex};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, NoTls};
+
+use super::StorageBackend;
+use crate::db::{
+    analysis_version, list_page, market_summary_from_facets, market_summary_stages, MongoDB,
+};
+use crate::models::{MarketSummary, StockAnalysis, StockFilter};
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS stock_analysis (
+    symbol TEXT PRIMARY KEY,
+    version BIGINT NOT NULL,
+    doc JSONB NOT NULL
+)";
+
+/// Insert or replace a symbol's analysis unless the stored one is newer.
+const UPSERT: &str = "INSERT INTO stock_analysis (symbol, version, doc) VALUES ($1, $2, $3)
+    ON CONFLICT (symbol) DO UPDATE SET version = EXCLUDED.version, doc = EXCLUDED.doc
+    WHERE stock_analysis.version <= EXCLUDED.version";
+
+pub struct PostgresBackend {
+    url: String,
+    /// Replaced on the next call once the connection drops.
+    client: Mutex<Arc<Client>>,
+    /// Themes and the analysis history.
+    db: MongoDB,
+}
+
+impl PostgresBackend {
+    /// Connect and create the table if it is missing.
+    pub async fn connect(url: &str, db: MongoDB) -> Result<Self> {
+        let client = connect(url).await?;
+        client.batch_execute(SCHEMA).await?;
+        Ok(Self {
+            url: url.to_string(),
+            client: Mutex::new(Arc::new(client)),
+            db,
+        })
+    }
+
+    async fn client(&self) -> Result<Arc<Client>> {
+        let mut client = self.client.lock().await;
+        if client.is_closed() {
+            tracing::warn!("PostgreSQL connection closed, reconnecting");
+            *client = Arc::new(connect(&self.url).await?);
+        }
+        Ok(client.clone())
+    }
+
+    /// The documents `select` reads.
+    async fn rows(&self, select: &Select) -> Result<Vec<Value>> {
+        let (query, sql) = select.to_sql()?;
+        let rows = self.client().await?.query(&query, &sql.params()).await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn count(&self, select: &Select) -> Result<u64> {
+        let (query, sql) = select.to_count_sql()?;
+        let row = self
+            .client()
+            .await?
+            .query_one(&query, &sql.params())
+            .await?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    /// Run one `$facet` pipeline: its rows, or `{ field: n }` for `$count`
+    /// (no row when nothing matched, as in MongoDB).
+    async fn facet(&self, plan: &Plan) -> Result<Vec<Document>> {
+        match plan {
+            Plan::Rows(select) => self
+                .rows(select)
+                .await?
+                .iter()
+                .map(|row| Ok(bson::to_document(row)?))
+                .collect(),
+            Plan::Count { select, field } => match self.count(select).await? {
+                0 => Ok(Vec::new()),
+                n => {
+                    let mut count = Document::new();
+                    count.insert(field.as_str(), n as i64);
+                    Ok(vec![count])
+                }
+            },
+            Plan::Facets(_) => Err(anyhow!("nested $facet is not supported")),
+        }
+    }
+}
+
+async fn connect(url: &str) -> Result<Client> {
+    let (client, connection) = tokio_postgres::connect(url, NoTls).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::error!("PostgreSQL connection error: {}", e);
+        }
+    });
+    Ok(client)
+}
+
+#[async_trait]
+impl StorageBackend for PostgresBackend {
+    async fn save_analysis(&self, analysis: &StockAnalysis) -> Result<bool> {
+        let doc = serde_json::to_value(analysis)?;
+        let written = self
+            .client()
+            .await?
+            .execute(
+                UPSERT,
+                &[&analysis.symbol, &analysis_version(analysis), &doc],
+            )
+            .await?;
+        Ok(written > 0)
+    }
+
+    async fn get_analysis_by_symbol(&self, symbol: &str) -> Result<Option<StockAnalysis>> {
+        let symbol = crate::symbols::normalize_symbol_key(symbol);
+        let row = self
+            .client()
+            .await?
+            .query_opt(
+                "SELECT doc FROM stock_analysis WHERE symbol = $1",
+                &[&symbol],
+            )
+            .await?;
+        row.map(|row| Ok(serde_json::from_value(row.get(0))?))
+            .transpose()
+    }
+
+    async fn get_latest_analyses(&self, filter: StockFilter) -> Result<Vec<StockAnalysis>> {
+        if filter.as_of.is_some() {
+            return self.db.get_latest_analyses(filter).await;
+        }
+        let (sort, skip, limit) = list_page(&filter);
+        let select = Select {
+            sort: Some(sort),
+            skip,
+            limit: Some(limit),
+            ..Select::matching(self.db.filter_doc(&filter).await?)
+        };
+        self.rows(&select)
+            .await?
+            .into_iter()
+            .map(|row| Ok(serde_json::from_value(row)?))
+            .collect()
+    }
+
+    async fn get_filtered_count(&self, filter: StockFilter) -> Result<u64> {
+        if filter.as_of.is_some() {
+            return self.db.get_filtered_count(filter).await;
+        }
+        self.count(&Select::matching(self.db.filter_doc(&filter).await?))
+            .await
+    }
+
+    async fn get_market_summary(
+        &self,
+        limit: usize,
+        min_market_cap: Option<f64>,
+        max_price_change_percent: Option<f64>,
+        as_of: Option<DateTime<Utc>>,
+    ) -> Result<MarketSummary> {
+        if as_of.is_some() {
+            return self
+                .db
+                .get_market_summary(limit, min_market_cap, max_price_change_percent, as_of)
+                .await;
+        }
+        let stages =
+            market_summary_stages((limit * 2) as i64, min_market_cap, max_price_change_percent);
+        let Plan::Facets(facets) = plan(Select::default(), &stages)? else {
+            return Err(anyhow!("the market summary pipeline must end in $facet"));
+        };
+        let results = try_join_all(facets.iter().map(|(_, plan)| self.facet(plan))).await?;
+        let mut out = Document::new();
+        for ((name, _), rows) in facets.iter().zip(results) {
+            out.insert(
+                name.as_str(),
+                rows.into_iter().map(Bson::Document).collect::<Vec<_>>(),
+            );
+        }
+        Ok(market_summary_from_facets(out, limit))
+    }
+
+    async fn get_analysis_count(&self) -> Result<u64> {
+        self.count(&Select::default()).await
+    }
+}
+
+/// A read of `stock_analysis`, built up from pipeline stages.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Select {
+    /// Query documents, all of which must match.
+    filters: Vec<Document>,
+    /// Dotted paths left out of the returned documents.
+    excluded: Vec<String>,
+    sort: Option<Document>,
+    skip: u64,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Plan {
+    Rows(Select),
+    Count { select: Select, field: String },
+    Facets(Vec<(String, Plan)>),
+}
+
+impl Select {
+    fn matching(filter: Document) -> Self {
+        Self {
+            filters: vec![filter],
+            ..Self::default()
+        }
+    }
+
+    fn to_sql(&self) -> Result<(String, Sql)> {
+        let mut sql = Sql::default();
+        let mut columns = "doc".to_string();
+        for path in &self.excluded {
+            columns = format!("({} #- {}::text[])", columns, sql.path(path));
+        }
+        let mut query = format!(
+            "SELECT {} FROM stock_analysis WHERE {}",
+            columns,
+            sql.conditions(&self.filters)?
+        );
+        if let Some(sort) = &self.sort {
+            query.push_str(&format!(" ORDER BY {}", sql.order_by(sort)?));
+        }
+        if let Some(limit) = self.limit {
+            query.push_str(&format!(" LIMIT {}", limit.max(0)));
+        }
+        if self.skip > 0 {
+            query.push_str(&format!(" OFFSET {}", self.skip));
+        }
+        Ok((query, sql))
+    }
+
+    fn to_count_sql(&self) -> Result<(String, Sql)> {
+        let mut sql = Sql::default();
+        let query = format!(
+            "SELECT count(*) FROM stock_analysis WHERE {}",
+            sql.conditions(&self.filters)?
+        );
+        Ok((query, sql))
+    }
+}
+
+/// Fold aggregation `stages` into reads of `stock_analysis`. `$match` and
+/// `$sort` must come before any `$limit`, and `$count` and `$facet` last.
+fn plan(mut select: Select, stages: &[Document]) -> Result<Plan> {
+    for (i, stage) in stages.iter().enumerate() {
+        let last = i + 1 == stages.len();
+        let (name, spec) = stage
+            .iter()
+            .next()
+            .ok_or_else(|| anyhow!("empty pipeline stage"))?;
+        match (name.as_str(), spec) {
+            ("$match" | "$sort", _) if select.limit.is_some() => {
+                return Err(anyhow!("{} after $limit is not supported", name));
+            }
+            ("$match", Bson::Document(filter)) => select.filters.push(filter.clone()),
+            ("$sort", Bson::Document(sort)) => select.sort = Some(sort.clone()),
+            ("$limit", limit) => {
+                let limit = limit
+                    .as_i64()
+                    .or_else(|| limit.as_i32().map(i64::from))
+                    .ok_or_else(|| anyhow!("$limit must be an integer"))?;
+                select.limit = Some(select.limit.map_or(limit, |l| l.min(limit)));
+            }
+            ("$project", Bson::Document(projection)) => {
+                for (field, keep) in projection {
+                    if matches!(keep, Bson::Int32(0) | Bson::Int64(0) | Bson::Boolean(false)) {
+                        select.excluded.push(field.clone());
+                    } else {
+                        return Err(anyhow!("only exclusion projections are supported"));
+                    }
+                }
+            }
+            ("$count", Bson::String(field)) if last => {
+                return Ok(Plan::Count {
+                    select,
+                    field: field.clone(),
+                });
+            }
+            ("$facet", Bson::Document(facets)) if last => {
+                let mut plans = Vec::with_capacity(facets.len());
+                for (facet, pipeline) in facets {
+                    let pipeline: Vec<Document> = match pipeline {
+                        Bson::Array(stages) => stages
+                            .iter()
+                            .map(|s| s.as_document().cloned())
+                            .collect::<Option<_>>(),
+                        _ => None,
+                    }
+                    .ok_or_else(|| anyhow!("$facet {} is not a pipeline", facet))?;
+                    plans.push((facet.clone(), plan(select.clone(), &pipeline)?));
+                }
+                return Ok(Plan::Facets(plans));
+            }
+            (other, _) => return Err(anyhow!("unsupported pipeline stage {}", other)),
+        }
+    }
+    Ok(Plan::Rows(select))
+}
+
+/// SQL text under construction and the values it binds.
+#[derive(Default)]
+struct Sql {
+    params: Vec<Box<dyn ToSql + Sync + Send>>,
+}
+
+impl Sql {
+    fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.params
+            .iter()
+            .map(|p| p.as_ref() as &(dyn ToSql + Sync))
+            .collect()
+    }
+
+    /// Bind `value`, returning its placeholder.
+    fn bind(&mut self, value: impl ToSql + Sync + Send + 'static) -> String {
+        self.params.push(Box::new(value));
+        format!("${}", self.params.len())
+    }
+
+    fn bind_json(&mut self, value: &Bson) -> String {
+        let placeholder = self.bind(value.clone().into_relaxed_extjson());
+        format!("{}::jsonb", placeholder)
+    }
+
+    /// Placeholder for a dotted path as `text[]` parts.
+    fn path(&mut self, path: &str) -> String {
+        self.bind(path.split('.').map(str::to_string).collect::<Vec<_>>())
+    }
+
+    fn conditions(&mut self, filters: &[Document]) -> Result<String> {
+        let clauses = filters
+            .iter()
+            .map(|filter| self.matches(filter))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(all(clauses))
+    }
+
+    /// A query document as a condition on `doc`.
+    fn matches(&mut self, filter: &Document) -> Result<String> {
+        let mut clauses = Vec::with_capacity(filter.len());
+        for (key, condition) in filter {
+            clauses.push(match key.as_str() {
+                "$and" | "$or" => {
+                    let Bson::Array(items) = condition else {
+                        return Err(anyhow!("{} needs an array", key));
+                    };
+                    let mut parts = Vec::with_capacity(items.len());
+                    for item in items {
+                        let item = item
+                            .as_document()
+                            .ok_or_else(|| anyhow!("{} clauses must be documents", key))?;
+                        parts.push(self.matches(item)?);
+                    }
+                    if key == "$and" {
+                        all(parts)
+                    } else {
+                        any(parts)
+                    }
+                }
+                op if op.starts_with('$') => {
+                    return Err(anyhow!("unsupported query operator {}", op))
+                }
+                path => self.field_matches(path, condition)?,
+            });
+        }
+        Ok(all(clauses))
+    }
+
+    fn field_matches(&mut self, path: &str, condition: &Bson) -> Result<String> {
+        let raw = format!("(doc #> {}::text[])", self.path(path));
+        // Missing and `null` read alike, as in MongoDB equality.
+        let field = format!("nullif({}, 'null')", raw);
+        let operators = match condition {
+            Bson::Document(doc) if doc.keys().next().is_some_and(|k| k.starts_with('$')) => doc,
+            Bson::RegularExpression(regex) => return Ok(self.like(path, regex)),
+            _ => return Ok(self.equals(&field, condition)),
+        };
+        let mut clauses = Vec::with_capacity(operators.len());
+        for (op, operand) in operators {
+            clauses.push(match op.as_str() {
+                "$in" | "$nin" => {
+                    let Bson::Array(options) = operand else {
+                        return Err(anyhow!("{} needs an array", op));
+                    };
+                    let found = any(options.iter().map(|o| self.equals(&field, o)).collect());
+                    if op == "$in" {
+                        found
+                    } else {
+                        format!("NOT {}", found)
+                    }
+                }
+                "$gt" => self.compare(&field, ">", operand),
+                "$gte" => self.compare(&field, ">=", operand),
+                "$lt" => self.compare(&field, "<", operand),
+                "$lte" => self.compare(&field, "<=", operand),
+                "$ne" => format!("NOT {}", self.equals(&field, operand)),
+                "$exists" if operand.as_bool().unwrap_or(true) => format!("{} IS NOT NULL", raw),
+                "$exists" => format!("{} IS NULL", raw),
+                "$regex" => match operand {
+                    Bson::RegularExpression(regex) => self.like(path, regex),
+                    _ => return Err(anyhow!("$regex needs a regular expression")),
+                },
+                other => return Err(anyhow!("unsupported query operator {}", other)),
+            });
+        }
+        Ok(all(clauses))
+    }
+
+    /// MongoDB equality: `null` also matches a missing field, and an array
+    /// field matches when any element does (`@>` on a scalar).
+    fn equals(&mut self, field: &str, wanted: &Bson) -> String {
+        match wanted {
+            Bson::Null => format!("({} IS NULL)", field),
+            Bson::Array(_) | Bson::Document(_) => {
+                format!("coalesce({} = {}, false)", field, self.bind_json(wanted))
+            }
+            _ => format!("coalesce({} @> {}, false)", field, self.bind_json(wanted)),
+        }
+    }
+
+    /// Range operators compare only values of the same JSON type, like
+    /// MongoDB's type bracketing.
+    fn compare(&mut self, field: &str, op: &str, bound: &Bson) -> String {
+        let bound = self.bind_json(bound);
+        format!(
+            "coalesce(jsonb_typeof({field}) = jsonb_typeof({bound}) AND {field} {op} {bound}, false)"
+        )
+    }
+
+    fn like(&mut self, path: &str, regex: &Regex) -> String {
+        let text = format!("(doc #>> {}::text[])", self.path(path));
+        let op = if regex.options.contains('i') {
+            "ILIKE"
+        } else {
+            "LIKE"
+        };
+        let pattern = self.bind(like_pattern(regex));
+        format!("coalesce({} {} {}, false)", text, op, pattern)
+    }
+
+    /// Each sort field in turn; missing and `null` values first ascending,
+    /// as in MongoDB.
+    fn order_by(&mut self, sort: &Document) -> Result<String> {
+        let mut keys = Vec::with_capacity(sort.len());
+        for (field, order) in sort {
+            let direction = match order {
+                Bson::Int32(-1) | Bson::Int64(-1) => "DESC NULLS LAST",
+                Bson::Int32(1) | Bson::Int64(1) => "ASC NULLS FIRST",
+                _ => return Err(anyhow!("sort order for {} must be 1 or -1", field)),
+            };
+            keys.push(format!(
+                "nullif(doc #> {}::text[], 'null') {}",
+                self.path(field),
+                direction
+            ));
+        }
+        Ok(keys.join(", "))
+    }
+}
+
+fn all(clauses: Vec<String>) -> String {
+    match clauses.len() {
+        0 => "TRUE".to_string(),
+        1 => clauses.into_iter().next().unwrap_or_default(),
+        _ => format!("({})", clauses.join(" AND ")),
+    }
+}
+
+fn any(clauses: Vec<String>) -> String {
+    match clauses.len() {
+        0 => "FALSE".to_string(),
+        1 => clauses.into_iter().next().unwrap_or_default(),
+        _ => format!("({})", clauses.join(" OR ")),
+    }
+}
+
+/// `build_filter_doc` only sends escaped literal text (the symbol search),
+/// so the pattern is unescaped and matched as a substring.
+fn like_pattern(regex: &Regex) -> String {
+    let mut pattern = String::with_capacity(regex.pattern.len() + 2);
+    pattern.push('%');
+    let mut chars = regex.pattern.chars();
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        };
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::build_filter_doc;
+    use mongodb::bson::doc;
+
+    fn where_sql(filter: &Document) -> (String, usize) {
+        let mut sql = Sql::default();
+        let clause = sql.matches(filter).unwrap();
+        (clause, sql.params.len())
+    }
+
+    #[test]
+    fn translates_query_operators() {
+        let (clause, params) = where_sql(&doc! {
+            "price": { "$gte": 10.0 },
+            "asset_type": { "$in": ["equity", Bson::Null] },
+        });
+        assert_eq!(
+            clause,
+            "(coalesce(jsonb_typeof(nullif((doc #> $1::text[]), 'null')) = jsonb_typeof($2::jsonb) \
+             AND nullif((doc #> $1::text[]), 'null') >= $2::jsonb, false) AND \
+             (coalesce(nullif((doc #> $3::text[]), 'null') @> $4::jsonb, false) OR \
+             (nullif((doc #> $3::text[]), 'null') IS NULL)))"
+        );
+        assert_eq!(params, 4);
+
+        let (clause, _) =
+            where_sql(&doc! { "symbol": { "$nin": ["GOOG"] }, "rsi": { "$exists": true } });
+        assert_eq!(
+            clause,
+            "(NOT coalesce(nullif((doc #> $1::text[]), 'null') @> $2::jsonb, false) AND \
+             (doc #> $3::text[]) IS NOT NULL)"
+        );
+    }
+
+    #[test]
+    fn translates_every_stock_filter() {
+        // A filter touching each kind of clause `build_filter_doc` emits.
+        let filter = StockFilter {
+            min_price: Some(5.0),
+            sectors: Some(vec!["Energy".to_string()]),
+            only_oversold: Some(true),
+            index: Some("sp500".to_string()),
+            symbol_search: Some("x_".to_string()),
+            primary_class_only: Some(true),
+            asset_type: Some("all".to_string()),
+            ..Default::default()
+        };
+        let (clause, _) = where_sql(&build_filter_doc(&filter));
+        assert!(clause.contains("ILIKE"), "{}", clause);
+        assert!(Sql::default()
+            .matches(&doc! { "price": { "$mod": [2, 0] } })
+            .is_err());
+    }
+
+    #[test]
+    fn symbol_search_becomes_a_like_pattern() {
+        let regex = Regex {
+            pattern: r"BRK\.B%_".to_string(),
+            options: "i".to_string(),
+        };
+        assert_eq!(like_pattern(&regex), r"%BRK.B\%\_%");
+    }
+
+    #[test]
+    fn plans_the_market_summary() {
+        let Plan::Facets(facets) =
+            plan(Select::default(), &market_summary_stages(10, None, None)).unwrap()
+        else {
+            panic!("expected facets");
+        };
+        assert_eq!(facets.len(), 9);
+        let (_, Plan::Rows(gainers)) = &facets[0] else {
+            panic!("expected rows");
+        };
+        assert_eq!(gainers.filters.len(), 2);
+        assert_eq!(gainers.excluded, ["news"]);
+        assert_eq!(gainers.limit, Some(10));
+        let (query, _) = gainers.to_sql().unwrap();
+        assert!(query.ends_with("DESC NULLS LAST LIMIT 10"), "{}", query);
+        assert!(matches!(facets[8], (_, Plan::Count { .. })));
+
+        let stages = [doc! { "$limit": 5 }, doc! { "$match": {} }];
+        assert!(plan(Select::default(), &stages).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Thematic symbol sets (AI, EV, semiconductors, ...).
+//!
+//! Unlike the embedded index lists, themes live in the `themes` collection so
+//! their membership can be edited through the admin API. The defaults below
+//! are seeded once, when the collection is empty. Any screen can be scoped to
+//! a theme through `StockFilter::theme`, and every cycle records each theme's
+//! daily equal- vs cap-weighted return in `theme_performance`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Theme {
+    /// Lowercase slug, e.g. `ai`.
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Normalized symbol keys.
+    pub symbols: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body of `PUT /api/admin/themes/:id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub symbols: Vec<String>,
+}
+
+/// Body of `POST /api/admin/themes/:id/symbols`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeMembershipChange {
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// Theme ids are lowercase ASCII letters, digits and dashes.
+pub fn normalize_theme_id(input: &str) -> Option<String> {
+    let id = input.trim().to_ascii_lowercase();
+    let valid = !id.is_empty()
+        && id.len() <= 32
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    valid.then_some(id)
+}
+
+/// Normalized, de-duplicated symbols in input order.
+fn normalize_symbols<'a>(symbols: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    symbols
+        .into_iter()
+        .map(|s| crate::symbols::normalize_symbol_key(s))
+        .filter(|s| !s.is_empty() && seen.insert(s.clone()))
+        .collect()
+}
+
+impl Theme {
+    pub fn from_input(id: &str, input: &ThemeInput) -> Result<Self, String> {
+        let id = normalize_theme_id(id)
+            .ok_or_else(|| "theme id must be 1-32 letters, digits or dashes".to_string())?;
+        let name = input.name.trim();
+        if name.is_empty() {
+            return Err("name is required".to_string());
+        }
+        Ok(Self {
+            id,
+            name: name.to_string(),
+            description: input.description.trim().to_string(),
+            symbols: normalize_symbols(&input.symbols),
+            updated_at: Utc::now(),
+        })
+    }
+
+    /// Add then remove symbols. Returns whether membership changed.
+    pub fn apply(&mut self, change: &ThemeMembershipChange) -> bool {
+        let before = self.symbols.clone();
+        let removed = normalize_symbols(&change.remove);
+        self.symbols = normalize_symbols(self.symbols.iter().chain(&change.add));
+        self.symbols.retain(|s| !removed.contains(s));
+        let changed = self.symbols != before;
+        if changed {
+            self.updated_at = Utc::now();
+        }
+        changed
+    }
+}
+
+/// Seeded into an empty `themes` collection at startup.
+pub fn default_themes() -> Vec<Theme> {
+    let theme = |id: &str, name: &str, description: &str, symbols: &[&str]| Theme {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        symbols: symbols.iter().map(|s| s.to_string()).collect(),
+        updated_at: Utc::now(),
+    };
+    vec![
+        theme(
+            "ai",
+            "Artificial Intelligence",
+            "AI compute, infrastructure and software",
+            &[
+                "NVDA", "MSFT", "GOOGL", "META", "AMZN", "AMD", "AVGO", "ORCL", "PLTR", "SNOW",
+                "CRM", "NOW", "SMCI", "ANET", "DELL", "MRVL", "AI", "PATH",
+            ],
+        ),
+        theme(
+            "ev",
+            "Electric Vehicles",
+            "EV makers, batteries and charging",
+            &[
+                "TSLA", "RIVN", "LCID", "NIO", "XPEV", "LI", "GM", "F", "ALB", "SQM", "CHPT",
+                "BLNK", "QS", "ENPH",
+            ],
+        ),
+        theme(
+            "semis",
+            "Semiconductors",
+            "Chip designers, foundries and equipment",
+            &[
+                "NVDA", "AMD", "AVGO", "INTC", "QCOM", "TXN", "MU", "TSM", "ASML", "AMAT", "LRCX",
+                "KLAC", "ADI", "MRVL", "NXPI", "MCHP", "ON", "ARM",
+            ],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_input_and_membership() {
+        let input = ThemeInput {
+            name: " Cloud ".to_string(),
+            description: String::new(),
+            symbols: vec!["snow".into(), "NET".into(), "brk.b".into(), "SNOW".into()],
+        };
+        let mut theme = Theme::from_input("Cloud", &input).unwrap();
+        assert_eq!(theme.id, "cloud");
+        assert_eq!(theme.name, "Cloud");
+        assert_eq!(theme.symbols, vec!["SNOW", "NET", "BRK-B"]);
+
+        let changed = theme.apply(&ThemeMembershipChange {
+            add: vec!["ddog".into(), "NET".into()],
+            remove: vec!["brk.b".into()],
+        });
+        assert!(changed);
+        assert_eq!(theme.symbols, vec!["SNOW", "NET", "DDOG"]);
+        assert!(!theme.apply(&ThemeMembershipChange::default()));
+
+        assert!(Theme::from_input("bad id", &input).is_err());
+        assert!(normalize_theme_id("").is_none());
+    }
+}
@@ -0,0 +1,116 @@
+//! Per-request timezone for API timestamps.
+//!
+//! Timestamps are stored and produced in UTC. A middleware rewrites every
+//! RFC 3339 timestamp in JSON responses (`analyzed_at`, history bar dates,
+//! news times, ...) into the zone from `?tz=America/New_York`, falling back to
+//! `API_TIMEZONE`. Date-only strings are left alone, and UTC responses are
+//! passed through untouched.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Query, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, SecondsFormat};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Default, Deserialize)]
+struct TzQuery {
+    tz: Option<String>,
+}
+
+/// Parse an IANA timezone name.
+pub fn parse_tz(name: &str) -> anyhow::Result<Tz> {
+    name.trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("unknown timezone '{}'", name))
+}
+
+/// Rewrite every RFC 3339 timestamp inside `value` into `tz`.
+pub fn localize(value: &mut Value, tz: Tz) {
+    match value {
+        // Cheap pre-check before attempting a parse on every string.
+        Value::String(s) if s.len() >= 20 && s.as_bytes().get(10) == Some(&b'T') => {
+            if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                *s = dt
+                    .with_timezone(&tz)
+                    .to_rfc3339_opts(SecondsFormat::AutoSi, true);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| localize(v, tz)),
+        Value::Object(map) => map.values_mut().for_each(|v| localize(v, tz)),
+        _ => {}
+    }
+}
+
+/// Middleware: validate `?tz=` and localize timestamps in JSON responses.
+pub async fn localize_timestamps(
+    State(default_tz): State<Tz>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let requested = Query::<TzQuery>::try_from_uri(request.uri())
+        .map(|Query(q)| q.tz)
+        .unwrap_or_default();
+    let tz = match requested.as_deref().map(parse_tz) {
+        Some(Ok(tz)) => tz,
+        Some(Err(e)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "success": false, "error": e.to_string() })),
+            )
+                .into_response()
+        }
+        None => default_tz,
+    };
+
+    let response = next.run(request).await;
+    if tz == chrono_tz::UTC || !is_json(&response) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    localize(&mut value, tz);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localizes_nested_timestamps_only() {
+        let mut v = json!({
+            "analyzed_at": "2025-06-02T14:30:00Z",
+            "history": [{ "date": "2025-01-02T21:00:00.500Z", "close": 1.0 }],
+            "date": "2025-06-02",
+            "title": "Shares rise"
+        });
+        localize(&mut v, parse_tz("America/New_York").unwrap());
+        assert_eq!(v["analyzed_at"], "2025-06-02T10:30:00-04:00");
+        assert_eq!(v["history"][0]["date"], "2025-01-02T16:00:00.500-05:00");
+        assert_eq!(v["date"], "2025-06-02");
+        assert_eq!(v["title"], "Shares rise");
+        assert!(parse_tz("Mars/Olympus").is_err());
+    }
+}
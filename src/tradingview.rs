@@ -0,0 +1,274 @@
+//! TradingView webhook alerts.
+//!
+//! `POST /api/ingest/tradingview` takes the body TradingView posts for an
+//! alert as is and maps it onto a `SignalInput` for the external signal inbox
+//! (see `ingest.rs`). Two shapes are understood:
+//!
+//! - JSON alert messages built from TradingView placeholders, with keys either
+//!   flat (`"ticker"`, `"order_action"`, `"close"`), nested like the
+//!   placeholders (`"strategy": { "order": { "action": ... } }`) or named
+//!   after them verbatim (`"strategy.order.action"`).
+//! - The default plain-text strategy alert, e.g.
+//!   `My Strategy (20, 5): order buy @ 1 filled on NASDAQ:AAPL. New strategy position is 1`.
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::ingest::SignalInput;
+
+const SOURCE: &str = "tradingview";
+
+/// Candidate keys for each field, most specific first.
+const TICKER_KEYS: &[&str] = &["ticker", "syminfo.ticker", "symbol"];
+const EXCHANGE_KEYS: &[&str] = &["exchange", "syminfo.exchange"];
+const ACTION_KEYS: &[&str] = &[
+    "strategy.order.action",
+    "strategy.order_action",
+    "order.action",
+    "order_action",
+    "action",
+    "signal",
+    "side",
+];
+const POSITION_KEYS: &[&str] = &["strategy.market_position", "market_position"];
+const PRICE_KEYS: &[&str] = &[
+    "strategy.order.price",
+    "strategy.order_price",
+    "order.price",
+    "order_price",
+    "price",
+    "close",
+];
+const STRATEGY_KEYS: &[&str] = &["strategy.name", "strategy_name", "strategy", "name"];
+const COMMENT_KEYS: &[&str] = &[
+    "strategy.order.comment",
+    "strategy.order_comment",
+    "order.comment",
+    "order_comment",
+    "comment",
+    "message",
+    "text",
+    "note",
+];
+const CONTRACTS_KEYS: &[&str] = &[
+    "strategy.order.contracts",
+    "strategy.order_contracts",
+    "order.contracts",
+    "order_contracts",
+    "contracts",
+];
+const POSITION_SIZE_KEYS: &[&str] = &["strategy.position_size", "position_size"];
+const INTERVAL_KEYS: &[&str] = &["interval", "timeframe"];
+const TOKEN_KEYS: &[&str] = &["token", "passphrase"];
+
+/// Map a TradingView alert body onto a `SignalInput` with `source`
+/// `tradingview`.
+pub fn parse_alert(body: &str) -> Result<SignalInput> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(anyhow!("empty TradingView alert"));
+    }
+    match serde_json::from_str::<Value>(body) {
+        Ok(value @ Value::Object(_)) => from_json(&value),
+        Ok(_) => Err(anyhow!("TradingView alert JSON must be an object")),
+        Err(_) => from_text(body),
+    }
+}
+
+fn from_json(value: &Value) -> Result<SignalInput> {
+    let ticker = lookup(value, TICKER_KEYS).ok_or_else(|| anyhow!("alert has no ticker"))?;
+    let exchange = lookup(value, EXCHANGE_KEYS);
+    let signal = lookup(value, ACTION_KEYS).or_else(|| lookup(value, POSITION_KEYS));
+
+    let mut details = Vec::new();
+    if let Some(contracts) = lookup(value, CONTRACTS_KEYS) {
+        details.push(format!("contracts {}", contracts));
+    }
+    if let Some(size) = lookup(value, POSITION_SIZE_KEYS) {
+        details.push(format!("position {}", size));
+    }
+    if let Some(interval) = lookup(value, INTERVAL_KEYS) {
+        details.push(format!("interval {}", interval));
+    }
+    let comment = lookup(value, COMMENT_KEYS);
+    let message = match (comment, details.is_empty()) {
+        (Some(comment), true) => Some(comment),
+        (Some(comment), false) => Some(format!("{} ({})", comment, details.join(", "))),
+        (None, false) => Some(details.join(", ")),
+        (None, true) => None,
+    };
+
+    let price = match lookup(value, PRICE_KEYS) {
+        Some(raw) => Some(
+            raw.parse::<f64>()
+                .map_err(|_| anyhow!("invalid price '{}'", raw))?,
+        ),
+        None => None,
+    };
+
+    Ok(SignalInput {
+        symbol: to_symbol(&ticker, exchange.as_deref()),
+        // A bare alert with no action still records that it fired.
+        signal: Some(signal.unwrap_or_else(|| "alert".to_string())),
+        source: Some(SOURCE.to_string()),
+        strategy: lookup(value, STRATEGY_KEYS),
+        message,
+        price,
+        token: lookup(value, TOKEN_KEYS),
+    })
+}
+
+/// `<strategy>: order <action> @ <contracts> filled on <ticker>. New strategy position is <size>`
+fn from_text(body: &str) -> Result<SignalInput> {
+    let unrecognized = || anyhow!("unrecognized TradingView alert text");
+    let (strategy, rest) = body.rsplit_once(": order ").ok_or_else(unrecognized)?;
+    let (action, rest) = rest.trim().split_once(' ').ok_or_else(unrecognized)?;
+    let (contracts, rest) = rest
+        .trim()
+        .strip_prefix("@ ")
+        .and_then(|r| r.split_once(" filled on "))
+        .ok_or_else(unrecognized)?;
+    let (ticker, position) = match rest.split_once(". New strategy position is ") {
+        Some((ticker, position)) => (ticker, Some(position.trim().trim_end_matches('.'))),
+        None => (rest.trim().trim_end_matches('.'), None),
+    };
+    if ticker.trim().is_empty() {
+        return Err(unrecognized());
+    }
+
+    let mut message = format!("contracts {}", contracts.trim());
+    if let Some(position) = position {
+        message.push_str(&format!(", position {}", position));
+    }
+    Ok(SignalInput {
+        symbol: to_symbol(ticker, None),
+        signal: Some(action.to_string()),
+        source: Some(SOURCE.to_string()),
+        strategy: Some(strategy.trim().to_string()),
+        message: Some(message),
+        price: None,
+        token: None,
+    })
+}
+
+/// TradingView tickers may carry an exchange prefix (`NASDAQ:AAPL`). TSX and
+/// TSX Venture listings map to Yahoo's `.TO` / `.V` suffixes; every other
+/// exchange is dropped.
+fn to_symbol(ticker: &str, exchange: Option<&str>) -> String {
+    let (prefix, ticker) = match ticker.trim().split_once(':') {
+        Some((prefix, ticker)) => (Some(prefix), ticker),
+        None => (None, ticker.trim()),
+    };
+    let suffix = match prefix
+        .or(exchange)
+        .map(|e| e.trim().to_ascii_uppercase())
+        .as_deref()
+    {
+        Some("TSX") => ".TO",
+        Some("TSXV") => ".V",
+        _ => "",
+    };
+    format!("{}{}", ticker, suffix)
+}
+
+/// First non-empty value among `keys`. A dotted key matches either a
+/// literal key of that name or the nested path it spells out; numbers are
+/// returned as text.
+fn lookup(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| {
+        let found = value
+            .get(*key)
+            .or_else(|| key.split('.').try_fold(value, |v, part| v.get(part)))?;
+        match found {
+            Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn flat_strategy_template() {
+        let input = parse_alert(
+            r#"{
+                "ticker": "NASDAQ:AAPL",
+                "strategy": "RSI Reversal",
+                "order_action": "BUY",
+                "order_price": "214.90",
+                "order_contracts": 10,
+                "order_comment": "Long entry",
+                "interval": "15"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(input.symbol, "AAPL");
+        assert_eq!(input.signal.as_deref(), Some("BUY"));
+        assert_eq!(input.strategy.as_deref(), Some("RSI Reversal"));
+        assert_eq!(input.price, Some(214.9));
+        assert_eq!(
+            input.message.as_deref(),
+            Some("Long entry (contracts 10, interval 15)")
+        );
+
+        let signal = input.into_signal(Utc::now()).unwrap();
+        assert_eq!(signal.signal, "buy");
+        assert_eq!(signal.source, "tradingview");
+    }
+
+    #[test]
+    fn nested_and_verbatim_placeholder_keys() {
+        let nested = parse_alert(
+            r#"{"ticker": "MSFT", "close": 410.5,
+                "strategy": {"name": "Breakout", "order": {"action": "sell", "price": 411}}}"#,
+        )
+        .unwrap();
+        assert_eq!(nested.signal.as_deref(), Some("sell"));
+        assert_eq!(nested.strategy.as_deref(), Some("Breakout"));
+        assert_eq!(nested.price, Some(411.0));
+
+        let verbatim = parse_alert(
+            r#"{"ticker": "SHOP", "exchange": "TSX", "strategy.order.action": "buy",
+                "strategy.market_position": "long", "passphrase": "s3cret"}"#,
+        )
+        .unwrap();
+        assert_eq!(verbatim.symbol, "SHOP.TO");
+        assert_eq!(verbatim.signal.as_deref(), Some("buy"));
+        assert_eq!(verbatim.token.as_deref(), Some("s3cret"));
+        assert_eq!(verbatim.price, None);
+    }
+
+    #[test]
+    fn indicator_alert_without_action() {
+        let input = parse_alert(r#"{"ticker": "TSLA", "close": "251.2"}"#).unwrap();
+        assert_eq!(input.signal.as_deref(), Some("alert"));
+        assert_eq!(input.message, None);
+        assert_eq!(input.price, Some(251.2));
+    }
+
+    #[test]
+    fn default_strategy_message() {
+        let input = parse_alert(
+            "My Strategy (20, 5): order sell @ 3 filled on NYSE:BRK.B. New strategy position is -3",
+        )
+        .unwrap();
+        assert_eq!(input.symbol, "BRK.B");
+        assert_eq!(input.signal.as_deref(), Some("sell"));
+        assert_eq!(input.strategy.as_deref(), Some("My Strategy (20, 5)"));
+        assert_eq!(input.message.as_deref(), Some("contracts 3, position -3"));
+        assert_eq!(input.into_signal(Utc::now()).unwrap().symbol, "BRK-B");
+    }
+
+    #[test]
+    fn rejects_unusable_bodies() {
+        assert!(parse_alert("").is_err());
+        assert!(parse_alert("price crossed up").is_err());
+        assert!(parse_alert("[1, 2]").is_err());
+        assert!(parse_alert(r#"{"close": 1}"#).is_err());
+        assert!(parse_alert(r#"{"ticker": "AAPL", "price": "n/a"}"#).is_err());
+    }
+}
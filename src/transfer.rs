@@ -0,0 +1,317 @@
+//! Export and import of market data over HTTP, for moving it between
+//! environments.
+//!
+//! `GET /api/admin/export` streams collections in the seed format (see
+//! `seed.rs`): one `{"collection", "document"}` NDJSON line per document,
+//! gzipped unless `?format=ndjson`. A gzipped export of every collection is
+//! a valid `SEED_SNAPSHOT`. `POST /api/admin/import` takes either form back
+//! and replaces documents by their natural key (a stock's symbol, an
+//! index's day, ...), so importing into a populated database updates it
+//! instead of duplicating rows. Analyses go through
+//! `StorageBackend::save_analysis`, so an older export never overwrites a
+//! newer analysis. Only [`SEED_COLLECTIONS`] are exported or imported; user
+//! data never travels this way.
+
+use std::io::{BufReader, Cursor, Write};
+
+use anyhow::{bail, Result};
+use axum::body::Bytes;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::stream::{Stream, StreamExt};
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Collection;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CacheLayer;
+use crate::db::MongoDB;
+use crate::models::StockAnalysis;
+use crate::seed::{self, SeedSummary, SEED_COLLECTIONS};
+use crate::storage::StorageBackend;
+
+/// Fields identifying a document in each seed collection, matching the
+/// unique indexes in `db.rs`.
+const IMPORT_KEYS: [(&str, &[&str]); 10] = [
+    ("stock_analysis", &["symbol"]),
+    ("price_history", &["symbol"]),
+    ("sector_etfs", &["etf"]),
+    ("index_performance", &["index_id", "date"]),
+    ("index_contributors", &["index_id", "date"]),
+    ("theme_performance", &["index_id", "date"]),
+    ("screen_results", &["name"]),
+    ("cross_section_stats", &["symbol"]),
+    ("week52_events", &["symbol", "kind", "date"]),
+    ("signals", &["symbol", "strategy", "signal_date"]),
+];
+
+/// Uncompressed bytes buffered before a chunk is sent.
+const EXPORT_CHUNK: usize = 256 * 1024;
+
+/// Collections exported by default.
+pub const DEFAULT_EXPORT: &str = "stock_analysis";
+
+/// `?collections=` for export: `all`, or a comma-separated subset of
+/// [`SEED_COLLECTIONS`].
+pub fn parse_collections(value: Option<&str>) -> Result<Vec<&'static str>> {
+    let value = value.unwrap_or(DEFAULT_EXPORT).trim();
+    if value.eq_ignore_ascii_case("all") {
+        return Ok(SEED_COLLECTIONS.to_vec());
+    }
+    let mut collections = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let Some(known) = SEED_COLLECTIONS.iter().find(|c| **c == name) else {
+            bail!(
+                "unknown collection '{}'; expected all or {}",
+                name,
+                SEED_COLLECTIONS.join(", ")
+            );
+        };
+        if !collections.contains(known) {
+            collections.push(*known);
+        }
+    }
+    if collections.is_empty() {
+        bail!("no collections to export");
+    }
+    Ok(collections)
+}
+
+/// Where export lines are written before being sent.
+enum Output {
+    Plain(Vec<u8>),
+    Gzip(GzEncoder<Vec<u8>>),
+}
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Output::Plain(buf) => buf.write(bytes),
+            Output::Gzip(encoder) => encoder.write(bytes),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::Plain(buf) => buf.flush(),
+            Output::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl Output {
+    fn buffered(&self) -> usize {
+        match self {
+            Output::Plain(buf) => buf.len(),
+            Output::Gzip(encoder) => encoder.get_ref().len(),
+        }
+    }
+
+    /// Bytes ready to send so far.
+    fn take(&mut self) -> Bytes {
+        match self {
+            Output::Plain(buf) => Bytes::from(std::mem::take(buf)),
+            Output::Gzip(encoder) => Bytes::from(std::mem::take(encoder.get_mut())),
+        }
+    }
+
+    fn finish(self) -> Result<Bytes> {
+        Ok(match self {
+            Output::Plain(buf) => Bytes::from(buf),
+            Output::Gzip(encoder) => Bytes::from(encoder.finish()?),
+        })
+    }
+}
+
+/// Every document of `collections` as seed lines, in chunks, without
+/// holding the export in memory.
+pub fn export_stream(
+    db: MongoDB,
+    collections: Vec<&'static str>,
+    gzip: bool,
+) -> impl Stream<Item = Result<Bytes>> {
+    async_stream::try_stream! {
+        let mut out = if gzip {
+            Output::Gzip(GzEncoder::new(Vec::new(), Compression::default()))
+        } else {
+            Output::Plain(Vec::new())
+        };
+        for collection in collections {
+            let source: Collection<Document> = db.database().collection(collection);
+            let mut cursor = source.find(doc! {}).await?;
+            while let Some(document) = cursor.next().await {
+                seed::write_line(&mut out, collection, document?)?;
+                if out.buffered() >= EXPORT_CHUNK {
+                    yield out.take();
+                }
+            }
+        }
+        yield out.finish()?;
+    }
+}
+
+/// Documents written by an import.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// Documents inserted or replaced per collection.
+    pub imported: SeedSummary,
+    /// Lines for collections outside the seed set, ignored.
+    pub skipped: u64,
+    /// `stock_analysis` lines older than the stored analysis, left unchanged.
+    pub stale: u64,
+}
+
+/// Filter matching the stored copy of `document`: its natural key, or its
+/// `_id` when a key field is missing.
+fn import_filter(collection: &str, document: &Document) -> Option<Document> {
+    let (_, keys) = IMPORT_KEYS.iter().find(|(name, _)| *name == collection)?;
+    let mut filter = Document::new();
+    for key in *keys {
+        match document.get(*key) {
+            Some(value) if *value != Bson::Null => {
+                filter.insert(*key, value.clone());
+            }
+            _ => return document.get("_id").map(|id| doc! { "_id": id.clone() }),
+        }
+    }
+    Some(filter)
+}
+
+/// Load an export (gzipped or plain NDJSON), replacing stored documents
+/// with the same key, and drop the cached copies of imported analyses.
+/// Analyses are saved through `storage`, which keeps a newer stored one.
+pub async fn import(
+    db: &MongoDB,
+    storage: &dyn StorageBackend,
+    cache: &CacheLayer,
+    body: &[u8],
+) -> Result<ImportSummary> {
+    let lines: Box<dyn Iterator<Item = Result<(String, Document)>> + Send> =
+        if body.starts_with(&[0x1f, 0x8b]) {
+            Box::new(seed::parse_lines(BufReader::new(GzDecoder::new(body))))
+        } else {
+            Box::new(seed::parse_lines(Cursor::new(body)))
+        };
+    let mut summary = ImportSummary::default();
+    for line in lines {
+        let (collection, mut document) = line?;
+        if collection == "stock_analysis" {
+            let mut analysis: StockAnalysis = mongodb::bson::from_document(document)?;
+            // The stored copy keeps its own `_id`.
+            analysis.id = None;
+            if storage.save_analysis(&analysis).await? {
+                cache.invalidate_stock(&analysis.symbol).await;
+                *summary.imported.entry(collection).or_default() += 1;
+            } else {
+                summary.stale += 1;
+            }
+            continue;
+        }
+        let Some(filter) = import_filter(&collection, &document) else {
+            summary.skipped += 1;
+            continue;
+        };
+        // The stored copy keeps its own `_id` when matched by key.
+        if !filter.contains_key("_id") {
+            document.remove("_id");
+        }
+        let target: Collection<Document> = db.database().collection(&collection);
+        target.replace_one(filter, &document).upsert(true).await?;
+        *summary.imported.entry(collection).or_default() += 1;
+    }
+    cache.invalidate_all_lists().await;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_keys_cover_the_seed_collections() {
+        let keyed: Vec<&str> = IMPORT_KEYS.iter().map(|(name, _)| *name).collect();
+        assert_eq!(keyed, SEED_COLLECTIONS.to_vec());
+    }
+
+    #[test]
+    fn test_gzip_chunks_concatenate_to_one_export() {
+        let analysis = doc! { "symbol": "AAPL", "price": 190.5 };
+        let history = doc! { "symbol": "AAPL", "closes": [1.0, 2.0] };
+        let mut out = Output::Gzip(GzEncoder::new(Vec::new(), Compression::default()));
+        seed::write_line(&mut out, "stock_analysis", analysis.clone()).unwrap();
+        let mut bytes = out.take().to_vec();
+        seed::write_line(&mut out, "price_history", history.clone()).unwrap();
+        bytes.extend_from_slice(&out.finish().unwrap());
+
+        let lines: Vec<(String, Document)> =
+            seed::parse_lines(BufReader::new(GzDecoder::new(bytes.as_slice())))
+                .collect::<Result<_>>()
+                .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                ("stock_analysis".to_string(), analysis),
+                ("price_history".to_string(), history),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_collections() {
+        assert_eq!(parse_collections(None).unwrap(), vec!["stock_analysis"]);
+        assert_eq!(parse_collections(Some("all")).unwrap().len(), 10);
+        assert_eq!(
+            parse_collections(Some("signals, stock_analysis,signals")).unwrap(),
+            vec!["signals", "stock_analysis"]
+        );
+        assert!(parse_collections(Some("watchlists")).is_err());
+        assert!(parse_collections(Some(" , ")).is_err());
+    }
+
+    #[test]
+    fn test_import_filter_uses_the_natural_key() {
+        let id = mongodb::bson::oid::ObjectId::new();
+        let analysis = doc! { "_id": id, "symbol": "AAPL", "price": 190.5 };
+        assert_eq!(
+            import_filter("stock_analysis", &analysis),
+            Some(doc! { "symbol": "AAPL" })
+        );
+        let event = doc! { "_id": id, "symbol": "AAPL", "kind": "high", "date": "2025-06-02" };
+        assert_eq!(
+            import_filter("week52_events", &event),
+            Some(doc! { "symbol": "AAPL", "kind": "high", "date": "2025-06-02" })
+        );
+        // Missing key fields fall back to `_id`.
+        assert_eq!(
+            import_filter("week52_events", &analysis),
+            Some(doc! { "_id": id })
+        );
+        assert_eq!(import_filter("watchlists", &analysis), None);
+    }
+
+    #[tokio::test]
+    async fn test_import_keeps_a_newer_stored_analysis() {
+        use crate::storage::MemoryStorage;
+
+        let mut stored = StockAnalysis::sample("NVDA", 120.0);
+        stored.analyzed_at = "2025-06-06T20:00:00Z".parse().unwrap();
+        let storage = MemoryStorage::new([stored]);
+        let mut older = StockAnalysis::sample("NVDA", 100.0);
+        older.analyzed_at = "2025-06-02T20:00:00Z".parse().unwrap();
+        let mut body = Vec::new();
+        let document = mongodb::bson::to_document(&older).unwrap();
+        seed::write_line(&mut body, "stock_analysis", document).unwrap();
+
+        let db = MongoDB::unreachable().await;
+        let cache = CacheLayer::new(300, 60, 16);
+        let summary = import(&db, &storage, &cache, &body).await.unwrap();
+        assert_eq!(summary.stale, 1);
+        assert!(summary.imported.is_empty());
+        let kept = storage
+            .get_analysis_by_symbol("NVDA")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(kept.price, 120.0);
+    }
+}
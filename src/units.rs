@@ -0,0 +1,107 @@
+//! Whole-unit quantities: market caps in dollars and volumes in shares.
+//!
+//! Both used to be stored and served as `f64`, which loses precision above
+//! 2^53 and can render as `2.5e12`. They are now `u64` on the models, stored
+//! as BSON `Int64` and served as JSON integers. Older documents, seeds and
+//! API payloads still carry floats (or numeric strings), so the models read
+//! them through [`opt_u64`]; `repair::run` rewrites the stored ones.
+
+use serde::{Deserialize, Deserializer};
+
+/// Round a float quantity to whole units. Negative, NaN and infinite
+/// values have no meaning as a cap or a volume and give `None`.
+pub fn to_u64(value: f64) -> Option<u64> {
+    (value.is_finite() && value >= 0.0).then(|| value.round() as u64)
+}
+
+/// Integer or legacy float/string form of a quantity.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Quantity {
+    Int(u64),
+    Signed(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Quantity {
+    fn units(self) -> Option<u64> {
+        match self {
+            Quantity::Int(v) => Some(v),
+            Quantity::Signed(v) => u64::try_from(v).ok(),
+            Quantity::Float(v) => to_u64(v),
+            Quantity::Text(s) => to_u64(s.trim().replace(',', "").parse().ok()?),
+        }
+    }
+}
+
+/// `deserialize_with` for `Option<u64>` quantities that accepts integers,
+/// floats (rounded, so `2.5e12` reads as 2500000000000) and numeric
+/// strings. Values that aren't a non-negative number read as `None` rather
+/// than failing the whole document.
+pub fn opt_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Quantity>::deserialize(deserializer)
+        .ok()
+        .flatten()
+        .and_then(Quantity::units))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Sample {
+        #[serde(default, deserialize_with = "opt_u64")]
+        market_cap: Option<u64>,
+    }
+
+    fn read_json(json: &str) -> Option<u64> {
+        serde_json::from_str::<Sample>(json).unwrap().market_cap
+    }
+
+    #[test]
+    fn test_reads_integers_floats_and_strings() {
+        assert_eq!(
+            read_json(r#"{"market_cap": 2500000000000}"#),
+            Some(2_500_000_000_000)
+        );
+        assert_eq!(
+            read_json(r#"{"market_cap": 2.5e12}"#),
+            Some(2_500_000_000_000)
+        );
+        assert_eq!(read_json(r#"{"market_cap": 1234.6}"#), Some(1235));
+        assert_eq!(read_json(r#"{"market_cap": "1,234,567"}"#), Some(1_234_567));
+        // Above 2^53, where an f64 would round.
+        assert_eq!(
+            read_json(r#"{"market_cap": 9007199254740993}"#),
+            Some(9_007_199_254_740_993)
+        );
+    }
+
+    #[test]
+    fn test_unusable_values_read_as_none() {
+        assert_eq!(read_json(r#"{"market_cap": null}"#), None);
+        assert_eq!(read_json(r#"{}"#), None);
+        assert_eq!(read_json(r#"{"market_cap": -5}"#), None);
+        assert_eq!(read_json(r#"{"market_cap": -5.0}"#), None);
+        assert_eq!(read_json(r#"{"market_cap": "N/A"}"#), None);
+        assert_eq!(read_json(r#"{"market_cap": {"v": 1}}"#), None);
+    }
+
+    #[test]
+    fn test_reads_bson_doubles_and_int64() {
+        use mongodb::bson::{doc, from_document};
+        let read = |doc| from_document::<Sample>(doc).unwrap().market_cap;
+        assert_eq!(read(doc! { "market_cap": 3.0e9 }), Some(3_000_000_000));
+        assert_eq!(
+            read(doc! { "market_cap": 3_000_000_000_i64 }),
+            Some(3_000_000_000)
+        );
+        assert_eq!(read(doc! { "market_cap": 7_i32 }), Some(7));
+    }
+}
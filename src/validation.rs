@@ -0,0 +1,275 @@
+//! Request body validation.
+//!
+//! `ValidatedJson<T>` is a drop-in replacement for `Json<T>` on handlers
+//! whose body type derives `validator::Validate`. Invalid bodies are
+//! rejected with `422 Unprocessable Entity` and every failing field:
+//!
+//! ```json
+//! {
+//!   "success": false,
+//!   "error": "invalid request: page_size must be between 1 and 200",
+//!   "errors": [{ "field": "page_size", "code": "range", "message": "must be between 1 and 200" }]
+//! }
+//! ```
+//!
+//! Nested fields are reported by path (`conditions.children[1].condition.value`).
+//! Malformed JSON keeps axum's status (400/415/422) in the same envelope.
+
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+/// One invalid field of a request body.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// `Json<T>` that also runs `T::validate()`.
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(json_rejection)?;
+        value.validate().map_err(|e| invalid(&e))?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+fn json_rejection(rejection: JsonRejection) -> Response {
+    (
+        rejection.status(),
+        Json(json!({ "success": false, "error": rejection.body_text() })),
+    )
+        .into_response()
+}
+
+/// The 422 response for a failed validation.
+pub fn invalid(errors: &ValidationErrors) -> Response {
+    let fields = field_errors(errors);
+    let summary: Vec<String> = fields
+        .iter()
+        .map(|f| format!("{} {}", f.field, f.message))
+        .collect();
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(json!({
+            "success": false,
+            "error": format!("invalid request: {}", summary.join("; ")),
+            "errors": fields,
+        })),
+    )
+        .into_response()
+}
+
+/// Flatten nested validation errors into one entry per failing field,
+/// sorted by path.
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut out = Vec::new();
+    collect(errors, "", &mut out);
+    out.sort_by(|a, b| a.field.cmp(&b.field));
+    out
+}
+
+fn collect(errors: &ValidationErrors, prefix: &str, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(errs) => out.extend(errs.iter().map(|e| FieldError {
+                field: path.clone(),
+                code: e.code.to_string(),
+                message: describe(e),
+            })),
+            ValidationErrorsKind::Struct(inner) => collect(inner, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (i, inner) in items {
+                    collect(inner, &format!("{}[{}]", path, i), out);
+                }
+            }
+        }
+    }
+}
+
+/// The error's own message, or one built from the `range` / `length`
+/// parameters the derive attaches.
+fn describe(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    let param = |name: &str| error.params.get(name).map(show);
+    match error.code.as_ref() {
+        "range" => match (
+            param("min"),
+            param("max"),
+            param("exclusive_min"),
+            param("exclusive_max"),
+        ) {
+            (Some(min), Some(max), _, _) => format!("must be between {} and {}", min, max),
+            (Some(min), None, _, _) => format!("must be at least {}", min),
+            (None, Some(max), _, _) => format!("must be at most {}", max),
+            (None, None, Some(min), _) => format!("must be greater than {}", min),
+            (None, None, None, Some(max)) => format!("must be less than {}", max),
+            _ => "is out of range".to_string(),
+        },
+        "length" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("must have between {} and {} entries", min, max),
+            (Some(min), None) => format!("must have at least {} entries", min),
+            (None, Some(max)) => format!("must have at most {} entries", max),
+            _ => "has an invalid length".to_string(),
+        },
+        code => format!("is invalid ({})", code),
+    }
+}
+
+/// `0.0` → `0`, `2.5` → `2.5`.
+fn show(value: &Value) -> String {
+    match value.as_f64() {
+        Some(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", n as i64),
+        _ => value.to_string(),
+    }
+}
+
+/// A `ValidationError` with a fixed message, for hand-written checks.
+pub fn error(code: &'static str, message: impl Into<Cow<'static, str>>) -> ValidationError {
+    ValidationError::new(code).with_message(message.into())
+}
+
+/// `#[validate(custom(function = "crate::validation::not_blank"))]`
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(error("blank", "must not be empty"));
+    }
+    Ok(())
+}
+
+/// `#[validate(custom(function = "crate::validation::non_zero"))]`
+pub fn non_zero(value: f64) -> Result<(), ValidationError> {
+    if !value.is_finite() || value == 0.0 {
+        return Err(error("non_zero", "must be a non-zero number"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StockFilter;
+
+    #[test]
+    fn flattens_filter_errors_with_messages() {
+        let filter = StockFilter {
+            min_price: Some(-1.0),
+            max_rsi: Some(120.0),
+            page: Some(0),
+            page_size: Some(500),
+            ..StockFilter::default()
+        };
+        let errors = field_errors(&filter.validate().unwrap_err());
+        let summary: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|e| (e.field.as_str(), e.message.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("max_rsi", "must be between 0 and 100"),
+                ("min_price", "must be at least 0"),
+                ("page", "must be at least 1"),
+                ("page_size", "must be between 1 and 200"),
+            ]
+        );
+
+        assert!(StockFilter {
+            min_rsi: Some(30.0),
+            page_size: Some(200),
+            ..StockFilter::default()
+        }
+        .validate()
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn extractor_rejects_with_422_and_field_list() {
+        use axum::{body::Body, routing::post, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new().route(
+            "/filter",
+            post(|ValidatedJson(f): ValidatedJson<StockFilter>| async move {
+                Json(json!({ "success": true, "page_size": f.page_size }))
+            }),
+        );
+        let send = |body: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::post("/filter")
+                            .header("content-type", "application/json")
+                            .body(Body::from(body))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, serde_json::from_slice::<Value>(&bytes).unwrap())
+            }
+        };
+
+        let (status, body) = send(r#"{"page_size": 20}"#).await;
+        assert_eq!(
+            (status, body["page_size"].as_u64()),
+            (StatusCode::OK, Some(20))
+        );
+
+        let (status, body) = send(r#"{"page_size": 500, "min_rsi": -5}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["errors"][0]["field"], "min_rsi");
+        assert_eq!(body["errors"][1]["field"], "page_size");
+        assert_eq!(
+            body["error"],
+            "invalid request: min_rsi must be between 0 and 100; page_size must be between 1 and 200"
+        );
+
+        let (status, body) = send("{").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+    }
+
+    #[test]
+    fn custom_checks() {
+        assert!(not_blank("AAPL").is_ok());
+        assert!(not_blank("  ").is_err());
+        assert!(non_zero(-2.0).is_ok());
+        assert!(non_zero(0.0).is_err());
+        assert!(non_zero(f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+//! Weekend deep-analysis mode.
+//!
+//! Nothing the cycle tracks moves between Friday's close and Monday's open,
+//! so once a weekend (Saturday at `WEEKEND_HOUR_NY`, New York time, or on
+//! `POST /api/admin/weekend`) [`run`] does the heavy work the intraday cycle
+//! skips:
+//!
+//! 1. backfills `WEEKEND_BACKFILL_YEARS` of daily closes for every analysed
+//!    symbol into `long_price_history`,
+//! 2. recomputes each symbol's month-of-year [`Seasonality`] from them,
+//! 3. stores AI summaries of the `WEEKEND_AI_SUMMARIES` largest names in
+//!    `ai_summaries` when OpenRouter is enabled,
+//! 4. prunes per-symbol documents of symbols that left the universe and
+//!    compacts the large collections.
+//!
+//! A failing symbol or step is logged and counted; the run carries on.
+
+use std::time::Duration as StdDuration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::America::New_York;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::cross_section::PriceHistory;
+use crate::db::MongoDB;
+use crate::maintenance::MaintenanceMode;
+use crate::models::{HistoricalPrice, MonthlySeasonality, Seasonality};
+use crate::openrouter::OpenRouterClient;
+use crate::yahoo::{PriceAdjustment, YahooFinanceClient};
+
+/// Collections `compact` is run on, largest first.
+const COMPACTED_COLLECTIONS: [&str; 4] = [
+    "analysis_history",
+    "stock_analysis",
+    "long_price_history",
+    "price_history",
+];
+
+/// Only one run at a time, whether scheduled or on demand.
+static WEEKEND_RUNNING: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Report of the last completed run, in memory only.
+static LAST_REPORT: Lazy<std::sync::RwLock<Option<WeekendReport>>> =
+    Lazy::new(|| std::sync::RwLock::new(None));
+
+pub fn is_running() -> bool {
+    WEEKEND_RUNNING.try_lock().is_err()
+}
+
+pub fn last_report() -> Option<WeekendReport> {
+    LAST_REPORT.read().ok()?.clone()
+}
+
+#[derive(Debug, Clone)]
+pub struct WeekendSettings {
+    /// New York hour on Saturday the run starts at; `None` only runs on
+    /// demand.
+    pub hour_ny: Option<u32>,
+    /// Years of daily closes backfilled per symbol.
+    pub backfill_years: u32,
+    /// Largest names by market cap that get an AI summary; `0` skips them.
+    pub ai_summaries: usize,
+    /// Pause between per-symbol Yahoo requests.
+    pub delay: StdDuration,
+}
+
+/// What one run did.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekendReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Symbols whose multi-year closes were refreshed.
+    pub backfilled: usize,
+    pub backfill_failures: usize,
+    /// Symbols whose seasonality was recomputed.
+    pub seasonality: usize,
+    pub ai_summaries: usize,
+    pub ai_failures: usize,
+    /// Documents of symbols without an analysis that were deleted.
+    pub orphans_removed: u64,
+    /// Collections compacted; empty where the server doesn't allow it.
+    pub compacted: Vec<String>,
+}
+
+/// Every close in `prices`, oldest first.
+fn full_history(symbol: &str, prices: &[HistoricalPrice]) -> PriceHistory {
+    PriceHistory {
+        id: None,
+        symbol: symbol.to_string(),
+        dates: prices
+            .iter()
+            .map(|p| p.date.date_naive().format("%Y-%m-%d").to_string())
+            .collect(),
+        closes: prices.iter().map(|p| p.close).collect(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// Average and hit rate of each calendar month's return. The month in
+/// progress at `now` is left out, as are gaps in the data; `None` when no
+/// full month is left.
+pub(crate) fn seasonality(history: &PriceHistory, now: DateTime<Utc>) -> Option<Seasonality> {
+    // Last close of each (year, month), in date order.
+    let mut month_ends: Vec<((i32, u32), f64)> = Vec::new();
+    for (date, &close) in history.dates.iter().zip(&history.closes) {
+        let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+            continue;
+        };
+        if !close.is_finite() || close <= 0.0 {
+            continue;
+        }
+        let key = (date.year(), date.month());
+        match month_ends.last_mut() {
+            Some((last, last_close)) if *last == key => *last_close = close,
+            _ => month_ends.push((key, close)),
+        }
+    }
+    if month_ends
+        .last()
+        .is_some_and(|(key, _)| *key == (now.year(), now.month()))
+    {
+        month_ends.pop();
+    }
+
+    // (sum of returns, positive months, observations) per calendar month
+    let mut totals = [(0.0, 0u32, 0u32); 12];
+    for pair in month_ends.windows(2) {
+        let (((y0, m0), c0), ((y1, m1), c1)) = (pair[0], pair[1]);
+        if y1 * 12 + m1 as i32 - (y0 * 12 + m0 as i32) != 1 {
+            continue;
+        }
+        let change = (c1 / c0 - 1.0) * 100.0;
+        let total = &mut totals[m1 as usize - 1];
+        total.0 += change;
+        total.1 += u32::from(change > 0.0);
+        total.2 += 1;
+    }
+    let months: Vec<MonthlySeasonality> = totals
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, _, years))| *years > 0)
+        .map(|(i, &(sum, positive, years))| MonthlySeasonality {
+            month: i as u32 + 1,
+            avg_return_pct: sum / years as f64,
+            positive_pct: positive as f64 / years as f64 * 100.0,
+            years,
+        })
+        .collect();
+    if months.is_empty() {
+        return None;
+    }
+    Some(Seasonality {
+        symbol: history.symbol.clone(),
+        months,
+        from: history.dates.first()?.clone(),
+        to: history.dates.last()?.clone(),
+        computed_at: now,
+    })
+}
+
+/// Backfill and recompute seasonality for every analysed symbol. A symbol
+/// whose backfill fails falls back to its stored long history.
+async fn backfill(
+    db: &MongoDB,
+    yahoo: &YahooFinanceClient,
+    settings: &WeekendSettings,
+    maintenance: &MaintenanceMode,
+    report: &mut WeekendReport,
+) -> Result<()> {
+    let days = settings.backfill_years as i64 * 366;
+    for symbol in db.get_analysis_symbols().await? {
+        if maintenance.is_read_only() {
+            bail!("read-only mode switched on");
+        }
+        let history = match yahoo
+            .get_historical_prices(&symbol, days, PriceAdjustment::Adjusted)
+            .await
+        {
+            Ok(prices) if !prices.is_empty() => {
+                let history = full_history(&symbol, &prices);
+                db.save_long_price_history(&history).await?;
+                report.backfilled += 1;
+                Some(history)
+            }
+            Ok(_) => {
+                report.backfill_failures += 1;
+                db.get_long_price_history(&symbol).await?
+            }
+            Err(e) => {
+                debug!("Weekend backfill failed for {}: {}", symbol, e);
+                report.backfill_failures += 1;
+                db.get_long_price_history(&symbol).await?
+            }
+        };
+        if let Some(seasonality) = history.and_then(|h| seasonality(&h, Utc::now())) {
+            db.save_seasonality(&seasonality).await?;
+            report.seasonality += 1;
+        }
+        tokio::time::sleep(settings.delay).await;
+    }
+    Ok(())
+}
+
+/// Summaries of the largest names, one model call each.
+async fn summarize(
+    db: &MongoDB,
+    openrouter: &OpenRouterClient,
+    limit: usize,
+    maintenance: &MaintenanceMode,
+    report: &mut WeekendReport,
+) -> Result<()> {
+    for analysis in db.get_top_analyses_by_market_cap(limit as i64).await? {
+        if maintenance.is_read_only() {
+            bail!("read-only mode switched on");
+        }
+        match openrouter.analyze_stock(&analysis).await {
+            Ok(summary) => {
+                db.save_ai_summary(&summary).await?;
+                report.ai_summaries += 1;
+            }
+            Err(e) => {
+                debug!("Weekend AI summary failed for {}: {}", analysis.symbol, e);
+                report.ai_failures += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prune orphaned documents, then compact. The first refused `compact`
+/// ends compaction; a hosted tier refuses them all.
+async fn tidy_database(db: &MongoDB, report: &mut WeekendReport) -> Result<()> {
+    report.orphans_removed = db.prune_orphaned_symbol_docs().await?;
+    for collection in COMPACTED_COLLECTIONS {
+        match db.compact(collection).await {
+            Ok(()) => report.compacted.push(collection.to_string()),
+            Err(e) => {
+                warn!("compact {} refused, skipping compaction: {}", collection, e);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run every weekend job once.
+pub async fn run(
+    db: &MongoDB,
+    yahoo: &YahooFinanceClient,
+    openrouter: &OpenRouterClient,
+    settings: &WeekendSettings,
+    maintenance: &MaintenanceMode,
+) -> Result<WeekendReport> {
+    let _running = WEEKEND_RUNNING
+        .try_lock()
+        .map_err(|_| anyhow!("the weekend run is already in progress"))?;
+
+    let started_at = Utc::now();
+    let mut report = WeekendReport {
+        started_at,
+        finished_at: started_at,
+        backfilled: 0,
+        backfill_failures: 0,
+        seasonality: 0,
+        ai_summaries: 0,
+        ai_failures: 0,
+        orphans_removed: 0,
+        compacted: Vec::new(),
+    };
+
+    if settings.backfill_years > 0 {
+        if let Err(e) = backfill(db, yahoo, settings, maintenance, &mut report).await {
+            warn!("Weekend backfill stopped early: {}", e);
+        }
+    }
+    if settings.ai_summaries > 0 && openrouter.is_enabled() {
+        let limit = settings.ai_summaries;
+        if let Err(e) = summarize(db, openrouter, limit, maintenance, &mut report).await {
+            warn!("Weekend AI summaries stopped early: {}", e);
+        }
+    }
+    if maintenance.is_read_only() {
+        warn!("Weekend database maintenance skipped: read-only mode");
+    } else if let Err(e) = tidy_database(db, &mut report).await {
+        warn!("Weekend database maintenance failed: {}", e);
+    }
+
+    report.finished_at = Utc::now();
+    if let Ok(mut last) = LAST_REPORT.write() {
+        *last = Some(report.clone());
+    }
+    Ok(report)
+}
+
+/// Time until the next Saturday `hour`:00 New York time.
+fn until_next_run(now: DateTime<Utc>, hour: u32) -> StdDuration {
+    let at = NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or(NaiveTime::MIN);
+    let mut date = now.with_timezone(&New_York).date_naive();
+    loop {
+        if date.weekday() == Weekday::Sat {
+            let next = New_York
+                .from_local_datetime(&date.and_time(at))
+                .earliest()
+                .map(|t| t.with_timezone(&Utc));
+            if let Some(next) = next.filter(|next| *next > now) {
+                return (next - now).to_std().unwrap_or_default();
+            }
+        }
+        date = date.succ_opt().unwrap_or(date);
+    }
+}
+
+/// Run every Saturday at `settings.hour_ny` for the life of the process.
+/// Skipped while read-only mode is on.
+pub fn spawn(
+    db: MongoDB,
+    yahoo: YahooFinanceClient,
+    openrouter: OpenRouterClient,
+    settings: WeekendSettings,
+    maintenance: MaintenanceMode,
+) {
+    let Some(hour) = settings.hour_ny else {
+        return;
+    };
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(until_next_run(Utc::now(), hour)).await;
+            if maintenance.is_read_only() {
+                info!("🗓️  Weekend run skipped: read-only mode");
+                continue;
+            }
+            match run(&db, &yahoo, &openrouter, &settings, &maintenance).await {
+                Ok(report) => info!(
+                    "🗓️  Weekend run: {} backfilled ({} failed), {} seasonality, {} AI summaries, {} orphans pruned",
+                    report.backfilled,
+                    report.backfill_failures,
+                    report.seasonality,
+                    report.ai_summaries,
+                    report.orphans_removed
+                ),
+                Err(e) => warn!("Weekend run failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(closes: &[(&str, f64)]) -> PriceHistory {
+        PriceHistory {
+            id: None,
+            symbol: "AAPL".to_string(),
+            dates: closes.iter().map(|(d, _)| d.to_string()).collect(),
+            closes: closes.iter().map(|(_, c)| *c).collect(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_seasonality_averages_calendar_months() {
+        let history = history(&[
+            ("2023-12-29", 100.0),
+            ("2024-01-15", 105.0),
+            ("2024-01-31", 110.0),
+            ("2024-02-29", 99.0),
+            ("2024-12-31", 100.0),
+            ("2025-01-31", 90.0),
+            ("2025-02-28", 99.0),
+            ("2025-03-10", 120.0),
+        ]);
+        let now = Utc.with_ymd_and_hms(2025, 3, 12, 0, 0, 0).unwrap();
+        let s = seasonality(&history, now).unwrap();
+
+        // March is still in progress; Feb 2024 → Dec 2024 is a gap.
+        let months: Vec<u32> = s.months.iter().map(|m| m.month).collect();
+        assert_eq!(months, vec![1, 2]);
+        let january = &s.months[0];
+        assert_eq!(january.years, 2);
+        assert!((january.avg_return_pct - 0.0).abs() < 1e-9);
+        assert_eq!(january.positive_pct, 50.0);
+        let february = &s.months[1];
+        assert!((february.avg_return_pct - 0.0).abs() < 1e-9);
+        assert_eq!(
+            (s.from.as_str(), s.to.as_str()),
+            ("2023-12-29", "2025-03-10")
+        );
+    }
+
+    #[test]
+    fn test_seasonality_needs_a_full_month() {
+        let history = history(&[("2025-03-03", 100.0), ("2025-03-10", 101.0)]);
+        let now = Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap();
+        assert!(seasonality(&history, now).is_none());
+    }
+
+    #[test]
+    fn test_until_next_run_waits_for_saturday_in_new_york() {
+        // Thursday 2025-06-05 12:00 UTC; Saturday 10:00 EDT is 14:00 UTC.
+        let now = Utc.with_ymd_and_hms(2025, 6, 5, 12, 0, 0).unwrap();
+        assert_eq!(
+            until_next_run(now, 10),
+            StdDuration::from_secs((2 * 24 + 2) * 3600)
+        );
+        // Just past this Saturday's run: a week later.
+        let now = Utc.with_ymd_and_hms(2025, 6, 7, 14, 0, 1).unwrap();
+        assert_eq!(
+            until_next_run(now, 10),
+            StdDuration::from_secs(7 * 24 * 3600 - 1)
+        );
+        // Winter: 10:00 EST is 15:00 UTC.
+        let now = Utc.with_ymd_and_hms(2025, 1, 11, 14, 0, 0).unwrap();
+        assert_eq!(until_next_run(now, 10), StdDuration::from_secs(3600));
+    }
+}
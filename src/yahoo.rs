@@ -1,10 +1,15 @@
-use crate::models::{CompanyProfile, EarningsData, HistoricalPrice};
+use crate::archive::{RawArchive, Source};
+use crate::models::{
+    CompanyProfile, DividendEvent, EarningsData, HistoricalPrice, LiveQuote, SplitEvent,
+};
+use crate::rate_budget::{BudgetStats, Priority, RequestBudget};
 use anyhow::{anyhow, Result};
 use chrono::DateTime;
 use rand::Rng;
 use reqwest;
 use reqwest::header::ACCEPT;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration as StdDuration, Instant};
 use tokio::sync::RwLock;
@@ -209,11 +214,41 @@ struct Chart {
 struct ChartResult {
     timestamp: Option<Vec<i64>>,
     indicators: Indicators,
+    /// Only present when requested with `events=div` or `events=splits`.
+    #[serde(default)]
+    events: Option<ChartEvents>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartEvents {
+    /// Keyed by the event's unix timestamp as a string.
+    dividends: Option<HashMap<String, ChartDividend>>,
+    splits: Option<HashMap<String, ChartSplit>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartDividend {
+    amount: f64,
+    date: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartSplit {
+    date: i64,
+    numerator: f64,
+    denominator: f64,
 }
 
 #[derive(Debug, Deserialize)]
 struct Indicators {
     quote: Vec<Quote>,
+    #[serde(default)]
+    adjclose: Option<Vec<AdjClose>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdjClose {
+    adjclose: Option<Vec<Option<f64>>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -225,6 +260,41 @@ struct Quote {
     volume: Option<Vec<Option<i64>>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct BatchQuoteResponse {
+    #[serde(rename = "quoteResponse")]
+    quote_response: BatchQuoteResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchQuoteResult {
+    #[serde(default)]
+    result: Vec<BatchQuote>,
+    error: Option<YahooError>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchQuote {
+    symbol: String,
+    regular_market_price: Option<f64>,
+    regular_market_volume: Option<f64>,
+    regular_market_time: Option<i64>,
+}
+
+/// Whether daily bars come back as traded or back-adjusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceAdjustment {
+    #[default]
+    Raw,
+    /// Back-adjusted for splits and cash dividends, so indicators and
+    /// returns don't jump at split and ex-dividend dates.
+    Adjusted,
+}
+
+/// Most symbols Yahoo's v7 quote endpoint accepts per request.
+pub const BATCH_QUOTE_LIMIT: usize = 50;
+
 #[derive(Debug, Deserialize)]
 struct YahooError {
     code: String,
@@ -238,6 +308,10 @@ pub struct YahooFinanceClient {
     crumb: Arc<RwLock<Option<String>>>,
     last_refresh: Arc<RwLock<Option<Instant>>>,
     max_retries: u32,
+    /// Shared by every clone; `None` leaves requests unmetered.
+    budget: Option<Arc<RequestBudget>>,
+    priority: Priority,
+    archive: Option<RawArchive>,
 }
 
 impl YahooFinanceClient {
@@ -255,6 +329,45 @@ impl YahooFinanceClient {
             crumb: Arc::new(RwLock::new(None)),
             last_refresh: Arc::new(RwLock::new(None)),
             max_retries: 3,
+            budget: None,
+            priority: Priority::Interactive,
+            archive: None,
+        }
+    }
+
+    /// Meter every request through `budget` (see `rate_budget.rs`).
+    pub fn with_budget(mut self, budget: Arc<RequestBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Keep raw response bodies in `archive` (see `archive.rs`).
+    pub fn with_archive(mut self, archive: RawArchive) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    fn archive(&self, kind: &str, symbol: &str, url: &str, body: &str) {
+        if let Some(archive) = &self.archive {
+            archive.record(Source::Yahoo, kind, symbol, url, body.as_bytes());
+        }
+    }
+
+    /// A clone whose requests draw on the budget as background work.
+    pub fn background(&self) -> Self {
+        Self {
+            priority: Priority::Background,
+            ..self.clone()
+        }
+    }
+
+    pub fn budget_stats(&self) -> Option<BudgetStats> {
+        self.budget.as_ref().map(|b| b.stats())
+    }
+
+    async fn acquire_budget(&self) {
+        if let Some(budget) = &self.budget {
+            budget.acquire(self.priority).await;
         }
     }
 
@@ -342,6 +455,7 @@ impl YahooFinanceClient {
 
         let full_url = Self::url_with_crumb(base_url, &crumb)?;
 
+        self.acquire_budget().await;
         let response = self
             .client
             .get(&full_url)
@@ -360,6 +474,7 @@ impl YahooFinanceClient {
             let crumb = self.get_crumb().await?;
             let full_url = Self::url_with_crumb(base_url, &crumb)?;
 
+            self.acquire_budget().await;
             let retry_response = self
                 .client
                 .get(&full_url)
@@ -417,6 +532,7 @@ impl YahooFinanceClient {
         &self,
         symbol: &str,
         days: i64,
+        adjustment: PriceAdjustment,
     ) -> Result<Vec<HistoricalPrice>> {
         let mut attempt = 0;
         let mut last_error = None;
@@ -436,7 +552,7 @@ impl YahooFinanceClient {
                 sleep(StdDuration::from_secs(delay)).await;
             }
 
-            match self.fetch_historical_prices(symbol, days).await {
+            match self.fetch_historical_prices(symbol, days, adjustment).await {
                 Ok(prices) => {
                     if attempt > 0 {
                         tracing::info!(
@@ -471,21 +587,75 @@ impl YahooFinanceClient {
         &self,
         symbol: &str,
         days: i64,
+        adjustment: PriceAdjustment,
     ) -> Result<Vec<HistoricalPrice>> {
-        let url = format!(
+        let mut url = format!(
             "https://query2.finance.yahoo.com/v8/finance/chart/{}?interval=1d&range={}d",
             crate::symbols::yahoo_symbol(symbol),
             days
         );
+        if adjustment == PriceAdjustment::Adjusted {
+            url.push_str("&events=div,splits");
+        }
 
         tracing::debug!("Fetching {} from Yahoo Finance (query2): {}", symbol, url);
 
         let text = self.fetch_with_crumb(&url).await?;
-        parse_historical_prices(&text, symbol)
+        self.archive("chart", symbol, &url, &text);
+        match adjustment {
+            PriceAdjustment::Raw => parse_historical_prices(&text, symbol),
+            PriceAdjustment::Adjusted => parse_adjusted_prices(&text, symbol),
+        }
+    }
+
+    /// Daily bars plus the cash dividends paid over the same range, for
+    /// total-return calculations. Single attempt (no rate-limit retries).
+    pub async fn get_history_with_dividends(
+        &self,
+        symbol: &str,
+        days: i64,
+    ) -> Result<(Vec<HistoricalPrice>, Vec<DividendEvent>)> {
+        let url = format!(
+            "https://query2.finance.yahoo.com/v8/finance/chart/{}?interval=1d&range={}d&events=div",
+            crate::symbols::yahoo_symbol(symbol),
+            days
+        );
+        let text = self.fetch_with_crumb(&url).await?;
+        self.archive("chart", symbol, &url, &text);
+        Ok((
+            parse_historical_prices(&text, symbol)?,
+            parse_dividends(&text, symbol)?,
+        ))
+    }
+
+    /// Latest quotes for up to [`BATCH_QUOTE_LIMIT`] symbols in one request.
+    pub async fn get_batch_quotes(&self, symbols: &[String]) -> Result<Vec<LiveQuote>> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        if symbols.len() > BATCH_QUOTE_LIMIT {
+            return Err(anyhow!(
+                "at most {} symbols per batch quote request",
+                BATCH_QUOTE_LIMIT
+            ));
+        }
+        let list: Vec<String> = symbols
+            .iter()
+            .map(|s| crate::symbols::yahoo_symbol(s))
+            .collect();
+        let url = format!(
+            "https://query1.finance.yahoo.com/v7/finance/quote?symbols={}",
+            list.join(",")
+        );
+        let text = self.fetch_with_crumb(&url).await?;
+        self.archive("quote", "_batch", &url, &text);
+        parse_batch_quotes(&text)
     }
 
     pub async fn get_latest_quote(&self, symbol: &str) -> Result<(f64, f64)> {
-        let prices = self.get_historical_prices(symbol, 5).await?;
+        let prices = self
+            .get_historical_prices(symbol, 5, PriceAdjustment::Raw)
+            .await?;
         let latest = prices
             .last()
             .ok_or_else(|| anyhow!("No latest quote for {}", symbol))?;
@@ -497,8 +667,9 @@ impl YahooFinanceClient {
         &self,
         symbol: &str,
         days: i64,
+        adjustment: PriceAdjustment,
     ) -> Result<Vec<HistoricalPrice>> {
-        self.get_historical_prices(symbol, days).await
+        self.get_historical_prices(symbol, days, adjustment).await
     }
 
     /// Fetch company profile with financial data from Yahoo Finance quoteSummary endpoint
@@ -515,6 +686,7 @@ impl YahooFinanceClient {
         );
 
         let text = self.fetch_with_crumb(&url).await?;
+        self.archive("profile", symbol, &url, &text);
         parse_company_profile(&text, symbol)
     }
 
@@ -528,6 +700,7 @@ impl YahooFinanceClient {
         tracing::debug!("Fetching earnings data for {} from Yahoo Finance", symbol);
 
         let text = self.fetch_with_crumb(&url).await?;
+        self.archive("earnings", symbol, &url, &text);
         parse_earnings_data(&text, symbol)
     }
 }
@@ -613,6 +786,145 @@ pub(crate) fn parse_historical_prices(text: &str, symbol: &str) -> Result<Vec<Hi
     Ok(prices)
 }
 
+/// Parse the `events.dividends` block of a chart response, oldest first.
+/// A response without the block (no dividends in range) yields an empty list.
+pub(crate) fn parse_dividends(text: &str, symbol: &str) -> Result<Vec<DividendEvent>> {
+    let yahoo_response: YahooResponse = serde_json::from_str(text)
+        .map_err(|e| anyhow!("Failed to parse JSON for {}: {}", symbol, e))?;
+
+    let dividends = yahoo_response
+        .chart
+        .result
+        .and_then(|r| r.into_iter().next())
+        .and_then(|r| r.events)
+        .and_then(|e| e.dividends)
+        .unwrap_or_default();
+
+    let mut events: Vec<DividendEvent> = dividends
+        .into_values()
+        .filter(|d| d.amount.is_finite() && d.amount > 0.0)
+        .filter_map(|d| {
+            DateTime::from_timestamp(d.date, 0).map(|date| DividendEvent {
+                date,
+                amount: d.amount,
+            })
+        })
+        .collect();
+    events.sort_by_key(|d| d.date);
+    Ok(events)
+}
+
+/// Parse the `events.splits` block of a chart response, oldest first.
+/// Splits without a usable ratio are dropped.
+pub(crate) fn parse_splits(text: &str, symbol: &str) -> Result<Vec<SplitEvent>> {
+    let yahoo_response: YahooResponse = serde_json::from_str(text)
+        .map_err(|e| anyhow!("Failed to parse JSON for {}: {}", symbol, e))?;
+
+    let splits = yahoo_response
+        .chart
+        .result
+        .and_then(|r| r.into_iter().next())
+        .and_then(|r| r.events)
+        .and_then(|e| e.splits)
+        .unwrap_or_default();
+
+    let mut events: Vec<SplitEvent> = splits
+        .into_values()
+        .filter_map(|s| {
+            let split = SplitEvent {
+                date: DateTime::from_timestamp(s.date, 0)?,
+                numerator: s.numerator,
+                denominator: s.denominator,
+            };
+            split.ratio().map(|_| split)
+        })
+        .collect();
+    events.sort_by_key(|s| s.date);
+    Ok(events)
+}
+
+/// Parse a chart response requested with `events=div,splits` into
+/// back-adjusted bars. Each bar is scaled by Yahoo's `adjclose` over its
+/// close where Yahoo sends that series; otherwise the bars are adjusted from
+/// the split and dividend events (see `analytics::adjust_for_events`), which
+/// also scales volume across splits.
+pub(crate) fn parse_adjusted_prices(text: &str, symbol: &str) -> Result<Vec<HistoricalPrice>> {
+    let mut prices = parse_historical_prices(text, symbol)?;
+
+    let yahoo_response: YahooResponse = serde_json::from_str(text)
+        .map_err(|e| anyhow!("Failed to parse JSON for {}: {}", symbol, e))?;
+    let adjcloses: HashMap<i64, f64> = yahoo_response
+        .chart
+        .result
+        .and_then(|r| r.into_iter().next())
+        .and_then(|r| {
+            let timestamps = r.timestamp?;
+            let adjclose = r.indicators.adjclose?.into_iter().next()?.adjclose?;
+            Some(
+                timestamps
+                    .into_iter()
+                    .zip(adjclose)
+                    .filter_map(|(t, adj)| Some((t, adj?)))
+                    .filter(|(_, adj)| adj.is_finite() && *adj > 0.0)
+                    .collect(),
+            )
+        })
+        .unwrap_or_default();
+
+    if adjcloses.is_empty() {
+        let splits = parse_splits(text, symbol)?;
+        let dividends = parse_dividends(text, symbol)?;
+        crate::analytics::adjust_for_events(&mut prices, &splits, &dividends);
+        return Ok(prices);
+    }
+    for bar in &mut prices {
+        let Some(&adjclose) = adjcloses.get(&bar.date.timestamp()) else {
+            continue;
+        };
+        if bar.close > 0.0 {
+            let factor = adjclose / bar.close;
+            bar.open *= factor;
+            bar.high *= factor;
+            bar.low *= factor;
+            bar.close = adjclose;
+        }
+    }
+    Ok(prices)
+}
+
+/// Parse a v7 batch quote response. Quotes without a price are dropped.
+pub(crate) fn parse_batch_quotes(text: &str) -> Result<Vec<LiveQuote>> {
+    let response: BatchQuoteResponse = serde_json::from_str(text)
+        .map_err(|e| anyhow!("Failed to parse batch quote JSON: {}", e))?;
+    if let Some(error) = response.quote_response.error {
+        return Err(anyhow!(
+            "Yahoo Finance error: {} - {}",
+            error.code,
+            error.description
+        ));
+    }
+    Ok(response
+        .quote_response
+        .result
+        .into_iter()
+        .filter_map(|q| {
+            let price = q
+                .regular_market_price
+                .filter(|p| p.is_finite() && *p > 0.0)?;
+            let quoted_at = q
+                .regular_market_time
+                .and_then(|t| DateTime::from_timestamp(t, 0))
+                .unwrap_or_else(chrono::Utc::now);
+            Some(LiveQuote {
+                symbol: crate::symbols::normalize_symbol_key(&q.symbol),
+                price,
+                day_volume: q.regular_market_volume,
+                quoted_at,
+            })
+        })
+        .collect())
+}
+
 /// Parse a Yahoo Finance quoteSummary response (assetProfile + financialData).
 pub(crate) fn parse_company_profile(text: &str, symbol: &str) -> Result<CompanyProfile> {
     let summary_response: QuoteSummaryResponse = serde_json::from_str(text)
@@ -982,6 +1294,101 @@ mod tests {
         assert_eq!(prices[0].volume, 0.0);
     }
 
+    #[test]
+    fn test_parse_dividends_sorted_and_optional() {
+        let json = r#"{
+            "chart": {
+                "result": [{
+                    "timestamp": [1700000000],
+                    "indicators": { "quote": [{ "open": [1.0], "high": [1.0], "low": [1.0], "close": [1.0], "volume": [1] }] },
+                    "events": { "dividends": {
+                        "1707402600": { "amount": 0.24, "date": 1707402600 },
+                        "1699540200": { "amount": 0.24, "date": 1699540200 }
+                    } }
+                }],
+                "error": null
+            }
+        }"#;
+        let divs = parse_dividends(json, "AAPL").unwrap();
+        assert_eq!(divs.len(), 2);
+        assert!(divs[0].date < divs[1].date);
+        assert_eq!(divs[0].amount, 0.24);
+
+        assert!(parse_dividends(chart_fixture_normal(), "AAPL")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_adjusted_prices_prefers_adjclose_over_events() {
+        let json = r#"{
+            "chart": {
+                "result": [{
+                    "timestamp": [1700000000, 1700086400, 1700172800],
+                    "indicators": {
+                        "quote": [{
+                            "open":   [400.0, 101.0, 102.0],
+                            "high":   [404.0, 106.0, 107.0],
+                            "low":    [396.0, 100.0, 101.0],
+                            "close":  [400.0, 104.0, 105.0],
+                            "volume": [1000, 4000, 4000]
+                        }],
+                        "adjclose": [{ "adjclose": [99.5, 104.0, 105.0] }]
+                    },
+                    "events": { "splits": {
+                        "1700086400": { "date": 1700086400, "numerator": 4, "denominator": 1, "splitRatio": "4:1" }
+                    } }
+                }],
+                "error": null
+            }
+        }"#;
+        let prices = parse_adjusted_prices(json, "NVDA").unwrap();
+        assert_eq!(prices[0].close, 99.5);
+        assert!((prices[0].high - 404.0 * 99.5 / 400.0).abs() < 1e-9);
+        assert_eq!(prices[0].volume, 1000.0);
+        assert_eq!(prices[1].close, 104.0);
+
+        // Without adjclose the split event itself is applied.
+        let events_only = json.replace(
+            r#""adjclose": [{ "adjclose": [99.5, 104.0, 105.0] }]"#,
+            r#""adjclose": null"#,
+        );
+        let prices = parse_adjusted_prices(&events_only, "NVDA").unwrap();
+        assert_eq!(prices[0].close, 100.0);
+        assert_eq!(prices[0].volume, 4000.0);
+        assert_eq!(
+            parse_splits(&events_only, "NVDA").unwrap()[0].ratio(),
+            Some(4.0)
+        );
+
+        // Raw parsing ignores both.
+        assert_eq!(
+            parse_historical_prices(json, "NVDA").unwrap()[0].close,
+            400.0
+        );
+    }
+
+    #[test]
+    fn test_parse_batch_quotes_drops_unpriced() {
+        let json = r#"{
+            "quoteResponse": {
+                "result": [
+                    { "symbol": "AAPL", "regularMarketPrice": 210.5, "regularMarketVolume": 1200000, "regularMarketTime": 1751300000 },
+                    { "symbol": "BRK.B", "regularMarketPrice": 480.0 },
+                    { "symbol": "DEAD" }
+                ],
+                "error": null
+            }
+        }"#;
+        let quotes = parse_batch_quotes(json).unwrap();
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].price, 210.5);
+        assert_eq!(quotes[0].day_volume, Some(1_200_000.0));
+        assert_eq!(quotes[0].quoted_at.timestamp(), 1751300000);
+        assert_eq!(quotes[1].symbol, "BRK-B");
+        assert_eq!(quotes[1].day_volume, None);
+    }
+
     #[test]
     fn test_parse_historical_prices_error_block() {
         let json = r#"{
@@ -0,0 +1,314 @@
+//! `StockFilter` evaluated in the browser over stocks already fetched.
+//!
+//! Mirrors `build_filter_doc` in the server's `db.rs`: a bound on a field the
+//! stock lacks excludes it, unknown return periods and percentile metrics are
+//! ignored. `theme` and `primary_class_only` need server-side lists and are
+//! not applied here; sorting and paging are left to the caller.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+const RETURN_PERIODS: &[(&str, &str)] = &[
+    ("1w", "performance.return_1w_pct"),
+    ("1m", "performance.return_1m_pct"),
+    ("3m", "performance.return_3m_pct"),
+    ("ytd", "performance.return_ytd_pct"),
+];
+
+const PERCENTILE_METRICS: &[&str] = &[
+    "rsi",
+    "price_change_percent",
+    "volume_ratio",
+    "pe_ratio",
+    "return_1m_pct",
+    "return_3m_pct",
+    "rs_rank",
+];
+
+/// The client-evaluable part of the server's `StockFilter`; other fields in
+/// the JSON are ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ClientFilter {
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub min_volume: Option<f64>,
+    pub min_market_cap: Option<f64>,
+    pub max_market_cap: Option<f64>,
+    pub min_rsi: Option<f64>,
+    pub max_rsi: Option<f64>,
+    pub sectors: Option<Vec<String>>,
+    pub only_oversold: Option<bool>,
+    pub only_overbought: Option<bool>,
+    pub only_stoch_oversold: Option<bool>,
+    pub only_stoch_overbought: Option<bool>,
+    pub cross_signal: Option<String>,
+    pub supertrend_uptrend: Option<bool>,
+    pub symbol_search: Option<String>,
+    pub min_stochastic_k: Option<f64>,
+    pub max_stochastic_k: Option<f64>,
+    pub min_williams_r: Option<f64>,
+    pub max_williams_r: Option<f64>,
+    pub min_cci: Option<f64>,
+    pub max_cci: Option<f64>,
+    pub min_mfi: Option<f64>,
+    pub max_mfi: Option<f64>,
+    pub min_bandwidth: Option<f64>,
+    pub max_bandwidth: Option<f64>,
+    pub max_abs_price_change_percent: Option<f64>,
+    pub index: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub return_period: Option<String>,
+    pub min_return_pct: Option<f64>,
+    pub max_return_pct: Option<f64>,
+    pub percentile_metric: Option<String>,
+    pub min_percentile: Option<f64>,
+    pub max_percentile: Option<f64>,
+    pub min_rs_rank: Option<f64>,
+}
+
+impl ClientFilter {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid filter: {}", e))
+    }
+
+    /// Whether `stock`, a `StockAnalysis` as the API serializes it, passes.
+    pub fn matches(&self, stock: &Value) -> bool {
+        let return_field = self.return_field();
+        let percentile_field = self.percentile_field();
+        let ranges = [
+            ("price", self.min_price, self.max_price),
+            ("volume", self.min_volume, None),
+            ("market_cap", self.min_market_cap, self.max_market_cap),
+            ("rsi", self.min_rsi, self.max_rsi),
+            (
+                "stochastic.k_line",
+                self.min_stochastic_k,
+                self.max_stochastic_k,
+            ),
+            ("williams_r", self.min_williams_r, self.max_williams_r),
+            ("cci", self.min_cci, self.max_cci),
+            ("mfi", self.min_mfi, self.max_mfi),
+            (
+                "bollinger.bandwidth",
+                self.min_bandwidth,
+                self.max_bandwidth,
+            ),
+            (
+                return_field.unwrap_or_default(),
+                return_field.and(self.min_return_pct),
+                return_field.and(self.max_return_pct),
+            ),
+            (
+                percentile_field.as_deref().unwrap_or_default(),
+                percentile_field.as_ref().and(self.min_percentile),
+                percentile_field.as_ref().and(self.max_percentile),
+            ),
+            ("percentiles.rs_rank", self.min_rs_rank, None),
+        ];
+        if !ranges
+            .iter()
+            .all(|&(path, min, max)| in_range(stock, path, min, max))
+        {
+            return false;
+        }
+
+        if let Some(max_abs) = self.max_abs_price_change_percent {
+            let max_abs = max_abs.abs();
+            if !in_range(stock, "price_change_percent", Some(-max_abs), Some(max_abs)) {
+                return false;
+            }
+        }
+        if self.only_oversold == Some(true) && stock["is_oversold"] != Value::Bool(true) {
+            return false;
+        }
+        if self.only_overbought == Some(true) && stock["is_overbought"] != Value::Bool(true) {
+            return false;
+        }
+        if self.only_stoch_oversold == Some(true) && stock["stoch_oversold"] != Value::Bool(true) {
+            return false;
+        }
+        if self.only_stoch_overbought == Some(true)
+            && stock["stoch_overbought"] != Value::Bool(true)
+        {
+            return false;
+        }
+        if let Some(signal) = &self.cross_signal {
+            if stock["cross_signal"].as_str().unwrap_or("none") != signal {
+                return false;
+            }
+        }
+        if let Some(uptrend) = self.supertrend_uptrend {
+            if stock["supertrend"]["uptrend"].as_bool() != Some(uptrend) {
+                return false;
+            }
+        }
+        if let Some(sectors) = self.sectors.as_ref().filter(|s| !s.is_empty()) {
+            let sector = stock["sector"].as_str();
+            if !sectors.iter().any(|s| Some(s.as_str()) == sector) {
+                return false;
+            }
+        }
+        if let Some(query) = non_empty(&self.symbol_search) {
+            let symbol = stock["symbol"].as_str().unwrap_or_default();
+            if !symbol.to_lowercase().contains(&query.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(index) = non_empty(&self.index) {
+            if !contains_str(&stock["indexes"], &index.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(tags) = &self.tags {
+            let tags: Vec<String> = tags.iter().filter_map(|t| normalize_tag(t)).collect();
+            if !tags.is_empty() && !tags.iter().any(|t| contains_str(&stock["tags"], t)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn return_field(&self) -> Option<&'static str> {
+        let period = non_empty(&self.return_period).unwrap_or("1m");
+        RETURN_PERIODS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(period))
+            .map(|(_, field)| *field)
+    }
+
+    fn percentile_field(&self) -> Option<String> {
+        let metric = non_empty(&self.percentile_metric)?;
+        PERCENTILE_METRICS
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(metric))
+            .map(|name| format!("percentiles.{}", name))
+    }
+}
+
+/// Keep the stocks of a JSON array that pass the filter, as a JSON array.
+pub fn filter_json(stocks_json: &str, filter_json: &str) -> Result<String, String> {
+    let filter = ClientFilter::parse(filter_json)?;
+    let stocks: Vec<Value> =
+        serde_json::from_str(stocks_json).map_err(|e| format!("invalid stocks: {}", e))?;
+    let kept: Vec<&Value> = stocks.iter().filter(|s| filter.matches(s)).collect();
+    serde_json::to_string(&kept).map_err(|e| e.to_string())
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// A number at a dotted path within the bounds. No bounds always passes; a
+/// missing or `null` field fails any bound, as in MongoDB.
+fn in_range(stock: &Value, path: &str, min: Option<f64>, max: Option<f64>) -> bool {
+    if min.is_none() && max.is_none() {
+        return true;
+    }
+    let Some(value) = path
+        .split('.')
+        .try_fold(stock, |v, key| v.get(key))
+        .and_then(Value::as_f64)
+    else {
+        return false;
+    };
+    min.is_none_or(|lo| value >= lo) && max.is_none_or(|hi| value <= hi)
+}
+
+fn contains_str(array: &Value, wanted: &str) -> bool {
+    array
+        .as_array()
+        .is_some_and(|items| items.iter().any(|v| v.as_str() == Some(wanted)))
+}
+
+/// Same normalization as the server's `notes::normalize_tag`.
+fn normalize_tag(input: &str) -> Option<String> {
+    let tag = input
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_ascii_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn stocks() -> Value {
+        json!([
+            {
+                "symbol": "AAPL", "price": 190.0, "market_cap": 3.0e12, "rsi": 28.0,
+                "is_oversold": true, "is_overbought": false, "sector": "Technology",
+                "cross_signal": "golden_cross", "williams_r": -92.0, "mfi": 15.0,
+                "supertrend": { "value": 182.5, "uptrend": true },
+                "price_change_percent": -1.2, "indexes": ["sp500", "nasdaq100"],
+                "tags": ["core"], "performance": { "return_1m_pct": 4.0 },
+                "percentiles": { "rsi": 12, "rs_rank": 91 }
+            },
+            {
+                "symbol": "XOM", "price": 110.0, "market_cap": 4.5e11, "rsi": null,
+                "is_oversold": false, "is_overbought": false, "stoch_overbought": true,
+                "sector": "Energy",
+                "price_change_percent": 14.0, "indexes": ["sp500"], "tags": []
+            }
+        ])
+    }
+
+    fn kept(filter: Value) -> Vec<String> {
+        let out = filter_json(&stocks().to_string(), &filter.to_string()).unwrap();
+        let out: Vec<Value> = serde_json::from_str(&out).unwrap();
+        out.iter()
+            .map(|s| s["symbol"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn applies_the_server_filter_fields() {
+        assert_eq!(kept(json!({})), vec!["AAPL", "XOM"]);
+        assert_eq!(kept(json!({ "min_price": 150.0 })), vec!["AAPL"]);
+        // A null RSI fails any RSI bound.
+        assert_eq!(kept(json!({ "max_rsi": 100.0 })), vec!["AAPL"]);
+        assert_eq!(kept(json!({ "only_oversold": true })), vec!["AAPL"]);
+        assert_eq!(kept(json!({ "only_stoch_overbought": true })), vec!["XOM"]);
+        assert_eq!(
+            kept(json!({ "max_williams_r": -80.0, "max_mfi": 20.0 })),
+            vec!["AAPL"]
+        );
+        assert_eq!(
+            kept(json!({ "cross_signal": "golden_cross" })),
+            vec!["AAPL"]
+        );
+        assert_eq!(kept(json!({ "cross_signal": "none" })), vec!["XOM"]);
+        assert_eq!(kept(json!({ "supertrend_uptrend": true })), vec!["AAPL"]);
+        assert!(kept(json!({ "supertrend_uptrend": false })).is_empty());
+        assert_eq!(kept(json!({ "sectors": ["Energy"] })), vec!["XOM"]);
+        assert_eq!(kept(json!({ "symbol_search": " xo " })), vec!["XOM"]);
+        assert_eq!(kept(json!({ "index": "NASDAQ100" })), vec!["AAPL"]);
+        assert_eq!(kept(json!({ "tags": ["Core"] })), vec!["AAPL"]);
+        assert_eq!(
+            kept(json!({ "max_abs_price_change_percent": 10.0 })),
+            vec!["AAPL"]
+        );
+        assert_eq!(kept(json!({ "min_return_pct": 1.0 })), vec!["AAPL"]);
+        assert_eq!(
+            kept(json!({ "percentile_metric": "rsi", "max_percentile": 20.0 })),
+            vec!["AAPL"]
+        );
+        assert_eq!(kept(json!({ "min_rs_rank": 80.0 })), vec!["AAPL"]);
+        // Unknown periods/metrics and server-only fields are ignored.
+        assert_eq!(
+            kept(
+                json!({ "return_period": "10y", "min_return_pct": 1.0, "theme": "ai", "page": 2 })
+            ),
+            vec!["AAPL", "XOM"]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(filter_json("{}", "{}").is_err());
+        assert!(filter_json("[]", r#"{"min_price": "cheap"}"#).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+//! Browser build of the indicator math and the stock filter.
+//!
+//! `wasm-pack build wasm --target web` produces a module a frontend can load
+//! to draw overlays from `/api/stocks/:symbol/history` and to re-filter an
+//! `/api/stocks` page without asking the server again. Series come back as
+//! `Float64Array`s aligned with the input bars (`NaN` until an indicator has
+//! enough data); structured results come back as JSON strings.
+//!
+//! The logic lives in plain Rust ([`overlays`], [`filter`]) so it is tested
+//! natively; the `#[wasm_bindgen]` functions here only convert errors.
+
+pub mod filter;
+pub mod overlays;
+
+use auto_analyser_indicators::TechnicalIndicators;
+use wasm_bindgen::prelude::*;
+
+/// RSI (Wilder's smoothing) of the last bar.
+#[wasm_bindgen]
+pub fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    TechnicalIndicators::calculate_rsi(closes, period)
+}
+
+/// Simple moving average of the last `period` closes.
+#[wasm_bindgen]
+pub fn sma(closes: &[f64], period: usize) -> Option<f64> {
+    TechnicalIndicators::calculate_sma(closes, period)
+}
+
+/// EMA of the closes, seeded with the SMA of the first `period`.
+#[wasm_bindgen]
+pub fn ema(closes: &[f64], period: usize) -> Option<f64> {
+    TechnicalIndicators::calculate_ema(closes, period)
+}
+
+/// RSI at every bar.
+#[wasm_bindgen]
+pub fn rsi_series(closes: &[f64], period: usize) -> Vec<f64> {
+    overlays::series(closes, |bars| {
+        TechnicalIndicators::calculate_rsi(bars, period)
+    })
+}
+
+/// SMA at every bar.
+#[wasm_bindgen]
+pub fn sma_series(closes: &[f64], period: usize) -> Vec<f64> {
+    overlays::series(closes, |bars| {
+        TechnicalIndicators::calculate_sma(bars, period)
+    })
+}
+
+/// EMA at every bar.
+#[wasm_bindgen]
+pub fn ema_series(closes: &[f64], period: usize) -> Vec<f64> {
+    overlays::series(closes, |bars| {
+        TechnicalIndicators::calculate_ema(bars, period)
+    })
+}
+
+/// Every overlay the analysis page draws, from the `history` array of
+/// `/api/stocks/:symbol/history`. Returns the JSON of [`overlays::Overlays`].
+#[wasm_bindgen]
+pub fn overlays(history_json: &str) -> Result<String, JsError> {
+    let bars = overlays::parse_history(history_json).map_err(|e| JsError::new(&e))?;
+    serde_json::to_string(&overlays::Overlays::compute(&bars))
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Keep the stocks (a JSON array as in `/api/stocks`) that pass `filter_json`,
+/// a `StockFilter` as sent to `POST /api/stocks/filter`.
+#[wasm_bindgen]
+pub fn filter_stocks(stocks_json: &str, filter_json: &str) -> Result<String, JsError> {
+    filter::filter_json(stocks_json, filter_json).map_err(|e| JsError::new(&e))
+}
+
+/// Whether one stock (JSON object) passes `filter_json`.
+#[wasm_bindgen]
+pub fn matches_filter(stock_json: &str, filter_json: &str) -> Result<bool, JsError> {
+    let filter = filter::ClientFilter::parse(filter_json).map_err(|e| JsError::new(&e))?;
+    let stock = serde_json::from_str(stock_json).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(filter.matches(&stock))
+}
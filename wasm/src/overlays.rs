@@ -0,0 +1,121 @@
+//! Per-bar indicator series for charting.
+
+use auto_analyser_indicators::{
+    Bar, BollingerBands, MACDIndicator, StochasticOscillator, TechnicalIndicators,
+};
+use serde::{Deserialize, Serialize};
+
+/// One bar of `/api/stocks/:symbol/history`; the date, open and volume are
+/// not needed by any overlay.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryBar {
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl Bar for HistoryBar {
+    fn close(&self) -> f64 {
+        self.close
+    }
+
+    fn high(&self) -> f64 {
+        self.high
+    }
+
+    fn low(&self) -> f64 {
+        self.low
+    }
+}
+
+/// Parse the `history` array (oldest first).
+pub fn parse_history(json: &str) -> Result<Vec<HistoryBar>, String> {
+    serde_json::from_str(json).map_err(|e| format!("invalid history: {}", e))
+}
+
+/// `indicator` evaluated on every prefix of `bars`, so the value at `i` is
+/// what the server would have reported on bar `i`. `NaN` where it has too
+/// little data.
+pub fn series<B: Bar>(bars: &[B], indicator: impl Fn(&[B]) -> Option<f64>) -> Vec<f64> {
+    optional_series(bars, indicator)
+        .into_iter()
+        .map(|v| v.unwrap_or(f64::NAN))
+        .collect()
+}
+
+fn optional_series<B: Bar, T>(bars: &[B], indicator: impl Fn(&[B]) -> Option<T>) -> Vec<Option<T>> {
+    (1..=bars.len()).map(|n| indicator(&bars[..n])).collect()
+}
+
+/// The indicators `analysis.rs` stores, with the same periods, at every bar.
+/// `null` until each has enough bars.
+#[derive(Debug, Serialize)]
+pub struct Overlays {
+    pub sma_20: Vec<Option<f64>>,
+    pub sma_50: Vec<Option<f64>>,
+    pub rsi_14: Vec<Option<f64>>,
+    pub macd: Vec<Option<MACDIndicator>>,
+    pub bollinger: Vec<Option<BollingerBands>>,
+    pub stochastic: Vec<Option<StochasticOscillator>>,
+}
+
+impl Overlays {
+    pub fn compute<B: Bar>(bars: &[B]) -> Self {
+        Overlays {
+            sma_20: optional_series(bars, |b| TechnicalIndicators::calculate_sma(b, 20)),
+            sma_50: optional_series(bars, |b| TechnicalIndicators::calculate_sma(b, 50)),
+            rsi_14: optional_series(bars, |b| TechnicalIndicators::calculate_rsi(b, 14)),
+            macd: optional_series(bars, TechnicalIndicators::calculate_macd),
+            bollinger: optional_series(bars, |b| {
+                TechnicalIndicators::calculate_bollinger_bands(b, 20, 2.0)
+            }),
+            stochastic: optional_series(bars, |b| {
+                TechnicalIndicators::calculate_stochastic(b, 14, 3)
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn series_line_up_with_the_bars() {
+        let closes: Vec<f64> = (1..=60).map(f64::from).collect();
+        let sma = series(&closes, |b| TechnicalIndicators::calculate_sma(b, 20));
+        assert_eq!(sma.len(), 60);
+        assert!(sma[18].is_nan());
+        assert_eq!(sma[19], 10.5);
+        // The last value is what the server stores for the whole history.
+        assert_eq!(
+            sma.last().copied(),
+            TechnicalIndicators::calculate_sma(&closes, 20)
+        );
+    }
+
+    #[test]
+    fn overlays_from_history_json() {
+        let history: Vec<serde_json::Value> = (0..40)
+            .map(|i| {
+                let close = 100.0 + i as f64;
+                serde_json::json!({
+                    "date": "2025-01-02T00:00:00Z",
+                    "open": close,
+                    "high": close + 1.0,
+                    "low": close - 1.0,
+                    "close": close,
+                    "volume": 1000.0
+                })
+            })
+            .collect();
+        let bars = parse_history(&serde_json::to_string(&history).unwrap()).unwrap();
+        let overlays = Overlays::compute(&bars);
+        assert_eq!(overlays.rsi_14.iter().flatten().count(), 40 - 14);
+        assert!(overlays.macd[32].is_none() && overlays.macd[33].is_some());
+        assert!(overlays.sma_50.iter().all(Option::is_none));
+        assert!(overlays.stochastic[15].as_ref().unwrap().k_line > 50.0);
+
+        assert!(parse_history(r#"[{"close": 1.0}]"#).is_err());
+    }
+}